@@ -0,0 +1,228 @@
+//! Keys of the gamedata files of a project.
+//!
+//! SourceMod plugins load their gamedata with `LoadGameConfigFile("foo.games")` or
+//! `new GameData("foo.games")`, which reads `gamedata/foo.games.txt`, or the files of the
+//! `gamedata/foo.games` directory. Those files are KeyValues documents, which define the offsets,
+//! keys, addresses and signatures of each game:
+//!
+//! ```text
+//! "Games"
+//! {
+//!     "csgo"
+//!     {
+//!         "Offsets"
+//!         {
+//!             "GiveNamedItem"
+//!             {
+//!                 "windows"   "456"
+//!                 "linux"     "457"
+//!             }
+//!         }
+//!         "Keys"
+//!         {
+//!             "GameRulesProxy"    "CCSGameRulesProxy"
+//!         }
+//!     }
+//! }
+//! ```
+
+use std::collections::BTreeSet;
+
+use fxhash::FxHashMap;
+
+use crate::keyvalues::{Token, Tokens};
+
+/// Extension of the gamedata files.
+pub const GAMEDATA_FILE_EXTENSION: &str = ".txt";
+
+/// Section of a game of a gamedata file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GamedataSection {
+    Offsets,
+    Keys,
+    Addresses,
+    Signatures,
+}
+
+impl GamedataSection {
+    fn from_name(name: &str) -> Option<Self> {
+        [Self::Offsets, Self::Keys, Self::Addresses, Self::Signatures]
+            .into_iter()
+            .find(|section| section.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Name of the section in the gamedata files, e.g `Offsets`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Offsets => "Offsets",
+            Self::Keys => "Keys",
+            Self::Addresses => "Addresses",
+            Self::Signatures => "Signatures",
+        }
+    }
+
+    /// Name of an entry of the section, e.g `offset`.
+    pub fn entry_name(&self) -> &'static str {
+        match self {
+            Self::Offsets => "offset",
+            Self::Keys => "key",
+            Self::Addresses => "address",
+            Self::Signatures => "signature",
+        }
+    }
+
+    /// Index of the parameter of a function which is the name of an entry of a gamedata file,
+    /// and the section of the entry, e.g `(1, Offsets)` for `GameConfGetOffset`.
+    ///
+    /// # Arguments
+    /// * `function` - Name of the function.
+    /// * `methodmap` - Name of the methodmap of the function, if it is a method.
+    pub fn of_param(function: &str, methodmap: Option<&str>) -> Option<(usize, Self)> {
+        let res = match (methodmap, function) {
+            (None, "GameConfGetOffset") => (1, Self::Offsets),
+            (None, "GameConfGetKeyValue") => (1, Self::Keys),
+            (None, "GameConfGetAddress") => (1, Self::Addresses),
+            (Some("GameData"), "GetOffset") => (0, Self::Offsets),
+            (Some("GameData"), "GetKeyValue") => (0, Self::Keys),
+            (Some("GameData"), "GetAddress") => (0, Self::Addresses),
+            (Some("GameData"), "GetMemSig") => (0, Self::Signatures),
+            _ => return None,
+        };
+
+        Some(res)
+    }
+}
+
+/// Entries defined by the gamedata files of a project, indexed by the name of their file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Gamedata {
+    files: FxHashMap<String, FxHashMap<GamedataSection, BTreeSet<String>>>,
+}
+
+impl Gamedata {
+    /// Add the entries of a gamedata file.
+    ///
+    /// # Arguments
+    /// * `file` - Name of the gamedata file, as passed to `LoadGameConfigFile`, e.g
+    ///   `sdktools.games`.
+    /// * `text` - Contents of the gamedata file, or of one of the files of its directory.
+    pub fn add_file(&mut self, file: &str, text: &str) {
+        let sections = self.files.entry(file.to_owned()).or_default();
+        // Keys of the blocks the tokens are in, e.g `["Games", "csgo", "Offsets"]`.
+        let mut blocks: Vec<Option<&str>> = Vec::new();
+        let mut last_key: Option<&str> = None;
+        for token in (Tokens { text }) {
+            match token {
+                Token::Open => blocks.push(last_key.take()),
+                Token::Close => {
+                    blocks.pop();
+                    last_key = None;
+                }
+                Token::String(value) => {
+                    if last_key.take().is_some() {
+                        continue;
+                    }
+                    last_key = Some(value);
+                    let [Some(games), _, Some(section)] = blocks[..] else {
+                        continue;
+                    };
+                    if !games.eq_ignore_ascii_case("Games") {
+                        continue;
+                    }
+                    if let Some(section) = GamedataSection::from_name(section) {
+                        sections
+                            .entry(section)
+                            .or_default()
+                            .insert(value.to_owned());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Whether a gamedata file was found, e.g `sdktools.games`.
+    pub fn contains_file(&self, file: &str) -> bool {
+        self.files.contains_key(file)
+    }
+
+    /// Whether a gamedata file defines an entry in a section.
+    pub fn contains(&self, file: &str, section: GamedataSection, name: &str) -> bool {
+        self.files
+            .get(file)
+            .and_then(|sections| sections.get(&section))
+            .is_some_and(|names| names.contains(name))
+    }
+
+    /// Names of the entries of a section of a gamedata file.
+    pub fn entries(&self, file: &str, section: GamedataSection) -> impl Iterator<Item = &str> {
+        self.files
+            .get(file)
+            .and_then(|sections| sections.get(&section))
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+    }
+
+    /// Names of the gamedata files.
+    pub fn files(&self) -> impl Iterator<Item = &str> {
+        self.files.keys().map(String::as_str)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_gamedata() {
+        let text = r##""Games"
+{
+    // Offsets of all the games.
+    "#default"
+    {
+        "Offsets"
+        {
+            "GiveNamedItem"
+            {
+                "windows"   "456"
+                "linux"     "457"
+            }
+        }
+    }
+    "csgo"
+    {
+        "Keys"
+        {
+            "GameRulesProxy"    "CCSGameRulesProxy"
+        }
+        signatures
+        {
+            "Respawn"
+            {
+                "library"   "server"
+                "windows"   "\x55\x8B\xEC"
+            }
+        }
+    }
+}"##;
+        let mut gamedata = Gamedata::default();
+        gamedata.add_file("plugin.games", text);
+
+        assert!(gamedata.contains_file("plugin.games"));
+        assert!(gamedata.contains("plugin.games", GamedataSection::Offsets, "GiveNamedItem"));
+        assert!(gamedata.contains("plugin.games", GamedataSection::Keys, "GameRulesProxy"));
+        assert!(gamedata.contains("plugin.games", GamedataSection::Signatures, "Respawn"));
+        assert!(!gamedata.contains("plugin.games", GamedataSection::Keys, "CCSGameRulesProxy"));
+        assert!(!gamedata.contains("plugin.games", GamedataSection::Offsets, "windows"));
+        assert_eq!(
+            gamedata
+                .entries("plugin.games", GamedataSection::Offsets)
+                .collect::<Vec<_>>(),
+            vec!["GiveNamedItem"]
+        );
+    }
+}
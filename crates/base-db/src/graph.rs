@@ -64,8 +64,11 @@ impl Graph {
     /// Get the root of the [subgraph](SubGraph) from a given [file_id](FileId).
     ///
     /// - If the [file_id](FileId) is not in the graph, return [None].
-    /// - If the [file_id](FileId) or one of its parent has more than one parent, return [None].
     /// - If the [file_id](FileId) or one of its parent is an include file, return the [file_id](FileId) of the include file.
+    /// - If a main file is [pinned](crate::SourceDatabase::main_files) for the [file_id](FileId),
+    ///   and the file belongs to its subgraph, return the subgraph of the main file.
+    /// - If the [file_id](FileId) is included by several plugins, e.g a module shared between plugins,
+    ///   return the subgraph of the plugin with the lowest [file_id](FileId).
     pub fn projet_subgraph_query(
         db: &dyn SourceDatabase,
         file_id: FileId,
@@ -78,6 +81,14 @@ impl Graph {
             extension: FileExtension::Sp, // We don't care about the extension here. The hash is based on the file_id.
         };
 
+        if let Some(main_file_id) = db.main_files().get(&file_id) {
+            if let Some(subgraph) = subgraphs.iter().find(|subgraph| {
+                subgraph.root.file_id == *main_file_id && subgraph.nodes.contains(&dummy_node)
+            }) {
+                return Some(Arc::new(subgraph.clone()));
+            }
+        }
+
         subgraphs
             .iter()
             .filter(|subgraph| subgraph.root.extension == FileExtension::Sp)
@@ -186,25 +197,57 @@ impl Graph {
         nodes.iter().map(|node| node.file_id).collect()
     }
 
+    /// Find the [subgraphs](SubGraph) of the graph, sorted by the [file_id](FileId) of their root.
+    ///
+    /// Files which are not reachable from a root, because they are part of an include cycle, e.g
+    /// a module which includes the main file of its plugin, are attributed to the subgraph of
+    /// the file of the cycle which looks the most like a main file.
     pub fn find_subgraphs(&self) -> Vec<SubGraph> {
         let adj_targets = self.get_adjacent_targets();
+        let mut roots = self.find_roots();
+        roots.sort_by_key(|root| root.file_id);
         let mut subgraphs = vec![];
-        for root in self.find_roots() {
+        let mut covered: FxHashSet<Node> = FxHashSet::default();
+        let mut push_subgraph = |root: Node, covered: &mut FxHashSet<Node>| {
             let mut visited = FxHashSet::default();
             let mut nodes = FxHashSet::default();
             let mut edges = FxHashSet::default();
             dfs(&root, &adj_targets, &mut visited, &mut nodes, &mut edges);
-            visited.insert(root.clone());
-            subgraphs.push(SubGraph {
-                root: root.clone(),
-                nodes,
-                edges,
-            });
+            covered.extend(nodes.iter().cloned());
+            subgraphs.push(SubGraph { root, nodes, edges });
+        };
+        for root in roots {
+            push_subgraph(root, &mut covered);
+        }
+        while let Some(root) = self.find_cycle_root(&adj_targets, &covered) {
+            push_subgraph(root, &mut covered);
         }
 
         subgraphs
     }
 
+    /// Pick the root of the next subgraph among the files which are not yet covered by a subgraph.
+    ///
+    /// Plugin files are preferred over include files, then the files with the most includes, as
+    /// the main file of a plugin usually includes all of its modules.
+    fn find_cycle_root(
+        &self,
+        adj_targets: &FxHashMap<Node, FxHashSet<Node>>,
+        covered: &FxHashSet<Node>,
+    ) -> Option<Node> {
+        self.nodes
+            .iter()
+            .filter(|node| !covered.contains(*node))
+            .min_by_key(|node| {
+                (
+                    node.extension != FileExtension::Sp,
+                    std::cmp::Reverse(adj_targets.get(*node).map_or(0, |it| it.len())),
+                    node.file_id,
+                )
+            })
+            .cloned()
+    }
+
     pub fn subgraphs_with_roots(&self) -> FxHashMap<FileId, SubGraph> {
         let subgraphs = self.find_subgraphs();
         subgraphs
@@ -438,4 +481,49 @@ mod test {
         graph.add_file_include(node_2.clone(), node_1.clone());
         assert_eq!(graph.find_roots(), vec![]);
     }
+
+    #[test]
+    fn test_circular_include_subgraphs() {
+        let mut graph = Graph::default();
+        let file_1 = FileId::from(1);
+        let file_2 = FileId::from(2);
+        let file_3 = FileId::from(3);
+        let file_4 = FileId::from(4);
+        let node_1 = graph.add_file(file_1, FileExtension::Sp);
+        let node_2 = graph.add_file(file_2, FileExtension::Sp);
+        let node_3 = graph.add_file(file_3, FileExtension::Sp);
+        let node_4 = graph.add_file(file_4, FileExtension::Inc);
+        graph.add_file_include(node_1.clone(), node_2.clone());
+        graph.add_file_include(node_2.clone(), node_3.clone());
+        graph.add_file_include(node_3.clone(), node_2.clone());
+        graph.add_file_include(node_3.clone(), node_4.clone());
+        graph.add_file_include(node_3.clone(), node_1.clone());
+        let subgraphs = graph.find_subgraphs();
+        assert_eq!(subgraphs.len(), 1);
+        assert_eq!(subgraphs[0].root, node_3);
+        assert_eq!(subgraphs[0].file_ids().len(), 4);
+    }
+
+    #[test]
+    fn test_module_subgraphs() {
+        let mut graph = Graph::default();
+        let file_1 = FileId::from(1);
+        let file_2 = FileId::from(2);
+        let file_3 = FileId::from(3);
+        let file_4 = FileId::from(4);
+        let node_1 = graph.add_file(file_1, FileExtension::Sp);
+        let node_2 = graph.add_file(file_2, FileExtension::Sp);
+        let node_3 = graph.add_file(file_3, FileExtension::Sp);
+        let node_4 = graph.add_file(file_4, FileExtension::Sp);
+        graph.add_file_include(node_1.clone(), node_2.clone());
+        graph.add_file_include(node_1.clone(), node_3.clone());
+        graph.add_file_include(node_4.clone(), node_3.clone());
+        let subgraphs = graph.find_subgraphs();
+        assert_eq!(subgraphs.len(), 2);
+        assert_eq!(subgraphs[0].root, node_1);
+        assert!(subgraphs[0].contains_file(file_2));
+        assert!(subgraphs[0].contains_file(file_3));
+        assert_eq!(subgraphs[1].root, node_4);
+        assert!(subgraphs[1].contains_file(file_3));
+    }
 }
@@ -26,12 +26,101 @@ pub enum IncludeKind {
     Quotes,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// Location searched when resolving an include.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IncludeSearchLocation {
+    /// Directory of the including file, and its `include` subdirectory.
+    File,
+
+    /// Root of the workspace.
+    Workspace,
+
+    /// Include directories, in the order of the settings.
+    IncludeDirectories,
+}
+
+/// Order of the locations searched when resolving the includes of each kind.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IncludeSearchOrder {
+    /// Locations searched for `#include "foo"`.
+    pub quotes: Vec<IncludeSearchLocation>,
+
+    /// Locations searched for `#include <foo>`, and for the implicit includes.
+    pub chevrons: Vec<IncludeSearchLocation>,
+}
+
+impl Default for IncludeSearchOrder {
+    fn default() -> Self {
+        Self {
+            quotes: vec![
+                IncludeSearchLocation::File,
+                IncludeSearchLocation::Workspace,
+                IncludeSearchLocation::IncludeDirectories,
+            ],
+            chevrons: vec![
+                IncludeSearchLocation::Workspace,
+                IncludeSearchLocation::IncludeDirectories,
+            ],
+        }
+    }
+}
+
+/// Limits of the includes expanded when preprocessing a project, which guard against
+/// pathological projects, e.g symlink loops. A limit of `0` disables it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct IncludeLimits {
+    /// Maximum depth of the include chains, the root of the project being at depth `0`.
+    pub depth: usize,
+
+    /// Maximum number of files included by a project, including its root.
+    pub files: usize,
+}
+
+impl Default for IncludeLimits {
+    fn default() -> Self {
+        Self {
+            depth: 50,
+            files: 2000,
+        }
+    }
+}
+
+/// Resolve the `path` of an include of `file_id`, by searching the locations of the
+/// [`IncludeSearchOrder`] of its kind.
+///
+/// The extension of `path` is expected to be inferred already, see [`infer_include_ext`].
+pub fn resolve_include<DB: SourceDatabase + ?Sized>(
+    db: &DB,
+    file_id: FileId,
+    path: &str,
+    kind: IncludeKind,
+) -> Option<FileId> {
+    let search_order = db.include_search_order();
+    let locations = match kind {
+        IncludeKind::Quotes => &search_order.quotes,
+        IncludeKind::Chevrons => &search_order.chevrons,
+    };
+    locations.iter().find_map(|location| match location {
+        IncludeSearchLocation::File => {
+            db.resolve_path(AnchoredPath::new(file_id, path))
+                .or_else(|| {
+                    // Hack to detect `include` folders when it's a relative include.
+                    let path_with_include = format!("include/{}", path);
+                    db.resolve_path(AnchoredPath::new(file_id, &path_with_include))
+                })
+        }
+        IncludeSearchLocation::Workspace => db.resolve_path_in_roots(path, false),
+        IncludeSearchLocation::IncludeDirectories => db.resolve_path_in_roots(path, true),
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Include {
     id: FileId,
     kind: IncludeKind,
     type_: IncludeType,
     extension: FileExtension,
+    range: Option<TextRange>,
 }
 
 impl Include {
@@ -40,12 +129,14 @@ impl Include {
         kind: IncludeKind,
         type_: IncludeType,
         extension: FileExtension,
+        range: Option<TextRange>,
     ) -> Self {
         Self {
             id,
             kind,
             type_,
             extension,
+            range,
         }
     }
 
@@ -64,6 +155,11 @@ impl Include {
     pub fn extension(&self) -> FileExtension {
         self.extension
     }
+
+    /// Range of the directive of the include, [`None`] for the implicit includes.
+    pub fn range(&self) -> Option<TextRange> {
+        self.range
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -89,14 +185,18 @@ pub(crate) fn file_includes_query(
     let mut res = vec![];
     let mut unresolved = vec![];
 
-    // Include sourcemod by default
-    if let Some(include_file_id) = db.resolve_path_relative_to_roots("sourcemod.inc") {
-        res.push(Include::new(
-            include_file_id,
-            IncludeKind::Chevrons,
-            IncludeType::TryInclude,
-            FileExtension::Inc,
-        ));
+    for path in db.implicit_includes().iter() {
+        let mut path = path.clone();
+        let extension = infer_include_ext(&mut path);
+        if let Some(include_file_id) = resolve_include(db, file_id, &path, IncludeKind::Chevrons) {
+            res.push(Include::new(
+                include_file_id,
+                IncludeKind::Chevrons,
+                IncludeType::TryInclude,
+                extension,
+                None,
+            ));
+        }
     }
 
     let input = db.file_text(file_id);
@@ -120,61 +220,25 @@ pub(crate) fn file_includes_query(
                     symbol.delta,
                 );
 
-                let mut kind = IncludeKind::Chevrons;
-                let mut path = None;
-                let mut ext = None;
-
-                if let Some(m) = RE_QUOTE.captures(&text).and_then(|caps| caps.get(1)) {
-                    kind = IncludeKind::Quotes;
-                    let mut raw_path = m.as_str().to_string();
-                    let raw_ext = infer_include_ext(&mut raw_path);
-                    if let Some(include_file_id) =
-                        db.resolve_path(AnchoredPath::new(file_id, &raw_path))
-                    {
-                        res.push(Include::new(include_file_id, kind, type_, raw_ext));
-                        continue;
-                    }
-                    // Hack to detect `include` folders when it's a relative include.
-                    let raw_path_with_include = format!("include/{}", raw_path);
-                    if let Some(include_file_id) =
-                        db.resolve_path(AnchoredPath::new(file_id, &raw_path_with_include))
-                    {
-                        res.push(Include::new(include_file_id, kind, type_, raw_ext));
-                        continue;
-                    }
-                    path = Some(raw_path);
-                    ext = Some(raw_ext);
-                }
-
-                if path.is_none() {
-                    if let Some(m) = RE_CHEVRON.captures(&text).and_then(|caps| caps.get(1)) {
-                        kind = IncludeKind::Chevrons;
-                        let mut raw_path = m.as_str().to_string();
-                        ext = Some(infer_include_ext(&mut raw_path));
-                        path = Some(raw_path);
-                    }
-                }
-                let (path, ext) = match (path, ext) {
-                    (Some(path), Some(ext)) => (path, ext),
-                    (Some(path), _) => {
-                        if type_ == IncludeType::Include {
-                            // TODO: Optional diagnostic for tryinclude ?
-                            // FIXME: Emit the diagnostics in the preprocessor. This would make more sense as some
-                            // includes might be disabled by the preprocessor.
-                            unresolved.push(UnresolvedInclude {
-                                file_id,
-                                range: symbol.range,
-                                path,
-                            })
-                        }
-                        continue;
-                    }
-                    _ => continue,
+                let (kind, mut path) = if let Some(m) =
+                    RE_QUOTE.captures(&text).and_then(|caps| caps.get(1))
+                {
+                    (IncludeKind::Quotes, m.as_str().to_string())
+                } else if let Some(m) = RE_CHEVRON.captures(&text).and_then(|caps| caps.get(1)) {
+                    (IncludeKind::Chevrons, m.as_str().to_string())
+                } else {
+                    continue;
                 };
-                match db.resolve_path_relative_to_roots(&path) {
+                let ext = infer_include_ext(&mut path);
+                match resolve_include(db, file_id, &path, kind) {
                     Some(include_file_id) => {
-                        res.push(Include::new(include_file_id, kind, type_, ext));
-                        continue;
+                        res.push(Include::new(
+                            include_file_id,
+                            kind,
+                            type_,
+                            ext,
+                            Some(symbol.range),
+                        ));
                     }
                     None => {
                         if type_ == IncludeType::Include {
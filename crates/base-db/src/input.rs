@@ -38,6 +38,11 @@ impl SourceRoot {
         }
     }
 
+    /// Path of the root directory.
+    pub fn root(&self) -> &VfsPath {
+        &self.root
+    }
+
     pub fn path_for_file(&self, file: &FileId) -> Option<&VfsPath> {
         self.file_set.path_for_file(file)
     }
@@ -63,6 +68,9 @@ impl SourceRoot {
 #[derive(Default, Debug)]
 pub struct SourceRootConfig {
     pub fsc: FileSetConfig,
+    /// Number of roots of the [`FileSetConfig`] which are workspace roots, the other ones being
+    /// include directories. The first root is always a workspace root.
+    pub local_roots: usize,
 }
 
 impl SourceRootConfig {
@@ -71,9 +79,8 @@ impl SourceRootConfig {
             .partition(vfs)
             .into_iter()
             .enumerate()
-            // Assume that the first file set is the local one.
             .map(|(idx, (file_set, root))| {
-                if idx == 0 {
+                if idx < self.local_roots.max(1) {
                     SourceRoot::new_local(file_set, root)
                 } else {
                     SourceRoot::new_include_dir(file_set, root)
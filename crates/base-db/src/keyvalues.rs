@@ -0,0 +1,67 @@
+//! Tokenizer of the KeyValues documents of SourceMod, e.g the translation and gamedata files.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Token<'a> {
+    Open,
+    Close,
+    String(&'a str),
+}
+
+/// Tokenizer of KeyValues documents.
+pub(crate) struct Tokens<'a> {
+    pub(crate) text: &'a str,
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        loop {
+            self.text = self.text.trim_start();
+            if let Some(rest) = self.text.strip_prefix("//") {
+                self.text = rest.find('\n').map_or("", |idx| &rest[idx..]);
+                continue;
+            }
+            break;
+        }
+        let mut chars = self.text.chars();
+        let token = match chars.next()? {
+            '{' => {
+                self.text = chars.as_str();
+                Token::Open
+            }
+            '}' => {
+                self.text = chars.as_str();
+                Token::Close
+            }
+            '"' => {
+                let rest = chars.as_str();
+                let mut end = rest.len();
+                let mut escaped = false;
+                for (idx, c) in rest.char_indices() {
+                    match c {
+                        '\\' if !escaped => escaped = true,
+                        '"' if !escaped => {
+                            end = idx;
+                            break;
+                        }
+                        _ => escaped = false,
+                    }
+                }
+                self.text = rest.get(end + 1..).unwrap_or_default();
+                Token::String(&rest[..end])
+            }
+            _ => {
+                let end = self
+                    .text
+                    .find(|c: char| c.is_whitespace() || matches!(c, '{' | '}' | '"'))
+                    .unwrap_or(self.text.len());
+                let (value, rest) = self.text.split_at(end);
+                self.text = rest;
+                Token::String(value)
+            }
+        };
+
+        Some(token)
+    }
+}
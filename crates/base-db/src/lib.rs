@@ -1,5 +1,6 @@
 use std::{hash::Hash, sync::Arc};
 
+use fxhash::FxHashMap;
 use include::file_includes_query;
 use input::{SourceRoot, SourceRootId};
 use syntax::utils::lsp_position_to_ts_point;
@@ -7,22 +8,31 @@ use text_size::{TextRange, TextSize};
 use vfs::{AnchoredPath, FileId};
 
 mod change;
+mod gamedata;
 mod graph;
 mod include;
 mod input;
+mod keyvalues;
+mod phrases;
 
 pub use {
     change::Change,
+    gamedata::{Gamedata, GamedataSection, GAMEDATA_FILE_EXTENSION},
     graph::{Graph, SubGraph},
     include::{
-        infer_include_ext, Include, IncludeKind, IncludeType, UnresolvedInclude, RE_CHEVRON,
+        infer_include_ext, resolve_include, Include, IncludeKind, IncludeLimits,
+        IncludeSearchLocation, IncludeSearchOrder, IncludeType, UnresolvedInclude, RE_CHEVRON,
         RE_QUOTE,
     },
     input::SourceRootConfig,
+    phrases::{Phrase, Phrases, PHRASES_FILE_EXTENSION},
 };
 
 pub const DEFAULT_PARSE_LRU_CAP: usize = 128;
 
+/// Files implicitly included by spcomp at the top of every file.
+pub const DEFAULT_IMPLICIT_INCLUDES: &[&str] = &["sourcemod"];
+
 pub trait FileLoader {
     /// Text of the file.
     fn file_text(&self, file_id: FileId) -> Arc<str>;
@@ -35,6 +45,10 @@ pub trait FileLoader {
 
     /// Resolve a path relative to the roots.
     fn resolve_path_relative_to_roots(&self, path: &str) -> Option<FileId>;
+
+    /// Resolve a path relative to the include directories if `include_dirs` is `true`, or to the
+    /// other roots otherwise. The roots are searched in order.
+    fn resolve_path_in_roots(&self, path: &str, include_dirs: bool) -> Option<FileId>;
 }
 
 #[derive(Debug, Clone)]
@@ -122,6 +136,42 @@ pub trait SourceDatabase: FileLoader + std::fmt::Debug {
 
     #[salsa::invoke(graph::Graph::projet_subgraph_query)]
     fn projet_subgraph(&self, file_id: FileId) -> Option<Arc<graph::SubGraph>>;
+
+    /// Main file pinned by the user for a file, used when the file belongs to several
+    /// projects or when its project can't be detected.
+    #[salsa::input]
+    fn main_files(&self) -> Arc<FxHashMap<FileId, FileId>>;
+
+    /// Phrases of the translation files of the project.
+    #[salsa::input]
+    fn phrases(&self) -> Arc<Phrases>;
+
+    /// Entries of the gamedata files of the project.
+    #[salsa::input]
+    fn gamedata(&self) -> Arc<Gamedata>;
+
+    /// Files implicitly included at the top of every file, e.g `sourcemod`, followed by the
+    /// force-included files.
+    #[salsa::input]
+    fn implicit_includes(&self) -> Arc<Vec<String>>;
+
+    /// Order of the locations searched when resolving the includes.
+    #[salsa::input]
+    fn include_search_order(&self) -> Arc<IncludeSearchOrder>;
+
+    /// Limits of the includes expanded when preprocessing a project.
+    #[salsa::input]
+    fn include_limits(&self) -> IncludeLimits;
+
+    /// Branches of the conditions forced active by the user in each file, by their index among
+    /// the `#if`, `#elseif` and `#else` directives of the file.
+    #[salsa::input]
+    fn forced_branches(&self) -> Arc<FxHashMap<FileId, Vec<u32>>>;
+
+    /// Additional configurations the files are analyzed in, as the macros each of them defines
+    /// before the first line of the projects, e.g `DEBUG` or `MAX_PLAYERS=64`.
+    #[salsa::input]
+    fn macro_configurations(&self) -> Arc<Vec<Vec<String>>>;
 }
 
 /// We don't want to give HIR knowledge of source roots, hence we extract these
@@ -172,6 +222,13 @@ impl<T: SourceDatabaseExt> FileLoader for FileLoaderDelegate<&'_ T> {
         }
         None
     }
+    fn resolve_path_in_roots(&self, path: &str, include_dirs: bool) -> Option<FileId> {
+        self.0
+            .source_roots()
+            .iter()
+            .filter(|source_root| source_root.is_include_dir == include_dirs)
+            .find_map(|source_root| source_root.resolve_path_relative_to_root(path))
+    }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
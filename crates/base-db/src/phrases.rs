@@ -0,0 +1,158 @@
+//! Phrases of the translation files of a project.
+//!
+//! SourceMod plugins load their translations with `LoadTranslations("foo.phrases")`, which reads
+//! `translations/foo.phrases.txt`. Those files are KeyValues documents, which map the name of
+//! each phrase to its `#format` and to its text in each language:
+//!
+//! ```text
+//! "Phrases"
+//! {
+//!     "Kicked player"
+//!     {
+//!         "#format"   "{1:s},{2:d}"
+//!         "en"        "Kicked {1} after {2} warnings"
+//!     }
+//! }
+//! ```
+
+use fxhash::FxHashMap;
+
+use crate::keyvalues::{Token, Tokens};
+
+/// Extension of the translation files.
+pub const PHRASES_FILE_EXTENSION: &str = ".phrases.txt";
+
+/// Phrases defined by the translation files of a project, indexed by their name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Phrases {
+    phrases: FxHashMap<String, Phrase>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Phrase {
+    /// Name of the translation file defining the phrase, as passed to `LoadTranslations`,
+    /// e.g `common.phrases`.
+    pub file: String,
+    /// Number of format parameters of the phrase, e.g `2` for `{1:s},{2:d}`.
+    pub params: usize,
+}
+
+impl Phrases {
+    /// Add the phrases of a translation file.
+    ///
+    /// # Arguments
+    /// * `file` - Name of the translation file, e.g `common.phrases`.
+    /// * `text` - Contents of the translation file.
+    pub fn add_file(&mut self, file: &str, text: &str) {
+        let mut depth = 0usize;
+        let mut phrase: Option<String> = None;
+        let mut last_key: Option<&str> = None;
+        for token in (Tokens { text }) {
+            match token {
+                Token::Open => {
+                    if depth == 1 {
+                        phrase = last_key.map(ToOwned::to_owned);
+                        if let Some(name) = &phrase {
+                            self.phrases.insert(
+                                name.clone(),
+                                Phrase {
+                                    file: file.to_owned(),
+                                    params: 0,
+                                },
+                            );
+                        }
+                    }
+                    depth += 1;
+                    last_key = None;
+                }
+                Token::Close => {
+                    depth = depth.saturating_sub(1);
+                    if depth <= 1 {
+                        phrase = None;
+                    }
+                    last_key = None;
+                }
+                Token::String(value) => match last_key.take() {
+                    Some("#format") if depth == 2 => {
+                        if let Some(phrase) =
+                            phrase.as_ref().and_then(|it| self.phrases.get_mut(it))
+                        {
+                            phrase.params = format_params(value);
+                        }
+                    }
+                    Some(_) => (),
+                    None => last_key = Some(value),
+                },
+            }
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Phrase> {
+        self.phrases.get(name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Phrase)> {
+        self.phrases
+            .iter()
+            .map(|(name, phrase)| (name.as_str(), phrase))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.phrases.is_empty()
+    }
+}
+
+/// Number of parameters of a `#format` value, i.e the highest index of its `{N:x}` parameters.
+fn format_params(format: &str) -> usize {
+    format
+        .split('{')
+        .skip(1)
+        .filter_map(|param| {
+            let end = param.find(|c: char| !c.is_ascii_digit())?;
+            param[..end].parse::<usize>().ok()
+        })
+        .max()
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_phrases() {
+        let text = r##"// Translations of the plugin.
+"Phrases"
+{
+    "Kicked player"
+    {
+        "#format"   "{1:s},{2:d}"
+        "en"        "Kicked {1} after {2} warnings"
+    }
+    "No format"
+    {
+        "en"        "Hello"
+        "fr"        "Bonjour"
+    }
+    Unquoted
+    {
+        "#format"   "{1:s}"
+        "en"        "\"{1}\""
+    }
+}"##;
+        let mut phrases = Phrases::default();
+        phrases.add_file("plugin.phrases", text);
+
+        let params = |name: &str| phrases.get(name).map(|phrase| phrase.params);
+        assert_eq!(params("Kicked player"), Some(2));
+        assert_eq!(params("No format"), Some(0));
+        assert_eq!(params("Unquoted"), Some(1));
+        assert_eq!(params("en"), None);
+        assert_eq!(
+            phrases
+                .get("Kicked player")
+                .map(|phrase| phrase.file.as_str()),
+            Some("plugin.phrases")
+        );
+    }
+}
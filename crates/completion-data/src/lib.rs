@@ -28,6 +28,16 @@ impl Game<'_> {
     pub fn events(&self) -> &[Event] {
         &self.events
     }
+
+    /// Returns the url of the description of the fields of an event of the game, on the
+    /// Alliedmodders wiki.
+    pub fn wiki_url(&self, event: &str) -> String {
+        format!(
+            "https://wiki.alliedmods.net/{}_Events#{}",
+            self.name.replace(' ', "_"),
+            event
+        )
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize)]
@@ -109,25 +119,24 @@ impl Database<'_> {
         res
     }
 
+    /// Returns the games whose events are available in all the games.
+    pub fn generic_games(&self) -> impl Iterator<Item = &Game> + '_ {
+        GENERIC_GAMES
+            .iter()
+            .map(|name| self.0.get(name).expect("expected generic events"))
+    }
+
     /// Returns all the generic events as a vector of owned [`Events`](Event).
     pub fn generic_events(&self) -> Vec<Event> {
-        let mut res = Vec::new();
-        let names = ["Generic Source", "Generic Source Server"];
-        for name in names {
-            res.extend(
-                self.0
-                    .get(name)
-                    .expect("expected generic events")
-                    .events
-                    .iter()
-                    .cloned(),
-            )
-        }
-
-        res
+        self.generic_games()
+            .flat_map(|game| game.events.iter().cloned())
+            .collect()
     }
 }
 
+/// Names of the games whose events are available in all the games.
+const GENERIC_GAMES: [&str; 2] = ["Generic Source", "Generic Source Server"];
+
 /// Bytes of the compressed JSON data for events completion.
 const EVENTS_JSON_GZ: &[u8] = include_bytes!("../data/events.json.gz");
 
@@ -24,7 +24,9 @@ use stdx::process::streaming_output;
 
 mod spcomp;
 
-pub use spcomp::{SpCompDiagnostic, SpCompSeverity};
+pub use spcomp::{
+    build_compile_args, parse_output, CompileOptions, SpCompDiagnostic, SpCompSeverity,
+};
 
 #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
 pub enum InvocationStrategy {
@@ -300,16 +302,7 @@ impl FlycheckActor {
             &self.config.include_directories,
             &self.config.args,
         );
-        #[cfg(all(target_arch = "aarch64", target_os = "macos"))]
-        let program = "arch";
-        #[cfg(not(all(target_arch = "aarch64", target_os = "macos")))]
-        let program = &self.config.command;
-
-        let mut command = Command::new(program);
-
-        #[cfg(all(target_arch = "aarch64", target_os = "macos"))]
-        command.arg("-x86_64").arg(&self.config.command);
-
+        let mut command = spcomp_command(&self.config.command);
         command.args(args);
 
         command
@@ -325,6 +318,47 @@ impl FlycheckActor {
     }
 }
 
+/// Output of a compilation with spcomp.
+#[derive(Debug)]
+pub struct CompileOutput {
+    /// Whether spcomp exited successfully, i.e the `.smx` file was written.
+    pub success: bool,
+    pub diagnostics: Vec<SpCompDiagnostic>,
+    /// Output of spcomp, stdout followed by stderr.
+    pub output: String,
+}
+
+/// Compile a plugin with spcomp, blocking until it exits.
+///
+/// # Arguments
+/// * `program` - Path to spcomp.
+/// * `args` - Arguments of spcomp, see [`build_compile_args`].
+pub fn compile(program: &str, args: Vec<String>) -> io::Result<CompileOutput> {
+    let output = spcomp_command(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .output()?;
+    let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+    text.push_str(&String::from_utf8_lossy(&output.stderr));
+
+    Ok(CompileOutput {
+        success: output.status.success(),
+        diagnostics: parse_output(&text),
+        output: text,
+    })
+}
+
+/// [Command] running spcomp, through Rosetta on Apple Silicon as spcomp is only built for x86_64.
+fn spcomp_command(program: &str) -> Command {
+    if cfg!(all(target_arch = "aarch64", target_os = "macos")) {
+        let mut command = Command::new("arch");
+        command.arg("-x86_64").arg(program);
+        return command;
+    }
+
+    Command::new(program)
+}
+
 struct JodGroupChild(GroupChild);
 
 impl Drop for JodGroupChild {
@@ -1,9 +1,24 @@
+//! Parser of the output of spcomp.
+//!
+//! spcomp reports its diagnostics on stdout, one per line:
+//!
+//! ```text
+//! /path/to/plugin.sp(12) : error 017: undefined symbol "foo"
+//! /path/to/plugin.sp(20) : warning 203: symbol is never used: "bar"
+//! ```
+//!
+//! The other lines, e.g the header of the compiler and the size of the compiled plugin, are
+//! ignored.
+
 use lazy_static::lazy_static;
 use paths::AbsPathBuf;
 use regex::Regex;
 
+/// Url of the documentation of the spcomp diagnostics.
+const SPCOMP_DOCS_URL: &str = "https://sarrus1.github.io/sourcepawn-studio/docs/spcomp";
+
 /// Severity levels of spcomp errors.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SpCompSeverity {
     Warning,
     Error,
@@ -11,6 +26,24 @@ pub enum SpCompSeverity {
 }
 
 impl SpCompSeverity {
+    /// Parse a severity as written by spcomp, e.g `fatal error`.
+    pub fn parse(text: &str) -> Option<Self> {
+        match text {
+            "warning" => Some(SpCompSeverity::Warning),
+            "error" => Some(SpCompSeverity::Error),
+            "fatal error" => Some(SpCompSeverity::FatalError),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SpCompSeverity::Warning => "warning",
+            SpCompSeverity::Error => "error",
+            SpCompSeverity::FatalError => "fatal error",
+        }
+    }
+
     /// Convert to a [LSP DiagnosticSeverity](lsp_types::DiagnosticSeverity).
     pub fn to_lsp_severity(&self) -> lsp_types::DiagnosticSeverity {
         match self {
@@ -22,7 +55,7 @@ impl SpCompSeverity {
 }
 
 /// Representation of an spcomp error.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SpCompDiagnostic {
     /// [Path](AbsPathBuf) of the document where the error comes from.
     path: AbsPathBuf,
@@ -33,7 +66,7 @@ pub struct SpCompDiagnostic {
     /// Severity of the error.
     severity: SpCompSeverity,
 
-    /// Code of the error.
+    /// Code of the error, e.g `017`.
     code: String,
 
     /// Message of the error.
@@ -61,29 +94,59 @@ impl SpCompDiagnostic {
         &self.message
     }
 
+    /// Url of the documentation of the error, e.g `.../spcomp#warning-203`.
+    pub fn url(&self) -> String {
+        format!(
+            "{}#{}-{}",
+            SPCOMP_DOCS_URL,
+            self.severity.as_str().replace(' ', "-"),
+            self.code
+        )
+    }
+
     pub fn try_from_line(line: &str) -> Option<Self> {
         lazy_static! {
-            static ref RE: Regex = Regex::new(
-                r"([:/\\A-Za-z\-_0-9. ]*)\((\d+)+\) : (?:(error|fatal error|warning) ([0-9]*)):\s+(.*)"
-            )
-            .expect("Failed to compile spcomp error regex.");
+            static ref RE: Regex =
+                Regex::new(r"^\s*(.+?)\((\d+)\) : (error|fatal error|warning) (\d+):\s+(.*?)\s*$")
+                    .expect("Failed to compile spcomp error regex.");
         }
         let capture = RE.captures(line)?;
         Some(Self {
             path: AbsPathBuf::try_from(capture.get(1)?.as_str()).ok()?,
-            line_index: capture.get(2)?.as_str().parse::<u32>().ok()? - 1,
-            severity: match capture.get(3)?.as_str() {
-                "warning" => SpCompSeverity::Warning,
-                "error" => SpCompSeverity::Error,
-                "fatal error" => SpCompSeverity::FatalError,
-                _ => unreachable!(),
-            },
+            line_index: capture
+                .get(2)?
+                .as_str()
+                .parse::<u32>()
+                .ok()?
+                .saturating_sub(1),
+            severity: SpCompSeverity::parse(capture.get(3)?.as_str())?,
             code: capture.get(4)?.as_str().to_string(),
             message: capture.get(5)?.as_str().to_string(),
         })
     }
 }
 
+/// Parse the diagnostics of the output of spcomp, skipping the lines which are not diagnostics.
+pub fn parse_output(output: &str) -> Vec<SpCompDiagnostic> {
+    output
+        .lines()
+        .filter_map(SpCompDiagnostic::try_from_line)
+        .collect()
+}
+
+/// Options of the compilation of a plugin into a `.smx` file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CompileOptions {
+    /// Optimization level, passed with `-O`.
+    pub optimization_level: Option<usize>,
+
+    /// Verbosity level, passed with `-v`.
+    pub verbosity: Option<usize>,
+
+    /// Symbols defined on the command line, e.g `DEBUG=1`.
+    pub defines: Vec<String>,
+}
+
 /// Return a [vector](Vec) of [strings](String) of the arguments to run spcomp.
 pub fn build_args(
     root_path: &AbsPathBuf,
@@ -92,11 +155,49 @@ pub fn build_args(
     linter_arguments: &[String],
 ) -> Vec<String> {
     let mut args = vec![root_path.to_string()];
+    args.extend(include_args(root_path, includes_directories));
+
+    args.push(format!("-o{}", out_path));
+    args.push("--syntax-only".to_string());
+
+    args.extend_from_slice(linter_arguments);
+
+    args
+}
+
+/// Return a [vector](Vec) of [strings](String) of the arguments to compile a plugin into
+/// `out_path`.
+pub fn build_compile_args(
+    root_path: &AbsPathBuf,
+    out_path: &AbsPathBuf,
+    includes_directories: &[AbsPathBuf],
+    options: &CompileOptions,
+    arguments: &[String],
+) -> Vec<String> {
+    let mut args = vec![root_path.to_string()];
+    args.extend(include_args(root_path, includes_directories));
+
+    args.push(format!("-o{}", out_path));
     args.extend(
-        includes_directories
-            .iter()
-            .map(|includes_directory| format!("-i{}", includes_directory)),
+        options
+            .optimization_level
+            .map(|level| format!("-O{}", level)),
     );
+    args.extend(options.verbosity.map(|level| format!("-v{}", level)));
+    args.extend(options.defines.iter().cloned());
+
+    args.extend_from_slice(arguments);
+
+    args
+}
+
+/// Include directories arguments of spcomp, followed by the directory of the compiled file and its
+/// `include` subdirectory.
+fn include_args(root_path: &AbsPathBuf, includes_directories: &[AbsPathBuf]) -> Vec<String> {
+    let mut args = includes_directories
+        .iter()
+        .map(|includes_directory| format!("-i{}", includes_directory))
+        .collect::<Vec<_>>();
     if let Some(parent_path) = root_path.parent() {
         args.push(format!("-i{}", parent_path));
         let include_path = parent_path.join("include");
@@ -105,10 +206,49 @@ pub fn build_args(
         }
     }
 
-    args.push(format!("-o{}", out_path));
-    args.push("--syntax-only".to_string());
-
-    args.extend_from_slice(linter_arguments);
-
     args
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_spcomp_output() {
+        let output = r#"SourcePawn Compiler 1.11.0.6911
+Copyright (c) 1997-2006 ITB CompuPhase
+Copyright (c) 2004-2021 AlliedModders LLC
+
+/home/user/plugin.sp(12) : error 017: undefined symbol "foo"
+/home/user/my plugin (copy).sp(20) : warning 203: symbol is never used: "bar"
+/home/user/plugin.sp(1) : fatal error 417: cannot read from file: "baz"
+
+2 Errors.
+"#;
+        let diagnostics = parse_output(output);
+        assert_eq!(diagnostics.len(), 3);
+
+        assert_eq!(diagnostics[0].path().to_string(), "/home/user/plugin.sp");
+        assert_eq!(diagnostics[0].line_index(), 11);
+        assert_eq!(diagnostics[0].severity(), &SpCompSeverity::Error);
+        assert_eq!(diagnostics[0].code(), "017");
+        assert_eq!(diagnostics[0].message(), r#"undefined symbol "foo""#);
+
+        assert_eq!(
+            diagnostics[1].path().to_string(),
+            "/home/user/my plugin (copy).sp"
+        );
+        assert_eq!(diagnostics[1].severity(), &SpCompSeverity::Warning);
+        assert_eq!(
+            diagnostics[1].url(),
+            format!("{SPCOMP_DOCS_URL}#warning-203")
+        );
+
+        assert_eq!(diagnostics[2].line_index(), 0);
+        assert_eq!(diagnostics[2].severity(), &SpCompSeverity::FatalError);
+        assert_eq!(
+            diagnostics[2].url(),
+            format!("{SPCOMP_DOCS_URL}#fatal-error-417")
+        );
+    }
+}
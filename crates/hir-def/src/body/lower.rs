@@ -170,13 +170,7 @@ impl ExprCollector<'_> {
                     file_id: self.file_id,
                 });
                 let mut statements = Vec::new();
-                for child in expr.children(&mut expr.walk()) {
-                    match TSKind::from(child) {
-                        TSKind::anon_LBRACE | TSKind::anon_RBRACE => continue,
-                        _ => (),
-                    }
-                    statements.push(self.collect_expr(child));
-                }
+                self.collect_statements(expr, &mut statements);
                 let block = Expr::Block {
                     id: Some(block_id),
                     statements: statements.into_boxed_slice(),
@@ -331,7 +325,8 @@ impl ExprCollector<'_> {
                         };
                         Some(self.alloc_expr(call, NodePtr::from(&method)))
                     }
-                    _ => unreachable!(),
+                    // The parser recovered from a syntax error with another callee.
+                    _ => None,
                 }
             }
             TSKind::array_indexed_access => {
@@ -491,7 +486,8 @@ impl ExprCollector<'_> {
             | TSKind::anon_RBRACK
             | TSKind::anon_LPAREN
             | TSKind::anon_RPAREN
-            | TSKind::anon_COMMA => None,
+            | TSKind::anon_COMMA
+            | TSKind::ERROR => None,
             _ => {
                 log::warn!("Unhandled expression: {:?}", expr);
                 None
@@ -499,6 +495,24 @@ impl ExprCollector<'_> {
         }
     }
 
+    /// Collect the statements of a block.
+    ///
+    /// The statements the parser could not make sense of are wrapped in an error node, along with
+    /// the statements around them it gave up on. The children of the error nodes are collected as
+    /// statements of the block, so that a half-typed statement does not hide the declarations
+    /// which follow it.
+    fn collect_statements(&mut self, node: tree_sitter::Node, statements: &mut Vec<ExprId>) {
+        let in_error = TSKind::from(node) == TSKind::ERROR;
+        for child in node.children(&mut node.walk()) {
+            match TSKind::from(child) {
+                TSKind::anon_LBRACE | TSKind::anon_RBRACE => continue,
+                TSKind::ERROR => self.collect_statements(child, statements),
+                _ if in_error && !child.is_named() => continue,
+                _ => statements.push(self.collect_expr(child)),
+            }
+        }
+    }
+
     fn alloc_expr_desugared(&mut self, expr: Expr) -> ExprId {
         self.body.exprs.alloc(expr)
     }
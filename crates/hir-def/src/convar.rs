@@ -0,0 +1,65 @@
+use std::sync::Arc;
+
+use smol_str::SmolStr;
+use syntax::TSKind;
+use vfs::FileId;
+
+use crate::{db::visit_calls, unquote, DefDatabase, NodePtr};
+
+/// A console variable created with `CreateConVar`, e.g
+/// `CreateConVar("sm_foo_enabled", "1", "Whether foo is enabled")`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConVar {
+    pub name: SmolStr,
+    pub default: Option<SmolStr>,
+    pub description: Option<SmolStr>,
+    /// String literal of the name, in the preprocessed file.
+    pub name_ptr: NodePtr,
+}
+
+impl ConVar {
+    pub(crate) fn file_convars_query(db: &dyn DefDatabase, file_id: FileId) -> Arc<[ConVar]> {
+        let tree = db.parse(file_id);
+        let source = db.preprocessed_text(file_id);
+        let mut res = Vec::new();
+        visit_calls(&tree, |node| {
+            if let Some(convar) = ConVar::from_call(&node, &source) {
+                res.push(convar);
+            }
+        });
+
+        res.into()
+    }
+
+    /// ConVar created by a call, if it is a call to `CreateConVar` (or to its `AutoExecConfig`
+    /// wrapper) whose name is a string literal.
+    fn from_call(call: &tree_sitter::Node, source: &str) -> Option<Self> {
+        let function = call.child_by_field_name("function")?;
+        if !matches!(
+            function.utf8_text(source.as_bytes()).ok()?,
+            "CreateConVar" | "AutoExecConfig_CreateConVar"
+        ) {
+            return None;
+        }
+        let arguments = call.child_by_field_name("arguments")?;
+        let mut args = arguments
+            .named_children(&mut arguments.walk())
+            .filter(|arg| TSKind::from(arg) != TSKind::comment)
+            .collect::<Vec<_>>()
+            .into_iter();
+        let literal = |arg: Option<tree_sitter::Node>| {
+            arg.filter(|arg| TSKind::from(arg) == TSKind::string_literal)
+                .and_then(|arg| arg.utf8_text(source.as_bytes()).ok())
+                .map(|text| SmolStr::from(unquote(text)))
+        };
+        let name_node = args.next()?;
+        let name = literal(Some(name_node))?;
+
+        Some(Self {
+            name,
+            default: literal(args.next()),
+            description: literal(args.next()),
+            name_ptr: NodePtr::from(&name_node),
+        })
+    }
+}
@@ -3,7 +3,7 @@ use std::{fmt, sync::Arc};
 use fxhash::FxHashMap;
 use itertools::Itertools;
 use la_arena::{Arena, ArenaMap, Idx};
-use smol_str::ToSmolStr;
+use smol_str::{SmolStr, ToSmolStr};
 use syntax::TSKind;
 
 use crate::{
@@ -69,6 +69,7 @@ pub struct FunctionData {
     pub visibility: RawVisibilityId,
     pub special: Option<SpecialMethod>,
     pub deprecated: bool,
+    pub deprecation_message: Option<SmolStr>,
 }
 
 impl FunctionData {
@@ -90,6 +91,7 @@ impl FunctionData {
             visibility: function.visibility,
             special: function.special,
             deprecated: function.deprecated,
+            deprecation_message: function.deprecation_message.clone(),
         };
 
         Arc::new(function_data)
@@ -121,6 +123,23 @@ impl FunctionData {
             Some(self.params.len())
         }
     }
+
+    /// Index of the format string parameter, if the function formats its rest parameter, e.g
+    /// `PrintToChat(int client, const char[] format, any ...)`.
+    pub fn format_param_index(&self) -> Option<usize> {
+        let (rest, params) = self.params.split_last()?;
+        if !rest.is_rest {
+            return None;
+        }
+        let idx = params.len().checked_sub(1)?;
+        // The dimensions of `char format[]` are not part of its type.
+        let type_ref = match params[idx].type_ref.as_ref()? {
+            TypeRef::Array((type_ref, _)) => &**type_ref,
+            type_ref => type_ref,
+        };
+
+        matches!(type_ref, TypeRef::Char | TypeRef::OldString).then_some(idx)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -830,6 +849,7 @@ pub struct GlobalData {
     name: Name,
     type_ref: Option<TypeRef>,
     visibility: RawVisibilityId,
+    is_const: bool,
 }
 
 impl GlobalData {
@@ -841,6 +861,7 @@ impl GlobalData {
             name: global.name.clone(),
             type_ref: global.type_ref.clone(),
             visibility: global.visibility,
+            is_const: global.is_const,
         };
 
         Arc::new(global_data)
@@ -857,6 +878,10 @@ impl GlobalData {
     pub fn visibility(&self) -> RawVisibilityId {
         self.visibility
     }
+
+    pub fn is_const(&self) -> bool {
+        self.is_const
+    }
 }
 
 impl HasChildSource<LocalFieldId> for EnumStructId {
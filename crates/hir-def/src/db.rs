@@ -1,18 +1,20 @@
 use std::sync::Arc;
 
 use base_db::{
-    infer_include_ext, FileExtension, IncludeKind, IncludeType, SourceDatabase, Tree, RE_CHEVRON,
-    RE_QUOTE,
+    infer_include_ext, resolve_include, FileExtension, IncludeKind, IncludeType, SourceDatabase,
+    Tree, RE_CHEVRON, RE_QUOTE,
 };
-use fxhash::FxHashMap;
+use fxhash::{FxHashMap, FxHashSet};
 use preprocessor::db::PreprocDatabase;
 use smallvec::SmallVec;
+use smol_str::SmolStr;
 use syntax::TSKind;
-use vfs::{AnchoredPath, FileId};
+use vfs::FileId;
 
 use crate::{
     ast_id_map::AstIdMap,
     body::{scope::ExprScopes, Body, BodySourceMap},
+    convar::ConVar,
     data::{
         EnumData, EnumStructData, FuncenumData, FunctagData, FunctionData, GlobalData, MacroData,
         MethodmapData, PropertyData, StructData, TypedefData, TypesetData, VariantData,
@@ -66,6 +68,20 @@ pub trait DefDatabase: InternDatabase + PreprocDatabase {
     #[salsa::invoke(parse_query)]
     fn parse(&self, file_id: FileId) -> Tree;
 
+    /// Names of the translation files loaded with `LoadTranslations` by the file, e.g
+    /// `common.phrases`.
+    #[salsa::invoke(loaded_translations_query)]
+    fn loaded_translations(&self, file_id: FileId) -> Arc<FxHashSet<SmolStr>>;
+
+    /// Names of the gamedata files loaded with `LoadGameConfigFile` or `new GameData` by the
+    /// file, e.g `sdktools.games`.
+    #[salsa::invoke(loaded_gamedata_query)]
+    fn loaded_gamedata(&self, file_id: FileId) -> Arc<FxHashSet<SmolStr>>;
+
+    /// ConVars created with `CreateConVar` by the file.
+    #[salsa::invoke(ConVar::file_convars_query)]
+    fn file_convars(&self, file_id: FileId) -> Arc<[ConVar]>;
+
     #[salsa::invoke(ItemTree::file_item_tree_query)]
     fn file_item_tree(&self, file_id: FileId) -> Arc<ItemTree>;
 
@@ -156,6 +172,102 @@ fn parse_query(db: &dyn DefDatabase, file_id: FileId) -> Tree {
         .into()
 }
 
+fn loaded_translations_query(db: &dyn DefDatabase, file_id: FileId) -> Arc<FxHashSet<SmolStr>> {
+    let tree = db.parse(file_id);
+    let source = db.preprocessed_text(file_id);
+    let mut res = FxHashSet::default();
+    visit_calls(&tree, |node| {
+        if let Some(file) = loaded_translation(&node, &source) {
+            res.insert(file.into());
+        }
+    });
+
+    Arc::new(res)
+}
+
+fn loaded_gamedata_query(db: &dyn DefDatabase, file_id: FileId) -> Arc<FxHashSet<SmolStr>> {
+    let tree = db.parse(file_id);
+    let source = db.preprocessed_text(file_id);
+    let mut res = FxHashSet::default();
+    visit_calls(&tree, |node| {
+        if let Some(file) = loaded_gamedata_file(&node, &source) {
+            res.insert(file.into());
+        }
+    });
+
+    Arc::new(res)
+}
+
+/// Calls `f` with each call expression of the tree, including the calls of constructors with
+/// `new`.
+pub(crate) fn visit_calls(tree: &Tree, mut f: impl FnMut(tree_sitter::Node)) {
+    let root = tree.root_node();
+    let mut cursor = root.walk();
+    'outer: loop {
+        let node = cursor.node();
+        if matches!(
+            TSKind::from(node),
+            TSKind::call_expression | TSKind::new_expression
+        ) {
+            f(node);
+        }
+        if cursor.goto_first_child() {
+            continue;
+        }
+        loop {
+            if cursor.goto_next_sibling() {
+                break;
+            }
+            if !cursor.goto_parent() {
+                break 'outer;
+            }
+        }
+    }
+}
+
+/// Name of the translation file loaded by a call, if it is a call to `LoadTranslations` with a
+/// string literal, without the `.txt` extension SourceMod adds to it.
+fn loaded_translation<'a>(call: &tree_sitter::Node, source: &'a str) -> Option<&'a str> {
+    let function = call.child_by_field_name("function")?;
+    if function.utf8_text(source.as_bytes()).ok()? != "LoadTranslations" {
+        return None;
+    }
+    let arguments = call.child_by_field_name("arguments")?;
+    let file = arguments
+        .named_children(&mut arguments.walk())
+        .find(|arg| TSKind::from(arg) != TSKind::comment)
+        .filter(|arg| TSKind::from(arg) == TSKind::string_literal)?
+        .utf8_text(source.as_bytes())
+        .ok()?
+        .trim_matches('"');
+
+    Some(file.strip_suffix(".txt").unwrap_or(file))
+}
+
+/// Name of the gamedata file loaded by a call, if it is a call to `LoadGameConfigFile` or to the
+/// constructor of `GameData` with a string literal.
+fn loaded_gamedata_file<'a>(call: &tree_sitter::Node, source: &'a str) -> Option<&'a str> {
+    let function = match TSKind::from(call) {
+        TSKind::new_expression => call.child_by_field_name("class")?,
+        _ => call.child_by_field_name("function")?,
+    };
+    if !matches!(
+        function.utf8_text(source.as_bytes()).ok()?,
+        "LoadGameConfigFile" | "GameData"
+    ) {
+        return None;
+    }
+    let arguments = call.child_by_field_name("arguments")?;
+    let file = arguments
+        .named_children(&mut arguments.walk())
+        .find(|arg| TSKind::from(arg) != TSKind::comment)
+        .filter(|arg| TSKind::from(arg) == TSKind::string_literal)?
+        .utf8_text(source.as_bytes())
+        .ok()?;
+
+    Some(file.trim_matches('"'))
+}
+
 /// Resolves an include node to a file id and include type and kind.
 ///
 /// # Returns
@@ -186,40 +298,16 @@ pub fn resolve_include_node(
             RE_CHEVRON.captures(text)?.get(1)?.as_str().to_string(),
             IncludeKind::Chevrons,
         ),
-        TSKind::string_literal => {
-            let mut text = RE_QUOTE.captures(text)?.get(1)?.as_str().to_string();
-            let extension = infer_include_ext(&mut text);
-            // try to resolve path relative to the referencing file.
-            if let Some(file_id) = db.resolve_path(AnchoredPath::new(file_id, &text)) {
-                return Some((
-                    Some(file_id),
-                    IncludeKind::Quotes,
-                    type_,
-                    text.to_string(),
-                    NodePtr::from(&path_node),
-                    extension,
-                ));
-            }
-            // Hack to detect `include` folders when it's a relative include.
-            let text_with_include = format!("include/{}", text);
-            if let Some(file_id) = db.resolve_path(AnchoredPath::new(file_id, &text_with_include)) {
-                return Some((
-                    Some(file_id),
-                    IncludeKind::Quotes,
-                    type_,
-                    text.to_string(),
-                    NodePtr::from(&path_node),
-                    extension,
-                ));
-            }
-            (text, IncludeKind::Quotes)
-        }
+        TSKind::string_literal => (
+            RE_QUOTE.captures(text)?.get(1)?.as_str().to_string(),
+            IncludeKind::Quotes,
+        ),
         _ => unreachable!(),
     };
     let extension = infer_include_ext(&mut text);
 
     (
-        db.resolve_path_relative_to_roots(&text),
+        resolve_include(db, file_id, &text, kind),
         kind,
         type_,
         text,
@@ -447,6 +535,12 @@ impl DefMap {
         self.macros.get(idx).copied()
     }
 
+    pub fn macro_idx(&self, id: MacroId) -> Option<u32> {
+        self.macros
+            .iter()
+            .find_map(|(idx, macro_id)| (*macro_id == id).then_some(*idx))
+    }
+
     pub(crate) fn block_def_map_query(db: &dyn DefDatabase, block_id: BlockId) -> Arc<DefMap> {
         let item_tree = db.block_item_tree(block_id);
         let file_id = block_id.lookup(db).file_id;
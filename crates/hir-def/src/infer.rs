@@ -1,7 +1,10 @@
 use std::sync::Arc;
 
+use base_db::GamedataSection;
 use fxhash::FxHashMap;
+use itertools::Itertools;
 use smallvec::smallvec;
+use smol_str::SmolStr;
 use stdx::impl_from;
 
 use crate::{
@@ -61,6 +64,87 @@ pub enum InferenceDiagnostic {
     InvalidUseOfThis {
         expr: ExprId,
     },
+    DeprecatedCall {
+        expr: ExprId,
+        name: Name,
+        message: Option<SmolStr>,
+    },
+    IncorrectNumberOfPhraseArguments {
+        expr: ExprId,
+        phrase: SmolStr,
+        expected: usize,
+        actual: usize,
+    },
+    UnknownPhrase {
+        expr: ExprId,
+        phrase: SmolStr,
+    },
+    UnloadedTranslation {
+        expr: ExprId,
+        phrase: SmolStr,
+        file: SmolStr,
+    },
+    UnknownGamedataEntry {
+        expr: ExprId,
+        name: SmolStr,
+        section: GamedataSection,
+        files: Vec<SmolStr>,
+    },
+    IncorrectNumberOfFormatArguments {
+        expr: ExprId,
+        expected: usize,
+        actual: usize,
+    },
+    MismatchedFormatArgument {
+        expr: ExprId,
+        specifier: char,
+        expected: FormatArgKind,
+        actual: TypeRef,
+    },
+}
+
+/// Kind of value a specifier of a format string expects, e.g an integer for `%d`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum FormatArgKind {
+    Int,
+    Float,
+    String,
+}
+
+impl FormatArgKind {
+    fn of_specifier(specifier: char) -> Option<Self> {
+        match specifier {
+            'd' | 'i' | 'u' | 'b' | 'c' | 'x' | 'X' | 'N' | 'L' => Some(Self::Int),
+            'f' => Some(Self::Float),
+            's' | 't' | 'T' => Some(Self::String),
+            _ => None,
+        }
+    }
+
+    /// Kind of value of a type, if it is unambiguous.
+    ///
+    /// The dimensions of the variables are not part of their type, so a `char` may be a string.
+    fn of_type(type_ref: &TypeRef) -> Option<Self> {
+        match type_ref {
+            TypeRef::Int | TypeRef::Bool => Some(Self::Int),
+            TypeRef::Float | TypeRef::OldFloat => Some(Self::Float),
+            TypeRef::OldString => Some(Self::String),
+            TypeRef::Array((type_ref, _)) => {
+                matches!(**type_ref, TypeRef::Char | TypeRef::OldString).then_some(Self::String)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for FormatArgKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Int => f.write_str("an integer"),
+            Self::Float => f.write_str("a float"),
+            Self::String => f.write_str("a string"),
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -180,6 +264,227 @@ impl<'a> InferenceContext<'a> {
 
         data.name().into()
     }
+
+    /// Check the arguments of the current call against the specifiers of its format string, if
+    /// the function formats its rest parameter and the format string is a literal.
+    ///
+    /// Report the arguments whose type does not match their specifier, and the call if it does not
+    /// receive as many arguments as the format string expects. The arguments of a phrase formatted
+    /// with `%t` or `%T` are the format parameters of the phrase, so they are only counted when
+    /// all the phrases of the format string are known.
+    ///
+    /// Report the phrases which are not defined by the translation files, if any was found, and
+    /// those whose translation file is not loaded by the project.
+    fn check_format_arguments(&mut self, args: &[ExprId], arg_types: &[Option<TypeRef>]) {
+        let Some(data) = self.current_call_data() else {
+            return;
+        };
+        let Some(format_idx) = data.format_param_index() else {
+            return;
+        };
+        if args
+            .iter()
+            .any(|arg| matches!(self.body[*arg], Expr::NamedArg { .. }))
+        {
+            return;
+        }
+        let string_literal = |idx: usize| match args.get(idx).map(|arg| &self.body[*arg]) {
+            Some(Expr::Literal(Literal::String(text))) => Some(unquote(text)),
+            _ => None,
+        };
+        let Some(format) = string_literal(format_idx) else {
+            return;
+        };
+        let phrases = self.db.phrases();
+        let mut diagnostics = Vec::new();
+        let mut check_type = |idx: usize, specifier: char, expected: FormatArgKind| {
+            let (Some(expr), Some(Some(actual))) = (args.get(idx), arg_types.get(idx)) else {
+                return;
+            };
+            if FormatArgKind::of_type(actual).is_some_and(|kind| kind != expected) {
+                diagnostics.push(InferenceDiagnostic::MismatchedFormatArgument {
+                    expr: *expr,
+                    specifier,
+                    expected,
+                    actual: actual.clone(),
+                });
+            }
+        };
+        let mut phrase_diagnostics = Vec::new();
+        let mut last_phrase = None;
+        let mut next_arg = format_idx + 1;
+        let mut counted = true;
+        for specifier in format_specifiers(format) {
+            if let Some(expected) = FormatArgKind::of_specifier(specifier) {
+                check_type(next_arg, specifier, expected);
+            }
+            match specifier {
+                't' | 'T' => {
+                    let phrase_idx = next_arg;
+                    next_arg += 1;
+                    // `%T` takes the client to translate the phrase for after its name.
+                    if specifier == 'T' {
+                        check_type(next_arg, specifier, FormatArgKind::Int);
+                        next_arg += 1;
+                    }
+                    let Some(phrase) = string_literal(phrase_idx) else {
+                        counted = false;
+                        break;
+                    };
+                    let Some(data) = phrases.get(phrase) else {
+                        if !phrases.is_empty() {
+                            phrase_diagnostics.push(InferenceDiagnostic::UnknownPhrase {
+                                expr: args[phrase_idx],
+                                phrase: phrase.into(),
+                            });
+                        }
+                        counted = false;
+                        break;
+                    };
+                    if !self.is_translation_loaded(&data.file) {
+                        phrase_diagnostics.push(InferenceDiagnostic::UnloadedTranslation {
+                            expr: args[phrase_idx],
+                            phrase: phrase.into(),
+                            file: data.file.as_str().into(),
+                        });
+                    }
+                    last_phrase = Some((args[phrase_idx], phrase, data.params));
+                    next_arg += data.params;
+                }
+                _ => next_arg += 1,
+            }
+        }
+        self.result.diagnostics.extend(diagnostics);
+        self.result.diagnostics.extend(phrase_diagnostics);
+        if !counted || next_arg == args.len() {
+            return;
+        }
+        match last_phrase {
+            Some((expr, phrase, expected)) => {
+                let actual = (expected + args.len()).saturating_sub(next_arg);
+                self.result.diagnostics.push(
+                    InferenceDiagnostic::IncorrectNumberOfPhraseArguments {
+                        expr,
+                        phrase: phrase.into(),
+                        expected,
+                        actual,
+                    },
+                );
+            }
+            None => {
+                self.result.diagnostics.push(
+                    InferenceDiagnostic::IncorrectNumberOfFormatArguments {
+                        expr: args[format_idx],
+                        expected: next_arg - format_idx - 1,
+                        actual: args.len() - format_idx - 1,
+                    },
+                );
+            }
+        }
+    }
+
+    /// Whether a translation file is loaded by one of the files of the project of the current
+    /// function. SourceMod loads `core.phrases` itself.
+    fn is_translation_loaded(&self, file: &str) -> bool {
+        if file == "core.phrases" {
+            return true;
+        }
+        let file_id = self.owner.file_id(self.db);
+        let file_ids = self
+            .db
+            .projet_subgraph(file_id)
+            .map(|subgraph| subgraph.file_ids().into_iter().collect::<Vec<_>>())
+            .unwrap_or_else(|| vec![file_id]);
+
+        file_ids
+            .into_iter()
+            .any(|file_id| self.db.loaded_translations(file_id).contains(file))
+    }
+
+    /// Report the name of an entry of a gamedata file passed to the current call, e.g the offset
+    /// of `GameConfGetOffset(gc, "GiveNamedItem")`, if none of the gamedata files loaded by the
+    /// project defines it. Nothing is reported unless all the loaded gamedata files were found.
+    fn check_gamedata_entry(&mut self, args: &[ExprId]) {
+        let Some(ValueNs::FunctionId(fn_ids)) = self.current_call().and_then(|it| it.id) else {
+            return;
+        };
+        let Some(fn_id) = fn_ids.first().map(|it| it.value) else {
+            return;
+        };
+        let methodmap = match fn_id.lookup(self.db).container {
+            ItemContainerId::MethodmapId(id) => {
+                Some(String::from(self.db.methodmap_data(id).name.clone()))
+            }
+            _ => None,
+        };
+        let function = String::from(self.db.function_data(fn_id).name.clone());
+        let Some((idx, section)) = GamedataSection::of_param(&function, methodmap.as_deref())
+        else {
+            return;
+        };
+        let Some(Expr::Literal(Literal::String(text))) = args.get(idx).map(|arg| &self.body[*arg])
+        else {
+            return;
+        };
+        let name = unquote(text);
+        let gamedata = self.db.gamedata();
+        let files = self.loaded_gamedata();
+        if files.is_empty()
+            || files.iter().any(|file| !gamedata.contains_file(file))
+            || files
+                .iter()
+                .any(|file| gamedata.contains(file, section, name))
+        {
+            return;
+        }
+        self.result
+            .diagnostics
+            .push(InferenceDiagnostic::UnknownGamedataEntry {
+                expr: args[idx],
+                name: name.into(),
+                section,
+                files,
+            });
+    }
+
+    /// Names of the gamedata files loaded by the files of the project of the current function.
+    fn loaded_gamedata(&self) -> Vec<SmolStr> {
+        let file_id = self.owner.file_id(self.db);
+        let file_ids = self
+            .db
+            .projet_subgraph(file_id)
+            .map(|subgraph| subgraph.file_ids().into_iter().collect::<Vec<_>>())
+            .unwrap_or_else(|| vec![file_id]);
+
+        file_ids
+            .into_iter()
+            .flat_map(|file_id| {
+                self.db
+                    .loaded_gamedata(file_id)
+                    .iter()
+                    .cloned()
+                    .collect_vec()
+            })
+            .sorted()
+            .dedup()
+            .collect()
+    }
+
+    /// Report the current call if the function it calls is deprecated.
+    fn check_deprecated_call(&mut self, expr: ExprId) {
+        let Some(data) = self.current_call_data() else {
+            return;
+        };
+        if data.deprecated {
+            self.result
+                .diagnostics
+                .push(InferenceDiagnostic::DeprecatedCall {
+                    expr,
+                    name: data.name(),
+                    message: data.deprecation_message.clone(),
+                });
+        }
+    }
 }
 
 impl InferenceContext<'_> {
@@ -425,18 +730,17 @@ impl InferenceContext<'_> {
             } => {
                 self.push_call(*target);
                 let ty = self.infer_method_call(expr, target, method_name);
-                for arg in args.iter() {
-                    self.infer_expr(arg);
-                }
+                let arg_types = args.iter().map(|arg| self.infer_expr(arg)).collect_vec();
+                self.check_format_arguments(args, &arg_types);
+                self.check_gamedata_entry(args);
+                self.check_deprecated_call(*expr);
                 self.pop_call();
                 ty
             }
             Expr::Call { callee, args } => {
                 self.push_call(*callee);
                 let ty = self.infer_expr(callee);
-                for arg in args.iter() {
-                    self.infer_expr(arg);
-                }
+                let arg_types = args.iter().map(|arg| self.infer_expr(arg)).collect_vec();
                 if let Some((min, max)) = self.current_call_params_numbers() {
                     if args.len() < min || args.len() > max.unwrap_or(usize::MAX) {
                         self.result.diagnostics.push(
@@ -453,6 +757,9 @@ impl InferenceContext<'_> {
                         );
                     }
                 }
+                self.check_format_arguments(args, &arg_types);
+                self.check_gamedata_entry(args);
+                self.check_deprecated_call(*callee);
                 self.pop_call();
                 ty
             }
@@ -698,3 +1005,27 @@ impl InferenceContext<'_> {
         None
     }
 }
+
+/// Text of a string literal, without its quotes.
+pub fn unquote(text: &str) -> &str {
+    let text = text.strip_prefix('"').unwrap_or(text);
+    text.strip_suffix('"').unwrap_or(text)
+}
+
+/// Conversion characters of the specifiers of a format string, e.g `['d', 't']` for
+/// `"%5d players, %t"`.
+pub fn format_specifiers(format: &str) -> impl Iterator<Item = char> + '_ {
+    let mut chars = format.chars();
+    std::iter::from_fn(move || loop {
+        if chars.next()? != '%' {
+            continue;
+        }
+        // Skip the flags, the width and the precision of the specifier.
+        let specifier = chars
+            .by_ref()
+            .find(|c| !(c.is_ascii_digit() || matches!(c, '-' | '.')))?;
+        if specifier != '%' {
+            return Some(specifier);
+        }
+    })
+}
@@ -135,6 +135,9 @@ impl ItemTree {
             match TSKind::from(child) {
                 TSKind::variable_declaration_statement => {
                     let type_ref = TypeRef::from_returntype_node(&child, "type", &source);
+                    let is_const = child
+                        .children(&mut child.walk())
+                        .any(|c| TSKind::from(c) == TSKind::variable_storage_class);
                     for sub_child in child.children(&mut child.walk()) {
                         if matches!(
                             TSKind::from(sub_child),
@@ -146,6 +149,7 @@ impl ItemTree {
                                         name_node.utf8_text(source.as_bytes()).unwrap(),
                                     ),
                                     visibility: RawVisibilityId::NONE,
+                                    is_const,
                                     type_ref: type_ref.clone(),
                                     ast_id: ast_id_map.ast_id_of(&sub_child),
                                 };
@@ -157,6 +161,9 @@ impl ItemTree {
                 }
                 TSKind::old_variable_declaration_statement
                 | TSKind::old_for_loop_variable_declaration_statement => {
+                    let is_const = child
+                        .children(&mut child.walk())
+                        .any(|c| TSKind::from(c) == TSKind::variable_storage_class);
                     for sub_child in child
                         .children(&mut child.walk())
                         .filter(|n| TSKind::from(n) == TSKind::old_variable_declaration)
@@ -166,6 +173,7 @@ impl ItemTree {
                             let res = Variable {
                                 name: Name::from(name_node.utf8_text(source.as_bytes()).unwrap()),
                                 visibility: RawVisibilityId::NONE,
+                                is_const,
                                 type_ref: type_ref.clone(),
                                 ast_id: ast_id_map.ast_id_of(&sub_child),
                             };
@@ -296,6 +304,8 @@ pub struct Macro {
 pub struct Variable {
     pub name: Name,
     pub visibility: RawVisibilityId,
+    /// Whether the variable is declared with the `const` storage class.
+    pub is_const: bool,
     pub type_ref: Option<TypeRef>,
     pub ast_id: AstId,
 }
@@ -316,6 +326,8 @@ pub struct Function {
     pub ret_type: Option<TypeRef>,
     pub ast_id: AstId,
     pub deprecated: bool,
+    /// Message of the `#pragma deprecated` preceding the function, if any.
+    pub deprecation_message: Option<SmolStr>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -1,9 +1,10 @@
 use std::sync::Arc;
 
 use base_db::Tree;
-use fxhash::FxHashSet;
+use fxhash::FxHashMap;
 use la_arena::{Idx, IdxRange, RawIdx};
 use lazy_static::lazy_static;
+use smol_str::SmolStr;
 use streaming_iterator::StreamingIterator;
 use syntax::TSKind;
 use tree_sitter::QueryCursor;
@@ -25,7 +26,8 @@ pub(super) struct Ctx<'db> {
     source_ast_id_map: Arc<AstIdMap>,
     source: Arc<str>,
     file_id: FileId,
-    deprecated: FxHashSet<usize>,
+    /// Rows of the `#pragma deprecated` directives, and their message.
+    deprecated: FxHashMap<usize, SmolStr>,
 }
 
 impl<'db> Ctx<'db> {
@@ -48,26 +50,7 @@ impl<'db> Ctx<'db> {
         let tree = self.db.parse(self.file_id);
         self.collect_deprecated(&tree);
 
-        let root_node = tree.root_node();
-        for child in root_node.children(&mut root_node.walk()) {
-            match TSKind::from(child) {
-                TSKind::function_definition | TSKind::function_declaration => {
-                    self.lower_function(&child)
-                }
-                TSKind::r#enum => self.lower_enum(&child),
-                TSKind::struct_declaration => self.lower_struct_declaration(&child),
-                TSKind::global_variable_declaration => self.lower_global_variable(&child),
-                TSKind::old_global_variable_declaration => self.lower_old_global_variable(&child),
-                TSKind::enum_struct => self.lower_enum_struct(&child),
-                TSKind::methodmap => self.lower_methodmap(&child),
-                TSKind::typedef => self.lower_typedef(&child),
-                TSKind::typeset => self.lower_typeset(&child),
-                TSKind::functag => self.lower_functag(&child),
-                TSKind::funcenum => self.lower_funcenum(&child),
-                TSKind::r#struct => self.lower_struct(&child),
-                _ => (),
-            }
-        }
+        self.lower_items(&tree.root_node());
 
         // query for all macro definitions in the file
         lazy_static! {
@@ -106,6 +89,33 @@ impl<'db> Ctx<'db> {
         }
     }
 
+    /// Lower the items declared in a node.
+    ///
+    /// The items the parser gave up on while recovering from a syntax error are wrapped in an
+    /// error node, they are lowered as if they were declared in its parent.
+    fn lower_items(&mut self, node: &tree_sitter::Node) {
+        for child in node.children(&mut node.walk()) {
+            match TSKind::from(child) {
+                TSKind::function_definition | TSKind::function_declaration => {
+                    self.lower_function(&child)
+                }
+                TSKind::r#enum => self.lower_enum(&child),
+                TSKind::struct_declaration => self.lower_struct_declaration(&child),
+                TSKind::global_variable_declaration => self.lower_global_variable(&child),
+                TSKind::old_global_variable_declaration => self.lower_old_global_variable(&child),
+                TSKind::enum_struct => self.lower_enum_struct(&child),
+                TSKind::methodmap => self.lower_methodmap(&child),
+                TSKind::typedef => self.lower_typedef(&child),
+                TSKind::typeset => self.lower_typeset(&child),
+                TSKind::functag => self.lower_functag(&child),
+                TSKind::funcenum => self.lower_funcenum(&child),
+                TSKind::r#struct => self.lower_struct(&child),
+                TSKind::ERROR => self.lower_items(&child),
+                _ => (),
+            }
+        }
+    }
+
     fn collect_deprecated(&mut self, tree: &Tree) {
         // query for all pragmas
         lazy_static! {
@@ -122,16 +132,31 @@ impl<'db> Ctx<'db> {
                 let Ok(pragma) = c.node.utf8_text(self.source.as_bytes()) else {
                     return;
                 };
-                if pragma.starts_with("#pragma deprecated") {
-                    self.deprecated.insert(c.node.range().start_point.row);
+                if let Some(message) = pragma.strip_prefix("#pragma deprecated") {
+                    self.deprecated
+                        .insert(c.node.range().start_point.row, message.trim().into());
                 }
             }
         }
     }
 
+    /// Whether the node is preceded by a `#pragma deprecated` directive, or by a doc comment with
+    /// a `@deprecated` tag.
     fn is_deprecated(&self, node: &tree_sitter::Node) -> bool {
         self.deprecated
-            .contains(&node.range().start_point.row.saturating_sub(1))
+            .contains_key(&node.range().start_point.row.saturating_sub(1))
+            || node
+                .prev_sibling()
+                .filter(|prev| TSKind::from(prev) == TSKind::comment)
+                .and_then(|prev| prev.utf8_text(self.source.as_bytes()).ok())
+                .is_some_and(|doc| doc.contains("@deprecated"))
+    }
+
+    fn deprecation_message(&self, node: &tree_sitter::Node) -> Option<SmolStr> {
+        self.deprecated
+            .get(&node.range().start_point.row.saturating_sub(1))
+            .filter(|message| !message.is_empty())
+            .cloned()
     }
 
     fn lower_struct_declaration(&mut self, node: &tree_sitter::Node) {
@@ -141,6 +166,7 @@ impl<'db> Ctx<'db> {
             let res = Variable {
                 name: Name::from(name_node.utf8_text(self.source.as_bytes()).unwrap()),
                 visibility,
+                is_const: false,
                 type_ref: type_ref.clone(),
                 ast_id: self.source_ast_id_map.ast_id_of(node),
             };
@@ -151,6 +177,7 @@ impl<'db> Ctx<'db> {
 
     fn lower_global_variable(&mut self, node: &tree_sitter::Node) {
         let visibility = RawVisibilityId::from_node(node);
+        let is_const = node.child_by_field_name("storage_class").is_some();
         let type_ref = TypeRef::from_returntype_node(node, "type", &self.source);
         for child in node.children(&mut node.walk()) {
             if matches!(
@@ -161,6 +188,7 @@ impl<'db> Ctx<'db> {
                     let res = Variable {
                         name: Name::from(name_node.utf8_text(self.source.as_bytes()).unwrap()),
                         visibility,
+                        is_const,
                         type_ref: type_ref.clone(),
                         ast_id: self.source_ast_id_map.ast_id_of(&child),
                     };
@@ -173,6 +201,7 @@ impl<'db> Ctx<'db> {
 
     fn lower_old_global_variable(&mut self, node: &tree_sitter::Node) {
         let visibility = RawVisibilityId::from_node(node);
+        let is_const = node.child_by_field_name("storage_class").is_some();
         for child in node
             .children(&mut node.walk())
             .filter(|n| TSKind::from(n) == TSKind::old_variable_declaration)
@@ -182,6 +211,7 @@ impl<'db> Ctx<'db> {
                 let res = Variable {
                     name: Name::from(name_node.utf8_text(self.source.as_bytes()).unwrap()),
                     visibility,
+                    is_const,
                     type_ref: type_ref.clone(),
                     ast_id: self.source_ast_id_map.ast_id_of(&child),
                 };
@@ -292,6 +322,7 @@ impl<'db> Ctx<'db> {
             special,
             ast_id: self.source_ast_id_map.ast_id_of(node),
             deprecated: self.is_deprecated(node),
+            deprecation_message: self.deprecation_message(node),
         };
 
         self.tree.data_mut().functions.alloc(res).into()
@@ -599,6 +630,7 @@ impl<'db> Ctx<'db> {
                     special: None,
                     ast_id: self.source_ast_id_map.ast_id_of(parent),
                     deprecated: self.is_deprecated(node),
+                    deprecation_message: self.deprecation_message(node),
                 };
                 self.tree.data_mut().functions.alloc(res);
             }
@@ -626,6 +658,7 @@ impl<'db> Ctx<'db> {
                     special: None,
                     ast_id: self.source_ast_id_map.ast_id_of(parent), // We care about the method itself, not the getter/setter in the grammar.
                     deprecated: self.is_deprecated(node),
+                    deprecation_message: self.deprecation_message(node),
                 };
                 self.tree.data_mut().functions.alloc(res);
             }
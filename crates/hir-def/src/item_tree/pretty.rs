@@ -79,6 +79,7 @@ impl<'a> Printer<'a> {
         let Variable {
             name,
             visibility,
+            is_const,
             type_ref,
             ast_id,
         } = &self.tree[*idx];
@@ -88,6 +89,9 @@ impl<'a> Printer<'a> {
             self.push(&visibility.to_string());
             self.push(" ");
         }
+        if *is_const {
+            self.push("const ");
+        }
         if let Some(type_ref) = type_ref {
             self.push(&type_ref.to_string());
             self.push(" ");
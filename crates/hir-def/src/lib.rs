@@ -15,6 +15,7 @@ use vfs::FileId;
 mod ast_id_map;
 pub mod body;
 pub mod child_by_source;
+mod convar;
 mod data;
 pub mod db;
 mod diagnostics;
@@ -26,6 +27,7 @@ pub mod resolver;
 pub mod src;
 
 pub use ast_id_map::NodePtr;
+pub use convar::ConVar;
 pub use data::{MethodmapExtension, PropertyItem};
 pub use db::resolve_include_node;
 pub use db::DefDatabase;
@@ -33,7 +35,10 @@ pub use db::{BlockDefMapQuery, BlockItemTreeQuery, BodyQuery, FileDefMapQuery, F
 pub use diagnostics::DefDiagnostic;
 pub use hir::type_ref::type_string_from_node;
 pub use hir::ExprId;
-pub use infer::{AttributeId, ConstructorDiagnosticKind, InferenceDiagnostic, InferenceResult};
+pub use infer::{
+    format_specifiers, unquote, AttributeId, ConstructorDiagnosticKind, FormatArgKind,
+    InferenceDiagnostic, InferenceResult,
+};
 pub use item_tree::{
     print_item_tree, FileItem, FunctionKind, Name, RawVisibilityId, SpecialMethod,
 };
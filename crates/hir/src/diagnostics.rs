@@ -4,8 +4,11 @@
 //! This probably isn't the best way to do this -- ideally, diagnostics should
 //! be expressed in terms of hir types themselves.
 
+use base_db::GamedataSection;
 use hir_def::{InFile, Name, NodePtr};
+use preprocessor::{InactiveDirectiveReason, IncludeLimitKind};
 use sourcepawn_lexer::TextRange;
+use vfs::FileId;
 
 macro_rules! diagnostics {
     ($($diag:ident,)*) => {
@@ -35,7 +38,24 @@ diagnostics![
     PreprocessorEvaluationError,
     UnresolvedMacro,
     InactiveCode,
+    InactiveDirective,
     InvalidUseOfThis,
+    UnknownDirective,
+    CircularInclude,
+    IncludeLimitExceeded,
+    DeprecatedCall,
+    UnusedMacro,
+    UnusedInclude,
+    MacroArgumentCountMismatch,
+    UseAfterUndef,
+    AssertionFailed,
+    IncorrectNumberOfPhraseArguments,
+    UnknownPhrase,
+    UnloadedTranslation,
+    UnknownGamedataEntry,
+    IncorrectNumberOfFormatArguments,
+    MismatchedFormatArgument,
+    UnbalancedCondition,
 ];
 
 #[derive(Debug)]
@@ -93,6 +113,72 @@ pub struct IncorrectNumberOfArguments {
     pub actual: usize,
 }
 
+#[derive(Debug)]
+pub struct IncorrectNumberOfPhraseArguments {
+    pub expr: InFile<NodePtr>,
+    pub phrase: String,
+    pub expected: usize,
+    pub actual: usize,
+}
+
+#[derive(Debug)]
+pub struct UnknownPhrase {
+    pub expr: InFile<NodePtr>,
+    pub phrase: String,
+}
+
+#[derive(Debug)]
+pub struct UnloadedTranslation {
+    pub expr: InFile<NodePtr>,
+    pub phrase: String,
+    /// Name of the translation file of the phrase, e.g `common.phrases`.
+    pub file: String,
+}
+
+#[derive(Debug)]
+pub struct UnknownGamedataEntry {
+    pub expr: InFile<NodePtr>,
+    pub name: String,
+    pub section: GamedataSection,
+    /// Names of the gamedata files loaded by the project, e.g `sdktools.games`.
+    pub files: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct IncorrectNumberOfFormatArguments {
+    pub expr: InFile<NodePtr>,
+    pub expected: usize,
+    pub actual: usize,
+}
+
+#[derive(Debug)]
+pub struct MismatchedFormatArgument {
+    pub expr: InFile<NodePtr>,
+    pub specifier: char,
+    /// Kind of value the specifier expects, e.g `an integer`.
+    pub expected: String,
+    pub actual: String,
+}
+
+#[derive(Debug)]
+pub struct AssertionFailed {
+    pub range: TextRange,
+    pub expression: String,
+}
+
+#[derive(Debug)]
+pub struct UnbalancedCondition {
+    pub range: TextRange,
+    pub message: String,
+}
+
+#[derive(Debug)]
+pub struct UseAfterUndef {
+    pub range: TextRange,
+    pub name: String,
+    pub undef_range: TextRange,
+}
+
 #[derive(Debug)]
 pub struct UnresolvedInherit {
     pub expr: InFile<NodePtr>,
@@ -112,7 +198,67 @@ pub struct UnresolvedMacro {
     pub name: String,
 }
 
+#[derive(Debug)]
+pub struct MacroArgumentCountMismatch {
+    pub range: TextRange,
+    pub name: String,
+    pub expected: usize,
+    pub actual: usize,
+}
+
 #[derive(Debug)]
 pub struct InactiveCode {
     pub range: TextRange,
 }
+
+#[derive(Debug)]
+pub struct InactiveDirective {
+    pub range: TextRange,
+    pub reason: InactiveDirectiveReason,
+}
+
+#[derive(Debug)]
+pub struct UnknownDirective {
+    pub range: TextRange,
+    pub name: String,
+    pub suggestion: Option<&'static str>,
+}
+
+#[derive(Debug)]
+pub struct CircularInclude {
+    pub range: TextRange,
+    pub path: String,
+    /// Files of the cycle, starting and ending with the included file.
+    pub cycle: Vec<FileId>,
+}
+
+#[derive(Debug)]
+pub struct IncludeLimitExceeded {
+    pub range: TextRange,
+    pub path: String,
+    pub kind: IncludeLimitKind,
+    /// Files of the include chain, starting with the root of the project and ending with the
+    /// included file.
+    pub chain: Vec<FileId>,
+}
+
+#[derive(Debug)]
+pub struct DeprecatedCall {
+    pub expr: InFile<NodePtr>,
+    pub name: Name,
+    /// Message of the `#pragma deprecated` of the function, if any.
+    pub message: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct UnusedMacro {
+    pub define: InFile<NodePtr>,
+    pub name: Name,
+}
+
+#[derive(Debug)]
+pub struct UnusedInclude {
+    pub range: TextRange,
+    /// Text of the include directive.
+    pub path: String,
+}
@@ -3,6 +3,7 @@ use std::hash::Hash;
 
 use base_db::Tree;
 use db::HirDatabase;
+use fxhash::FxHashSet;
 use hir_def::{
     resolver::{HasResolver, ValueNs},
     type_string_from_node, DefDiagnostic, DefWithBodyId, EnumId, EnumStructId, ExprId, FuncenumId,
@@ -269,6 +270,24 @@ impl File {
                 .into(),
             )
         }));
+        acc.extend(errors.assertion_failed_errors.iter().map(|it| {
+            AnyDiagnostic::AssertionFailed(
+                AssertionFailed {
+                    range: *it.range(),
+                    expression: it.text().to_owned(),
+                }
+                .into(),
+            )
+        }));
+        acc.extend(errors.unbalanced_condition_errors.iter().map(|it| {
+            AnyDiagnostic::UnbalancedCondition(
+                UnbalancedCondition {
+                    range: *it.range(),
+                    message: it.text().to_owned(),
+                }
+                .into(),
+            )
+        }));
         acc.extend(errors.unresolved_include_errors.iter().map(|it| {
             AnyDiagnostic::UnresolvedInclude(
                 UnresolvedInclude {
@@ -287,16 +306,244 @@ impl File {
                 .into(),
             )
         }));
+        acc.extend(errors.macro_argument_count_errors.iter().map(|it| {
+            AnyDiagnostic::MacroArgumentCountMismatch(
+                MacroArgumentCountMismatch {
+                    range: *it.range(),
+                    name: it.text().to_owned(),
+                    expected: it.expected(),
+                    actual: it.actual(),
+                }
+                .into(),
+            )
+        }));
+        acc.extend(errors.use_after_undef_errors.iter().map(|it| {
+            AnyDiagnostic::UseAfterUndef(
+                UseAfterUndef {
+                    range: *it.range(),
+                    name: it.text().to_owned(),
+                    undef_range: *it.undef_range(),
+                }
+                .into(),
+            )
+        }));
+        acc.extend(errors.circular_include_errors.iter().map(|it| {
+            AnyDiagnostic::CircularInclude(
+                CircularInclude {
+                    range: *it.range(),
+                    path: it.text().to_owned(),
+                    cycle: it.cycle().to_vec(),
+                }
+                .into(),
+            )
+        }));
+        acc.extend(errors.include_limit_errors.iter().map(|it| {
+            AnyDiagnostic::IncludeLimitExceeded(
+                IncludeLimitExceeded {
+                    range: *it.range(),
+                    path: it.text().to_owned(),
+                    kind: it.kind(),
+                    chain: it.chain().to_vec(),
+                }
+                .into(),
+            )
+        }));
+        acc.extend(errors.unknown_directive_errors.iter().map(|it| {
+            AnyDiagnostic::UnknownDirective(
+                UnknownDirective {
+                    range: *it.range(),
+                    name: it.text().to_owned(),
+                    suggestion: it.suggestion(),
+                }
+                .into(),
+            )
+        }));
+        acc.extend(errors.inactive_directive_errors.iter().map(|it| {
+            AnyDiagnostic::InactiveDirective(
+                InactiveDirective {
+                    range: *it.range(),
+                    reason: it.reason().clone(),
+                }
+                .into(),
+            )
+        }));
         acc.extend(
             result
                 .inactive_ranges()
                 .iter()
                 .map(|range| AnyDiagnostic::InactiveCode(InactiveCode { range: *range }.into())),
         );
-        self.declarations(db)
+        self.unused_includes_diagnostics(db, acc);
+        let declarations = self.declarations(db);
+        self.unused_macros_diagnostics(db, &declarations, acc);
+        declarations
             .iter()
             .for_each(|it| acc.extend(it.diagnostics(db)));
     }
+
+    /// Report the includes of the file which do not provide any of the symbols or macros it
+    /// references.
+    ///
+    /// An include also counts as used when one of the macros it provides is expanded by another
+    /// file of the project, as the file may rely on it being included first.
+    fn unused_includes_diagnostics(self, db: &dyn HirDatabase, acc: &mut Vec<AnyDiagnostic>) {
+        let file_includes = db.file_includes(self.id).0;
+        // The implicit includes do not have a range, and cannot be removed.
+        let includes = file_includes
+            .iter()
+            .filter_map(|include| Some((include.file_id(), include.range()?)))
+            .collect_vec();
+        if includes.is_empty() {
+            return;
+        }
+
+        let text = db.file_text(self.id);
+        let result = db.preprocess_file(self.id);
+        let resolver = self.id.resolver(db.upcast());
+        let mut used_files: FxHashSet<FileId> = result
+            .source_map()
+            .expanded_symbols()
+            .iter()
+            .map(|symbol| symbol.file_id())
+            .collect();
+        for symbol in sourcepawn_lexer::SourcepawnLexer::new(&text) {
+            if symbol.token_kind != sourcepawn_lexer::TokenKind::Identifier
+                || result
+                    .inactive_ranges()
+                    .iter()
+                    .any(|range| range.contains_range(symbol.range))
+            {
+                continue;
+            }
+            let provider = match resolver.resolve_ident(&symbol.text()) {
+                None | Some(ValueNs::LocalId(_)) => continue,
+                Some(ValueNs::FunctionId(ids)) => {
+                    used_files.extend(ids.iter().map(|id| id.file_id));
+                    continue;
+                }
+                Some(ValueNs::GlobalId(it)) => it.file_id,
+                Some(ValueNs::MacroId(it)) => it.file_id,
+                Some(ValueNs::EnumStructId(it)) => it.file_id,
+                Some(ValueNs::MethodmapId(it)) => it.file_id,
+                Some(ValueNs::EnumId(it)) => it.file_id,
+                Some(ValueNs::VariantId(it)) => it.file_id,
+                Some(ValueNs::TypedefId(it)) => it.file_id,
+                Some(ValueNs::TypesetId(it)) => it.file_id,
+                Some(ValueNs::FunctagId(it)) => it.file_id,
+                Some(ValueNs::FuncenumId(it)) => it.file_id,
+                Some(ValueNs::StructId(it)) => it.file_id,
+            };
+            used_files.insert(provider);
+        }
+
+        // Macros expanded by the other files of the project, with the file expanding them.
+        let project_files = db
+            .projet_subgraph(self.id)
+            .map(|subgraph| subgraph.file_ids())
+            .unwrap_or_default();
+        let mut expanded_macros = Vec::new();
+        for file_id in project_files {
+            if file_id == self.id {
+                continue;
+            }
+            expanded_macros.extend(
+                db.preprocess_file(file_id)
+                    .source_map()
+                    .expanded_symbols()
+                    .iter()
+                    .map(|symbol| (file_id, symbol.file_id())),
+            );
+        }
+
+        for (file_id, range) in includes {
+            let provided_files = included_files(db, file_id);
+            if provided_files.iter().any(|it| used_files.contains(it))
+                || expanded_macros.iter().any(|(user, provider)| {
+                    provided_files.contains(provider) && !provided_files.contains(user)
+                })
+            {
+                continue;
+            }
+            acc.push(
+                UnusedInclude {
+                    range,
+                    path: text[range].to_string(),
+                }
+                .into(),
+            );
+        }
+    }
+
+    /// Report the macros of the file which are never expanded nor checked with `defined` in
+    /// any file of the project. A module shared between plugins is checked against all of them.
+    fn unused_macros_diagnostics(
+        self,
+        db: &dyn HirDatabase,
+        declarations: &[FileDef],
+        acc: &mut Vec<AnyDiagnostic>,
+    ) {
+        let macros = declarations
+            .iter()
+            .filter_map(|it| match it {
+                FileDef::Macro(it) => Some(*it),
+                _ => None,
+            })
+            .collect_vec();
+        if macros.is_empty() {
+            return;
+        }
+        let mut file_ids: FxHashSet<FileId> = db
+            .graph()
+            .find_subgraphs()
+            .iter()
+            .filter(|subgraph| subgraph.contains_file(self.id))
+            .flat_map(|subgraph| subgraph.file_ids())
+            .collect();
+        file_ids.insert(self.id);
+        let mut used_macros = FxHashSet::default();
+        for file_id in file_ids {
+            used_macros.extend(db.preprocess_file(file_id).used_macros().iter().cloned());
+        }
+
+        let tree = db.parse(self.id);
+        for macro_ in macros {
+            let name = macro_.name(db);
+            if used_macros.contains(name.to_string().as_str()) {
+                continue;
+            }
+            let Some(source) = macro_.source(db, &tree) else {
+                continue;
+            };
+            acc.push(
+                UnusedMacro {
+                    define: InFile::new(source.file_id, NodePtr::from(&source.value)),
+                    name,
+                }
+                .into(),
+            );
+        }
+    }
+}
+
+/// Files provided by an include of `file_id`, i.e `file_id` and the files it includes,
+/// directly or through other files.
+fn included_files(db: &dyn HirDatabase, file_id: FileId) -> FxHashSet<FileId> {
+    let mut res = FxHashSet::default();
+    let mut stack = vec![file_id];
+    while let Some(file_id) = stack.pop() {
+        if !res.insert(file_id) {
+            continue;
+        }
+        stack.extend(
+            db.file_includes(file_id)
+                .0
+                .iter()
+                .filter(|include| include.range().is_some())
+                .map(|include| include.file_id()),
+        );
+    }
+
+    res
 }
 
 impl<'tree> File {
@@ -507,12 +754,93 @@ impl DefWithBody {
                     }
                     .into(),
                 ),
+                InferenceDiagnostic::IncorrectNumberOfPhraseArguments {
+                    expr,
+                    phrase,
+                    expected,
+                    actual,
+                } => acc.push(
+                    IncorrectNumberOfPhraseArguments {
+                        expr: expr_syntax(*expr),
+                        phrase: phrase.to_string(),
+                        expected: *expected,
+                        actual: *actual,
+                    }
+                    .into(),
+                ),
+                InferenceDiagnostic::UnknownPhrase { expr, phrase } => acc.push(
+                    UnknownPhrase {
+                        expr: expr_syntax(*expr),
+                        phrase: phrase.to_string(),
+                    }
+                    .into(),
+                ),
+                InferenceDiagnostic::UnloadedTranslation { expr, phrase, file } => acc.push(
+                    UnloadedTranslation {
+                        expr: expr_syntax(*expr),
+                        phrase: phrase.to_string(),
+                        file: file.to_string(),
+                    }
+                    .into(),
+                ),
+                InferenceDiagnostic::UnknownGamedataEntry {
+                    expr,
+                    name,
+                    section,
+                    files,
+                } => acc.push(
+                    UnknownGamedataEntry {
+                        expr: expr_syntax(*expr),
+                        name: name.to_string(),
+                        section: *section,
+                        files: files.iter().map(ToString::to_string).collect(),
+                    }
+                    .into(),
+                ),
+                InferenceDiagnostic::IncorrectNumberOfFormatArguments {
+                    expr,
+                    expected,
+                    actual,
+                } => acc.push(
+                    IncorrectNumberOfFormatArguments {
+                        expr: expr_syntax(*expr),
+                        expected: *expected,
+                        actual: *actual,
+                    }
+                    .into(),
+                ),
+                InferenceDiagnostic::MismatchedFormatArgument {
+                    expr,
+                    specifier,
+                    expected,
+                    actual,
+                } => acc.push(
+                    MismatchedFormatArgument {
+                        expr: expr_syntax(*expr),
+                        specifier: *specifier,
+                        expected: expected.to_string(),
+                        actual: actual.to_string(),
+                    }
+                    .into(),
+                ),
                 InferenceDiagnostic::InvalidUseOfThis { expr } => acc.push(
                     InvalidUseOfThis {
                         expr: expr_syntax(*expr),
                     }
                     .into(),
                 ),
+                InferenceDiagnostic::DeprecatedCall {
+                    expr,
+                    name,
+                    message,
+                } => acc.push(
+                    DeprecatedCall {
+                        expr: expr_syntax(*expr),
+                        name: name.clone(),
+                        message: message.as_ref().map(ToString::to_string),
+                    }
+                    .into(),
+                ),
             }
         }
     }
@@ -635,6 +963,14 @@ impl Function {
         buf.to_string().into()
     }
 
+    /// Returns the methodmap the function is a member of, if any.
+    pub fn parent_methodmap(self, db: &dyn HirDatabase) -> Option<Methodmap> {
+        match self.id.lookup(db.upcast()).container {
+            ItemContainerId::MethodmapId(it) => Some(it.into()),
+            _ => None,
+        }
+    }
+
     pub fn kind(self, db: &dyn HirDatabase) -> FunctionType {
         let item = self.id.lookup(db.upcast());
         match item.container {
@@ -749,6 +1085,12 @@ impl Macro {
         db.macro_data(self.id).name.clone()
     }
 
+    /// Index of the macro in the file it is defined in, as stored by the preprocessor.
+    pub fn idx(self, db: &dyn HirDatabase) -> Option<u32> {
+        let file_id = self.id.lookup(db.upcast()).id.file_id();
+        db.file_def_map(file_id).macro_idx(self.id)
+    }
+
     pub fn render(self, db: &dyn HirDatabase) -> Option<String> {
         let file_id = self.id.lookup(db.upcast()).id.file_id();
         let tree = db.parse(file_id);
@@ -782,6 +1124,18 @@ impl Macro {
         buf.trim().to_string().into()
     }
 
+    /// Returns the raw doc comment (`/** ... */`) above the macro, as captured by the preprocessor.
+    pub fn docs(self, db: &dyn HirDatabase) -> Option<String> {
+        let file_id = self.id.lookup(db.upcast()).id.file_id();
+        let idx = self.idx(db)?;
+        db.preprocess_file(file_id)
+            .macros()
+            .values()
+            .find(|it| it.file_id() == file_id && it.idx() == idx)?
+            .docs()
+            .map(|it| it.to_string())
+    }
+
     /// Returns whether the macro is deprecated.
     ///
     /// This method is "fast" as it does not do a lookup of the node in the tree.
@@ -856,6 +1210,26 @@ impl Methodmap {
         res
     }
 
+    /// Returns whether the methodmap is a `Handle` or inherits from it, i.e whether its
+    /// instances have to be freed.
+    pub fn is_handle(self, db: &dyn HirDatabase) -> bool {
+        let mut visited = FxHashSet::default();
+        let mut id = Some(self.id);
+        while let Some(current) = id {
+            if !visited.insert(current) {
+                // Cyclic inheritance.
+                return false;
+            }
+            let data = db.methodmap_data(current);
+            if data.name.to_string() == "Handle" {
+                return true;
+            }
+            id = data.inherits;
+        }
+
+        false
+    }
+
     /// Returns whether the methodmap is deprecated.
     ///
     /// This method is "fast" as it does not do a lookup of the node in the tree.
@@ -889,6 +1263,10 @@ impl Property {
         buf.into()
     }
 
+    pub fn type_ref(self, db: &dyn HirDatabase) -> String {
+        db.property_data(self.id).type_ref.to_string()
+    }
+
     pub fn type_(self, db: &dyn HirDatabase) -> Option<DefResolution> {
         let ty = db.property_data(self.id).type_ref.clone();
         let ty_str = ty.type_as_string();
@@ -1600,6 +1978,10 @@ impl Global {
         buf.into()
     }
 
+    pub fn type_ref(self, db: &dyn HirDatabase) -> Option<String> {
+        db.global_data(self.id).type_ref().map(|it| it.to_string())
+    }
+
     pub fn type_(self, db: &dyn HirDatabase) -> Option<DefResolution> {
         let ty = db.global_data(self.id).type_ref().cloned()?;
         let ty_str = ty.type_as_string();
@@ -1617,6 +1999,15 @@ impl Global {
 
         res
     }
+
+    /// Returns the number of dimensions of the global, e.g `2` for `char buffers[4][64]`.
+    pub fn dimensions(self, db: &dyn HirDatabase) -> usize {
+        let file_id = self.id.lookup(db.upcast()).id.file_id();
+        let tree = db.parse(file_id);
+        self.source(db, &tree)
+            .map(|it| declaration_dimensions(it.value))
+            .unwrap_or_default()
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -1744,6 +2135,15 @@ impl Field {
         format!("{} {}::{};", type_str, parent_data.name, self.name(db)).into()
     }
 
+    pub fn type_ref(self, db: &dyn HirDatabase) -> String {
+        let parent_data = db.enum_struct_data(self.parent.id);
+        parent_data
+            .field(self.id)
+            .expect("expected a field to have a type")
+            .type_ref
+            .to_string()
+    }
+
     pub fn type_(self, db: &dyn HirDatabase) -> Option<DefResolution> {
         let parent_data = db.enum_struct_data(self.parent.id);
         let ty_str = parent_data
@@ -1928,6 +2328,33 @@ impl<'tree> Local {
         }
     }
 
+    pub fn type_ref(self, db: &dyn HirDatabase) -> Option<String> {
+        let file_id = self.parent.file_id(db.upcast());
+        let tree = db.parse(file_id);
+        let source = db.preprocessed_text(file_id);
+        let node = self.source(db, &tree).map(|s| s.source.value)?;
+        let decl = if TSKind::from(node) == TSKind::parameter_declaration {
+            node
+        } else {
+            node.parent()?
+        };
+        let type_node = decl.child_by_field_name("type")?;
+        let is_dimension = |child: &Node| {
+            matches!(
+                TSKind::from(child),
+                TSKind::dimension | TSKind::fixed_dimension
+            )
+        };
+        let mut dimensions = node.children(&mut node.walk()).filter(is_dimension).count();
+        if decl != node {
+            dimensions += decl.children(&mut decl.walk()).filter(is_dimension).count();
+        }
+        let mut res = type_string_from_node(&type_node, &source);
+        res.push_str(&"[]".repeat(dimensions));
+
+        Some(res)
+    }
+
     pub fn type_(self, db: &dyn HirDatabase) -> Option<DefResolution> {
         let file_id = self.parent.file_id(db.upcast());
         let tree = db.parse(file_id);
@@ -1954,6 +2381,46 @@ impl<'tree> Local {
 
         res
     }
+
+    /// Returns the number of dimensions of the local, e.g `2` for `char buffers[4][64]`.
+    pub fn dimensions(self, db: &dyn HirDatabase) -> usize {
+        let file_id = self.parent.file_id(db.upcast());
+        let tree = db.parse(file_id);
+        self.source(db, &tree)
+            .map(|it| declaration_dimensions(it.source.value))
+            .unwrap_or_default()
+    }
+}
+
+/// Number of dimensions of a variable, given its declaration node.
+///
+/// The dimensions can be on the type (`char[] buffer`) or on the name (`char buffer[64]`).
+fn declaration_dimensions(node: tree_sitter::Node) -> usize {
+    let count = |node: tree_sitter::Node| {
+        node.children(&mut node.walk())
+            .filter(|child| {
+                matches!(
+                    TSKind::from(child),
+                    TSKind::dimension | TSKind::fixed_dimension
+                )
+            })
+            .count()
+    };
+    let mut res = count(node);
+    match TSKind::from(node) {
+        TSKind::variable_declaration | TSKind::dynamic_array_declaration => {
+            res += node.parent().map(count).unwrap_or_default();
+        }
+        TSKind::parameter_declaration => {
+            res += node
+                .child_by_field_name("type")
+                .map(count)
+                .unwrap_or_default();
+        }
+        _ => (),
+    }
+
+    res
 }
 
 pub struct LocalSource<'tree> {
@@ -13,7 +13,7 @@ use lazy_static::lazy_static;
 use log::warn;
 use preprocessor::ExpandedSymbolOffset;
 use smol_str::ToSmolStr;
-use sourcepawn_lexer::{SourcepawnLexer, TextSize, TokenKind};
+use sourcepawn_lexer::{PreprocDir, SourcepawnLexer, TextSize, TokenKind};
 use streaming_iterator::StreamingIterator;
 use syntax::{utils::ts_range_to_text_range, TSKind};
 use tree_sitter::QueryCursor;
@@ -23,8 +23,8 @@ use crate::{
     db::HirDatabase,
     source_analyzer::SourceAnalyzer,
     source_to_def::{SourceToDefCache, SourceToDefCtx},
-    Attribute, DefResolution, Enum, EnumStruct, Field, File, Function, Global, Macro, Methodmap,
-    Property, Struct, Variant,
+    Attribute, DefResolution, Enum, EnumStruct, Field, File, Function, Global, HasSource, Macro,
+    Methodmap, Property, Struct, Variant,
 };
 
 /// Primary API to get semantic information, like types, from syntax trees.
@@ -134,7 +134,8 @@ impl<DB: HirDatabase> Semantics<'_, DB> {
                     | TSKind::old_for_loop_variable_declaration_statement => {
                         self.local_to_def(src).map(|it| it.into())
                     }
-                    _ => unreachable!(),
+                    // The declaration was recovered from a syntax error.
+                    _ => None,
                 }
             }
             TSKind::preproc_macro | TSKind::preproc_define => {
@@ -174,6 +175,14 @@ impl<DB: HirDatabase> Semantics<'_, DB> {
         }
     }
 
+    /// Find the [`Macro`] defined in `file_id` with the index `idx` given by the preprocessor.
+    pub fn macro_from_idx(&self, file_id: FileId, idx: u32) -> Option<Macro> {
+        self.db
+            .file_def_map(file_id)
+            .get_macro(&idx)
+            .map(Macro::from)
+    }
+
     /// Try to find the definition of a macro at the given [`user position`](FilePosition).
     pub fn find_macro_def(
         &self,
@@ -187,11 +196,7 @@ impl<DB: HirDatabase> Semantics<'_, DB> {
 
         (
             offset,
-            self.db
-                .file_def_map(file_id)
-                .get_macro(&idx)
-                .map(Macro::from)
-                .map(DefResolution::from)?,
+            self.macro_from_idx(file_id, idx).map(DefResolution::from)?,
         )
             .into()
     }
@@ -692,6 +697,9 @@ impl<DB: HirDatabase> Semantics<'_, DB> {
             .descendant_for_byte_range(fpos.raw_offset_usize(), fpos.raw_offset_usize())?;
         let src_text = node.utf8_text(source.as_bytes()).ok()?;
         let def = self.find_def(fpos.file_id, &node)?;
+        if let DefResolution::Macro(macro_) = def {
+            return Some((def, self.macro_references(macro_, fpos.file_id)?));
+        }
         let mut res = Vec::new();
         let file_ids = if let DefResolution::Local(_) = def {
             // Only search in the current file for local definitions
@@ -740,26 +748,83 @@ impl<DB: HirDatabase> Semantics<'_, DB> {
     /// A tuple containing the definition of the macro and a list of [`user seen FileRanges`](FileRange).
     fn find_macro_references(&self, fpos: FilePosition) -> Option<(DefResolution, Vec<FileRange>)> {
         let (_, def) = self.find_macro_def(&fpos)?;
-        let name = def.name(self.db).map(|it| it.to_smolstr())?;
-        let graph = self.db.projet_subgraph(fpos.file_id)?;
-        let mut res = Vec::new();
+        let DefResolution::Macro(macro_) = def else {
+            return None;
+        };
+
+        Some((def, self.macro_references(macro_, fpos.file_id)?))
+    }
+
+    /// Find the references to a macro in the project of a file.
+    ///
+    /// The references are the name of the `#define`, the symbols the macro was expanded from,
+    /// including in `#if` conditions, and the occurrences of its name in other preprocessor
+    /// directives, e.g `#undef` or the body of other macros.
+    ///
+    /// # Arguments
+    /// * `macro_` - The [`Macro`] to find references to.
+    /// * `file_id` - The [`file_id`](FileId) of a file of the project.
+    ///
+    /// # Returns
+    /// A list of [`user seen FileRanges`](FileRange), sorted and without duplicates.
+    fn macro_references(&self, macro_: Macro, file_id: FileId) -> Option<Vec<FileRange>> {
+        let def_file_id = DefResolution::Macro(macro_).file_id(self.db);
+        let idx = macro_.idx(self.db)?;
+        let name = macro_.name(self.db).to_smolstr();
+        let def_tree = self.parse(def_file_id);
+        let def_node = macro_.source(self.db, &def_tree)?.value;
+        let def_name_range = ts_range_to_text_range(&def_node.child_by_field_name("name")?.range());
+        let mut res = vec![FileRange {
+            file_id: def_file_id,
+            range: self
+                .preprocess_file(def_file_id)
+                .source_map()
+                .closest_u_range_always(def_name_range),
+        }];
+        let graph = self.db.projet_subgraph(file_id)?;
         for graph_node in graph.nodes.iter() {
             let file_id = graph_node.file_id;
-            let source = self.db.file_text(file_id);
-            let lexer = SourcepawnLexer::new(&source);
-            res.extend(lexer.filter_map(|token| {
-                if token.token_kind == TokenKind::Identifier && token.text() == name {
-                    return FileRange {
+            let preprocessing_results = self.preprocess_file(file_id);
+            res.extend(
+                preprocessing_results
+                    .source_map()
+                    .expanded_symbols()
+                    .iter()
+                    .filter(|it| it.file_id() == def_file_id && it.idx() == idx)
+                    .map(|it| FileRange {
                         file_id,
-                        range: token.range,
+                        range: it.name_range(),
+                    }),
+            );
+
+            // Symbols in directives are not expanded, look for them in the source.
+            let source = self.db.file_text(file_id);
+            let mut lexer = SourcepawnLexer::new(&source);
+            let mut after_define = false;
+            while let Some(token) = lexer.next() {
+                match token.token_kind {
+                    TokenKind::PreprocDir(PreprocDir::MDefine) => {
+                        after_define = true;
+                        continue;
+                    }
+                    // The name of a `#define` is another definition of the macro.
+                    TokenKind::Identifier
+                        if !after_define && lexer.in_preprocessor() && token.text() == name =>
+                    {
+                        res.push(FileRange {
+                            file_id,
+                            range: token.range,
+                        })
                     }
-                    .into();
+                    _ => (),
                 }
-                None
-            }));
+                after_define = false;
+            }
         }
+        res.sort_by_key(|it| (it.file_id, it.range.start()));
+        res.dedup();
 
-        Some((def, res))
+        Some(res)
     }
 }
 
@@ -0,0 +1,49 @@
+//! This module defines the `Assist` data structure. The actual assists live in
+//! the `ide-diagnostics` crate, as fixes of the diagnostics, and in the `ide` crate
+//! for those which are not tied to a diagnostic, e.g the auto-import.
+
+use base_db::FilePosition;
+use line_index::TextRange;
+
+use crate::SourceChange;
+
+#[derive(Debug, Clone)]
+pub struct Assist {
+    pub id: AssistId,
+    /// Short description of the assist, as shown in the UI.
+    pub label: String,
+    /// Target ranges are used to sort assists: the smaller the target range,
+    /// the more specific assist is, and so it should be sorted first.
+    pub target: TextRange,
+    /// Edits to apply to the files when the assist is accepted.
+    pub source_change: SourceChange,
+    /// Position of a symbol to rename once the source change is applied, e.g the name of an
+    /// extracted variable. The offset is in the edited text of the file.
+    pub trigger_rename: Option<FilePosition>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssistKind {
+    QuickFix,
+    Generate,
+    OrganizeIncludes,
+    RefactorExtract,
+    RefactorRewrite,
+}
+
+impl AssistKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            AssistKind::QuickFix => "QuickFix",
+            AssistKind::Generate => "Generate",
+            AssistKind::OrganizeIncludes => "OrganizeIncludes",
+            AssistKind::RefactorExtract => "RefactorExtract",
+            AssistKind::RefactorRewrite => "RefactorRewrite",
+        }
+    }
+}
+
+/// Unique identifier of the assist, should not be shown to the user
+/// directly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AssistId(pub &'static str, pub AssistKind);
@@ -0,0 +1,26 @@
+use base_db::SourceDatabaseExt;
+use fxhash::FxHashMap;
+use hir_def::{ConVar, DefDatabase, InFile};
+use smol_str::SmolStr;
+
+use crate::RootDatabase;
+
+/// ConVars created by the files of the workspace, by name.
+///
+/// The files of the include directories are skipped, as the ConVars of the third party
+/// includes are not created by the plugins of the workspace.
+pub fn workspace_convars(db: &RootDatabase) -> FxHashMap<SmolStr, Vec<InFile<ConVar>>> {
+    let mut res: FxHashMap<SmolStr, Vec<InFile<ConVar>>> = FxHashMap::default();
+    for (file_id, _) in db.known_files() {
+        if db.source_root(db.file_source_root(file_id)).is_include_dir {
+            continue;
+        }
+        for convar in db.file_convars(file_id).iter() {
+            res.entry(convar.name.clone())
+                .or_default()
+                .push(InFile::new(file_id, convar.clone()));
+        }
+    }
+
+    res
+}
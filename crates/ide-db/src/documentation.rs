@@ -61,6 +61,11 @@ impl Documentation {
         &self.0
     }
 
+    /// Builds the documentation from the raw text of a doc comment, e.g `/** ... */`.
+    pub fn from_comment(text: &str) -> Documentation {
+        Documentation(comment_to_doc(text))
+    }
+
     pub fn from_node(mut node: tree_sitter::Node, source: &[u8]) -> Option<Documentation> {
         let mut pragma = None;
         let mut docs = Vec::new();
@@ -187,42 +192,154 @@ impl Documentation {
     }
 
     pub fn to_markdown(&self) -> String {
-        lazy_static! {
-            static ref RE1: Regex = Regex::new(r"^\*<").unwrap();
-            static ref RE2: Regex = Regex::new(r"\*\s*\r?\n\s*\*").unwrap();
-            static ref RE3: Regex = Regex::new(r"\r?\n\s*\*").unwrap();
-            static ref RE4: Regex = Regex::new(r"^\*").unwrap();
-            static ref RE5: Regex = Regex::new(r"<").unwrap();
-            static ref RE6: Regex = Regex::new(r">").unwrap();
-            static ref RE7: Regex = Regex::new(r"\s*(@[A-Za-z]+)\s+").unwrap();
-            static ref RE8: Regex = Regex::new(r"(_@param_) ([A-Za-z0-9_.]+)\s*").unwrap();
-            static ref RE9: Regex = Regex::new(r"(\w+\([A-Za-z0-9_ :]*\))").unwrap();
+        self.parse().to_markdown()
+    }
+
+    /// Parses the documentation into its description and its tags.
+    pub fn parse(&self) -> DocComment {
+        let mut res = DocComment::default();
+        let mut description = Vec::new();
+        let mut tag: Option<(String, String)> = None;
+        for line in self.as_str().lines() {
+            let line = line.trim();
+            let line = line
+                .strip_prefix("*<")
+                .or_else(|| line.strip_prefix('*'))
+                .unwrap_or(line)
+                .trim();
+            if let Some(message) = line.strip_prefix("DEPRECATED:") {
+                res.deprecated = Some(message.trim().to_string());
+                continue;
+            }
+            if let Some(tag_line) = line.strip_prefix('@') {
+                if let Some((name, text)) = tag.take() {
+                    res.push_tag(&name, text);
+                }
+                let (name, text) = tag_line
+                    .split_once(char::is_whitespace)
+                    .unwrap_or((tag_line, ""));
+                tag = Some((name.to_string(), text.trim().to_string()));
+                continue;
+            }
+            match &mut tag {
+                Some((_, text)) if !line.is_empty() => {
+                    if !text.is_empty() {
+                        text.push('\n');
+                    }
+                    text.push_str(line);
+                }
+                Some(_) => (),
+                None => description.push(line),
+            }
+        }
+        if let Some((name, text)) = tag {
+            res.push_tag(&name, text);
         }
-        let text = RE1.replace_all(self.as_str(), "").into_owned();
-        let text = RE2.replace_all(&text, "\n\n").into_owned();
-        let text = RE3.replace_all(&text, "").into_owned();
-        let text = RE4.replace_all(&text, "").into_owned();
-        let text = RE5.replace_all(&text, "\\<").into_owned();
-        let text = RE6.replace_all(&text, "\\>").into_owned();
+        res.description = description.join("\n").trim().to_string();
 
-        let text = RE7.replace_all(&text, "\n\n_${1}_ ").into_owned();
-        let text = RE8.replace_all(&text, "${1} `${2}` — >").into_owned();
-        let text = RE9.replace_all(&text, "`${1}`").into_owned();
-        text.replace("DEPRECATED", "\n\n**DEPRECATED**")
-            .trim()
-            .to_string()
+        res
     }
 
     /// Extracts the description of a parameter from the documentation.
     pub fn param_description(&self, param_name: &str) -> Option<Self> {
-        let re = Regex::new(&format!(r"\s*@param\s+{}\s+", regex::escape(param_name))).ok()?;
-        let start = re.find(self.as_str())?.end();
-        let end = self.as_str()[start..]
-            .find('@')
-            .map(|i| start + i)
-            .unwrap_or_else(|| self.as_str().len());
-        Some(self.as_str()[start..end].trim().to_string().into())
+        self.parse().param(param_name).map(Self::from)
+    }
+}
+
+/// Doc comment parsed into its description and its block tags, e.g `@param`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DocComment {
+    pub description: String,
+    /// Name and description of the `@param` tags.
+    pub params: Vec<(String, String)>,
+    /// Description of the `@return` tag.
+    pub returns: Option<String>,
+    pub notes: Vec<String>,
+    /// Descriptions of the `@error` tags, the cases where a native throws an error.
+    pub errors: Vec<String>,
+    /// Message of the `#pragma deprecated` of the item.
+    pub deprecated: Option<String>,
+    /// Name and text of the other tags, e.g `@noreturn`.
+    pub others: Vec<(String, String)>,
+}
+
+impl DocComment {
+    fn push_tag(&mut self, name: &str, text: String) {
+        match name {
+            "param" => {
+                let (param, description) =
+                    text.split_once(char::is_whitespace).unwrap_or((&text, ""));
+                self.params
+                    .push((param.to_string(), description.trim().to_string()));
+            }
+            "return" | "returns" => self.returns = Some(text),
+            "note" => self.notes.push(text),
+            "error" => self.errors.push(text),
+            _ => self.others.push((name.to_string(), text)),
+        }
+    }
+
+    /// Description of a parameter.
+    pub fn param(&self, param_name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(name, _)| name == param_name)
+            .map(|(_, description)| description.as_str())
+    }
+
+    /// Removes the `@param` tags, e.g when the parameters are documented separately.
+    pub fn without_params(mut self) -> Self {
+        self.params.clear();
+        self
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut sections = Vec::new();
+        if let Some(deprecated) = &self.deprecated {
+            sections.push(format!("**DEPRECATED**: {}", inline_markdown(deprecated)));
+        }
+        if !self.description.is_empty() {
+            sections.push(inline_markdown(&self.description));
+        }
+        if !self.params.is_empty() {
+            let params = self
+                .params
+                .iter()
+                .map(|(name, description)| {
+                    format!("- `{}` — {}", name, inline_markdown(description))
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            sections.push(format!("**Parameters**\n\n{}", params));
+        }
+        if let Some(returns) = &self.returns {
+            sections.push(format!("**Returns** — {}", inline_markdown(returns)));
+        }
+        for note in &self.notes {
+            sections.push(format!("**Note** — {}", inline_markdown(note)));
+        }
+        for error in &self.errors {
+            sections.push(format!("**Error** — {}", inline_markdown(error)));
+        }
+        for (name, text) in &self.others {
+            sections.push(
+                format!("_@{}_ {}", name, inline_markdown(text))
+                    .trim_end()
+                    .to_string(),
+            );
+        }
+
+        sections.join("\n\n")
+    }
+}
+
+/// Escapes the angle brackets of a text, and quotes the function calls it mentions.
+fn inline_markdown(text: &str) -> String {
+    lazy_static! {
+        static ref CALL_RE: Regex = Regex::new(r"(\w+\([A-Za-z0-9_ :]*\))").unwrap();
     }
+    let text = text.replace('<', "\\<").replace('>', "\\>");
+    CALL_RE.replace_all(&text, "`${1}`").into_owned()
 }
 
 fn comment_to_doc(text: &str) -> String {
@@ -241,3 +358,94 @@ fn comment_to_doc(text: &str) -> String {
 
     text
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_comment() {
+        let doc = Documentation::from_comment(
+            r#"/**
+ * Kicks a client.
+ *
+ * @param client    Client index.
+ * @param format    Optional kick message,
+ *                  formatted.
+ * @return          True on success.
+ * @note            Can be deferred.
+ * @error           Invalid client index.
+ */"#,
+        );
+        assert_eq!(
+            doc.parse(),
+            DocComment {
+                description: "Kicks a client.".to_string(),
+                params: vec![
+                    ("client".to_string(), "Client index.".to_string()),
+                    (
+                        "format".to_string(),
+                        "Optional kick message,\nformatted.".to_string()
+                    ),
+                ],
+                returns: Some("True on success.".to_string()),
+                notes: vec!["Can be deferred.".to_string()],
+                errors: vec!["Invalid client index.".to_string()],
+                deprecated: None,
+                others: vec![],
+            }
+        );
+        assert_eq!(
+            doc.param_description("client"),
+            Some("Client index.".into())
+        );
+        assert_eq!(doc.param_description("clients"), None);
+    }
+
+    #[test]
+    fn to_markdown() {
+        let doc = Documentation::from(
+            "DEPRECATED: Use GetClientNameEx().
+Gets the name of a client.
+@param client    Client index.
+@param buffer    Buffer for the <name>.
+@return          True on success, see IsClientConnected(client).
+@note            Names are truncated.
+@error           Invalid client index.
+@noreturn",
+        );
+        assert_eq!(
+            doc.to_markdown(),
+            "**DEPRECATED**: Use `GetClientNameEx()`.
+
+Gets the name of a client.
+
+**Parameters**
+
+- `client` — Client index.
+- `buffer` — Buffer for the \\<name\\>.
+
+**Returns** — True on success, see `IsClientConnected(client)`.
+
+**Note** — Names are truncated.
+
+**Error** — Invalid client index.
+
+_@noreturn_"
+        );
+        assert_eq!(
+            doc.parse().without_params().to_markdown(),
+            "**DEPRECATED**: Use `GetClientNameEx()`.
+
+Gets the name of a client.
+
+**Returns** — True on success, see `IsClientConnected(client)`.
+
+**Note** — Names are truncated.
+
+**Error** — Invalid client index.
+
+_@noreturn_"
+        );
+    }
+}
@@ -0,0 +1,50 @@
+//! Analyses which are still in development.
+//!
+//! They ship disabled, and are enabled one by one with the `analysis.experimental` setting.
+
+use fxhash::FxHashSet;
+
+/// An analysis in development, gated behind a flag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ExperimentalFlag {
+    /// Report the includes none of whose symbols are used.
+    UnusedIncludes,
+}
+
+impl ExperimentalFlag {
+    pub const ALL: &'static [ExperimentalFlag] = &[ExperimentalFlag::UnusedIncludes];
+
+    /// Name of the flag in the settings.
+    pub fn name(self) -> &'static str {
+        match self {
+            ExperimentalFlag::UnusedIncludes => "unusedIncludes",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|flag| flag.name() == name)
+    }
+}
+
+/// Set of the experimental analyses enabled by the user.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExperimentalFlags(FxHashSet<ExperimentalFlag>);
+
+impl ExperimentalFlags {
+    pub fn is_enabled(&self, flag: ExperimentalFlag) -> bool {
+        self.0.contains(&flag)
+    }
+
+    /// Names of the enabled flags, sorted.
+    pub fn names(&self) -> Vec<&'static str> {
+        let mut flags = self.0.iter().copied().collect::<Vec<_>>();
+        flags.sort();
+        flags.into_iter().map(ExperimentalFlag::name).collect()
+    }
+}
+
+impl FromIterator<ExperimentalFlag> for ExperimentalFlags {
+    fn from_iter<T: IntoIterator<Item = ExperimentalFlag>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
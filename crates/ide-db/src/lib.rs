@@ -1,15 +1,19 @@
 //! base_db defines basic database traits. The concrete DB is defined by ide.
 
+mod assists;
 mod call_item;
+mod convars;
 mod documentation;
+mod experimental;
+mod plugin_info;
 mod source_change;
 mod symbols;
 
 use std::{fmt, mem::ManuallyDrop, sync::Arc};
 
 use base_db::{
-    Change, FileLoader, FileLoaderDelegate, SourceDatabaseExt, SourceDatabaseExtStorage,
-    SourceDatabaseStorage, Upcast,
+    Change, FileLoader, FileLoaderDelegate, SourceDatabase, SourceDatabaseExt,
+    SourceDatabaseExtStorage, SourceDatabaseStorage, Upcast,
 };
 use fxhash::FxHashMap;
 use hir::{db::HirDatabase, FunctionType};
@@ -18,8 +22,12 @@ use line_index::LineIndex;
 use salsa::{Cancelled, Durability};
 use vfs::FileId;
 
+pub use assists::{Assist, AssistId, AssistKind};
 pub use call_item::{CallItem, IncomingCallItem, OutgoingCallItem};
-pub use documentation::Documentation;
+pub use convars::workspace_convars;
+pub use documentation::{DocComment, Documentation};
+pub use experimental::{ExperimentalFlag, ExperimentalFlags};
+pub use plugin_info::{PluginInfo, PLUGIN_INFO_FIELDS, REQUIRED_PLUGIN_INFO_FIELDS};
 pub use source_change::{SourceChange, TextEdit};
 pub use symbols::{Symbol, SymbolId, Symbols, SymbolsBuilder};
 
@@ -44,6 +52,7 @@ pub struct RootDatabase {
     // which duplicates `Weak::drop` and `Arc::drop` tens of thousands of times, which makes
     // compile times of all `ide_*` and downstream crates suffer greatly.
     storage: ManuallyDrop<salsa::Storage<RootDatabase>>,
+    preprocessing_cache: Arc<preprocessor::PreprocessingCache>,
 }
 
 impl Drop for RootDatabase {
@@ -58,6 +67,12 @@ impl fmt::Debug for RootDatabase {
     }
 }
 
+impl preprocessor::PreprocessingCacheProvider for RootDatabase {
+    fn preprocessing_cache(&self) -> &preprocessor::PreprocessingCache {
+        &self.preprocessing_cache
+    }
+}
+
 impl Upcast<dyn DefDatabase> for RootDatabase {
     #[inline]
     fn upcast(&self) -> &(dyn DefDatabase + 'static) {
@@ -85,6 +100,9 @@ impl FileLoader for RootDatabase {
     fn resolve_path_relative_to_roots(&self, path: &str) -> Option<FileId> {
         FileLoaderDelegate(self).resolve_path_relative_to_roots(path)
     }
+    fn resolve_path_in_roots(&self, path: &str, include_dirs: bool) -> Option<FileId> {
+        FileLoaderDelegate(self).resolve_path_in_roots(path, include_dirs)
+    }
 }
 
 impl salsa::Database for RootDatabase {}
@@ -99,9 +117,26 @@ impl RootDatabase {
     pub fn new(lru_capacity: Option<usize>) -> RootDatabase {
         let mut db = RootDatabase {
             storage: ManuallyDrop::new(salsa::Storage::default()),
+            preprocessing_cache: Default::default(),
         };
         db.set_known_files_with_durability(Default::default(), Durability::HIGH);
         db.set_source_roots_with_durability(Default::default(), Durability::HIGH);
+        db.set_main_files_with_durability(Default::default(), Durability::HIGH);
+        db.set_phrases_with_durability(Default::default(), Durability::HIGH);
+        db.set_gamedata_with_durability(Default::default(), Durability::HIGH);
+        db.set_implicit_includes_with_durability(
+            Arc::new(
+                base_db::DEFAULT_IMPLICIT_INCLUDES
+                    .iter()
+                    .map(|it| it.to_string())
+                    .collect(),
+            ),
+            Durability::HIGH,
+        );
+        db.set_include_search_order_with_durability(Default::default(), Durability::HIGH);
+        db.set_include_limits_with_durability(Default::default(), Durability::HIGH);
+        db.set_forced_branches_with_durability(Default::default(), Durability::HIGH);
+        db.set_macro_configurations_with_durability(Default::default(), Durability::HIGH);
         db.update_parse_query_lru_capacity(lru_capacity);
         db
     }
@@ -169,6 +204,7 @@ impl salsa::ParallelDatabase for RootDatabase {
     fn snapshot(&self) -> salsa::Snapshot<RootDatabase> {
         salsa::Snapshot::new(RootDatabase {
             storage: ManuallyDrop::new(self.storage.snapshot()),
+            preprocessing_cache: self.preprocessing_cache.clone(),
         })
     }
 }
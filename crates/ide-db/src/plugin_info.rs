@@ -0,0 +1,96 @@
+use hir_def::unquote;
+use syntax::TSKind;
+use tree_sitter::Node;
+
+/// Fields of the `Plugin` struct, in the order of its declaration.
+pub const PLUGIN_INFO_FIELDS: &[&str] = &["name", "author", "description", "version", "url"];
+
+/// Fields of the `Plugin` struct shown by SourceMod in the list of the plugins, which every
+/// plugin should set.
+pub const REQUIRED_PLUGIN_INFO_FIELDS: &[&str] = &["name", "author", "version"];
+
+/// Metadata of a plugin, declared by its `myinfo` variable:
+///
+/// ```sourcepawn
+/// public Plugin myinfo =
+/// {
+///     name = "Foo",
+///     author = "Bar",
+///     version = "1.0.0",
+/// };
+/// ```
+#[derive(Debug, Clone)]
+pub struct PluginInfo<'tree> {
+    /// The `struct_declaration` node of the variable.
+    pub node: Node<'tree>,
+    /// The name of the variable.
+    pub name: Node<'tree>,
+    /// The `struct_constructor` node of the value of the variable.
+    pub constructor: Node<'tree>,
+    /// The name of each field, with its value.
+    pub fields: Vec<(String, Node<'tree>)>,
+}
+
+impl<'tree> PluginInfo<'tree> {
+    /// Find the `myinfo` variable declared at the root of a file.
+    ///
+    /// # Arguments
+    /// * `root` - Root node of the preprocessed file.
+    /// * `source` - Preprocessed text of the file.
+    pub fn find(root: Node<'tree>, source: &str) -> Option<Self> {
+        root.children(&mut root.walk())
+            .find_map(|node| Self::from_node(node, source))
+    }
+
+    /// Metadata of a plugin, if the node is the declaration of its `myinfo` variable.
+    ///
+    /// # Arguments
+    /// * `node` - Node of the declaration.
+    /// * `source` - Preprocessed text of the file of the node.
+    pub fn from_node(node: Node<'tree>, source: &str) -> Option<Self> {
+        if TSKind::from(node) != TSKind::struct_declaration {
+            return None;
+        }
+        let text = |node: Node| node.utf8_text(source.as_bytes()).ok();
+        let name = node.child_by_field_name("name")?;
+        if text(name)? != "myinfo" || text(node.child_by_field_name("type")?)? != "Plugin" {
+            return None;
+        }
+        let constructor = node.child_by_field_name("value")?;
+        let fields = constructor
+            .children(&mut constructor.walk())
+            .filter(|child| TSKind::from(child) == TSKind::struct_field_value)
+            .filter_map(|child| {
+                Some((
+                    text(child.child_by_field_name("field")?)?.to_string(),
+                    child.child_by_field_name("value")?,
+                ))
+            })
+            .collect();
+
+        Some(Self {
+            node,
+            name,
+            constructor,
+            fields,
+        })
+    }
+
+    /// Value node of a field, if it is set.
+    pub fn field(&self, name: &str) -> Option<Node<'tree>> {
+        self.fields
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, value)| *value)
+    }
+
+    /// Value of a field, if it is set with a string literal.
+    pub fn literal<'a>(&self, name: &str, source: &'a str) -> Option<&'a str> {
+        let value = self.field(name)?;
+        if TSKind::from(value) != TSKind::string_literal {
+            return None;
+        }
+
+        value.utf8_text(source.as_bytes()).ok().map(unquote)
+    }
+}
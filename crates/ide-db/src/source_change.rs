@@ -5,6 +5,8 @@ use vfs::FileId;
 #[derive(Default, Debug, Clone)]
 pub struct SourceChange {
     pub source_file_edits: IntMap<FileId, Vec<TextEdit>>,
+    /// Whether the replacement texts are snippets, with tabstops like `$0` or `${1:name}`.
+    pub is_snippet: bool,
 }
 
 impl SourceChange {
@@ -72,6 +72,11 @@ impl<'a> SymbolsBuilder<'a> {
         self.source_map.closest_u_range_always(s_range)
     }
 
+    fn s_name_range_to_u_range(&self, s_range: &tree_sitter::Range) -> TextRange {
+        let s_range = ts_range_to_text_range(s_range);
+        self.source_map.closest_u_name_range(s_range)
+    }
+
     fn is_deprecated(&self, node: &tree_sitter::Node) -> bool {
         self.deprecated
             .contains(&node.range().start_point.row.saturating_sub(1))
@@ -148,7 +153,7 @@ impl<'a> SymbolsBuilder<'a> {
                 .map(ToString::to_string),
             kind,
             self.s_range_to_u_range(&node.range()),
-            self.s_range_to_u_range(&name_node.range()).into(),
+            self.s_name_range_to_u_range(&name_node.range()).into(),
             children,
             self.is_deprecated(node),
         )?;
@@ -185,7 +190,7 @@ impl<'a> SymbolsBuilder<'a> {
             None,
             SymbolKind::Methodmap,
             self.s_range_to_u_range(&node.range()),
-            self.s_range_to_u_range(&name_node.range()).into(),
+            self.s_name_range_to_u_range(&name_node.range()).into(),
             children,
             self.is_deprecated(node),
         )?;
@@ -220,7 +225,7 @@ impl<'a> SymbolsBuilder<'a> {
             type_,
             SymbolKind::Property,
             self.s_range_to_u_range(&node.range()),
-            self.s_range_to_u_range(&name_node.range()).into(),
+            self.s_name_range_to_u_range(&name_node.range()).into(),
             children,
             self.is_deprecated(node),
         )?;
@@ -250,7 +255,7 @@ impl<'a> SymbolsBuilder<'a> {
                                 None,
                                 SymbolKind::Variant,
                                 self.s_range_to_u_range(&e.range()),
-                                self.s_range_to_u_range(&name_node.range()).into(),
+                                self.s_name_range_to_u_range(&name_node.range()).into(),
                                 vec![],
                                 self.is_deprecated(&e),
                             )?;
@@ -263,13 +268,13 @@ impl<'a> SymbolsBuilder<'a> {
         let symbol = Symbol::try_new(
             name,
             None,
-            SymbolKind::Property,
+            SymbolKind::Enum,
             self.s_range_to_u_range(&node.range()),
-            name_node.map(|node| self.s_range_to_u_range(&node.range())),
+            name_node.map(|node| self.s_name_range_to_u_range(&node.range())),
             children,
             self.is_deprecated(node),
         )?;
-        self.alloc(symbol).into()
+        self.alloc_top(symbol).into()
     }
 
     fn alloc_variable_declaration(&mut self, node: &Node) -> Option<SymbolId> {
@@ -289,7 +294,7 @@ impl<'a> SymbolsBuilder<'a> {
             type_,
             SymbolKind::Local,
             self.s_range_to_u_range(&node.range()),
-            self.s_range_to_u_range(&name_node.range()).into(),
+            self.s_name_range_to_u_range(&name_node.range()).into(),
             vec![],
             self.is_deprecated(node),
         )?;
@@ -328,9 +333,9 @@ impl<'a> SymbolsBuilder<'a> {
                             .ok()?
                             .to_smolstr(),
                         type_,
-                        SymbolKind::Variant,
+                        SymbolKind::Field,
                         self.s_range_to_u_range(&child.range()),
-                        self.s_range_to_u_range(&name_node.range()).into(),
+                        self.s_name_range_to_u_range(&name_node.range()).into(),
                         vec![],
                         self.is_deprecated(&child),
                     )?;
@@ -347,7 +352,7 @@ impl<'a> SymbolsBuilder<'a> {
             None,
             SymbolKind::EnumStruct,
             self.s_range_to_u_range(&node.range()),
-            self.s_range_to_u_range(&name_node.range()).into(),
+            self.s_name_range_to_u_range(&name_node.range()).into(),
             children,
             self.is_deprecated(node),
         )?;
@@ -365,7 +370,7 @@ impl<'a> SymbolsBuilder<'a> {
             None,
             SymbolKind::Typedef,
             self.s_range_to_u_range(&node.range()),
-            name_node.map(|node| self.s_range_to_u_range(&node.range())),
+            name_node.map(|node| self.s_name_range_to_u_range(&node.range())),
             vec![],
             self.is_deprecated(node),
         )?;
@@ -391,7 +396,7 @@ impl<'a> SymbolsBuilder<'a> {
             None,
             SymbolKind::Typeset,
             self.s_range_to_u_range(&node.range()),
-            self.s_range_to_u_range(&name_node.range()).into(),
+            self.s_name_range_to_u_range(&name_node.range()).into(),
             children,
             self.is_deprecated(node),
         )?;
@@ -410,7 +415,7 @@ impl<'a> SymbolsBuilder<'a> {
                 .map(ToString::to_string),
             SymbolKind::Functag,
             self.s_range_to_u_range(&node.range()),
-            self.s_range_to_u_range(&name_node.range()).into(),
+            self.s_name_range_to_u_range(&name_node.range()).into(),
             vec![],
             self.is_deprecated(node),
         )?;
@@ -445,7 +450,7 @@ impl<'a> SymbolsBuilder<'a> {
             None,
             SymbolKind::Funcenum,
             self.s_range_to_u_range(&node.range()),
-            self.s_range_to_u_range(&name_node.range()).into(),
+            self.s_name_range_to_u_range(&name_node.range()).into(),
             children,
             self.is_deprecated(node),
         )?;
@@ -467,7 +472,7 @@ impl<'a> SymbolsBuilder<'a> {
                     None,
                     SymbolKind::Field,
                     self.s_range_to_u_range(&n.range()),
-                    self.s_range_to_u_range(&name_node.range()).into(),
+                    self.s_name_range_to_u_range(&name_node.range()).into(),
                     vec![],
                     self.is_deprecated(&n),
                 )?;
@@ -482,7 +487,7 @@ impl<'a> SymbolsBuilder<'a> {
             None,
             SymbolKind::Struct,
             self.s_range_to_u_range(&node.range()),
-            self.s_range_to_u_range(&name_node.range()).into(),
+            self.s_name_range_to_u_range(&name_node.range()).into(),
             children,
             self.is_deprecated(node),
         )?;
@@ -499,7 +504,7 @@ impl<'a> SymbolsBuilder<'a> {
             None,
             SymbolKind::Struct,
             self.s_range_to_u_range(&node.range()),
-            self.s_range_to_u_range(&name_node.range()).into(),
+            self.s_name_range_to_u_range(&name_node.range()).into(),
             vec![],
             self.is_deprecated(node),
         )?;
@@ -600,6 +605,10 @@ impl Symbol {
             // Name is falsy
             return None;
         }
+        // The name of an item declared by a macro call can be mapped to an argument of the call,
+        // outside of the range of the item.
+        let full_range =
+            focus_range.map_or(full_range, |focus_range| full_range.cover(focus_range));
         Self {
             name,
             details,
@@ -0,0 +1,57 @@
+//! Extended explanations of the [diagnostic codes](crate::DiagnosticCode).
+
+macro_rules! explanations {
+    ($($code:literal),* $(,)?) => {
+        /// Codes of all the diagnostics emitted by the server.
+        pub const DIAGNOSTIC_CODES: &[&str] = &[$($code),*];
+
+        /// Get the extended explanation of a diagnostic code, in markdown.
+        ///
+        /// # Arguments
+        /// * `code` - Code of the diagnostic, e.g `SP0001`.
+        pub fn explain(code: &str) -> Option<&'static str> {
+            match code {
+                $($code => Some(include_str!(concat!("explanations/", $code, ".md"))),)*
+                _ => None,
+            }
+        }
+    };
+}
+
+explanations![
+    "SP0001",
+    "SP0002",
+    "SP0003",
+    "SP0004",
+    "SP0005",
+    "SP0006",
+    "SP0007",
+    "SP0008",
+    "SP0009",
+    "SP0010",
+    "SP0011",
+    "SP0012",
+    "SP0013",
+    "SP0014",
+    "SP0015",
+    "SP0016",
+    "SP0017",
+    "SP0018",
+    "SP0019",
+    "SPLINT-duplicate-convar",
+    "SPLINT-inactive-code",
+    "SPLINT-inactive-directive",
+    "SPLINT-incomplete-plugin-info",
+    "SPLINT-incorrect-format-arguments",
+    "SPLINT-incorrect-phrase-arguments",
+    "SPLINT-mismatched-format-argument",
+    "SPLINT-mismatched-sql-parameters",
+    "SPLINT-non-literal-plugin-version",
+    "SPLINT-unescaped-sql-argument",
+    "SPLINT-unknown-gamedata-entry",
+    "SPLINT-unknown-phrase",
+    "SPLINT-unloaded-translation",
+    "SPLINT-unused-include",
+    "SPLINT-unused-macro",
+    "SPLINT-use-after-undef",
+];
@@ -1,7 +1,24 @@
+pub(crate) mod assertion_failed;
+pub(crate) mod circular_include;
+pub(crate) mod deprecated_call;
+pub(crate) mod duplicate_convar;
 pub(crate) mod inactive_code;
+pub(crate) mod inactive_directive;
+pub(crate) mod include_limit_exceeded;
 pub(crate) mod incorrect_number_of_arguments;
+pub(crate) mod incorrect_number_of_format_arguments;
+pub(crate) mod incorrect_number_of_phrase_arguments;
 pub(crate) mod invalid_use_of_this;
+pub(crate) mod macro_argument_count_mismatch;
+pub(crate) mod mismatched_format_argument;
+pub(crate) mod plugin_info;
 pub(crate) mod preprocessor_evaluation_error;
+pub(crate) mod sql_queries;
+pub(crate) mod unbalanced_condition;
+pub(crate) mod unknown_directive;
+pub(crate) mod unknown_gamedata_entry;
+pub(crate) mod unknown_phrase;
+pub(crate) mod unloaded_translation;
 pub(crate) mod unresolved_constructor;
 pub(crate) mod unresolved_field;
 pub(crate) mod unresolved_include;
@@ -9,3 +26,6 @@ pub(crate) mod unresolved_inherit;
 pub(crate) mod unresolved_macro;
 pub(crate) mod unresolved_method_call;
 pub(crate) mod unresolved_named_arg;
+pub(crate) mod unused_include;
+pub(crate) mod unused_macro;
+pub(crate) mod use_after_undef;
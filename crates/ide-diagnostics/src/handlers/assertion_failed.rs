@@ -0,0 +1,17 @@
+use crate::{Diagnostic, DiagnosticCode, DiagnosticsContext};
+
+pub(crate) use self::assertion_failed as f;
+
+// Diagnostic: assertion-failed
+//
+// This diagnostic is triggered if the expression of an `#assert` directive is false.
+pub(crate) fn assertion_failed(
+    _ctx: &DiagnosticsContext<'_>,
+    d: &hir::AssertionFailed,
+) -> Diagnostic {
+    Diagnostic::new_for_u_range(
+        DiagnosticCode::SpCompError("SP0017"),
+        format!("assertion failed: `{}`", d.expression),
+        d.range,
+    )
+}
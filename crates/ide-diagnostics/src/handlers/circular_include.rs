@@ -0,0 +1,23 @@
+use crate::{file_name, Diagnostic, DiagnosticCode, DiagnosticsContext};
+
+pub(crate) use self::circular_include as f;
+
+// Diagnostic: circular-include
+//
+// This diagnostic is triggered if an include closes a cycle of includes.
+pub(crate) fn circular_include(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::CircularInclude,
+) -> Diagnostic {
+    let cycle = d
+        .cycle
+        .iter()
+        .map(|file_id| file_name(ctx.sema.db, *file_id))
+        .collect::<Vec<_>>()
+        .join(" -> ");
+    Diagnostic::new_for_u_range(
+        DiagnosticCode::SpCompError("SP0014"),
+        format!("circular include of `{}`: {cycle}", d.path),
+        d.range,
+    )
+}
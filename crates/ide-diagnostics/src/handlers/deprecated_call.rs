@@ -0,0 +1,20 @@
+use crate::{Diagnostic, DiagnosticCode, DiagnosticsContext};
+
+pub(crate) use self::deprecated_call as f;
+
+// Diagnostic: deprecated-call
+//
+// This diagnostic is triggered if a function marked with `#pragma deprecated` is called.
+pub(crate) fn deprecated_call(ctx: &DiagnosticsContext<'_>, d: &hir::DeprecatedCall) -> Diagnostic {
+    let message = match &d.message {
+        Some(message) => format!("`{}` is deprecated: {message}", d.name),
+        None => format!("`{}` is deprecated", d.name),
+    };
+    Diagnostic::new_with_syntax_node_ptr(
+        ctx,
+        DiagnosticCode::SpCompWarning("SP0015"),
+        message,
+        d.expr,
+    )
+    .with_deprecated(true)
+}
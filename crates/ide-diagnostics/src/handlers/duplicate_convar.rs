@@ -0,0 +1,67 @@
+use base_db::SourceDatabaseExt;
+use hir_def::{DefDatabase, InFile};
+use ide_db::workspace_convars;
+use line_index::{TextRange, TextSize};
+use preprocessor::db::PreprocDatabase;
+
+use crate::{
+    file_name, Diagnostic, DiagnosticCode, DiagnosticsContext, RelatedInformation, Severity,
+};
+
+// Diagnostic: duplicate-convar
+//
+// This diagnostic is triggered if a ConVar created with `CreateConVar` is also created by another
+// file of the workspace.
+pub(crate) fn duplicate_convars(ctx: &DiagnosticsContext<'_>, acc: &mut Vec<Diagnostic>) {
+    let db = ctx.sema.db;
+    if db
+        .source_root(db.file_source_root(ctx.file_id))
+        .is_include_dir
+    {
+        return;
+    }
+    let convars = db.file_convars(ctx.file_id);
+    if convars.is_empty() {
+        return;
+    }
+    let workspace_convars = workspace_convars(db);
+    for convar in convars.iter() {
+        let Some(others) = workspace_convars.get(&convar.name) else {
+            continue;
+        };
+        let others = others
+            .iter()
+            .filter(|other| other.file_id != ctx.file_id)
+            .collect::<Vec<_>>();
+        if others.is_empty() {
+            continue;
+        }
+        let mut files = others
+            .iter()
+            .map(|other| format!("`{}`", file_name(db, other.file_id)))
+            .collect::<Vec<_>>();
+        files.dedup();
+        let files = files.join(", ");
+        let mut diagnostic = Diagnostic::new_with_syntax_node_ptr(
+            ctx,
+            DiagnosticCode::Lint("SPLINT-duplicate-convar", Severity::Warning),
+            format!("ConVar `{}` is also created in {files}", convar.name),
+            InFile::new(ctx.file_id, convar.name_ptr),
+        );
+        diagnostic.related.extend(others.iter().map(|other| {
+            let s_range = TextRange::new(
+                TextSize::new(other.value.name_ptr.start_byte() as u32),
+                TextSize::new(other.value.name_ptr.end_byte() as u32),
+            );
+            RelatedInformation {
+                file_id: other.file_id,
+                u_range: db
+                    .preprocess_file(other.file_id)
+                    .source_map()
+                    .closest_u_range_always(s_range),
+                message: format!("`{}` is also created here", convar.name),
+            }
+        }));
+        acc.push(diagnostic);
+    }
+}
@@ -10,7 +10,7 @@ pub(crate) fn inactive_code(_ctx: &DiagnosticsContext<'_>, d: &hir::InactiveCode
 
     // FIXME: This shouldn't be a diagnostic
     Diagnostic::new_for_u_range(
-        DiagnosticCode::Lint("inactive-code", Severity::WeakWarning),
+        DiagnosticCode::Lint("SPLINT-inactive-code", Severity::WeakWarning),
         message,
         d.range,
     )
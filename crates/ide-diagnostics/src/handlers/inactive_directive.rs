@@ -0,0 +1,28 @@
+use preprocessor::InactiveDirectiveReason;
+
+use crate::{Diagnostic, DiagnosticCode, DiagnosticsContext, Severity};
+
+pub(crate) use self::inactive_directive as f;
+
+// Diagnostic: inactive-directive
+//
+// This diagnostic is triggered if a directive of an inactive region is malformed, and would be an
+// error if the region was active. It is only reported when
+// `SourcePawnLanguageServer.diagnostics.lintInactiveCode` is enabled.
+pub(crate) fn inactive_directive(
+    _ctx: &DiagnosticsContext<'_>,
+    d: &hir::InactiveDirective,
+) -> Diagnostic {
+    let message = match &d.reason {
+        InactiveDirectiveReason::UnknownDirective {
+            suggestion: Some(suggestion),
+            ..
+        } => format!("{}, did you mean `{suggestion}`?", d.reason),
+        _ => d.reason.to_string(),
+    };
+    Diagnostic::new_for_u_range(
+        DiagnosticCode::Lint("SPLINT-inactive-directive", Severity::Warning),
+        format!("{message} (in inactive code)"),
+        d.range,
+    )
+}
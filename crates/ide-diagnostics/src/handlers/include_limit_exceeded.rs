@@ -0,0 +1,30 @@
+use preprocessor::IncludeLimitKind;
+
+use crate::{file_name, Diagnostic, DiagnosticCode, DiagnosticsContext};
+
+pub(crate) use self::include_limit_exceeded as f;
+
+// Diagnostic: include-limit-exceeded
+//
+// This diagnostic is triggered if an include exceeds the maximum depth of the include chains, or
+// the maximum number of files included by a project.
+pub(crate) fn include_limit_exceeded(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::IncludeLimitExceeded,
+) -> Diagnostic {
+    let chain = d
+        .chain
+        .iter()
+        .map(|file_id| file_name(ctx.sema.db, *file_id))
+        .collect::<Vec<_>>()
+        .join(" -> ");
+    let limit = match d.kind {
+        IncludeLimitKind::Depth(depth) => format!("include depth limit of {depth}"),
+        IncludeLimitKind::Files(files) => format!("limit of {files} included files"),
+    };
+    Diagnostic::new_for_u_range(
+        DiagnosticCode::SpCompError("SP0019"),
+        format!("include of `{}` exceeds the {limit}: {chain}", d.path),
+        d.range,
+    )
+}
@@ -0,0 +1,22 @@
+use crate::{Diagnostic, DiagnosticCode, DiagnosticsContext, Severity};
+
+pub(crate) use self::incorrect_number_of_format_arguments as f;
+
+// Diagnostic: incorrect-number-of-format-arguments
+//
+// This diagnostic is triggered if a format string does not receive as many arguments as it has
+// specifiers.
+pub(crate) fn incorrect_number_of_format_arguments(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::IncorrectNumberOfFormatArguments,
+) -> Diagnostic {
+    Diagnostic::new_with_syntax_node_ptr(
+        ctx,
+        DiagnosticCode::Lint("SPLINT-incorrect-format-arguments", Severity::Warning),
+        format!(
+            "format string expects {} arguments, found {}",
+            d.expected, d.actual
+        ),
+        d.expr,
+    )
+}
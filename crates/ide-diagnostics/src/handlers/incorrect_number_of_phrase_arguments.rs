@@ -0,0 +1,22 @@
+use crate::{Diagnostic, DiagnosticCode, DiagnosticsContext, Severity};
+
+pub(crate) use self::incorrect_number_of_phrase_arguments as f;
+
+// Diagnostic: incorrect-number-of-phrase-arguments
+//
+// This diagnostic is triggered if a phrase formatted with `%t` or `%T` does not receive as many
+// arguments as it has format parameters in its translation file.
+pub(crate) fn incorrect_number_of_phrase_arguments(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::IncorrectNumberOfPhraseArguments,
+) -> Diagnostic {
+    Diagnostic::new_with_syntax_node_ptr(
+        ctx,
+        DiagnosticCode::Lint("SPLINT-incorrect-phrase-arguments", Severity::Warning),
+        format!(
+            "phrase `{}` expects {} arguments, found {}",
+            d.phrase, d.expected, d.actual
+        ),
+        d.expr,
+    )
+}
@@ -0,0 +1,21 @@
+use crate::{Diagnostic, DiagnosticCode, DiagnosticsContext};
+
+pub(crate) use self::macro_argument_count_mismatch as f;
+
+// Diagnostic: macro-argument-count-mismatch
+//
+// This diagnostic is triggered if a function-like macro is called with fewer or more arguments
+// than it has parameters.
+pub(crate) fn macro_argument_count_mismatch(
+    _ctx: &DiagnosticsContext<'_>,
+    d: &hir::MacroArgumentCountMismatch,
+) -> Diagnostic {
+    Diagnostic::new_for_u_range(
+        DiagnosticCode::SpCompError("SP0016"),
+        format!(
+            "macro `{}` expects {} arguments, found {}",
+            d.name, d.expected, d.actual
+        ),
+        d.range,
+    )
+}
@@ -0,0 +1,22 @@
+use crate::{Diagnostic, DiagnosticCode, DiagnosticsContext, Severity};
+
+pub(crate) use self::mismatched_format_argument as f;
+
+// Diagnostic: mismatched-format-argument
+//
+// This diagnostic is triggered if the type of an argument of a format string does not match the
+// specifier it is formatted with, e.g an `int` formatted with `%f`.
+pub(crate) fn mismatched_format_argument(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::MismatchedFormatArgument,
+) -> Diagnostic {
+    Diagnostic::new_with_syntax_node_ptr(
+        ctx,
+        DiagnosticCode::Lint("SPLINT-mismatched-format-argument", Severity::Warning),
+        format!(
+            "`%{}` expects {}, found `{}`",
+            d.specifier, d.expected, d.actual
+        ),
+        d.expr,
+    )
+}
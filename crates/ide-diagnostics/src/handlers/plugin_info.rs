@@ -0,0 +1,119 @@
+use ide_db::{
+    Assist, AssistId, AssistKind, PluginInfo, SourceChange, TextEdit, REQUIRED_PLUGIN_INFO_FIELDS,
+};
+use line_index::{TextRange, TextSize};
+use syntax::{utils::ts_range_to_text_range, TSKind};
+
+use crate::{Diagnostic, DiagnosticCode, DiagnosticsContext, Severity};
+
+// Diagnostic: incomplete-plugin-info
+//
+// This diagnostic is triggered if the `myinfo` variable of a plugin does not set its `name`,
+// `author` or `version`.
+//
+// Diagnostic: non-literal-plugin-version
+//
+// This diagnostic is triggered if the `version` of the `myinfo` variable of a plugin is not a
+// string literal once preprocessed.
+pub(crate) fn plugin_info(ctx: &DiagnosticsContext<'_>, acc: &mut Vec<Diagnostic>) {
+    let tree = ctx.sema.parse(ctx.file_id);
+    let preprocessing_results = ctx.preprocess_file();
+    let source = preprocessing_results.preprocessed_text();
+    let Some(info) = PluginInfo::find(tree.root_node(), &source) else {
+        return;
+    };
+
+    let missing = REQUIRED_PLUGIN_INFO_FIELDS
+        .iter()
+        .copied()
+        .filter(|field| info.field(field).is_none() || info.literal(field, &source) == Some(""))
+        .collect::<Vec<_>>();
+    if !missing.is_empty() {
+        let diagnostic = Diagnostic::new_for_s_range(
+            ctx,
+            DiagnosticCode::Lint("SPLINT-incomplete-plugin-info", Severity::Warning),
+            format!(
+                "`myinfo` does not set the {} of the plugin",
+                missing
+                    .iter()
+                    .map(|field| format!("`{field}`"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            ts_range_to_text_range(&info.name.range()),
+        );
+        let fixes = fixes(ctx, &info, &missing, &source, diagnostic.u_range);
+        acc.push(diagnostic.with_fixes(fixes));
+    }
+
+    if let Some(version) = info
+        .field("version")
+        .filter(|value| TSKind::from(value) != TSKind::string_literal)
+    {
+        acc.push(Diagnostic::new_for_s_range(
+            ctx,
+            DiagnosticCode::Lint("SPLINT-non-literal-plugin-version", Severity::Warning),
+            "the version of the plugin is not a string literal",
+            ts_range_to_text_range(&version.range()),
+        ));
+    }
+}
+
+/// Add the fields which are not set after the last field of `myinfo`, with an empty value.
+fn fixes(
+    ctx: &DiagnosticsContext<'_>,
+    info: &PluginInfo,
+    missing: &[&str],
+    source: &str,
+    u_range: TextRange,
+) -> Option<Vec<Assist>> {
+    let missing = missing
+        .iter()
+        .filter(|field| info.field(field).is_none())
+        .collect::<Vec<_>>();
+    if missing.is_empty() {
+        return None;
+    }
+    let last = info
+        .constructor
+        .children(&mut info.constructor.walk())
+        .filter(|child| TSKind::from(child) == TSKind::struct_field_value)
+        .last()?;
+    let comma = last
+        .next_sibling()
+        .filter(|next| TSKind::from(next) == TSKind::anon_COMMA);
+    let indent = source[..last.start_byte()]
+        .rsplit('\n')
+        .next()
+        .filter(|it| it.trim().is_empty())?;
+    let (s_offset, insert) = match comma {
+        Some(comma) => (
+            comma.end_byte(),
+            missing
+                .iter()
+                .map(|field| format!("\n{indent}{field} = \"\","))
+                .collect::<String>(),
+        ),
+        None => (
+            last.end_byte(),
+            missing
+                .iter()
+                .map(|field| format!(",\n{indent}{field} = \"\""))
+                .collect::<String>(),
+        ),
+    };
+    let offset = ctx
+        .preprocess_file()
+        .source_map()
+        .closest_u_position(TextSize::new(s_offset as u32), false)?;
+    let mut source_change = SourceChange::default();
+    source_change.insert(ctx.file_id, TextEdit::new(TextRange::empty(offset), insert));
+
+    Some(vec![Assist {
+        id: AssistId("add_plugin_info_fields", AssistKind::QuickFix),
+        label: "Add the missing fields of `myinfo`".to_string(),
+        target: u_range,
+        source_change,
+        trigger_rename: None,
+    }])
+}
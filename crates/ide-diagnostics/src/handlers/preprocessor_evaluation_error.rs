@@ -2,16 +2,15 @@ use crate::{Diagnostic, DiagnosticCode, DiagnosticsContext};
 
 pub(crate) use self::preprocessor_evaluation_error as f;
 
-// Diagnostic: unresolved-field
+// Diagnostic: preprocessor-evaluation-error
 //
-// This diagnostic is triggered if a field does not exist on a given type.
+// This diagnostic is triggered if the condition of a preprocessor directive cannot be evaluated.
 pub(crate) fn preprocessor_evaluation_error(
-    ctx: &DiagnosticsContext<'_>,
+    _ctx: &DiagnosticsContext<'_>,
     d: &hir::PreprocessorEvaluationError,
 ) -> Diagnostic {
-    Diagnostic::new_for_s_range(
-        ctx,
-        DiagnosticCode::SpCompError("E0000"),
+    Diagnostic::new_for_u_range(
+        DiagnosticCode::SpCompError("SP0010"),
         d.text.to_owned(),
         d.range,
     )
@@ -0,0 +1,264 @@
+use fxhash::{FxHashMap, FxHashSet};
+use hir_def::{format_specifiers, unquote};
+use syntax::{utils::ts_range_to_text_range, TSKind};
+use tree_sitter::Node;
+
+use crate::{Diagnostic, DiagnosticCode, DiagnosticsContext, Severity};
+
+/// Keywords the SQL queries formatted by the plugins start with.
+const SQL_KEYWORDS: &[&str] = &["SELECT", "INSERT", "UPDATE", "DELETE", "REPLACE"];
+
+// Diagnostic: unescaped-sql-argument
+//
+// This diagnostic is triggered if a string formatted with `%s` in an SQL query built with
+// `Format` or `FormatEx` is not escaped with `SQL_EscapeString` or `Database.Escape` by the
+// function.
+//
+// Diagnostic: mismatched-sql-parameters
+//
+// This diagnostic is triggered if the parameters of a prepared statement bound by a function do
+// not match the `?` placeholders of its query.
+pub(crate) fn sql_queries(ctx: &DiagnosticsContext<'_>, acc: &mut Vec<Diagnostic>) {
+    let tree = ctx.sema.parse(ctx.file_id);
+    let preprocessing_results = ctx.preprocess_file();
+    let source = preprocessing_results.preprocessed_text();
+    for node in descendants(tree.root_node()) {
+        if !matches!(
+            TSKind::from(node),
+            TSKind::function_definition
+                | TSKind::enum_struct_method
+                | TSKind::methodmap_method
+                | TSKind::methodmap_method_constructor
+                | TSKind::methodmap_method_destructor
+        ) {
+            continue;
+        }
+        if let Some(body) = node.child_by_field_name("body") {
+            check_body(ctx, body, &source, acc);
+        }
+    }
+}
+
+/// A statement prepared with `SQL_PrepareQuery`, and the indexes of the parameters bound to it.
+struct Statement<'tree> {
+    query: Node<'tree>,
+    placeholders: usize,
+    bound: Vec<(Node<'tree>, usize)>,
+    /// Whether a parameter is bound with an index which is not an integer literal.
+    unknown_index: bool,
+}
+
+fn check_body(ctx: &DiagnosticsContext<'_>, body: Node, source: &str, acc: &mut Vec<Diagnostic>) {
+    let text = |node: Node| node.utf8_text(source.as_bytes()).unwrap_or_default();
+    let calls = descendants(body)
+        .into_iter()
+        .filter(|node| TSKind::from(node) == TSKind::call_expression)
+        .filter_map(|call| {
+            let function = call.child_by_field_name("function")?;
+            let arguments = call.child_by_field_name("arguments")?;
+            let args = arguments
+                .named_children(&mut arguments.walk())
+                .filter(|arg| TSKind::from(arg) != TSKind::comment)
+                .collect::<Vec<_>>();
+            let (name, receiver) = match TSKind::from(function) {
+                TSKind::field_access => (
+                    function.child_by_field_name("field")?,
+                    function.child_by_field_name("target"),
+                ),
+                _ => (function, None),
+            };
+            Some((call, text(name), receiver, args))
+        })
+        .collect::<Vec<_>>();
+
+    let mut escaped = FxHashSet::default();
+    let mut statements: FxHashMap<&str, Statement> = FxHashMap::default();
+    for (call, name, receiver, args) in calls.iter() {
+        match (*name, receiver) {
+            ("SQL_EscapeString", None) => escaped.extend(args.get(2).map(|arg| text(*arg))),
+            ("Escape", Some(_)) => escaped.extend(args.get(1).map(|arg| text(*arg))),
+            ("SQL_PrepareQuery", None) => {
+                let Some(query) = args
+                    .get(1)
+                    .filter(|arg| TSKind::from(*arg) == TSKind::string_literal)
+                else {
+                    continue;
+                };
+                let Some(variable) = assigned_variable(*call, source) else {
+                    continue;
+                };
+                statements.insert(
+                    variable,
+                    Statement {
+                        query: *query,
+                        placeholders: placeholders(unquote(text(*query))),
+                        bound: Vec::new(),
+                        unknown_index: false,
+                    },
+                );
+            }
+            _ => (),
+        }
+    }
+
+    for (_, name, receiver, args) in calls.iter() {
+        let (statement, index) = match (*name, receiver) {
+            ("SQL_BindParamInt" | "SQL_BindParamFloat" | "SQL_BindParamString", None) => {
+                (args.first().map(|arg| text(*arg)), args.get(1))
+            }
+            ("BindInt" | "BindFloat" | "BindString", Some(receiver)) => {
+                (Some(text(*receiver)), args.first())
+            }
+            _ => continue,
+        };
+        let (Some(statement), Some(index)) =
+            (statement.and_then(|it| statements.get_mut(it)), index)
+        else {
+            continue;
+        };
+        match text(*index).parse::<usize>() {
+            Ok(value) if TSKind::from(index) == TSKind::int_literal => {
+                statement.bound.push((*index, value))
+            }
+            _ => statement.unknown_index = true,
+        }
+    }
+
+    for statement in statements.values() {
+        if statement.bound.is_empty() || statement.unknown_index {
+            continue;
+        }
+        for (index, value) in statement.bound.iter() {
+            if *value >= statement.placeholders {
+                acc.push(Diagnostic::new_for_s_range(
+                    ctx,
+                    DiagnosticCode::Lint("SPLINT-mismatched-sql-parameters", Severity::Warning),
+                    format!(
+                        "parameter {value} is out of range, the query has {} `?` placeholders",
+                        statement.placeholders
+                    ),
+                    ts_range_to_text_range(&index.range()),
+                ));
+            }
+        }
+        let bound = statement
+            .bound
+            .iter()
+            .map(|(_, value)| *value)
+            .collect::<FxHashSet<_>>();
+        let unbound = (0..statement.placeholders)
+            .filter(|value| !bound.contains(value))
+            .count();
+        if unbound > 0 {
+            acc.push(Diagnostic::new_for_s_range(
+                ctx,
+                DiagnosticCode::Lint("SPLINT-mismatched-sql-parameters", Severity::Warning),
+                format!(
+                    "{unbound} of the {} `?` placeholders of the query are never bound",
+                    statement.placeholders
+                ),
+                ts_range_to_text_range(&statement.query.range()),
+            ));
+        }
+    }
+
+    for (_, name, receiver, args) in calls.iter() {
+        if receiver.is_some() || !matches!(*name, "Format" | "FormatEx") {
+            continue;
+        }
+        let Some(format) = args
+            .get(2)
+            .filter(|arg| TSKind::from(*arg) == TSKind::string_literal)
+            .map(|arg| unquote(text(*arg)))
+        else {
+            continue;
+        };
+        if !is_sql_query(format) {
+            continue;
+        }
+        let mut next_arg = 3;
+        for specifier in format_specifiers(format) {
+            // The arguments of the phrases are not known here.
+            if matches!(specifier, 't' | 'T') {
+                break;
+            }
+            let Some(arg) = args.get(next_arg) else {
+                break;
+            };
+            next_arg += 1;
+            if specifier != 's'
+                || TSKind::from(arg) == TSKind::string_literal
+                || escaped.contains(text(*arg))
+            {
+                continue;
+            }
+            acc.push(Diagnostic::new_for_s_range(
+                ctx,
+                DiagnosticCode::Lint("SPLINT-unescaped-sql-argument", Severity::Warning),
+                format!(
+                    "`{}` is formatted in an SQL query without being escaped",
+                    text(*arg)
+                ),
+                ts_range_to_text_range(&arg.range()),
+            ));
+        }
+    }
+}
+
+/// Name of the variable a call is assigned to, e.g `stmt` for
+/// `DBStatement stmt = SQL_PrepareQuery(...)` or `stmt = SQL_PrepareQuery(...)`.
+fn assigned_variable<'a>(call: Node, source: &'a str) -> Option<&'a str> {
+    let parent = call.parent()?;
+    let variable = match TSKind::from(parent) {
+        TSKind::variable_declaration => parent.child_by_field_name("name")?,
+        TSKind::assignment_expression => parent.child_by_field_name("left")?,
+        _ => return None,
+    };
+
+    variable.utf8_text(source.as_bytes()).ok()
+}
+
+/// Whether a string is an SQL query, i.e starts with one of the [`SQL_KEYWORDS`].
+fn is_sql_query(text: &str) -> bool {
+    let keyword = text
+        .trim_start()
+        .split(|c: char| !c.is_ascii_alphabetic())
+        .next()
+        .unwrap_or_default();
+    SQL_KEYWORDS
+        .iter()
+        .any(|it| it.eq_ignore_ascii_case(keyword))
+}
+
+/// Number of the `?` placeholders of a query, outside of its quoted strings.
+fn placeholders(query: &str) -> usize {
+    let mut quote = None;
+    let mut escaped = false;
+    let mut res = 0;
+    for c in query.chars() {
+        match (c, quote) {
+            (_, _) if escaped => escaped = false,
+            ('\\', _) => escaped = true,
+            ('\'' | '"' | '`', None) => quote = Some(c),
+            (_, Some(it)) if it == c => quote = None,
+            ('?', None) => res += 1,
+            _ => (),
+        }
+    }
+
+    res
+}
+
+/// Nodes of the subtree of a node, in preorder.
+fn descendants(node: Node) -> Vec<Node> {
+    let mut res = Vec::new();
+    let mut stack = vec![node];
+    while let Some(node) = stack.pop() {
+        res.push(node);
+        let mut children = node.children(&mut node.walk()).collect::<Vec<_>>();
+        children.reverse();
+        stack.extend(children);
+    }
+
+    res
+}
@@ -0,0 +1,18 @@
+use crate::{Diagnostic, DiagnosticCode, DiagnosticsContext};
+
+pub(crate) use self::unbalanced_condition as f;
+
+// Diagnostic: unbalanced-condition
+//
+// This diagnostic is triggered if an `#else`, `#elseif` or `#endif` directive does not match an
+// `#if` directive, or if an `#if` directive is not closed.
+pub(crate) fn unbalanced_condition(
+    _ctx: &DiagnosticsContext<'_>,
+    d: &hir::UnbalancedCondition,
+) -> Diagnostic {
+    Diagnostic::new_for_u_range(
+        DiagnosticCode::SpCompError("SP0018"),
+        d.message.clone(),
+        d.range,
+    )
+}
@@ -0,0 +1,41 @@
+use ide_db::{Assist, AssistId, AssistKind, SourceChange, TextEdit};
+
+use crate::{Diagnostic, DiagnosticCode, DiagnosticsContext};
+
+pub(crate) use self::unknown_directive as f;
+
+// Diagnostic: unknown-directive
+//
+// This diagnostic is triggered if a preprocessor directive is not supported by SourcePawn.
+pub(crate) fn unknown_directive(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::UnknownDirective,
+) -> Diagnostic {
+    let message = match d.suggestion {
+        // Directives from other languages are replaced by a construct, e.g `#if defined`.
+        Some(suggestion) if suggestion.contains(' ') => {
+            format!("`{}` is not supported, use `{suggestion}` instead", d.name)
+        }
+        Some(suggestion) => format!(
+            "unknown preprocessor directive `{}`, did you mean `{suggestion}`?",
+            d.name
+        ),
+        None => format!("unknown preprocessor directive `{}`", d.name),
+    };
+    Diagnostic::new_for_u_range(DiagnosticCode::SpCompError("SP0013"), message, d.range)
+        .with_fixes(fixes(ctx, d))
+}
+
+fn fixes(ctx: &DiagnosticsContext<'_>, d: &hir::UnknownDirective) -> Option<Vec<Assist>> {
+    let suggestion = d.suggestion?;
+    let mut source_change = SourceChange::default();
+    source_change.insert(ctx.file_id, TextEdit::new(d.range, suggestion.to_string()));
+
+    Some(vec![Assist {
+        id: AssistId("replace_unknown_directive", AssistKind::QuickFix),
+        label: format!("Replace with `{suggestion}`"),
+        target: d.range,
+        source_change,
+        trigger_rename: None,
+    }])
+}
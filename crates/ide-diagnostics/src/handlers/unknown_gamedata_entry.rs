@@ -0,0 +1,29 @@
+use crate::{Diagnostic, DiagnosticCode, DiagnosticsContext, Severity};
+
+pub(crate) use self::unknown_gamedata_entry as f;
+
+// Diagnostic: unknown-gamedata-entry
+//
+// This diagnostic is triggered if the offset, key, address or signature read from a gamedata
+// file, e.g with `GameConfGetOffset`, is not defined by the gamedata files loaded by the project.
+pub(crate) fn unknown_gamedata_entry(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::UnknownGamedataEntry,
+) -> Diagnostic {
+    let files = d
+        .files
+        .iter()
+        .map(|file| format!("`{file}`"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    Diagnostic::new_with_syntax_node_ptr(
+        ctx,
+        DiagnosticCode::Lint("SPLINT-unknown-gamedata-entry", Severity::Warning),
+        format!(
+            "{} `{}` is not defined by {files}",
+            d.section.entry_name(),
+            d.name
+        ),
+        d.expr,
+    )
+}
@@ -0,0 +1,19 @@
+use crate::{Diagnostic, DiagnosticCode, DiagnosticsContext, Severity};
+
+pub(crate) use self::unknown_phrase as f;
+
+// Diagnostic: unknown-phrase
+//
+// This diagnostic is triggered if a phrase formatted with `%t` or `%T` is not defined by any of
+// the translation files.
+pub(crate) fn unknown_phrase(ctx: &DiagnosticsContext<'_>, d: &hir::UnknownPhrase) -> Diagnostic {
+    Diagnostic::new_with_syntax_node_ptr(
+        ctx,
+        DiagnosticCode::Lint("SPLINT-unknown-phrase", Severity::Warning),
+        format!(
+            "phrase `{}` is not defined by any translation file",
+            d.phrase
+        ),
+        d.expr,
+    )
+}
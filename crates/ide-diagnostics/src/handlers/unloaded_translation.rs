@@ -0,0 +1,83 @@
+use base_db::{SourceDatabase, SourceDatabaseExt};
+use ide_db::{Assist, AssistId, AssistKind, SourceChange, TextEdit};
+use line_index::TextRange;
+use syntax::{utils::ts_range_to_text_range, TSKind};
+
+use crate::{Diagnostic, DiagnosticCode, DiagnosticsContext, Severity};
+
+pub(crate) use self::unloaded_translation as f;
+
+// Diagnostic: unloaded-translation
+//
+// This diagnostic is triggered if the translation file of a phrase formatted with `%t` or `%T`
+// is never loaded with `LoadTranslations` by the project.
+pub(crate) fn unloaded_translation(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::UnloadedTranslation,
+) -> Diagnostic {
+    let diagnostic = Diagnostic::new_with_syntax_node_ptr(
+        ctx,
+        DiagnosticCode::Lint("SPLINT-unloaded-translation", Severity::Warning),
+        format!(
+            "phrase `{}` is defined by `{}`, which is never loaded with `LoadTranslations`",
+            d.phrase, d.file
+        ),
+        d.expr,
+    );
+    let fixes = fixes(ctx, d, diagnostic.u_range);
+
+    diagnostic.with_fixes(fixes)
+}
+
+/// Load the translation file at the start of `OnPluginStart`.
+fn fixes(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::UnloadedTranslation,
+    u_range: TextRange,
+) -> Option<Vec<Assist>> {
+    let db = ctx.sema.db;
+    let file_ids = db
+        .projet_subgraph(ctx.file_id)
+        .map(|subgraph| subgraph.file_ids().into_iter().collect::<Vec<_>>())
+        .unwrap_or_else(|| vec![ctx.file_id]);
+    let (file_id, offset) = file_ids.into_iter().find_map(|file_id| {
+        let tree = ctx.sema.parse(file_id);
+        let preprocessing_results = ctx.sema.preprocess_file(file_id);
+        let source = preprocessing_results.preprocessed_text();
+        let root = tree.root_node();
+        let body = root
+            .children(&mut root.walk())
+            .filter(|child| TSKind::from(child) == TSKind::function_definition)
+            .find(|function| {
+                function
+                    .child_by_field_name("name")
+                    .and_then(|name| name.utf8_text(source.as_bytes()).ok())
+                    == Some("OnPluginStart")
+            })?
+            .child_by_field_name("body")?;
+        let brace = body.child(0)?;
+        let range = preprocessing_results
+            .source_map()
+            .closest_u_range(ts_range_to_text_range(&brace.range()))?;
+        Some((file_id, range.end()))
+    })?;
+
+    let text = db.file_text(file_id);
+    let indent = if text.contains("\n\t") { "\t" } else { "    " };
+    let mut source_change = SourceChange::default();
+    source_change.insert(
+        file_id,
+        TextEdit::new(
+            TextRange::empty(offset),
+            format!("\n{indent}LoadTranslations(\"{}\");", d.file),
+        ),
+    );
+
+    Some(vec![Assist {
+        id: AssistId("load_translations", AssistKind::QuickFix),
+        label: format!("Load `{}` in `OnPluginStart`", d.file),
+        target: u_range,
+        source_change,
+        trigger_rename: None,
+    }])
+}
@@ -20,7 +20,12 @@ pub(crate) fn unresolved_constructor(
         }
         None => format!("methodmap `{}` does not exist", d.methodmap),
     };
-    Diagnostic::new_with_syntax_node_ptr(ctx, DiagnosticCode::SpCompError("E0000"), message, d.expr)
+    Diagnostic::new_with_syntax_node_ptr(
+        ctx,
+        DiagnosticCode::SpCompError("SP0006"),
+        message,
+        d.expr,
+    )
     // .with_fixes(fixes(ctx, d))
     // .experimental()
 }
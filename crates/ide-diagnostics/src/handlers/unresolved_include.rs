@@ -11,7 +11,7 @@ pub(crate) fn unresolved_include(
 ) -> Diagnostic {
     Diagnostic::new_for_s_range(
         ctx,
-        DiagnosticCode::SpCompError("E0000"),
+        DiagnosticCode::SpCompError("SP0005"),
         format!("file `{}` was not found", d.path),
         d.range,
     )
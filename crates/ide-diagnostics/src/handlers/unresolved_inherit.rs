@@ -14,5 +14,10 @@ pub(crate) fn unresolved_inherit(
     } else {
         format!("methodmap `{}` does not exist", d.inherit)
     };
-    Diagnostic::new_with_syntax_node_ptr(ctx, DiagnosticCode::SpCompError("E0000"), message, d.expr)
+    Diagnostic::new_with_syntax_node_ptr(
+        ctx,
+        DiagnosticCode::SpCompError("SP0009"),
+        message,
+        d.expr,
+    )
 }
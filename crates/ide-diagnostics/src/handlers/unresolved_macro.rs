@@ -6,12 +6,11 @@ pub(crate) use self::unresolved_macro as f;
 //
 // This diagnostic is triggered if a macro is unresolved in a preprocessing directive (#if).
 pub(crate) fn unresolved_macro(
-    ctx: &DiagnosticsContext<'_>,
+    _ctx: &DiagnosticsContext<'_>,
     d: &hir::UnresolvedMacro,
 ) -> Diagnostic {
-    Diagnostic::new_for_s_range(
-        ctx,
-        DiagnosticCode::SpCompError("E0000"),
+    Diagnostic::new_for_u_range(
+        DiagnosticCode::SpCompError("SP0011"),
         format!("no macro `{}` found", d.name),
         d.range,
     )
@@ -11,7 +11,7 @@ pub(crate) fn unresolved_named_arg(
 ) -> Diagnostic {
     Diagnostic::new_with_syntax_node_ptr(
         ctx,
-        DiagnosticCode::SpCompError("E0000"),
+        DiagnosticCode::SpCompError("SP0007"),
         format!("no parameter `{}` found for `{}`", d.name, d.callee),
         d.expr,
     )
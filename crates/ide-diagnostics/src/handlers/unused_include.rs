@@ -0,0 +1,38 @@
+use base_db::SourceDatabase;
+use ide_db::{Assist, AssistId, AssistKind, ExperimentalFlag, SourceChange, TextEdit};
+
+use crate::{lines_range, Diagnostic, DiagnosticCode, DiagnosticsContext, Severity};
+
+pub(crate) use self::unused_include as f;
+
+// Diagnostic: unused-include
+//
+// This diagnostic is triggered if none of the symbols and macros provided by an include are
+// referenced by the including file.
+pub(crate) fn unused_include(ctx: &DiagnosticsContext<'_>, d: &hir::UnusedInclude) -> Diagnostic {
+    Diagnostic::new_for_u_range(
+        DiagnosticCode::Lint("SPLINT-unused-include", Severity::WeakWarning),
+        format!("`{}` is never used", d.path),
+        d.range,
+    )
+    .with_unused(true)
+    .with_fixes(fixes(ctx, d))
+    .experimental(ExperimentalFlag::UnusedIncludes)
+}
+
+fn fixes(ctx: &DiagnosticsContext<'_>, d: &hir::UnusedInclude) -> Option<Vec<Assist>> {
+    let text = ctx.sema.db.file_text(ctx.file_id);
+    let mut source_change = SourceChange::default();
+    source_change.insert(
+        ctx.file_id,
+        TextEdit::new(lines_range(&text, d.range), String::new()),
+    );
+
+    Some(vec![Assist {
+        id: AssistId("remove_unused_include", AssistKind::QuickFix),
+        label: "Remove the unused include".to_string(),
+        target: d.range,
+        source_change,
+        trigger_rename: None,
+    }])
+}
@@ -0,0 +1,42 @@
+use base_db::SourceDatabase;
+use ide_db::{Assist, AssistId, AssistKind, SourceChange, TextEdit};
+use line_index::TextRange;
+
+use crate::{lines_range, Diagnostic, DiagnosticCode, DiagnosticsContext, Severity};
+
+pub(crate) use self::unused_macro as f;
+
+// Diagnostic: unused-macro
+//
+// This diagnostic is triggered if a macro is never expanded nor checked with `defined` in the
+// project.
+pub(crate) fn unused_macro(ctx: &DiagnosticsContext<'_>, d: &hir::UnusedMacro) -> Diagnostic {
+    let diagnostic = Diagnostic::new_with_syntax_node_ptr(
+        ctx,
+        DiagnosticCode::Lint("SPLINT-unused-macro", Severity::WeakWarning),
+        format!("macro `{}` is never used", d.name),
+        d.define,
+    );
+    let fixes = fixes(ctx, d, diagnostic.u_range);
+
+    diagnostic.with_unused(true).with_fixes(fixes)
+}
+
+fn fixes(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::UnusedMacro,
+    u_range: TextRange,
+) -> Option<Vec<Assist>> {
+    let text = ctx.sema.db.file_text(ctx.file_id);
+    let range = lines_range(&text, u_range);
+    let mut source_change = SourceChange::default();
+    source_change.insert(ctx.file_id, TextEdit::new(range, String::new()));
+
+    Some(vec![Assist {
+        id: AssistId("remove_unused_macro", AssistKind::QuickFix),
+        label: format!("Remove the definition of `{}`", d.name),
+        target: u_range,
+        source_change,
+        trigger_rename: None,
+    }])
+}
@@ -0,0 +1,15 @@
+use crate::{Diagnostic, DiagnosticCode, DiagnosticsContext, Severity};
+
+pub(crate) use self::use_after_undef as f;
+
+// Diagnostic: use-after-undef
+//
+// This diagnostic is triggered if the name of a macro is used after the macro was undefined with
+// `#undef` in the same file, and not defined again.
+pub(crate) fn use_after_undef(_ctx: &DiagnosticsContext<'_>, d: &hir::UseAfterUndef) -> Diagnostic {
+    Diagnostic::new_for_u_range(
+        DiagnosticCode::Lint("SPLINT-use-after-undef", Severity::Warning),
+        format!("macro `{}` is used after being undefined", d.name),
+        d.range,
+    )
+}
@@ -1,18 +1,27 @@
-use base_db::Tree;
-use fxhash::FxHashSet;
-use hir::{AnyDiagnostic, Semantics};
+use std::{iter, sync::Arc};
+
+use base_db::{SourceDatabase, SourceDatabaseExt, Tree};
+use fxhash::{FxHashMap, FxHashSet};
+use hir::{AnyDiagnostic, HasSource, Semantics};
 use hir_def::{InFile, NodePtr};
-use ide_db::RootDatabase;
+use ide_db::{Assist, ExperimentalFlag, ExperimentalFlags, RootDatabase, SourceChange, TextEdit};
 use line_index::{TextRange, TextSize};
+use preprocessor::{db::PreprocDatabase, PreprocessingResult};
 use queries::ERROR_QUERY;
 use streaming_iterator::StreamingIterator;
 use syntax::utils::ts_range_to_text_range;
 use tree_sitter::QueryCursor;
 use vfs::FileId;
 
+mod explain;
 mod handlers;
 mod queries;
 
+pub use explain::{explain, DIAGNOSTIC_CODES};
+
+/// Base url of the documentation of the diagnostics.
+const DIAGNOSTICS_DOCS_URL: &str = "https://sarrus1.github.io/sourcepawn-studio/docs/diagnostics";
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum DiagnosticCode {
     SpCompError(&'static str),
@@ -28,6 +37,15 @@ impl DiagnosticCode {
             | DiagnosticCode::Lint(r, _) => r,
         }
     }
+
+    /// Url of the documentation of the diagnostic.
+    pub fn url(&self) -> String {
+        format!(
+            "{}#{}",
+            DIAGNOSTICS_DOCS_URL,
+            self.as_str().to_ascii_lowercase()
+        )
+    }
 }
 
 #[derive(Debug)]
@@ -37,11 +55,23 @@ pub struct Diagnostic {
     pub u_range: TextRange,
     pub severity: Severity,
     pub unused: bool,
-    pub experimental: bool,
-    // pub fixes: Option<Vec<Assist>>,
+    pub deprecated: bool,
+    /// Flag gating the diagnostic, if it comes from an experimental analysis.
+    pub experimental: Option<ExperimentalFlag>,
+    pub fixes: Option<Vec<Assist>>,
+    /// Notes pointing at other locations, e.g the macros the diagnostic was expanded from.
+    pub related: Vec<RelatedInformation>,
     // The node that will be affected by `#[allow]` and similar attributes.
 }
 
+/// A location related to a [`Diagnostic`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelatedInformation {
+    pub file_id: FileId,
+    pub u_range: TextRange,
+    pub message: String,
+}
+
 impl Diagnostic {
     fn new_with_syntax_node_ptr(
         ctx: &DiagnosticsContext<'_>,
@@ -63,15 +93,20 @@ impl Diagnostic {
         message: impl Into<String>,
         s_range: TextRange,
     ) -> Self {
-        let preprocessing_results = ctx.sema.preprocess_file(ctx.file_id);
+        let preprocessing_results = ctx.preprocess_file();
 
-        Diagnostic::new_for_u_range(
+        let mut res = Diagnostic::new_for_u_range(
             code,
             message,
             preprocessing_results
                 .source_map()
                 .closest_u_range_always(s_range),
-        )
+        );
+        // The macros of the other configurations are not the ones the semantics know about.
+        if ctx.configuration.is_none() {
+            res.related = macro_provenance(ctx, &preprocessing_results, s_range);
+        }
+        res
     }
 
     fn new_for_u_range(
@@ -89,25 +124,76 @@ impl Diagnostic {
                 DiagnosticCode::Lint(_, s) => s,
             },
             unused: false,
-            experimental: false,
+            deprecated: false,
+            experimental: None,
+            fixes: None,
+            related: Vec::new(),
         }
     }
 
-    #[allow(unused)]
-    fn experimental(mut self) -> Diagnostic {
-        self.experimental = true;
+    fn experimental(mut self, flag: ExperimentalFlag) -> Diagnostic {
+        self.experimental = Some(flag);
         self
     }
 
-    // fn with_fixes(mut self, fixes: Option<Vec<Assist>>) -> Diagnostic {
-    //     self.fixes = fixes;
-    //     self
-    // }
+    fn with_fixes(mut self, fixes: Option<Vec<Assist>>) -> Diagnostic {
+        self.fixes = fixes;
+        self
+    }
 
     fn with_unused(mut self, unused: bool) -> Diagnostic {
         self.unused = unused;
         self
     }
+
+    fn with_deprecated(mut self, deprecated: bool) -> Diagnostic {
+        self.deprecated = deprecated;
+        self
+    }
+}
+
+/// Notes of a diagnostic found in the expansion of a macro call, which is reported at the call.
+///
+/// The first note points at the definition of the macro which was called, the next ones at the
+/// definitions of the macros it expanded.
+fn macro_provenance(
+    ctx: &DiagnosticsContext<'_>,
+    preprocessing_results: &PreprocessingResult,
+    s_range: TextRange,
+) -> Vec<RelatedInformation> {
+    let Some(symbol) = preprocessing_results
+        .source_map()
+        .expanded_symbol_from_s_pos(s_range.start())
+    else {
+        return Vec::new();
+    };
+    if symbol.in_condition() || !symbol.expanded_range().contains_range(s_range) {
+        return Vec::new();
+    }
+    iter::once((symbol.file_id(), symbol.idx()))
+        .chain(symbol.nested_macros().iter().copied())
+        .filter_map(|(file_id, idx)| {
+            let macro_ = ctx.sema.macro_from_idx(file_id, idx)?;
+            let tree = ctx.sema.parse(file_id);
+            let name_node = macro_
+                .source(ctx.sema.db, &tree)?
+                .value
+                .child_by_field_name("name")?;
+            let u_range = ctx
+                .sema
+                .preprocess_file(file_id)
+                .source_map()
+                .closest_u_range_always(ts_range_to_text_range(&name_node.range()));
+            Some(RelatedInformation {
+                file_id,
+                u_range,
+                message: format!(
+                    "in expansion of macro `{}` defined here",
+                    macro_.name(ctx.sema.db)
+                ),
+            })
+        })
+        .collect()
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -122,12 +208,29 @@ struct DiagnosticsContext<'a> {
     config: &'a DiagnosticsConfig,
     sema: Semantics<'a, RootDatabase>,
     file_id: FileId,
+    /// Index of the [macro configuration](SourceDatabase::macro_configurations) the diagnostics
+    /// are computed in, `None` for the default one.
+    configuration: Option<usize>,
+}
+
+impl DiagnosticsContext<'_> {
+    /// Preprocessing results of the file, in the configuration of the context.
+    fn preprocess_file(&self) -> Arc<PreprocessingResult> {
+        match self.configuration {
+            Some(configuration) => self
+                .sema
+                .db
+                .preprocess_file_in_configuration(self.file_id, configuration),
+            None => self.sema.preprocess_file(self.file_id),
+        }
+    }
 }
 
 pub struct DiagnosticsConfig {
     /// Whether native diagnostics are enabled.
     pub enabled: bool,
-    pub disable_experimental: bool,
+    /// Experimental analyses whose diagnostics are reported.
+    pub experimental: ExperimentalFlags,
     pub disabled: FxHashSet<String>,
 }
 
@@ -146,10 +249,18 @@ pub fn diagnostics(
         config,
         sema,
         file_id,
+        configuration: None,
     };
 
     syntax_error_diagnostics(&ctx, &source, &tree, &mut res);
+    configuration_diagnostics(&ctx, &mut res);
+    handlers::duplicate_convar::duplicate_convars(&ctx, &mut res);
+    handlers::plugin_info::plugin_info(&ctx, &mut res);
+    handlers::sql_queries::sql_queries(&ctx, &mut res);
 
+    // The macros of an include directory are defined for the plugins including it, which are
+    // not part of the workspace.
+    let is_include_dir = db.source_root(db.file_source_root(file_id)).is_include_dir;
     let mut diags = Vec::new();
     file.diagnostics(db, &mut diags);
     for diag in diags {
@@ -164,20 +275,170 @@ pub fn diagnostics(
             AnyDiagnostic::IncorrectNumberOfArguments(d) => {
                 handlers::incorrect_number_of_arguments::f(&ctx, &d)
             }
+            AnyDiagnostic::IncorrectNumberOfPhraseArguments(d) => {
+                handlers::incorrect_number_of_phrase_arguments::f(&ctx, &d)
+            }
+            AnyDiagnostic::UnknownPhrase(d) => handlers::unknown_phrase::f(&ctx, &d),
+            AnyDiagnostic::UnknownGamedataEntry(d) => handlers::unknown_gamedata_entry::f(&ctx, &d),
+            AnyDiagnostic::UnloadedTranslation(d) => handlers::unloaded_translation::f(&ctx, &d),
+            AnyDiagnostic::IncorrectNumberOfFormatArguments(d) => {
+                handlers::incorrect_number_of_format_arguments::f(&ctx, &d)
+            }
+            AnyDiagnostic::MismatchedFormatArgument(d) => {
+                handlers::mismatched_format_argument::f(&ctx, &d)
+            }
             AnyDiagnostic::UnresolvedInherit(d) => handlers::unresolved_inherit::f(&ctx, &d),
             AnyDiagnostic::PreprocessorEvaluationError(d) => {
                 handlers::preprocessor_evaluation_error::f(&ctx, &d)
             }
             AnyDiagnostic::UnresolvedMacro(d) => handlers::unresolved_macro::f(&ctx, &d),
+            AnyDiagnostic::AssertionFailed(d) => handlers::assertion_failed::f(&ctx, &d),
+            AnyDiagnostic::UnbalancedCondition(d) => handlers::unbalanced_condition::f(&ctx, &d),
+            AnyDiagnostic::MacroArgumentCountMismatch(d) => {
+                handlers::macro_argument_count_mismatch::f(&ctx, &d)
+            }
             AnyDiagnostic::InactiveCode(d) => handlers::inactive_code::f(&ctx, &d),
+            AnyDiagnostic::InactiveDirective(d) => handlers::inactive_directive::f(&ctx, &d),
             AnyDiagnostic::InvalidUseOfThis(d) => handlers::invalid_use_of_this::f(&ctx, &d),
+            AnyDiagnostic::DeprecatedCall(d) => handlers::deprecated_call::f(&ctx, &d),
+            AnyDiagnostic::UnknownDirective(d) => handlers::unknown_directive::f(&ctx, &d),
+            AnyDiagnostic::CircularInclude(d) => handlers::circular_include::f(&ctx, &d),
+            AnyDiagnostic::IncludeLimitExceeded(d) => handlers::include_limit_exceeded::f(&ctx, &d),
+            AnyDiagnostic::UnusedMacro(_) if is_include_dir => continue,
+            AnyDiagnostic::UnusedMacro(d) => handlers::unused_macro::f(&ctx, &d),
+            AnyDiagnostic::UnusedInclude(d) => handlers::unused_include::f(&ctx, &d),
+            AnyDiagnostic::UseAfterUndef(d) => handlers::use_after_undef::f(&ctx, &d),
         };
         res.push(d);
     }
+    res.retain(|d| {
+        !config.disabled.contains(d.code.as_str())
+            && d.experimental
+                .map_or(true, |flag| config.experimental.is_enabled(flag))
+    });
+
+    res
+}
+
+/// Fixes of many diagnostics, merged in a single [`SourceChange`].
+#[derive(Debug, Default)]
+pub struct FixAll {
+    pub source_change: SourceChange,
+    /// Number of fixes applied for each diagnostic code.
+    pub fixes: FxHashMap<&'static str, usize>,
+}
+
+impl FixAll {
+    /// Human readable summary of the fixes, e.g `Applied 3 fixes in 2 files (SP0013: 3)`.
+    pub fn summary(&self) -> String {
+        let total: usize = self.fixes.values().sum();
+        if total == 0 {
+            return "No fixes to apply".to_string();
+        }
+        let files = self.source_change.source_file_edits.len();
+        let mut codes = self.fixes.iter().collect::<Vec<_>>();
+        codes.sort_unstable();
+        format!(
+            "Applied {total} fix{} in {files} file{} ({})",
+            if total == 1 { "" } else { "es" },
+            if files == 1 { "" } else { "s" },
+            codes
+                .into_iter()
+                .map(|(code, count)| format!("{code}: {count}"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+}
+
+/// Apply the first fix of every diagnostic of the given files.
+///
+/// Fixes whose edits overlap the edits of a previous fix are skipped, they can be applied by
+/// running the fixes again.
+///
+/// # Arguments
+/// * `db` - Database to compute the diagnostics with.
+/// * `config` - [DiagnosticsConfig](DiagnosticsConfig) of the diagnostics.
+/// * `code` - Only apply the fixes of the diagnostics with this code, if any.
+/// * `file_ids` - Files to fix.
+pub fn fix_all(
+    db: &RootDatabase,
+    config: &DiagnosticsConfig,
+    code: Option<&str>,
+    file_ids: &[FileId],
+) -> FixAll {
+    let mut res = FixAll::default();
+    // The fixes of a file may edit other files, e.g its main file, which are tracked too.
+    let mut edited_ranges: FxHashMap<FileId, Vec<TextRange>> = FxHashMap::default();
+    for file_id in file_ids {
+        for diagnostic in diagnostics(db, config, *file_id) {
+            if code.is_some_and(|code| code != diagnostic.code.as_str()) {
+                continue;
+            }
+            let Some(fix) = diagnostic.fixes.into_iter().flatten().next() else {
+                continue;
+            };
+            let is_snippet = fix.source_change.is_snippet;
+            let edits: Vec<(FileId, TextEdit)> = fix
+                .source_change
+                .source_file_edits
+                .into_iter()
+                .flat_map(|(file_id, edits)| edits.into_iter().map(move |edit| (file_id, edit)))
+                .collect();
+            if edits.iter().any(|(file_id, edit)| {
+                edited_ranges.get(file_id).is_some_and(|ranges| {
+                    ranges
+                        .iter()
+                        .any(|range| range.intersect(*edit.range()).is_some())
+                })
+            }) {
+                continue;
+            }
+            for (file_id, edit) in edits {
+                edited_ranges
+                    .entry(file_id)
+                    .or_default()
+                    .push(*edit.range());
+                res.source_change.insert(file_id, edit);
+            }
+            res.source_change.is_snippet |= is_snippet;
+            *res.fixes.entry(diagnostic.code.as_str()).or_default() += 1;
+        }
+    }
 
     res
 }
 
+/// Name of a file with its extension, e.g `foo.inc`, or its id if it has no path.
+fn file_name(db: &RootDatabase, file_id: FileId) -> String {
+    db.source_root(db.file_source_root(file_id))
+        .path_for_file(&file_id)
+        .and_then(|path| path.name_and_extension())
+        .map(|(name, ext)| match ext {
+            Some(ext) => format!("{name}.{ext}"),
+            None => name.to_string(),
+        })
+        .unwrap_or_else(|| file_id.to_string())
+}
+
+/// Extend a range to the whole lines it spans, including the line break of its last line.
+///
+/// This is the range to delete to remove a directive or a definition from a file.
+fn lines_range(text: &str, range: TextRange) -> TextRange {
+    let (start, end) = (usize::from(range.start()), usize::from(range.end()));
+    let start = text[..start].rfind('\n').map_or(0, |idx| idx + 1);
+    // The range may already include its line break.
+    let end = if text[..end].ends_with('\n') {
+        end
+    } else {
+        text[end..]
+            .find('\n')
+            .map_or(text.len(), |idx| end + idx + 1)
+    };
+
+    TextRange::new(TextSize::new(start as u32), TextSize::new(end as u32))
+}
+
 /// Capture all the syntax errors of a document and add them to its Local Diagnostics.
 /// Overrides all previous Local Diagnostics.
 ///
@@ -201,7 +462,7 @@ fn syntax_error_diagnostics(
             ts_error_to_diagnostic(ctx, c.node).unwrap_or_else(|| {
                 Diagnostic::new_for_s_range(
                     ctx,
-                    DiagnosticCode::SpCompError("syntax-error"),
+                    DiagnosticCode::SpCompError("SP0001"),
                     c.node.to_sexp(),
                     ts_range_to_text_range(&c.node.range()),
                 )
@@ -212,6 +473,50 @@ fn syntax_error_diagnostics(
     missing_nodes(ctx, tree.root_node(), diagnostics);
 }
 
+/// Capture the syntax errors of the code which is only active in the additional
+/// [macro configurations](SourceDatabase::macro_configurations), e.g in the `#else` branch of a
+/// `#if defined DEBUG`, and add the ones which are not already reported.
+///
+/// The message of the diagnostics is suffixed with the definitions of their configuration.
+fn configuration_diagnostics(ctx: &DiagnosticsContext, diagnostics: &mut Vec<Diagnostic>) {
+    let configurations = ctx.sema.db.macro_configurations();
+    if configurations.is_empty() {
+        return;
+    }
+    let mut reported: FxHashSet<(&'static str, TextRange, String)> = diagnostics
+        .iter()
+        .map(|d| (d.code.as_str(), d.u_range, d.message.clone()))
+        .collect();
+    let mut parser = tree_sitter::Parser::new();
+    if parser
+        .set_language(&tree_sitter_sourcepawn::language())
+        .is_err()
+    {
+        return;
+    }
+    for (configuration, definitions) in configurations.iter().enumerate() {
+        let ctx = DiagnosticsContext {
+            config: ctx.config,
+            sema: Semantics::new(ctx.sema.db),
+            file_id: ctx.file_id,
+            configuration: Some(configuration),
+        };
+        let source = ctx.preprocess_file().preprocessed_text();
+        let Some(tree) = parser.parse(source.as_bytes(), None) else {
+            continue;
+        };
+        let mut res = Vec::new();
+        syntax_error_diagnostics(&ctx, &source, &tree.into(), &mut res);
+        for mut d in res {
+            if !reported.insert((d.code.as_str(), d.u_range, d.message.clone())) {
+                continue;
+            }
+            d.message = format!("{} (with `{}`)", d.message, definitions.join(", "));
+            diagnostics.push(d);
+        }
+    }
+}
+
 /// Capture all the missing nodes of a document and add them to its Local Diagnostics.
 ///
 /// # Arguments
@@ -227,7 +532,7 @@ fn missing_nodes(
     if node.is_missing() {
         let diagnostic = Diagnostic::new_for_s_range(
             ctx,
-            DiagnosticCode::SpCompError("missing-node"),
+            DiagnosticCode::SpCompError("SP0002"),
             format!("expected `{}`", node.kind()),
             ts_range_to_text_range(&node.range()),
         );
@@ -251,9 +556,31 @@ fn ts_error_to_diagnostic(ctx: &DiagnosticsContext, node: tree_sitter::Node) ->
         .collect();
     Diagnostic::new_for_s_range(
         ctx,
-        DiagnosticCode::SpCompError("syntax-error"),
+        DiagnosticCode::SpCompError("SP0001"),
         format!("expected {:?}", expected.join(", ")),
-        ts_range_to_text_range(&node.range()),
+        error_range(node),
     )
     .into()
 }
+
+/// Range of the code a tree-sitter error node failed to parse.
+///
+/// While recovering from a syntax error, the parser may wrap the valid statements which follow
+/// the broken one in the error node. The range stops before the first of them, so that the
+/// diagnostic is only reported on the broken statement.
+fn error_range(node: tree_sitter::Node) -> TextRange {
+    let range = ts_range_to_text_range(&node.range());
+    let start_row = node.start_position().row;
+    let mut prev: Option<tree_sitter::Node> = None;
+    for child in node.children(&mut node.walk()) {
+        if child.start_position().row > start_row && child.is_named() && !child.has_error() {
+            return match prev {
+                Some(prev) => TextRange::new(range.start(), TextSize::new(prev.end_byte() as u32)),
+                None => range,
+            };
+        }
+        prev = Some(child);
+    }
+
+    range
+}
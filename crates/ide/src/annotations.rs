@@ -0,0 +1,117 @@
+use base_db::{FilePosition, FileRange, SourceDatabase};
+use hir_def::DefDatabase;
+use line_index::{TextRange, TextSize};
+use preprocessor::db::PreprocDatabase;
+use syntax::{utils::ts_range_to_text_range, TSKind};
+use vfs::FileId;
+
+use crate::{references, RootDatabase};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AnnotationConfig {
+    pub annotate_references: bool,
+    pub annotate_compile: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Annotation {
+    /// User range the annotation is displayed above.
+    pub range: TextRange,
+    pub kind: AnnotationKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AnnotationKind {
+    /// References of the symbol at `pos`, computed when the annotation is resolved.
+    HasReferences {
+        pos: FilePosition,
+        data: Option<Vec<FileRange>>,
+    },
+    /// Compile the main file of the project.
+    Compile { file_id: FileId },
+}
+
+// Feature: Annotations
+//
+// Shows code lenses above the items of the file:
+//
+// - `N references` above the global functions and methodmaps, which opens their references,
+// - `▶ Compile` above the `OnPluginStart` forward of the main file of a plugin, or at the top of
+//   the file if it does not implement it, which compiles the plugin with spcomp.
+//
+// The number of references is only computed when the lens is displayed.
+pub(crate) fn annotations(
+    db: &RootDatabase,
+    config: &AnnotationConfig,
+    file_id: FileId,
+) -> Vec<Annotation> {
+    let tree = db.parse(file_id);
+    let source = db.preprocessed_text(file_id);
+    let preprocessing_results = db.preprocess_file(file_id);
+    let source_map = preprocessing_results.source_map();
+    let is_main_file = db
+        .projet_subgraph(file_id)
+        .is_some_and(|subgraph| subgraph.root.file_id == file_id);
+
+    let mut res = Vec::new();
+    let mut plugin_start = None;
+    let root = tree.root_node();
+    for child in root.children(&mut root.walk()) {
+        if !matches!(
+            TSKind::from(child),
+            TSKind::function_definition | TSKind::methodmap
+        ) {
+            continue;
+        }
+        let Some(name) = child.child_by_field_name("name") else {
+            continue;
+        };
+        // Items expanded from a macro are not written in the file.
+        let Some(range) = source_map.closest_u_range(ts_range_to_text_range(&name.range())) else {
+            continue;
+        };
+        if TSKind::from(child) == TSKind::function_definition
+            && name.utf8_text(source.as_bytes()).ok() == Some("OnPluginStart")
+        {
+            plugin_start = Some(range);
+        }
+        if config.annotate_references {
+            res.push(Annotation {
+                range,
+                kind: AnnotationKind::HasReferences {
+                    pos: FilePosition {
+                        file_id,
+                        offset: range.start(),
+                    },
+                    data: None,
+                },
+            });
+        }
+    }
+
+    if config.annotate_compile && is_main_file {
+        res.push(Annotation {
+            range: plugin_start.unwrap_or_else(|| TextRange::empty(TextSize::new(0))),
+            kind: AnnotationKind::Compile { file_id },
+        });
+    }
+    res.sort_by_key(|annotation| annotation.range.start());
+
+    res
+}
+
+/// Compute the data of an annotation returned by [`annotations`].
+pub(crate) fn resolve_annotation(db: &RootDatabase, mut annotation: Annotation) -> Annotation {
+    if let AnnotationKind::HasReferences { pos, data } = &mut annotation.kind {
+        *data = references::references(db, *pos).map(|refs| {
+            refs.into_iter()
+                // The definition is not a reference.
+                .filter(|frange| {
+                    frange.file_id != pos.file_id || !frange.range.contains_inclusive(pos.offset)
+                })
+                .collect()
+        });
+    }
+
+    annotation
+}
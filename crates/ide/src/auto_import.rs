@@ -0,0 +1,142 @@
+use base_db::{FileRange, IncludeKind, SourceDatabase, SourceDatabaseExt};
+use hir::Semantics;
+use ide_db::{Assist, AssistId, AssistKind, RootDatabase, SourceChange, SymbolKind, TextEdit};
+use itertools::Itertools;
+use line_index::{TextRange, TextSize};
+use syntax::{utils::ts_range_to_text_range, TSKind};
+use vfs::FileId;
+
+use crate::symbols::symbols;
+
+// Feature: Auto Import
+//
+// Offers to include the files of the include directories which define the unresolved symbol
+// under the cursor, e.g `#include <tf2>` for `TF2_GetPlayerClass`.
+//
+// The directive is added after the last `#include <...>` directive of the file, or at the top of
+// the file if it has none.
+pub(crate) fn auto_import(db: &RootDatabase, frange: FileRange) -> Vec<Assist> {
+    let sema = &Semantics::new(db);
+    let preprocessing_results = sema.preprocess_file(frange.file_id);
+    let source_map = preprocessing_results.source_map();
+    let source = preprocessing_results.preprocessed_text();
+    let tree = sema.parse(frange.file_id);
+
+    let Some(s_range) = source_map.closest_s_range(frange.range) else {
+        return Vec::new();
+    };
+    let Some(node) = tree
+        .root_node()
+        .descendant_for_byte_range(s_range.start().into(), s_range.start().into())
+    else {
+        return Vec::new();
+    };
+    if TSKind::from(node) != TSKind::identifier || sema.find_def(frange.file_id, &node).is_some() {
+        return Vec::new();
+    }
+    let (Ok(name), Some(target)) = (
+        node.utf8_text(source.as_bytes()),
+        source_map.closest_u_range(ts_range_to_text_range(&node.range())),
+    ) else {
+        return Vec::new();
+    };
+
+    let text = db.file_text(frange.file_id);
+    let (offset, prefix, suffix) = insertion(db, frange.file_id, &text);
+    include_paths(db, name)
+        .into_iter()
+        .map(|path| {
+            let mut source_change = SourceChange::default();
+            source_change.insert(
+                frange.file_id,
+                TextEdit::new(
+                    TextRange::empty(offset),
+                    format!("{prefix}#include <{path}>{suffix}"),
+                ),
+            );
+            Assist {
+                id: AssistId("add_include", AssistKind::QuickFix),
+                label: format!("Add `#include <{path}>`"),
+                target,
+                source_change,
+                trigger_rename: None,
+            }
+        })
+        .collect()
+}
+
+/// Paths of the files of the include directories defining a global symbol named `name`, relative
+/// to their include directory and without their `.inc` extension.
+fn include_paths(db: &RootDatabase, name: &str) -> Vec<String> {
+    let mut res = Vec::new();
+    for source_root in db.source_roots().iter().filter(|it| it.is_include_dir) {
+        let Some(root) = source_root.root().as_path() else {
+            continue;
+        };
+        for file_id in source_root.iter() {
+            let Some(path) = source_root
+                .path_for_file(&file_id)
+                .and_then(|path| path.as_path())
+                .and_then(|path| path.strip_prefix(root))
+            else {
+                continue;
+            };
+            let path = path
+                .components()
+                .filter_map(|component| component.as_os_str().to_str())
+                .join("/");
+            let Some(path) = path.strip_suffix(".inc") else {
+                continue;
+            };
+            if defines(db, file_id, name) {
+                res.push(path.to_string());
+            }
+        }
+    }
+    res.sort();
+    res.dedup();
+
+    res
+}
+
+/// Whether a file defines a global symbol named `name`, including the variants of its enums.
+fn defines(db: &RootDatabase, file_id: FileId, name: &str) -> bool {
+    let Some(symbols) = symbols(db, file_id) else {
+        return false;
+    };
+    symbols.into_iter().any(|idx| {
+        let symbol = &symbols[idx];
+        symbol.name() == name
+            || (symbol.kind() == SymbolKind::Enum
+                && symbol
+                    .children()
+                    .iter()
+                    .any(|child| symbols[child].name() == name))
+    })
+}
+
+/// Offset where the new include directive is inserted, and the line endings to insert before
+/// and after it.
+fn insertion(
+    db: &RootDatabase,
+    file_id: FileId,
+    text: &str,
+) -> (TextSize, &'static str, &'static str) {
+    let line_ending = if text.contains("\r\n") { "\r\n" } else { "\n" };
+    // The implicit includes have no range.
+    let last_include = db
+        .file_includes(file_id)
+        .0
+        .iter()
+        .filter(|include| include.kind() == IncludeKind::Chevrons)
+        .filter_map(|include| include.range())
+        .max_by_key(|range| range.end());
+    let Some(range) = last_include else {
+        return (TextSize::new(0), "", line_ending);
+    };
+
+    match text[usize::from(range.end())..].find('\n') {
+        Some(idx) => (range.end() + TextSize::new(idx as u32 + 1), "", line_ending),
+        None => (TextSize::of(text), line_ending, ""),
+    }
+}
@@ -1,11 +1,15 @@
 mod defaults;
 mod documentation;
+mod gamedata;
 mod includes;
 mod item;
+mod macros;
+mod phrases;
+mod sizeof;
 
 use std::panic::AssertUnwindSafe;
 
-use base_db::FilePosition;
+use base_db::{FilePosition, SourceDatabaseExt};
 use hir::{DefResolution, Field, Function, HasSource, LocalDef, Property, Semantics};
 use hir_def::{DefDatabase, FieldId, FunctionKind};
 use ide_db::{Documentation, RootDatabase, SymbolKind};
@@ -24,8 +28,15 @@ use crate::{
     completion::{
         defaults::get_default_completions,
         documentation::{get_doc_completion, is_documentation_start},
+        gamedata::get_gamedata_completions,
         includes::{get_include_completions, is_include_statement},
+        macros::{get_macro_completions, is_preprocessor_condition},
+        phrases::get_phrase_completions,
+        sizeof::{
+            get_size_argument_completions, get_sizeof_dimension_completions, is_sizeof_operand,
+        },
     },
+    convars::{convar_name, convars_completions},
     events::{event_name, events_completions},
     hover::{render_def, Render},
 };
@@ -65,6 +76,15 @@ pub fn completions(
             file_id_to_url,
         );
     }
+    // Preprocessor directives are not part of the preprocessed text, look for them in the source.
+    let source = db.file_text(pos.file_id);
+    let u_pre_line = source
+        .get(..pos.raw_offset_usize())
+        .and_then(|it| it.rsplit('\n').next())
+        .unwrap_or_default();
+    if matches!(trigger_character, None | Some(' ')) && is_preprocessor_condition(u_pre_line) {
+        return get_macro_completions(sema, pos.file_id).into();
+    }
     if trigger_character == Some('/') || trigger_character == Some('<') {
         // We are past the include statement check, so we can return early.
         return None;
@@ -114,6 +134,19 @@ pub fn completions(
     if event_name(&node, &new_source_code).is_some() {
         return events_completions(events_game_name).into();
     }
+    if convar_name(&node, &new_source_code).is_some() {
+        return convars_completions(db).into();
+    }
+    if let Some(completions) =
+        get_phrase_completions(sema, pos, &node, &tree, &new_source_code, &source)
+    {
+        return Some(completions);
+    }
+    if let Some(completions) =
+        get_gamedata_completions(sema, pos, &node, &tree, &new_source_code, &source)
+    {
+        return Some(completions);
+    }
     if trigger_character == Some('"') {
         return None;
     }
@@ -126,6 +159,12 @@ pub fn completions(
         return None;
     }
 
+    let size_argument_completions = if is_triggered_by_scope_or_field_access(trigger_character) {
+        None
+    } else {
+        get_size_argument_completions(sema, pos.file_id, &node, &tree, &new_source_code)
+    };
+
     let mut container = node.parent()?;
     // If the node does not have a parent we are at the root, nothing to resolve.
     while !matches!(
@@ -218,6 +257,11 @@ pub fn completions(
 
     let mut res = Vec::new();
 
+    if is_sizeof_operand(&node) && !is_triggered_by_scope_or_field_access(trigger_character) {
+        res.extend(get_sizeof_dimension_completions(sema, &defs));
+    }
+    res.extend(size_argument_completions.into_iter().flatten());
+
     defs.into_iter().for_each(|def| match &def {
         DefResolution::Function(it) => {
             let data = sema.db.function_data(it.id());
@@ -665,7 +709,9 @@ pub fn resolve_completion(
     let source = db.preprocessed_text(file_id);
     let tree = db.parse(file_id);
     if let Some(def_node) = def.clone().source(db, &tree).map(|it| it.value) {
-        if let Some(docs) = Documentation::from_node(def_node, source.as_bytes()) {
+        if let Some(docs) = Documentation::from_node(def_node, source.as_bytes())
+            .or_else(|| macro_documentation(db, &def))
+        {
             item.documentation = Some(docs.into());
         }
     }
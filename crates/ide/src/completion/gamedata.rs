@@ -0,0 +1,130 @@
+use base_db::{FilePosition, Gamedata, GamedataSection, SourceDatabase};
+use hir::{DefResolution, Semantics};
+use hir_def::DefDatabase;
+use ide_db::RootDatabase;
+use itertools::Itertools;
+use line_index::TextRange;
+use smol_str::ToSmolStr;
+use syntax::TSKind;
+
+use crate::completion::{
+    item::{CompletionItem, CompletionKind},
+    phrases::string_content_range,
+};
+
+/// Suggest the entries of the gamedata files when completing the name of an offset, key, address
+/// or signature, e.g `GameConfGetOffset(gc, "|")` or `gamedata.GetMemSig("|")`, and the gamedata
+/// files when completing the argument of `LoadGameConfigFile` or `new GameData`.
+///
+/// Only the entries of the gamedata files loaded by the project are suggested, unless none of
+/// them was found.
+///
+/// # Arguments
+/// * `sema` - The semantics of the database.
+/// * `pos` - The position of the completion.
+/// * `node` - The node being completed, in the tree with the completion placeholder.
+/// * `tree` - The tree of the file, without the completion placeholder.
+/// * `source` - The source of the tree with the completion placeholder.
+/// * `text` - The text of the file.
+pub(super) fn get_gamedata_completions(
+    sema: &Semantics<RootDatabase>,
+    pos: FilePosition,
+    node: &tree_sitter::Node,
+    tree: &base_db::Tree,
+    source: &str,
+    text: &str,
+) -> Option<Vec<CompletionItem>> {
+    let literal = std::iter::successors(Some(*node), |node| node.parent())
+        .find(|node| TSKind::from(node) == TSKind::string_literal)?;
+    let arguments = literal.parent()?;
+    if TSKind::from(arguments) != TSKind::call_arguments {
+        return None;
+    }
+    let idx = arguments
+        .named_children(&mut arguments.walk())
+        .filter(|arg| TSKind::from(arg) != TSKind::comment)
+        .position(|arg| arg == literal)?;
+    let gamedata = sema.db.gamedata();
+    let range = string_content_range(text, pos.offset);
+
+    let callee = arguments.parent()?;
+    if let Some(class) = callee.child_by_field_name("class") {
+        if idx != 0 || class.utf8_text(source.as_bytes()).ok()? != "GameData" {
+            return None;
+        }
+        return file_completions(&gamedata, range).into();
+    }
+    let function = callee.child_by_field_name("function")?;
+    if function.utf8_text(source.as_bytes()).ok()? == "LoadGameConfigFile" {
+        if idx != 0 {
+            return None;
+        }
+        return file_completions(&gamedata, range).into();
+    }
+    let function = match TSKind::from(function) {
+        TSKind::field_access => function.child_by_field_name("field")?,
+        _ => function,
+    };
+    // The callee is before the cursor, its offsets are the same in both trees.
+    let function = tree
+        .root_node()
+        .descendant_for_byte_range(function.start_byte(), function.end_byte())?;
+    let DefResolution::Function(function) = sema.find_def(pos.file_id, &function)? else {
+        return None;
+    };
+    let methodmap = function
+        .parent_methodmap(sema.db)
+        .map(|it| it.name(sema.db).to_string());
+    let name = function.name(sema.db).to_string();
+    let (param_idx, section) = GamedataSection::of_param(&name, methodmap.as_deref())?;
+    if param_idx != idx {
+        return None;
+    }
+
+    let file_ids = sema
+        .db
+        .projet_subgraph(pos.file_id)
+        .map(|subgraph| subgraph.file_ids().into_iter().collect_vec())
+        .unwrap_or_else(|| vec![pos.file_id]);
+    let mut files = file_ids
+        .into_iter()
+        .flat_map(|file_id| {
+            sema.db
+                .loaded_gamedata(file_id)
+                .iter()
+                .cloned()
+                .collect_vec()
+        })
+        .filter(|file| gamedata.contains_file(file))
+        .unique()
+        .collect_vec();
+    if files.is_empty() {
+        files = gamedata.files().map(|file| file.to_smolstr()).collect();
+    }
+
+    files
+        .iter()
+        .flat_map(|file| {
+            gamedata.entries(file, section).map(|entry| CompletionItem {
+                label: entry.to_smolstr(),
+                kind: CompletionKind::Literal,
+                detail: Some(file.to_string()),
+                text_edit: Some((range, entry.to_string())),
+                ..Default::default()
+            })
+        })
+        .collect_vec()
+        .into()
+}
+
+fn file_completions(gamedata: &Gamedata, range: TextRange) -> Vec<CompletionItem> {
+    gamedata
+        .files()
+        .map(|file| CompletionItem {
+            label: file.to_smolstr(),
+            kind: CompletionKind::File,
+            text_edit: Some((range, file.to_string())),
+            ..Default::default()
+        })
+        .collect()
+}
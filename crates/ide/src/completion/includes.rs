@@ -1,4 +1,4 @@
-use base_db::SourceDatabaseExt;
+use base_db::{IncludeSearchLocation, SourceDatabase, SourceDatabaseExt};
 use fxhash::FxHashSet;
 use ide_db::RootDatabase;
 use itertools::Itertools;
@@ -86,8 +86,33 @@ pub(super) fn get_include_completions(
         }));
     known_paths.remove(&path);
 
-    if !include_st.use_chevron {
-        include_directories.push(parent_folder);
+    // Offer the files of the locations searched by the preprocessor, in the same order.
+    let search_order = db.include_search_order();
+    let locations = if include_st.use_chevron {
+        &search_order.chevrons
+    } else {
+        &search_order.quotes
+    };
+    let mut search_directories = Vec::new();
+    for location in locations {
+        match location {
+            IncludeSearchLocation::File => {
+                // The `include` subdirectory comes first, so that its files are offered
+                // directly rather than through the `include` directory.
+                search_directories.push(parent_folder.join("include"));
+                search_directories.push(parent_folder.clone());
+            }
+            IncludeSearchLocation::Workspace => search_directories.extend(
+                db.source_roots()
+                    .iter()
+                    .filter(|source_root| !source_root.is_include_dir)
+                    .filter_map(|source_root| source_root.root().as_path())
+                    .map(|it| it.to_path_buf()),
+            ),
+            IncludeSearchLocation::IncludeDirectories => {
+                search_directories.append(&mut include_directories)
+            }
+        }
     }
 
     lazy_static! {
@@ -97,15 +122,15 @@ pub(super) fn get_include_completions(
     // Extract everything that has already been typed in the statement.
     let typed_path = RE1.replace(&include_st.text, "$a").to_string();
 
-    let include_directories_hash = FxHashSet::from_iter(
-        include_directories
-            .into_iter()
-            .map(|it| it.join(&typed_path)),
-    );
+    let search_directories = search_directories
+        .into_iter()
+        .map(|it| it.join(&typed_path))
+        .unique()
+        .collect_vec();
     let completions = known_paths
         .iter()
         .filter_map(|it| {
-            for path in include_directories_hash.iter() {
+            for path in search_directories.iter() {
                 if let Some(stripped) = it.strip_prefix(path) {
                     return Some((
                         stripped.components().next()?.as_os_str().to_str()?,
@@ -132,8 +157,14 @@ pub(super) fn get_include_completions(
     let items = completions
         .into_iter()
         .map(|(it, is_dir)| {
-            let mut insert_text = it.replace(".inc", "");
-            insert_text.push_str(closing_element);
+            // Insert the separator of a directory and complete its entries right away.
+            let insert_text = if is_dir {
+                format!("{}/", it)
+            } else {
+                let mut insert_text = it.replace(".inc", "");
+                insert_text.push_str(closing_element);
+                insert_text
+            };
             crate::CompletionItem {
                 label: it.to_smolstr(),
                 kind: if is_dir {
@@ -143,6 +174,7 @@ pub(super) fn get_include_completions(
                 },
                 insert_text: Some(insert_text),
                 detail: Some(it.to_string()),
+                retrigger_completion: is_dir,
                 ..Default::default()
             }
         })
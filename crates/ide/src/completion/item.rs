@@ -33,6 +33,10 @@ pub struct CompletionItem {
     /// after completion.
     pub trigger_call_info: bool,
 
+    /// Ask the editor to show the completions again after completion, e.g to complete the entries
+    /// of a directory.
+    pub retrigger_completion: bool,
+
     pub text_edit: Option<(TextRange, String)>,
 
     pub data: Option<DefResolution>,
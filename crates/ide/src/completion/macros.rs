@@ -0,0 +1,44 @@
+use hir::{DefResolution, Semantics};
+use ide_db::{RootDatabase, SymbolKind};
+use itertools::Itertools;
+use lazy_static::lazy_static;
+use regex::Regex;
+use smol_str::ToSmolStr;
+use vfs::FileId;
+
+use crate::completion::item::CompletionItem;
+
+/// Check whether the current prefix line is in the condition of an `#if` or `#elseif` directive,
+/// e.g `#if defined FOO && BA`.
+///
+/// # Arguments
+///
+/// * `pre_line` - Prefix line to process, in the source of the file.
+pub(super) fn is_preprocessor_condition(pre_line: &str) -> bool {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(r"^\s*#\s*(?:if|elseif)\s(?:[^/]|/[^/*])*$").unwrap();
+    }
+    RE.is_match(pre_line)
+}
+
+/// Completions of the macros known at the end of the preprocessing of the file, with their
+/// value as the detail.
+pub(super) fn get_macro_completions(
+    sema: &Semantics<RootDatabase>,
+    file_id: FileId,
+) -> Vec<CompletionItem> {
+    sema.preprocess_file(file_id)
+        .macros()
+        .iter()
+        .sorted_by(|a, b| a.0.cmp(b.0))
+        .map(|(name, macro_)| CompletionItem {
+            label: name.to_smolstr(),
+            kind: SymbolKind::Macro.into(),
+            detail: Some(macro_.value()),
+            data: sema
+                .macro_from_idx(macro_.file_id(), macro_.idx())
+                .map(DefResolution::Macro),
+            ..Default::default()
+        })
+        .collect()
+}
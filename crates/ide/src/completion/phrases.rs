@@ -0,0 +1,126 @@
+use base_db::{FilePosition, SourceDatabase};
+use hir::{DefResolution, Semantics};
+use hir_def::{format_specifiers, unquote, DefDatabase};
+use ide_db::RootDatabase;
+use itertools::Itertools;
+use line_index::{TextRange, TextSize};
+use smol_str::ToSmolStr;
+use syntax::TSKind;
+
+use crate::completion::item::{CompletionItem, CompletionKind};
+
+/// Suggest the phrases of the translation files when completing the name of a phrase formatted
+/// with `%t` or `%T`, e.g `PrintToChat(client, "%t", "|")`, and the translation files when
+/// completing the argument of `LoadTranslations`.
+///
+/// # Arguments
+/// * `sema` - The semantics of the database.
+/// * `pos` - The position of the completion.
+/// * `node` - The node being completed, in the tree with the completion placeholder.
+/// * `tree` - The tree of the file, without the completion placeholder.
+/// * `source` - The source of the tree with the completion placeholder.
+/// * `text` - The text of the file.
+pub(super) fn get_phrase_completions(
+    sema: &Semantics<RootDatabase>,
+    pos: FilePosition,
+    node: &tree_sitter::Node,
+    tree: &base_db::Tree,
+    source: &str,
+    text: &str,
+) -> Option<Vec<CompletionItem>> {
+    let literal = std::iter::successors(Some(*node), |node| node.parent())
+        .find(|node| TSKind::from(node) == TSKind::string_literal)?;
+    let arguments = literal.parent()?;
+    if TSKind::from(arguments) != TSKind::call_arguments {
+        return None;
+    }
+    let args = arguments
+        .named_children(&mut arguments.walk())
+        .filter(|arg| TSKind::from(arg) != TSKind::comment)
+        .collect::<Vec<_>>();
+    let idx = args.iter().position(|arg| *arg == literal)?;
+    let text_of = |node: &tree_sitter::Node| node.utf8_text(source.as_bytes()).ok();
+    let phrases = sema.db.phrases();
+    let range = string_content_range(text, pos.offset);
+
+    let function = arguments.parent()?.child_by_field_name("function")?;
+    if text_of(&function)? == "LoadTranslations" {
+        if idx != 0 {
+            return None;
+        }
+        return phrases
+            .iter()
+            .map(|(_, phrase)| phrase.file.as_str())
+            .unique()
+            .map(|file| CompletionItem {
+                label: file.to_smolstr(),
+                kind: CompletionKind::File,
+                text_edit: Some((range, file.to_string())),
+                ..Default::default()
+            })
+            .collect_vec()
+            .into();
+    }
+
+    // The callee and the format string are before the cursor, their offsets are the same in
+    // both trees.
+    let function = tree
+        .root_node()
+        .descendant_for_byte_range(function.start_byte(), function.end_byte())?;
+    let DefResolution::Function(function) = sema.find_def(pos.file_id, &function)? else {
+        return None;
+    };
+    let format_idx = sema.db.function_data(function.id()).format_param_index()?;
+    let format = args
+        .get(format_idx)
+        .filter(|arg| TSKind::from(*arg) == TSKind::string_literal)
+        .and_then(text_of)?;
+    let mut next_arg = format_idx + 1;
+    for specifier in format_specifiers(unquote(format)) {
+        if next_arg > idx {
+            return None;
+        }
+        if !matches!(specifier, 't' | 'T') {
+            next_arg += 1;
+            continue;
+        }
+        if next_arg == idx {
+            return phrases
+                .iter()
+                .map(|(name, phrase)| CompletionItem {
+                    label: name.to_smolstr(),
+                    kind: CompletionKind::Literal,
+                    detail: Some(phrase.file.clone()),
+                    text_edit: Some((range, name.to_string())),
+                    ..Default::default()
+                })
+                .collect_vec()
+                .into();
+        }
+        // Skip the format parameters of the phrase, and the client of `%T`.
+        let phrase = args
+            .get(next_arg)
+            .filter(|arg| TSKind::from(*arg) == TSKind::string_literal)
+            .and_then(text_of)?;
+        next_arg += 1 + phrases.get(unquote(phrase))?.params;
+        if specifier == 'T' {
+            next_arg += 1;
+        }
+    }
+
+    None
+}
+
+/// Range of the text of the string literal under the cursor, without its quotes.
+pub(super) fn string_content_range(text: &str, offset: TextSize) -> TextRange {
+    let offset = usize::from(offset).min(text.len());
+    let line_start = text[..offset].rfind('\n').map_or(0, |idx| idx + 1);
+    let start = text[line_start..offset]
+        .rfind('"')
+        .map_or(offset, |idx| line_start + idx + 1);
+    let end = text[offset..]
+        .find(['"', '\r', '\n'])
+        .map_or(text.len(), |idx| offset + idx);
+
+    TextRange::new(TextSize::new(start as u32), TextSize::new(end as u32))
+}
@@ -0,0 +1,211 @@
+use hir::{DefResolution, LocalDef, Semantics};
+use ide_db::{RootDatabase, SymbolKind};
+use syntax::TSKind;
+use vfs::FileId;
+
+use crate::completion::item::CompletionItem;
+
+/// Kind of a size parameter, deduced from its name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SizeParam {
+    /// Maximum length of a string, in characters, e.g `maxlen` or `maxlength`.
+    MaxLength,
+    /// Size of a buffer, in cells, e.g `size` or `bufferSize`.
+    Size,
+}
+
+impl SizeParam {
+    fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_lowercase();
+        if name.contains("maxlen") || name == "len" || name == "length" {
+            Some(Self::MaxLength)
+        } else if name.ends_with("size") {
+            Some(Self::Size)
+        } else {
+            None
+        }
+    }
+}
+
+/// A variable passed as an argument, e.g `buffers[i]`.
+struct SizedArgument {
+    def: DefResolution,
+    name: String,
+    /// Number of indexes applied to the variable, e.g `1` for `buffers[i]`.
+    indexes: usize,
+    /// Number of dimensions of the variable.
+    dimensions: usize,
+}
+
+impl SizedArgument {
+    /// The `sizeof` expression of the dimension the argument points to, e.g
+    /// `sizeof(buffers[])` for `buffers[i]`.
+    fn sizeof(&self) -> String {
+        format!("sizeof({}{})", self.name, "[]".repeat(self.indexes))
+    }
+}
+
+/// Suggest the size of the previous argument of a call, when the parameter being completed is a
+/// size parameter, e.g `sizeof(buffers[])` in `GetClientName(client, buffers[i], |)`.
+///
+/// # Arguments
+/// * `sema` - The semantics of the database.
+/// * `file_id` - The file in which the completion is requested.
+/// * `node` - The node being completed, in the tree with the completion placeholder.
+/// * `tree` - The tree of the file, without the completion placeholder.
+/// * `source` - The source of the tree with the completion placeholder.
+pub(super) fn get_size_argument_completions(
+    sema: &Semantics<RootDatabase>,
+    file_id: FileId,
+    node: &tree_sitter::Node,
+    tree: &base_db::Tree,
+    source: &str,
+) -> Option<Vec<CompletionItem>> {
+    let arguments = node.parent()?;
+    if TSKind::from(arguments) != TSKind::call_arguments {
+        return None;
+    }
+    let args = arguments
+        .named_children(&mut arguments.walk())
+        .filter(|arg| TSKind::from(arg) != TSKind::comment)
+        .collect::<Vec<_>>();
+    let idx = args.iter().position(|arg| arg == node)?;
+    let previous_arg = args.get(idx.checked_sub(1)?)?;
+
+    // The callee and the previous argument are before the cursor, their offsets are the same in
+    // both trees.
+    let function = arguments.parent()?.child_by_field_name("function")?;
+    let function = match TSKind::from(function) {
+        TSKind::field_access => function.child_by_field_name("field")?,
+        _ => function,
+    };
+    let function = tree
+        .root_node()
+        .descendant_for_byte_range(function.start_byte(), function.end_byte())?;
+    let DefResolution::Function(function) = sema.find_def(file_id, &function)? else {
+        return None;
+    };
+    let param = function.parameters(sema.db).into_iter().nth(idx)?;
+    let kind = SizeParam::from_name(&param)?;
+
+    let arg = sized_argument(sema, file_id, previous_arg, tree, source)?;
+    let remaining = arg.dimensions.saturating_sub(arg.indexes);
+    let enum_struct = match &arg.def {
+        DefResolution::Local(LocalDef { def, .. }) => def.type_(sema.db),
+        DefResolution::Global(it) => it.type_(sema.db),
+        _ => None,
+    }
+    .and_then(|it| match it {
+        DefResolution::EnumStruct(it) => Some(it),
+        _ => None,
+    });
+    let label = match (kind, enum_struct) {
+        // An enum struct array passed as a buffer of cells.
+        (SizeParam::Size, Some(enum_struct)) if remaining > 0 => {
+            format!("{} * sizeof({})", arg.sizeof(), enum_struct.name(sema.db))
+        }
+        // An enum struct, e.g `foos[i]`, whose size is its number of cells.
+        (_, Some(_)) => arg.sizeof(),
+        // A scalar has no size.
+        (_, None) if remaining == 0 => return None,
+        (_, None) => arg.sizeof(),
+    };
+    let kind = match arg.def {
+        DefResolution::Global(_) => SymbolKind::Global,
+        _ => SymbolKind::Local,
+    };
+
+    Some(vec![CompletionItem {
+        label: label.clone().into(),
+        kind: kind.into(),
+        filter_text: Some(label),
+        data: Some(arg.def),
+        ..Default::default()
+    }])
+}
+
+/// Resolve a variable argument, e.g `buffer` or `buffers[i]`.
+fn sized_argument(
+    sema: &Semantics<RootDatabase>,
+    file_id: FileId,
+    arg: &tree_sitter::Node,
+    tree: &base_db::Tree,
+    source: &str,
+) -> Option<SizedArgument> {
+    let mut base = *arg;
+    let mut indexes = 0;
+    while TSKind::from(base) == TSKind::array_indexed_access {
+        base = base.child_by_field_name("array")?;
+        indexes += 1;
+    }
+    if TSKind::from(base) != TSKind::identifier {
+        return None;
+    }
+    let name = base.utf8_text(source.as_bytes()).ok()?.to_string();
+    let base = tree
+        .root_node()
+        .descendant_for_byte_range(base.start_byte(), base.end_byte())?;
+    let def = sema.find_def(file_id, &base)?;
+    let dimensions = match &def {
+        DefResolution::Local(LocalDef { def, .. }) => def.dimensions(sema.db),
+        DefResolution::Global(it) => it.dimensions(sema.db),
+        _ => return None,
+    };
+
+    Some(SizedArgument {
+        def,
+        name,
+        indexes,
+        dimensions,
+    })
+}
+
+/// Whether the node is the operand of a `sizeof` expression, e.g `sizeof(|)`.
+pub(super) fn is_sizeof_operand(node: &tree_sitter::Node) -> bool {
+    let Some(mut parent) = node.parent() else {
+        return false;
+    };
+    if TSKind::from(parent) == TSKind::parenthesized_expression {
+        let Some(grandparent) = parent.parent() else {
+            return false;
+        };
+        parent = grandparent;
+    }
+    TSKind::from(parent) == TSKind::sizeof_expression
+}
+
+/// Completions of the inner dimensions of the multi-dimensional arrays, e.g `buffers[]` for
+/// `char buffers[4][64]`, to be used as the operand of a `sizeof` expression.
+pub(super) fn get_sizeof_dimension_completions(
+    sema: &Semantics<RootDatabase>,
+    defs: &[DefResolution],
+) -> Vec<CompletionItem> {
+    let mut res = Vec::new();
+    for def in defs {
+        let (name, dimensions, kind) = match def {
+            DefResolution::Local(LocalDef {
+                name: Some(name),
+                def: local,
+            }) => (
+                name.to_string(),
+                local.dimensions(sema.db),
+                SymbolKind::Local,
+            ),
+            DefResolution::Global(it) => (
+                it.name(sema.db).to_string(),
+                it.dimensions(sema.db),
+                SymbolKind::Global,
+            ),
+            _ => continue,
+        };
+        res.extend((1..dimensions).map(|indexes| CompletionItem {
+            label: format!("{}{}", name, "[]".repeat(indexes)).into(),
+            kind: kind.into(),
+            filter_text: Some(name.clone()),
+            data: Some(def.clone()),
+            ..Default::default()
+        }));
+    }
+
+    res
+}
@@ -0,0 +1,137 @@
+//! This module provides completions/hovers/definitions for the names of the ConVars found with
+//! `FindConVar`, from the ConVars created with `CreateConVar` in the workspace.
+
+use hir_def::{ConVar, DefDatabase, InFile};
+use ide_db::{workspace_convars, Documentation, RootDatabase};
+use preprocessor::{db::PreprocDatabase, SourceMap};
+use syntax::{utils::ts_range_to_text_range, TSKind};
+use tree_sitter::Node;
+
+use crate::{
+    hover::HoverResult, CompletionItem, CompletionKind, Markup, NavigationTarget, RangeInfo,
+};
+
+/// Returns the ConVar name if the node is the first argument of a call to `FindConVar`.
+///
+/// # Arguments
+/// - `node`: The node to check
+/// - `source`: The preprocessed source code
+pub fn convar_name(node: &Node, source: &str) -> Option<String> {
+    if TSKind::from(node) != TSKind::string_literal {
+        return None;
+    }
+    let prev_sibling = node.prev_sibling()?;
+    if TSKind::from(&prev_sibling) != TSKind::anon_LPAREN {
+        return None;
+    }
+    let parent = node.parent()?;
+    if TSKind::from(&parent) != TSKind::call_arguments {
+        return None;
+    }
+    let function = parent.prev_named_sibling()?;
+    if TSKind::from(&function) != TSKind::identifier
+        || function.utf8_text(source.as_bytes()).ok()? != "FindConVar"
+    {
+        return None;
+    }
+
+    let raw_name = node.utf8_text(source.as_bytes()).ok()?;
+
+    raw_name.trim_matches('"').to_string().into()
+}
+
+/// Returns completions for the names of the ConVars created in the workspace.
+pub fn convars_completions(db: &RootDatabase) -> Vec<CompletionItem> {
+    workspace_convars(db)
+        .into_values()
+        .filter_map(|convars| {
+            let convar = convars.into_iter().next()?.value;
+            Some(CompletionItem {
+                label: convar.name.clone(),
+                kind: CompletionKind::Literal,
+                detail: convar.default.as_ref().map(|it| format!("\"{it}\"")),
+                documentation: convar_documentation(&convar).into(),
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+
+/// Returns hover information for a ConVar, with its default value and description.
+///
+/// # Arguments
+/// - `db`: The database
+/// - `name`: The name of the ConVar
+/// - `node`: The node of the string literal of the ConVar name
+/// - `source_map`: The source map of the file of the node
+pub fn convar_hover(
+    db: &RootDatabase,
+    name: &str,
+    node: &Node,
+    source_map: &SourceMap,
+) -> Option<RangeInfo<HoverResult>> {
+    let convars = workspace_convars(db).remove(name)?;
+    let sections = convars
+        .iter()
+        .map(|convar| {
+            let declaration = match &convar.value.default {
+                Some(default) => format!("ConVar {} = \"{}\"", convar.value.name, default),
+                None => format!("ConVar {}", convar.value.name),
+            };
+            format!(
+                "{}\n\n---\n\n{}",
+                Markup::fenced_block(declaration),
+                convar_documentation(&convar.value).to_markdown()
+            )
+        })
+        .collect::<Vec<_>>();
+
+    Some(RangeInfo::new(
+        source_map.closest_u_range_always(ts_range_to_text_range(&node.range())),
+        HoverResult {
+            markup: Markup::from(sections.join("\n\n---\n\n")),
+            actions: Default::default(),
+        },
+    ))
+}
+
+/// Returns the `CreateConVar` calls which create a ConVar.
+///
+/// # Arguments
+/// - `db`: The database
+/// - `name`: The name of the ConVar
+pub fn convar_definitions(db: &RootDatabase, name: &str) -> Vec<NavigationTarget> {
+    workspace_convars(db)
+        .remove(name)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|convar| convar_target(db, convar))
+        .collect()
+}
+
+fn convar_target(db: &RootDatabase, convar: InFile<ConVar>) -> Option<NavigationTarget> {
+    let InFile { file_id, value } = convar;
+    let tree = db.parse(file_id);
+    let name_node = value.name_ptr.to_node(&tree)?;
+    let call = std::iter::successors(Some(name_node), |node| node.parent())
+        .find(|node| TSKind::from(node) == TSKind::call_expression)?;
+    let preprocessing_results = db.preprocess_file(file_id);
+
+    Some(NavigationTarget::from_ranges(
+        value.name,
+        file_id,
+        preprocessing_results.source_map(),
+        ts_range_to_text_range(&call.range()),
+        ts_range_to_text_range(&name_node.range()),
+    ))
+}
+
+fn convar_documentation(convar: &ConVar) -> Documentation {
+    Documentation::from(
+        convar
+            .description
+            .as_deref()
+            .filter(|it| !it.is_empty())
+            .unwrap_or("No description."),
+    )
+}
@@ -0,0 +1,555 @@
+use base_db::{FileRange, SourceDatabase};
+use hir::{Semantics, Typedef, Typeset};
+use hir_def::{
+    resolver::{HasResolver, ValueNs},
+    FunctionKind,
+};
+use ide_db::{Assist, AssistId, AssistKind, RootDatabase, SourceChange, TextEdit};
+use line_index::{TextRange, TextSize};
+use preprocessor::{SourceMap, StylePragmaKind, StylePragmas};
+use syntax::{utils::ts_range_to_text_range, TSKind};
+use tree_sitter::Node;
+use vfs::FileId;
+
+use crate::inlay_hints::call;
+
+#[derive(Debug, Default)]
+pub struct SyntaxConversion {
+    pub source_change: SourceChange,
+    /// Number of declarations converted.
+    pub conversions: usize,
+}
+
+impl SyntaxConversion {
+    /// Human readable summary of the conversion, e.g `Converted 3 declarations in 2 files`.
+    pub fn summary(&self) -> String {
+        if self.conversions == 0 {
+            return "No declarations to convert".to_string();
+        }
+        let files = self.source_change.source_file_edits.len();
+        format!(
+            "Converted {} declaration{} in {files} file{}",
+            self.conversions,
+            if self.conversions == 1 { "" } else { "s" },
+            if files == 1 { "" } else { "s" },
+        )
+    }
+}
+
+// Feature: Convert to the New Syntax
+//
+// Rewrites the declarations of the old syntax into the syntax of SourceMod 1.7+, e.g:
+//
+// - `decl String:buf[64];` becomes `char buf[64];`,
+// - `new Handle:h, Float:x;` becomes `Handle h;` and `float x;`,
+// - `public OnClientPutInServer(client)` becomes `public void OnClientPutInServer(int client)`.
+//
+// The `public` functions without a return type return the type of the forward or the typedef
+// they implement. The other functions return `void`, unless they return a value. The `functag`
+// and `funcenum` declarations are not converted.
+//
+// The conversion is offered for the declaration under the cursor, for the whole file, and for
+// the whole workspace with the `Convert to the new syntax` command. When all the declarations of
+// the main file of a plugin are converted and it has no `#pragma newdecls` directive,
+// `#pragma newdecls required` is added after its includes. An explicit
+// `#pragma newdecls optional` is left untouched.
+pub(crate) fn convert_syntax_assists(db: &RootDatabase, frange: FileRange) -> Vec<Assist> {
+    let sema = &Semantics::new(db);
+    let preprocessing_results = sema.preprocess_file(frange.file_id);
+    let source_map = preprocessing_results.source_map();
+    let source = preprocessing_results.preprocessed_text();
+    let tree = sema.parse(frange.file_id);
+    let text = db.file_text(frange.file_id);
+    let converter = Converter {
+        sema,
+        file_id: frange.file_id,
+        text: &text,
+        source,
+        source_map,
+    };
+
+    let Some(s_offset) = source_map
+        .closest_s_range(frange.range)
+        .map(|range| range.start())
+    else {
+        return Vec::new();
+    };
+    let Some(item) = tree
+        .root_node()
+        .descendant_for_byte_range(s_offset.into(), s_offset.into())
+        .and_then(|node| {
+            std::iter::successors(Some(node), |node| node.parent()).find(|node| {
+                is_old_item(node)
+                    // Only offer the conversion of a function on its signature.
+                    && node
+                        .child_by_field_name("body")
+                        .map_or(true, |body| usize::from(s_offset) < body.start_byte())
+            })
+        })
+    else {
+        return Vec::new();
+    };
+    let Some((target, edits)) = source_map
+        .closest_u_range(ts_range_to_text_range(&item.range()))
+        .zip(converter.edits(&item))
+    else {
+        return Vec::new();
+    };
+
+    let mut res = Vec::new();
+    let mut source_change = SourceChange::default();
+    for edit in edits {
+        source_change.insert(frange.file_id, edit);
+    }
+    res.push(Assist {
+        id: AssistId("convert_to_new_syntax", AssistKind::RefactorRewrite),
+        label: "Convert to the new syntax".to_string(),
+        target,
+        source_change,
+        trigger_rename: None,
+    });
+
+    let conversion = convert_to_new_syntax(db, &[frange.file_id]);
+    if conversion.conversions > 1 {
+        res.push(Assist {
+            id: AssistId("convert_file_to_new_syntax", AssistKind::RefactorRewrite),
+            label: "Convert the file to the new syntax".to_string(),
+            target,
+            source_change: conversion.source_change,
+            trigger_rename: None,
+        });
+    }
+
+    res
+}
+
+/// Converts all the declarations of the old syntax of the files.
+pub(crate) fn convert_to_new_syntax(db: &RootDatabase, file_ids: &[FileId]) -> SyntaxConversion {
+    let sema = &Semantics::new(db);
+    let mut res = SyntaxConversion::default();
+    for &file_id in file_ids {
+        let preprocessing_results = sema.preprocess_file(file_id);
+        let source_map = preprocessing_results.source_map();
+        let tree = sema.parse(file_id);
+        let text = db.file_text(file_id);
+        let converter = Converter {
+            sema,
+            file_id,
+            text: &text,
+            source: preprocessing_results.preprocessed_text(),
+            source_map,
+        };
+
+        let mut conversions = 0;
+        // Whether declarations of the old syntax are left, which `#pragma newdecls required`
+        // would reject.
+        let mut leftovers = false;
+        let root = tree.root_node();
+        let mut cursor = root.walk();
+        'outer: loop {
+            let node = cursor.node();
+            if is_old_item(&node) {
+                // Items expanded from a macro are not written in the file.
+                match converter.edits(&node) {
+                    Some(edits) => {
+                        for edit in edits {
+                            res.source_change.insert(file_id, edit);
+                        }
+                        conversions += 1;
+                    }
+                    None => leftovers = true,
+                }
+            }
+            if cursor.goto_first_child() {
+                continue;
+            }
+            loop {
+                if cursor.goto_next_sibling() {
+                    break;
+                }
+                if !cursor.goto_parent() {
+                    break 'outer;
+                }
+            }
+        }
+
+        let is_main_file = db
+            .projet_subgraph(file_id)
+            .is_some_and(|subgraph| subgraph.root.file_id == file_id);
+        if conversions > 0 && !leftovers && is_main_file {
+            if let Some(edit) =
+                newdecls_pragma(db, file_id, &text, preprocessing_results.style_pragmas())
+            {
+                res.source_change.insert(file_id, edit);
+            }
+        }
+        res.conversions += conversions;
+    }
+
+    res
+}
+
+/// Whether a node is a declaration written with the old syntax.
+fn is_old_item(node: &Node) -> bool {
+    match TSKind::from(node) {
+        TSKind::old_global_variable_declaration
+        | TSKind::old_variable_declaration_statement
+        | TSKind::old_for_loop_variable_declaration_statement => true,
+        TSKind::function_definition | TSKind::function_declaration => {
+            node.child_by_field_name("returnType")
+                .map_or(true, |ty| TSKind::from(ty) == TSKind::old_type)
+                || node
+                    .child_by_field_name("parameters")
+                    .is_some_and(|params| {
+                        params
+                            .children(&mut params.walk())
+                            .any(|param| is_old_parameter(&param))
+                    })
+        }
+        _ => false,
+    }
+}
+
+fn is_old_parameter(node: &Node) -> bool {
+    match TSKind::from(node) {
+        TSKind::parameter_declaration => !node
+            .children_by_field_name("type", &mut node.walk())
+            .any(|ty| matches!(TSKind::from(ty), TSKind::r#type | TSKind::array_type)),
+        TSKind::rest_parameter => node
+            .child_by_field_name("type")
+            .is_some_and(|ty| TSKind::from(ty) == TSKind::old_type),
+        _ => false,
+    }
+}
+
+/// Converts the text of an `old_type` node, e.g `Float:`, to a type of the new syntax.
+pub(crate) fn new_type(old_type: Option<&str>) -> String {
+    match old_type.map(|ty| ty.trim().trim_end_matches(':').trim()) {
+        None | Some("_") => "int".to_string(),
+        Some("Float") => "float".to_string(),
+        Some("String") => "char".to_string(),
+        Some(ty) => ty.to_string(),
+    }
+}
+
+struct Converter<'a> {
+    sema: &'a Semantics<'a, RootDatabase>,
+    file_id: FileId,
+    text: &'a str,
+    source: &'a str,
+    source_map: &'a SourceMap,
+}
+
+impl Converter<'_> {
+    /// User range of a range of the preprocessed text.
+    fn u_range(&self, start: usize, end: usize) -> Option<TextRange> {
+        self.source_map.closest_u_range(TextRange::new(
+            TextSize::new(start as u32),
+            TextSize::new(end as u32),
+        ))
+    }
+
+    fn edit(&self, start: usize, end: usize, replacement: String) -> Option<TextEdit> {
+        Some(TextEdit::new(self.u_range(start, end)?, replacement))
+    }
+
+    fn node_text(&self, node: &Node) -> Option<&str> {
+        node.utf8_text(self.source.as_bytes()).ok()
+    }
+
+    /// Edits converting an item for which [`is_old_item`] returned `true`.
+    fn edits(&self, item: &Node) -> Option<Vec<TextEdit>> {
+        match TSKind::from(item) {
+            TSKind::function_definition | TSKind::function_declaration => self.function_edits(item),
+            _ => self.variables_edits(item),
+        }
+    }
+
+    fn variables_edits(&self, item: &Node) -> Option<Vec<TextEdit>> {
+        let mut prefix = String::new();
+        let mut declarations = Vec::new();
+        for child in item.children(&mut item.walk()) {
+            match TSKind::from(child) {
+                TSKind::visibility | TSKind::variable_storage_class => {
+                    prefix.push_str(self.node_text(&child)?);
+                    prefix.push(' ');
+                }
+                TSKind::old_variable_declaration => declarations.push(child),
+                _ => (),
+            }
+        }
+        let types = declarations
+            .iter()
+            .map(|decl| {
+                new_type(
+                    decl.child_by_field_name("type")
+                        .and_then(|ty| self.node_text(&ty)),
+                )
+            })
+            .collect::<Vec<_>>();
+        let first = declarations.first()?;
+        let same_type = types.iter().all(|ty| ty == &types[0]);
+        if !same_type && TSKind::from(item) == TSKind::old_for_loop_variable_declaration_statement {
+            // The declarations of a loop cannot be split.
+            return None;
+        }
+
+        let mut res = vec![self.edit(
+            item.start_byte(),
+            first.child_by_field_name("name")?.start_byte(),
+            format!("{prefix}{} ", types[0]),
+        )?];
+        let start = self.u_range(item.start_byte(), item.start_byte())?.start();
+        let line_start = self.text[..usize::from(start)]
+            .rfind('\n')
+            .map_or(0, |idx| idx + 1);
+        let indent = &self.text[line_start..usize::from(start)];
+        let indent = if indent.trim().is_empty() { indent } else { "" };
+        for (idx, decl) in declarations.iter().enumerate().skip(1) {
+            let name = decl.child_by_field_name("name")?;
+            res.push(if same_type {
+                self.edit(decl.start_byte(), name.start_byte(), String::new())?
+            } else {
+                self.edit(
+                    declarations[idx - 1].end_byte(),
+                    name.start_byte(),
+                    format!(";\n{indent}{prefix}{} ", types[idx]),
+                )?
+            });
+        }
+
+        Some(res)
+    }
+
+    fn function_edits(&self, item: &Node) -> Option<Vec<TextEdit>> {
+        let name = item.child_by_field_name("name")?;
+        let mut res = Vec::new();
+        match item.child_by_field_name("returnType") {
+            Some(ty) if TSKind::from(ty) == TSKind::old_type => {
+                res.push(self.edit(
+                    ty.start_byte(),
+                    name.start_byte(),
+                    format!("{} ", new_type(self.node_text(&ty))),
+                )?);
+            }
+            Some(_) => (),
+            None => {
+                let ty = match item.child_by_field_name("body") {
+                    Some(body) => self.callback_return_type(item).unwrap_or_else(|| {
+                        match returns_value(&body) {
+                            true => "int".to_string(),
+                            false => "void".to_string(),
+                        }
+                    }),
+                    // Natives return untagged integers.
+                    None if self.is_forward(item) => "void".to_string(),
+                    None => "int".to_string(),
+                };
+                res.push(self.edit(name.start_byte(), name.start_byte(), format!("{ty} "))?);
+            }
+        }
+
+        let params = item.child_by_field_name("parameters")?;
+        for param in params.children(&mut params.walk()) {
+            if !is_old_parameter(&param) {
+                continue;
+            }
+            let old_type = param
+                .children_by_field_name("type", &mut param.walk())
+                .find(|ty| TSKind::from(ty) == TSKind::old_type);
+            let ty = new_type(old_type.and_then(|ty| self.node_text(&ty)));
+            if TSKind::from(param) == TSKind::rest_parameter {
+                let old_type = old_type?;
+                res.push(self.edit(old_type.start_byte(), old_type.end_byte(), ty)?);
+                continue;
+            }
+            let start = param
+                .children(&mut param.walk())
+                .find(|child| TSKind::from(child) != TSKind::variable_storage_class)?;
+            let by_ref = param
+                .children(&mut param.walk())
+                .any(|child| self.node_text(&child) == Some("&"));
+            let name = param.child_by_field_name("name")?;
+            res.push(self.edit(
+                start.start_byte(),
+                name.start_byte(),
+                format!("{ty} {}", if by_ref { "&" } else { "" }),
+            )?);
+        }
+
+        Some(res)
+    }
+
+    fn is_forward(&self, item: &Node) -> bool {
+        item.child_by_field_name("kind")
+            .and_then(|kind| self.node_text(&kind))
+            == Some("forward")
+    }
+
+    /// Return type of the forward or the typedef implemented by a `public` function, e.g
+    /// `Action` for a timer callback.
+    fn callback_return_type(&self, item: &Node) -> Option<String> {
+        let is_public = item
+            .child_by_field_name("visibility")
+            .is_some_and(|visibility| {
+                visibility
+                    .children(&mut visibility.walk())
+                    .any(|child| TSKind::from(child) == TSKind::anon_public)
+            });
+        if !is_public {
+            return None;
+        }
+        let db = self.sema.db;
+        let name = self.node_text(&item.child_by_field_name("name")?)?;
+        if let Some(ValueNs::FunctionId(ids)) = self.file_id.resolver(db).resolve_ident(name) {
+            let forward = ids
+                .iter()
+                .map(|id| db.function_data(id.value))
+                .find(|data| data.kind == FunctionKind::Forward);
+            if let Some(forward) = forward {
+                let ty = forward.type_ref.as_ref().map(|ty| ty.type_as_string());
+                return Some(new_type(ty.as_deref()));
+            }
+        }
+
+        // The typedef of the parameters the function is passed to.
+        let arity = item.child_by_field_name("parameters").map_or(0, |params| {
+            params
+                .children(&mut params.walk())
+                .filter(|param| {
+                    matches!(
+                        TSKind::from(param),
+                        TSKind::parameter_declaration | TSKind::rest_parameter
+                    )
+                })
+                .count()
+        });
+        let root = std::iter::successors(Some(*item), |node| node.parent()).last()?;
+        let mut cursor = root.walk();
+        'outer: loop {
+            let node = cursor.node();
+            if TSKind::from(node) == TSKind::identifier && self.node_text(&node) == Some(name) {
+                if let Some(ty) = self.argument_return_type(&node, arity) {
+                    return Some(ty);
+                }
+            }
+            if cursor.goto_first_child() {
+                continue;
+            }
+            loop {
+                if cursor.goto_next_sibling() {
+                    break;
+                }
+                if !cursor.goto_parent() {
+                    break 'outer;
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Return type of the typedef of the parameter an argument is passed to. The typedef of a
+    /// typeset is chosen by its number of parameters.
+    fn argument_return_type(&self, arg: &Node, arity: usize) -> Option<String> {
+        let arguments = arg
+            .parent()
+            .filter(|parent| TSKind::from(parent) == TSKind::call_arguments)?;
+        let idx = arguments
+            .named_children(&mut arguments.walk())
+            .filter(|child| TSKind::from(child) != TSKind::comment)
+            .position(|child| child == *arg)?;
+        let (function, _) = call(self.sema, self.file_id, &arguments.parent()?)?;
+        let db = self.sema.db;
+        let ty = db
+            .function_data(function.id())
+            .params()
+            .get(idx)?
+            .type_ref
+            .as_ref()?
+            .type_as_string();
+        let typedef = match self.file_id.resolver(db).resolve_ident(&ty)? {
+            ValueNs::TypedefId(id) => Typedef::from(id.value),
+            ValueNs::TypesetId(id) => {
+                let typedefs = Typeset::from(id.value).children(db);
+                typedefs
+                    .iter()
+                    .find(|typedef| typedef.parameters(db).len() == arity)
+                    .or(typedefs.first())
+                    .copied()?
+            }
+            _ => return None,
+        };
+
+        Some(new_type(Some(&typedef.return_type(db))))
+    }
+}
+
+/// Whether a function body contains a `return` statement with a value.
+fn returns_value(body: &Node) -> bool {
+    let mut cursor = body.walk();
+    'outer: loop {
+        let node = cursor.node();
+        if TSKind::from(node) == TSKind::return_statement && node.named_child_count() > 0 {
+            return true;
+        }
+        if cursor.goto_first_child() {
+            continue;
+        }
+        loop {
+            if cursor.node() == *body {
+                break 'outer;
+            }
+            if cursor.goto_next_sibling() {
+                break;
+            }
+            if !cursor.goto_parent() {
+                break 'outer;
+            }
+        }
+    }
+
+    false
+}
+
+/// Edit adding `#pragma newdecls required` after the includes of a file, if none of its active
+/// directives, e.g `#pragma newdecls optional` or `#require_newdecls`, sets the requirement.
+fn newdecls_pragma(
+    db: &RootDatabase,
+    file_id: FileId,
+    text: &str,
+    style_pragmas: &StylePragmas,
+) -> Option<TextEdit> {
+    if style_pragmas
+        .iter()
+        .any(|pragma| pragma.kind() == StylePragmaKind::Newdecls)
+    {
+        return None;
+    }
+
+    // The implicit includes have no range.
+    let last_include = db
+        .file_includes(file_id)
+        .0
+        .iter()
+        .filter_map(|include| include.range())
+        .max_by_key(|range| range.end());
+    let edit = match last_include {
+        Some(range) => match text[usize::from(range.end())..].find('\n') {
+            Some(idx) => TextEdit::new(
+                TextRange::empty(range.end() + TextSize::new(idx as u32 + 1)),
+                "\n#pragma newdecls required\n".to_string(),
+            ),
+            None => TextEdit::new(
+                TextRange::empty(TextSize::of(text)),
+                "\n\n#pragma newdecls required\n".to_string(),
+            ),
+        },
+        None => TextEdit::new(
+            TextRange::empty(TextSize::new(0)),
+            "#pragma newdecls required\n\n".to_string(),
+        ),
+    };
+
+    Some(edit)
+}
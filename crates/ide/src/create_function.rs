@@ -0,0 +1,292 @@
+use base_db::{FileRange, SourceDatabase, SourceDatabaseExt};
+use hir::Semantics;
+use ide_db::{Assist, AssistId, AssistKind, RootDatabase, SourceChange, TextEdit};
+use line_index::{TextRange, TextSize};
+use syntax::{utils::ts_range_to_text_range, TSKind};
+use tree_sitter::Node;
+use vfs::FileId;
+
+use crate::extract_variable::{normalize_type, suggested_name, type_of};
+
+// Feature: Create Function
+//
+// Offers to generate the definition of the undefined function called under the cursor, e.g
+// `GiveCredits(client, 10)` generates:
+//
+// ```sourcepawn
+// void GiveCredits(int client, int value)
+// {
+// }
+// ```
+//
+// The types of the parameters are guessed from the arguments of the call, and the return type
+// from the way the value of the call is used. The function is either created below the current
+// function, or as a `stock` at the end of one of the files of the project included by the
+// current file.
+pub(crate) fn create_function(db: &RootDatabase, frange: FileRange) -> Vec<Assist> {
+    let sema = &Semantics::new(db);
+    let preprocessing_results = sema.preprocess_file(frange.file_id);
+    let source_map = preprocessing_results.source_map();
+    let source = preprocessing_results.preprocessed_text();
+    let tree = sema.parse(frange.file_id);
+
+    let Some(s_range) = source_map.closest_s_range(frange.range) else {
+        return Vec::new();
+    };
+    let Some(name_node) = tree
+        .root_node()
+        .descendant_for_byte_range(s_range.start().into(), s_range.start().into())
+    else {
+        return Vec::new();
+    };
+    let Some(call) = name_node.parent() else {
+        return Vec::new();
+    };
+    if TSKind::from(name_node) != TSKind::identifier
+        || TSKind::from(call) != TSKind::call_expression
+        || call.child_by_field_name("function") != Some(name_node)
+        || sema.find_def(frange.file_id, &name_node).is_some()
+    {
+        return Vec::new();
+    }
+    let (Ok(name), Some(target)) = (
+        name_node.utf8_text(source.as_bytes()),
+        source_map.closest_u_range(ts_range_to_text_range(&name_node.range())),
+    ) else {
+        return Vec::new();
+    };
+
+    let signature = format!(
+        "{} {name}({})",
+        return_type(sema, frange.file_id, &call, source),
+        parameters(sema, frange.file_id, &call, source).join(", ")
+    );
+    let mut res = Vec::new();
+
+    // Below the current function, or at the end of the file.
+    let text = db.file_text(frange.file_id);
+    let function = std::iter::successors(Some(call), |node| node.parent())
+        .find(|node| TSKind::from(node) == TSKind::function_definition);
+    let offset = match function {
+        Some(function) => source_map
+            .closest_u_range(ts_range_to_text_range(&function.range()))
+            .map(|range| range.end()),
+        None => Some(TextSize::of(text.as_ref())),
+    };
+    if let Some(offset) = offset {
+        let mut source_change = SourceChange::default();
+        source_change.insert(
+            frange.file_id,
+            TextEdit::new(
+                TextRange::empty(offset),
+                format!(
+                    "\n\n{}",
+                    stub(&signature, &text, return_value(&signature).as_deref())
+                ),
+            ),
+        );
+        res.push(Assist {
+            id: AssistId("create_function", AssistKind::QuickFix),
+            label: format!("Create function `{name}`"),
+            target,
+            source_change,
+            trigger_rename: None,
+        });
+    }
+
+    // At the end of an included file of the project.
+    for include in db.file_includes(frange.file_id).0.iter() {
+        let file_id = include.file_id();
+        // The implicit includes have no range.
+        if include.range().is_none() || file_id == frange.file_id {
+            continue;
+        }
+        let source_root = db.source_root(db.file_source_root(file_id));
+        if source_root.is_include_dir {
+            continue;
+        }
+        let Some(file_name) = source_root
+            .path_for_file(&file_id)
+            .and_then(|path| path.name_and_extension())
+            .map(|(name, extension)| match extension {
+                Some(extension) => format!("{name}.{extension}"),
+                None => name.to_string(),
+            })
+        else {
+            continue;
+        };
+        let include_text = db.file_text(file_id);
+        let separator = match include_text.as_ref() {
+            "" => "",
+            text if text.ends_with("\n\n") => "",
+            text if text.ends_with('\n') => "\n",
+            _ => "\n\n",
+        };
+        let mut source_change = SourceChange::default();
+        source_change.insert(
+            file_id,
+            TextEdit::new(
+                TextRange::empty(TextSize::of(include_text.as_ref())),
+                format!(
+                    "{separator}{}\n",
+                    stub(
+                        &format!("stock {signature}"),
+                        &include_text,
+                        return_value(&signature).as_deref()
+                    )
+                ),
+            ),
+        );
+        res.push(Assist {
+            id: AssistId("create_function_in_include", AssistKind::QuickFix),
+            label: format!("Create function `{name}` in `{file_name}`"),
+            target,
+            source_change,
+            trigger_rename: None,
+        });
+    }
+
+    res
+}
+
+/// Definition of a function with an empty body, or returning `value`.
+pub(crate) fn stub(signature: &str, text: &str, value: Option<&str>) -> String {
+    let indent = if text.contains("\n\t") { "\t" } else { "    " };
+    match value {
+        Some(value) => format!("{signature}\n{{\n{indent}return {value};\n}}"),
+        None => format!("{signature}\n{{\n}}"),
+    }
+}
+
+/// Default value returned by a function of the signature.
+pub(crate) fn return_value(signature: &str) -> Option<String> {
+    let ty = signature.split_whitespace().next()?;
+    let value = match ty {
+        "void" => return None,
+        "int" | "any" => "0".to_string(),
+        "float" => "0.0".to_string(),
+        "bool" => "false".to_string(),
+        "Action" => "Plugin_Continue".to_string(),
+        "char" => "'\\0'".to_string(),
+        ty => format!("view_as<{ty}>(0)"),
+    };
+
+    Some(value)
+}
+
+/// Parameters of the function, guessed from the arguments of the call.
+fn parameters(
+    sema: &Semantics<RootDatabase>,
+    file_id: FileId,
+    call: &Node,
+    source: &str,
+) -> Vec<String> {
+    let Some(arguments) = call.child_by_field_name("arguments") else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = Vec::new();
+    let mut res = Vec::new();
+    for argument in arguments.named_children(&mut arguments.walk()) {
+        if TSKind::from(argument) == TSKind::comment {
+            continue;
+        }
+        let ty = type_of(sema, file_id, &argument, source).unwrap_or_else(|| "any".to_string());
+        let name = match TSKind::from(argument) {
+            TSKind::string_literal => "text".to_string(),
+            _ => suggested_name(&argument, source),
+        };
+        let mut unique = name.clone();
+        let mut idx = 2;
+        while names.contains(&unique) {
+            unique = format!("{name}{idx}");
+            idx += 1;
+        }
+        names.push(unique.clone());
+        res.push(match ty.strip_suffix("[]") {
+            Some(ty) if TSKind::from(argument) == TSKind::string_literal => {
+                format!("const {ty}[] {unique}")
+            }
+            Some(ty) => format!("{ty}[] {unique}"),
+            None => format!("{ty} {unique}"),
+        });
+    }
+
+    res
+}
+
+/// Return type of the function, guessed from the way the value of the call is used.
+fn return_type(
+    sema: &Semantics<RootDatabase>,
+    file_id: FileId,
+    call: &Node,
+    source: &str,
+) -> String {
+    let mut node = *call;
+    while let Some(parent) = node.parent() {
+        let child = node;
+        let is_field = |field: &str| parent.child_by_field_name(field) == Some(child);
+        let ty = match TSKind::from(parent) {
+            TSKind::parenthesized_expression => {
+                node = parent;
+                continue;
+            }
+            TSKind::expression_statement => Some("void".to_string()),
+            TSKind::variable_declaration if is_field("initialValue") => parent
+                .parent()
+                .and_then(|statement| statement.child_by_field_name("type"))
+                .and_then(|ty| ty.utf8_text(source.as_bytes()).ok())
+                .and_then(normalize_type),
+            TSKind::old_variable_declaration if is_field("initialValue") => normalize_type(
+                parent
+                    .child_by_field_name("type")
+                    .and_then(|ty| ty.utf8_text(source.as_bytes()).ok())
+                    .unwrap_or("_"),
+            ),
+            TSKind::assignment_expression if is_field("right") => parent
+                .child_by_field_name("left")
+                .and_then(|left| type_of(sema, file_id, &left, source)),
+            TSKind::return_statement => std::iter::successors(Some(parent), |node| node.parent())
+                .find(|node| TSKind::from(node) == TSKind::function_definition)
+                .and_then(|function| function.child_by_field_name("returnType"))
+                .and_then(|ty| ty.utf8_text(source.as_bytes()).ok())
+                .and_then(normalize_type),
+            TSKind::condition_statement
+            | TSKind::while_statement
+            | TSKind::do_while_statement
+            | TSKind::for_statement
+                if is_field("condition") =>
+            {
+                Some("bool".to_string())
+            }
+            TSKind::unary_expression => {
+                match parent
+                    .child_by_field_name("operator")
+                    .and_then(|op| op.utf8_text(source.as_bytes()).ok())
+                {
+                    Some("!") => Some("bool".to_string()),
+                    _ => None,
+                }
+            }
+            TSKind::binary_expression => {
+                match parent
+                    .child_by_field_name("operator")
+                    .and_then(|op| op.utf8_text(source.as_bytes()).ok())
+                {
+                    Some("&&" | "||") => Some("bool".to_string()),
+                    _ => {
+                        let other = if is_field("left") { "right" } else { "left" };
+                        parent
+                            .child_by_field_name(other)
+                            .and_then(|other| type_of(sema, file_id, &other, source))
+                    }
+                }
+            }
+            _ => None,
+        };
+        return ty
+            .filter(|ty| !ty.ends_with("[]") && ty != "any")
+            .unwrap_or_else(|| "int".to_string());
+    }
+
+    "int".to_string()
+}
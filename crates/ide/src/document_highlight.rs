@@ -0,0 +1,137 @@
+use base_db::FilePosition;
+use hir::Semantics;
+use line_index::TextRange;
+use syntax::{utils::ts_range_to_text_range, TSKind};
+
+use crate::RootDatabase;
+
+/// Whether an occurrence of a symbol reads or writes to it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReferenceCategory {
+    Read,
+    Write,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HighlightedRange {
+    /// User range of the occurrence.
+    pub range: TextRange,
+    pub category: ReferenceCategory,
+}
+
+// Feature: Document Highlight
+//
+// Highlights the occurrences of the symbol under the cursor in the current function, or in the
+// file if the cursor is outside of a function.
+//
+// The assignments, e.g `x = 1` or `x++`, and the initialized declarations are writes, the other
+// occurrences are reads.
+pub(crate) fn document_highlight(
+    db: &RootDatabase,
+    pos: FilePosition,
+) -> Option<Vec<HighlightedRange>> {
+    let sema = &Semantics::new(db);
+    let preprocessing_results = sema.preprocess_file(pos.file_id);
+    let source_map = preprocessing_results.source_map();
+    let source = sema.preprocessed_text(pos.file_id);
+    let tree = sema.parse(pos.file_id);
+
+    let offset = source_map.closest_s_position_always(pos.offset);
+    let node = tree
+        .root_node()
+        .descendant_for_byte_range(offset.into(), offset.into())?;
+    if TSKind::from(node) != TSKind::identifier {
+        return None;
+    }
+    let name = node.utf8_text(source.as_bytes()).ok()?;
+    let def = sema.find_def(pos.file_id, &node)?;
+
+    let scope = std::iter::successors(node.parent(), |node| node.parent())
+        .find(|node| is_function(TSKind::from(node)))
+        .unwrap_or_else(|| tree.root_node());
+
+    let mut res: Vec<HighlightedRange> = Vec::new();
+    let mut cursor = scope.walk();
+    'outer: loop {
+        let node = cursor.node();
+        if TSKind::from(node) == TSKind::identifier
+            && node.utf8_text(source.as_bytes()).ok() == Some(name)
+            && sema.find_def(pos.file_id, &node).as_ref() == Some(&def)
+        {
+            // Skip the occurrences expanded from a macro which are not written in the file.
+            if let Some(range) = source_map.closest_u_range(ts_range_to_text_range(&node.range())) {
+                if !res.iter().any(|highlight| highlight.range == range) {
+                    res.push(HighlightedRange {
+                        range,
+                        category: category(&node),
+                    });
+                }
+            }
+        }
+        if cursor.goto_first_child() {
+            continue;
+        }
+        loop {
+            if cursor.node() == scope {
+                break 'outer;
+            }
+            if cursor.goto_next_sibling() {
+                break;
+            }
+            if !cursor.goto_parent() {
+                break 'outer;
+            }
+        }
+    }
+
+    Some(res)
+}
+
+pub(crate) fn is_function(kind: TSKind) -> bool {
+    matches!(
+        kind,
+        TSKind::function_definition
+            | TSKind::enum_struct_method
+            | TSKind::methodmap_method
+            | TSKind::methodmap_method_constructor
+            | TSKind::methodmap_method_destructor
+            | TSKind::methodmap_property_getter
+            | TSKind::methodmap_property_setter
+    )
+}
+
+fn category(node: &tree_sitter::Node) -> ReferenceCategory {
+    let Some(parent) = node.parent() else {
+        return ReferenceCategory::Read;
+    };
+    let is_field = |parent: &tree_sitter::Node, field: &str| {
+        parent.child_by_field_name(field).as_ref() == Some(node)
+    };
+    let is_write = match TSKind::from(parent) {
+        TSKind::variable_declaration | TSKind::old_variable_declaration => {
+            is_field(&parent, "name") && parent.child_by_field_name("initialValue").is_some()
+        }
+        TSKind::update_expression => is_field(&parent, "argument"),
+        TSKind::assignment_expression => is_field(&parent, "left"),
+        // `arr[i] = 1` or `this.x = 1`.
+        TSKind::array_indexed_access | TSKind::field_access => {
+            let field = if TSKind::from(parent) == TSKind::array_indexed_access {
+                "array"
+            } else {
+                "field"
+            };
+            is_field(&parent, field)
+                && parent.parent().is_some_and(|grandparent| {
+                    TSKind::from(grandparent) == TSKind::assignment_expression
+                        && grandparent.child_by_field_name("left") == Some(parent)
+                })
+        }
+        _ => false,
+    };
+
+    if is_write {
+        ReferenceCategory::Write
+    } else {
+        ReferenceCategory::Read
+    }
+}
@@ -0,0 +1,41 @@
+use base_db::{SourceDatabase, RE_CHEVRON, RE_QUOTE};
+use line_index::{TextRange, TextSize};
+use vfs::FileId;
+
+// Feature: Document Links
+//
+// The paths of the `#include` and `#tryinclude` directives are links to the files they resolve
+// to.
+
+/// Link from the path of an include directive to the included file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocumentLink {
+    /// Range of the path in the directive, without the chevrons or quotes.
+    pub range: TextRange,
+    pub target: FileId,
+}
+
+/// Compute the links of the resolved includes of the file.
+pub(crate) fn document_links(db: &dyn SourceDatabase, file_id: FileId) -> Vec<DocumentLink> {
+    let text = db.file_text(file_id);
+    db.file_includes(file_id)
+        .0
+        .iter()
+        .filter_map(|include| {
+            // The implicit includes have no range.
+            let range = include.range()?;
+            let directive = text.get(range.start().into()..range.end().into())?;
+            let path = RE_QUOTE
+                .captures(directive)
+                .or_else(|| RE_CHEVRON.captures(directive))?
+                .get(1)?;
+            Some(DocumentLink {
+                range: TextRange::new(
+                    range.start() + TextSize::new(path.start() as u32),
+                    range.start() + TextSize::new(path.end() as u32),
+                ),
+                target: include.file_id(),
+            })
+        })
+        .collect()
+}
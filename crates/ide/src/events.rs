@@ -1,7 +1,7 @@
 //! This module provides completions/hovers for event names and utilities to
 //! check if events completions/hovers should be provided for a given node.
 
-use completion_data::DATABASE;
+use completion_data::{Event, Game, DATABASE};
 use ide_db::Documentation;
 use preprocessor::SourceMap;
 use smol_str::ToSmolStr;
@@ -57,35 +57,19 @@ pub fn event_name(node: &Node, source: &str) -> Option<String> {
 /// Returns completions for event names.
 ///
 /// If `events_game_name` is `Some`, and if the game exits in the database,
-/// only completions for the given game will be returned.
+/// only completions for the given game and the generic events will be returned.
 ///
 /// # Arguments
 /// - `events_game_name`: The name of the game to get completions for
 pub fn events_completions(events_game_name: Option<&str>) -> Vec<CompletionItem> {
-    if let Some(game_name) = events_game_name {
-        if let Some(game) = DATABASE.get(game_name) {
-            return game
-                .events()
-                .iter()
-                .cloned()
-                .chain(DATABASE.generic_events())
-                .map(|ev| CompletionItem {
-                    label: ev.name().to_smolstr(),
-                    kind: CompletionKind::Literal,
-                    detail: Some(game_name.to_string()),
-                    documentation: Documentation::from(&ev).into(),
-                    ..Default::default()
-                })
-                .collect();
-        }
-    }
-    DATABASE
-        .iter()
-        .flat_map(|(_, game)| {
+    games(events_game_name)
+        .into_iter()
+        .flat_map(|game| {
             game.events().iter().map(|ev| CompletionItem {
                 label: ev.name().to_smolstr(),
                 kind: CompletionKind::Literal,
                 detail: Some(game.name().to_string()),
+                documentation: event_documentation(game, ev).into(),
                 ..Default::default()
             })
         })
@@ -95,7 +79,8 @@ pub fn events_completions(events_game_name: Option<&str>) -> Vec<CompletionItem>
 /// Returns hover information for an event.
 ///
 /// If `events_game_name` is `Some`, and if the game exits in the database, only return the
-/// hover information for the given game. Otherwise, return hover information for all games.
+/// hover information for the given game and the generic events. Otherwise, return hover
+/// information for all games.
 ///
 /// # Arguments
 /// - `events_game_name`: The name of the game to get hover information for
@@ -108,39 +93,51 @@ pub fn event_hover(
     node: &Node,
     source_map: &SourceMap,
 ) -> Option<RangeInfo<HoverResult>> {
-    if let Some(game) = events_game_name {
-        if let Some(ev) = DATABASE.get_event(game, name) {
-            let markup = Markup::from(format!(
+    let sections = games(events_game_name)
+        .into_iter()
+        .filter_map(|game| {
+            let ev = game.events().iter().find(|ev| ev.name() == name)?;
+            Some(format!(
                 "## {}\n\n{}",
-                game,
-                Documentation::from(ev).to_markdown()
-            ));
-            let res = HoverResult {
-                markup,
-                actions: Default::default(),
-            };
-            return Some(RangeInfo::new(
-                source_map.closest_u_range_always(ts_range_to_text_range(&node.range())),
-                res,
-            ));
-        }
+                game.name(),
+                event_documentation(game, ev).to_markdown()
+            ))
+        })
+        .collect::<Vec<_>>();
+    if sections.is_empty() {
+        return None;
+    }
 
-        None
-    } else {
-        let mut res = Vec::new();
-        DATABASE
-            .get_events(name)
-            .into_iter()
-            .for_each(|(game, ev)| {
-                res.push(format!("## {}", game));
-                res.push(Documentation::from(&ev).to_markdown());
-            });
-        Some(RangeInfo::new(
-            source_map.closest_u_range_always(ts_range_to_text_range(&node.range())),
-            HoverResult {
-                markup: Markup::from(res.join("\n\n")),
-                actions: Default::default(),
-            },
-        ))
+    Some(RangeInfo::new(
+        source_map.closest_u_range_always(ts_range_to_text_range(&node.range())),
+        HoverResult {
+            markup: Markup::from(sections.join("\n\n")),
+            actions: Default::default(),
+        },
+    ))
+}
+
+/// Returns the games whose events are known in the plugin: the given game and the generic
+/// events if it exists in the database, all the games otherwise.
+fn games(events_game_name: Option<&str>) -> Vec<&'static Game<'static>> {
+    match events_game_name.and_then(|name| DATABASE.get(name)) {
+        Some(game) => std::iter::once(game)
+            .chain(
+                DATABASE
+                    .generic_games()
+                    .filter(|generic| generic.name() != game.name()),
+            )
+            .collect(),
+        None => DATABASE.iter().map(|(_, game)| game).collect(),
     }
 }
+
+/// Returns the documentation of an event, with a link to the description of its fields.
+fn event_documentation(game: &Game, ev: &Event) -> Documentation {
+    Documentation::from(format!(
+        "{}\n\n[Fields of `{}` on the Alliedmodders wiki]({})",
+        Documentation::from(ev).as_str(),
+        ev.name(),
+        game.wiki_url(ev.name())
+    ))
+}
@@ -0,0 +1,425 @@
+use base_db::{FilePosition, FileRange, SourceDatabase};
+use fxhash::FxHashSet;
+use hir::{DefResolution, Semantics};
+use ide_db::{Assist, AssistId, AssistKind, RootDatabase, SourceChange, TextEdit};
+use line_index::{TextRange, TextSize};
+use syntax::{utils::ts_range_to_text_range, TSKind};
+use tree_sitter::Node;
+use vfs::FileId;
+
+use crate::document_highlight::is_function;
+
+// Feature: Extract Variable
+//
+// Extracts the selected expression into a local variable declared before the statement using
+// it, e.g `PrintToServer("%d", GetClientHealth(client) * 2)` becomes:
+//
+// ```sourcepawn
+// int clientHealth = GetClientHealth(client);
+// PrintToServer("%d", clientHealth * 2);
+// ```
+//
+// The type of the variable is guessed from the expression, and defaults to `any`. A rename of
+// the variable is triggered once the assist is applied.
+//
+// The expressions of the conditions of the loops are not extracted, as they are evaluated at
+// each iteration, and neither are the right operands of `&&` and `||` and the branches of the
+// ternaries, as they are not always evaluated.
+pub(crate) fn extract_variable(db: &RootDatabase, frange: FileRange) -> Option<Assist> {
+    let sema = &Semantics::new(db);
+    let preprocessing_results = sema.preprocess_file(frange.file_id);
+    let source_map = preprocessing_results.source_map();
+    let source = preprocessing_results.preprocessed_text();
+    let tree = sema.parse(frange.file_id);
+    let text = db.file_text(frange.file_id);
+
+    let selection = trim_range(&text, frange.range);
+    if selection.is_empty() {
+        return None;
+    }
+    let s_range = source_map.closest_s_range(selection)?;
+    let expr = tree
+        .root_node()
+        .descendant_for_byte_range(s_range.start().into(), s_range.end().into())?;
+    // The selection must cover exactly an expression written in the file.
+    if !is_extractable(&expr)
+        || source_map.closest_u_range(ts_range_to_text_range(&expr.range())) != Some(selection)
+    {
+        return None;
+    }
+
+    let statement = enclosing_statement(&expr)?;
+    let statement_range = source_map.closest_u_range(ts_range_to_text_range(&statement.range()))?;
+    let ty = type_of(sema, frange.file_id, &expr, source)?;
+    if ty.ends_with("[]") && TSKind::from(expr) != TSKind::string_literal {
+        // Arrays cannot be copied.
+        return None;
+    }
+
+    let scope = std::iter::successors(statement.parent(), |node| node.parent())
+        .find(|node| is_function(TSKind::from(node)))?;
+    let name = unique_name(
+        sema,
+        frange.file_id,
+        &scope,
+        source,
+        &suggested_name(&expr, source),
+    );
+
+    let line_start = text[..usize::from(statement_range.start())]
+        .rfind('\n')
+        .map_or(0, |idx| idx + 1);
+    let indent = &text[line_start..usize::from(statement_range.start())];
+    let separator = if indent.trim().is_empty() {
+        format!("\n{indent}")
+    } else {
+        " ".to_string()
+    };
+    let value = &text[selection];
+    let declaration = match ty.strip_suffix("[]") {
+        Some(ty) => format!("{ty} {name}[] = {value};"),
+        None => format!("{ty} {name} = {value};"),
+    };
+
+    let mut source_change = SourceChange::default();
+    source_change.insert(
+        frange.file_id,
+        TextEdit::new(
+            TextRange::empty(statement_range.start()),
+            format!("{declaration}{separator}"),
+        ),
+    );
+    source_change.insert(frange.file_id, TextEdit::new(selection, name));
+
+    Some(Assist {
+        id: AssistId("extract_variable", AssistKind::RefactorExtract),
+        label: "Extract into variable".to_string(),
+        target: selection,
+        source_change,
+        trigger_rename: Some(FilePosition {
+            file_id: frange.file_id,
+            offset: statement_range.start()
+                + TextSize::of(ty.trim_end_matches("[]"))
+                + TextSize::of(' '),
+        }),
+    })
+}
+
+/// Shrinks a range to exclude its leading and trailing whitespaces.
+pub(crate) fn trim_range(text: &str, range: TextRange) -> TextRange {
+    let selected = &text[range];
+    let start =
+        range.start() + TextSize::of(&selected[..selected.len() - selected.trim_start().len()]);
+    let end = range.end() - TextSize::of(&selected[selected.trim_end().len()..]);
+
+    TextRange::new(start, end.max(start))
+}
+
+fn is_extractable(node: &Node) -> bool {
+    if !matches!(
+        TSKind::from(node),
+        TSKind::array_indexed_access
+            | TSKind::binary_expression
+            | TSKind::bool_literal
+            | TSKind::call_expression
+            | TSKind::char_literal
+            | TSKind::field_access
+            | TSKind::float_literal
+            | TSKind::int_literal
+            | TSKind::new_expression
+            | TSKind::old_type_cast
+            | TSKind::parenthesized_expression
+            | TSKind::scope_access
+            | TSKind::sizeof_expression
+            | TSKind::string_literal
+            | TSKind::ternary_expression
+            | TSKind::unary_expression
+            | TSKind::view_as
+    ) {
+        return false;
+    }
+    let Some(parent) = node.parent() else {
+        return false;
+    };
+    let is_field = |field: &str| parent.child_by_field_name(field).as_ref() == Some(node);
+    match TSKind::from(parent) {
+        // Extracting the whole statement does not make sense.
+        TSKind::expression_statement => false,
+        TSKind::assignment_expression => !is_field("left"),
+        TSKind::update_expression => !is_field("argument"),
+        TSKind::array_indexed_access => !is_field("array"),
+        TSKind::call_expression => !is_field("function"),
+        _ => true,
+    }
+}
+
+/// Statement of the block in which the variable is declared.
+///
+/// Returns `None` if the expression is not always evaluated by the statement, e.g the right
+/// operand of `&&` or a branch of a ternary, as declaring it before would evaluate it anyway.
+fn enclosing_statement<'tree>(expr: &Node<'tree>) -> Option<Node<'tree>> {
+    let mut node = *expr;
+    let statement = loop {
+        let parent = node.parent()?;
+        if is_statement(TSKind::from(parent)) {
+            break parent;
+        }
+        if is_conditionally_evaluated(&parent, &node) {
+            return None;
+        }
+        node = parent;
+    };
+    if TSKind::from(statement.parent()?) != TSKind::block
+        || matches!(
+            TSKind::from(statement),
+            TSKind::for_statement | TSKind::while_statement | TSKind::do_while_statement
+        )
+    {
+        return None;
+    }
+
+    Some(statement)
+}
+
+/// Whether `child` is only evaluated depending on the value of another operand of `parent`.
+fn is_conditionally_evaluated(parent: &Node, child: &Node) -> bool {
+    let is_field = |field: &str| parent.child_by_field_name(field).as_ref() == Some(child);
+    match TSKind::from(parent) {
+        TSKind::binary_expression => {
+            is_field("right")
+                && parent
+                    .child_by_field_name("operator")
+                    .is_some_and(|operator| matches!(operator.kind(), "&&" | "||"))
+        }
+        TSKind::ternary_expression => is_field("consequence") || is_field("alternative"),
+        _ => false,
+    }
+}
+
+fn is_statement(kind: TSKind) -> bool {
+    matches!(
+        kind,
+        TSKind::block
+            | TSKind::break_statement
+            | TSKind::condition_statement
+            | TSKind::continue_statement
+            | TSKind::delete_statement
+            | TSKind::do_while_statement
+            | TSKind::expression_statement
+            | TSKind::for_statement
+            | TSKind::old_variable_declaration_statement
+            | TSKind::return_statement
+            | TSKind::switch_statement
+            | TSKind::variable_declaration_statement
+            | TSKind::while_statement
+    )
+}
+
+/// Guesses the type of an expression, as written in a declaration.
+///
+/// Returns `None` if the expression has no value, e.g a call to a `void` function.
+pub(crate) fn type_of(
+    sema: &Semantics<RootDatabase>,
+    file_id: FileId,
+    node: &Node,
+    source: &str,
+) -> Option<String> {
+    let field = |name: &str| node.child_by_field_name(name);
+    let text = |node: Node| node.utf8_text(source.as_bytes()).ok().map(String::from);
+    let ty = match TSKind::from(node) {
+        TSKind::int_literal | TSKind::sizeof_expression => "int".to_string(),
+        TSKind::float_literal => "float".to_string(),
+        TSKind::bool_literal => "bool".to_string(),
+        TSKind::char_literal => "char".to_string(),
+        TSKind::string_literal => "char[]".to_string(),
+        TSKind::parenthesized_expression => type_of(sema, file_id, &field("expression")?, source)?,
+        TSKind::ternary_expression => type_of(sema, file_id, &field("consequence")?, source)?,
+        TSKind::unary_expression => {
+            if text(field("operator")?)? == "!" {
+                "bool".to_string()
+            } else {
+                type_of(sema, file_id, &field("argument")?, source)?
+            }
+        }
+        TSKind::binary_expression => match text(field("operator")?)?.as_str() {
+            "==" | "!=" | "<" | "<=" | ">" | ">=" | "&&" | "||" => "bool".to_string(),
+            _ => type_of(sema, file_id, &field("left")?, source)?,
+        },
+        TSKind::view_as | TSKind::old_type_cast => normalize_type(&text(field("type")?)?)?,
+        TSKind::new_expression => text(field("class")?)?,
+        TSKind::array_indexed_access => {
+            let ty = type_of(sema, file_id, &field("array")?, source)?;
+            match ty.strip_suffix("[]") {
+                Some(ty) => ty.to_string(),
+                None => "any".to_string(),
+            }
+        }
+        TSKind::call_expression => {
+            let function = field("function")?;
+            match TSKind::from(function) {
+                TSKind::builtin_type => normalize_type(&text(function)?)?,
+                TSKind::field_access => {
+                    def_type(sema, file_id, &function.child_by_field_name("field")?, true)?
+                }
+                _ => def_type(sema, file_id, &function, true)?,
+            }
+        }
+        TSKind::field_access => def_type(sema, file_id, &field("field")?, false)?,
+        TSKind::identifier => def_type(sema, file_id, node, false)?,
+        _ => "any".to_string(),
+    };
+
+    Some(ty)
+}
+
+/// Type of the value of the symbol referenced by `node`, or of its return value if it is called.
+fn def_type(
+    sema: &Semantics<RootDatabase>,
+    file_id: FileId,
+    node: &Node,
+    called: bool,
+) -> Option<String> {
+    let db = sema.db;
+    let Some(def) = sema.find_def(file_id, node) else {
+        return Some("any".to_string());
+    };
+    let ty = match def {
+        // Functions without a return type return untagged integers.
+        DefResolution::Function(it) if called => {
+            it.type_ref(db).unwrap_or_else(|| "int".to_string())
+        }
+        DefResolution::Methodmap(_) | DefResolution::EnumStruct(_) if called => {
+            def.name(db)?.to_string()
+        }
+        DefResolution::Local(it) => it.def.type_ref(db).unwrap_or_else(|| "int".to_string()),
+        DefResolution::Global(it) => it.type_ref(db).unwrap_or_else(|| "int".to_string()),
+        DefResolution::Field(it) => it.type_ref(db),
+        DefResolution::Property(it) => it.type_ref(db),
+        DefResolution::Variant(it) => it
+            .type_def(db)
+            .first()
+            .and_then(|def| def.name(db))
+            .map(|name| name.to_string())
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| "int".to_string()),
+        _ => "any".to_string(),
+    };
+
+    normalize_type(&ty)
+}
+
+/// Converts a type of the old syntax, e.g `Float:`, to the new syntax.
+///
+/// Returns `None` for the types which cannot be declared.
+pub(crate) fn normalize_type(ty: &str) -> Option<String> {
+    match ty.trim().trim_end_matches(':') {
+        "void" | "String" => None,
+        "_" => Some("int".to_string()),
+        "Float" => Some("float".to_string()),
+        ty => Some(ty.to_string()),
+    }
+}
+
+pub(crate) fn suggested_name(expr: &Node, source: &str) -> String {
+    let text = |node: Option<Node>| {
+        node.and_then(|node| node.utf8_text(source.as_bytes()).ok())
+            .unwrap_or_default()
+            .to_string()
+    };
+    let name = match TSKind::from(expr) {
+        TSKind::call_expression => {
+            let function = expr.child_by_field_name("function");
+            let name = match function.map(TSKind::from) {
+                Some(TSKind::field_access) => {
+                    text(function.and_then(|it| it.child_by_field_name("field")))
+                }
+                _ => text(function),
+            };
+            match name.strip_prefix("Get") {
+                Some(rest) if rest.starts_with(|c: char| c.is_ascii_uppercase()) => {
+                    rest.to_string()
+                }
+                _ => name,
+            }
+        }
+        TSKind::identifier => text(Some(*expr)),
+        TSKind::field_access => text(expr.child_by_field_name("field")),
+        TSKind::new_expression => text(expr.child_by_field_name("class")),
+        TSKind::parenthesized_expression => {
+            return match expr.child_by_field_name("expression") {
+                Some(inner) => suggested_name(&inner, source),
+                None => "value".to_string(),
+            }
+        }
+        TSKind::view_as | TSKind::old_type_cast => {
+            return match expr.child_by_field_name("value") {
+                Some(inner) => suggested_name(&inner, source),
+                None => "value".to_string(),
+            }
+        }
+        _ => String::new(),
+    };
+
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) if first.is_ascii_alphabetic() => {
+            format!("{}{}", first.to_ascii_lowercase(), chars.as_str())
+        }
+        _ => "value".to_string(),
+    }
+}
+
+/// Appends a number to `name` until it is not used by another symbol visible in `scope`.
+pub(crate) fn unique_name(
+    sema: &Semantics<RootDatabase>,
+    file_id: FileId,
+    scope: &Node,
+    source: &str,
+    name: &str,
+) -> String {
+    let mut taken: FxHashSet<String> = sema
+        .defs_in_scope(file_id)
+        .into_iter()
+        .filter_map(|def| def.name(sema.db))
+        .map(|name| name.to_string())
+        .collect();
+    taken.extend(
+        sema.preprocess_file(file_id)
+            .macros()
+            .keys()
+            .map(|name| name.to_string()),
+    );
+
+    let mut cursor = scope.walk();
+    'outer: loop {
+        let node = cursor.node();
+        if TSKind::from(node) == TSKind::identifier {
+            if let Ok(text) = node.utf8_text(source.as_bytes()) {
+                taken.insert(text.to_string());
+            }
+        }
+        if cursor.goto_first_child() {
+            continue;
+        }
+        loop {
+            if cursor.node() == *scope {
+                break 'outer;
+            }
+            if cursor.goto_next_sibling() {
+                break;
+            }
+            if !cursor.goto_parent() {
+                break 'outer;
+            }
+        }
+    }
+
+    let mut res = name.to_string();
+    let mut idx = 2;
+    while taken.contains(&res) {
+        res = format!("{name}{idx}");
+        idx += 1;
+    }
+
+    res
+}
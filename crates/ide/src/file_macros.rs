@@ -0,0 +1,68 @@
+use hir::{HasSource, Semantics};
+use line_index::TextRange;
+use preprocessor::db::PreprocDatabase;
+use smol_str::SmolStr;
+use syntax::utils::ts_range_to_text_range;
+use vfs::FileId;
+
+use crate::{goto_definition::find_inner_name_range, NavigationTarget, RootDatabase};
+
+// Feature: File Macros
+//
+// Lists the macros visible at the end of a file, including the ones defined by its includes.
+//
+// |===
+// | Editor  | Action Name
+//
+// | VS Code | **sourcepawn-studio/macros**
+// |===
+
+/// Macro visible at the end of a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileMacro {
+    pub name: SmolStr,
+    /// Parameters of the macro, e.g `["%1", "%2"]`, [`None`] if it does not take arguments.
+    pub params: Option<Vec<SmolStr>>,
+    /// Text the macro expands to, on a single line.
+    pub body: String,
+    /// File which defines the macro.
+    pub file_id: FileId,
+    /// Range of the `#define` directive in `file_id`, [`None`] if the macro is not defined by a
+    /// directive, e.g the macros of the configuration.
+    pub range: Option<TextRange>,
+}
+
+/// Compute the macros visible at the end of the given file, sorted by name.
+pub(crate) fn file_macros(db: &RootDatabase, file_id: FileId) -> Vec<FileMacro> {
+    let sema = &Semantics::new(db);
+    let mut res: Vec<_> = db
+        .preprocess_file(file_id)
+        .macros()
+        .iter()
+        .map(|(name, macro_)| FileMacro {
+            name: name.clone(),
+            params: macro_.params(),
+            body: macro_.value(),
+            file_id: macro_.file_id(),
+            range: macro_range(sema, macro_.file_id(), macro_.idx()),
+        })
+        .collect();
+    res.sort_by(|a, b| a.name.cmp(&b.name));
+
+    res
+}
+
+fn macro_range(sema: &Semantics<RootDatabase>, file_id: FileId, idx: u32) -> Option<TextRange> {
+    let def = sema.macro_from_idx(file_id, idx)?;
+    let tree = sema.parse(file_id);
+    let node = def.source(sema.db, &tree)?.value;
+    let nav = NavigationTarget::from_ranges(
+        SmolStr::default(),
+        file_id,
+        sema.preprocess_file(file_id).source_map(),
+        ts_range_to_text_range(&node.range()),
+        find_inner_name_range(&node),
+    );
+
+    Some(nav.full_range)
+}
@@ -0,0 +1,133 @@
+use hir_def::DefDatabase;
+use line_index::{TextRange, TextSize};
+use preprocessor::db::PreprocDatabase;
+use syntax::{utils::ts_range_to_text_range, TSKind};
+use vfs::FileId;
+
+use crate::RootDatabase;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FoldKind {
+    /// Code between braces, e.g the body of a function.
+    Block,
+    /// Multi-line comment, or consecutive line comments.
+    Comment,
+    /// Consecutive `#include` and `#tryinclude` directives.
+    Includes,
+    /// Branch of a preprocessor condition, from its `#if`, `#elseif` or `#else` to the next
+    /// directive of the condition.
+    Condition,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fold {
+    pub range: TextRange,
+    pub kind: FoldKind,
+}
+
+// Feature: Folding
+//
+// Defines folding regions for the code between braces, e.g the bodies of the functions, enums,
+// enum structs and methodmaps, the comments, the blocks of includes, and the branches of the
+// preprocessor conditions, including the inactive ones.
+pub(crate) fn folding_ranges(db: &RootDatabase, file_id: FileId) -> Vec<Fold> {
+    let tree = db.parse(file_id);
+    let preprocessing_results = db.preprocess_file(file_id);
+    let source_map = preprocessing_results.source_map();
+    let mut res = Vec::new();
+
+    let source = db.preprocessed_text(file_id);
+    let mut push_fold = |s_range: TextRange, kind: FoldKind| {
+        if let Some(range) = source_map.closest_u_range(s_range) {
+            res.push(Fold { range, kind });
+        }
+    };
+    let mut cursor = tree.root_node().walk();
+    'outer: loop {
+        let node = cursor.node();
+        if let Some(s_range) = block_range(&node) {
+            push_fold(s_range, FoldKind::Block);
+        }
+        for (s_range, kind) in sibling_groups(&node, &source) {
+            push_fold(s_range, kind);
+        }
+        if cursor.goto_first_child() {
+            continue;
+        }
+        while !cursor.goto_next_sibling() {
+            if !cursor.goto_parent() {
+                break 'outer;
+            }
+        }
+    }
+
+    res.extend(
+        preprocessor::condition_branch_ranges(&db.file_text(file_id))
+            .into_iter()
+            .map(|range| Fold {
+                range,
+                kind: FoldKind::Condition,
+            }),
+    );
+    res.sort_by_key(|fold| fold.range.start());
+
+    res
+}
+
+/// Group the children of a node which fold together: the runs of includes and of line comments
+/// on consecutive lines. The block comments fold on their own.
+fn sibling_groups(node: &tree_sitter::Node, source: &str) -> Vec<(TextRange, FoldKind)> {
+    let mut res = Vec::new();
+    let mut current: Option<(TextRange, FoldKind)> = None;
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        // The directives include their trailing newline, which would otherwise count as the
+        // line break between two siblings.
+        let range = ts_range_to_text_range(&child.range());
+        let range = TextRange::at(range.start(), TextSize::of(source[range].trim_end()));
+        let kind = match TSKind::from(child) {
+            TSKind::preproc_include | TSKind::preproc_tryinclude => FoldKind::Includes,
+            TSKind::comment if !source[range].starts_with("//") => {
+                res.push((range, FoldKind::Comment));
+                continue;
+            }
+            TSKind::comment => FoldKind::Comment,
+            _ => {
+                res.extend(current.take());
+                continue;
+            }
+        };
+        match &mut current {
+            Some((current_range, current_kind))
+                if *current_kind == kind
+                    && is_next_line(
+                        &source[TextRange::new(current_range.end(), range.start())],
+                    ) =>
+            {
+                *current_range = current_range.cover(range);
+            }
+            _ => {
+                res.extend(current.take());
+                current = Some((range, kind));
+            }
+        }
+    }
+    res.extend(current);
+
+    res
+}
+
+/// Whether the text between two nodes only breaks the line once.
+fn is_next_line(text: &str) -> bool {
+    text.trim().is_empty() && text.matches('\n').count() <= 1
+}
+
+/// Range from the opening brace to the closing brace of a node delimited by braces.
+fn block_range(node: &tree_sitter::Node) -> Option<TextRange> {
+    let mut cursor = node.walk();
+    let mut children = node.children(&mut cursor);
+    let l_brace = children.find(|child| child.kind() == "{")?;
+    let r_brace = children.filter(|child| child.kind() == "}").last()?;
+
+    Some(ts_range_to_text_range(&l_brace.range()).cover(ts_range_to_text_range(&r_brace.range())))
+}
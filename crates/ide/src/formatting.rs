@@ -0,0 +1,1684 @@
+//! Formatter of SourcePawn files.
+//!
+//! The formatter works on the lines of a file instead of its syntax tree, so that it can format
+//! the code disabled by the preprocessor. Each line of code is split
+//! in tokens, which are joined back with a normalized spacing, and indented by the blocks,
+//! parentheses and statements without braces opened before it:
+//!
+//! - the braces of the blocks are moved according to the [`BraceStyle`],
+//! - the `case` labels are indented in their `switch`, and their statements in the label,
+//! - the lines longer than the column limit are wrapped after the commas of their arguments,
+//! - the values of consecutive `#define`s are aligned.
+//!
+//! The preprocessor directives are not indented, and the lines of block comments keep their
+//! indentation relative to the line which opened them, except for the leading stars of
+//! documentation comments which are aligned.
+//!
+//! The lines between `// fmt: off` and `// fmt: on` comments are kept as they are, e.g to protect
+//! the tables aligned by hand.
+//!
+//! A range is formatted by formatting the whole file, and keeping the formatted lines of the
+//! range, so that they are indented by the lines before them.
+//!
+//! The files with syntax errors are not formatted, as the lines following an unclosed block or
+//! parenthesis would be indented by it.
+
+use std::collections::VecDeque;
+
+use base_db::{FilePosition, FileRange, SourceDatabase};
+use hir_def::DefDatabase;
+use ide_db::{RootDatabase, TextEdit};
+use line_index::{TextRange, TextSize};
+use vfs::FileId;
+
+/// Maximum number of consecutive blank lines.
+const MAX_BLANK_LINES: usize = 1;
+
+/// Width of a tab, to measure the length of the lines.
+const TAB_WIDTH: usize = 4;
+
+/// Operators of more than one character, longest first.
+const OPERATORS: &[&str] = &[
+    ">>>=", "...", ">>>", "<<=", ">>=", "==", "!=", "<=", ">=", "&&", "||", "++", "--", "+=", "-=",
+    "*=", "/=", "%=", "&=", "|=", "^=", "<<", ">>",
+];
+
+/// Binary operators, which are surrounded by spaces.
+const BINARY_OPERATORS: &[&str] = &[
+    "=", "==", "!=", "<", ">", "<=", ">=", "&&", "||", "*", "/", "%", "^", "|", "<<", ">>", ">>>",
+    "+=", "-=", "*=", "/=", "%=", "&=", "|=", "^=", "<<=", ">>=", ">>>=",
+];
+
+/// Keywords followed by a space before their parenthesis.
+const SPACED_KEYWORDS: &[&str] = &["case", "for", "if", "return", "switch", "while"];
+
+/// Keywords which can be followed by a unary operator.
+const NON_OPERAND_KEYWORDS: &[&str] = &["case", "do", "else", "return"];
+
+/// Keywords of the control statements whose body can be a statement without braces.
+const CONTROL_KEYWORDS: &[&str] = &["do", "else", "for", "if", "while"];
+
+/// Indentation of the formatted files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndentStyle {
+    Tabs,
+    Spaces(u32),
+}
+
+/// Placement of the opening braces of the blocks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BraceStyle {
+    /// The braces are on their own line, e.g `if (x)\n{`.
+    Allman,
+    /// The braces are at the end of the line of their statement, e.g `if (x) {`.
+    Attach,
+}
+
+/// Line endings of the formatted files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EndOfLine {
+    Lf,
+    Crlf,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormattingConfig {
+    pub indent_style: IndentStyle,
+    pub brace_style: BraceStyle,
+    /// Maximum length of the lines, beyond which the arguments of the calls are wrapped. `0`
+    /// disables the wrapping.
+    pub column_limit: usize,
+    /// Line endings of the formatted files, or [`None`] to keep the ones of each file.
+    pub end_of_line: Option<EndOfLine>,
+    /// Whether the formatted files end with a newline.
+    pub insert_final_newline: bool,
+}
+
+impl Default for FormattingConfig {
+    fn default() -> Self {
+        Self {
+            indent_style: IndentStyle::Tabs,
+            brace_style: BraceStyle::Allman,
+            column_limit: 100,
+            end_of_line: None,
+            insert_final_newline: true,
+        }
+    }
+}
+
+impl FormattingConfig {
+    fn indent(&self, level: usize) -> String {
+        match self.indent_style {
+            IndentStyle::Tabs => "\t".repeat(level),
+            IndentStyle::Spaces(width) => " ".repeat(level * width as usize),
+        }
+    }
+
+    /// Line ending of a formatted text.
+    fn eol(&self, text: &str) -> &'static str {
+        match self.end_of_line {
+            Some(EndOfLine::Lf) => "\n",
+            Some(EndOfLine::Crlf) => "\r\n",
+            None if text.contains("\r\n") => "\r\n",
+            None => "\n",
+        }
+    }
+}
+
+/// Format a SourcePawn file.
+pub fn format(text: &str, config: &FormattingConfig) -> String {
+    let eol = config.eol(text);
+    let mut res = String::with_capacity(text.len());
+    for line in format_lines(text, config) {
+        res.push_str(&line.text);
+        res.push_str(eol);
+    }
+    if !config.insert_final_newline {
+        res.truncate(res.len().saturating_sub(eol.len()));
+    }
+
+    res
+}
+
+/// Edit formatting a file, or [`None`] if it is formatted or has syntax errors.
+pub(crate) fn format_file(
+    db: &RootDatabase,
+    file_id: FileId,
+    config: &FormattingConfig,
+) -> Option<TextEdit> {
+    if has_syntax_errors(db, file_id) {
+        return None;
+    }
+    let text = db.file_text(file_id);
+    let formatted = format(&text, config);
+
+    diff(&text, &formatted)
+}
+
+/// Edit formatting the lines of a range, or [`None`] if they are formatted or the file has
+/// syntax errors.
+pub(crate) fn format_range(
+    db: &RootDatabase,
+    frange: FileRange,
+    config: &FormattingConfig,
+) -> Option<TextEdit> {
+    if has_syntax_errors(db, frange.file_id) {
+        return None;
+    }
+    range_edit(&db.file_text(frange.file_id), frange.range, config)
+}
+
+fn has_syntax_errors(db: &RootDatabase, file_id: FileId) -> bool {
+    db.parse(file_id).root_node().has_error()
+}
+
+/// Edit applied after a character is typed:
+///
+/// - `}` reindents its line,
+/// - `:` reindents the `case` and `default` labels,
+/// - a newline in a documentation comment continues it with a leading star.
+pub(crate) fn on_char_typed(
+    db: &RootDatabase,
+    position: FilePosition,
+    char_typed: char,
+    config: &FormattingConfig,
+) -> Option<TextEdit> {
+    let text = db.file_text(position.file_id);
+
+    typed_edit(
+        &text,
+        u32::from(position.offset) as usize,
+        char_typed,
+        config,
+    )
+}
+
+/// Formatted lines of a file.
+fn format_lines(text: &str, config: &FormattingConfig) -> Vec<Line> {
+    let mut formatter = Formatter {
+        config: config.clone(),
+        queue: text
+            .lines()
+            .map(|line| line.trim_end().to_string())
+            .enumerate()
+            .collect(),
+        ..Default::default()
+    };
+    while let Some((source, line)) = formatter.queue.pop_front() {
+        formatter.source = source;
+        formatter.line(&line);
+    }
+    while formatter
+        .lines
+        .last()
+        .is_some_and(|line| line.text.is_empty())
+    {
+        formatter.lines.pop();
+    }
+    align_defines(&mut formatter.lines);
+
+    formatter.lines
+}
+
+/// Offsets of the starts of the lines of a text.
+fn line_starts(text: &str) -> Vec<usize> {
+    let mut res = vec![0];
+    res.extend(
+        text.match_indices('\n')
+            .map(|(offset, _)| offset + 1)
+            .filter(|offset| *offset < text.len()),
+    );
+
+    res
+}
+
+/// Text of a line, without its line ending.
+fn line_text<'a>(text: &'a str, starts: &[usize], line: usize) -> &'a str {
+    text[starts[line]..].lines().next().unwrap_or_default()
+}
+
+fn range_edit(text: &str, range: TextRange, config: &FormattingConfig) -> Option<TextEdit> {
+    let starts = line_starts(text);
+    let line_of =
+        |offset: TextSize| starts.partition_point(|start| *start <= u32::from(offset) as usize) - 1;
+    let mut first = line_of(range.start());
+    // A range ending at the start of a line, e.g a selection of whole lines, does not include it.
+    let mut last = match line_of(range.end()) {
+        line if line > first && starts[line] == u32::from(range.end()) as usize => line - 1,
+        line => line,
+    };
+
+    let lines = format_lines(text, config);
+    // Extend the range to the lines joined with its lines, e.g an attached brace.
+    loop {
+        let (start, end) = lines
+            .iter()
+            .map(|line| line.source)
+            .filter(|(start, end)| *start <= last && *end >= first)
+            .fold((first, last), |(first, last), (start, end)| {
+                (first.min(start), last.max(end))
+            });
+        if (start, end) == (first, last) {
+            break;
+        }
+        (first, last) = (start, end);
+    }
+
+    let eol = config.eol(text);
+    let mut formatted = lines
+        .iter()
+        .filter(|line| line.source.0 >= first && line.source.1 <= last)
+        .flat_map(|line| [line.text.as_str(), eol])
+        .collect::<String>();
+    let start = starts[first];
+    let end = starts.get(last + 1).copied().unwrap_or(text.len());
+    if end == text.len() && !config.insert_final_newline {
+        formatted.truncate(formatted.len().saturating_sub(eol.len()));
+    }
+    let edit = diff(&text[start..end], &formatted)?;
+    let offset = TextSize::new(start as u32);
+
+    Some(TextEdit::new(
+        TextRange::new(edit.range().start() + offset, edit.range().end() + offset),
+        edit.replacement_text().to_string(),
+    ))
+}
+
+fn typed_edit(
+    text: &str,
+    offset: usize,
+    char_typed: char,
+    config: &FormattingConfig,
+) -> Option<TextEdit> {
+    let starts = line_starts(text);
+    let line = starts.partition_point(|start| *start <= offset) - 1;
+    let line_start = starts[line];
+    let current = line_text(text, &starts, line);
+    let content = current.trim_start();
+    let indent_end = line_start + current.len() - content.len();
+
+    match char_typed {
+        '}' | ':' => {
+            let (tokens, _) = tokenize(content);
+            let first = tokens.first()?.text;
+            let is_trigger = match char_typed {
+                '}' => first == "}",
+                _ => matches!(first, "case" | "default"),
+            };
+            if !is_trigger {
+                return None;
+            }
+            let formatted = format_lines(text, config)
+                .into_iter()
+                .find(|it| it.source.0 == line)?;
+            let indent =
+                &formatted.text[..formatted.text.len() - formatted.text.trim_start().len()];
+            if text[line_start..indent_end] == *indent {
+                return None;
+            }
+
+            Some(TextEdit::new(
+                TextRange::new(
+                    TextSize::new(line_start as u32),
+                    TextSize::new(indent_end as u32),
+                ),
+                indent.to_string(),
+            ))
+        }
+        '\n' => {
+            // Only the indentation of the editor is before the cursor, and the line is not
+            // already continued.
+            let before = text.get(line_start..offset)?;
+            let after = text[offset..].lines().next().unwrap_or_default();
+            if line == 0 || !before.trim().is_empty() || after.trim_start().starts_with('*') {
+                return None;
+            }
+            let prefix = doc_comment_prefix(text, &starts, line - 1)?;
+
+            Some(TextEdit::new(
+                TextRange::new(
+                    TextSize::new(line_start as u32),
+                    TextSize::new(offset as u32),
+                ),
+                prefix,
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// Prefix of the line after a line of an open documentation comment, e.g ` * ` after `/**`.
+fn doc_comment_prefix(text: &str, starts: &[usize], line: usize) -> Option<String> {
+    let current = line_text(text, starts, line);
+    let content = current.trim_start();
+    let indent = &current[..current.len() - content.len()];
+    if content.contains("*/") {
+        return None;
+    }
+    if content.starts_with("/**") {
+        return Some(format!("{indent} * "));
+    }
+    if !content.starts_with('*') {
+        return None;
+    }
+    // The line is in a documentation comment if the lines before it lead to its opening line.
+    for previous in (0..line).rev() {
+        let content = line_text(text, starts, previous).trim_start();
+        if content.contains("*/") {
+            return None;
+        }
+        if content.starts_with("/**") {
+            return Some(format!("{indent}* "));
+        }
+        if !content.starts_with('*') {
+            return None;
+        }
+    }
+
+    None
+}
+
+/// Edit replacing the lines which differ between a text and its formatted version, so that the
+/// cursors and the markers of the editors are kept on the lines which are not edited.
+fn diff(text: &str, formatted: &str) -> Option<TextEdit> {
+    if text == formatted {
+        return None;
+    }
+    let prefix = text
+        .split_inclusive('\n')
+        .zip(formatted.split_inclusive('\n'))
+        .take_while(|(a, b)| a == b)
+        .map(|(a, _)| a.len())
+        .sum::<usize>();
+    let suffix = text[prefix..]
+        .rsplit_terminator('\n')
+        .zip(formatted[prefix..].rsplit_terminator('\n'))
+        .take_while(|(a, b)| a == b)
+        .map(|(a, _)| a.len() + 1)
+        .sum::<usize>()
+        .min(text.len() - prefix)
+        .min(formatted.len() - prefix);
+    // The suffix starts at the start of a line.
+    let suffix = match (text.ends_with('\n'), formatted.ends_with('\n')) {
+        (true, true) => suffix,
+        _ => 0,
+    };
+    let end = text.len() - suffix;
+
+    Some(TextEdit::new(
+        TextRange::new(TextSize::new(prefix as u32), TextSize::new(end as u32)),
+        formatted[prefix..formatted.len() - suffix].to_string(),
+    ))
+}
+
+#[derive(Debug, Default)]
+struct Line {
+    text: String,
+    /// First and last lines of the source the line is formatted from.
+    source: (usize, usize),
+    /// Name and value of the line, if it is a `#define`.
+    define: Option<(String, String)>,
+}
+
+/// Block of braces.
+#[derive(Debug, Clone, Default)]
+struct Block {
+    /// Whether the block is the body of a `switch`.
+    switch: bool,
+    /// Whether the lines of the block are in the body of a `case` label.
+    in_case: bool,
+    /// Control statements whose body ends with the block, outermost first, with whether they
+    /// are an `if`. The statement which opened the block is the last one, and the others indent
+    /// it, e.g when it is the body of an `if` without braces.
+    statements: Vec<bool>,
+}
+
+/// State of the blocks and statements, saved at a conditional directive to format each of its
+/// branches from the same state.
+#[derive(Debug, Clone)]
+struct State {
+    blocks: Vec<Block>,
+    parens: usize,
+    bodies: Vec<bool>,
+    completed: Vec<bool>,
+    switch: bool,
+    case_label: bool,
+}
+
+/// Line the next line can be joined to, with [`BraceStyle::Attach`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Attach {
+    /// The line can take the opening brace of its block, e.g `if (x)`.
+    Brace,
+    /// The line is a closing brace, which can take an `else`.
+    Else,
+}
+
+#[derive(Debug, Default)]
+struct Formatter {
+    config: FormattingConfig,
+    /// Lines left to format, with their index in the source. The lines whose braces are moved
+    /// are split in this queue.
+    queue: VecDeque<(usize, String)>,
+    /// Index in the source of the line being formatted.
+    source: usize,
+    /// Formatted lines.
+    lines: Vec<Line>,
+    /// Blocks opened before the line.
+    blocks: Vec<Block>,
+    /// Number of the parentheses and brackets opened before the line, and not closed.
+    parens: usize,
+    /// Whether the line is in a block comment.
+    in_comment: bool,
+    /// Indentation of the line which opened the block comment, before and after formatting.
+    comment_indent: Option<(String, String)>,
+    /// Whether the previous line ends with a line continuation.
+    continuation: bool,
+    /// Number of the blank lines before the line.
+    blank_lines: usize,
+    /// Control statements without braces whose body starts at the line, e.g `if (x)`, with
+    /// whether they are an `if`.
+    bodies: Vec<bool>,
+    /// Control statements whose body ended at the previous line, with whether they are an `if`,
+    /// to indent an `else` as its `if`.
+    completed: Vec<bool>,
+    /// Whether the statement of the line is the header of a control statement, which is an
+    /// `if` or not.
+    header: Option<bool>,
+    /// Whether the next block is the body of a `switch`.
+    switch: bool,
+    /// Whether the previous line of code is a `case` label.
+    case_label: bool,
+    /// Whether the line is in a region where the formatting is disabled with `// fmt: off`.
+    disabled: bool,
+    /// Kind, role and text of the last token of code before the line.
+    last: Option<(TokenKind, Role, String)>,
+    attach: Option<Attach>,
+    /// States at the conditional directives opened before the line, and at the end of their
+    /// first branch.
+    conditions: Vec<(State, Option<State>)>,
+}
+
+impl Formatter {
+    fn line(&mut self, line: &str) {
+        let marker = fmt_marker(line.trim_start()).filter(|_| !self.in_comment);
+        if marker == Some(true) {
+            self.disabled = false;
+        }
+        if !self.disabled {
+            self.format_line(line);
+        } else {
+            // The line is still formatted, to indent the lines after the region by its blocks.
+            let len = self.lines.len();
+            self.format_line(line);
+            self.lines.truncate(len);
+            self.push(line.to_string(), None);
+        }
+        if marker == Some(false) {
+            self.disabled = true;
+        }
+    }
+
+    fn format_line(&mut self, line: &str) {
+        let content = line.trim_start();
+        let indent = &line[..line.len() - content.len()];
+
+        if self.in_comment {
+            let text = match &self.comment_indent {
+                _ if content.is_empty() => String::new(),
+                // The stars of documentation comments are aligned on the star which opened them.
+                Some((_, new)) if content.starts_with('*') => format!("{new} {content}"),
+                Some((old, new)) => match line.strip_prefix(old.as_str()) {
+                    Some(rest) => format!("{new}{rest}"),
+                    None => line.to_string(),
+                },
+                None => line.to_string(),
+            };
+            if content.contains("*/") {
+                self.in_comment = false;
+                self.comment_indent = None;
+            }
+            self.push(text, None);
+            return;
+        }
+        if self.continuation {
+            self.continuation = content.ends_with('\\');
+            self.push(line.to_string(), None);
+            return;
+        }
+        if content.is_empty() {
+            if !self.lines.is_empty() && self.blank_lines < MAX_BLANK_LINES {
+                self.push(String::new(), None);
+                self.blank_lines += 1;
+            }
+            return;
+        }
+        if content.starts_with('#') {
+            self.continuation = content.ends_with('\\');
+            let define = if self.continuation {
+                None
+            } else {
+                parse_define(content)
+            };
+            self.directive(content);
+            self.open_comment(content, indent, "");
+            self.push(content.to_string(), define);
+            return;
+        }
+
+        self.code_line(content, indent);
+    }
+
+    /// Format the branches of the conditional directives from the state before them, keeping
+    /// the state at the end of the first branch after them, e.g for the two headers of a
+    /// function.
+    fn directive(&mut self, content: &str) {
+        let name = content[1..].trim_start();
+        let name = &name[..name
+            .find(|c: char| !c.is_ascii_alphanumeric())
+            .unwrap_or(name.len())];
+        match name {
+            "if" | "ifdef" | "ifndef" => {
+                let state = self.state();
+                self.conditions.push((state, None));
+            }
+            "else" | "elseif" | "elif" => {
+                let current = self.state();
+                if let Some((start, end)) = self.conditions.last_mut() {
+                    let start = start.clone();
+                    end.get_or_insert(current);
+                    self.restore(start);
+                }
+            }
+            "endif" => {
+                if let Some((_, Some(end))) = self.conditions.pop() {
+                    self.restore(end);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn state(&self) -> State {
+        State {
+            blocks: self.blocks.clone(),
+            parens: self.parens,
+            bodies: self.bodies.clone(),
+            completed: self.completed.clone(),
+            switch: self.switch,
+            case_label: self.case_label,
+        }
+    }
+
+    fn restore(&mut self, state: State) {
+        self.blocks = state.blocks;
+        self.parens = state.parens;
+        self.bodies = state.bodies;
+        self.completed = state.completed;
+        self.switch = state.switch;
+        self.case_label = state.case_label;
+    }
+
+    /// Pair an `else` with the innermost `if` whose body ended at the previous line. The control
+    /// statements it is nested in stay open.
+    fn pair_else(&mut self) {
+        let len = self
+            .completed
+            .iter()
+            .rposition(|&is_if| is_if)
+            .unwrap_or(self.completed.len().saturating_sub(1));
+        self.bodies = self.completed[..len].to_vec();
+    }
+
+    fn push(&mut self, text: String, define: Option<(String, String)>) {
+        if !text.is_empty() {
+            self.blank_lines = 0;
+        }
+        self.attach = None;
+        self.lines.push(Line {
+            text,
+            source: (self.source, self.source),
+            define,
+        });
+    }
+
+    /// Start a block comment if the line opens one.
+    fn open_comment(&mut self, content: &str, old_indent: &str, new_indent: &str) {
+        if tokenize(content).1 {
+            self.in_comment = true;
+            self.comment_indent = Some((old_indent.to_string(), new_indent.to_string()));
+        }
+    }
+
+    fn code_line(&mut self, content: &str, indent: &str) {
+        let (mut tokens, _) = tokenize(content);
+        let split = match self.disabled {
+            true => None,
+            false => split_braces(&tokens, self.config.brace_style),
+        };
+        if let Some(split) = split {
+            self.queue
+                .push_front((self.source, content[tokens[split].offset..].to_string()));
+            tokens.truncate(split);
+        }
+        assign_roles(&mut tokens, &self.last);
+
+        let first = tokens[0].text;
+        let is_comment = tokens.iter().all(|token| token.kind == TokenKind::Comment);
+        let top_is_switch = self.blocks.last().is_some_and(|block| block.switch);
+        let is_case = self.parens == 0 && top_is_switch && matches!(first, "case" | "default");
+        if is_case {
+            if let Some(block) = self.blocks.last_mut() {
+                block.in_case = false;
+            }
+        }
+        if first == "else" && self.parens == 0 && self.bodies.is_empty() {
+            self.pair_else();
+        }
+        let bodies = self.bodies.len();
+        let base = self
+            .blocks
+            .iter()
+            .map(|block| block.statements.len() + usize::from(block.in_case))
+            .sum::<usize>();
+        let level = if self.parens > 0 {
+            let closes = self.parens == 1 && matches!(first, ")" | "]");
+            base + bodies + usize::from(!closes)
+        } else if first == "{" {
+            let top_in_case = self.blocks.last().is_some_and(|block| block.in_case);
+            base - usize::from(self.case_label && top_in_case) + bodies.saturating_sub(1)
+        } else if first == "}" {
+            match self.blocks.last() {
+                Some(block) => base - 1 - usize::from(block.in_case),
+                None => 0,
+            }
+        } else {
+            base + bodies
+        };
+        let continuation_level = if self.parens > 0 { level } else { level + 1 };
+        let new_indent = self.config.indent(level);
+
+        let attach = self.attach.filter(|_| !self.disabled);
+        let rendered = self.wrap(&tokens, &new_indent, continuation_level);
+        self.update(&tokens, is_case, is_comment);
+        self.open_comment(content, indent, &new_indent);
+        // Join the line to the previous one with the attached brace style.
+        let joined = match (attach, first, tokens.len(), self.lines.last_mut()) {
+            (Some(Attach::Brace), "{", 1, Some(previous))
+            | (Some(Attach::Else), "else", _, Some(previous))
+                if rendered.len() == 1 =>
+            {
+                previous.text.push(' ');
+                previous.text.push_str(rendered[0].trim_start());
+                previous.source.1 = self.source;
+                true
+            }
+            _ => false,
+        };
+        if !joined {
+            for line in rendered {
+                self.push(line, None);
+            }
+        }
+
+        let last = tokens
+            .last()
+            .filter(|token| token.kind != TokenKind::Comment);
+        self.attach = match last {
+            _ if self.config.brace_style != BraceStyle::Attach => None,
+            Some(token) if token.text == "}" && tokens.len() == 1 => Some(Attach::Else),
+            Some(token) if opens_block(token) => Some(Attach::Brace),
+            _ => None,
+        };
+    }
+
+    /// Update the blocks, parentheses and statements with the tokens of a line of code.
+    fn update(&mut self, tokens: &[Token<'_>], is_case: bool, is_comment: bool) {
+        if is_comment {
+            return;
+        }
+        let code = tokens
+            .iter()
+            .filter(|token| token.kind != TokenKind::Comment);
+        if self.parens == 0 {
+            let mut words = code
+                .clone()
+                .skip_while(|token| token.text == "}")
+                .map(|token| token.text);
+            self.header = match words.next() {
+                Some("else") => Some(words.next() == Some("if")),
+                Some(keyword) if CONTROL_KEYWORDS.contains(&keyword) => Some(keyword == "if"),
+                _ => None,
+            };
+        }
+
+        let mut opened = false;
+        let mut last = "";
+        for (i, token) in code.enumerate() {
+            last = token.text;
+            match token.text {
+                "{" => {
+                    let statements = match (i, opened) {
+                        (0, false) if !self.bodies.is_empty() => self.bodies.clone(),
+                        (_, false) if i > 0 => {
+                            let mut statements = self.bodies.clone();
+                            statements.push(self.header.unwrap_or(false));
+                            statements
+                        }
+                        _ => vec![false],
+                    };
+                    if self.case_label || is_case {
+                        if let Some(block) = self.blocks.last_mut().filter(|block| block.switch) {
+                            block.in_case = false;
+                        }
+                    }
+                    self.blocks.push(Block {
+                        switch: self.switch,
+                        in_case: false,
+                        statements,
+                    });
+                    self.switch = false;
+                    self.bodies.clear();
+                    self.header = None;
+                    self.case_label = false;
+                    opened = true;
+                }
+                "}" => {
+                    if let Some(block) = self.blocks.pop() {
+                        self.completed = block.statements;
+                    }
+                    self.bodies.clear();
+                }
+                "(" | "[" => self.parens += 1,
+                ")" | "]" => self.parens = self.parens.saturating_sub(1),
+                "switch" if token.kind == TokenKind::Word => self.switch = true,
+                "else" if token.kind == TokenKind::Word && self.bodies.is_empty() => {
+                    self.pair_else();
+                }
+                _ => (),
+            }
+        }
+
+        if self.parens == 0 {
+            match last {
+                ";" => {
+                    // The line is the body of the control statements, or holds its own body.
+                    self.completed = std::mem::take(&mut self.bodies);
+                    self.completed.extend(self.header);
+                }
+                "}" => (),
+                "{" => self.completed.clear(),
+                _ => {
+                    self.bodies.extend(self.header);
+                    self.completed.clear();
+                }
+            }
+            self.header = None;
+        }
+        self.case_label = is_case && !opened;
+        if self.case_label {
+            if let Some(block) = self.blocks.last_mut() {
+                block.in_case = true;
+            }
+        }
+        if let Some(token) = tokens
+            .iter()
+            .rev()
+            .find(|token| token.kind != TokenKind::Comment)
+        {
+            self.last = Some((token.kind, token.role, token.text.to_string()));
+        }
+    }
+
+    /// Render the tokens of a line, wrapped after the commas of its outermost arguments if it is
+    /// longer than the column limit.
+    fn wrap(&self, tokens: &[Token<'_>], indent: &str, continuation_level: usize) -> Vec<String> {
+        // Columns of the start and the end of the tokens, on a single line.
+        let mut starts = Vec::with_capacity(tokens.len());
+        let mut ends = Vec::with_capacity(tokens.len());
+        let mut column = width(indent);
+        for (i, token) in tokens.iter().enumerate() {
+            if i > 0 {
+                column += width(spacing(&tokens[i - 1], token));
+            }
+            starts.push(column);
+            column += width(token.text);
+            ends.push(column);
+        }
+        // The trailing comments do not count in the length of the lines.
+        let code_len = match tokens.last() {
+            Some(token) if token.kind == TokenKind::Comment && tokens.len() > 1 => tokens.len() - 1,
+            _ => tokens.len(),
+        };
+
+        // The breaks are after the commas of the outermost arguments.
+        let mut depth = 0;
+        let mut breaks = Vec::new();
+        for (i, token) in tokens[..code_len].iter().enumerate() {
+            match token.text {
+                "(" | "[" | "{" => depth += 1,
+                ")" | "]" | "}" => depth -= 1,
+                "," if depth > 0 && i + 1 < code_len => breaks.push((depth, i)),
+                _ => (),
+            }
+        }
+        let min_depth = breaks.iter().map(|(depth, _)| *depth).min();
+        let breaks = breaks
+            .into_iter()
+            .filter(|(depth, _)| Some(*depth) == min_depth)
+            .map(|(_, i)| i)
+            .collect::<Vec<_>>();
+
+        let limit = self.config.column_limit;
+        let continuation_indent = self.config.indent(continuation_level);
+        let mut segments = Vec::new();
+        let mut start = 0;
+        let mut start_column = width(indent);
+        while start < tokens.len() {
+            let fits = |end: usize| start_column + ends[end] - starts[start] <= limit;
+            let end = if limit == 0 || fits(code_len - 1) {
+                tokens.len() - 1
+            } else {
+                let mut candidates = breaks.iter().copied().filter(|i| *i >= start);
+                match candidates.clone().rfind(|i| fits(*i)) {
+                    Some(end) => end,
+                    None => candidates.next().unwrap_or(tokens.len() - 1),
+                }
+            };
+            segments.push((start, end));
+            start = end + 1;
+            start_column = width(&continuation_indent);
+        }
+
+        segments
+            .into_iter()
+            .enumerate()
+            .map(|(i, (start, end))| {
+                let mut line = if i == 0 {
+                    indent.to_string()
+                } else {
+                    continuation_indent.clone()
+                };
+                line.push_str(&render(&tokens[start..=end]));
+                line
+            })
+            .collect()
+    }
+}
+
+/// Whether a line is a `// fmt: on` marker, or a `// fmt: off` one.
+fn fmt_marker(content: &str) -> Option<bool> {
+    match content.strip_prefix("//")?.trim() {
+        "fmt: on" => Some(true),
+        "fmt: off" => Some(false),
+        _ => None,
+    }
+}
+
+/// Width of a text, with the tabs expanded.
+fn width(text: &str) -> usize {
+    text.chars()
+        .map(|c| if c == '\t' { TAB_WIDTH } else { 1 })
+        .sum()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenKind {
+    Word,
+    Number,
+    /// String or character literal.
+    Literal,
+    Comment,
+    Punct,
+}
+
+/// Role of a punctuation in its expression, which decides its spacing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Role {
+    None,
+    Binary,
+    Prefix,
+    Postfix,
+    /// Colon of a `case` label or of an old style tag, e.g `Float:x`.
+    Label,
+    /// Angle brackets of `view_as<int>`.
+    Generic,
+    /// Unknown punctuation, whose spacing is kept.
+    Verbatim,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Token<'a> {
+    kind: TokenKind,
+    role: Role,
+    text: &'a str,
+    /// Whitespace before the token in the line.
+    space: &'a str,
+    /// Offset of the token in the line.
+    offset: usize,
+}
+
+/// Split a line in tokens, and return whether it opens a block comment.
+fn tokenize(line: &str) -> (Vec<Token<'_>>, bool) {
+    let bytes = line.as_bytes();
+    let mut res = Vec::new();
+    let mut opens_comment = false;
+    let mut i = 0;
+    while i < line.len() {
+        let space_start = i;
+        while i < line.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        if i == line.len() {
+            break;
+        }
+        let start = i;
+        let rest = &line[i..];
+        let kind = if rest.starts_with("//") {
+            i = line.len();
+            TokenKind::Comment
+        } else if let Some(comment) = rest.strip_prefix("/*") {
+            match comment.find("*/") {
+                Some(end) => i += end + 4,
+                None => {
+                    i = line.len();
+                    opens_comment = true;
+                }
+            }
+            TokenKind::Comment
+        } else if bytes[i] == b'"' || bytes[i] == b'\'' {
+            let quote = bytes[i];
+            i += 1;
+            while i < line.len() {
+                match bytes[i] {
+                    b'\\' => i += 2,
+                    c if c == quote => {
+                        i += 1;
+                        break;
+                    }
+                    _ => i += 1,
+                }
+            }
+            i = i.min(line.len());
+            TokenKind::Literal
+        } else if is_word_byte(bytes[i]) {
+            let is_number = bytes[i].is_ascii_digit();
+            i += 1;
+            while i < line.len() {
+                let is_exponent = is_number
+                    && matches!(bytes[i], b'+' | b'-')
+                    && matches!(bytes[i - 1], b'e' | b'E')
+                    && !line[start..i].starts_with("0x");
+                if !(is_word_byte(bytes[i]) || (is_number && bytes[i] == b'.') || is_exponent) {
+                    break;
+                }
+                i += 1;
+            }
+            if is_number {
+                TokenKind::Number
+            } else {
+                TokenKind::Word
+            }
+        } else {
+            i += OPERATORS
+                .iter()
+                .find(|op| rest.starts_with(*op))
+                .map_or_else(
+                    || rest.chars().next().map_or(1, char::len_utf8),
+                    |op| op.len(),
+                );
+            TokenKind::Punct
+        };
+        res.push(Token {
+            kind,
+            role: Role::None,
+            text: &line[start..i],
+            space: &line[space_start..start],
+            offset: start,
+        });
+    }
+
+    (res, opens_comment)
+}
+
+fn is_word_byte(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_'
+}
+
+/// Assign their role to the punctuations, given the last token of code before the line.
+fn assign_roles(tokens: &mut [Token<'_>], last: &Option<(TokenKind, Role, String)>) {
+    let mut previous = last.clone();
+    let mut ternaries = 0;
+    let mut generic = false;
+    for i in 0..tokens.len() {
+        let token = tokens[i];
+        if token.kind == TokenKind::Comment {
+            continue;
+        }
+        let is_operand = previous
+            .as_ref()
+            .is_some_and(|(kind, role, text)| match kind {
+                TokenKind::Word => !NON_OPERAND_KEYWORDS.contains(&text.as_str()),
+                TokenKind::Number | TokenKind::Literal => true,
+                TokenKind::Punct => matches!(text.as_str(), ")" | "]") || *role == Role::Postfix,
+                TokenKind::Comment => false,
+            });
+        let previous_text = previous.as_ref().map(|(_, _, text)| text.as_str());
+        let role = match token.text {
+            _ if token.kind != TokenKind::Punct => Role::None,
+            _ if previous_text == Some("operator") => Role::Verbatim,
+            "++" | "--" if is_operand => Role::Postfix,
+            "++" | "--" | "!" | "~" => Role::Prefix,
+            "-" | "+" if is_operand => Role::Binary,
+            "-" | "+" => Role::Prefix,
+            "&" => {
+                // A reference parameter, e.g `int &x`.
+                let is_reference = previous.as_ref().is_some_and(|(kind, _, _)| {
+                    *kind == TokenKind::Word
+                        && !token.space.is_empty()
+                        && tokens.get(i + 1).is_some_and(|next| {
+                            next.kind == TokenKind::Word && next.space.is_empty()
+                        })
+                });
+                if is_operand && !is_reference {
+                    Role::Binary
+                } else {
+                    Role::Prefix
+                }
+            }
+            "<" if previous_text == Some("view_as") => {
+                generic = true;
+                Role::Generic
+            }
+            ">" if generic => {
+                generic = false;
+                Role::Generic
+            }
+            "?" => {
+                ternaries += 1;
+                Role::Binary
+            }
+            ":" if ternaries > 0 => {
+                ternaries -= 1;
+                Role::Binary
+            }
+            ":" => Role::Label,
+            text if BINARY_OPERATORS.contains(&text) => Role::Binary,
+            "(" | ")" | "[" | "]" | "{" | "}" | "," | ";" | "." | "..." => Role::None,
+            _ => Role::Verbatim,
+        };
+        tokens[i].role = role;
+        previous = Some((token.kind, role, token.text.to_string()));
+    }
+}
+
+/// Whitespace between two consecutive tokens of a line.
+fn spacing<'a>(a: &Token<'a>, b: &Token<'a>) -> &'a str {
+    let space = if b.space.is_empty() { "" } else { " " };
+    if b.kind == TokenKind::Comment {
+        // The whitespace before the trailing comments is kept, as they can be aligned.
+        return match a.text {
+            "(" | "[" => space,
+            _ if b.space.is_empty() => " ",
+            _ => b.space,
+        };
+    }
+    if a.kind == TokenKind::Comment || a.role == Role::Verbatim || b.role == Role::Verbatim {
+        return space;
+    }
+    let res = match (a.text, b.text) {
+        (_, "," | ";") => "",
+        (",", _) => " ",
+        (";", ")") => "",
+        (";", _) => " ",
+        ("(" | "[", _) | (_, ")" | "]") => "",
+        (".", _) | (_, ".") => "",
+        ("...", _) => " ",
+        (_, "...") if a.kind == TokenKind::Word => " ",
+        (_, "...") => "",
+        (_, "(") if a.kind == TokenKind::Word => {
+            if SPACED_KEYWORDS.contains(&a.text) {
+                " "
+            } else {
+                ""
+            }
+        }
+        (")" | "]", "(" | "[") => "",
+        (_, "[")
+            if matches!(
+                a.kind,
+                TokenKind::Word | TokenKind::Number | TokenKind::Literal
+            ) =>
+        {
+            ""
+        }
+        _ if a.role == Role::Generic || b.role == Role::Generic => "",
+        _ if a.role == Role::Prefix || b.role == Role::Postfix => "",
+        _ if a.role == Role::Binary || b.role == Role::Binary => " ",
+        _ if b.role == Role::Label => "",
+        _ if a.role == Role::Label => space,
+        ("{", "}") => "",
+        ("{", _) | (_, "}") => space,
+        _ => " ",
+    };
+    // Never join two operators into another one, e.g `-` and `-x` into `--x`.
+    if res.is_empty() && a.kind == TokenKind::Punct && b.kind == TokenKind::Punct {
+        let joined = format!("{}{}", a.text, &b.text[..1]);
+        if joined == "//" || joined == "/*" || OPERATORS.iter().any(|op| op.starts_with(&joined)) {
+            return " ";
+        }
+    }
+
+    res
+}
+
+/// Join the tokens of a line with their spacing.
+fn render(tokens: &[Token<'_>]) -> String {
+    let mut res = String::new();
+    for (i, token) in tokens.iter().enumerate() {
+        if i > 0 {
+            res.push_str(spacing(&tokens[i - 1], token));
+        }
+        res.push_str(token.text);
+    }
+
+    res
+}
+
+/// Whether a token can be followed by the opening brace of a block, e.g `)` in `if (x) {`, as
+/// opposed to the braces of the initializers, e.g `=` in `int x[] = {`.
+fn opens_block(token: &Token<'_>) -> bool {
+    match token.kind {
+        TokenKind::Word => true,
+        TokenKind::Punct => token.text == ")" || token.role == Role::Label,
+        _ => false,
+    }
+}
+
+/// Index of the token of a line of code where it is split to move its braces, e.g the `{` of
+/// `if (x) {` with [`BraceStyle::Allman`].
+fn split_braces(tokens: &[Token<'_>], brace_style: BraceStyle) -> Option<usize> {
+    if brace_style != BraceStyle::Allman {
+        return None;
+    }
+    if tokens.len() >= 2 && tokens[0].text == "}" && tokens[1].text == "else" {
+        return Some(1);
+    }
+    let last = tokens.len().checked_sub(1)?;
+    if last > 0 && tokens[last].text == "{" {
+        // The roles are not assigned yet, but the colon of a label is the only one before a
+        // block.
+        let previous = &tokens[last - 1];
+        if opens_block(previous) || previous.text == ":" {
+            return Some(last);
+        }
+    }
+
+    None
+}
+
+/// Name and value of a `#define`, e.g `("#define FOO(%1)", "%1 + 1")`.
+fn parse_define(content: &str) -> Option<(String, String)> {
+    let rest = content.strip_prefix("#define")?;
+    if !rest.starts_with([' ', '\t']) {
+        return None;
+    }
+    let rest = rest.trim_start();
+    let mut name_end = rest
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .unwrap_or(rest.len());
+    if rest[name_end..].starts_with('(') {
+        name_end += rest[name_end..].find(')')? + 1;
+    }
+    let name = &rest[..name_end];
+    if name.is_empty() {
+        return None;
+    }
+
+    Some((
+        format!("#define {name}"),
+        rest[name_end..].trim().to_string(),
+    ))
+}
+
+/// Align the values of the consecutive `#define`s.
+fn align_defines(lines: &mut [Line]) {
+    for group in lines.split_mut(|line| line.define.is_none()) {
+        let width = group
+            .iter()
+            .filter_map(|line| line.define.as_ref())
+            .filter(|(_, value)| !value.is_empty())
+            .map(|(name, _)| name.chars().count())
+            .max()
+            .unwrap_or_default();
+        for line in group {
+            if let Some((name, value)) = &line.define {
+                line.text = if value.is_empty() {
+                    name.clone()
+                } else {
+                    format!("{name:width$} {value}")
+                };
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(text: &str, expected: &str, config: &FormattingConfig) {
+        let formatted = format(text, config);
+        assert_eq!(formatted, expected);
+        assert_eq!(format(&formatted, config), formatted, "not idempotent");
+    }
+
+    #[test]
+    fn format_indentation() {
+        let text = r#"
+
+#include <sourcemod>
+  #define FOO(%1) { %1 }
+
+/**
+   * Called when the plugin starts.
+   */
+public void OnPluginStart()
+{
+        if (IsFoo(1,
+    2))
+  {
+    PrintToServer("{");  // }
+
+
+        }
+}
+
+
+"#;
+        check(
+            text,
+            r#"#include <sourcemod>
+#define FOO(%1) { %1 }
+
+/**
+ * Called when the plugin starts.
+ */
+public void OnPluginStart()
+{
+	if (IsFoo(1,
+		2))
+	{
+		PrintToServer("{");  // }
+
+	}
+}
+"#,
+            &FormattingConfig::default(),
+        );
+    }
+
+    #[test]
+    fn format_with_spaces_and_crlf() {
+        let config = FormattingConfig {
+            indent_style: IndentStyle::Spaces(2),
+            ..Default::default()
+        };
+        check(
+            "void foo()\r\n{\r\nbar();\r\n}",
+            "void foo()\r\n{\r\n  bar();\r\n}\r\n",
+            &config,
+        );
+        let config = FormattingConfig {
+            end_of_line: Some(EndOfLine::Lf),
+            insert_final_newline: false,
+            ..Default::default()
+        };
+        check("void foo()\r\n{\r\n}\r\n\r\n", "void foo()\n{\n}", &config);
+    }
+
+    #[test]
+    fn format_spacing() {
+        let text = r#"int Foo(int &x,const char[] name,any ...)
+{
+for(int i=0;i<x;i++)
+x+=-i*2;
+if(!IsValid(x)&&x>-1)return view_as<int>(x)?a:b;
+new Float:y=1.0e-5;
+int z[]={1, 2};
+return x - -y;
+}
+"#;
+        check(
+            text,
+            r#"int Foo(int &x, const char[] name, any ...)
+{
+	for (int i = 0; i < x; i++)
+		x += -i * 2;
+	if (!IsValid(x) && x > -1) return view_as<int>(x) ? a : b;
+	new Float:y = 1.0e-5;
+	int z[] = {1, 2};
+	return x - -y;
+}
+"#,
+            &FormattingConfig::default(),
+        );
+    }
+
+    #[test]
+    fn format_braces() {
+        let text = r#"void Foo() {
+    if (x) {
+        Bar();
+    } else {
+        Baz();
+    }
+}
+"#;
+        let allman = r#"void Foo()
+{
+	if (x)
+	{
+		Bar();
+	}
+	else
+	{
+		Baz();
+	}
+}
+"#;
+        check(text, allman, &FormattingConfig::default());
+        let attach = FormattingConfig {
+            brace_style: BraceStyle::Attach,
+            ..Default::default()
+        };
+        check(
+            allman,
+            "void Foo() {\n\tif (x) {\n\t\tBar();\n\t} else {\n\t\tBaz();\n\t}\n}\n",
+            &attach,
+        );
+    }
+
+    #[test]
+    fn format_statements() {
+        let text = r#"void Foo()
+{
+switch (x)
+{
+case 1:
+Bar();
+case 2, 3:
+{
+Bar();
+}
+default: Baz();
+}
+if (x)
+if (y)
+Bar();
+else
+Baz();
+Qux();
+}
+"#;
+        check(
+            text,
+            r#"void Foo()
+{
+	switch (x)
+	{
+		case 1:
+			Bar();
+		case 2, 3:
+		{
+			Bar();
+		}
+		default: Baz();
+	}
+	if (x)
+		if (y)
+			Bar();
+		else
+			Baz();
+	Qux();
+}
+"#,
+            &FormattingConfig::default(),
+        );
+    }
+
+    #[test]
+    fn format_nested_else() {
+        let text = r#"void Foo()
+{
+if (x)
+if (y)
+Bar();
+else
+Baz();
+else Qux();
+if (x)
+while (y)
+if (z) Bar();
+else Baz();
+Qux();
+}
+"#;
+        check(
+            text,
+            r#"void Foo()
+{
+	if (x)
+		if (y)
+			Bar();
+		else
+			Baz();
+	else Qux();
+	if (x)
+		while (y)
+			if (z) Bar();
+			else Baz();
+	Qux();
+}
+"#,
+            &FormattingConfig::default(),
+        );
+    }
+
+    #[test]
+    fn format_conditional_directives() {
+        let text = r#"#if SOURCEMOD_V_MINOR >= 11
+public void OnClientPutInServer(int client) {
+#elseif defined FOO
+public void OnClientPutInServer(int client, int other) {
+#else
+public void OnClientPutInServer(int client, bool other) {
+#endif
+if (client)
+#if defined DEBUG
+{
+Log(client);
+#else
+{
+#endif
+}
+}
+
+void Bar()
+{
+}
+"#;
+        check(
+            text,
+            r#"#if SOURCEMOD_V_MINOR >= 11
+public void OnClientPutInServer(int client)
+{
+#elseif defined FOO
+public void OnClientPutInServer(int client, int other)
+{
+#else
+public void OnClientPutInServer(int client, bool other)
+{
+#endif
+	if (client)
+#if defined DEBUG
+	{
+		Log(client);
+#else
+	{
+#endif
+	}
+}
+
+void Bar()
+{
+}
+"#,
+            &FormattingConfig::default(),
+        );
+    }
+
+    #[test]
+    fn format_off_regions() {
+        let text = r#"void Foo()
+{
+  // fmt: off
+  int table[][] = {
+    { 1,  10 },
+    { 20, 2  },
+  };
+	// fmt: on
+  Bar( );
+}
+"#;
+        check(
+            text,
+            r#"void Foo()
+{
+	// fmt: off
+  int table[][] = {
+    { 1,  10 },
+    { 20, 2  },
+  };
+	// fmt: on
+	Bar();
+}
+"#,
+            &FormattingConfig::default(),
+        );
+    }
+
+    #[test]
+    fn diff_formatted_lines() {
+        assert_eq!(diff("a\nb\n", "a\nb\n"), None);
+        assert_eq!(
+            diff("a\n b\nc\n d\ne\n", "a\nb\nc\nd\ne\n"),
+            Some(TextEdit::new(
+                TextRange::new(2.into(), 10.into()),
+                "b\nc\nd\n".to_string()
+            ))
+        );
+        assert_eq!(
+            diff("a\n\n\n", "a\n"),
+            Some(TextEdit::new(
+                TextRange::new(2.into(), 4.into()),
+                String::new()
+            ))
+        );
+    }
+
+    #[test]
+    fn format_range_of_lines() {
+        let text = "void Foo()\n{\nif(x)\n{\nBar( 1 );\n}\nBaz( 2 );\n}\n";
+        // The selection of the lines of the `if` ends at the start of the line of `Baz`.
+        let range = TextRange::new(13.into(), 33.into());
+        assert_eq!(
+            range_edit(text, range, &FormattingConfig::default()),
+            Some(TextEdit::new(
+                TextRange::new(13.into(), 33.into()),
+                "\tif (x)\n\t{\n\t\tBar(1);\n\t}\n".to_string()
+            ))
+        );
+        let attach = FormattingConfig {
+            brace_style: BraceStyle::Attach,
+            ..Default::default()
+        };
+        // The brace is attached to the line before the range.
+        assert_eq!(
+            range_edit(text, TextRange::new(19.into(), 19.into()), &attach),
+            Some(TextEdit::new(
+                TextRange::new(13.into(), 21.into()),
+                "\tif (x) {\n".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn edit_on_typing() {
+        let config = FormattingConfig::default();
+        let text = "void Foo()\n{\n\tswitch (x)\n\t{\n\tcase 1:\n\t\tBar();\n\t\t}\n}\n";
+        assert_eq!(
+            typed_edit(text, 36, ':', &config),
+            Some(TextEdit::new(
+                TextRange::new(28.into(), 29.into()),
+                "\t\t".to_string()
+            ))
+        );
+        assert_eq!(
+            typed_edit(text, 49, '}', &config),
+            Some(TextEdit::new(
+                TextRange::new(46.into(), 48.into()),
+                "\t".to_string()
+            ))
+        );
+        assert_eq!(typed_edit(text, 44, '}', &config), None);
+
+        let text = "\t/**\n\t * Foo.\n\t\n\t */\n";
+        assert_eq!(
+            typed_edit(text, 15, '\n', &config),
+            Some(TextEdit::new(
+                TextRange::new(14.into(), 15.into()),
+                "\t * ".to_string()
+            ))
+        );
+        assert_eq!(
+            typed_edit("\t/**\n\t", 6, '\n', &config),
+            Some(TextEdit::new(
+                TextRange::new(5.into(), 6.into()),
+                "\t * ".to_string()
+            ))
+        );
+        assert_eq!(typed_edit("/* */\n", 6, '\n', &config), None);
+    }
+
+    #[test]
+    fn format_defines_and_long_calls() {
+        let config = FormattingConfig {
+            column_limit: 40,
+            ..Default::default()
+        };
+        let text = r#"#define   FOO 1
+#define LONGER_NAME(%1)    (%1 + 1)
+#define EMPTY
+
+void Foo()
+{
+	PrintToServer("%d %d", Bar(1, 2), Baz(3, 4), 5);
+}
+"#;
+        check(
+            text,
+            r#"#define FOO             1
+#define LONGER_NAME(%1) (%1 + 1)
+#define EMPTY
+
+void Foo()
+{
+	PrintToServer("%d %d", Bar(1, 2),
+		Baz(3, 4), 5);
+}
+"#,
+            &config,
+        );
+    }
+}
@@ -0,0 +1,245 @@
+use base_db::{FilePosition, FileRange, SourceDatabase};
+use hir::Semantics;
+use ide_db::{Assist, AssistId, AssistKind, RootDatabase, SourceChange, TextEdit};
+use line_index::{TextRange, TextSize};
+use syntax::{utils::ts_range_to_text_range, TSKind};
+use tree_sitter::Node;
+
+// Feature: Generate Property
+//
+// Inside of a methodmap, offers to generate a `property` block:
+//
+// - with `native` accessors, to be implemented by an extension,
+// - backed by a value of the map, if the methodmap inherits from `StringMap`,
+// - wrapping the `GetName` and `SetName` methods under the cursor, e.g:
+//
+// ```sourcepawn
+// property int Name
+// {
+//     public get()
+//     {
+//         return this.GetName();
+//     }
+//     public set(int value)
+//     {
+//         this.SetName(value);
+//     }
+// }
+// ```
+//
+// The property is added at the end of the methodmap. When its name is a placeholder, a rename
+// of the property is triggered once the assist is applied.
+pub(crate) fn generate_property(db: &RootDatabase, frange: FileRange) -> Vec<Assist> {
+    let sema = &Semantics::new(db);
+    let preprocessing_results = sema.preprocess_file(frange.file_id);
+    let source_map = preprocessing_results.source_map();
+    let source = preprocessing_results.preprocessed_text();
+    let tree = sema.parse(frange.file_id);
+    let text = db.file_text(frange.file_id);
+
+    let Some(s_range) = source_map.closest_s_range(frange.range) else {
+        return Vec::new();
+    };
+    let Some(node) = tree
+        .root_node()
+        .descendant_for_byte_range(s_range.start().into(), s_range.start().into())
+    else {
+        return Vec::new();
+    };
+    let ancestors = || std::iter::successors(Some(node), |node| node.parent());
+    // Do not offer the generation inside of the bodies of the methods.
+    if ancestors().any(|node| TSKind::from(node) == TSKind::block) {
+        return Vec::new();
+    }
+    let Some(methodmap) = ancestors().find(|node| TSKind::from(node) == TSKind::methodmap) else {
+        return Vec::new();
+    };
+    let (Some(target), Some(offset)) = (
+        source_map.closest_u_range(ts_range_to_text_range(&methodmap.range())),
+        insertion(&methodmap, &text, |range| source_map.closest_u_range(range)),
+    ) else {
+        return Vec::new();
+    };
+    let text_of = |node: Node| node.utf8_text(source.as_bytes()).ok();
+    let members = methodmap
+        .named_children(&mut methodmap.walk())
+        .filter(|member| !matches!(TSKind::from(member), TSKind::identifier | TSKind::comment))
+        .collect::<Vec<_>>();
+    let member_names = members
+        .iter()
+        .filter_map(|member| member.child_by_field_name("name").and_then(text_of))
+        .collect::<Vec<_>>();
+    let unit = if text.contains("\n\t") { "\t" } else { "    " };
+    let assist = |id: &'static str, label: String, property: String, name: &str, rename: bool| {
+        let mut source_change = SourceChange::default();
+        source_change.insert(
+            frange.file_id,
+            TextEdit::new(TextRange::empty(offset), property.clone()),
+        );
+        let name_offset = property
+            .find(&format!(" {name}\n"))
+            .map(|idx| offset + TextSize::new(idx as u32 + 1));
+        Assist {
+            id: AssistId(id, AssistKind::Generate),
+            label,
+            target,
+            source_change,
+            trigger_rename: name_offset.filter(|_| rename).map(|offset| FilePosition {
+                file_id: frange.file_id,
+                offset,
+            }),
+        }
+    };
+
+    let mut res = Vec::new();
+    if let Some((name, ty, has_setter)) = accessor_methods(&node, &members, &member_names, source) {
+        let mut accessors = vec![(
+            "get()".to_string(),
+            vec![format!("return this.Get{name}();")],
+        )];
+        if has_setter {
+            accessors.push((
+                format!("set({ty} value)"),
+                vec![format!("this.Set{name}(value);")],
+            ));
+        }
+        res.push(assist(
+            "generate_property_from_methods",
+            format!("Generate property `{name}`"),
+            property(unit, &ty, &name, &accessors),
+            &name,
+            false,
+        ));
+    }
+
+    let mut name = "Property".to_string();
+    let mut idx = 2;
+    while member_names.contains(&name.as_str()) {
+        name = format!("Property{idx}");
+        idx += 1;
+    }
+    res.push(assist(
+        "generate_native_property",
+        "Generate native property".to_string(),
+        native_property(unit, &name),
+        &name,
+        true,
+    ));
+    if methodmap.child_by_field_name("inherits").and_then(text_of) == Some("StringMap") {
+        let accessors = [
+            (
+                "get()".to_string(),
+                vec![
+                    "int value;".to_string(),
+                    format!("this.GetValue(\"{name}\", value);"),
+                    "return value;".to_string(),
+                ],
+            ),
+            (
+                "set(int value)".to_string(),
+                vec![format!("this.SetValue(\"{name}\", value);")],
+            ),
+        ];
+        res.push(assist(
+            "generate_string_map_property",
+            "Generate property backed by a value of the map".to_string(),
+            property(unit, "int", &name, &accessors),
+            &name,
+            true,
+        ));
+    }
+
+    res
+}
+
+/// Name, type and whether there is a setter of the `GetName` and `SetName` methods, if the
+/// cursor is on one of them and the property does not exist yet.
+fn accessor_methods(
+    node: &Node,
+    members: &[Node],
+    member_names: &[&str],
+    source: &str,
+) -> Option<(String, String, bool)> {
+    let text_of = |node: Node| node.utf8_text(source.as_bytes()).ok();
+    let method = std::iter::successors(Some(*node), |node| node.parent()).find(|node| {
+        matches!(
+            TSKind::from(node),
+            TSKind::methodmap_method | TSKind::methodmap_native
+        )
+    })?;
+    let method_name = text_of(method.child_by_field_name("name")?)?;
+    let name = method_name
+        .strip_prefix("Get")
+        .or_else(|| method_name.strip_prefix("Set"))
+        .filter(|name| !name.is_empty() && !member_names.contains(name))?;
+
+    let find = |accessor: &str| {
+        members.iter().find(|member| {
+            matches!(
+                TSKind::from(*member),
+                TSKind::methodmap_method | TSKind::methodmap_native
+            ) && member.child_by_field_name("name").and_then(text_of) == Some(accessor)
+        })
+    };
+    let params = |member: &Node| {
+        member
+            .child_by_field_name("parameters")
+            .map_or(0, |params| params.named_child_count())
+    };
+    let getter = find(&format!("Get{name}")).filter(|getter| params(getter) == 0)?;
+    let return_type = getter.child_by_field_name("returnType")?;
+    // Arrays cannot be the value of a property.
+    if TSKind::from(return_type) != TSKind::r#type
+        || return_type.next_sibling().map(TSKind::from) == Some(TSKind::dimension)
+    {
+        return None;
+    }
+    let ty = text_of(return_type)?;
+    if ty == "void" {
+        return None;
+    }
+    let has_setter = find(&format!("Set{name}")).is_some_and(|setter| params(setter) == 1);
+
+    Some((name.to_string(), ty.to_string(), has_setter))
+}
+
+fn property(unit: &str, ty: &str, name: &str, accessors: &[(String, Vec<String>)]) -> String {
+    let mut res = format!("{unit}property {ty} {name}\n{unit}{{\n");
+    for (signature, statements) in accessors {
+        res.push_str(&format!("{unit}{unit}public {signature}\n{unit}{unit}{{\n"));
+        for statement in statements {
+            res.push_str(&format!("{unit}{unit}{unit}{statement}\n"));
+        }
+        res.push_str(&format!("{unit}{unit}}}\n"));
+    }
+    res.push_str(&format!("{unit}}}\n"));
+
+    res
+}
+
+fn native_property(unit: &str, name: &str) -> String {
+    format!(
+        "{unit}property int {name}\n{unit}{{\n{unit}{unit}public native get();\n\
+         {unit}{unit}public native set(int value);\n{unit}}}\n"
+    )
+}
+
+/// Start of the line of the closing brace of the methodmap.
+fn insertion(
+    methodmap: &Node,
+    text: &str,
+    closest_u_range: impl Fn(TextRange) -> Option<TextRange>,
+) -> Option<TextSize> {
+    let closing_brace = methodmap
+        .children(&mut methodmap.walk())
+        .filter(|child| TSKind::from(child) == TSKind::anon_RBRACE)
+        .last()?;
+    let start = closest_u_range(ts_range_to_text_range(&closing_brace.range()))?.start();
+    let line_start = text[..usize::from(start)].rfind('\n')? + 1;
+    // The closing brace must be alone on its line.
+    if !text[line_start..usize::from(start)].trim().is_empty() {
+        return None;
+    }
+
+    Some(TextSize::new(line_start as u32))
+}
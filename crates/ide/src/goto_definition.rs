@@ -4,11 +4,15 @@ use base_db::FilePosition;
 use hir::{HasSource, Semantics};
 
 use line_index::TextRange;
+use preprocessor::SourceMap;
 use smol_str::{SmolStr, ToSmolStr};
 use syntax::{utils::ts_range_to_text_range, TSKind};
 use vfs::FileId;
 
-use crate::{RangeInfo, RootDatabase};
+use crate::{
+    convars::{convar_definitions, convar_name},
+    RangeInfo, RootDatabase,
+};
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub struct NavigationTarget {
@@ -22,6 +26,29 @@ impl NavigationTarget {
     pub fn focus_or_full_range(&self) -> TextRange {
         self.focus_range.unwrap_or(self.full_range)
     }
+
+    /// Build a target from the server ranges of a definition and of its name.
+    ///
+    /// The name of a definition declared by a macro call is mapped to the argument of the call it
+    /// comes from, and the full range is extended to contain it.
+    pub(crate) fn from_ranges(
+        name: SmolStr,
+        file_id: FileId,
+        source_map: &SourceMap,
+        s_full_range: TextRange,
+        s_name_range: TextRange,
+    ) -> Self {
+        let focus_range = source_map.closest_u_name_range(s_name_range);
+        let full_range = source_map
+            .closest_u_range_always(s_full_range)
+            .cover(focus_range);
+        Self {
+            name,
+            file_id,
+            full_range,
+            focus_range: Some(focus_range),
+        }
+    }
 }
 
 pub(crate) fn goto_definition(
@@ -42,17 +69,13 @@ pub(crate) fn goto_definition(
         let name_range = find_inner_name_range(&def_node);
         let target_preprocessing_results = sema.preprocess_file(file_id);
 
-        let navs = vec![NavigationTarget {
+        let navs = vec![NavigationTarget::from_ranges(
             name,
             file_id,
-            full_range: target_preprocessing_results
-                .source_map()
-                .closest_u_range_always(ts_range_to_text_range(&def_node.range())),
-            focus_range: target_preprocessing_results
-                .source_map()
-                .closest_u_range_always(name_range)
-                .into(),
-        }];
+            target_preprocessing_results.source_map(),
+            ts_range_to_text_range(&def_node.range()),
+            name_range,
+        )];
 
         return RangeInfo::new(u_range, navs).into();
     }
@@ -63,11 +86,19 @@ pub(crate) fn goto_definition(
         .into();
 
     let node = root_node.descendant_for_byte_range(offset as usize, offset as usize)?;
-    let def = sema.find_def(pos.file_id, &node)?;
     let ts_range = ts_range_to_text_range(&node.range());
     let u_range = preprocessing_results
         .source_map()
         .closest_u_range_always(ts_range);
+    if let Some(name) = convar_name(&node, &preprocessing_results.preprocessed_text()) {
+        let navs = convar_definitions(db, &name);
+        if navs.is_empty() {
+            return None;
+        }
+        return RangeInfo::new(u_range, navs).into();
+    }
+
+    let def = sema.find_def(pos.file_id, &node)?;
 
     let file_id = def.file_id(db);
     let source_tree = sema.parse(file_id);
@@ -77,17 +108,13 @@ pub(crate) fn goto_definition(
     let name_range = find_inner_name_range(&def_node);
 
     let target_preprocessing_results = sema.preprocess_file(file_id);
-    let navs = vec![NavigationTarget {
+    let navs = vec![NavigationTarget::from_ranges(
         name,
         file_id,
-        full_range: target_preprocessing_results
-            .source_map()
-            .closest_u_range_always(ts_range_to_text_range(&def_node.range())),
-        focus_range: target_preprocessing_results
-            .source_map()
-            .closest_u_range_always(name_range)
-            .into(),
-    }];
+        target_preprocessing_results.source_map(),
+        ts_range_to_text_range(&def_node.range()),
+        name_range,
+    )];
 
     RangeInfo::new(u_range, navs).into()
 }
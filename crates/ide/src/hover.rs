@@ -3,8 +3,8 @@ mod render;
 
 use std::panic::AssertUnwindSafe;
 
-use hir::{DefResolution, HasSource, Semantics};
-use ide_db::{Documentation, RootDatabase};
+use hir::{DefResolution, FunctionType, HasSource, Semantics};
+use ide_db::{Documentation, PluginInfo, RootDatabase, PLUGIN_INFO_FIELDS};
 use itertools::Itertools;
 use preprocessor::{db::PreprocDatabase, PreprocessingResult};
 use smol_str::ToSmolStr;
@@ -12,6 +12,7 @@ use syntax::utils::ts_range_to_text_range;
 use vfs::FileId;
 
 use crate::{
+    convars::{convar_hover, convar_name},
     events::{event_hover, event_name},
     goto_definition::find_inner_name_range,
     markup::Markup,
@@ -65,17 +66,13 @@ impl HoverAction {
                 let target_preprocessing_results = sema.preprocess_file(file_id);
                 Some(HoverGotoTypeData {
                     mod_path: Default::default(),
-                    nav: NavigationTarget {
+                    nav: NavigationTarget::from_ranges(
                         name,
                         file_id,
-                        full_range: target_preprocessing_results
-                            .source_map()
-                            .closest_u_range_always(ts_range_to_text_range(&def_node.range())),
-                        focus_range: target_preprocessing_results
-                            .source_map()
-                            .closest_u_range_always(name_range)
-                            .into(),
-                    },
+                        target_preprocessing_results.source_map(),
+                        ts_range_to_text_range(&def_node.range()),
+                        name_range,
+                    ),
                 })
             })
             .collect_vec();
@@ -120,6 +117,9 @@ pub(crate) fn hover(
             preprocessing_results.source_map(),
         );
     }
+    if let Some(name) = convar_name(&node, &preprocessing_results.preprocessed_text()) {
+        return convar_hover(db, &name, &node, preprocessing_results.source_map());
+    }
 
     let def = sema.find_def(fpos.file_id, &node)?;
     let u_range = preprocessing_results
@@ -135,7 +135,7 @@ pub(crate) fn hover(
         .flatten()
         .collect_vec();
     actions.dedup();
-    let def_node = def.source(db, &source_tree)?.value;
+    let def_node = def.clone().source(db, &source_tree)?.value;
 
     let markup = match render {
         Render::FileId(file_id) => Markup::from(file_id_to_url(file_id).unwrap_or_default()),
@@ -146,21 +146,129 @@ pub(crate) fn hover(
         let res = HoverResult { markup, actions };
         return Some(RangeInfo::new(u_range, res));
     }
-    if let Some(docs) = Documentation::from_node(def_node, text.as_bytes()) {
+    let docs = Documentation::from_node(def_node, text.as_bytes())
+        .or_else(|| macro_documentation(db, &def));
+    let extra_docs = constructor_documentation(db, &def, docs.as_ref())
+        .or_else(|| plugin_info_documentation(def_node, &text));
+    if let Some(docs) = docs {
+        let mut markup = format!("{}\n\n---\n\n{}", markup, docs.to_markdown());
+        if let Some(extra_docs) = extra_docs {
+            markup.push_str("\n\n---\n\n");
+            markup.push_str(&extra_docs);
+        }
         let res = HoverResult {
-            markup: Markup::from(format!(
-                "{}\n\n---\n\n{}",
-                markup,
-                Markup::from(docs.to_markdown()),
-            )),
+            markup: Markup::from(markup),
             actions,
         };
         return Some(RangeInfo::new(u_range, res));
     }
+    let markup = match extra_docs {
+        Some(extra_docs) => Markup::from(format!("{}\n\n---\n\n{}", markup, extra_docs)),
+        None => markup,
+    };
     let res = HoverResult { markup, actions };
     Some(RangeInfo::new(u_range, res))
 }
 
+/// Documentation of a macro, from the doc comment captured by the preprocessor above its
+/// `#define`.
+pub(crate) fn macro_documentation(db: &RootDatabase, def: &DefResolution) -> Option<Documentation> {
+    let DefResolution::Macro(macro_) = def else {
+        return None;
+    };
+    macro_.docs(db).map(|it| Documentation::from_comment(&it))
+}
+
+/// Metadata of the plugin, if the definition is its `myinfo` variable, with the macros of its
+/// fields expanded.
+fn plugin_info_documentation(def_node: tree_sitter::Node, text: &str) -> Option<String> {
+    let info = PluginInfo::from_node(def_node, text)?;
+    let rows = PLUGIN_INFO_FIELDS
+        .iter()
+        .filter_map(|field| {
+            let value = match info.literal(field, text) {
+                Some(value) => value.replace('|', "\\|"),
+                None => format!("`{}`", info.field(field)?.utf8_text(text.as_bytes()).ok()?),
+            };
+            Some(format!("| {field} | {value} |"))
+        })
+        .collect::<Vec<_>>();
+    if rows.is_empty() {
+        return None;
+    }
+
+    Some(format!(
+        "| Field | Value |\n| --- | --- |\n{}",
+        rows.join("\n")
+    ))
+}
+
+/// Documentation of the methodmap of a constructor, e.g `new ArrayList()`, and whether the
+/// created instance is a Handle which has to be deleted.
+///
+/// # Arguments
+/// * `db` - The database.
+/// * `def` - The definition being hovered.
+/// * `docs` - The documentation of the constructor.
+fn constructor_documentation(
+    db: &RootDatabase,
+    def: &DefResolution,
+    docs: Option<&Documentation>,
+) -> Option<String> {
+    let DefResolution::Function(function) = def else {
+        return None;
+    };
+    if function.kind(db) != FunctionType::Constructor {
+        return None;
+    }
+    let methodmap = function.parent_methodmap(db)?;
+    let file_id = DefResolution::from(methodmap).file_id(db);
+    let tree = db.parse(file_id);
+    let text = db.preprocessed_text(file_id);
+    let methodmap_docs = methodmap
+        .source(db, &tree)
+        .and_then(|node| Documentation::from_node(node.value, text.as_bytes()));
+
+    let mut res = Vec::new();
+    if let Some(methodmap_docs) = &methodmap_docs {
+        res.push(methodmap_docs.to_markdown());
+    }
+    if methodmap.is_handle(db) {
+        let auto_closed = [docs, methodmap_docs.as_ref()]
+            .into_iter()
+            .flatten()
+            .any(|docs| is_auto_closed(docs.as_str()));
+        let name = methodmap.name(db);
+        res.push(if auto_closed {
+            format!("`{}` is a Handle which is closed automatically.", name)
+        } else {
+            format!(
+                "`{}` is a Handle: `delete` it once it is no longer needed to avoid a leak.",
+                name
+            )
+        });
+    }
+
+    (!res.is_empty()).then(|| res.join("\n\n"))
+}
+
+/// Whether a documentation states that the Handle it returns is closed automatically.
+fn is_auto_closed(docs: &str) -> bool {
+    let docs = docs.to_lowercase();
+    [
+        "closed automatically",
+        "automatically closed",
+        "auto-closed",
+        "freed automatically",
+        "automatically freed",
+        "do not close",
+        "must not be closed",
+        "should not be closed",
+    ]
+    .iter()
+    .any(|pattern| docs.contains(pattern))
+}
+
 fn find_macro_hover(
     preprocessing_results: &PreprocessingResult,
     sema: &Semantics<RootDatabase>,
@@ -170,26 +278,37 @@ fn find_macro_hover(
     let preprocessed_text = preprocessing_results.preprocessed_text();
     let file_id = def.file_id(sema.db);
     let source_tree = sema.parse(file_id);
-    let def_node = def.source(sema.db, &source_tree)?.value;
+    let def_node = def.clone().source(sema.db, &source_tree)?.value;
     let source = sema.db.preprocessed_text(file_id);
     let source_text = def_node.utf8_text(source.as_bytes()).ok()?;
 
-    let start: u32 = offset.expanded_range().start().into();
-    let end: u32 = offset.expanded_range().end().into();
-    let slc = start as usize..end as usize;
-    // The preprocessed file might be shorter than the original file
-    let hover_text = preprocessed_text
-        .get(slc)
-        .map(String::from)
-        .unwrap_or_default();
-
-    let markup = Markup::from(format!(
-        "{}\nExpands to:\n{}",
-        Markup::fenced_block(source_text),
-        Markup::fenced_block(hover_text.trim())
-    ));
-
-    let res = if let Some(docs) = Documentation::from_node(def_node, source.as_bytes()) {
+    let hover_text = if offset.in_condition() {
+        // Symbols in preprocessor directives are not part of the preprocessed text.
+        offset.condition_text().map(String::from)
+    } else {
+        let start: u32 = offset.expanded_range().start().into();
+        let end: u32 = offset.expanded_range().end().into();
+        let slc = start as usize..end as usize;
+        // The preprocessed file might be shorter than the original file
+        preprocessed_text
+            .get(slc)
+            .map(String::from)
+            .unwrap_or_default()
+            .into()
+    };
+
+    let markup = match hover_text {
+        Some(hover_text) => Markup::from(format!(
+            "{}\nExpands to:\n{}",
+            Markup::fenced_block(source_text),
+            Markup::fenced_block(hover_text.trim())
+        )),
+        None => Markup::fenced_block(source_text),
+    };
+
+    let res = if let Some(docs) = Documentation::from_node(def_node, source.as_bytes())
+        .or_else(|| macro_documentation(sema.db, &def))
+    {
         HoverResult {
             markup: Markup::from(format!(
                 "{}\n\n---\n\n{}",
@@ -0,0 +1,197 @@
+use std::ops::Range;
+
+use base_db::{FileRange, SourceDatabase, SourceDatabaseExt};
+use fxhash::FxHashSet;
+use hir::Semantics;
+use ide_db::{Assist, AssistId, AssistKind, RootDatabase, SourceChange, TextEdit};
+use line_index::{TextRange, TextSize};
+use syntax::{utils::ts_range_to_text_range, TSKind};
+use vfs::FileId;
+
+use crate::{
+    convert_syntax::new_type,
+    create_function::{return_value, stub},
+};
+
+/// Forward declared by an included file.
+struct Forward {
+    name: String,
+    return_type: String,
+    /// Text of the parameters, including the parentheses.
+    parameters: String,
+}
+
+// Feature: Implement Forwards
+//
+// On an `#include` directive, offers to implement the forwards declared by the included file
+// which are not implemented yet by the plugin, either all at once or one by one, e.g:
+//
+// ```sourcepawn
+// public Action OnPlayerRunCmd(int client, int &buttons, ...)
+// {
+//     return Plugin_Continue;
+// }
+// ```
+//
+// The implementations are added at the end of the file.
+pub(crate) fn implement_forwards(db: &RootDatabase, frange: FileRange) -> Vec<Assist> {
+    let sema = &Semantics::new(db);
+    let Some((include_file_id, target)) =
+        db.file_includes(frange.file_id)
+            .0
+            .iter()
+            .find_map(|include| {
+                let range = include.range()?;
+                range
+                    .contains_inclusive(frange.range.start())
+                    .then_some((include.file_id(), range))
+            })
+    else {
+        return Vec::new();
+    };
+    let Some(file_name) = db
+        .source_root(db.file_source_root(include_file_id))
+        .path_for_file(&include_file_id)
+        .and_then(|path| path.name_and_extension())
+        .map(|(name, _)| name.to_string())
+    else {
+        return Vec::new();
+    };
+
+    let implemented = implemented_functions(sema, frange.file_id);
+    let forwards = forwards(sema, include_file_id)
+        .into_iter()
+        .filter(|forward| !implemented.contains(&forward.name))
+        .collect::<Vec<_>>();
+    if forwards.is_empty() {
+        return Vec::new();
+    }
+
+    let text = db.file_text(frange.file_id);
+    let implementation = |forward: &Forward| {
+        let value = match forward.return_type.as_str() {
+            // Most of the boolean forwards allow an action when returning `true`.
+            "bool" => Some("true".to_string()),
+            ty => return_value(ty),
+        };
+        stub(
+            &format!(
+                "public {} {}{}",
+                forward.return_type, forward.name, forward.parameters
+            ),
+            &text,
+            value.as_deref(),
+        )
+    };
+    let separator = match text.as_ref() {
+        "" => "",
+        text if text.ends_with("\n\n") => "",
+        text if text.ends_with('\n') => "\n",
+        _ => "\n\n",
+    };
+    let assist = |id: &'static str, label: String, implementations: Vec<String>| {
+        let mut source_change = SourceChange::default();
+        source_change.insert(
+            frange.file_id,
+            TextEdit::new(
+                TextRange::empty(TextSize::of(text.as_ref())),
+                format!("{separator}{}\n", implementations.join("\n\n")),
+            ),
+        );
+        Assist {
+            id: AssistId(id, AssistKind::Generate),
+            label,
+            target,
+            source_change,
+            trigger_rename: None,
+        }
+    };
+
+    let mut res = Vec::new();
+    if forwards.len() > 1 {
+        res.push(assist(
+            "implement_forwards",
+            format!(
+                "Implement the {} missing forwards of `{file_name}`",
+                forwards.len()
+            ),
+            forwards.iter().map(implementation).collect(),
+        ));
+    }
+    for forward in forwards.iter() {
+        res.push(assist(
+            "implement_forward",
+            format!("Implement forward `{}`", forward.name),
+            vec![implementation(forward)],
+        ));
+    }
+
+    res
+}
+
+/// Forwards declared at the top level of a file.
+fn forwards(sema: &Semantics<RootDatabase>, file_id: FileId) -> Vec<Forward> {
+    let preprocessing_results = sema.preprocess_file(file_id);
+    let source_map = preprocessing_results.source_map();
+    let source = preprocessing_results.preprocessed_text();
+    let tree = sema.parse(file_id);
+    let text = sema.db.file_text(file_id);
+    // Prefer the text written in the file, as the preprocessed text has its macros expanded.
+    let text_of = |node: tree_sitter::Node| {
+        match source_map.closest_u_range(ts_range_to_text_range(&node.range())) {
+            Some(range) => text.get(Range::<usize>::from(range)),
+            None => node.utf8_text(source.as_bytes()).ok(),
+        }
+        .map(String::from)
+    };
+
+    let root = tree.root_node();
+    root.children(&mut root.walk())
+        .filter(|child| {
+            TSKind::from(child) == TSKind::function_declaration
+                && child
+                    .child_by_field_name("kind")
+                    .and_then(|kind| kind.utf8_text(source.as_bytes()).ok())
+                    == Some("forward")
+        })
+        .filter_map(|forward| {
+            let return_type = match forward.child_by_field_name("returnType") {
+                Some(ty) if TSKind::from(ty) == TSKind::old_type => {
+                    new_type(text_of(ty).as_deref())
+                }
+                Some(ty) => text_of(ty)?,
+                None => "void".to_string(),
+            };
+            Some(Forward {
+                name: text_of(forward.child_by_field_name("name")?)?,
+                return_type,
+                parameters: text_of(forward.child_by_field_name("parameters")?)?,
+            })
+        })
+        .collect()
+}
+
+/// Names of the functions defined by the files of the project of a file.
+fn implemented_functions(sema: &Semantics<RootDatabase>, file_id: FileId) -> FxHashSet<String> {
+    let file_ids = sema
+        .db
+        .projet_subgraph(file_id)
+        .map(|subgraph| subgraph.file_ids().into_iter().collect::<Vec<_>>())
+        .unwrap_or_else(|| vec![file_id]);
+
+    let mut res = FxHashSet::default();
+    for file_id in file_ids {
+        let source = sema.preprocessed_text(file_id);
+        let tree = sema.parse(file_id);
+        let root = tree.root_node();
+        res.extend(
+            root.children(&mut root.walk())
+                .filter(|child| TSKind::from(child) == TSKind::function_definition)
+                .filter_map(|function| function.child_by_field_name("name"))
+                .filter_map(|name| name.utf8_text(source.as_bytes()).ok())
+                .map(String::from),
+        );
+    }
+
+    res
+}
@@ -0,0 +1,65 @@
+use base_db::{IncludeType, SourceDatabase};
+use fxhash::FxHashSet;
+use line_index::TextRange;
+use vfs::FileId;
+
+// Feature: Include Graph
+//
+// Shows the resolved includes of a file, or of the whole workspace, as a directed graph.
+//
+// |===
+// | Editor  | Action Name
+//
+// | VS Code | **sourcepawn-studio/includeGraph**
+// |===
+
+/// Resolved include graph of a file or of the whole workspace.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct IncludeGraph {
+    pub nodes: Vec<FileId>,
+    pub edges: Vec<IncludeEdge>,
+}
+
+/// Include of the `target` file by the `source` file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IncludeEdge {
+    pub source: FileId,
+    pub target: FileId,
+    pub type_: IncludeType,
+    /// Range of the include directive in the `source` file, [`None`] for the implicit
+    /// includes, e.g sourcemod.
+    pub range: Option<TextRange>,
+}
+
+/// Compute the include graph of the given file, or of all the known files if it is [`None`].
+pub(crate) fn include_graph(db: &dyn SourceDatabase, file_id: Option<FileId>) -> IncludeGraph {
+    let mut stack = match file_id {
+        Some(file_id) => vec![file_id],
+        None => db
+            .known_files()
+            .into_iter()
+            .map(|(file_id, _)| file_id)
+            .collect(),
+    };
+    stack.reverse();
+
+    let mut visited = FxHashSet::default();
+    let mut res = IncludeGraph::default();
+    while let Some(source) = stack.pop() {
+        if !visited.insert(source) {
+            continue;
+        }
+        res.nodes.push(source);
+        for include in db.file_includes(source).0.iter() {
+            res.edges.push(IncludeEdge {
+                source,
+                target: include.file_id(),
+                type_: include.type_(),
+                range: include.range(),
+            });
+            stack.push(include.file_id());
+        }
+    }
+
+    res
+}
@@ -0,0 +1,190 @@
+use hir::{DefResolution, Function, Semantics};
+use hir_def::DefDatabase;
+use line_index::{TextRange, TextSize};
+use preprocessor::db::PreprocDatabase;
+use syntax::{utils::ts_range_to_text_range, TSKind};
+use vfs::FileId;
+
+use crate::RootDatabase;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InlayHintsConfig {
+    pub parameter_hints: ParameterHintsMode,
+}
+
+/// Arguments of the calls which get the name of their parameter as a hint.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParameterHintsMode {
+    All,
+    /// Only the literals, e.g `true` or `"foo"`, whose meaning is the least obvious.
+    Literals,
+    Off,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InlayKind {
+    Parameter,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InlayHint {
+    /// User position the hint is displayed at.
+    pub position: TextSize,
+    pub kind: InlayKind,
+    pub label: String,
+}
+
+// Feature: Inlay Hints
+//
+// Shows the names of the parameters before the arguments of the function, method and
+// constructor calls, e.g `SetEntityHealth(client, /* amount: */ 100)`.
+//
+// The arguments of the calls expanded from a macro are not hinted, as they are not written in
+// the file. The hints can be shown for all the arguments, only for the literals, or disabled
+// with `#SourcePawnLanguageServer.inlayHints.parameterHints#`.
+pub(crate) fn inlay_hints(
+    db: &RootDatabase,
+    config: &InlayHintsConfig,
+    file_id: FileId,
+    range: Option<TextRange>,
+) -> Vec<InlayHint> {
+    if config.parameter_hints == ParameterHintsMode::Off {
+        return Vec::new();
+    }
+    let sema = &Semantics::new(db);
+    let tree = db.parse(file_id);
+    let source = db.preprocessed_text(file_id);
+    let preprocessing_results = db.preprocess_file(file_id);
+    let source_map = preprocessing_results.source_map();
+    let s_range = range.and_then(|range| source_map.closest_s_range(range));
+
+    let mut res = Vec::new();
+    let mut cursor = tree.root_node().walk();
+    'outer: loop {
+        let node = cursor.node();
+        let in_range = s_range.is_none_or(|range| {
+            range
+                .intersect(ts_range_to_text_range(&node.range()))
+                .is_some()
+        });
+        if in_range {
+            if let Some((function, arguments)) = call(sema, file_id, &node) {
+                let params = function.parameters(db);
+                let args = arguments
+                    .children(&mut arguments.walk())
+                    .filter(|arg| arg.is_named() && TSKind::from(arg) != TSKind::comment);
+                for (param, arg) in params.iter().zip(args) {
+                    // The rest parameter and the named arguments are not positional.
+                    if param == "..." || TSKind::from(arg) == TSKind::named_arg {
+                        break;
+                    }
+                    if !should_hint(config, &arg, param, &source) {
+                        continue;
+                    }
+                    let s_start = TextSize::new(arg.start_byte() as u32);
+                    // Skip the arguments written in the definition of a macro.
+                    if source_map
+                        .expanded_symbol_from_s_pos(s_start)
+                        .is_some_and(|symbol| {
+                            let expanded_range = symbol.expanded_range();
+                            expanded_range.start() < s_start && s_start < expanded_range.end()
+                        })
+                    {
+                        continue;
+                    }
+                    if let Some(position) = source_map.closest_u_position(s_start, false) {
+                        res.push(InlayHint {
+                            position,
+                            kind: InlayKind::Parameter,
+                            label: param.clone(),
+                        });
+                    }
+                }
+            }
+        }
+        if in_range && cursor.goto_first_child() {
+            continue;
+        }
+        while !cursor.goto_next_sibling() {
+            if !cursor.goto_parent() {
+                break 'outer;
+            }
+        }
+    }
+
+    res
+}
+
+/// Resolve the function called by a call or a `new` expression, along with its arguments.
+pub(crate) fn call<'tree>(
+    sema: &Semantics<RootDatabase>,
+    file_id: FileId,
+    node: &tree_sitter::Node<'tree>,
+) -> Option<(Function, tree_sitter::Node<'tree>)> {
+    match TSKind::from(node) {
+        TSKind::call_expression => {
+            let callee = node.child_by_field_name("function")?;
+            let name = match TSKind::from(callee) {
+                TSKind::identifier => callee,
+                TSKind::field_access => callee.child_by_field_name("field")?,
+                _ => return None,
+            };
+            let DefResolution::Function(function) = sema.find_def(file_id, &name)? else {
+                return None;
+            };
+            Some((function, node.child_by_field_name("arguments")?))
+        }
+        TSKind::new_expression => {
+            let class = node.child_by_field_name("class")?;
+            let DefResolution::Methodmap(methodmap) = sema.find_def(file_id, &class)? else {
+                return None;
+            };
+            let constructor = *sema.db.methodmap_data(methodmap.id()).constructor()?;
+            Some((
+                Function::from(constructor),
+                node.child_by_field_name("arguments")?,
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// Whether the name of a parameter adds information to its argument.
+fn should_hint(
+    config: &InlayHintsConfig,
+    arg: &tree_sitter::Node,
+    param: &str,
+    source: &str,
+) -> bool {
+    if config.parameter_hints == ParameterHintsMode::Literals && !is_literal(arg) {
+        return false;
+    }
+    // `client` is obvious for `client` or `iClient`.
+    let name = match TSKind::from(arg) {
+        TSKind::identifier => Some(*arg),
+        TSKind::field_access => arg.child_by_field_name("field"),
+        _ => None,
+    };
+    let Some(name) = name.and_then(|name| name.utf8_text(source.as_bytes()).ok()) else {
+        return true;
+    };
+
+    !name.to_lowercase().ends_with(&param.to_lowercase())
+}
+
+fn is_literal(node: &tree_sitter::Node) -> bool {
+    match TSKind::from(node) {
+        TSKind::int_literal
+        | TSKind::float_literal
+        | TSKind::char_literal
+        | TSKind::string_literal
+        | TSKind::bool_literal
+        | TSKind::null
+        | TSKind::array_literal => true,
+        // `-1`
+        TSKind::unary_expression => node
+            .child_by_field_name("argument")
+            .is_some_and(|argument| is_literal(&argument)),
+        _ => false,
+    }
+}
@@ -0,0 +1,215 @@
+use base_db::{FilePosition, FileRange, SourceDatabase};
+use hir::Semantics;
+use ide_db::{Assist, AssistId, AssistKind, RootDatabase, SourceChange, TextEdit};
+use itertools::Itertools;
+use line_index::{TextRange, TextSize};
+use syntax::{utils::ts_range_to_text_range, TSKind};
+use tree_sitter::Node;
+
+use crate::extract_variable::{trim_range, unique_name};
+
+// Feature: Introduce Constant
+//
+// Replaces the occurrences in the file of the literal under the cursor by a constant, if it is
+// used more than once. The constant is declared before the first item of the file using the
+// literal, either as a macro:
+//
+// ```sourcepawn
+// #define MAX_HEALTH 100
+// ```
+//
+// or as a static constant:
+//
+// ```sourcepawn
+// static const int MAX_HEALTH = 100;
+// ```
+//
+// A rename of the constant is triggered once the assist is applied.
+pub(crate) fn introduce_constant(db: &RootDatabase, frange: FileRange) -> Vec<Assist> {
+    let sema = &Semantics::new(db);
+    let preprocessing_results = sema.preprocess_file(frange.file_id);
+    let source_map = preprocessing_results.source_map();
+    let source = preprocessing_results.preprocessed_text();
+    let tree = sema.parse(frange.file_id);
+    let text = db.file_text(frange.file_id);
+
+    let selection = trim_range(&text, frange.range);
+    let Some(s_range) = source_map.closest_s_range(selection) else {
+        return Vec::new();
+    };
+    let Some(literal) = tree
+        .root_node()
+        .descendant_for_byte_range(s_range.start().into(), s_range.end().into())
+        .and_then(|node| {
+            // The cursor can be on a child of the literal, e.g an escape sequence.
+            std::iter::successors(Some(node), |node| node.parent()).find(is_literal)
+        })
+    else {
+        return Vec::new();
+    };
+    let Some(value) = literal.utf8_text(source.as_bytes()).ok() else {
+        return Vec::new();
+    };
+
+    // Occurrences expanded from a macro are not written in the file.
+    let occurrences = occurrences(&tree.root_node(), &literal, source)
+        .into_iter()
+        .filter_map(|node| {
+            let range = source_map.closest_u_range(ts_range_to_text_range(&node.range()))?;
+            Some((node, range))
+        })
+        .collect_vec();
+    if occurrences.len() < 2 {
+        return Vec::new();
+    }
+    let Some(target) = occurrences
+        .iter()
+        .map(|(_, range)| *range)
+        .find(|range| range.contains_range(selection))
+    else {
+        return Vec::new();
+    };
+    let Some(offset) = insertion(&occurrences[0].0, &text, |range| {
+        source_map.closest_u_range(range)
+    }) else {
+        return Vec::new();
+    };
+
+    let name = unique_name(
+        sema,
+        frange.file_id,
+        &tree.root_node(),
+        source,
+        &suggested_name(&literal, value),
+    );
+    let declarations = [
+        ("introduce_macro", "macro", format!("#define {name} ")),
+        (
+            "introduce_static_const",
+            "static constant",
+            match TSKind::from(literal) {
+                TSKind::string_literal => format!("static const char {name}[] = "),
+                kind => format!("static const {} {name} = ", literal_type(kind)),
+            },
+        ),
+    ];
+
+    declarations
+        .into_iter()
+        .map(|(id, kind, prefix)| {
+            let mut declaration = format!("{prefix}{value}");
+            if !declaration.starts_with('#') {
+                declaration.push(';');
+            }
+            let mut source_change = SourceChange::default();
+            source_change.insert(
+                frange.file_id,
+                TextEdit::new(TextRange::empty(offset), format!("{declaration}\n\n")),
+            );
+            for (_, range) in occurrences.iter() {
+                source_change.insert(frange.file_id, TextEdit::new(*range, name.clone()));
+            }
+            let name_offset = prefix.find(name.as_str()).unwrap_or_default();
+            Assist {
+                id: AssistId(id, AssistKind::RefactorExtract),
+                label: format!("Introduce {kind} for `{value}`"),
+                target,
+                source_change,
+                trigger_rename: Some(FilePosition {
+                    file_id: frange.file_id,
+                    offset: offset + TextSize::new(name_offset as u32),
+                }),
+            }
+        })
+        .collect()
+}
+
+fn is_literal(node: &Node) -> bool {
+    matches!(
+        TSKind::from(node),
+        TSKind::int_literal | TSKind::float_literal | TSKind::char_literal | TSKind::string_literal
+    )
+}
+
+fn literal_type(kind: TSKind) -> &'static str {
+    match kind {
+        TSKind::float_literal => "float",
+        TSKind::char_literal => "char",
+        _ => "int",
+    }
+}
+
+/// Literals of the tree with the same kind and text as `literal`, in the order of the source.
+fn occurrences<'tree>(root: &Node<'tree>, literal: &Node, source: &str) -> Vec<Node<'tree>> {
+    let value = literal.utf8_text(source.as_bytes()).ok();
+    let mut res = Vec::new();
+    let mut cursor = root.walk();
+    'outer: loop {
+        let node = cursor.node();
+        if TSKind::from(node) == TSKind::from(literal)
+            && node.utf8_text(source.as_bytes()).ok() == value
+        {
+            res.push(node);
+        } else if cursor.goto_first_child() {
+            continue;
+        }
+        loop {
+            if cursor.node() == *root {
+                break 'outer;
+            }
+            if cursor.goto_next_sibling() {
+                break;
+            }
+            if !cursor.goto_parent() {
+                break 'outer;
+            }
+        }
+    }
+
+    res
+}
+
+/// Offset before the top-level item containing `node` and its leading comments.
+fn insertion(
+    node: &Node,
+    text: &str,
+    closest_u_range: impl Fn(TextRange) -> Option<TextRange>,
+) -> Option<TextSize> {
+    let mut item = std::iter::successors(Some(*node), |node| node.parent()).find(|node| {
+        node.parent()
+            .is_some_and(|parent| parent.parent().is_none())
+    })?;
+    while let Some(prev) = item.prev_sibling() {
+        if TSKind::from(prev) != TSKind::comment
+            || prev.end_position().row + 1 < item.start_position().row
+        {
+            break;
+        }
+        item = prev;
+    }
+    let start = closest_u_range(ts_range_to_text_range(&item.range()))?.start();
+    // Do not split a line.
+    if !text[..usize::from(start)].ends_with('\n') && start != TextSize::new(0) {
+        return None;
+    }
+
+    Some(start)
+}
+
+/// Name of the constant, e.g `MAX_HEALTH` for `"max health"`.
+fn suggested_name(literal: &Node, value: &str) -> String {
+    if TSKind::from(literal) != TSKind::string_literal {
+        return "CONSTANT".to_string();
+    }
+    let name = value
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .take(4)
+        .map(|word| word.to_ascii_uppercase())
+        .join("_");
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        return "CONSTANT".to_string();
+    }
+
+    name
+}
@@ -1,31 +1,54 @@
 //! base_db defines basic database traits. The concrete DB is defined by ide.
 
+mod annotations;
+mod auto_import;
 mod call_hierarchy;
 mod completion;
+mod convars;
+mod convert_syntax;
+mod create_function;
+mod document_highlight;
+mod document_links;
 mod events;
+mod extract_variable;
+mod file_macros;
+mod folding_ranges;
+mod formatting;
+mod generate_property;
 mod goto_definition;
 mod hover;
+mod implement_forwards;
+mod include_graph;
+mod inlay_hints;
+mod introduce_constant;
+mod linked_editing;
 mod markup;
+mod organize_includes;
 mod prime_caches;
 mod references;
 mod rename;
 mod signature_help;
+mod ssr;
 mod status;
+mod surround_with;
 mod symbols;
 mod syntax_highlighting;
+mod syntax_tree;
+mod workspace_symbols;
 
 use std::{panic::AssertUnwindSafe, sync::Arc};
 
 use base_db::{
-    Change, FileExtension, FilePosition, FileRange, Graph, SourceDatabase, SourceDatabaseExt, Tree,
+    Change, FileExtension, FilePosition, FileRange, Gamedata, Graph, IncludeLimits,
+    IncludeSearchOrder, Phrases, SourceDatabase, SourceDatabaseExt, Tree,
 };
 use fxhash::FxHashMap;
 use hir::{DefResolution, Function};
 use hir_def::{print_item_tree, DefDatabase};
 use hover::HoverResult;
 use ide_db::{
-    CallItem, IncomingCallItem, LineIndexDatabase, OutgoingCallItem, RootDatabase, SourceChange,
-    Symbols,
+    Assist, CallItem, IncomingCallItem, LineIndexDatabase, OutgoingCallItem, RootDatabase,
+    SourceChange, Symbols, TextEdit,
 };
 use itertools::Itertools;
 use line_index::TextRange;
@@ -36,16 +59,31 @@ use salsa::{Cancelled, ParallelDatabase};
 use serde_json::Value;
 use vfs::FileId;
 
+pub use annotations::{Annotation, AnnotationConfig, AnnotationKind};
 pub use completion::{CompletionItem, CompletionKind};
+pub use convert_syntax::SyntaxConversion;
+pub use document_highlight::{HighlightedRange, ReferenceCategory};
+pub use document_links::DocumentLink;
+pub use file_macros::FileMacro;
+pub use folding_ranges::{Fold, FoldKind};
+pub use formatting::{format, BraceStyle, EndOfLine, FormattingConfig, IndentStyle};
 pub use goto_definition::NavigationTarget;
 pub use hover::{HoverAction, HoverConfig, HoverDocFormat, HoverGotoTypeData};
-pub use ide_db::Cancellable;
-pub use ide_diagnostics::{Diagnostic, DiagnosticsConfig, Severity};
+pub use ide_db::{Cancellable, ExperimentalFlag, ExperimentalFlags};
+pub use ide_diagnostics::{
+    explain as explain_diagnostic, Diagnostic, DiagnosticsConfig, FixAll, Severity,
+    DIAGNOSTIC_CODES,
+};
+pub use include_graph::{IncludeEdge, IncludeGraph};
+pub use inlay_hints::{InlayHint, InlayHintsConfig, InlayKind, ParameterHintsMode};
 pub use line_index::{LineCol, LineIndex, WideEncoding, WideLineCol};
 pub use markup::Markup;
 pub use prime_caches::ParallelPrimeCachesProgress;
+pub use rename::RenameError;
 pub use signature_help::SignatureHelp;
+pub use ssr::{SsrError, SsrMatches};
 pub use syntax_highlighting::{Highlight, HlMod, HlMods, HlRange, HlTag};
+pub use workspace_symbols::WorkspaceSymbol;
 
 /// Info associated with a [`range`](TextRange).
 #[derive(Debug)]
@@ -97,6 +135,94 @@ impl AnalysisHost {
         self.db.set_known_files(files);
     }
 
+    /// Sets the main files pinned by the user.
+    ///
+    /// Returns `true` if the main files changed.
+    pub fn set_main_files(&mut self, main_files: FxHashMap<FileId, FileId>) -> bool {
+        if *self.db.main_files() == main_files {
+            return false;
+        }
+        self.db.set_main_files(Arc::new(main_files));
+        true
+    }
+
+    /// Sets the phrases of the translation files of the project.
+    ///
+    /// Returns `true` if the phrases changed.
+    pub fn set_phrases(&mut self, phrases: Phrases) -> bool {
+        if *self.db.phrases() == phrases {
+            return false;
+        }
+        self.db.set_phrases(Arc::new(phrases));
+        true
+    }
+
+    /// Sets the entries of the gamedata files of the project.
+    ///
+    /// Returns `true` if the gamedata changed.
+    pub fn set_gamedata(&mut self, gamedata: Gamedata) -> bool {
+        if *self.db.gamedata() == gamedata {
+            return false;
+        }
+        self.db.set_gamedata(Arc::new(gamedata));
+        true
+    }
+
+    /// Sets the files implicitly included at the top of every file.
+    ///
+    /// Returns `true` if the implicit includes changed.
+    pub fn set_implicit_includes(&mut self, implicit_includes: Vec<String>) -> bool {
+        if *self.db.implicit_includes() == implicit_includes {
+            return false;
+        }
+        self.db.set_implicit_includes(Arc::new(implicit_includes));
+        true
+    }
+
+    /// Sets the order of the locations searched when resolving the includes.
+    ///
+    /// Returns `true` if the search order changed.
+    pub fn set_include_search_order(&mut self, search_order: IncludeSearchOrder) -> bool {
+        if *self.db.include_search_order() == search_order {
+            return false;
+        }
+        self.db.set_include_search_order(Arc::new(search_order));
+        true
+    }
+
+    /// Sets the limits of the includes expanded when preprocessing a project.
+    ///
+    /// Returns `true` if the limits changed.
+    pub fn set_include_limits(&mut self, limits: IncludeLimits) -> bool {
+        if self.db.include_limits() == limits {
+            return false;
+        }
+        self.db.set_include_limits(limits);
+        true
+    }
+
+    /// Sets the branches of the conditions forced active in each file.
+    ///
+    /// Returns `true` if the forced branches changed.
+    pub fn set_forced_branches(&mut self, forced_branches: FxHashMap<FileId, Vec<u32>>) -> bool {
+        if *self.db.forced_branches() == forced_branches {
+            return false;
+        }
+        self.db.set_forced_branches(Arc::new(forced_branches));
+        true
+    }
+
+    /// Sets the additional macro configurations the files are analyzed in.
+    ///
+    /// Returns `true` if the configurations changed.
+    pub fn set_macro_configurations(&mut self, configurations: Vec<Vec<String>>) -> bool {
+        if *self.db.macro_configurations() == configurations {
+            return false;
+        }
+        self.db.set_macro_configurations(Arc::new(configurations));
+        true
+    }
+
     /// Applies changes to the current state of the world.
     pub fn apply_change(&mut self, change: Change) {
         self.db.apply_change(change)
@@ -123,6 +249,45 @@ impl Analysis {
         self.with_db(|db| db.parse(file_id))
     }
 
+    /// Returns the folding ranges of a file.
+    pub fn folding_ranges(&self, file_id: FileId) -> Cancellable<Vec<Fold>> {
+        self.with_db(|db| folding_ranges::folding_ranges(db, file_id))
+    }
+
+    /// Returns the edit formatting a file, if it is not formatted.
+    pub fn format(
+        &self,
+        file_id: FileId,
+        config: &FormattingConfig,
+    ) -> Cancellable<Option<TextEdit>> {
+        self.with_db(|db| formatting::format_file(db, file_id, config))
+    }
+
+    /// Returns the edit formatting the lines of a range, if they are not formatted.
+    pub fn format_range(
+        &self,
+        frange: FileRange,
+        config: &FormattingConfig,
+    ) -> Cancellable<Option<TextEdit>> {
+        self.with_db(|db| formatting::format_range(db, frange, config))
+    }
+
+    /// Returns the edit to apply after a character is typed, e.g to reindent a closing brace.
+    pub fn on_char_typed(
+        &self,
+        position: FilePosition,
+        char_typed: char,
+        config: &FormattingConfig,
+    ) -> Cancellable<Option<TextEdit>> {
+        self.with_db(|db| formatting::on_char_typed(db, position, char_typed, config))
+    }
+
+    /// Returns a textual representation of the parse tree of a file, or of the smallest node
+    /// containing `range`.
+    pub fn syntax_tree(&self, file_id: FileId, range: Option<TextRange>) -> Cancellable<String> {
+        self.with_db(|db| syntax_tree::syntax_tree(db, file_id, range))
+    }
+
     pub fn graph(&self) -> Cancellable<Arc<Graph>> {
         self.with_db(|db| db.graph())
     }
@@ -132,6 +297,32 @@ impl Analysis {
         self.with_db(|db| db.preprocessed_text(file_id))
     }
 
+    /// Gets the pairs of ranges of the file and of its preprocessed text which correspond to
+    /// each other, including the ranges of the expanded macros.
+    pub fn preprocessed_source_map(
+        &self,
+        file_id: FileId,
+    ) -> Cancellable<Vec<(TextRange, TextRange)>> {
+        self.with_db(|db| {
+            let result = db.preprocess_file(file_id);
+            let source_map = result.source_map();
+            let mut res = source_map.u_range_to_s_range_vec();
+            res.extend(
+                source_map
+                    .expanded_symbols()
+                    .iter()
+                    .map(|symbol| (*symbol.range(), *symbol.expanded_range())),
+            );
+            res.sort_by_key(|(u_range, _)| (u_range.start(), u_range.end()));
+            res
+        })
+    }
+
+    /// Gets the ranges of the file which are skipped by the preprocessor.
+    pub fn inactive_ranges(&self, file_id: FileId) -> Cancellable<Vec<TextRange>> {
+        self.with_db(|db| db.preprocess_file(file_id).inactive_ranges().to_vec())
+    }
+
     /// Gets the [`String`] representation of the item tree of the file.
     pub fn pretty_item_tree(&self, file_id: FileId) -> Cancellable<String> {
         self.with_db(|db| {
@@ -158,6 +349,43 @@ impl Analysis {
         })
     }
 
+    /// Get the root file of the project of the file, taking the main file pinned by the user into
+    /// account.
+    pub fn project_main_file(&self, file_id: FileId) -> Cancellable<Option<FileId>> {
+        self.with_db(|db| {
+            db.projet_subgraph(file_id)
+                .map(|subgraph| subgraph.root.file_id)
+        })
+    }
+
+    /// Get the main file pinned by the user for the file, if any.
+    pub fn pinned_main_file(&self, file_id: FileId) -> Cancellable<Option<FileId>> {
+        self.with_db(|db| db.main_files().get(&file_id).copied())
+    }
+
+    /// Returns the index of the `#if`, `#elseif` or `#else` directive on the line of the
+    /// position, which identifies the branch when forcing it active.
+    pub fn branch_directive_at(&self, position: FilePosition) -> Cancellable<Option<u32>> {
+        self.with_db(|db| {
+            preprocessor::branch_directive_at(&db.file_text(position.file_id), position.offset)
+        })
+    }
+
+    /// Returns the links of the include directives of the file.
+    pub fn document_links(&self, file_id: FileId) -> Cancellable<Vec<DocumentLink>> {
+        self.with_db(|db| document_links::document_links(db, file_id))
+    }
+
+    /// Get the include graph of the file, or of the whole workspace if no file is given.
+    pub fn include_graph(&self, file_id: Option<FileId>) -> Cancellable<IncludeGraph> {
+        self.with_db(|db| include_graph::include_graph(db, file_id))
+    }
+
+    /// Returns the macros visible at the end of a file.
+    pub fn file_macros(&self, file_id: FileId) -> Cancellable<Vec<FileMacro>> {
+        self.with_db(|db| file_macros::file_macros(db, file_id))
+    }
+
     /// Debug info about the current state of the analysis.
     pub fn status(&self, file_id: Option<FileId>) -> Cancellable<String> {
         self.with_db(|db| status::status(db, file_id))
@@ -169,10 +397,13 @@ impl Analysis {
         self.with_db(|db| db.line_index(file_id))
     }
 
+    /// Compute the def maps of the projects of `files_to_prime`, or of all the projects if
+    /// [`None`], then analyze `opened_files` ahead of the first request on them.
     pub fn parallel_prime_caches<F1, F2>(
         &self,
         num_worker_threads: u8,
         files_to_prime: Option<Vec<FileId>>,
+        opened_files: Vec<FileId>,
         cb: F1,
         file_id_to_name: F2,
     ) -> Cancellable<()>
@@ -185,6 +416,7 @@ impl Analysis {
                 db,
                 num_worker_threads,
                 files_to_prime,
+                opened_files,
                 &cb,
                 file_id_to_name,
             )
@@ -220,6 +452,17 @@ impl Analysis {
         self.with_db(|db| ide_diagnostics::diagnostics(db, config, file_id))
     }
 
+    /// Merges the fixes of the diagnostics of the given files, optionally only the ones with
+    /// the given code.
+    pub fn fix_all(
+        &self,
+        config: &DiagnosticsConfig,
+        code: Option<&str>,
+        file_ids: &[FileId],
+    ) -> Cancellable<FixAll> {
+        self.with_db(|db| ide_diagnostics::fix_all(db, config, code, file_ids))
+    }
+
     /// Returns the definitions from the symbol at `position`.
     pub fn goto_definition(
         &self,
@@ -233,16 +476,114 @@ impl Analysis {
         self.with_db(|db| references::references(db, pos))
     }
 
+    /// Returns the occurrences of the symbol at `pos` in its function, to highlight them.
+    pub fn highlight_related(
+        &self,
+        pos: FilePosition,
+    ) -> Cancellable<Option<Vec<HighlightedRange>>> {
+        self.with_db(|db| document_highlight::document_highlight(db, pos))
+    }
+
+    /// Returns the ranges of the occurrences of the local at `pos`, to edit them together.
+    pub fn linked_editing_ranges(&self, pos: FilePosition) -> Cancellable<Option<Vec<TextRange>>> {
+        self.with_db(|db| linked_editing::linked_editing_ranges(db, pos))
+    }
+
+    /// Checks that the symbol at `fpos` can be renamed, and returns the range of its name.
+    pub fn prepare_rename(
+        &self,
+        fpos: FilePosition,
+    ) -> Cancellable<Result<TextRange, RenameError>> {
+        self.with_db(|db| rename::prepare_rename(db, fpos))
+    }
+
     /// Returns the source change to rename the symbol at `position` to `new_name`.
-    pub fn rename(&self, fpos: FilePosition, new_name: &str) -> Cancellable<Option<SourceChange>> {
+    pub fn rename(
+        &self,
+        fpos: FilePosition,
+        new_name: &str,
+    ) -> Cancellable<Result<SourceChange, RenameError>> {
         self.with_db(|db| rename::rename(db, fpos, new_name))
     }
 
+    /// Searches the code of `file_ids` matching the pattern of the `query`, and the edits
+    /// replacing it if the query has a replacement.
+    pub fn structural_search_replace(
+        &self,
+        query: &str,
+        file_ids: &[FileId],
+    ) -> Cancellable<Result<SsrMatches, SsrError>> {
+        self.with_db(|db| ssr::structural_search_replace(db, query, file_ids))
+    }
+
+    /// Returns the assists adding the include of the unresolved symbol at the start of `frange`.
+    pub fn auto_import(&self, frange: FileRange) -> Cancellable<Vec<Assist>> {
+        self.with_db(|db| auto_import::auto_import(db, frange))
+    }
+
+    /// Returns the assists creating the undefined function called at the start of `frange`.
+    pub fn create_function(&self, frange: FileRange) -> Cancellable<Vec<Assist>> {
+        self.with_db(|db| create_function::create_function(db, frange))
+    }
+
+    /// Returns the assist sorting and deduplicating the includes of the file, if they are not
+    /// already organized.
+    pub fn organize_includes(&self, file_id: FileId) -> Cancellable<Option<Assist>> {
+        self.with_db(|db| organize_includes::organize_includes(db, file_id))
+    }
+
+    /// Returns the assist extracting the expression selected by `frange` into a local variable.
+    pub fn extract_variable(&self, frange: FileRange) -> Cancellable<Option<Assist>> {
+        self.with_db(|db| extract_variable::extract_variable(db, frange))
+    }
+
+    /// Returns the assists generating a property in the methodmap at the start of `frange`.
+    pub fn generate_property(&self, frange: FileRange) -> Cancellable<Vec<Assist>> {
+        self.with_db(|db| generate_property::generate_property(db, frange))
+    }
+
+    /// Returns the assists implementing the forwards of the file included at the start of
+    /// `frange`.
+    pub fn implement_forwards(&self, frange: FileRange) -> Cancellable<Vec<Assist>> {
+        self.with_db(|db| implement_forwards::implement_forwards(db, frange))
+    }
+
+    /// Returns the assists replacing the occurrences of the literal at `frange` by a constant.
+    pub fn introduce_constant(&self, frange: FileRange) -> Cancellable<Vec<Assist>> {
+        self.with_db(|db| introduce_constant::introduce_constant(db, frange))
+    }
+
+    /// Returns the assists converting the declaration of the old syntax at `frange`, or its whole
+    /// file, to the new syntax.
+    pub fn convert_syntax_assists(&self, frange: FileRange) -> Cancellable<Vec<Assist>> {
+        self.with_db(|db| convert_syntax::convert_syntax_assists(db, frange))
+    }
+
+    /// Returns the assists surrounding the statements of `frange`, whose edits are snippets if
+    /// `snippets` is set.
+    pub fn surround_with(&self, frange: FileRange, snippets: bool) -> Cancellable<Vec<Assist>> {
+        self.with_db(|db| surround_with::surround_with(db, frange, snippets))
+    }
+
+    /// Converts all the declarations of the old syntax of the files to the new syntax.
+    pub fn convert_to_new_syntax(&self, file_ids: &[FileId]) -> Cancellable<SyntaxConversion> {
+        self.with_db(|db| convert_syntax::convert_to_new_syntax(db, file_ids))
+    }
+
     /// Returns the document symbol that corresponds to the `file_id`.
     pub fn symbols(&self, file_id: FileId) -> Cancellable<Option<Symbols>> {
         self.with_db(|db| symbols::symbols(db, file_id))
     }
 
+    /// Returns at most `limit` symbols of the workspace matching the `query`, `0` for no limit.
+    pub fn workspace_symbols(
+        &self,
+        query: &str,
+        limit: usize,
+    ) -> Cancellable<Vec<WorkspaceSymbol>> {
+        self.with_db(|db| workspace_symbols::workspace_symbols(db, query, limit))
+    }
+
     /// Returns the hover information at `position`.
     pub fn hover(
         &self,
@@ -254,6 +595,30 @@ impl Analysis {
         self.with_db(|db| hover::hover(db, pos, config, file_id_to_url, events_game_name))
     }
 
+    /// Returns the inlay hints of a file, or of a range of it.
+    pub fn inlay_hints(
+        &self,
+        config: &InlayHintsConfig,
+        file_id: FileId,
+        range: Option<TextRange>,
+    ) -> Cancellable<Vec<InlayHint>> {
+        self.with_db(|db| inlay_hints::inlay_hints(db, config, file_id, range))
+    }
+
+    /// Returns the annotations, i.e. the code lenses, of a file.
+    pub fn annotations(
+        &self,
+        config: &AnnotationConfig,
+        file_id: FileId,
+    ) -> Cancellable<Vec<Annotation>> {
+        self.with_db(|db| annotations::annotations(db, config, file_id))
+    }
+
+    /// Computes the data of an annotation returned by [`Analysis::annotations`].
+    pub fn resolve_annotation(&self, annotation: Annotation) -> Cancellable<Annotation> {
+        self.with_db(|db| annotations::resolve_annotation(db, annotation))
+    }
+
     /// Returns the hover information at `position`.
     pub fn signature_help(&self, pos: FilePosition) -> Cancellable<Option<SignatureHelp>> {
         self.with_db(|db| signature_help::signature_help(db, pos))
@@ -0,0 +1,45 @@
+use base_db::FilePosition;
+use hir::{DefResolution, Semantics};
+use line_index::TextRange;
+use syntax::TSKind;
+
+use crate::{document_highlight, RootDatabase};
+
+// Feature: Linked Editing
+//
+// Editing the name of a local variable or of a parameter also edits its other occurrences in
+// the function, e.g the loop variable of a `for` statement.
+//
+// The global symbols are not linked, as their occurrences in other files would not be updated.
+// Use the rename instead.
+pub(crate) fn linked_editing_ranges(
+    db: &RootDatabase,
+    pos: FilePosition,
+) -> Option<Vec<TextRange>> {
+    let sema = &Semantics::new(db);
+    let preprocessing_results = sema.preprocess_file(pos.file_id);
+    let tree = sema.parse(pos.file_id);
+    let offset = preprocessing_results
+        .source_map()
+        .closest_s_position_always(pos.offset);
+    let node = tree
+        .root_node()
+        .descendant_for_byte_range(offset.into(), offset.into())?;
+    if TSKind::from(node) != TSKind::identifier {
+        return None;
+    }
+    let DefResolution::Local(_) = sema.find_def(pos.file_id, &node)? else {
+        return None;
+    };
+
+    let ranges = document_highlight::document_highlight(db, pos)?
+        .into_iter()
+        .map(|highlight| highlight.range)
+        .collect::<Vec<_>>();
+    // Editing a single occurrence does not need to be linked.
+    if ranges.len() < 2 {
+        return None;
+    }
+
+    Some(ranges)
+}
@@ -0,0 +1,208 @@
+use base_db::SourceDatabase;
+use fxhash::FxHashSet;
+use ide_db::{Assist, AssistId, AssistKind, RootDatabase, SourceChange, TextEdit};
+use lazy_static::lazy_static;
+use line_index::{TextRange, TextSize};
+use regex::Regex;
+use vfs::FileId;
+
+/// Includes shipped with SourceMod.
+const CORE_INCLUDES: &[&str] = &[
+    "admin",
+    "adminmenu",
+    "adt",
+    "adt_array",
+    "adt_stack",
+    "adt_trie",
+    "banning",
+    "basecomm",
+    "bitbuffer",
+    "clientprefs",
+    "clients",
+    "commandfilters",
+    "commandline",
+    "console",
+    "convars",
+    "core",
+    "cstrike",
+    "datapack",
+    "dbi",
+    "dhooks",
+    "entity",
+    "entity_prop_stocks",
+    "events",
+    "files",
+    "float",
+    "functions",
+    "geoip",
+    "halflife",
+    "handles",
+    "helpers",
+    "keyvalues",
+    "lang",
+    "logging",
+    "mapchooser",
+    "menus",
+    "nextmap",
+    "profiler",
+    "protobuf",
+    "regex",
+    "sdkhooks",
+    "sdktools",
+    "sorting",
+    "sourcemod",
+    "string",
+    "testing",
+    "textparse",
+    "tf2",
+    "tf2_stocks",
+    "timers",
+    "topmenus",
+    "usermessages",
+    "vector",
+    "version",
+];
+
+/// Groups of includes, in the order they are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Group {
+    Core,
+    ThirdParty,
+    Local,
+}
+
+#[derive(Debug)]
+struct Directive<'a> {
+    /// Line of the directive, without its indentation and line ending.
+    line: &'a str,
+    path: &'a str,
+    chevrons: bool,
+}
+
+impl Directive<'_> {
+    fn group(&self) -> Group {
+        if !self.chevrons {
+            Group::Local
+        } else if CORE_INCLUDES.contains(&self.path) || self.path.starts_with("sdktools_") {
+            Group::Core
+        } else {
+            Group::ThirdParty
+        }
+    }
+}
+
+/// Consecutive unconditional include directives, possibly separated by blank lines.
+#[derive(Debug, Default)]
+struct Run<'a> {
+    range: Option<TextRange>,
+    directives: Vec<Directive<'a>>,
+}
+
+// Feature: Organize Includes
+//
+// Sorts the `#include` and `#tryinclude` directives of a file, and removes the duplicated ones.
+// The includes are grouped, with a blank line between the groups:
+//
+// 1. the includes of SourceMod, starting with `sourcemod`,
+// 2. the other includes with chevrons, e.g `#include <multicolors>`,
+// 3. the local includes with quotes, e.g `#include "plugin/utils.sp"`.
+//
+// Only the directives written one after the other are reordered together, as the directives
+// between them, e.g `#pragma newdecls required`, may apply to some of them. The directives
+// inside of a conditional block, e.g `#if defined _tf2_included`, are left untouched.
+pub(crate) fn organize_includes(db: &RootDatabase, file_id: FileId) -> Option<Assist> {
+    lazy_static! {
+        static ref RE_INCLUDE: Regex = Regex::new(
+            r#"^#\s*(?:include|tryinclude)\s*(?:<([^>]+)>|"([^"]+)")\s*(?://.*|/\*.*\*/)?$"#
+        )
+        .unwrap();
+        static ref RE_CONDITION_START: Regex = Regex::new(r"^#\s*if").unwrap();
+        static ref RE_CONDITION_END: Regex = Regex::new(r"^#\s*endif").unwrap();
+    }
+
+    let text = db.file_text(file_id);
+    let line_ending = if text.contains("\r\n") { "\r\n" } else { "\n" };
+
+    let mut runs = vec![Run::default()];
+    let mut depth = 0u32;
+    let mut offset = TextSize::new(0);
+    for raw_line in text.split_inclusive('\n') {
+        let start = offset;
+        offset += TextSize::of(raw_line);
+        let line = raw_line.trim();
+        let run = runs.last_mut()?;
+        if line.is_empty() {
+            continue;
+        }
+        if depth == 0 {
+            if let Some(captures) = RE_INCLUDE.captures(line) {
+                let (path, chevrons) = match captures.get(1) {
+                    Some(path) => (path.as_str(), true),
+                    None => (captures.get(2)?.as_str(), false),
+                };
+                let indent =
+                    TextSize::of(&raw_line[..raw_line.len() - raw_line.trim_start().len()]);
+                let range = TextRange::at(start + indent, TextSize::of(line));
+                run.range = Some(run.range.map_or(range, |it| it.cover(range)));
+                run.directives.push(Directive {
+                    line,
+                    path,
+                    chevrons,
+                });
+                continue;
+            }
+        }
+        if RE_CONDITION_START.is_match(line) {
+            depth += 1;
+        } else if RE_CONDITION_END.is_match(line) {
+            depth = depth.saturating_sub(1);
+        }
+        if !run.directives.is_empty() {
+            runs.push(Run::default());
+        }
+    }
+
+    let mut seen = FxHashSet::default();
+    let mut source_change = SourceChange::default();
+    let mut target = None;
+    for run in runs {
+        let Some(range) = run.range else {
+            continue;
+        };
+        let mut directives = run
+            .directives
+            .into_iter()
+            .filter(|directive| seen.insert((directive.chevrons, directive.path)))
+            .collect::<Vec<_>>();
+        directives.sort_by_cached_key(|directive| {
+            (
+                directive.group(),
+                directive.path != "sourcemod",
+                directive.path.to_lowercase(),
+            )
+        });
+        let mut organized = String::new();
+        for (idx, directive) in directives.iter().enumerate() {
+            if idx > 0 {
+                organized.push_str(line_ending);
+                if directives[idx - 1].group() != directive.group() {
+                    organized.push_str(line_ending);
+                }
+            }
+            organized.push_str(directive.line);
+        }
+        if text[range] == organized {
+            continue;
+        }
+        target.get_or_insert(range);
+        source_change.insert(file_id, TextEdit::new(range, organized));
+    }
+
+    Some(Assist {
+        id: AssistId("organize_includes", AssistKind::OrganizeIncludes),
+        label: "Organize includes".to_string(),
+        target: target?,
+        source_change,
+        trigger_rename: None,
+    })
+}
@@ -3,11 +3,14 @@ use std::time::Duration;
 use base_db::{FileExtension, SourceDatabase, SubGraph};
 use crossbeam::channel::Sender;
 use fxhash::{FxHashMap, FxHashSet};
+use hir::FileDef;
 use hir_def::DefDatabase;
 use ide_db::{FxIndexMap, RootDatabase};
 use salsa::{Cancelled, Database, ParallelDatabase, Snapshot};
 use vfs::FileId;
 
+use crate::syntax_highlighting;
+
 #[derive(Debug)]
 pub struct ParallelPrimeCachesProgress {
     /// the projects that we are currently priming.
@@ -22,6 +25,7 @@ pub(crate) fn parallel_prime_caches<F>(
     db: &RootDatabase,
     num_worker_threads: u8,
     files_to_prime: Option<Vec<FileId>>,
+    opened_files: Vec<FileId>,
     cb: &(dyn Fn(ParallelPrimeCachesProgress) + Sync),
     file_id_to_name: F,
 ) where
@@ -134,4 +138,21 @@ pub(crate) fn parallel_prime_caches<F>(
 
         cb(progress);
     }
+
+    for file_id in opened_files {
+        db.unwind_if_cancelled();
+        prime_file(db, file_id);
+    }
+}
+
+/// Compute the most expensive artifacts of an opened file, i.e the type inference of its bodies
+/// and its syntax highlighting, so that the first request on the file is answered from the
+/// caches.
+fn prime_file(db: &RootDatabase, file_id: FileId) {
+    let file = hir::File::from(file_id);
+    file.declarations(db)
+        .into_iter()
+        .filter_map(FileDef::as_def_with_body)
+        .for_each(|def| def.diagnostics(db, &mut Vec::new()));
+    syntax_highlighting::highlight(db, file_id, None);
 }
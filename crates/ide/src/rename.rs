@@ -1,18 +1,336 @@
-use base_db::FilePosition;
-use hir::Semantics;
+use std::fmt;
+
+use base_db::{FilePosition, FileRange, SourceDatabaseExt};
+use hir::{DefResolution, FunctionType, Semantics};
 use ide_db::{RootDatabase, SourceChange, TextEdit};
+use itertools::Itertools;
+use line_index::TextRange;
+use syntax::{utils::ts_range_to_text_range, TSKind};
+
+/// Reason why a symbol cannot be renamed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenameError(String);
+
+impl fmt::Display for RenameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for RenameError {}
+
+/// Check that the symbol at `fpos` can be renamed, and return the user range of its name, used
+/// as the placeholder of the new name.
+///
+/// The keywords, the literals, the symbols defined in an include directory, e.g the natives of
+/// SourceMod, and the symbols written in the body of a macro cannot be renamed.
+pub(crate) fn prepare_rename(
+    db: &RootDatabase,
+    fpos: FilePosition,
+) -> Result<TextRange, RenameError> {
+    let sema = &Semantics::new(db);
+    if let Some((offset, def)) = sema.find_macro_def(&fpos) {
+        check_def_is_local(db, &def)?;
+        return Ok(offset.name_range());
+    }
+
+    let preprocessing_results = sema.preprocess_file(fpos.file_id);
+    let source_map = preprocessing_results.source_map();
+    let source = sema.preprocessed_text(fpos.file_id);
+    let tree = sema.parse(fpos.file_id);
+    let offset = source_map.closest_s_position_always(fpos.offset);
+    let node = tree
+        .root_node()
+        .descendant_for_byte_range(offset.into(), offset.into())
+        .ok_or_else(|| RenameError("no symbol to rename".to_string()))?;
+    match TSKind::from(node) {
+        TSKind::identifier => (),
+        TSKind::int_literal
+        | TSKind::float_literal
+        | TSKind::char_literal
+        | TSKind::string_literal
+        | TSKind::bool_literal
+        | TSKind::null => return Err(RenameError("cannot rename a literal".to_string())),
+        TSKind::this => return Err(RenameError("cannot rename a keyword".to_string())),
+        _ if !node.is_named() => return Err(RenameError("cannot rename a keyword".to_string())),
+        _ => return Err(RenameError("no symbol to rename".to_string())),
+    }
+    let name = node.utf8_text(source.as_bytes()).unwrap_or_default();
+    let def = sema
+        .find_def(fpos.file_id, &node)
+        .ok_or_else(|| RenameError(format!("no definition found for `{name}`")))?;
+    check_def_is_local(db, &def)?;
+
+    source_map
+        .closest_u_range(ts_range_to_text_range(&node.range()))
+        .ok_or_else(|| RenameError(format!("`{name}` is written in the body of a macro")))
+}
 
+/// Check that a definition is not in an include directory, whose files are not edited.
+fn check_def_is_local(db: &RootDatabase, def: &DefResolution) -> Result<(), RenameError> {
+    let source_root = db.source_root(db.file_source_root(def.file_id(db)));
+    if source_root.is_include_dir {
+        let name = def.name(db).map(|it| it.to_string()).unwrap_or_default();
+        return Err(RenameError(format!(
+            "cannot rename `{name}`, it is defined in an include directory"
+        )));
+    }
+
+    Ok(())
+}
+
+// Feature: Rename
+//
+// Renames a symbol and all its references in the files of the project.
+//
+// The rename is rejected before editing any file if the new name is not a valid identifier, or
+// if it conflicts with:
+//
+// - a symbol of the same name in the scope of one of the edited files, e.g in an include, unless
+//   the renamed symbol is a member of an enum struct or a methodmap,
+// - a macro of the same name, which would capture the renamed references,
+// - a local of the same name in a function using the renamed symbol,
+// - a symbol of the same name used in the function of a renamed local, which it would shadow.
 pub(crate) fn rename(
     db: &RootDatabase,
     fpos: FilePosition,
     new_name: &str,
-) -> Option<SourceChange> {
+) -> Result<SourceChange, RenameError> {
+    prepare_rename(db, fpos)?;
+    check_identifier(new_name)?;
     let sema = &Semantics::new(db);
-    let refs = sema.find_references_from_pos(fpos)?;
+    let (def, refs) = sema
+        .find_references_from_pos(fpos)
+        .ok_or_else(|| RenameError("no references found".to_string()))?;
+
+    let conflicts = conflicts(sema, &def, &refs, new_name);
+    if !conflicts.is_empty() {
+        return Err(RenameError(format!(
+            "cannot rename to `{new_name}`, it conflicts with {}",
+            conflicts.join(", ")
+        )));
+    }
+
     let mut res = SourceChange::default();
-    refs.1.iter().for_each(|it| {
+    refs.iter().for_each(|it| {
         res.insert(it.file_id, TextEdit::new(it.range, new_name.to_string()));
     });
 
-    res.into()
+    Ok(res)
+}
+
+const KEYWORDS: &[&str] = &[
+    "acquire",
+    "as",
+    "break",
+    "case",
+    "cast_to",
+    "char",
+    "const",
+    "continue",
+    "decl",
+    "default",
+    "delete",
+    "do",
+    "else",
+    "enum",
+    "explicit",
+    "false",
+    "float",
+    "for",
+    "forward",
+    "funcenum",
+    "functag",
+    "function",
+    "if",
+    "implicit",
+    "int",
+    "methodmap",
+    "native",
+    "new",
+    "null",
+    "object",
+    "property",
+    "public",
+    "return",
+    "sizeof",
+    "static",
+    "stock",
+    "struct",
+    "switch",
+    "this",
+    "true",
+    "typedef",
+    "typeset",
+    "union",
+    "view_as",
+    "void",
+    "while",
+];
+
+fn check_identifier(name: &str) -> Result<(), RenameError> {
+    let mut chars = name.chars();
+    let is_identifier = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !is_identifier || KEYWORDS.contains(&name) {
+        return Err(RenameError(format!("`{name}` is not a valid identifier")));
+    }
+
+    Ok(())
+}
+
+/// Describe the definitions which would be confused with `def` once renamed to `new_name`.
+fn conflicts(
+    sema: &Semantics<RootDatabase>,
+    def: &DefResolution,
+    refs: &[FileRange],
+    new_name: &str,
+) -> Vec<String> {
+    let db = sema.db;
+    let mut res = Vec::new();
+    if def
+        .name(db)
+        .is_some_and(|name| name.to_string() == new_name)
+    {
+        return res;
+    }
+    let namespaced = is_namespaced(db, def);
+    let mut push = |conflict: String| {
+        if !res.contains(&conflict) {
+            res.push(conflict);
+        }
+    };
+
+    for file_id in refs.iter().map(|frange| frange.file_id).unique() {
+        if sema
+            .preprocess_file(file_id)
+            .macros()
+            .contains_key(new_name)
+        {
+            push(format!("the macro `{new_name}`"));
+        }
+        // Locals do not conflict with the global symbols, they shadow them. The members are
+        // accessed through their enum struct or methodmap.
+        if matches!(def, DefResolution::Local(_)) || namespaced {
+            continue;
+        }
+        for other in sema.defs_in_scope(file_id) {
+            if &other != def
+                && other
+                    .name(db)
+                    .is_some_and(|name| name.to_string() == new_name)
+            {
+                push(format!("the {} `{new_name}`", kind_name(&other)));
+            }
+        }
+    }
+
+    // Locals of the same name in the functions using the symbol would capture its references,
+    // and a renamed local would shadow the symbols of the same name used in its function.
+    if namespaced {
+        return res;
+    }
+    let mut functions = Vec::new();
+    for frange in refs {
+        let tree = sema.parse(frange.file_id);
+        let preprocessing_results = sema.preprocess_file(frange.file_id);
+        let source = preprocessing_results.preprocessed_text();
+        let Some(s_range) = preprocessing_results
+            .source_map()
+            .closest_s_range(frange.range)
+        else {
+            continue;
+        };
+        let Some(function) = tree
+            .root_node()
+            .descendant_for_byte_range(s_range.start().into(), s_range.end().into())
+            .and_then(|node| {
+                std::iter::successors(Some(node), |node| node.parent())
+                    .find(|node| TSKind::from(node) == TSKind::function_definition)
+            })
+        else {
+            continue;
+        };
+        if functions.contains(&(frange.file_id, function.id())) {
+            continue;
+        }
+        functions.push((frange.file_id, function.id()));
+
+        let mut cursor = function.walk();
+        'outer: loop {
+            let node = cursor.node();
+            if TSKind::from(node) == TSKind::identifier
+                && node.utf8_text(source.as_bytes()).ok() == Some(new_name)
+            {
+                match sema.find_def(frange.file_id, &node) {
+                    Some(other @ DefResolution::Local(_)) if &other != def => {
+                        push(format!("the local `{new_name}`"));
+                    }
+                    Some(other)
+                        if matches!(def, DefResolution::Local(_))
+                            && &other != def
+                            && !is_namespaced(db, &other) =>
+                    {
+                        push(format!("the {} `{new_name}`", kind_name(&other)));
+                    }
+                    _ => (),
+                }
+            }
+            if cursor.goto_first_child() {
+                continue;
+            }
+            loop {
+                if cursor.node() == function {
+                    break 'outer;
+                }
+                if cursor.goto_next_sibling() {
+                    break;
+                }
+                if !cursor.goto_parent() {
+                    break 'outer;
+                }
+            }
+        }
+    }
+
+    res
+}
+
+/// Whether a definition is a member of an enum struct or a methodmap, which is accessed through
+/// them.
+fn is_namespaced(db: &RootDatabase, def: &DefResolution) -> bool {
+    match def {
+        DefResolution::Field(_) | DefResolution::Property(_) | DefResolution::StructField(_) => {
+            true
+        }
+        DefResolution::Function(function) => !matches!(
+            function.kind(db),
+            FunctionType::Function | FunctionType::Constructor
+        ),
+        _ => false,
+    }
+}
+
+fn kind_name(def: &DefResolution) -> &'static str {
+    match def {
+        DefResolution::Function(_) => "function",
+        DefResolution::Macro(_) => "macro",
+        DefResolution::EnumStruct(_) => "enum struct",
+        DefResolution::Methodmap(_) => "methodmap",
+        DefResolution::Property(_) => "property",
+        DefResolution::Enum(_) => "enum",
+        DefResolution::Variant(_) => "enum variant",
+        DefResolution::Typedef(_) => "typedef",
+        DefResolution::Typeset(_) => "typeset",
+        DefResolution::Functag(_) => "functag",
+        DefResolution::Funcenum(_) => "funcenum",
+        DefResolution::Field(_) => "field",
+        DefResolution::Struct(_) => "struct",
+        DefResolution::StructField(_) => "struct field",
+        DefResolution::Global(_) => "global variable",
+        DefResolution::Local(_) => "local",
+        DefResolution::File(_) => "file",
+    }
 }
@@ -0,0 +1,325 @@
+use std::{fmt, ops::Range};
+
+use base_db::FileRange;
+use fxhash::FxHashMap;
+use hir_def::DefDatabase;
+use ide_db::{RootDatabase, SourceChange, TextEdit};
+use preprocessor::db::PreprocDatabase;
+use smol_str::SmolStr;
+use syntax::{utils::ts_range_to_text_range, TSKind};
+use vfs::FileId;
+
+/// Separator of the search pattern and of the replacement template of a query.
+const REPLACEMENT_SEPARATOR: &str = "==>>";
+
+/// Prefix of the identifiers the placeholders of a pattern are parsed as.
+const PLACEHOLDER_PREFIX: &str = "__ssr_";
+
+/// Error in a structural search query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SsrError(String);
+
+impl fmt::Display for SsrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for SsrError {}
+
+/// Result of a structural search.
+#[derive(Debug, Default, Clone)]
+pub struct SsrMatches {
+    /// Ranges of the code matching the pattern.
+    pub matches: Vec<FileRange>,
+    /// Edits replacing the matches, empty if the query has no replacement.
+    pub source_change: SourceChange,
+}
+
+/// Search pattern, parsed inside a dummy function.
+struct Pattern {
+    source: String,
+    tree: tree_sitter::Tree,
+    range: Range<usize>,
+}
+
+impl Pattern {
+    fn parse(text: &str) -> Result<Self, SsrError> {
+        let text = text.trim().trim_end_matches(';').trim_end();
+        if text.is_empty() {
+            return Err(SsrError("the search pattern is empty".to_string()));
+        }
+        let text =
+            replace_placeholders(text, |name| Ok(format!("{}{}", PLACEHOLDER_PREFIX, name)))?;
+        let prefix = "void __ssr() {\n";
+        let source = format!("{}{};\n}}\n", prefix, text);
+        let mut parser = tree_sitter::Parser::new();
+        parser
+            .set_language(&tree_sitter_sourcepawn::language())
+            .map_err(|_| SsrError("failed to load the parser".to_string()))?;
+        let tree = parser
+            .parse(source.as_bytes(), None)
+            .ok_or_else(|| SsrError("failed to parse the search pattern".to_string()))?;
+        let range = prefix.len()..prefix.len() + text.len();
+        let pattern = Self {
+            source,
+            tree,
+            range,
+        };
+        match pattern.root() {
+            Some(node) if !node.has_error() => Ok(pattern),
+            _ => Err(SsrError(
+                "the search pattern is not a valid expression".to_string(),
+            )),
+        }
+    }
+
+    /// Node of the pattern, e.g the call expression of `Foo($a)`.
+    fn root(&self) -> Option<tree_sitter::Node> {
+        let node = self
+            .tree
+            .root_node()
+            .named_descendant_for_byte_range(self.range.start, self.range.end)?;
+        (node.byte_range() == self.range).then_some(node)
+    }
+
+    fn placeholders(&self) -> Vec<SmolStr> {
+        let mut res = Vec::new();
+        let Some(root) = self.root() else {
+            return res;
+        };
+        let mut cursor = root.walk();
+        'outer: loop {
+            if let Some(name) = placeholder_name(&cursor.node(), &self.source) {
+                res.push(name);
+            }
+            if cursor.goto_first_child() {
+                continue;
+            }
+            while !cursor.goto_next_sibling() {
+                if !cursor.goto_parent() || cursor.node() == root {
+                    break 'outer;
+                }
+            }
+        }
+
+        res
+    }
+}
+
+// Feature: Structural Search and Replace
+//
+// Searches the code of the workspace matching a pattern, and optionally replaces it. The query
+// is made of a pattern and of a replacement, separated by `==>>`. The placeholders of the form
+// `$name` match any expression, and are substituted by the matched code in the replacement:
+//
+// ```
+// GetClientOfUserId(GetClientUserId($c)) ==>> $c
+// ```
+//
+// A placeholder used several times in the pattern only matches the same code. Without a
+// replacement, the matches are only listed. The code expanded from macros is never matched.
+//
+// |===
+// | Editor  | Action Name
+//
+// | VS Code | **SM: Structural Search Replace**
+// |===
+pub(crate) fn structural_search_replace(
+    db: &RootDatabase,
+    query: &str,
+    file_ids: &[FileId],
+) -> Result<SsrMatches, SsrError> {
+    let (search, replacement) = match query.split_once(REPLACEMENT_SEPARATOR) {
+        Some((search, replacement)) => (search, Some(replacement.trim())),
+        None => (query, None),
+    };
+    let pattern = Pattern::parse(search)?;
+    let placeholders = pattern.placeholders();
+    if let Some(replacement) = replacement {
+        replace_placeholders(replacement, |name| {
+            if placeholders.iter().any(|it| it == name) {
+                Ok(String::new())
+            } else {
+                Err(SsrError(format!(
+                    "the placeholder `${}` of the replacement is not in the search pattern",
+                    name
+                )))
+            }
+        })?;
+    }
+    let pattern_root = pattern
+        .root()
+        .ok_or_else(|| SsrError("the search pattern is not a valid expression".to_string()))?;
+
+    let mut res = SsrMatches::default();
+    for &file_id in file_ids {
+        let tree = db.parse(file_id);
+        let source = db.preprocessed_text(file_id);
+        let file_text = db.file_text(file_id);
+        let preprocessing_results = db.preprocess_file(file_id);
+        let source_map = preprocessing_results.source_map();
+        // Map a range of the matched code to the file, if it was not expanded from a macro.
+        let u_range = |node: &tree_sitter::Node| {
+            let s_range = ts_range_to_text_range(&node.range());
+            let u_range = source_map.closest_u_range(s_range)?;
+            (file_text.get(Range::<usize>::from(u_range))
+                == source.get(Range::<usize>::from(s_range)))
+            .then_some(u_range)
+        };
+
+        let mut cursor = tree.root_node().walk();
+        'outer: loop {
+            let node = cursor.node();
+            let mut bindings = FxHashMap::default();
+            let matched = match_node(
+                &pattern_root,
+                &pattern.source,
+                &node,
+                &source,
+                &mut bindings,
+            );
+            if let Some(range) = matched.then(|| u_range(&node)).flatten() {
+                let edit = match replacement {
+                    Some(replacement) => replace_placeholders(replacement, |name| {
+                        bindings
+                            .get(name)
+                            .and_then(&u_range)
+                            .map(|range| file_text[range].to_string())
+                            .ok_or_else(|| SsrError(format!("failed to substitute `${}`", name)))
+                    })
+                    .ok()
+                    .map(|text| Some(TextEdit::new(range, text))),
+                    None => Some(None),
+                };
+                // A placeholder bound to code expanded from a macro cannot be substituted.
+                if let Some(edit) = edit {
+                    res.matches.push(FileRange { file_id, range });
+                    if let Some(edit) = edit {
+                        res.source_change.insert(file_id, edit);
+                    }
+                }
+            } else if cursor.goto_first_child() {
+                continue;
+            }
+            // The matches do not overlap, the children of a match are not searched.
+            while !cursor.goto_next_sibling() {
+                if !cursor.goto_parent() {
+                    break 'outer;
+                }
+            }
+        }
+    }
+
+    Ok(res)
+}
+
+/// Match a node of the code against a node of the pattern, binding the placeholders of the
+/// pattern to the nodes they match.
+fn match_node<'tree>(
+    pattern: &tree_sitter::Node,
+    pattern_source: &str,
+    node: &tree_sitter::Node<'tree>,
+    source: &str,
+    bindings: &mut FxHashMap<SmolStr, tree_sitter::Node<'tree>>,
+) -> bool {
+    if let Some(name) = placeholder_name(pattern, pattern_source) {
+        if !node.is_named() || TSKind::from(node) == TSKind::comment {
+            return false;
+        }
+        return match bindings.get(&name) {
+            Some(bound) => source.get(bound.byte_range()) == source.get(node.byte_range()),
+            None => {
+                bindings.insert(name, *node);
+                true
+            }
+        };
+    }
+    if pattern.kind_id() != node.kind_id() {
+        return false;
+    }
+    if pattern.child_count() == 0 {
+        return pattern_source.get(pattern.byte_range()) == source.get(node.byte_range());
+    }
+    let mut pattern_cursor = pattern.walk();
+    let mut cursor = node.walk();
+    let mut pattern_children = pattern
+        .children(&mut pattern_cursor)
+        .filter(|child| !child.is_extra());
+    let mut children = node.children(&mut cursor).filter(|child| !child.is_extra());
+    loop {
+        match (pattern_children.next(), children.next()) {
+            (None, None) => return true,
+            (Some(pattern_child), Some(child)) => {
+                if !match_node(&pattern_child, pattern_source, &child, source, bindings) {
+                    return false;
+                }
+            }
+            _ => return false,
+        }
+    }
+}
+
+/// Name of the placeholder a node of the pattern is, if any.
+fn placeholder_name(node: &tree_sitter::Node, pattern_source: &str) -> Option<SmolStr> {
+    if TSKind::from(node) != TSKind::identifier {
+        return None;
+    }
+    pattern_source
+        .get(node.byte_range())?
+        .strip_prefix(PLACEHOLDER_PREFIX)
+        .map(SmolStr::from)
+}
+
+/// Replace the `$name` placeholders of a text.
+///
+/// The `$` of the string and character literals are not placeholders, e.g `"$%d"`.
+fn replace_placeholders(
+    text: &str,
+    mut f: impl FnMut(&str) -> Result<String, SsrError>,
+) -> Result<String, SsrError> {
+    let mut res = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(idx) = rest.find(['$', '"', '\'']) {
+        res.push_str(&rest[..idx]);
+        let quote = rest[idx..].chars().next().unwrap_or_default();
+        rest = &rest[idx + 1..];
+        if quote != '$' {
+            let len = literal_len(rest, quote);
+            res.push(quote);
+            res.push_str(&rest[..len]);
+            rest = &rest[len..];
+            continue;
+        }
+        let len = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+        if len == 0 {
+            return Err(SsrError(
+                "`$` must be followed by the name of a placeholder".to_string(),
+            ));
+        }
+        res.push_str(&f(&rest[..len])?);
+        rest = &rest[len..];
+    }
+    res.push_str(rest);
+
+    Ok(res)
+}
+
+/// Length of the rest of a literal after its opening quote, including its closing quote if it
+/// is terminated.
+fn literal_len(text: &str, quote: char) -> usize {
+    let mut chars = text.char_indices();
+    while let Some((idx, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            c if c == quote => return idx + 1,
+            _ => (),
+        }
+    }
+
+    text.len()
+}
@@ -9,7 +9,7 @@ use hir_def::db::{
 use ide_db::RootDatabase;
 use preprocessor::{
     db::{PreprocessFileInnerDataQuery, PreprocessFileInnerParamsQuery, PreprocessingParams},
-    Macro, PreprocessingResult,
+    Macro, PreprocessingCacheProvider, PreprocessingResult,
 };
 use profile::Bytes;
 use salsa::{
@@ -47,6 +47,7 @@ pub(crate) fn status(db: &RootDatabase, _file_id: Option<FileId>) -> String {
         "{}\n",
         collect_query(PreprocessFileInnerParamsQuery.in_db(db))
     );
+    format_to!(buf, "{}\n", db.preprocessing_cache().stats());
 
     format_to!(buf, "\nDebug info:\n");
     format_to!(
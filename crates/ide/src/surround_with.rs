@@ -0,0 +1,169 @@
+use base_db::{FileRange, SourceDatabase};
+use hir::Semantics;
+use ide_db::{Assist, AssistId, AssistKind, RootDatabase, SourceChange, TextEdit};
+use line_index::{TextRange, TextSize};
+use syntax::TSKind;
+use tree_sitter::Node;
+
+use crate::extract_variable::trim_range;
+
+// Feature: Surround With
+//
+// Offers to surround the statements of the selection with:
+//
+// - `#if defined DEBUG ... #endif`,
+// - an `if` statement,
+// - a `for` loop,
+// - a `do { } while (false);` block.
+//
+// When the client supports snippets, the condition and the bounds of the loop are placeholders
+// to fill in once the assist is applied.
+pub(crate) fn surround_with(db: &RootDatabase, frange: FileRange, snippets: bool) -> Vec<Assist> {
+    let sema = &Semantics::new(db);
+    let preprocessing_results = sema.preprocess_file(frange.file_id);
+    let source_map = preprocessing_results.source_map();
+    let tree = sema.parse(frange.file_id);
+    let text = db.file_text(frange.file_id);
+
+    let selection = trim_range(&text, frange.range);
+    if selection.is_empty() {
+        return Vec::new();
+    }
+    let start = usize::from(selection.start());
+    let end = usize::from(selection.end());
+    let line_start = text[..start].rfind('\n').map_or(0, |idx| idx + 1);
+    let line_end = text[end..].find('\n').map_or(text.len(), |idx| end + idx);
+    // The selected lines must only contain the selected statements.
+    if !text[line_start..start].trim().is_empty() || !text[end..line_end].trim().is_empty() {
+        return Vec::new();
+    }
+    let Some(s_range) = source_map.closest_s_range(selection) else {
+        return Vec::new();
+    };
+    let Some(container) = tree
+        .root_node()
+        .descendant_for_byte_range(s_range.start().into(), s_range.end().into())
+        .and_then(|node| container(&node, s_range))
+    else {
+        return Vec::new();
+    };
+
+    let indent = &text[line_start..start];
+    let unit = if text.contains("\n\t") { "\t" } else { "    " };
+    let lines = text[line_start..line_end].lines().map(|line| {
+        if snippets {
+            escape(line)
+        } else {
+            line.to_string()
+        }
+    });
+    let body = lines.clone().collect::<Vec<_>>().join("\n");
+    let indented_body = lines
+        .map(|line| {
+            if line.trim().is_empty() {
+                line
+            } else {
+                format!("{unit}{line}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let placeholder = |idx: u32, name: &str| {
+        if snippets {
+            format!("${{{idx}:{name}}}")
+        } else {
+            name.to_string()
+        }
+    };
+    let end_tabstop = if snippets { "$0" } else { "" };
+
+    let mut surroundings = vec![(
+        "surround_with_if_defined",
+        "#if defined DEBUG",
+        format!(
+            "{indent}#if defined {}\n{body}\n{indent}#endif{end_tabstop}",
+            placeholder(1, "DEBUG")
+        ),
+    )];
+    // The other statements are only allowed in the bodies of the functions.
+    if TSKind::from(container) == TSKind::block {
+        let i = placeholder(1, "i");
+        surroundings.extend([
+            (
+                "surround_with_if",
+                "if",
+                format!(
+                    "{indent}if ({})\n{indent}{{\n{indented_body}\n{indent}}}{end_tabstop}",
+                    placeholder(1, "condition")
+                ),
+            ),
+            (
+                "surround_with_for",
+                "for",
+                format!(
+                    "{indent}for (int {i} = 0; {i} < {}; {i}++)\n{indent}{{\n{indented_body}\n\
+                     {indent}}}{end_tabstop}",
+                    placeholder(2, "count")
+                ),
+            ),
+            (
+                "surround_with_do_while",
+                "do { } while (false);",
+                format!(
+                    "{indent}do\n{indent}{{\n{indented_body}\n{indent}}} while ({});{end_tabstop}",
+                    placeholder(1, "false")
+                ),
+            ),
+        ]);
+    }
+
+    surroundings
+        .into_iter()
+        .map(|(id, label, replacement)| {
+            let mut source_change = SourceChange {
+                is_snippet: snippets,
+                ..Default::default()
+            };
+            source_change.insert(
+                frange.file_id,
+                TextEdit::new(
+                    TextRange::new(
+                        TextSize::new(line_start as u32),
+                        TextSize::new(line_end as u32),
+                    ),
+                    replacement,
+                ),
+            );
+            Assist {
+                id: AssistId(id, AssistKind::RefactorRewrite),
+                label: format!("Surround with `{label}`"),
+                target: selection,
+                source_change,
+                trigger_rename: None,
+            }
+        })
+        .collect()
+}
+
+/// Block or file whose children start and end at the bounds of `range`.
+fn container<'tree>(node: &Node<'tree>, range: TextRange) -> Option<Node<'tree>> {
+    std::iter::successors(Some(*node), |node| node.parent())
+        .filter(|node| matches!(TSKind::from(node), TSKind::block | TSKind::source_file))
+        .find(|container| {
+            let mut children = container
+                .named_children(&mut container.walk())
+                .collect::<Vec<_>>();
+            children.retain(|child| {
+                child.start_byte() >= range.start().into() && child.end_byte() <= range.end().into()
+            });
+            children.first().map(|child| child.start_byte()) == Some(range.start().into())
+                && children.last().map(|child| child.end_byte()) == Some(range.end().into())
+        })
+}
+
+/// Escapes the characters of the text which have a meaning in a snippet.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('$', "\\$")
+        .replace('}', "\\}")
+}
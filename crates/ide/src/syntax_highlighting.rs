@@ -1,10 +1,19 @@
-use std::fmt::{self, Debug, Write};
+use std::{
+    fmt::{self, Debug, Write},
+    ops,
+};
 
-use hir::Semantics;
-use hir_def::resolver::{HasResolver, ValueNs};
+use base_db::Tree;
+use fxhash::FxHashMap;
+use hir::{DefResolution, Semantics};
+use hir_def::{
+    resolver::{HasResolver, Resolver, ValueNs},
+    DefDatabase, FunctionKind,
+};
 use ide_db::{RootDatabase, SymbolKind};
-use itertools::Itertools;
 use line_index::{TextRange, TextSize};
+use preprocessor::SourceMap;
+use smol_str::SmolStr;
 use sourcepawn_lexer::{Literal, SourcepawnLexer, TokenKind};
 use vfs::FileId;
 
@@ -73,15 +82,22 @@ pub enum HlTag {
 #[repr(u8)]
 pub enum HlMod {
     Macro,
+    /// Symbol marked by a `#pragma deprecated` directive or a `@deprecated` doc tag.
+    Deprecated,
+    /// Value which cannot change, e.g an enum variant or a `const` variable.
+    Readonly,
 }
 
 impl HlMod {
-    const ALL: &'static [HlMod; HlMod::Macro as usize + 1] = &[HlMod::Macro];
+    const ALL: &'static [HlMod; HlMod::Readonly as usize + 1] =
+        &[HlMod::Macro, HlMod::Deprecated, HlMod::Readonly];
 
     #[allow(unused)]
     fn as_str(self) -> &'static str {
         match self {
             HlMod::Macro => "macro",
+            HlMod::Deprecated => "deprecated",
+            HlMod::Readonly => "readonly",
         }
     }
 
@@ -90,6 +106,12 @@ impl HlMod {
     }
 }
 
+impl ops::BitOrAssign<HlMod> for HlMods {
+    fn bitor_assign(&mut self, rhs: HlMod) {
+        self.0 |= rhs.mask();
+    }
+}
+
 impl HlMods {
     pub fn is_empty(&self) -> bool {
         self.0 == 0
@@ -129,61 +151,181 @@ pub(crate) fn highlight(
     };
     let lexer = SourcepawnLexer::new(&source);
     let resolver = file_id.resolver(db);
+    let tree = sema.parse(file_id);
+    let preprocessing_results = sema.preprocess_file(file_id);
     /* Ideally, we would want to use the AST here and do some range adjustments to get the user visible ranges.
     This would allow us to get proper modifiers for the symbols such as declarations or references.
     However, the current implementation is much simpler and should be good enough for now.
+    Only the members, e.g `foo.bar`, which can't be resolved by name, are looked up in the AST.
      */
-    lexer
-        .filter(|symbol| range_to_highlight.intersect(symbol.range).is_some())
-        .flat_map(|symbol| match symbol.token_kind {
+    let mut prev_kind = None;
+    let mut resolved: FxHashMap<SmolStr, Option<Highlight>> = FxHashMap::default();
+    let mut res = Vec::new();
+    for symbol in lexer {
+        if symbol.range.start() >= range_to_highlight.end() {
+            // The tokens after the range cannot change the highlighting of the range.
+            break;
+        }
+        let is_member = matches!(prev_kind, Some(TokenKind::Dot));
+        if !matches!(
+            symbol.token_kind,
+            TokenKind::Comment(_) | TokenKind::Newline | TokenKind::LineContinuation
+        ) {
+            prev_kind = Some(symbol.token_kind);
+        }
+        if range_to_highlight.intersect(symbol.range).is_none() {
+            continue;
+        }
+        let highlight = match symbol.token_kind {
+            TokenKind::Identifier if is_member => {
+                let Some(highlight) = member_highlight(
+                    &sema,
+                    file_id,
+                    &tree,
+                    preprocessing_results.source_map(),
+                    symbol.range,
+                ) else {
+                    continue;
+                };
+                highlight
+            }
             TokenKind::Identifier => {
-                let kind = match resolver.resolve_ident(&symbol.text())? {
-                    ValueNs::MacroId(_) => SymbolKind::Macro,
-                    ValueNs::LocalId(_) => SymbolKind::Local,
-                    ValueNs::GlobalId(_) => SymbolKind::Global,
-                    ValueNs::FunctionId(_) => SymbolKind::Function,
-                    ValueNs::EnumStructId(_) => SymbolKind::EnumStruct,
-                    ValueNs::MethodmapId(_) => SymbolKind::Methodmap,
-                    ValueNs::EnumId(_) => SymbolKind::Enum,
-                    ValueNs::VariantId(_) => SymbolKind::Variant,
-                    ValueNs::TypedefId(_) => SymbolKind::Typedef,
-                    ValueNs::TypesetId(_) => SymbolKind::Typeset,
-                    ValueNs::FunctagId(_) => SymbolKind::Functag,
-                    ValueNs::FuncenumId(_) => SymbolKind::Funcenum,
-                    ValueNs::StructId(_) => SymbolKind::Struct,
+                // The file resolver does not depend on the position, cache its results by name.
+                let highlight = *resolved
+                    .entry(symbol.text())
+                    .or_insert_with_key(|name| ident_highlight(db, &resolver, name));
+                let Some(highlight) = highlight else {
+                    continue;
                 };
-                Some(HlRange {
-                    range: symbol.range,
-                    highlight: Highlight {
-                        tag: HlTag::Symbol(kind),
-                        mods: HlMods::default(),
-                    },
-                })
+                highlight
             }
-            TokenKind::True | TokenKind::False => Some(HlRange {
-                range: symbol.range,
-                highlight: Highlight::new(HlTag::BoolLiteral),
-            }),
-            TokenKind::Comment(_) => Some(HlRange {
-                range: symbol.range,
-                highlight: Highlight::new(HlTag::Comment),
-            }),
+            TokenKind::True | TokenKind::False => Highlight::new(HlTag::BoolLiteral),
+            TokenKind::Comment(_) => Highlight::new(HlTag::Comment),
             TokenKind::Literal(lit) => match lit {
-                Literal::StringLiteral | Literal::CharLiteral => None, // FIXME: We can handle this but it overrides escaped characters.
-                Literal::FloatLiteral => Some(HlRange {
-                    range: symbol.range,
-                    highlight: Highlight::new(HlTag::FloatLiteral),
-                }),
+                Literal::StringLiteral | Literal::CharLiteral => continue, // FIXME: We can handle this but it overrides escaped characters.
+                Literal::FloatLiteral => Highlight::new(HlTag::FloatLiteral),
                 Literal::IntegerLiteral
                 | Literal::BinaryLiteral
                 | Literal::HexLiteral
-                | Literal::OctodecimalLiteral => Some(HlRange {
-                    range: symbol.range,
-                    highlight: Highlight::new(HlTag::IntLiteral),
-                }),
+                | Literal::OctodecimalLiteral => Highlight::new(HlTag::IntLiteral),
             },
 
-            _ => None,
-        })
-        .collect_vec()
+            _ => continue,
+        };
+        res.push(HlRange {
+            range: symbol.range,
+            highlight,
+        });
+    }
+
+    res
+}
+
+fn ident_highlight(db: &RootDatabase, resolver: &Resolver, name: &str) -> Option<Highlight> {
+    let (kind, deprecated, readonly) = match resolver.resolve_ident(name)? {
+        ValueNs::MacroId(id) => (SymbolKind::Macro, db.macro_data(id.value).deprecated, false),
+        ValueNs::LocalId(_) => (SymbolKind::Local, false, false),
+        ValueNs::GlobalId(id) => (
+            SymbolKind::Global,
+            false,
+            db.global_data(id.value).is_const(),
+        ),
+        ValueNs::FunctionId(ids) => {
+            let data = db.function_data(ids.first()?.value);
+            let kind = match data.kind {
+                FunctionKind::Def => SymbolKind::Function,
+                FunctionKind::Forward => SymbolKind::Forward,
+                FunctionKind::Native => SymbolKind::Native,
+            };
+            (kind, data.deprecated, false)
+        }
+        ValueNs::EnumStructId(id) => (
+            SymbolKind::EnumStruct,
+            db.enum_struct_data(id.value).deprecated,
+            false,
+        ),
+        ValueNs::MethodmapId(id) => (
+            SymbolKind::Methodmap,
+            db.methodmap_data(id.value).deprecated,
+            false,
+        ),
+        ValueNs::EnumId(id) => (SymbolKind::Enum, db.enum_data(id.value).deprecated, false),
+        ValueNs::VariantId(id) => (
+            SymbolKind::Variant,
+            db.variant_data(id.value).deprecated,
+            true,
+        ),
+        ValueNs::TypedefId(id) => (
+            SymbolKind::Typedef,
+            db.typedef_data(id.value).deprecated,
+            false,
+        ),
+        ValueNs::TypesetId(id) => (
+            SymbolKind::Typeset,
+            db.typeset_data(id.value).deprecated,
+            false,
+        ),
+        ValueNs::FunctagId(id) => (
+            SymbolKind::Functag,
+            db.functag_data(id.value).deprecated,
+            false,
+        ),
+        ValueNs::FuncenumId(id) => (
+            SymbolKind::Funcenum,
+            db.funcenum_data(id.value).deprecated,
+            false,
+        ),
+        ValueNs::StructId(id) => (
+            SymbolKind::Struct,
+            db.struct_data(id.value).deprecated,
+            false,
+        ),
+    };
+
+    Some(highlight_with_mods(kind, deprecated, readonly))
+}
+
+fn highlight_with_mods(kind: SymbolKind, deprecated: bool, readonly: bool) -> Highlight {
+    let mut highlight = Highlight::from(kind);
+    if deprecated {
+        highlight.mods |= HlMod::Deprecated;
+    }
+    if readonly {
+        highlight.mods |= HlMod::Readonly;
+    }
+
+    highlight
+}
+
+/// Resolve the highlight of a member, e.g `bar` in `foo.bar`, from the AST.
+fn member_highlight(
+    sema: &Semantics<RootDatabase>,
+    file_id: FileId,
+    tree: &Tree,
+    source_map: &SourceMap,
+    u_range: TextRange,
+) -> Option<Highlight> {
+    let s_range = source_map.closest_s_range(u_range)?;
+    let node = tree.root_node().descendant_for_byte_range(
+        u32::from(s_range.start()) as usize,
+        u32::from(s_range.end()) as usize,
+    )?;
+    let db = sema.db;
+    let highlight = match sema.find_def(file_id, &node)? {
+        DefResolution::Field(it) => {
+            highlight_with_mods(SymbolKind::Field, it.is_deprecated(db), false)
+        }
+        DefResolution::StructField(it) => {
+            highlight_with_mods(SymbolKind::Field, it.is_deprecated(db), false)
+        }
+        DefResolution::Property(it) => {
+            highlight_with_mods(SymbolKind::Property, it.is_deprecated(db), false)
+        }
+        DefResolution::Function(it) => {
+            highlight_with_mods(it.kind(db).into(), it.is_deprecated(db), false)
+        }
+        _ => return None,
+    };
+
+    Some(highlight)
 }
@@ -0,0 +1,93 @@
+use hir_def::DefDatabase;
+use line_index::TextRange;
+use preprocessor::db::PreprocDatabase;
+use stdx::format_to;
+use vfs::FileId;
+
+use crate::RootDatabase;
+
+/// Maximum length of the text printed for a leaf node.
+const MAX_LEAF_TEXT_LEN: usize = 64;
+
+// Feature: Show Syntax Tree
+//
+// Shows the parse tree of the current file, or of the smallest node containing the selection.
+// The ranges are byte offsets in the preprocessed text of the file, which is what the parser
+// reads. It is mostly useful to report parser bugs and to write structural tools.
+//
+// |===
+// | Editor  | Action Name
+//
+// | VS Code | **sourcepawn-studio (debug command): Reveal document's syntax tree**
+// |===
+pub(crate) fn syntax_tree(db: &RootDatabase, file_id: FileId, range: Option<TextRange>) -> String {
+    let tree = db.parse(file_id);
+    let source = db.preprocessed_text(file_id);
+    let mut node = tree.root_node();
+    if let Some(s_range) = range.and_then(|u_range| {
+        db.preprocess_file(file_id)
+            .source_map()
+            .closest_s_range(u_range)
+    }) {
+        if let Some(it) = node.named_descendant_for_byte_range(
+            u32::from(s_range.start()) as usize,
+            u32::from(s_range.end()) as usize,
+        ) {
+            node = it;
+        }
+    }
+
+    let mut buf = String::new();
+    let mut cursor = node.walk();
+    let mut depth = 0;
+    'outer: loop {
+        let node = cursor.node();
+        if node.is_named() || node.is_missing() {
+            format_to!(buf, "{:indent$}", "", indent = depth * 2);
+            if let Some(field_name) = cursor.field_name() {
+                format_to!(buf, "{}: ", field_name);
+            }
+            if node.is_missing() {
+                buf.push_str("MISSING ");
+            }
+            format_to!(
+                buf,
+                "{}@{}..{}",
+                node.kind(),
+                node.start_byte(),
+                node.end_byte()
+            );
+            if node.named_child_count() == 0 {
+                if let Some(text) = source.get(node.byte_range()) {
+                    let mut text = text.to_string();
+                    if text.len() > MAX_LEAF_TEXT_LEN {
+                        let end = (0..=MAX_LEAF_TEXT_LEN)
+                            .rev()
+                            .find(|idx| text.is_char_boundary(*idx))
+                            .unwrap_or_default();
+                        text.truncate(end);
+                        text.push_str("...");
+                    }
+                    format_to!(buf, " {:?}", text);
+                }
+            }
+            buf.push('\n');
+        }
+        if cursor.goto_first_child() {
+            depth += 1;
+            continue;
+        }
+        loop {
+            if depth == 0 {
+                break 'outer;
+            }
+            if cursor.goto_next_sibling() {
+                break;
+            }
+            cursor.goto_parent();
+            depth -= 1;
+        }
+    }
+
+    buf
+}
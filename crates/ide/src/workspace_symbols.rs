@@ -0,0 +1,195 @@
+use base_db::{FileRange, SourceDatabase};
+use ide_db::{RootDatabase, SymbolId, SymbolKind, Symbols};
+use smol_str::SmolStr;
+use vfs::FileId;
+
+use crate::symbols::symbols;
+
+/// Symbol of the workspace matching a query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkspaceSymbol {
+    pub name: SmolStr,
+    pub kind: SymbolKind,
+    /// Name of the symbol containing this one, e.g the methodmap of a method.
+    pub container_name: Option<SmolStr>,
+    pub deprecated: bool,
+    /// Range of the name of the symbol, or of the whole symbol if it has no name.
+    pub range: FileRange,
+}
+
+/// Kinds of symbols a query can be restricted to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum QueryKind {
+    /// `#foo`
+    Types,
+    /// `f foo`
+    Functions,
+}
+
+impl QueryKind {
+    fn matches(self, kind: SymbolKind) -> bool {
+        match self {
+            QueryKind::Types => matches!(
+                kind,
+                SymbolKind::EnumStruct
+                    | SymbolKind::Methodmap
+                    | SymbolKind::Enum
+                    | SymbolKind::Struct
+                    | SymbolKind::Typedef
+                    | SymbolKind::Typeset
+                    | SymbolKind::Functag
+                    | SymbolKind::Funcenum
+            ),
+            QueryKind::Functions => matches!(
+                kind,
+                SymbolKind::Function
+                    | SymbolKind::Native
+                    | SymbolKind::Forward
+                    | SymbolKind::Method
+                    | SymbolKind::Constructor
+                    | SymbolKind::Destructor
+            ),
+        }
+    }
+}
+
+// Feature: Workspace Symbol
+//
+// Searches the symbols of every file of the workspace and of their includes. The query is
+// matched fuzzily, e.g `gcn` matches `GetClientName`, and the results are ranked from the exact
+// matches to the loosest ones.
+//
+// The query can be prefixed to only search some kinds of symbols:
+//
+// - `#` for the types, e.g `#Handle`.
+// - `f ` for the functions and methods, e.g `f OnPluginStart`.
+//
+// |===
+// | Editor  | Shortcut
+//
+// | VS Code | kbd:[Ctrl+T]
+// |===
+pub(crate) fn workspace_symbols(
+    db: &RootDatabase,
+    query: &str,
+    limit: usize,
+) -> Vec<WorkspaceSymbol> {
+    let (kind, pattern) = if let Some(pattern) = query.strip_prefix('#') {
+        (Some(QueryKind::Types), pattern)
+    } else if let Some(pattern) = query.strip_prefix("f ") {
+        (Some(QueryKind::Functions), pattern)
+    } else {
+        (None, query)
+    };
+    let pattern = pattern.trim();
+
+    let mut res = Vec::new();
+    for node in db.graph().nodes.iter() {
+        let Some(symbols) = symbols(db, node.file_id) else {
+            continue;
+        };
+        for idx in &symbols {
+            collect_matches(&symbols, idx, None, kind, pattern, node.file_id, &mut res);
+        }
+    }
+    res.sort_by(|(a_score, a), (b_score, b)| {
+        a_score
+            .cmp(b_score)
+            .then(a.name.len().cmp(&b.name.len()))
+            .then(a.name.cmp(&b.name))
+    });
+    if limit != 0 {
+        res.truncate(limit);
+    }
+
+    res.into_iter().map(|(_, symbol)| symbol).collect()
+}
+
+fn collect_matches(
+    symbols: &Symbols,
+    idx: &SymbolId,
+    container_name: Option<&SmolStr>,
+    kind: Option<QueryKind>,
+    pattern: &str,
+    file_id: FileId,
+    acc: &mut Vec<(u8, WorkspaceSymbol)>,
+) {
+    let symbol = &symbols[idx];
+    if symbol.kind() == SymbolKind::Local {
+        return;
+    }
+    let name = SmolStr::from(symbol.name());
+    if kind.is_none_or(|kind| kind.matches(symbol.kind())) {
+        if let Some(score) = fuzzy_score(&name, pattern) {
+            acc.push((
+                score,
+                WorkspaceSymbol {
+                    name: name.clone(),
+                    kind: symbol.kind(),
+                    container_name: container_name.cloned(),
+                    deprecated: symbol.deprecated(),
+                    range: FileRange {
+                        file_id,
+                        range: symbol.focus_range().unwrap_or(symbol.full_range()),
+                    },
+                },
+            ));
+        }
+    }
+    for child in symbol.children() {
+        collect_matches(symbols, child, Some(&name), kind, pattern, file_id, acc);
+    }
+}
+
+/// Score how well a name matches a pattern, the lower the better, [`None`] if it does not match.
+///
+/// From the best to the worst match: the same name, a prefix, the start of the words of the name
+/// (camel humps and underscores), and any subsequence, all case insensitive.
+fn fuzzy_score(name: &str, pattern: &str) -> Option<u8> {
+    if pattern.is_empty() {
+        return Some(3);
+    }
+    let name_lower = name.to_lowercase();
+    let pattern_lower = pattern.to_lowercase();
+    if name_lower == pattern_lower {
+        return Some(0);
+    }
+    if name_lower.starts_with(&pattern_lower) {
+        return Some(1);
+    }
+    if matches_word_starts(name, &pattern_lower) {
+        return Some(2);
+    }
+    let mut name_chars = name_lower.chars();
+    pattern_lower
+        .chars()
+        .all(|c| name_chars.any(|it| it == c))
+        .then_some(3)
+}
+
+/// Whether the characters of the pattern are found in order in the name, each of them either
+/// starting a word of the name or following the previous matched character.
+fn matches_word_starts(name: &str, pattern_lower: &str) -> bool {
+    let chars: Vec<char> = name.chars().collect();
+    let is_word_start = |idx: usize| {
+        idx == 0
+            || (chars[idx].is_uppercase() && !chars[idx - 1].is_uppercase())
+            || (chars[idx - 1] == '_' && chars[idx] != '_')
+    };
+    let mut pos = 0;
+    let mut prev_match: Option<usize> = None;
+    'pattern: for c in pattern_lower.chars() {
+        while pos < chars.len() {
+            let idx = pos;
+            pos += 1;
+            let is_next = prev_match.is_some_and(|prev| prev + 1 == idx);
+            if (is_next || is_word_start(idx)) && chars[idx].to_lowercase().eq(c.to_lowercase()) {
+                prev_match = Some(idx);
+                continue 'pattern;
+            }
+        }
+        return false;
+    }
+
+    true
+}
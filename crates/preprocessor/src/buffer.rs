@@ -2,34 +2,67 @@ use sourcepawn_lexer::{Symbol, TextRange, TextSize, TokenKind};
 
 use crate::offset::SourceMap;
 
-#[derive(Debug, Default)]
-pub struct PreprocessorBuffer {
+/// Buffer of the preprocessed text.
+///
+/// The line breaks of the input are kept as they are, `\r\n` or `\n`, so that the lines which
+/// are copied verbatim have the same offsets in the preprocessed text and in the input. The line
+/// breaks which do not come from a newline of the input, e.g the lines of a skipped directive,
+/// use the line ending of the first line of the input. Likewise, the whitespaces before the
+/// symbols of the input are copied from the input, so that the columns of the lines indented with
+/// tabs are preserved.
+#[derive(Debug)]
+pub struct PreprocessorBuffer<'a> {
+    input: &'a str,
     contents: String,
     offset: u32,
     source_map: SourceMap,
+    /// Line ending of the input, pushed by [`PreprocessorBuffer::push_new_line`].
+    line_ending: &'static str,
 }
 
-impl PreprocessorBuffer {
-    pub fn new(input_len: usize) -> Self {
+impl<'a> PreprocessorBuffer<'a> {
+    pub fn new(input: &'a str) -> Self {
         let mut contents = String::new();
-        contents.reserve(input_len);
+        contents.reserve(input.len());
+        let line_ending = match input.find('\n') {
+            Some(idx) if input[..idx].ends_with('\r') => "\r\n",
+            _ => "\n",
+        };
         Self {
+            input,
             contents,
-            ..Default::default()
+            offset: 0,
+            source_map: Default::default(),
+            line_ending,
         }
     }
 
     /// Push the whitespaces before the symbol based on the symbol's delta.
+    ///
+    /// The whitespaces are copied from the input when they precede the symbol there, and are
+    /// spaces otherwise, e.g for the symbols of an expanded macro.
     pub fn push_ws(&mut self, symbol: &Symbol) {
         let delta = symbol.delta.unsigned_abs();
         self.offset += delta;
-        self.contents.push_str(&" ".repeat(delta as usize));
+        let start = usize::from(symbol.range.start());
+        let ws = start
+            .checked_sub(delta as usize)
+            .filter(|_| !symbol.range.is_empty())
+            .and_then(|ws_start| self.input.get(ws_start..start))
+            .filter(|ws| {
+                ws.chars()
+                    .all(|c| matches!(c, ' ' | '\t' | '\x0b' | '\x0c'))
+            });
+        match ws {
+            Some(ws) => self.contents.push_str(ws),
+            None => self.contents.push_str(&" ".repeat(delta as usize)),
+        }
     }
 
-    // TODO: Test if \n or \r\n matters at all here.
+    /// Push a line break with the line ending of the input.
     pub fn push_new_line(&mut self) {
-        self.offset += 1;
-        self.contents.push('\n');
+        self.offset += self.line_ending.len() as u32;
+        self.contents.push_str(self.line_ending);
     }
 
     pub fn push_new_lines(&mut self, count: u32) {
@@ -44,8 +77,14 @@ impl PreprocessorBuffer {
     }
 
     pub fn push_symbol_no_delta(&mut self, symbol: &Symbol) {
+        // The lexer gives `\n` as the text of all the newlines, keep the `\r\n` of the input.
+        let text = if symbol.token_kind == TokenKind::Newline && symbol.range.len() == 2.into() {
+            "\r\n".into()
+        } else {
+            symbol.text()
+        };
         if symbol.token_kind != TokenKind::Eof {
-            self.contents.push_str(&symbol.text());
+            self.contents.push_str(&text);
         }
         if !symbol.range.is_empty() {
             // Symbols with empty ranges are expanded macros.
@@ -54,7 +93,7 @@ impl PreprocessorBuffer {
                 TextRange::at(TextSize::new(self.offset), symbol.range.len()),
             );
         }
-        self.offset += symbol.text().len() as u32;
+        self.offset += text.len() as u32;
     }
 
     pub fn push_str(&mut self, string: &str) {
@@ -82,14 +121,14 @@ impl PreprocessorBuffer {
     }
 }
 
-impl std::fmt::Display for PreprocessorBuffer {
+impl std::fmt::Display for PreprocessorBuffer<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.contents)
     }
 }
 
-impl From<PreprocessorBuffer> for String {
-    fn from(value: PreprocessorBuffer) -> Self {
+impl From<PreprocessorBuffer<'_>> for String {
+    fn from(value: PreprocessorBuffer<'_>) -> Self {
         value.to_string()
     }
 }
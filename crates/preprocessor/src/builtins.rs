@@ -0,0 +1,81 @@
+//! Macros defined by the compiler itself, e.g `__DATE__`.
+//!
+//! spcomp defines them from the time of the compilation and from the path of the compiled
+//! plugin. The preprocessor does not compile anything, the macros are therefore given plausible
+//! values, only computed when one of them is first looked up.
+
+use std::{
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use smol_str::SmolStr;
+use sourcepawn_lexer::{SourcepawnLexer, TokenKind};
+use vfs::FileId;
+
+use crate::{symbol::range_less_symbol, Macro, MacrosMap};
+
+/// Names of the builtin macros.
+pub(crate) const BUILTIN_MACRO_NAMES: [&str; 4] =
+    ["__DATE__", "__TIME__", "__BINARY_PATH__", "__BINARY_NAME__"];
+
+/// Index given to the builtin macros, which do not come from a `#define` of their file.
+pub(crate) const BUILTIN_MACRO_IDX: u32 = u32::MAX;
+
+/// Build the builtin macros, as seen by a file being preprocessed.
+///
+/// # Arguments
+/// * `file_id` - [`FileId`] of the file being preprocessed.
+pub(crate) fn builtin_macros(file_id: FileId) -> MacrosMap {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|it| it.as_secs())
+        .unwrap_or_default();
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    let secs_of_day = secs % 86_400;
+
+    let values = [
+        format!("\"{:02}/{:02}/{:04}\"", month, day, year),
+        format!(
+            "\"{:02}:{:02}:{:02}\"",
+            secs_of_day / 3600,
+            secs_of_day % 3600 / 60,
+            secs_of_day % 60
+        ),
+        "\"plugins/plugin.smx\"".to_string(),
+        "\"plugin.smx\"".to_string(),
+    ];
+
+    BUILTIN_MACRO_NAMES
+        .into_iter()
+        .zip(values)
+        .map(|(name, value)| {
+            let mut macro_ = Macro::default(file_id);
+            macro_.idx = BUILTIN_MACRO_IDX;
+            macro_.name_len = name.len();
+            macro_.body = SourcepawnLexer::new(&value)
+                .filter(|symbol| symbol.token_kind != TokenKind::Eof)
+                .map(|symbol| range_less_symbol(&symbol))
+                .collect();
+            (SmolStr::from(name), Arc::new(macro_))
+        })
+        .collect()
+}
+
+/// Convert a number of days since 1970-01-01 to a `(year, month, day)` date of the proleptic
+/// Gregorian calendar.
+///
+/// See [Howard Hinnant's algorithms](http://howardhinnant.github.io/date_algorithms.html#civil_from_days).
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    (year, month, day)
+}
@@ -1,4 +1,5 @@
-use sourcepawn_lexer::{TextRange, TextSize};
+use fxhash::FxHashMap;
+use sourcepawn_lexer::{PreprocDir, SourcepawnLexer, TextRange, TextSize, TokenKind};
 
 /// State of a preprocessor condition.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -13,6 +14,25 @@ pub enum ConditionState {
     Active,
 }
 
+/// An `#if` directive whose `#endif` has not been reached yet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OpenCondition {
+    /// Range of the `#if` directive.
+    pub range: TextRange,
+
+    /// Whether an `#else` directive was found for the condition.
+    pub has_else: bool,
+}
+
+impl OpenCondition {
+    pub fn new(range: TextRange) -> Self {
+        Self {
+            range,
+            has_else: false,
+        }
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct ConditionStack {
     stack: Vec<ConditionState>,
@@ -77,3 +97,154 @@ impl ConditionOffsetStack {
             .sort_unstable_by(|a, b| a.start().cmp(&b.start()).then(a.end().cmp(&b.end())));
     }
 }
+
+/// Whether the directive starts a branch of a condition, i.e is an `#if`, `#elseif` or `#else`.
+fn is_branch_directive(dir: PreprocDir) -> bool {
+    matches!(
+        dir,
+        PreprocDir::MIf | PreprocDir::MElseif | PreprocDir::MElse
+    )
+}
+
+/// Returns the index of the branch directive (`#if`, `#elseif` or `#else`) on the line of
+/// `offset`, among all the branch directives of `input`.
+///
+/// The index identifies a branch forced active by the user, and unlike an offset, it is not
+/// invalidated by edits inside of the branches.
+pub fn branch_directive_at(input: &str, offset: TextSize) -> Option<u32> {
+    let offset = usize::from(offset).min(input.len());
+    let line_start = input[..offset].rfind('\n').map_or(0, |it| it + 1);
+    let line_end = input[offset..]
+        .find('\n')
+        .map_or(input.len(), |it| offset + it);
+    let mut idx = 0;
+    for symbol in SourcepawnLexer::new(input) {
+        let start = usize::from(symbol.range.start());
+        if start > line_end {
+            break;
+        }
+        let TokenKind::PreprocDir(dir) = symbol.token_kind else {
+            continue;
+        };
+        if !is_branch_directive(dir) {
+            continue;
+        }
+        if start >= line_start {
+            return Some(idx);
+        }
+        idx += 1;
+    }
+
+    None
+}
+
+/// Returns the ranges of the branches of the conditions of `input`, including the conditions
+/// nested in inactive branches.
+///
+/// A branch starts at its `#if`, `#elseif` or `#else` directive, and ends at the end of the line
+/// before the next branch directive of its condition, or before its `#endif`.
+pub fn condition_branch_ranges(input: &str) -> Vec<TextRange> {
+    let mut res = Vec::new();
+    let mut stack: Vec<TextSize> = Vec::new();
+    let mut close_branch = |branch_start: TextSize, next: TextSize| {
+        let line_start = input[..usize::from(next)].rfind('\n').unwrap_or_default();
+        let end = TextSize::new(line_start as u32).max(branch_start);
+        res.push(TextRange::new(branch_start, end));
+    };
+    for symbol in SourcepawnLexer::new(input) {
+        let TokenKind::PreprocDir(dir) = symbol.token_kind else {
+            continue;
+        };
+        let start = symbol.range.start();
+        match dir {
+            PreprocDir::MIf => stack.push(start),
+            PreprocDir::MElseif | PreprocDir::MElse => {
+                if let Some(branch_start) = stack.last_mut() {
+                    close_branch(*branch_start, start);
+                    *branch_start = start;
+                }
+            }
+            PreprocDir::MEndif => {
+                if let Some(branch_start) = stack.pop() {
+                    close_branch(branch_start, start);
+                }
+            }
+            _ => (),
+        }
+    }
+    res.sort_by_key(|range| range.start());
+
+    res
+}
+
+/// A chain of branches, from an `#if` to its `#endif`.
+#[derive(Debug, Default)]
+struct BranchChain {
+    /// Offsets of the branch directives of the chain.
+    branches: Vec<TextSize>,
+
+    /// Offset of the branch directive forced active, if any.
+    forced: Option<TextSize>,
+}
+
+/// Resolve the branches forced active, given by their index (see [`branch_directive_at`]), into
+/// the value each branch directive of their chain must evaluate to, by offset.
+///
+/// The branches which contain a forced branch are forced active as well, so that it is reached.
+/// When several branches of a chain are forced, the first one wins.
+pub(crate) fn forced_conditions(input: &str, forced: &[u32]) -> FxHashMap<TextSize, bool> {
+    let mut res = FxHashMap::default();
+    if forced.is_empty() {
+        return res;
+    }
+    let mut stack: Vec<BranchChain> = Vec::new();
+    let mut close_chain = |stack: &mut Vec<BranchChain>| {
+        let Some(chain) = stack.pop() else {
+            return;
+        };
+        let Some(forced) = chain.forced else {
+            return;
+        };
+        res.extend(chain.branches.iter().map(|it| (*it, *it == forced)));
+        if let Some(parent) = stack.last_mut() {
+            if let Some(branch) = parent.branches.last() {
+                parent.forced.get_or_insert(*branch);
+            }
+        }
+    };
+    let mut idx = 0;
+    for symbol in SourcepawnLexer::new(input) {
+        let TokenKind::PreprocDir(dir) = symbol.token_kind else {
+            continue;
+        };
+        let start = symbol.range.start();
+        match dir {
+            PreprocDir::MIf => stack.push(BranchChain {
+                branches: vec![start],
+                forced: None,
+            }),
+            PreprocDir::MElseif | PreprocDir::MElse => {
+                if let Some(chain) = stack.last_mut() {
+                    chain.branches.push(start);
+                }
+            }
+            PreprocDir::MEndif => {
+                close_chain(&mut stack);
+                continue;
+            }
+            _ => continue,
+        }
+        if forced.contains(&idx) {
+            if let Some(chain) = stack.last_mut() {
+                chain.forced.get_or_insert(start);
+            }
+        }
+        idx += 1;
+    }
+    // Unterminated conditions.
+    while !stack.is_empty() {
+        close_chain(&mut stack);
+    }
+
+    res
+}
@@ -1,36 +1,58 @@
 use std::sync::Arc;
 
 use anyhow::bail;
-use base_db::{infer_include_ext, SourceDatabase};
-use fxhash::FxHashMap;
+use base_db::{infer_include_ext, resolve_include, IncludeKind, IncludeLimits, SourceDatabase};
+use fxhash::{FxHashMap, FxHashSet};
 use stdx::hashable_hash_map::{HashableHashMap, HashableHashSet};
-use vfs::{AnchoredPath, FileId};
+use vfs::FileId;
 
-use crate::{HMacrosMap, MacrosMap, PreprocessingResult, SourcepawnPreprocessor};
+use crate::{
+    configuration_macros,
+    incremental::{ResolvedInclude, ResolvedIncludes},
+    HMacrosMap, IncludeCycle, IncludeLimitExceeded, IncludeLimitKind, MacrosMap,
+    PreprocessingCacheProvider, PreprocessingResult, SourcepawnPreprocessor,
+};
 
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+/// Macro environment a file is preprocessed in.
+///
+/// The environment of a file only depends on the macros defined before it is included and on
+/// its own include subtree, which makes the preprocessing of a file deterministic regardless of
+/// the other files of the project.
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash)]
 pub struct PreprocessingParams {
+    /// Macros defined before the file is included.
     input_macros: HMacrosMap,
-    output_macros: HashableHashMap<FileId, HMacrosMap>,
+    /// Macros defined after each include directive of the file, in the order the preprocessor
+    /// reaches them. `None` if the include was not expanded, e.g because the file was already
+    /// included, as spcomp only includes a file once.
+    include_macros: Vec<Option<HMacrosMap>>,
+    /// Macros defined at the end of the file.
+    output_macros: HMacrosMap,
+    /// Includes which were not expanded because they exceed the include limits, by their index
+    /// among the includes of the file.
+    include_limit_errors: Vec<(usize, IncludeLimitExceeded)>,
     being_preprocessed: HashableHashSet<FileId>,
 }
 
 impl PreprocessingParams {
     pub fn shrink_to_fit(&mut self) {
         self.input_macros.shrink_to_fit();
+        self.include_macros.shrink_to_fit();
         self.output_macros.shrink_to_fit();
+        self.include_limit_errors.shrink_to_fit();
         self.being_preprocessed.shrink_to_fit();
     }
 }
 
 #[salsa::query_group(PreprocDatabaseStorage)]
-pub trait PreprocDatabase: SourceDatabase {
+pub trait PreprocDatabase: SourceDatabase + PreprocessingCacheProvider {
     #[salsa::invoke(_preprocess_file_params_query)]
     fn preprocess_file_inner_params(
         &self,
         file_id: FileId,
         macros: HMacrosMap,
         being_preprocessed: HashableHashSet<FileId>,
+        include_chain: Vec<FileId>,
     ) -> Arc<FxHashMap<FileId, Arc<PreprocessingParams>>>;
 
     #[salsa::invoke(_preprocess_file_data_query)]
@@ -43,6 +65,16 @@ pub trait PreprocDatabase: SourceDatabase {
     #[salsa::invoke(preprocess_file_query)]
     fn preprocess_file(&self, file_id: FileId) -> Arc<PreprocessingResult>;
 
+    /// Preprocess the file in one of the
+    /// [macro configurations](base_db::SourceDatabase::macro_configurations), i.e with the
+    /// macros of the configuration defined before the first line of its project.
+    #[salsa::invoke(preprocess_file_in_configuration_query)]
+    fn preprocess_file_in_configuration(
+        &self,
+        file_id: FileId,
+        configuration: usize,
+    ) -> Arc<PreprocessingResult>;
+
     #[salsa::invoke(preprocessed_text_query)]
     fn preprocessed_text(&self, file_id: FileId) -> Arc<str>;
 }
@@ -50,6 +82,31 @@ pub trait PreprocDatabase: SourceDatabase {
 pub(crate) fn preprocess_file_query(
     db: &dyn PreprocDatabase,
     file_id: FileId,
+) -> Arc<PreprocessingResult> {
+    preprocess_file_with_macros(db, file_id, HashableHashMap::default())
+}
+
+pub(crate) fn preprocess_file_in_configuration_query(
+    db: &dyn PreprocDatabase,
+    file_id: FileId,
+    configuration: usize,
+) -> Arc<PreprocessingResult> {
+    let Some(definitions) = db.macro_configurations().get(configuration).cloned() else {
+        return db.preprocess_file(file_id);
+    };
+    let root_file_id = db
+        .projet_subgraph(file_id)
+        .map_or(file_id, |subgraph| subgraph.root.file_id);
+    let macros = configuration_macros(root_file_id, &definitions);
+
+    preprocess_file_with_macros(db, file_id, macros.into())
+}
+
+/// Preprocess the file with `macros` defined before the first line of its project.
+fn preprocess_file_with_macros(
+    db: &dyn PreprocDatabase,
+    file_id: FileId,
+    macros: HMacrosMap,
 ) -> Arc<PreprocessingResult> {
     let Some(subgraph) = db.projet_subgraph(file_id) else {
         log::warn!("No subgraph found for file_id: {}", file_id);
@@ -58,8 +115,9 @@ pub(crate) fn preprocess_file_query(
     let root_file_id = subgraph.root.file_id;
     let res = db.preprocess_file_inner_params(
         root_file_id,
-        HashableHashMap::default(),
+        macros,
         HashableHashSet::default(),
+        Vec::new(),
     );
     let Some(params) = res.get(&file_id) else {
         log::warn!("No preprocessing params found for file_id: {}", file_id);
@@ -80,38 +138,46 @@ pub(crate) fn _preprocess_file_params_query(
     file_id: FileId,
     macros: HMacrosMap,
     mut being_preprocessed: HashableHashSet<FileId>,
+    mut include_chain: Vec<FileId>,
 ) -> Arc<FxHashMap<FileId, Arc<PreprocessingParams>>> {
     being_preprocessed.insert(file_id);
+    include_chain.push(file_id);
+    let limits = db.include_limits();
     let text = db.file_text(file_id);
     let mut results: FxHashMap<FileId, Arc<PreprocessingParams>> = FxHashMap::default();
     let input_macros = macros.clone();
     let mut being_preprocessed = being_preprocessed.clone();
-    let mut output_macros: HashableHashMap<FileId, HMacrosMap> = HashableHashMap::default();
+    let mut include_macros: Vec<Option<HMacrosMap>> = Vec::new();
+    let mut include_limit_errors = Vec::new();
 
     let mut extend_macros =
         |macros: &mut MacrosMap, mut path: String, file_id: FileId, quoted: bool| {
-            let mut inc_file_id = None;
+            // Every include gets an entry, so that they can be matched by their index when
+            // the file is preprocessed again.
+            include_macros.push(None);
             infer_include_ext(&mut path);
-            if quoted {
-                inc_file_id = db.resolve_path(AnchoredPath::new(file_id, &path));
-                if inc_file_id.is_none() {
-                    // Hack to try and resolve files in include folder.
-                    let path_with_include = format!("include/{}", path);
-                    inc_file_id = db.resolve_path(AnchoredPath::new(file_id, &path_with_include));
-                }
-            };
-            if inc_file_id.is_none() {
-                inc_file_id = db.resolve_path_relative_to_roots(&path);
-            }
-            let inc_file_id = inc_file_id.ok_or_else(|| anyhow::anyhow!("Include not found"))?;
+            let inc_file_id = resolve_include(db, file_id, &path, include_kind(quoted))
+                .ok_or_else(|| anyhow::anyhow!("Include not found"))?;
             if being_preprocessed.contains(&inc_file_id) {
                 // Avoid cyclic deps
                 return Ok(());
             }
+            if let Some(kind) =
+                include_limit_exceeded(limits, include_chain.len(), being_preprocessed.len())
+            {
+                let mut chain = include_chain.clone();
+                chain.push(inc_file_id);
+                include_limit_errors.push((
+                    include_macros.len() - 1,
+                    IncludeLimitExceeded { kind, chain },
+                ));
+                return Ok(());
+            }
             let res = db.preprocess_file_inner_params(
                 inc_file_id,
                 macros.clone().into(),
                 being_preprocessed.clone(),
+                include_chain.clone(),
             );
             results.extend(res.as_ref().clone());
             being_preprocessed.extend(res[&inc_file_id].being_preprocessed.clone());
@@ -119,26 +185,31 @@ pub(crate) fn _preprocess_file_params_query(
             let Some(params) = res.as_ref().get(&inc_file_id) else {
                 bail!("No preprocessing params found for file_id: {}", inc_file_id);
             };
-            output_macros.extend(params.output_macros.clone());
-            macros.extend(
-                params
-                    .output_macros
-                    .get(&inc_file_id)
-                    .map(|m| m.to_map().clone())
-                    .unwrap_or_default(),
-            );
+            // The included file starts from the current macros, its output therefore also
+            // accounts for the macros it undefined.
+            *macros = params.output_macros.to_map();
+            if let Some(last) = include_macros.last_mut() {
+                *last = Some(params.output_macros.clone());
+            }
 
             Ok(())
         };
 
+    let cancellation_check = || db.unwind_if_cancelled();
     let mut preprocessor = SourcepawnPreprocessor::new(file_id, &text, &mut extend_macros);
+    preprocessor.set_cancellation_check(&cancellation_check);
     preprocessor.set_macros(macros.to_map());
+    preprocessor.set_implicit_includes(db.implicit_includes().to_vec());
+    if let Some(forced_branches) = db.forced_branches().get(&file_id) {
+        preprocessor.set_forced_branches(forced_branches);
+    }
     let res = preprocessor.preprocess_input();
 
-    output_macros.insert(file_id, res.macros().clone().into());
     let mut preprocessing_params = PreprocessingParams {
         input_macros,
-        output_macros,
+        include_macros,
+        output_macros: res.macros().clone().into(),
+        include_limit_errors,
         being_preprocessed,
     };
     preprocessing_params.shrink_to_fit();
@@ -154,37 +225,240 @@ pub(crate) fn _preprocess_file_data_query(
     params: Arc<PreprocessingParams>,
 ) -> Arc<PreprocessingResult> {
     let text = db.file_text(file_id);
+    let forced_branches = db.forced_branches().get(&file_id).cloned();
+    // The cache does not know about the forced branches, bypass it for the files which have some.
+    if forced_branches.is_none() {
+        if let Some(res) = db
+            .preprocessing_cache()
+            .reuse(file_id, &text, &params, |includes| {
+                resolves_identically(db, includes)
+            })
+        {
+            return res;
+        }
+    }
+    let mut includes = ResolvedIncludes {
+        implicit_includes: db.implicit_includes(),
+        includes: Vec::new(),
+    };
+    let mut include_idx = 0;
     let mut extend_macros =
         |macros: &mut MacrosMap, mut path: String, file_id: FileId, quoted: bool| {
-            let mut inc_file_id = None;
+            let idx = include_idx;
+            include_idx += 1;
             infer_include_ext(&mut path);
-            if quoted {
-                inc_file_id = db.resolve_path(AnchoredPath::new(file_id, &path));
-                // FIXME: Investigate why uncommenting this causes upstream macros to not be found.
-                // if inc_file_id.is_none() {
-                //     // Hack to try and resolve files in include folder.
-                //     let path_with_include = format!("include/{}", path);
-                //     inc_file_id = db.resolve_path(AnchoredPath::new(file_id, &path_with_include));
-                // }
-            };
-            if inc_file_id.is_none() {
-                inc_file_id = db.resolve_path_relative_to_roots(&path);
+            if let Some((_, exceeded)) = params
+                .include_limit_errors
+                .iter()
+                .find(|(limited_idx, _)| *limited_idx == idx)
+            {
+                includes.includes.push(ResolvedInclude {
+                    file_id,
+                    path,
+                    quoted,
+                    inc_file_id: None,
+                });
+                return Err(exceeded.clone().into());
+            }
+            let include_macros = params.include_macros.get(idx).cloned().flatten();
+            let inc_file_id = resolve_include(db, file_id, &path, include_kind(quoted))
+                .ok_or_else(|| anyhow::anyhow!("Include not found"))?;
+            includes.includes.push(ResolvedInclude {
+                file_id,
+                path,
+                quoted,
+                inc_file_id: Some(inc_file_id),
+            });
+            if let Some(cycle) = include_cycle(db, file_id, inc_file_id, macros) {
+                return Err(IncludeCycle(cycle).into());
+            }
+            if let Some(include_macros) = include_macros {
+                *macros = include_macros.to_map();
             }
-            let inc_file_id = inc_file_id.ok_or_else(|| anyhow::anyhow!("Include not found"))?;
-            macros.extend(
-                params
-                    .as_ref()
-                    .output_macros
-                    .get(&inc_file_id)
-                    .map(|m| m.to_map().clone())
-                    .unwrap_or_default(),
-            );
 
             Ok(())
         };
 
+    let cancellation_check = || db.unwind_if_cancelled();
     let mut preprocessor = SourcepawnPreprocessor::new(file_id, &text, &mut extend_macros);
+    preprocessor.set_cancellation_check(&cancellation_check);
     preprocessor.set_macros(params.input_macros.to_map());
+    preprocessor.set_implicit_includes(db.implicit_includes().to_vec());
+    if let Some(forced_branches) = &forced_branches {
+        preprocessor.set_forced_branches(forced_branches);
+    }
+    let res: Arc<PreprocessingResult> = preprocessor.preprocess_input().into();
+    if forced_branches.is_none() {
+        db.preprocessing_cache().insert(
+            file_id,
+            text.clone(),
+            params.clone(),
+            includes.into(),
+            res.clone(),
+        );
+    }
+
+    res
+}
+
+/// Whether the `includes` of a cached result resolve to the same files, which reads the inputs
+/// the include callback of [`_preprocess_file_data_query`] would read for them.
+///
+/// The includes of a file included back by the included file are not checked, as the cycle they
+/// may close depends on the macros defined at the include, which are not cached.
+fn resolves_identically(db: &dyn PreprocDatabase, includes: &ResolvedIncludes) -> bool {
+    if db.implicit_includes() != includes.implicit_includes {
+        return false;
+    }
+
+    includes.includes.iter().all(|include| {
+        // The includes exceeding the limits are not resolved, and are part of the parameters.
+        let Some(inc_file_id) = include.inc_file_id else {
+            return true;
+        };
+        resolve_include(
+            db,
+            include.file_id,
+            &include.path,
+            include_kind(include.quoted),
+        ) == Some(inc_file_id)
+            && !includes_file(db, inc_file_id, include.file_id)
+    })
+}
+
+/// Returns the limit exceeded by an include at depth `depth`, when `files_count` files are
+/// already included by the project, if any.
+fn include_limit_exceeded(
+    limits: IncludeLimits,
+    depth: usize,
+    files_count: usize,
+) -> Option<IncludeLimitKind> {
+    if limits.depth != 0 && depth > limits.depth {
+        return Some(IncludeLimitKind::Depth(limits.depth));
+    }
+    if limits.files != 0 && files_count >= limits.files {
+        return Some(IncludeLimitKind::Files(limits.files));
+    }
+
+    None
+}
+
+fn include_kind(quoted: bool) -> IncludeKind {
+    if quoted {
+        IncludeKind::Quotes
+    } else {
+        IncludeKind::Chevrons
+    }
+}
+
+/// Find the include cycle closed by the include of `inc_file_id` in `file_id`, with `macros`
+/// defined at the include.
+///
+/// The includes of the project are walked in the order of the preprocessor, so that only the
+/// include which closes the cycle is reported. Files with an include guard are not reported, see
+/// [`is_guarded`].
+///
+/// Returns the files of the cycle, starting and ending with `inc_file_id`.
+fn include_cycle(
+    db: &dyn PreprocDatabase,
+    file_id: FileId,
+    inc_file_id: FileId,
+    macros: &MacrosMap,
+) -> Option<Vec<FileId>> {
+    if !includes_file(db, inc_file_id, file_id) || is_guarded(db, inc_file_id, file_id, macros) {
+        return None;
+    }
+    let root = db.projet_subgraph(file_id)?.root.file_id;
+    let mut visited = FxHashSet::default();
+    let mut stack = Vec::new();
+
+    find_back_edge(db, root, (file_id, inc_file_id), &mut visited, &mut stack)
+}
+
+/// Whether the preprocessor reaches an `#endinput` directive of `inc_file_id` before its includes
+/// leading back to `file_id`, when it is included with `macros` defined, e.g because of an
+/// include guard.
+fn is_guarded(
+    db: &dyn PreprocDatabase,
+    inc_file_id: FileId,
+    file_id: FileId,
+    macros: &MacrosMap,
+) -> bool {
+    let text = db.file_text(inc_file_id);
+    // Only the directives of the file itself matter, its includes are not expanded.
+    let mut include_file =
+        |_: &mut MacrosMap, _: String, _: FileId, _: bool| -> anyhow::Result<()> { Ok(()) };
+    let mut preprocessor = SourcepawnPreprocessor::new(inc_file_id, &text, &mut include_file);
+    preprocessor.set_macros(macros.clone());
+    preprocessor.set_implicit_includes(Vec::new());
+    let Some(endinput) = preprocessor.preprocess_input().endinput() else {
+        return false;
+    };
+
+    db.file_includes(inc_file_id)
+        .0
+        .iter()
+        .filter(|include| includes_file(db, include.file_id(), file_id))
+        .filter_map(|include| include.range())
+        .all(|range| range.start() > endinput)
+}
+
+/// Whether `file_id` includes `target`, directly or through other files.
+fn includes_file(db: &dyn PreprocDatabase, file_id: FileId, target: FileId) -> bool {
+    let mut visited = FxHashSet::default();
+    let mut stack = vec![file_id];
+    while let Some(file_id) = stack.pop() {
+        if file_id == target {
+            return true;
+        }
+        if !visited.insert(file_id) {
+            continue;
+        }
+        stack.extend(
+            db.file_includes(file_id)
+                .0
+                .iter()
+                .filter(|include| include.range().is_some())
+                .map(|include| include.file_id()),
+        );
+    }
+
+    false
+}
+
+fn find_back_edge(
+    db: &dyn PreprocDatabase,
+    file_id: FileId,
+    edge: (FileId, FileId),
+    visited: &mut FxHashSet<FileId>,
+    stack: &mut Vec<FileId>,
+) -> Option<Vec<FileId>> {
+    visited.insert(file_id);
+    stack.push(file_id);
+    // The implicit includes do not have a range, and are not part of cycles.
+    for include in db
+        .file_includes(file_id)
+        .0
+        .iter()
+        .filter(|include| include.range().is_some())
+    {
+        let target = include.file_id();
+        if let Some(idx) = stack.iter().position(|it| *it == target) {
+            if (file_id, target) == edge {
+                let mut cycle = stack[idx..].to_vec();
+                cycle.push(target);
+                return Some(cycle);
+            }
+            continue;
+        }
+        if visited.contains(&target) {
+            continue;
+        }
+        if let Some(cycle) = find_back_edge(db, target, edge, visited, stack) {
+            return Some(cycle);
+        }
+    }
+    stack.pop();
 
-    preprocessor.preprocess_input().into()
+    None
 }
@@ -0,0 +1,105 @@
+//! Detection of unknown preprocessor directives.
+//!
+//! The lexer only knows about the directives it supports, a misspelled directive such as
+//! `#inlcude` is therefore lexed as an unknown token, and `#ifdef` is lexed as `#if` followed
+//! by the identifier `def`. The name of the directive is read from the source text instead.
+
+/// Directives supported by the preprocessor.
+const DIRECTIVES: &[&str] = &[
+    "#assert",
+    "#define",
+    "#deprecate",
+    "#else",
+    "#elseif",
+    "#endif",
+    "#endinput",
+    "#file",
+    "#if",
+    "#include",
+    "#leaving",
+    "#optional_newdecls",
+    "#optional_semicolons",
+    "#pragma",
+    "#require_newdecls",
+    "#require_semicolons",
+    "#tryinclude",
+    "#undef",
+];
+
+/// Directives which are valid for spcomp, but ignored by the preprocessor.
+const IGNORED_DIRECTIVES: &[&str] = &["#endscript", "#error", "#line", "#warning"];
+
+/// Directives from other languages, and the SourcePawn construct they should be replaced with.
+const FOREIGN_DIRECTIVES: &[(&str, &str)] = &[
+    ("#ifdef", "#if defined"),
+    ("#ifndef", "#if !defined"),
+    ("#elif", "#elseif"),
+    ("#elifdef", "#elseif defined"),
+    ("#elifndef", "#elseif !defined"),
+    ("#import", "#include"),
+    ("#try_include", "#tryinclude"),
+];
+
+/// Maximum edit distance between an unknown directive and its suggestion.
+const MAX_DISTANCE: usize = 2;
+
+/// Name of the directive starting at `offset` in `input`, including the leading `#`.
+pub(crate) fn directive_name(input: &str, offset: usize) -> Option<&str> {
+    let rest = input.get(offset..)?.strip_prefix('#')?;
+    let len = rest
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .unwrap_or(rest.len());
+    if len == 0 {
+        return None;
+    }
+
+    Some(&input[offset..offset + len + 1])
+}
+
+/// Whether `name` is a directive known by spcomp.
+pub(crate) fn is_known_directive(name: &str) -> bool {
+    DIRECTIVES.contains(&name) || IGNORED_DIRECTIVES.contains(&name)
+}
+
+/// Whether `name` is a directive known by spcomp, but ignored by the preprocessor.
+pub(crate) fn is_ignored_directive(name: &str) -> bool {
+    IGNORED_DIRECTIVES.contains(&name)
+}
+
+/// Suggest the directive an unknown directive was meant to be.
+pub(crate) fn suggest_directive(name: &str) -> Option<&'static str> {
+    if let Some((_, replacement)) = FOREIGN_DIRECTIVES.iter().find(|(it, _)| *it == name) {
+        return Some(replacement);
+    }
+    DIRECTIVES
+        .iter()
+        .chain(IGNORED_DIRECTIVES)
+        .map(|it| (*it, edit_distance(name, it)))
+        .filter(|(_, distance)| *distance <= MAX_DISTANCE)
+        .min_by_key(|(_, distance)| *distance)
+        .map(|(it, _)| it)
+}
+
+/// Optimal string alignment distance between two strings, i.e the Levenshtein distance where
+/// the transposition of two adjacent characters counts as a single edit.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a = a.as_bytes();
+    let b = b.as_bytes();
+    let mut prev_prev = vec![0; b.len() + 1];
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut curr = vec![0; b.len() + 1];
+    for i in 1..=a.len() {
+        curr[0] = i;
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            curr[j] = (prev[j] + 1).min(curr[j - 1] + 1).min(prev[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                curr[j] = curr[j].min(prev_prev[j - 2] + 1);
+            }
+        }
+        std::mem::swap(&mut prev_prev, &mut prev);
+        std::mem::swap(&mut prev, &mut curr);
+    }
+
+    prev[b.len()]
+}
@@ -1,5 +1,8 @@
-use sourcepawn_lexer::TextRange;
+use sourcepawn_lexer::{TextRange, TextSize};
 use std::{error, fmt};
+use vfs::FileId;
+
+use crate::offset::shift_range;
 
 pub trait PreprocessorError {
     fn text(&self) -> &str;
@@ -66,6 +69,202 @@ impl PreprocessorError for MacroNotFoundError {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MacroArgumentCountError {
+    pub(super) macro_name: String,
+    pub(super) expected: usize,
+    pub(super) actual: usize,
+    pub(super) range: TextRange,
+}
+
+impl MacroArgumentCountError {
+    pub(super) fn new(
+        macro_name: String,
+        expected: usize,
+        actual: usize,
+        range: TextRange,
+    ) -> MacroArgumentCountError {
+        MacroArgumentCountError {
+            macro_name,
+            expected,
+            actual,
+            range,
+        }
+    }
+
+    /// Number of parameters of the macro.
+    pub fn expected(&self) -> usize {
+        self.expected
+    }
+
+    /// Number of arguments of the call.
+    pub fn actual(&self) -> usize {
+        self.actual
+    }
+}
+
+impl PreprocessorError for MacroArgumentCountError {
+    fn text(&self) -> &str {
+        &self.macro_name
+    }
+
+    fn range(&self) -> &TextRange {
+        &self.range
+    }
+}
+
+impl fmt::Display for MacroArgumentCountError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Macro {} expects {} arguments, found {} at {:?}",
+            self.macro_name, self.expected, self.actual, self.range
+        )
+    }
+}
+
+impl error::Error for MacroArgumentCountError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UseAfterUndefError {
+    pub(super) macro_name: String,
+    pub(super) undef_range: TextRange,
+    pub(super) range: TextRange,
+}
+
+impl UseAfterUndefError {
+    pub(super) fn new(
+        macro_name: String,
+        undef_range: TextRange,
+        range: TextRange,
+    ) -> UseAfterUndefError {
+        UseAfterUndefError {
+            macro_name,
+            undef_range,
+            range,
+        }
+    }
+
+    /// Range of the name of the macro in the `#undef` directive.
+    pub fn undef_range(&self) -> &TextRange {
+        &self.undef_range
+    }
+}
+
+impl PreprocessorError for UseAfterUndefError {
+    fn text(&self) -> &str {
+        &self.macro_name
+    }
+
+    fn range(&self) -> &TextRange {
+        &self.range
+    }
+}
+
+impl fmt::Display for UseAfterUndefError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Macro {} used at {:?} after being undefined at {:?}",
+            self.macro_name, self.range, self.undef_range
+        )
+    }
+}
+
+impl error::Error for UseAfterUndefError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssertionFailedError {
+    pub(super) expression: String,
+    pub(super) range: TextRange,
+}
+
+impl AssertionFailedError {
+    pub(super) fn new(expression: String, range: TextRange) -> AssertionFailedError {
+        AssertionFailedError { expression, range }
+    }
+}
+
+impl PreprocessorError for AssertionFailedError {
+    fn text(&self) -> &str {
+        &self.expression
+    }
+
+    fn range(&self) -> &TextRange {
+        &self.range
+    }
+}
+
+impl fmt::Display for AssertionFailedError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Assertion {} failed at {:?}",
+            self.expression, self.range
+        )
+    }
+}
+
+impl error::Error for AssertionFailedError {}
+
+/// Kind of a conditional directive which does not match the other conditional directives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnbalancedConditionKind {
+    ElseWithoutIf,
+    ElseifWithoutIf,
+    EndifWithoutIf,
+    ElseAfterElse,
+    ElseifAfterElse,
+    UnterminatedIf,
+}
+
+impl UnbalancedConditionKind {
+    pub fn message(self) -> &'static str {
+        match self {
+            UnbalancedConditionKind::ElseWithoutIf => "#else without matching #if",
+            UnbalancedConditionKind::ElseifWithoutIf => "#elseif without matching #if",
+            UnbalancedConditionKind::EndifWithoutIf => "#endif without matching #if",
+            UnbalancedConditionKind::ElseAfterElse => "#else after #else",
+            UnbalancedConditionKind::ElseifAfterElse => "#elseif after #else",
+            UnbalancedConditionKind::UnterminatedIf => "unterminated #if at end of file",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnbalancedConditionError {
+    pub(super) kind: UnbalancedConditionKind,
+    pub(super) range: TextRange,
+}
+
+impl UnbalancedConditionError {
+    pub(super) fn new(kind: UnbalancedConditionKind, range: TextRange) -> UnbalancedConditionError {
+        UnbalancedConditionError { kind, range }
+    }
+
+    pub fn kind(&self) -> UnbalancedConditionKind {
+        self.kind
+    }
+}
+
+impl PreprocessorError for UnbalancedConditionError {
+    fn text(&self) -> &str {
+        self.kind.message()
+    }
+
+    fn range(&self) -> &TextRange {
+        &self.range
+    }
+}
+
+impl fmt::Display for UnbalancedConditionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at {:?}", self.kind.message(), self.range)
+    }
+}
+
+impl error::Error for UnbalancedConditionError {}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct UnresolvedIncludeError {
     pub(super) include_text: String,
@@ -103,6 +302,150 @@ impl fmt::Display for UnresolvedIncludeError {
 
 impl error::Error for UnresolvedIncludeError {}
 
+/// Error returned by the include callback of the
+/// [`SourcepawnPreprocessor`](crate::SourcepawnPreprocessor) when an include closes a cycle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IncludeCycle(pub Vec<FileId>);
+
+impl fmt::Display for IncludeCycle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Circular include {:?}", self.0)
+    }
+}
+
+impl error::Error for IncludeCycle {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CircularIncludeError {
+    pub(super) include_text: String,
+    pub(super) cycle: Vec<FileId>,
+    pub(super) range: TextRange,
+}
+
+impl PreprocessorError for CircularIncludeError {
+    fn text(&self) -> &str {
+        &self.include_text
+    }
+
+    fn range(&self) -> &TextRange {
+        &self.range
+    }
+}
+
+impl CircularIncludeError {
+    pub(super) fn new(
+        include_text: String,
+        cycle: Vec<FileId>,
+        range: TextRange,
+    ) -> CircularIncludeError {
+        CircularIncludeError {
+            include_text,
+            cycle,
+            range,
+        }
+    }
+
+    /// Files of the cycle, starting and ending with the included file.
+    pub fn cycle(&self) -> &[FileId] {
+        &self.cycle
+    }
+}
+
+impl fmt::Display for CircularIncludeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Circular include {} at {:?}",
+            self.include_text, self.range
+        )
+    }
+}
+
+impl error::Error for CircularIncludeError {}
+
+/// Limit of the includes exceeded by an include, with the value of the limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IncludeLimitKind {
+    /// Maximum depth of the include chains.
+    Depth(usize),
+    /// Maximum number of files included by a project.
+    Files(usize),
+}
+
+/// Error returned by the include callback of the
+/// [`SourcepawnPreprocessor`](crate::SourcepawnPreprocessor) when an include exceeds the
+/// limits of the includes.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IncludeLimitExceeded {
+    pub kind: IncludeLimitKind,
+    /// Files of the include chain, starting with the root of the project and ending with the
+    /// included file.
+    pub chain: Vec<FileId>,
+}
+
+impl fmt::Display for IncludeLimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Include limit {:?} exceeded {:?}", self.kind, self.chain)
+    }
+}
+
+impl error::Error for IncludeLimitExceeded {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IncludeLimitError {
+    pub(super) include_text: String,
+    pub(super) kind: IncludeLimitKind,
+    pub(super) chain: Vec<FileId>,
+    pub(super) range: TextRange,
+}
+
+impl PreprocessorError for IncludeLimitError {
+    fn text(&self) -> &str {
+        &self.include_text
+    }
+
+    fn range(&self) -> &TextRange {
+        &self.range
+    }
+}
+
+impl IncludeLimitError {
+    pub(super) fn new(
+        include_text: String,
+        exceeded: IncludeLimitExceeded,
+        range: TextRange,
+    ) -> IncludeLimitError {
+        IncludeLimitError {
+            include_text,
+            kind: exceeded.kind,
+            chain: exceeded.chain,
+            range,
+        }
+    }
+
+    pub fn kind(&self) -> IncludeLimitKind {
+        self.kind
+    }
+
+    /// Files of the include chain, starting with the root of the project and ending with the
+    /// included file.
+    pub fn chain(&self) -> &[FileId] {
+        &self.chain
+    }
+}
+
+impl fmt::Display for IncludeLimitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Include limit {:?} exceeded by {} at {:?}",
+            self.kind, self.include_text, self.range
+        )
+    }
+}
+
+impl error::Error for IncludeLimitError {}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(super) struct ParseIntError {
     pub(super) text: String,
@@ -171,17 +514,188 @@ impl fmt::Display for EvaluationError {
 
 impl error::Error for EvaluationError {}
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownDirectiveError {
+    pub(super) name: String,
+    pub(super) suggestion: Option<&'static str>,
+    pub(super) range: TextRange,
+}
+
+impl PreprocessorError for UnknownDirectiveError {
+    fn text(&self) -> &str {
+        &self.name
+    }
+
+    fn range(&self) -> &TextRange {
+        &self.range
+    }
+}
+
+impl UnknownDirectiveError {
+    pub(super) fn new(
+        name: String,
+        suggestion: Option<&'static str>,
+        range: TextRange,
+    ) -> UnknownDirectiveError {
+        UnknownDirectiveError {
+            name,
+            suggestion,
+            range,
+        }
+    }
+
+    /// Directive the unknown directive was most likely meant to be, e.g `#include` for
+    /// `#inlcude` or `#if defined` for `#ifdef`.
+    pub fn suggestion(&self) -> Option<&'static str> {
+        self.suggestion
+    }
+}
+
+impl fmt::Display for UnknownDirectiveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Unknown directive {} at {:?}", self.name, self.range)
+    }
+}
+
+impl error::Error for UnknownDirectiveError {}
+
+/// Reason why a directive of an inactive region would be an error if the region was active.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InactiveDirectiveReason {
+    /// The directive is not supported, see [`UnknownDirectiveError`].
+    UnknownDirective {
+        name: String,
+        suggestion: Option<&'static str>,
+    },
+    /// A `#define` is not followed by the name of the macro.
+    MissingMacroName,
+    /// The parameters of a `#define` are not of the form `(%1, %2)`.
+    InvalidMacroParameters,
+    /// An `#include` or a `#tryinclude` is not followed by a path.
+    MissingIncludePath,
+    /// An `#undef` is not followed by the name of a macro.
+    MissingUndefName,
+}
+
+impl fmt::Display for InactiveDirectiveReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InactiveDirectiveReason::UnknownDirective { name, .. } => {
+                write!(f, "unknown preprocessor directive `{}`", name)
+            }
+            InactiveDirectiveReason::MissingMacroName => write!(f, "expected a macro name"),
+            InactiveDirectiveReason::InvalidMacroParameters => {
+                write!(f, "invalid macro parameters")
+            }
+            InactiveDirectiveReason::MissingIncludePath => write!(f, "expected an include path"),
+            InactiveDirectiveReason::MissingUndefName => {
+                write!(f, "expected the name of the macro to undefine")
+            }
+        }
+    }
+}
+
+/// Malformed directive in an inactive region of the file.
+///
+/// Directives of inactive regions are not preprocessed, only their structure is checked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InactiveDirectiveError {
+    pub(super) reason: InactiveDirectiveReason,
+    pub(super) range: TextRange,
+}
+
+impl InactiveDirectiveError {
+    pub(super) fn new(reason: InactiveDirectiveReason, range: TextRange) -> Self {
+        Self { reason, range }
+    }
+
+    pub fn reason(&self) -> &InactiveDirectiveReason {
+        &self.reason
+    }
+
+    pub fn range(&self) -> &TextRange {
+        &self.range
+    }
+}
+
+impl fmt::Display for InactiveDirectiveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} in inactive code at {:?}", self.reason, self.range)
+    }
+}
+
+impl error::Error for InactiveDirectiveError {}
+
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct PreprocessorErrors {
     pub macro_not_found_errors: Vec<MacroNotFoundError>,
+    pub macro_argument_count_errors: Vec<MacroArgumentCountError>,
+    pub use_after_undef_errors: Vec<UseAfterUndefError>,
     pub evaluation_errors: Vec<EvaluationError>,
+    pub assertion_failed_errors: Vec<AssertionFailedError>,
+    pub unbalanced_condition_errors: Vec<UnbalancedConditionError>,
     pub unresolved_include_errors: Vec<UnresolvedIncludeError>,
+    pub unknown_directive_errors: Vec<UnknownDirectiveError>,
+    pub circular_include_errors: Vec<CircularIncludeError>,
+    pub include_limit_errors: Vec<IncludeLimitError>,
+    pub inactive_directive_errors: Vec<InactiveDirectiveError>,
 }
 
 impl PreprocessorErrors {
     pub fn shrink_to_fit(&mut self) {
         self.macro_not_found_errors.shrink_to_fit();
+        self.macro_argument_count_errors.shrink_to_fit();
+        self.use_after_undef_errors.shrink_to_fit();
         self.evaluation_errors.shrink_to_fit();
+        self.assertion_failed_errors.shrink_to_fit();
+        self.unbalanced_condition_errors.shrink_to_fit();
         self.unresolved_include_errors.shrink_to_fit();
+        self.unknown_directive_errors.shrink_to_fit();
+        self.circular_include_errors.shrink_to_fit();
+        self.include_limit_errors.shrink_to_fit();
+        self.inactive_directive_errors.shrink_to_fit();
+    }
+
+    /// Shift the ranges of all the errors that start after `offset` by `delta`.
+    pub(crate) fn shift_after(&mut self, offset: TextSize, delta: i64) {
+        let shift = |range: &mut TextRange| {
+            if range.start() >= offset {
+                *range = shift_range(*range, delta);
+            }
+        };
+        self.macro_not_found_errors
+            .iter_mut()
+            .for_each(|err| shift(&mut err.range));
+        self.macro_argument_count_errors
+            .iter_mut()
+            .for_each(|err| shift(&mut err.range));
+        self.use_after_undef_errors.iter_mut().for_each(|err| {
+            shift(&mut err.undef_range);
+            shift(&mut err.range);
+        });
+        self.evaluation_errors
+            .iter_mut()
+            .for_each(|err| shift(&mut err.range));
+        self.assertion_failed_errors
+            .iter_mut()
+            .for_each(|err| shift(&mut err.range));
+        self.unbalanced_condition_errors
+            .iter_mut()
+            .for_each(|err| shift(&mut err.range));
+        self.unresolved_include_errors
+            .iter_mut()
+            .for_each(|err| shift(&mut err.range));
+        self.unknown_directive_errors
+            .iter_mut()
+            .for_each(|err| shift(&mut err.range));
+        self.circular_include_errors
+            .iter_mut()
+            .for_each(|err| shift(&mut err.range));
+        self.include_limit_errors
+            .iter_mut()
+            .for_each(|err| shift(&mut err.range));
+        self.inactive_directive_errors
+            .iter_mut()
+            .for_each(|err| shift(&mut err.range));
     }
 }
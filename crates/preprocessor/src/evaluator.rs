@@ -1,7 +1,8 @@
+use smol_str::SmolStr;
 use sourcepawn_lexer::{Comment, Literal, Operator, Symbol, TextRange, TokenKind};
 
 use super::{
-    errors::{EvaluationError, ExpansionError, MacroNotFoundError},
+    errors::{EvaluationError, ExpansionError, MacroArgumentCountError, MacroNotFoundError},
     macros::expand_identifier,
     preprocessor_operator::PreOperator,
 };
@@ -26,21 +27,64 @@ impl OperatorStack {
     }
 }
 
+/// Value of a preprocessor expression.
+///
+/// Integers are promoted to floats when they are mixed with floats, e.g in `SOME_VERSION >= 1.7`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Value {
+    Int(i32),
+    Float(f32),
+}
+
+impl Value {
+    pub(crate) fn to_bool(self) -> bool {
+        match self {
+            Value::Int(value) => value != 0,
+            Value::Float(value) => value != 0.0,
+        }
+    }
+
+    pub(crate) fn to_float(self) -> f32 {
+        match self {
+            Value::Int(value) => value as f32,
+            Value::Float(value) => value,
+        }
+    }
+}
+
+impl From<i32> for Value {
+    fn from(value: i32) -> Self {
+        Value::Int(value)
+    }
+}
+
+impl From<f32> for Value {
+    fn from(value: f32) -> Self {
+        Value::Float(value)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Int(value.into())
+    }
+}
+
 #[derive(Debug, Default)]
 pub(crate) struct OutputStack {
-    stack: Vec<i32>,
+    stack: Vec<Value>,
 }
 
 impl OutputStack {
-    pub fn push(&mut self, value: i32) {
-        self.stack.push(value);
+    pub fn push(&mut self, value: impl Into<Value>) {
+        self.stack.push(value.into());
     }
 
-    pub fn pop(&mut self) -> Option<i32> {
+    pub fn pop(&mut self) -> Option<Value> {
         self.stack.pop()
     }
 
-    pub fn top(&mut self) -> Option<&i32> {
+    pub fn top(&mut self) -> Option<&Value> {
         self.stack.last()
     }
 }
@@ -49,6 +93,7 @@ impl OutputStack {
 pub struct IfCondition<'a> {
     pub symbols: Vec<Symbol>,
     pub(super) macro_not_found_errors: Vec<MacroNotFoundError>,
+    pub(super) macro_argument_count_errors: Vec<MacroArgumentCountError>,
     macro_store: &'a mut MacroStore,
     expansion_stack: Vec<Symbol>,
     line_continuation_count: u32,
@@ -60,6 +105,7 @@ impl<'a> IfCondition<'a> {
         Self {
             symbols: vec![],
             macro_not_found_errors: vec![],
+            macro_argument_count_errors: vec![],
             macro_store,
             expansion_stack: vec![],
             line_continuation_count: Default::default(),
@@ -77,10 +123,13 @@ impl<'a> IfCondition<'a> {
             .clone() // TODO: This is horrible.
             .into_iter()
             .peekable();
-        while let Some(symbol) = if !self.expansion_stack.is_empty() {
-            self.expansion_stack.pop()
+        let mut has_unresolved_macros = false;
+        // Symbols popped from the expansion stack come from the body of a macro and are not
+        // part of the condition the user wrote.
+        while let Some((symbol, from_expansion)) = if !self.expansion_stack.is_empty() {
+            self.expansion_stack.pop().map(|symbol| (symbol, true))
         } else {
-            symbol_iter.next()
+            symbol_iter.next().map(|symbol| (symbol, false))
         } {
             match &symbol.token_kind {
                 TokenKind::LineContinuation | TokenKind::Newline => self.line_continuation_count += 1,
@@ -166,11 +215,39 @@ impl<'a> IfCondition<'a> {
                     Literal::IntegerLiteral
                     | Literal::BinaryLiteral
                     | Literal::HexLiteral
-                    | Literal::OctodecimalLiteral
-                    | Literal::CharLiteral => {
+                    | Literal::OctodecimalLiteral => {
                         output_stack.push(symbol.to_int().unwrap_or(0) as i32);
                         may_be_unary = false;
                     }
+                    Literal::CharLiteral => {
+                        let value = char_literal_value(&symbol.text()).ok_or_else(|| {
+                            EvaluationError::new(
+                                format!(
+                                    "Invalid character literal {}, expected a single character.",
+                                    symbol.text()
+                                ),
+                                symbol.range,
+                            )
+                        })?;
+                        output_stack.push(value);
+                        may_be_unary = false;
+                    }
+                    Literal::StringLiteral => {
+                        return Err(EvaluationError::new(
+                            "String literals cannot be compared in preprocessor conditions, use a character literal or an integer instead.".to_string(),
+                            symbol.range,
+                        ))
+                    }
+                    Literal::FloatLiteral => {
+                        let value = symbol.text().replace('_', "").parse::<f32>().map_err(|_| {
+                            EvaluationError::new(
+                                format!("Invalid float literal {}.", symbol.text()),
+                                symbol.range,
+                            )
+                        })?;
+                        output_stack.push(value);
+                        may_be_unary = false;
+                    }
                     _ => {
                         return Err(EvaluationError::new(
                             format!(
@@ -191,8 +268,11 @@ impl<'a> IfCondition<'a> {
                 }
                 _ => {
                     if looking_for_defined {
+                        self.macro_store.mark_referenced(symbol.text());
                         if let Some(macro_) = self.macro_store.get(&symbol.text()) {
-                            self.source_map.push_expanded_symbol(symbol.range, symbol.range.start().into(), symbol.range.end().into(), macro_); // FIXME: This is wrong.
+                            if !from_expansion {
+                                self.source_map.push_condition_symbol(symbol.range, None, macro_);
+                            }
                             output_stack.push(1);
                         } else {
                             output_stack.push(0);
@@ -207,24 +287,48 @@ impl<'a> IfCondition<'a> {
                                 continue;
                             }
                         };
-                        match expand_identifier(
+                        let expansion_start = self.expansion_stack.len();
+                        let mut argument_count_errors = vec![];
+                        let res = expand_identifier(
                             &mut symbol_iter,
                             self.macro_store,
                             &symbol,
                             &mut self.expansion_stack,
                             false,
-                        ) {
+                            &mut argument_count_errors,
+                        );
+                        if !from_expansion {
+                            self.macro_argument_count_errors.extend(argument_count_errors);
+                        }
+                        match res {
                             Ok(r_paren_offset) => {
+                                if from_expansion {
+                                    continue;
+                                }
                                 if let Some(macro_) = self.macro_store.get(&symbol.text()) {
-                                    let s_range = if let Some(r_paren_offset) = r_paren_offset{
+                                    let range = if let Some(r_paren_offset) = r_paren_offset{
                                         TextRange::new(symbol.range.start(), r_paren_offset)
                                     } else {
                                         symbol.range
                                     };
-                                    self.source_map.push_expanded_symbol(s_range, symbol.range.start().into(), symbol.range.end().into(), macro_);
+                                    let condition_text = expansion_text(&self.expansion_stack[expansion_start..]);
+                                    self.source_map.push_condition_symbol(range, Some(condition_text), macro_);
                                 }
                             }, // No need to keep track of expanded macros here, we do that when calling expand_symbol.
-                            Err(ExpansionError::MacroNotFound(err)) => {
+                            Err(ExpansionError::MacroNotFound(err)) if !from_expansion && err.range == symbol.range => {
+                                // The identifier itself is unresolved, report it and keep evaluating
+                                // the condition to report the other unresolved identifiers.
+                                self.macro_not_found_errors.push(err);
+                                has_unresolved_macros = true;
+                                output_stack.push(0);
+                                may_be_unary = false;
+                            }
+                            Err(ExpansionError::MacroNotFound(mut err)) => {
+                                if !from_expansion {
+                                    // Report the error on the identifier of the condition rather
+                                    // than in the body of the macro.
+                                    err.range = symbol.range;
+                                }
                                 self.macro_not_found_errors.push(err.clone());
                                 return Err(EvaluationError::new(
                                     "Unresolved macro".into(), // The error is already propagated in `macro_not_found_errors`.
@@ -246,6 +350,11 @@ impl<'a> IfCondition<'a> {
             op.process_op(&range, &mut output_stack)?;
         }
 
+        if has_unresolved_macros {
+            // The unresolved identifiers are already reported in `macro_not_found_errors`.
+            return Ok(false);
+        }
+
         let res = *output_stack.top().ok_or_else(|| {
             EvaluationError::new(
                 "Invalid preprocessor condition, expected a result.".to_string(),
@@ -253,7 +362,7 @@ impl<'a> IfCondition<'a> {
             )
         })?;
 
-        Ok(res != 0)
+        Ok(res.to_bool())
     }
 
     pub fn line_continuation_count(&self) -> u32 {
@@ -261,6 +370,64 @@ impl<'a> IfCondition<'a> {
     }
 }
 
+/// Build the text of an expansion from the [symbols](Symbol) pushed on the expansion stack.
+///
+/// The expansion stack stores the symbols in reverse order.
+fn expansion_text(symbols: &[Symbol]) -> SmolStr {
+    let mut text = String::new();
+    for symbol in symbols.iter().rev() {
+        if matches!(
+            symbol.token_kind,
+            TokenKind::Newline | TokenKind::LineContinuation | TokenKind::Eof
+        ) {
+            continue;
+        }
+        if !text.is_empty() && symbol.delta > 0 {
+            text.push(' ');
+        }
+        text.push_str(&symbol.inline_text());
+    }
+
+    text.into()
+}
+
+/// Value of a character literal, e.g `97` for `'a'`, or `10` for `'\n'`.
+///
+/// Returns `None` if the literal does not hold exactly one character.
+fn char_literal_value(text: &str) -> Option<i32> {
+    let inner = text.strip_prefix('\'')?.strip_suffix('\'')?;
+    let Some(escape) = inner.strip_prefix('\\') else {
+        let mut chars = inner.chars();
+        let c = chars.next()?;
+        return chars.next().is_none().then_some(c as i32);
+    };
+    let value = match escape {
+        "a" => 7,
+        "b" => 8,
+        "e" => 27,
+        "f" => 12,
+        "n" => 10,
+        "r" => 13,
+        "t" => 9,
+        "v" => 11,
+        _ => {
+            // Numeric escapes may be terminated by a semicolon, e.g `'\x41;'`.
+            let escape = escape.trim_end_matches(';');
+            if let Some(hex) = escape.strip_prefix('x') {
+                i32::from_str_radix(hex, 16).ok()?
+            } else if escape.starts_with(|c: char| c.is_ascii_digit()) {
+                escape.parse().ok()?
+            } else {
+                let mut chars = escape.chars();
+                let c = chars.next()?;
+                return chars.next().is_none().then_some(c as i32);
+            }
+        }
+    };
+
+    Some(value)
+}
+
 fn is_unary(op: &Operator) -> bool {
     matches!(
         op,
@@ -0,0 +1,445 @@
+//! Incremental re-preprocessing of edited files.
+//!
+//! Most edits do not touch preprocessor directives or macro invocations, e.g typing inside the
+//! body of a function. For those edits, the preprocessed text is a verbatim copy of the edited
+//! lines, and the previous [`PreprocessingResult`] can be patched instead of running the whole
+//! [`SourcepawnPreprocessor`](crate::SourcepawnPreprocessor) again.
+//!
+//! The patching is only attempted when it is guaranteed to produce the same result as a full
+//! run, which keeps the queries of the [`PreprocDatabase`](crate::db::PreprocDatabase)
+//! deterministic. The includes of a cached result are resolved again before it is reused, so that
+//! the query depends on the same inputs as a full run, and is invalidated when they change.
+
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    ops::Range,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use fxhash::{FxHashMap, FxHashSet, FxHasher};
+use smol_str::SmolStr;
+use sourcepawn_lexer::{
+    Comment, Operator, PreprocDir, SourcepawnLexer, TextRange, TextSize, TokenKind,
+};
+use vfs::FileId;
+
+use crate::{
+    builtins::BUILTIN_MACRO_NAMES, db::PreprocessingParams, offset::shift_range,
+    PreprocessingResult,
+};
+
+/// Maximum number of results kept in a [`PreprocessingCache`].
+const MAX_CACHED_RESULTS: usize = 128;
+
+/// Access to the [`PreprocessingCache`] of a database.
+pub trait PreprocessingCacheProvider {
+    fn preprocessing_cache(&self) -> &PreprocessingCache;
+}
+
+/// Last [`PreprocessingResult`] computed for each file, used to reprocess edited files
+/// incrementally.
+#[derive(Debug, Default)]
+pub struct PreprocessingCache {
+    entries: Mutex<FxHashMap<FileId, CachedResult>>,
+    hits: AtomicUsize,
+    patches: AtomicUsize,
+    misses: AtomicUsize,
+}
+
+/// Counters of a [`PreprocessingCache`], for debugging purposes.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PreprocessingCacheStats {
+    /// Number of results reused as is, because the text and the parameters of the file did not
+    /// change.
+    pub hits: usize,
+    /// Number of results patched incrementally after an edit.
+    pub patches: usize,
+    /// Number of files which had to be preprocessed again.
+    pub misses: usize,
+}
+
+impl fmt::Display for PreprocessingCacheStats {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            fmt,
+            "{} preprocessing cache hits, {} patches, {} misses",
+            self.hits, self.patches, self.misses
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+struct CachedResult {
+    text: Arc<str>,
+    text_hash: u64,
+    params: Arc<PreprocessingParams>,
+    includes: Arc<ResolvedIncludes>,
+    result: Arc<PreprocessingResult>,
+}
+
+/// Includes resolved by the preprocessor when computing a cached [`PreprocessingResult`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(crate) struct ResolvedIncludes {
+    /// Files implicitly included at the top of the file.
+    pub(crate) implicit_includes: Arc<Vec<String>>,
+    /// Includes of the file, in the order the preprocessor reached them.
+    pub(crate) includes: Vec<ResolvedInclude>,
+}
+
+/// Include resolved by the preprocessor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ResolvedInclude {
+    /// [`FileId`] of the file the include is resolved from.
+    pub(crate) file_id: FileId,
+    /// Path of the include, with its inferred extension.
+    pub(crate) path: String,
+    pub(crate) quoted: bool,
+    /// [`FileId`] of the included file, or [`None`] if the include exceeds the include limits and
+    /// was not resolved.
+    pub(crate) inc_file_id: Option<FileId>,
+}
+
+impl PreprocessingCache {
+    /// Try to compute the [`PreprocessingResult`] of a file from its last cached result.
+    ///
+    /// If the text of the file hashes identically to the cached one, the cached result is returned
+    /// as is, unless it contains include errors, which may have been fixed by something else than
+    /// the text and the parameters, e.g the creation of the missing file.
+    ///
+    /// # Arguments
+    /// * `file_id` - [`FileId`] of the file to preprocess.
+    /// * `text` - New text of the file.
+    /// * `params` - [`Parameters`](PreprocessingParams) the file is preprocessed with.
+    /// * `resolves_identically` - Whether the includes of the cached result still resolve to the
+    ///   same files. It reads the inputs of the database a full run would read for them.
+    pub(crate) fn reuse(
+        &self,
+        file_id: FileId,
+        text: &Arc<str>,
+        params: &Arc<PreprocessingParams>,
+        resolves_identically: impl FnOnce(&ResolvedIncludes) -> bool,
+    ) -> Option<Arc<PreprocessingResult>> {
+        let Some(cached) = self
+            .entries
+            .lock()
+            .ok()
+            .and_then(|entries| entries.get(&file_id).cloned())
+        else {
+            return self.miss();
+        };
+        if !Arc::ptr_eq(&cached.params, params) && cached.params != *params {
+            return self.miss();
+        }
+        let errors = cached.result.errors();
+        if !errors.unresolved_include_errors.is_empty()
+            || !errors.circular_include_errors.is_empty()
+            || !errors.include_limit_errors.is_empty()
+        {
+            return self.miss();
+        }
+        // The edited lines of a patched result do not contain directives, its includes are the
+        // same as the cached ones.
+        if !resolves_identically(&cached.includes) {
+            return self.miss();
+        }
+        if Arc::ptr_eq(&cached.text, text)
+            || (cached.text_hash == text_hash(text) && cached.text == *text)
+        {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Some(cached.result);
+        }
+        let Some(result) = reprocess_incrementally(&cached.text, &cached.result, text) else {
+            return self.miss();
+        };
+        let result: Arc<PreprocessingResult> = result.into();
+        self.patches.fetch_add(1, Ordering::Relaxed);
+        self.insert(
+            file_id,
+            text.clone(),
+            params.clone(),
+            cached.includes,
+            result.clone(),
+        );
+
+        Some(result)
+    }
+
+    fn miss(&self) -> Option<Arc<PreprocessingResult>> {
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        None
+    }
+
+    pub(crate) fn insert(
+        &self,
+        file_id: FileId,
+        text: Arc<str>,
+        params: Arc<PreprocessingParams>,
+        includes: Arc<ResolvedIncludes>,
+        result: Arc<PreprocessingResult>,
+    ) {
+        let Ok(mut entries) = self.entries.lock() else {
+            return;
+        };
+        if entries.len() >= MAX_CACHED_RESULTS && !entries.contains_key(&file_id) {
+            if let Some(evicted) = entries.keys().next().copied() {
+                entries.remove(&evicted);
+            }
+        }
+        entries.insert(
+            file_id,
+            CachedResult {
+                text_hash: text_hash(&text),
+                text,
+                params,
+                includes,
+                result,
+            },
+        );
+    }
+
+    pub fn clear(&self) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.clear();
+        }
+    }
+
+    /// Returns the hit and miss counters of the cache since it was created.
+    pub fn stats(&self) -> PreprocessingCacheStats {
+        PreprocessingCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            patches: self.patches.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+}
+
+fn text_hash(text: &str) -> u64 {
+    let mut hasher = FxHasher::default();
+    text.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Reprocess an edited file by patching its previous [`PreprocessingResult`].
+///
+/// Returns [`None`] if the edit touches a preprocessor directive, a macro invocation, an inactive
+/// region, or anything that could change how the rest of the file is preprocessed. In that case,
+/// the whole file has to be preprocessed again.
+///
+/// # Arguments
+/// * `old_text` - Text of the file before the edit.
+/// * `old_result` - [`PreprocessingResult`] of `old_text`.
+/// * `new_text` - Text of the file after the edit.
+pub fn reprocess_incrementally(
+    old_text: &str,
+    old_result: &PreprocessingResult,
+    new_text: &str,
+) -> Option<PreprocessingResult> {
+    if !old_result.is_complete() {
+        return None;
+    }
+    let (old_window, new_window) = edited_windows(old_text, new_text);
+    let old_window_text = &old_text[old_window.clone()];
+    let new_window_text = &new_text[new_window.clone()];
+    if !is_standalone_window(old_text, old_window.start) {
+        return None;
+    }
+
+    let mut macro_names: FxHashSet<SmolStr> = old_result.macros().keys().cloned().collect();
+    macro_names.extend(undefined_macro_names(old_text));
+    macro_names.extend(BUILTIN_MACRO_NAMES.map(SmolStr::from));
+    let new_ranges = lex_window(new_window_text, &macro_names)?;
+    lex_window(old_window_text, &macro_names)?;
+
+    let u_window = TextRange::new(
+        TextSize::new(old_window.start as u32),
+        TextSize::new(old_window.end as u32),
+    );
+    let source_map = old_result.source_map();
+    let intersects =
+        |range: &TextRange| range.start() <= u_window.end() && range.end() >= u_window.start();
+    if source_map
+        .expanded_symbols()
+        .iter()
+        .any(|symbol| intersects(symbol.range()))
+        || old_result.inactive_ranges().iter().any(intersects)
+    {
+        return None;
+    }
+
+    // Find where the window starts in the preprocessed text, and make sure it was copied verbatim.
+    let s_start = if old_window.start == 0 {
+        0
+    } else {
+        let newline_len = if old_text[..old_window.start].ends_with("\r\n") {
+            2
+        } else {
+            1
+        };
+        let newline = TextRange::at(
+            TextSize::new((old_window.start - newline_len) as u32),
+            TextSize::new(newline_len as u32),
+        );
+        let s_newline: u32 = source_map.s_range_from_u_range(newline)?.end().into();
+        s_newline as usize
+    };
+    let old_preprocessed_text = old_result.preprocessed_text();
+    let s_end = s_start + old_window_text.len();
+    if old_preprocessed_text.get(s_start..s_end)? != old_window_text {
+        return None;
+    }
+
+    let mut preprocessed_text = String::with_capacity(
+        old_preprocessed_text.len() + new_window_text.len() - old_window_text.len(),
+    );
+    preprocessed_text.push_str(&old_preprocessed_text[..s_start]);
+    preprocessed_text.push_str(new_window_text);
+    preprocessed_text.push_str(&old_preprocessed_text[s_end..]);
+
+    let delta = new_window.end as i64 - old_window.end as i64;
+    let u_offset = new_window.start as i64;
+    let s_offset = s_start as i64;
+    let source_map = source_map.splice(
+        u_window,
+        delta,
+        new_ranges
+            .into_iter()
+            .map(|range| (shift_range(range, u_offset), shift_range(range, s_offset))),
+        new_text.len(),
+        preprocessed_text.len(),
+    );
+    let mut errors = old_result.errors().clone();
+    errors.shift_after(u_window.end(), delta);
+    let inactive_ranges = old_result
+        .inactive_ranges()
+        .iter()
+        .map(|range| {
+            if range.start() >= u_window.end() {
+                shift_range(*range, delta)
+            } else {
+                *range
+            }
+        })
+        .collect();
+    let mut style_pragmas = old_result.style_pragmas().clone();
+    style_pragmas.shift_after(u_window.end(), delta);
+
+    let mut res = PreprocessingResult::new(
+        preprocessed_text.into(),
+        old_result.macros().clone(),
+        old_result.used_macros().clone(),
+        source_map,
+        errors,
+        inactive_ranges,
+        style_pragmas,
+    );
+    res.shrink_to_fit();
+
+    Some(res)
+}
+
+/// Compute the byte ranges of the lines affected by the edit, in the old and new texts.
+fn edited_windows(old_text: &str, new_text: &str) -> (Range<usize>, Range<usize>) {
+    let old_bytes = old_text.as_bytes();
+    let new_bytes = new_text.as_bytes();
+    let mut prefix = old_bytes
+        .iter()
+        .zip(new_bytes.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    while !old_text.is_char_boundary(prefix) || !new_text.is_char_boundary(prefix) {
+        prefix -= 1;
+    }
+    let max_suffix = old_bytes.len().min(new_bytes.len()) - prefix;
+    let mut suffix = old_bytes
+        .iter()
+        .rev()
+        .zip(new_bytes.iter().rev())
+        .take(max_suffix)
+        .take_while(|(a, b)| a == b)
+        .count();
+    while !old_text.is_char_boundary(old_bytes.len() - suffix)
+        || !new_text.is_char_boundary(new_bytes.len() - suffix)
+    {
+        suffix -= 1;
+    }
+
+    let start = old_text[..prefix].rfind('\n').map_or(0, |idx| idx + 1);
+    let line_end = |text: &str, edit_end: usize| {
+        let mut end = text[edit_end..]
+            .find('\n')
+            .map_or(text.len(), |idx| edit_end + idx);
+        if end > edit_end && text[..end].ends_with('\r') {
+            end -= 1;
+        }
+        end
+    };
+    let old_end = line_end(old_text, old_bytes.len() - suffix);
+    let new_end = line_end(new_text, new_bytes.len() - suffix);
+
+    (start..old_end, start..new_end)
+}
+
+/// Check that the lines starting at `start` are not the continuation of a previous line,
+/// i.e that they do not belong to a directive or a block comment.
+fn is_standalone_window(text: &str, start: usize) -> bool {
+    let before = &text[..start];
+    if before
+        .trim_end_matches('\n')
+        .trim_end_matches('\r')
+        .ends_with('\\')
+    {
+        return false;
+    }
+
+    before.rfind("/*") <= before.rfind("*/")
+}
+
+/// Lex the text of an edited window and return the ranges of its tokens.
+///
+/// Returns [`None`] if the window contains a token which is handled by the preprocessor.
+fn lex_window(text: &str, macro_names: &FxHashSet<SmolStr>) -> Option<Vec<TextRange>> {
+    if text.contains(['#', '\\', '\r']) || text.contains("/*") || text.contains("*/") {
+        return None;
+    }
+    let mut ranges = Vec::new();
+    for symbol in SourcepawnLexer::new(text) {
+        match symbol.token_kind {
+            TokenKind::Unknown
+            | TokenKind::PreprocDir(_)
+            | TokenKind::LineContinuation
+            | TokenKind::Using
+            | TokenKind::Intrinsics
+            | TokenKind::Operator(Operator::Stringize)
+            | TokenKind::Comment(Comment::BlockComment) => return None,
+            TokenKind::Identifier if macro_names.contains(&symbol.text()) => return None,
+            TokenKind::Eof => (),
+            _ if !symbol.range.is_empty() => ranges.push(symbol.range),
+            _ => (),
+        }
+    }
+
+    Some(ranges)
+}
+
+/// Names of the macros which are undefined in the text.
+///
+/// Those macros are not part of the final macros map of the file, but could have been expanded
+/// before being undefined.
+fn undefined_macro_names(text: &str) -> impl Iterator<Item = SmolStr> + '_ {
+    let undef = PreprocDir::MUndef.text();
+    text.match_indices(undef.as_str())
+        .filter_map(move |(idx, _)| {
+            let name = text[idx + undef.len()..]
+                .trim_start_matches([' ', '\t'])
+                .split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .next()?;
+            if name.is_empty() {
+                return None;
+            }
+            Some(SmolStr::from(name))
+        })
+}
@@ -2,7 +2,8 @@ use std::{cmp::max, hash::Hash, sync::Arc};
 
 use anyhow::{bail, Context};
 use base_db::{RE_CHEVRON, RE_QUOTE};
-use conditions::{ConditionOffsetStack, ConditionStack, ConditionState};
+use conditions::{ConditionOffsetStack, ConditionStack, ConditionState, OpenCondition};
+use fxhash::FxHashMap;
 use lsp_types::Diagnostic;
 use smol_str::SmolStr;
 use sourcepawn_lexer::{
@@ -10,31 +11,63 @@ use sourcepawn_lexer::{
 };
 use vfs::FileId;
 
-use errors::{ExpansionError, PreprocessorErrors, UnresolvedIncludeError};
+use errors::{
+    ExpansionError, InactiveDirectiveError, InactiveDirectiveReason, PreprocessorErrors,
+    UnresolvedIncludeError,
+};
 use evaluator::IfCondition;
 use macros::expand_identifier;
+use symbol::range_less_symbol;
 
 mod buffer;
+mod builtins;
 mod conditions;
 pub mod db;
+mod directives;
 mod errors;
 pub(crate) mod evaluator;
+mod incremental;
 mod macros;
 mod offset;
+mod pragmas;
 mod preprocessor_operator;
 mod result;
 mod symbol;
 
 use buffer::PreprocessorBuffer;
-pub use errors::{EvaluationError, PreprocessorError};
+pub use conditions::{branch_directive_at, condition_branch_ranges};
+pub use errors::{
+    AssertionFailedError, CircularIncludeError, EvaluationError, InactiveDirectiveError,
+    InactiveDirectiveReason, IncludeCycle, IncludeLimitError, IncludeLimitExceeded,
+    IncludeLimitKind, MacroArgumentCountError, PreprocessorError, UnbalancedConditionError,
+    UnbalancedConditionKind, UnknownDirectiveError, UseAfterUndefError,
+};
+pub use incremental::{
+    reprocess_incrementally, PreprocessingCache, PreprocessingCacheProvider,
+    PreprocessingCacheStats,
+};
 pub(crate) use macros::MacroStore;
 pub use macros::{HMacrosMap, Macro, MacrosMap};
 pub use offset::{ExpandedSymbolOffset, SourceMap};
+pub use pragmas::{StylePragma, StylePragmaKind, StylePragmas};
 pub use result::PreprocessingResult;
 
 #[cfg(test)]
 mod test;
 
+/// Number of bytes of the input preprocessed between two cancellation checks.
+const CANCELLATION_CHECK_INTERVAL: u32 = 64 * 1024;
+
+/// Function called between the chunks of the input, which unwinds to abort the preprocessing.
+#[derive(Clone, Copy)]
+struct CancellationCheck<'a>(&'a dyn Fn());
+
+impl std::fmt::Debug for CancellationCheck<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("CancellationCheck")
+    }
+}
+
 #[derive(Debug)]
 pub struct SourcepawnPreprocessor<'a, F>
 where
@@ -43,12 +76,28 @@ where
     lexer: SourcepawnLexer<'a>,
     input: &'a str,
     macro_store: MacroStore,
+    /// Range of the last `#undef` of each macro undefined in the file, and not redefined since.
+    undefined_macros: FxHashMap<SmolStr, TextRange>,
     expansion_stack: Vec<Symbol>,
     errors: PreprocessorErrors,
     file_id: FileId,
     conditions_stack: ConditionStack,
     condition_offsets_stack: ConditionOffsetStack,
-    buffer: PreprocessorBuffer,
+    /// `#if` directives whose `#endif` has not been reached yet.
+    open_conditions: Vec<OpenCondition>,
+    buffer: PreprocessorBuffer<'a>,
+    style_pragmas: StylePragmas,
+    /// Offset of the first active `#endinput` directive of the input.
+    endinput: Option<TextSize>,
+    /// Files included before the first line of the input.
+    implicit_includes: Vec<String>,
+    /// Value of the branch directives of the conditions forced by the user, by offset.
+    forced_conditions: FxHashMap<TextSize, bool>,
+    /// Checks whether the preprocessing is cancelled, see
+    /// [`SourcepawnPreprocessor::set_cancellation_check`].
+    cancellation_check: Option<CancellationCheck<'a>>,
+    /// Offset of the input after which the next cancellation check happens.
+    next_cancellation_check: u32,
     include_file: &'a mut F,
 }
 
@@ -75,8 +124,16 @@ where
             errors: Default::default(),
             conditions_stack: Default::default(),
             condition_offsets_stack: Default::default(),
-            buffer: PreprocessorBuffer::new(input.len()),
-            macro_store: Default::default(),
+            open_conditions: Default::default(),
+            buffer: PreprocessorBuffer::new(input),
+            style_pragmas: Default::default(),
+            endinput: None,
+            implicit_includes: vec!["sourcemod".to_string()],
+            forced_conditions: Default::default(),
+            cancellation_check: None,
+            next_cancellation_check: CANCELLATION_CHECK_INTERVAL,
+            macro_store: MacroStore::with_builtins(file_id),
+            undefined_macros: Default::default(),
             expansion_stack: Default::default(),
         }
     }
@@ -85,16 +142,42 @@ where
         self.macro_store.extend(map);
     }
 
+    /// Set the files included before the first line of the input, `sourcemod` by default.
+    pub fn set_implicit_includes(&mut self, implicit_includes: Vec<String>) {
+        self.implicit_includes = implicit_includes;
+    }
+
+    /// Force branches of the conditions active, regardless of the evaluation of their condition.
+    ///
+    /// The branches are given by their index among the branch directives of the input, see
+    /// [`branch_directive_at`].
+    pub fn set_forced_branches(&mut self, forced_branches: &[u32]) {
+        self.forced_conditions = conditions::forced_conditions(self.input, forced_branches);
+    }
+
+    /// Set a function called after each chunk of the input, which aborts the preprocessing by
+    /// unwinding when it is cancelled, e.g `salsa::Database::unwind_if_cancelled`.
+    ///
+    /// This keeps very large files, e.g generated tables of several megabytes, from blocking the
+    /// requests which cancel the preprocessing.
+    pub fn set_cancellation_check(&mut self, cancellation_check: &'a dyn Fn()) {
+        self.cancellation_check = Some(CancellationCheck(cancellation_check));
+    }
+
     pub fn result(mut self) -> PreprocessingResult {
         let inactive_ranges = self.get_inactive_ranges();
         let preprocessed_text: Arc<str> = self.buffer.contents().into();
+        let (macros, used_macros) = self.macro_store.into_parts();
         let mut res = PreprocessingResult::new(
             preprocessed_text.clone(),
-            self.macro_store.into_macros_map(),
+            macros,
+            used_macros,
             self.buffer.into_source_map(self.input, &preprocessed_text),
             self.errors,
             inactive_ranges,
+            self.style_pragmas,
         );
+        res.set_endinput(self.endinput);
         res.shrink_to_fit();
         res
     }
@@ -102,13 +185,18 @@ where
     pub fn error_result(mut self) -> PreprocessingResult {
         let inactive_ranges = self.get_inactive_ranges();
         let preprocessed_text: Arc<str> = self.buffer.contents().into();
+        let (macros, used_macros) = self.macro_store.into_parts();
         let mut res = PreprocessingResult::new(
             preprocessed_text.clone(),
-            self.macro_store.into_macros_map(),
+            macros,
+            used_macros,
             self.buffer.into_source_map(self.input, &preprocessed_text),
             self.errors,
             inactive_ranges,
+            self.style_pragmas,
         );
+        res.set_endinput(self.endinput);
+        res.set_incomplete();
         res.shrink_to_fit();
         res
     }
@@ -182,42 +270,79 @@ where
         }));
     }
 
-    fn include_sourcemod(&mut self) {
-        let _ = (self.include_file)(
-            self.macro_store.map_mut(),
-            "sourcemod".to_string(),
-            self.file_id,
-            false,
-        );
+    fn include_implicit_files(&mut self) {
+        for path in std::mem::take(&mut self.implicit_includes) {
+            let _ = (self.include_file)(self.macro_store.map_mut(), path, self.file_id, false);
+        }
     }
 
     pub fn preprocess_input(mut self) -> PreprocessingResult {
-        self.include_sourcemod();
+        self.include_implicit_files();
         let mut intrinsics_parse_status = None;
         let mut expanded_symbol: Option<(Symbol, Arc<Macro>, u32)> = None;
-        while let Some(symbol) = if !self.expansion_stack.is_empty() {
-            self.expansion_stack.pop()
-        } else {
-            if let Some((expanded_symbol, macro_, start_offset)) = expanded_symbol.take() {
-                let end_offset = self.buffer.offset();
-                self.buffer.source_map_mut().push_expanded_symbol(
-                    expanded_symbol.range,
-                    start_offset,
-                    end_offset,
-                    &macro_,
-                );
-            }
-            self.lexer.next()
-        } {
+        loop {
+            let from_expansion = !self.expansion_stack.is_empty();
+            let Some(symbol) = (if from_expansion {
+                self.expansion_stack.pop()
+            } else {
+                if let Some((expanded_symbol, macro_, start_offset)) = expanded_symbol.take() {
+                    let end_offset = self.buffer.offset();
+                    self.buffer.source_map_mut().push_expanded_symbol(
+                        expanded_symbol.range,
+                        start_offset,
+                        end_offset,
+                        &macro_,
+                        self.macro_store.take_expansions(),
+                    );
+                }
+                self.lexer.next()
+            }) else {
+                break;
+            };
+            if !from_expansion && u32::from(symbol.range.start()) >= self.next_cancellation_check {
+                self.next_cancellation_check =
+                    u32::from(symbol.range.start()) + CANCELLATION_CHECK_INTERVAL;
+                if let Some(CancellationCheck(check)) = self.cancellation_check {
+                    check();
+                }
+            }
             if self.conditions_stack.top_is_activated_or_not_activated() {
                 if self.process_negative_condition(&symbol).is_err() {
                     return self.error_result();
                 }
                 continue;
             }
+            if !from_expansion
+                && symbol.token_kind == TokenKind::Operator(Operator::Stringize)
+                && self.directive_name(&symbol) == Some("#assert")
+            {
+                if self.process_assert_directive(&symbol) {
+                    break;
+                }
+                continue;
+            }
+            if !from_expansion
+                && self.endinput.is_none()
+                && matches!(symbol.token_kind, TokenKind::PreprocDir(_))
+                && self.directive_name(&symbol) == Some("#endinput")
+            {
+                self.endinput = Some(symbol.range.start());
+            }
+            if !from_expansion && self.process_unknown_directive(&symbol) {
+                if self.skip_directive_line() {
+                    break;
+                }
+                continue;
+            }
             match &symbol.token_kind {
                 TokenKind::Unknown => return self.error_result(),
                 TokenKind::PreprocDir(dir) => {
+                    if !self.balance_condition_directive(*dir, &symbol) {
+                        if self.skip_directive_line() {
+                            break;
+                        }
+                        continue;
+                    }
                     if self.process_directive(dir, &symbol).is_err() {
                         return self.error_result();
                     }
@@ -243,13 +368,27 @@ where
                                 self.buffer.push_symbol(&symbol);
                                 continue;
                             }
-                            match expand_identifier(
+                            if !from_expansion {
+                                // Forget the expansions of the preprocessor conditions.
+                                self.macro_store.take_expansions();
+                            }
+                            let mut argument_count_errors = vec![];
+                            let res = expand_identifier(
                                 &mut self.lexer,
                                 &mut self.macro_store,
                                 &symbol,
                                 &mut self.expansion_stack,
                                 true,
-                            ) {
+                                &mut argument_count_errors,
+                            );
+                            // The ranges of the symbols of an expansion are in the body of
+                            // their macro.
+                            if !from_expansion {
+                                self.errors
+                                    .macro_argument_count_errors
+                                    .extend(argument_count_errors);
+                            }
+                            match res {
                                 Ok(r_paren_offset) => {
                                     match r_paren_offset {
                                         Some(r_paren_offset)
@@ -284,6 +423,9 @@ where
                             }
                         }
                         None => {
+                            if !from_expansion {
+                                self.check_use_after_undef(&symbol);
+                            }
                             self.buffer.push_symbol(&symbol);
                         }
                     }
@@ -319,6 +461,14 @@ where
                 _ => self.buffer.push_symbol(&symbol),
             }
         }
+        for condition in std::mem::take(&mut self.open_conditions) {
+            self.errors
+                .unbalanced_condition_errors
+                .push(UnbalancedConditionError::new(
+                    UnbalancedConditionKind::UnterminatedIf,
+                    condition.range,
+                ));
+        }
 
         self.result()
     }
@@ -342,6 +492,11 @@ where
                 false
             }
         };
+        let if_condition_eval = self
+            .forced_conditions
+            .get(&symbol.range.start())
+            .copied()
+            .unwrap_or(if_condition_eval);
 
         if if_condition_eval {
             self.conditions_stack.push(ConditionState::Active);
@@ -352,6 +507,9 @@ where
         self.errors
             .macro_not_found_errors
             .extend(if_condition.macro_not_found_errors.clone());
+        self.errors
+            .macro_argument_count_errors
+            .extend(if_condition.macro_argument_count_errors.clone());
         drop(if_condition);
         self.buffer.push_new_lines(line_continuation_count);
     }
@@ -425,12 +583,42 @@ where
         Ok(())
     }
 
+    /// Returns the doc comment (`/** ... */`) which ends on the line above `offset`, if any.
+    fn doc_comment_before(&self, offset: TextSize) -> Option<SmolStr> {
+        let before = self.input[..usize::from(offset)].trim_end_matches([' ', '\t']);
+        let before = before
+            .strip_suffix('\n')
+            .map(|it| it.strip_suffix('\r').unwrap_or(it))?
+            .trim_end_matches([' ', '\t']);
+        if !before.ends_with("*/") {
+            return None;
+        }
+        let start = before.rfind("/*")?;
+        let comment = &before[start..];
+        if !comment.starts_with("/**") || comment.len() < "/***/".len() {
+            return None;
+        }
+        // The comment must be on its own lines.
+        if !before[..start]
+            .rsplit('\n')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .is_empty()
+        {
+            return None;
+        }
+
+        Some(comment.into())
+    }
+
     fn process_directive(&mut self, dir: &PreprocDir, symbol: &Symbol) -> anyhow::Result<()> {
         match dir {
             PreprocDir::MDefine => {
                 self.buffer.push_symbol(symbol);
                 let mut macro_name = SmolStr::default();
                 let mut macro_ = Macro::default(self.file_id);
+                macro_.docs = self.doc_comment_before(symbol.range.start());
                 enum State {
                     Start,
                     Params,
@@ -460,13 +648,13 @@ where
                                 {
                                     state = State::Params;
                                 } else {
-                                    macro_.body.push(symbol.into());
+                                    macro_.body.push(range_less_symbol(&symbol));
                                     state = State::Body;
                                 }
                             }
                             State::Params => {
                                 if symbol.delta > 0 {
-                                    macro_.body.push(symbol.into());
+                                    macro_.body.push(range_less_symbol(&symbol));
                                     state = State::Body;
                                     continue;
                                 }
@@ -499,7 +687,7 @@ where
                                 }
                             }
                             State::Body => {
-                                macro_.body.push(symbol.into());
+                                macro_.body.push(range_less_symbol(&symbol));
                             }
                         }
                     }
@@ -510,6 +698,7 @@ where
                 }
                 self.buffer.push_new_line();
                 macro_.name_len = macro_name.len();
+                self.undefined_macros.remove(&macro_name);
                 self.macro_store.insert_macro(macro_name, macro_);
             }
             PreprocDir::MUndef => {
@@ -521,6 +710,14 @@ where
                             self.buffer.push_symbol_no_delta(&symbol);
                         }
                         if symbol.token_kind == TokenKind::Identifier {
+                            if let Some(macro_) = self.macro_store.get(&symbol.text()) {
+                                self.buffer.source_map_mut().push_condition_symbol(
+                                    symbol.range,
+                                    None,
+                                    macro_,
+                                );
+                                self.undefined_macros.insert(symbol.text(), symbol.range);
+                            }
                             self.macro_store.remove_macro(&symbol.text());
                             break;
                         }
@@ -535,66 +732,59 @@ where
             PreprocDir::MEndif => self.process_endif_directive(symbol)?,
             PreprocDir::MInclude => self.process_include_directive(symbol, false),
             PreprocDir::MTryinclude => self.process_include_directive(symbol, true),
+            PreprocDir::MPragma
+            | PreprocDir::MRequireSemi
+            | PreprocDir::MOptionalSemi
+            | PreprocDir::MRequireNewdecls
+            | PreprocDir::MOptionalNewdecls => {
+                if let Some(pragma) = StylePragma::parse(dir, &symbol.text(), symbol.range) {
+                    self.style_pragmas.push(pragma);
+                }
+                self.buffer.push_symbol(symbol);
+            }
             _ => self.buffer.push_symbol(symbol),
         }
 
         Ok(())
     }
 
+    /// Record an error if the identifier is the name of a macro which was undefined earlier in the
+    /// file.
+    fn check_use_after_undef(&mut self, symbol: &Symbol) {
+        let name = symbol.text();
+        if let Some(undef_range) = self.undefined_macros.get(&name) {
+            self.errors
+                .use_after_undef_errors
+                .push(UseAfterUndefError::new(
+                    name.to_string(),
+                    *undef_range,
+                    symbol.range,
+                ));
+        }
+    }
+
     fn process_include_directive(&mut self, symbol: &Symbol, is_try: bool) {
         let text = symbol.inline_text().trim().to_string();
         let line_delta = linebreak_count(symbol.text().as_str());
 
         if let Some(path) = RE_CHEVRON.captures(&text).and_then(|c| c.get(1)) {
-            match (self.include_file)(
+            if let Err(err) = (self.include_file)(
                 self.macro_store.map_mut(),
                 path.as_str().to_string(),
                 self.file_id,
                 false,
             ) {
-                Ok(_) => (),
-                Err(_) => {
-                    if !is_try {
-                        // TODO: Emit a warning here for #tryinclude?
-                        let start: usize = symbol.range.start().into();
-                        let range = TextRange::new(
-                            TextSize::new((start + path.start()) as u32),
-                            TextSize::new((start + path.end()) as u32),
-                        );
-                        self.errors
-                            .unresolved_include_errors
-                            .push(UnresolvedIncludeError::new(
-                                path.as_str().to_string(),
-                                range,
-                            ))
-                    }
-                }
+                self.push_include_error(err, symbol, path, is_try);
             }
         };
         if let Some(path) = RE_QUOTE.captures(&text).and_then(|c| c.get(1)) {
-            match (self.include_file)(
+            if let Err(err) = (self.include_file)(
                 self.macro_store.map_mut(),
                 path.as_str().to_string(),
                 self.file_id,
                 true,
             ) {
-                Ok(_) => (),
-                Err(_) => {
-                    if !is_try {
-                        // TODO: Emit a warning here for #tryinclude?
-                        let start: usize = symbol.range.start().into();
-                        let range = TextRange::new(
-                            TextSize::new((start + path.start()) as u32),
-                            TextSize::new((start + path.end()) as u32),
-                        );
-                        self.errors
-                            .unresolved_include_errors
-                            .push(UnresolvedIncludeError::new(
-                                path.as_str().to_string(),
-                                range,
-                            ))
-                    }
-                }
+                self.push_include_error(err, symbol, path, is_try);
             }
         };
 
@@ -602,8 +792,223 @@ where
         self.buffer.push_new_lines(line_delta as u32);
     }
 
+    fn push_include_error(
+        &mut self,
+        err: anyhow::Error,
+        symbol: &Symbol,
+        path: regex::Match,
+        is_try: bool,
+    ) {
+        let start: usize = symbol.range.start().into();
+        let range = TextRange::new(
+            TextSize::new((start + path.start()) as u32),
+            TextSize::new((start + path.end()) as u32),
+        );
+        if let Some(IncludeCycle(cycle)) = err.downcast_ref::<IncludeCycle>() {
+            self.errors
+                .circular_include_errors
+                .push(CircularIncludeError::new(
+                    path.as_str().to_string(),
+                    cycle.clone(),
+                    range,
+                ));
+        } else if let Some(exceeded) = err.downcast_ref::<IncludeLimitExceeded>() {
+            self.errors
+                .include_limit_errors
+                .push(IncludeLimitError::new(
+                    path.as_str().to_string(),
+                    exceeded.clone(),
+                    range,
+                ));
+        } else if !is_try {
+            // TODO: Emit a warning here for #tryinclude?
+            self.errors
+                .unresolved_include_errors
+                .push(UnresolvedIncludeError::new(
+                    path.as_str().to_string(),
+                    range,
+                ))
+        }
+    }
+
+    /// Check if the symbol starts a directive which is not supported by the preprocessor, and
+    /// record an error if the directive is unknown.
+    ///
+    /// Returns `true` if the rest of the directive has to be skipped.
+    fn process_unknown_directive(&mut self, symbol: &Symbol) -> bool {
+        match symbol.token_kind {
+            TokenKind::Operator(Operator::Stringize) | TokenKind::Unknown => (),
+            TokenKind::PreprocDir(dir) if dir != PreprocDir::MLine => (),
+            _ => return false,
+        }
+        let Some(name) = self.directive_name(symbol) else {
+            return false;
+        };
+        if directives::is_ignored_directive(name) {
+            return true;
+        }
+        if directives::is_known_directive(name) {
+            return false;
+        }
+        if symbol.token_kind == TokenKind::PreprocDir(PreprocDir::MIf) {
+            // Keep the conditions balanced for `#ifdef` and `#ifndef`, their matching `#endif`
+            // would otherwise close a parent condition.
+            self.condition_offsets_stack.push(symbol.range.start());
+            self.conditions_stack.push(ConditionState::Active);
+            self.open_conditions.push(OpenCondition::new(symbol.range));
+        }
+        self.errors
+            .unknown_directive_errors
+            .push(UnknownDirectiveError::new(
+                name.to_string(),
+                directives::suggest_directive(name),
+                TextRange::at(symbol.range.start(), TextSize::new(name.len() as u32)),
+            ));
+
+        true
+    }
+
+    /// Name of the directive starting at the symbol, if the symbol is at the start of a line.
+    fn directive_name(&self, symbol: &Symbol) -> Option<&'a str> {
+        let start: usize = symbol.range.start().into();
+        let line_start = self.input[..start].rfind('\n').map_or(0, |idx| idx + 1);
+        if !self.input[line_start..start].trim().is_empty() {
+            return None;
+        }
+
+        directives::directive_name(self.input, start)
+    }
+
+    /// Evaluate the condition of an `#assert` directive, and record an error if it is false.
+    ///
+    /// Returns `true` if the end of the file was reached.
+    fn process_assert_directive(&mut self, symbol: &Symbol) -> bool {
+        let mut if_condition =
+            IfCondition::new(&mut self.macro_store, self.buffer.source_map_mut());
+        let mut eof = None;
+        // Skip the `assert` identifier following the `#`.
+        for symbol in self.lexer.by_ref().skip(1) {
+            match symbol.token_kind {
+                TokenKind::Eof => {
+                    eof = Some(symbol);
+                    break;
+                }
+                TokenKind::Newline => {
+                    if_condition.symbols.push(symbol);
+                    break;
+                }
+                _ => if_condition.symbols.push(symbol),
+            }
+        }
+        let end = if_condition
+            .symbols
+            .iter()
+            .rev()
+            .find(|symbol| symbol.token_kind != TokenKind::Newline)
+            .map_or(symbol.range.end(), |symbol| symbol.range.end());
+        let range = TextRange::new(symbol.range.start(), end);
+        match if_condition.evaluate() {
+            // The condition is assumed false when it uses unresolved macros, which are
+            // reported on their own.
+            Ok(false) if if_condition.macro_not_found_errors.is_empty() => {
+                let text = self.input[usize::from(range.start())..usize::from(range.end())]
+                    .trim_start_matches('#')
+                    .trim();
+                let expression = text.strip_prefix("assert").unwrap_or(text).trim();
+                self.errors
+                    .assertion_failed_errors
+                    .push(AssertionFailedError::new(expression.to_string(), range));
+            }
+            Ok(_) => (),
+            Err(err) => self.errors.evaluation_errors.push(err),
+        }
+        let line_continuation_count = if_condition.line_continuation_count();
+        self.errors
+            .macro_not_found_errors
+            .extend(if_condition.macro_not_found_errors.clone());
+        self.errors
+            .macro_argument_count_errors
+            .extend(if_condition.macro_argument_count_errors.clone());
+        drop(if_condition);
+        self.buffer.push_new_lines(line_continuation_count);
+        if let Some(eof) = eof {
+            self.buffer.push_symbol(&eof);
+            return true;
+        }
+
+        false
+    }
+
+    /// Skip the rest of the line of a directive.
+    ///
+    /// Returns `true` if the end of the file was reached.
+    fn skip_directive_line(&mut self) -> bool {
+        for symbol in self.lexer.by_ref() {
+            match symbol.token_kind {
+                TokenKind::LineContinuation => self.buffer.push_new_line(),
+                TokenKind::Newline => {
+                    self.buffer.push_symbol(&symbol);
+                    return false;
+                }
+                TokenKind::Eof => {
+                    self.buffer.push_symbol(&symbol);
+                    return true;
+                }
+                _ => (),
+            }
+        }
+
+        true
+    }
+
+    /// Keep track of the `#if` directives which are not closed yet, and record an error for the
+    /// conditional directives which do not match them.
+    ///
+    /// Returns `false` if the directive does not match any `#if` and must be ignored.
+    fn balance_condition_directive(&mut self, dir: PreprocDir, symbol: &Symbol) -> bool {
+        let (without_if, after_else) = match dir {
+            PreprocDir::MIf => {
+                self.open_conditions.push(OpenCondition::new(symbol.range));
+                return true;
+            }
+            PreprocDir::MElse => (
+                UnbalancedConditionKind::ElseWithoutIf,
+                Some(UnbalancedConditionKind::ElseAfterElse),
+            ),
+            PreprocDir::MElseif => (
+                UnbalancedConditionKind::ElseifWithoutIf,
+                Some(UnbalancedConditionKind::ElseifAfterElse),
+            ),
+            PreprocDir::MEndif => (UnbalancedConditionKind::EndifWithoutIf, None),
+            _ => return true,
+        };
+        let Some(condition) = self.open_conditions.last_mut() else {
+            self.errors
+                .unbalanced_condition_errors
+                .push(UnbalancedConditionError::new(without_if, symbol.range));
+            return false;
+        };
+        match after_else {
+            Some(after_else) if condition.has_else => {
+                self.errors
+                    .unbalanced_condition_errors
+                    .push(UnbalancedConditionError::new(after_else, symbol.range));
+            }
+            Some(_) => condition.has_else |= dir == PreprocDir::MElse,
+            None => {
+                self.open_conditions.pop();
+            }
+        }
+
+        true
+    }
+
     fn process_negative_condition(&mut self, symbol: &Symbol) -> anyhow::Result<()> {
+        self.check_inactive_directive(symbol);
         if let TokenKind::PreprocDir(dir) = symbol.token_kind {
+            if !self.balance_condition_directive(dir, symbol) {
+                return Ok(());
+            }
             match dir {
                 PreprocDir::MIf => {
                     // Keep track of any nested if statements to ensure we properly pop when reaching an endif.
@@ -618,6 +1023,93 @@ where
 
         Ok(())
     }
+
+    /// Check the structure of a directive of an inactive region, and record an error if the
+    /// directive would be an error if the region was active.
+    ///
+    /// The directive is not preprocessed, e.g a `#define` does not define a macro.
+    fn check_inactive_directive(&mut self, symbol: &Symbol) {
+        let reason = match symbol.token_kind {
+            TokenKind::PreprocDir(PreprocDir::MDefine) => self.check_inactive_define(symbol),
+            TokenKind::PreprocDir(PreprocDir::MUndef) => self
+                .next_directive_symbol()
+                .filter(|it| it.token_kind == TokenKind::Identifier)
+                .is_none()
+                .then_some((InactiveDirectiveReason::MissingUndefName, symbol.range)),
+            TokenKind::PreprocDir(PreprocDir::MInclude | PreprocDir::MTryinclude) => {
+                let text = symbol.inline_text();
+                (!RE_CHEVRON.is_match(&text) && !RE_QUOTE.is_match(&text))
+                    .then_some((InactiveDirectiveReason::MissingIncludePath, symbol.range))
+            }
+            TokenKind::Operator(Operator::Stringize)
+            | TokenKind::Unknown
+            | TokenKind::PreprocDir(PreprocDir::MIf) => self
+                .directive_name(symbol)
+                .filter(|name| !directives::is_known_directive(name))
+                .map(|name| {
+                    (
+                        InactiveDirectiveReason::UnknownDirective {
+                            name: name.to_string(),
+                            suggestion: directives::suggest_directive(name),
+                        },
+                        TextRange::at(symbol.range.start(), TextSize::new(name.len() as u32)),
+                    )
+                }),
+            _ => None,
+        };
+        if let Some((reason, range)) = reason {
+            self.errors
+                .inactive_directive_errors
+                .push(InactiveDirectiveError::new(reason, range));
+        }
+    }
+
+    /// Check the name and the parameters of a `#define` of an inactive region, the same way
+    /// they are parsed in an active region.
+    fn check_inactive_define(
+        &mut self,
+        symbol: &Symbol,
+    ) -> Option<(InactiveDirectiveReason, TextRange)> {
+        if self
+            .next_directive_symbol()
+            .filter(|it| it.token_kind == TokenKind::Identifier)
+            .is_none()
+        {
+            return Some((InactiveDirectiveReason::MissingMacroName, symbol.range));
+        }
+        let symbols: Vec<_> = std::iter::from_fn(|| self.next_directive_symbol()).collect();
+        if !symbols
+            .first()
+            .is_some_and(|it| it.delta == 0 && it.token_kind == TokenKind::LParen)
+        {
+            return None;
+        }
+        symbols[1..]
+            .iter()
+            .take_while(|it| it.delta == 0 && it.token_kind != TokenKind::RParen)
+            .find(|it| match it.token_kind {
+                TokenKind::Comma | TokenKind::Operator(Operator::Percent) => false,
+                TokenKind::Literal(Literal::IntegerLiteral) => {
+                    !it.to_int().is_some_and(|idx| idx < 10)
+                }
+                _ => true,
+            })
+            .map(|it| (InactiveDirectiveReason::InvalidMacroParameters, it.range))
+    }
+
+    /// Next symbol of the current directive line, [`None`] at the end of the line.
+    fn next_directive_symbol(&mut self) -> Option<Symbol> {
+        while self.lexer.in_preprocessor() {
+            let symbol = self.lexer.next()?;
+            match symbol.token_kind {
+                TokenKind::Newline | TokenKind::Eof => return None,
+                TokenKind::LineContinuation | TokenKind::Comment(_) => continue,
+                _ => return Some(symbol),
+            }
+        }
+
+        None
+    }
 }
 
 pub fn linebreak_count(text: &str) -> usize {
@@ -627,3 +1119,26 @@ pub fn linebreak_count(text: &str) -> usize {
         .filter(|&w| w == substring)
         .count()
 }
+
+/// Macros of a configuration, defined before the first line of `file_id`.
+///
+/// The definitions use the syntax of spcomp's command line, i.e `NAME=VALUE`, `NAME=` for
+/// `NAME` defined to `0`, and `NAME` for a macro without a value.
+pub fn configuration_macros(file_id: FileId, definitions: &[String]) -> MacrosMap {
+    let text = definitions
+        .iter()
+        .map(|definition| match definition.split_once('=') {
+            Some((name, value)) if value.trim().is_empty() => {
+                format!("#define {} 0\n", name.trim())
+            }
+            Some((name, value)) => format!("#define {} {}\n", name.trim(), value.trim()),
+            None => format!("#define {}\n", definition.trim()),
+        })
+        .collect::<String>();
+    let mut extend_macros =
+        |_: &mut MacrosMap, _: String, _: FileId, _: bool| -> anyhow::Result<()> { Ok(()) };
+    let mut preprocessor = SourcepawnPreprocessor::new(file_id, &text, &mut extend_macros);
+    preprocessor.set_implicit_includes(vec![]);
+
+    preprocessor.preprocess_input().macros().clone()
+}
@@ -1,14 +1,14 @@
-use std::{cmp::Ordering, collections::VecDeque, sync::Arc};
+use std::{cell::OnceCell, cmp::Ordering, collections::VecDeque, sync::Arc};
 
 use deepsize::DeepSizeOf;
 use fxhash::{FxHashMap, FxHashSet};
-use smol_str::SmolStr;
+use smol_str::{format_smolstr, SmolStr};
 use sourcepawn_lexer::{Literal, Operator, Symbol, TextRange, TextSize, TokenKind};
 use stdx::hashable_hash_map::HashableHashMap;
 use vfs::FileId;
 
-use super::errors::{ExpansionError, MacroNotFoundError, ParseIntError};
-use crate::symbol::RangeLessSymbol;
+use super::builtins::{builtin_macros, BUILTIN_MACRO_NAMES};
+use super::errors::{ExpansionError, MacroArgumentCountError, MacroNotFoundError, ParseIntError};
 
 const MAX_MACRO_EXPANSION_DEPTH: usize = 5;
 
@@ -67,6 +67,9 @@ impl ArgumentsCollector {
     /// Assuming we are right before a macro call in the lexer, collect the arguments
     /// and store them in an array, in the order they appear in.
     ///
+    /// Returns the arguments, the number of arguments of the call and the offset of the
+    /// closing parenthesis.
+    ///
     /// # Arguments
     ///
     /// * `lexer` - [SourcepawnLexer](sourcepawn_lexer::lexer) to iterate over.
@@ -78,13 +81,15 @@ impl ArgumentsCollector {
         lexer: &mut T,
         context: &mut MacroContext,
         nb_params: usize,
-    ) -> Option<(MacroArguments, TextSize)>
+    ) -> Option<(MacroArguments, usize, TextSize)>
     where
         T: Iterator<Item = Symbol>,
     {
         let mut temp_expanded_stack = vec![];
         let mut paren_depth = 0;
         let mut arg_idx: usize = 0;
+        // Empty parentheses are a call without arguments.
+        let mut nb_args: usize = 0;
         let mut args: MacroArguments = Default::default();
         let mut found_left_paren = false;
         let mut r_paren_offset: TextSize = TextSize::default();
@@ -123,6 +128,7 @@ impl ArgumentsCollector {
                 TokenKind::LParen => {
                     paren_depth += 1;
                     if paren_depth > 1 {
+                        nb_args = nb_args.max(1);
                         args[arg_idx].push(sub_token)
                     }
                 }
@@ -139,6 +145,7 @@ impl ArgumentsCollector {
                 TokenKind::Comma => {
                     match paren_depth.cmp(&1) {
                         Ordering::Equal => {
+                            nb_args = nb_args.max(1) + 1;
                             if arg_idx + 1 < nb_params {
                                 arg_idx += 1;
                             } else {
@@ -151,15 +158,21 @@ impl ArgumentsCollector {
                         Ordering::Less => (),
                     }
                 }
+                TokenKind::Newline | TokenKind::LineContinuation | TokenKind::Comment(_) => {
+                    if paren_depth > 0 {
+                        args[arg_idx].push(sub_token);
+                    }
+                }
                 _ => {
                     if paren_depth > 0 {
+                        nb_args = nb_args.max(1);
                         args[arg_idx].push(sub_token);
                     }
                 }
             }
         }
 
-        Some((args, r_paren_offset))
+        Some((args, nb_args, r_paren_offset))
     }
 }
 
@@ -183,12 +196,15 @@ impl ArgumentsCollector {
 /// * `symbol` - Identifier [symbol](Symbol) to expand.
 /// * `expansion_stack` - Expansion stack used instead of the lexer if it is not empty.
 /// * `allow_undefined_macros` - Should not found macros throw an error.
+/// * `argument_count_errors` - Calls of `symbol` whose number of arguments does not match the
+///   parameters of the macro. The macro is still expanded with the arguments of the call.
 pub(super) fn expand_identifier<T>(
     lexer: &mut T,
     macro_store: &mut MacroStore,
     symbol: &Symbol,
     expansion_stack: &mut Vec<Symbol>,
     allow_undefined_macros: bool,
+    argument_count_errors: &mut Vec<MacroArgumentCountError>,
 ) -> Result<Option<TextSize>, ExpansionError>
 where
     T: Iterator<Item = Symbol>,
@@ -207,7 +223,8 @@ where
         };
         match queued_symbol.symbol.token_kind {
             TokenKind::Identifier => {
-                let macro_ = match macro_store.get_mut(&queued_symbol.symbol.text()) {
+                macro_store.mark_used(&queued_symbol.symbol.text());
+                let macro_ = match macro_store.get(&queued_symbol.symbol.text()).cloned() {
                     Some(m) => m,
                     None => {
                         if !allow_undefined_macros {
@@ -224,14 +241,15 @@ where
                         continue;
                     }
                 };
+                let macro_ref = (macro_.file_id, macro_.idx);
                 let new_context = if macro_.params.is_none() {
                     expand_non_macro_define(
-                        macro_,
+                        &macro_,
                         symbol.delta,
                         queued_symbol.symbol.range.to_owned(),
                     )
                 } else {
-                    let Some((args, r_paren_offset_)) = &args_collector.collect_arguments(
+                    let Some((args, nb_args, r_paren_offset_)) = &args_collector.collect_arguments(
                         lexer,
                         &mut current_context,
                         macro_.nb_params as usize,
@@ -239,16 +257,29 @@ where
                         // The macro was not expanded, put it back on the expansion stack
                         // and disable it to avoid an infinite loop.
                         reversed_expansion_stack.push(queued_symbol.symbol);
-                        let cloned_macro = macro_.clone();
-                        macro_store.disable_macro(cloned_macro);
+                        macro_store.disable_macro(macro_);
                         context_stack.push(current_context);
                         continue;
                     };
                     if context_stack.is_empty() {
                         r_paren_offset = r_paren_offset_.to_owned().into();
+                        // Empty parentheses pass an empty argument to a macro with one parameter.
+                        let nb_params = macro_.nb_params as usize;
+                        if *nb_args != nb_params && !(nb_params == 1 && *nb_args == 0) {
+                            argument_count_errors.push(MacroArgumentCountError::new(
+                                queued_symbol.symbol.text().into(),
+                                nb_params,
+                                *nb_args,
+                                TextRange::new(
+                                    queued_symbol.symbol.range.start(),
+                                    *r_paren_offset_,
+                                ),
+                            ));
+                        }
                     }
-                    expand_macro(args, macro_, &queued_symbol.symbol, &symbol.delta)?
+                    expand_macro(args, &macro_, &queued_symbol.symbol, &symbol.delta)?
                 };
+                macro_store.record_expansion(macro_ref);
                 context_stack.push(current_context);
                 context_stack.push(new_context);
             }
@@ -291,7 +322,7 @@ fn expand_non_macro_define(
         .iter()
         .enumerate()
         .map(|(i, child)| {
-            let s = QueuedSymbol::new(child.into(), if i == 0 { delta } else { child.delta });
+            let s = QueuedSymbol::new(child.clone(), if i == 0 { delta } else { child.delta });
             s.symbol.range.clone_into(&mut prev_range);
             s
         })
@@ -328,15 +359,7 @@ fn expand_macro(
     let mut new_context = MacroContext::default();
     let mut consecutive_percent = 0;
     let mut stringize_delta = None;
-    for (i, child) in macro_
-        .body
-        .iter()
-        .map(|it| {
-            let it: Symbol = it.into();
-            it
-        })
-        .enumerate()
-    {
+    for (i, child) in macro_.body.iter().enumerate() {
         match &child.token_kind {
             TokenKind::Operator(Operator::Percent) => {
                 // Count consecutive % tokens.
@@ -433,8 +456,11 @@ pub struct Macro {
     pub(crate) idx: u32,
     pub(crate) params: Option<Vec<i8>>,
     pub(crate) nb_params: i8,
-    pub(crate) body: Vec<RangeLessSymbol>,
+    /// Symbols of the body, see [`range_less_symbol`](crate::symbol::range_less_symbol).
+    pub(crate) body: Vec<Symbol>,
     pub(crate) name_len: usize,
+    /// Doc comment (`/** ... */`) directly above the `#define`, if any.
+    pub(crate) docs: Option<SmolStr>,
 }
 
 impl DeepSizeOf for Macro {
@@ -443,7 +469,12 @@ impl DeepSizeOf for Macro {
             + self.idx.deep_size_of_children(context)
             + self.params.deep_size_of_children(context)
             + self.nb_params.deep_size_of_children(context)
-            + self.body.deep_size_of_children(context)
+            + self
+                .body
+                .iter()
+                .map(|it| std::mem::size_of::<Symbol>() + it.text().len())
+                .sum::<usize>()
+            + self.docs.as_ref().map_or(0, |it| it.len())
     }
 }
 
@@ -456,24 +487,117 @@ impl Macro {
             nb_params: 0,
             body: vec![],
             name_len: 0,
+            docs: None,
         }
     }
+
+    pub fn file_id(&self) -> FileId {
+        self.file_id
+    }
+
+    /// Index of the macro in the file it is defined in.
+    pub fn idx(&self) -> u32 {
+        self.idx
+    }
+
+    /// Parameters of the macro in the order they are declared, e.g `["%1", "%2"]` for
+    /// `#define FOO(%1,%2)`, or [`None`] if the macro does not take arguments.
+    pub fn params(&self) -> Option<Vec<SmolStr>> {
+        let mut params: Vec<_> = self
+            .params
+            .as_ref()?
+            .iter()
+            .enumerate()
+            .filter(|(_, pos)| **pos != -1)
+            .map(|(idx, pos)| (*pos, idx))
+            .collect();
+        params.sort_unstable();
+
+        params
+            .into_iter()
+            .map(|(_, idx)| format_smolstr!("%{}", idx))
+            .collect::<Vec<_>>()
+            .into()
+    }
+
+    /// Text the macro expands to, on a single line, e.g `a + %1` for `#define FOO(%1) a + %1`.
+    pub fn value(&self) -> String {
+        let mut res = String::new();
+        for symbol in self.body.iter().filter(|it| {
+            !matches!(
+                it.token_kind,
+                TokenKind::Newline | TokenKind::LineContinuation | TokenKind::Comment(_)
+            )
+        }) {
+            if !res.is_empty() && symbol.delta > 0 {
+                res.push(' ');
+            }
+            res.push_str(&symbol.text());
+        }
+
+        res
+    }
+
+    /// Raw text of the doc comment of the macro, including the comment delimiters.
+    pub fn docs(&self) -> Option<&str> {
+        self.docs.as_deref()
+    }
 }
 
 #[derive(Debug, Default)]
 pub struct MacroStore {
     idx: u32,
     map: MacrosMap,
-    disabled_macros: FxHashSet<Arc<Macro>>,
+    /// Builtin macros, see [`builtins`](crate::builtins). They are built on their first lookup,
+    /// and are not part of the output of the preprocessor.
+    builtins: Option<Builtins>,
+    /// Macros whose expansion is cancelled, compared by pointer so that the bodies of the macros
+    /// are not hashed on every identifier.
+    disabled_macros: Vec<Arc<Macro>>,
+    /// Names of the macros which were expanded or checked with `defined`.
+    used_macros: FxHashSet<SmolStr>,
+    /// Macros expanded since the last call to [`MacroStore::take_expansions`], as
+    /// `(file_id, idx)`, in the order of their expansion.
+    expansions: Vec<(FileId, u32)>,
+}
+
+#[derive(Debug)]
+struct Builtins {
+    file_id: FileId,
+    macros: OnceCell<MacrosMap>,
+    /// Names of the builtin macros which were undefined.
+    undefined: FxHashSet<SmolStr>,
 }
 
 impl MacroStore {
+    /// Create a store which also defines the builtin macros, e.g `__DATE__`.
+    ///
+    /// # Arguments
+    /// * `file_id` - [`FileId`] of the file being preprocessed.
+    pub fn with_builtins(file_id: FileId) -> Self {
+        Self {
+            builtins: Some(Builtins {
+                file_id,
+                macros: OnceCell::new(),
+                undefined: FxHashSet::default(),
+            }),
+            ..Default::default()
+        }
+    }
+
     pub fn get(&self, name: &SmolStr) -> Option<&Arc<Macro>> {
-        self.map.get(name)
+        self.map.get(name).or_else(|| self.get_builtin(name))
     }
 
-    pub fn get_mut(&mut self, name: &SmolStr) -> Option<&mut Arc<Macro>> {
-        self.map.get_mut(name)
+    fn get_builtin(&self, name: &SmolStr) -> Option<&Arc<Macro>> {
+        let builtins = self.builtins.as_ref()?;
+        if !BUILTIN_MACRO_NAMES.contains(&name.as_str()) || builtins.undefined.contains(name) {
+            return None;
+        }
+        builtins
+            .macros
+            .get_or_init(|| builtin_macros(builtins.file_id))
+            .get(name)
     }
 
     pub fn insert_macro(&mut self, name: SmolStr, mut macro_: Macro) {
@@ -491,23 +615,59 @@ impl MacroStore {
     }
 
     pub fn remove_macro(&mut self, name: &SmolStr) {
-        self.map.remove(name);
+        if self.map.remove(name).is_none() && self.get_builtin(name).is_some() {
+            if let Some(builtins) = &mut self.builtins {
+                builtins.undefined.insert(name.clone());
+            }
+        }
     }
 
     pub fn disable_macro(&mut self, macro_: Arc<Macro>) {
-        self.disabled_macros.insert(macro_);
+        if !self.is_macro_disabled(&macro_) {
+            self.disabled_macros.push(macro_);
+        }
     }
 
     pub fn enable_macro(&mut self, macro_: &Arc<Macro>) {
-        self.disabled_macros.remove(macro_);
+        self.disabled_macros.retain(|it| !Arc::ptr_eq(it, macro_));
+    }
+
+    pub fn is_macro_disabled(&self, macro_: &Arc<Macro>) -> bool {
+        self.disabled_macros
+            .iter()
+            .any(|it| Arc::ptr_eq(it, macro_))
+    }
+
+    /// Record that the macro named `name` is expanded, if it is defined.
+    pub fn mark_used(&mut self, name: &SmolStr) {
+        if self.map.contains_key(name) {
+            self.used_macros.insert(name.clone());
+        }
+    }
+
+    /// Record that `name` is checked with `defined`.
+    ///
+    /// The name is recorded even if the macro is not defined yet, which is the case of include
+    /// guards, as the check still refers to the macro.
+    pub fn mark_referenced(&mut self, name: SmolStr) {
+        self.used_macros.insert(name);
+    }
+
+    /// Record that the macro `(file_id, idx)` was expanded.
+    pub fn record_expansion(&mut self, macro_ref: (FileId, u32)) {
+        if !self.expansions.contains(&macro_ref) {
+            self.expansions.push(macro_ref);
+        }
     }
 
-    pub fn is_macro_disabled(&mut self, macro_: &Arc<Macro>) -> bool {
-        self.disabled_macros.contains(macro_)
+    /// Returns the macros expanded since the last call, and forget them.
+    pub fn take_expansions(&mut self) -> Vec<(FileId, u32)> {
+        std::mem::take(&mut self.expansions)
     }
 
-    pub fn into_macros_map(self) -> MacrosMap {
-        self.map
+    /// Split the store into its macros and the names of the macros which were used.
+    pub fn into_parts(self) -> (MacrosMap, FxHashSet<SmolStr>) {
+        (self.map, self.used_macros)
     }
 }
 
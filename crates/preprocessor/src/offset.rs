@@ -29,9 +29,16 @@
 //! This modules introduces several data structures, most notably [SourceMap]
 //! which allows to go back and forth between a user range and a server range.
 //!
+//! All the queries of [SourceMap] are binary searches over the mapped ranges,
+//! which are kept sorted in both directions:
+//! * user to server: [SourceMap::closest_s_position] and [SourceMap::closest_s_range],
+//! * server to user: [SourceMap::closest_u_position] and [SourceMap::closest_u_range],
+//! * batches of ranges: [SourceMap::closest_s_ranges] and [SourceMap::closest_u_ranges].
+//!
 
 use itertools::Itertools;
 use la_arena::{Arena, Idx};
+use smol_str::SmolStr;
 use sourcepawn_lexer::{TextRange, TextSize};
 use vfs::FileId;
 
@@ -53,6 +60,17 @@ pub struct ExpandedSymbolOffset {
 
     /// The [`file_id`](FileId) of the file containing the macro that was expanded.
     file_id: FileId,
+
+    /// Whether the symbol was found in a preprocessor condition or in an `#undef`, in which case
+    /// the expanded text is not part of the preprocessed text.
+    in_condition: bool,
+
+    /// The text the symbol expanded to, if it was expanded in a preprocessor condition.
+    condition_text: Option<SmolStr>,
+
+    /// The macros expanded inside the expansion of the symbol, as `(file_id, idx)`, in the order
+    /// they were expanded.
+    nested_macros: Vec<(FileId, u32)>,
 }
 
 impl ExpandedSymbolOffset {
@@ -75,8 +93,29 @@ impl ExpandedSymbolOffset {
     pub fn file_id(&self) -> FileId {
         self.file_id
     }
+
+    pub fn in_condition(&self) -> bool {
+        self.in_condition
+    }
+
+    pub fn condition_text(&self) -> Option<&SmolStr> {
+        self.condition_text.as_ref()
+    }
+
+    /// The macros expanded by the macros the symbol expanded to, as `(file_id, idx)`.
+    ///
+    /// Together with [`ExpandedSymbolOffset::file_id`] and [`ExpandedSymbolOffset::idx`], this is
+    /// the provenance of the text in [`ExpandedSymbolOffset::expanded_range`].
+    pub fn nested_macros(&self) -> &[(FileId, u32)] {
+        &self.nested_macros
+    }
 }
 
+/// Mapping between the user ranges of a file and the server ranges of its preprocessed text.
+///
+/// The tokens copied from the source are mapped range by range. The tokens produced by a macro
+/// expansion are mapped as a whole to the macro call they come from, see
+/// [`ExpandedSymbolOffset`].
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct SourceMap {
     arena: Arena<TextRange>,
@@ -103,19 +142,32 @@ impl SourceMap {
         self.s_range_to_u_range.push((s_range_idx, u_range_idx));
     }
 
+    /// Push a symbol which was expanded in the preprocessed text.
+    ///
+    /// # Arguments
+    /// * `range` - Range of the symbol, including the arguments of the macro call.
+    /// * `start_offset` - Offset of the start of the expansion in the preprocessed text.
+    /// * `end_offset` - Offset of the end of the expansion in the preprocessed text.
+    /// * `macro_` - [`Macro`] the symbol resolved to.
+    /// * `nested_macros` - Macros expanded during the expansion, as `(file_id, idx)`.
     pub fn push_expanded_symbol(
         &mut self,
         range: TextRange,
         start_offset: u32,
         end_offset: u32,
         macro_: &Macro,
+        mut nested_macros: Vec<(FileId, u32)>,
     ) {
+        nested_macros.retain(|it| *it != (macro_.file_id, macro_.idx));
         self.expanded_symbols.push(ExpandedSymbolOffset {
             range,
             expanded_range: TextRange::new(start_offset.into(), end_offset.into()),
             idx: macro_.idx,
             name_len: TextSize::new(macro_.name_len as u32),
             file_id: macro_.file_id,
+            in_condition: false,
+            condition_text: None,
+            nested_macros,
         });
         // self.push_new_range(
         //     range,
@@ -123,6 +175,32 @@ impl SourceMap {
         // );
     }
 
+    /// Push a symbol which was resolved to a macro in a preprocessor condition or in an `#undef`.
+    ///
+    /// # Arguments
+    /// * `range` - Range of the symbol, including the arguments of the macro call.
+    /// * `condition_text` - Text the symbol expanded to in a condition, or [`None`] if the symbol
+    ///   was not expanded, e.g when checked with `defined` or in an `#undef`.
+    /// * `macro_` - [`Macro`] the symbol resolved to.
+    pub fn push_condition_symbol(
+        &mut self,
+        range: TextRange,
+        condition_text: Option<SmolStr>,
+        macro_: &Macro,
+    ) {
+        let name_len = TextSize::new(macro_.name_len as u32);
+        self.expanded_symbols.push(ExpandedSymbolOffset {
+            range,
+            expanded_range: TextRange::at(range.start(), name_len),
+            idx: macro_.idx,
+            name_len,
+            file_id: macro_.file_id,
+            in_condition: true,
+            condition_text,
+            nested_macros: vec![],
+        });
+    }
+
     pub fn expanded_symbol_from_u_pos(&self, u_pos: TextSize) -> Option<ExpandedSymbolOffset> {
         let idx = self
             .expanded_symbols
@@ -159,6 +237,9 @@ impl SourceMap {
         Some(self.expanded_symbols[idx].clone())
     }
 
+    /// Find the server position of a user position.
+    ///
+    /// A position inside of a macro call is mapped to the start of its expansion.
     pub fn closest_s_position(&self, u_pos: TextSize) -> Option<TextSize> {
         if let Some(symbol) = self.expanded_symbol_from_u_pos(u_pos) {
             return Some(symbol.expanded_range.start());
@@ -181,11 +262,20 @@ impl SourceMap {
         self.arena[s_range_idx].start().checked_add(delta)
     }
 
+    /// Same as [`SourceMap::closest_s_position`], but falls back to the user position, clamped to
+    /// the preprocessed text, if it is not mapped.
     pub fn closest_s_position_always(&self, u_pos: TextSize) -> TextSize {
         self.closest_s_position(u_pos)
             .unwrap_or_else(|| std::cmp::min(self.max_preprocessed_text_offset(), u_pos))
     }
 
+    /// Find the user position of a server position.
+    ///
+    /// # Arguments
+    /// * `s_pos` - The server position.
+    /// * `end` - Whether `s_pos` is the end of a range, in which case it is matched against the
+    ///   ends of the mapped ranges. A position inside of a macro expansion is mapped to the start,
+    ///   or end, of the name of the macro.
     pub fn closest_u_position(&self, s_pos: TextSize, end: bool) -> Option<TextSize> {
         let Some(idx) = self
             .s_range_to_u_range
@@ -225,6 +315,8 @@ impl SourceMap {
         }
     }
 
+    /// Same as [`SourceMap::closest_u_position`], but falls back to the server position, clamped
+    /// to the source, if it is not mapped.
     pub fn closest_u_position_always(&self, s_pos: TextSize, end: bool) -> TextSize {
         self.closest_u_position(s_pos, end)
             .unwrap_or_else(|| self.clamp_u_pos(s_pos))
@@ -240,6 +332,7 @@ impl SourceMap {
         TextRange::new(start, end)
     }
 
+    /// Find the user range of a server range.
     pub fn closest_u_range(&self, s_range: TextRange) -> Option<TextRange> {
         let start = self.closest_u_position_always(s_range.start(), false);
         let end = self.closest_u_position_always(s_range.end(), true);
@@ -249,11 +342,142 @@ impl SourceMap {
         Some(TextRange::new(start, end))
     }
 
+    /// Same as [`SourceMap::closest_u_range`], but falls back to the server range, clamped to the
+    /// source, if it is not mapped.
     pub fn closest_u_range_always(&self, s_range: TextRange) -> TextRange {
         self.closest_u_range(s_range)
             .unwrap_or_else(|| self.clamp_u_range(&s_range))
     }
 
+    /// Find the server range of a user range.
+    ///
+    /// A range which starts, or ends, inside of a macro call is extended to the whole expansion
+    /// of the macro.
+    pub fn closest_s_range(&self, u_range: TextRange) -> Option<TextRange> {
+        let start = self.closest_s_position(u_range.start())?;
+        let end = match self.expanded_symbol_from_u_pos(u_range.end()) {
+            Some(symbol) if symbol.range.start() < u_range.end() => symbol.expanded_range.end(),
+            _ => self.closest_s_position(u_range.end())?,
+        };
+        if start > end {
+            return None;
+        }
+        Some(TextRange::new(start, end))
+    }
+
+    /// Map a batch of server ranges to user ranges, see [`SourceMap::closest_u_range_always`].
+    pub fn closest_u_ranges<'a>(
+        &'a self,
+        s_ranges: impl IntoIterator<Item = TextRange> + 'a,
+    ) -> impl Iterator<Item = TextRange> + 'a {
+        s_ranges
+            .into_iter()
+            .map(|s_range| self.closest_u_range_always(s_range))
+    }
+
+    /// Map a batch of user ranges to server ranges, see [`SourceMap::closest_s_range`].
+    pub fn closest_s_ranges<'a>(
+        &'a self,
+        u_ranges: impl IntoIterator<Item = TextRange> + 'a,
+    ) -> impl Iterator<Item = Option<TextRange>> + 'a {
+        u_ranges
+            .into_iter()
+            .map(|u_range| self.closest_s_range(u_range))
+    }
+
+    /// Find the user range of a name, e.g the name of a function, from its server range.
+    ///
+    /// A name produced by a macro expansion is mapped to the arguments of the macro call it comes
+    /// from, e.g `Foo` in `PLUGIN_NATIVE(Foo)` with `#define PLUGIN_NATIVE(%1) any Native_%1()`,
+    /// instead of the name of the macro.
+    pub fn closest_u_name_range(&self, s_range: TextRange) -> TextRange {
+        let expanded_symbol = self
+            .expanded_symbol_from_s_pos(s_range.start())
+            .filter(|it| !it.in_condition);
+        if let Some(expanded_symbol) = expanded_symbol {
+            let start = self
+                .s_range_to_u_range
+                .partition_point(|&(s_range_idx, _)| {
+                    self.arena[s_range_idx].start() < s_range.start()
+                });
+            let argument_range = self.s_range_to_u_range[start..]
+                .iter()
+                .take_while(|&&(s_range_idx, _)| self.arena[s_range_idx].start() < s_range.end())
+                .filter(|&&(s_range_idx, _)| s_range.contains_range(self.arena[s_range_idx]))
+                .map(|&(_, u_range_idx)| self.arena[u_range_idx])
+                .filter(|u_range| expanded_symbol.range.contains_range(*u_range))
+                .reduce(TextRange::cover);
+            if let Some(argument_range) = argument_range {
+                return argument_range;
+            }
+        }
+
+        self.closest_u_range_always(s_range)
+    }
+
+    /// Find the server range of a token from its exact user range.
+    pub fn s_range_from_u_range(&self, u_range: TextRange) -> Option<TextRange> {
+        let idx = self
+            .u_range_to_s_range
+            .binary_search_by(|&(u_range_idx, _)| self.arena[u_range_idx].ordering(u_range))
+            .ok()?;
+        let (u_range_idx, s_range_idx) = self.u_range_to_s_range[idx];
+        if self.arena[u_range_idx] != u_range {
+            return None;
+        }
+        Some(self.arena[s_range_idx])
+    }
+
+    /// Replace the ranges mapped inside of `u_window` by `new_ranges` and shift all the ranges
+    /// that come after it by `delta`.
+    ///
+    /// This assumes that the text in `u_window` was copied verbatim in the preprocessed text,
+    /// i.e that the user and server offsets move by the same `delta` after the window.
+    pub(crate) fn splice(
+        &self,
+        u_window: TextRange,
+        delta: i64,
+        new_ranges: impl IntoIterator<Item = (TextRange, TextRange)>,
+        source_len: usize,
+        preprocecessed_text_len: usize,
+    ) -> SourceMap {
+        let mut res = SourceMap::default();
+        let mut new_ranges = new_ranges.into_iter().peekable();
+        for (u_range, s_range) in self.u_range_to_s_range_vec() {
+            if u_range.start() >= u_window.start() && u_range.end() <= u_window.end() {
+                continue;
+            }
+            if u_range.start() >= u_window.end() {
+                for (u_range, s_range) in new_ranges.by_ref() {
+                    res.push_new_range(u_range, s_range);
+                }
+                res.push_new_range(shift_range(u_range, delta), shift_range(s_range, delta));
+            } else {
+                res.push_new_range(u_range, s_range);
+            }
+        }
+        for (u_range, s_range) in new_ranges {
+            res.push_new_range(u_range, s_range);
+        }
+        res.expanded_symbols = self
+            .expanded_symbols
+            .iter()
+            .cloned()
+            .map(|mut symbol| {
+                if symbol.range.start() >= u_window.end() {
+                    symbol.range = shift_range(symbol.range, delta);
+                    symbol.expanded_range = shift_range(symbol.expanded_range, delta);
+                }
+                symbol
+            })
+            .collect();
+        res.set_source_len(source_len);
+        res.set_preprocecessed_text_len(preprocecessed_text_len);
+        res.sort();
+
+        res
+    }
+
     pub fn shrink_to_fit(&mut self) {
         self.arena.shrink_to_fit();
         self.u_range_to_s_range.shrink_to_fit();
@@ -323,3 +547,12 @@ impl SourceMap {
         }
     }
 }
+
+/// Shift a [`range`](TextRange) by a signed `delta`.
+pub(crate) fn shift_range(range: TextRange, delta: i64) -> TextRange {
+    let shift = |offset: TextSize| {
+        let offset: u32 = offset.into();
+        TextSize::new((offset as i64 + delta).max(0) as u32)
+    };
+    TextRange::new(shift(range.start()), shift(range.end()))
+}
@@ -0,0 +1,117 @@
+//! Tracking of the style pragmas of a file.
+//!
+//! spcomp lets a file require semicolons at the end of statements and new-style declarations,
+//! either with `#pragma semicolon 1` and `#pragma newdecls required`, or with the
+//! `#require_semicolons` and `#require_newdecls` directives. The state in effect at a given
+//! offset is the one set by the last of these directives before it.
+
+use sourcepawn_lexer::{PreprocDir, TextRange, TextSize};
+
+use crate::offset::shift_range;
+
+/// Style requirement toggled by a pragma.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StylePragmaKind {
+    /// Statements must end with a semicolon.
+    Semicolon,
+    /// Declarations must use the new syntax.
+    Newdecls,
+}
+
+/// A directive changing a style requirement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StylePragma {
+    kind: StylePragmaKind,
+    required: bool,
+    range: TextRange,
+}
+
+impl StylePragma {
+    /// Parse a style pragma from a directive and its text, including the arguments.
+    ///
+    /// Returns `None` if the directive does not change a style requirement.
+    pub(crate) fn parse(dir: &PreprocDir, text: &str, range: TextRange) -> Option<Self> {
+        let (kind, required) = match dir {
+            PreprocDir::MRequireSemi => (StylePragmaKind::Semicolon, true),
+            PreprocDir::MOptionalSemi => (StylePragmaKind::Semicolon, false),
+            PreprocDir::MRequireNewdecls => (StylePragmaKind::Newdecls, true),
+            PreprocDir::MOptionalNewdecls => (StylePragmaKind::Newdecls, false),
+            PreprocDir::MPragma => {
+                let mut args = text.trim_start_matches("#pragma").split_whitespace();
+                match (args.next()?, args.next()?) {
+                    // spcomp treats any non zero value as true.
+                    ("semicolon", value) => {
+                        (StylePragmaKind::Semicolon, value.parse::<i64>().ok()? != 0)
+                    }
+                    ("newdecls", "required") => (StylePragmaKind::Newdecls, true),
+                    ("newdecls", "optional") => (StylePragmaKind::Newdecls, false),
+                    _ => return None,
+                }
+            }
+            _ => return None,
+        };
+
+        Some(Self {
+            kind,
+            required,
+            range,
+        })
+    }
+
+    pub fn kind(&self) -> StylePragmaKind {
+        self.kind
+    }
+
+    pub fn required(&self) -> bool {
+        self.required
+    }
+
+    pub fn range(&self) -> &TextRange {
+        &self.range
+    }
+}
+
+/// Style pragmas of a file, in the order they appear in the source.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct StylePragmas(Vec<StylePragma>);
+
+impl StylePragmas {
+    pub(crate) fn push(&mut self, pragma: StylePragma) {
+        self.0.push(pragma);
+    }
+
+    pub(crate) fn shrink_to_fit(&mut self) {
+        self.0.shrink_to_fit();
+    }
+
+    /// Shift the pragmas starting after `offset` by `delta`.
+    pub(crate) fn shift_after(&mut self, offset: TextSize, delta: i64) {
+        self.0
+            .iter_mut()
+            .filter(|pragma| pragma.range.start() >= offset)
+            .for_each(|pragma| pragma.range = shift_range(pragma.range, delta));
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &StylePragma> {
+        self.0.iter()
+    }
+
+    /// Whether statements must end with a semicolon at `offset` in the source.
+    pub fn requires_semicolons_at(&self, offset: TextSize) -> bool {
+        self.required_at(StylePragmaKind::Semicolon, offset)
+    }
+
+    /// Whether declarations must use the new syntax at `offset` in the source.
+    pub fn requires_newdecls_at(&self, offset: TextSize) -> bool {
+        self.required_at(StylePragmaKind::Newdecls, offset)
+    }
+
+    fn required_at(&self, kind: StylePragmaKind, offset: TextSize) -> bool {
+        self.0
+            .iter()
+            .take_while(|pragma| pragma.range.end() <= offset)
+            .filter(|pragma| pragma.kind == kind)
+            .last()
+            .is_some_and(|pragma| pragma.required)
+    }
+}
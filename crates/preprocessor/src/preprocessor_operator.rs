@@ -1,7 +1,7 @@
 use anyhow::bail;
 use sourcepawn_lexer::{Operator, TextRange};
 
-use crate::evaluator::OutputStack;
+use crate::evaluator::{OutputStack, Value};
 
 use super::errors::EvaluationError;
 
@@ -162,11 +162,13 @@ impl PreOperator {
             let right = stack
                 .pop()
                 .ok_or_else(|| EvaluationError::new("Expected expression.".to_string(), *range))?;
-            let result: i32 = match self {
-                PreOperator::Not => (!to_bool(right)).into(),
-                PreOperator::Tilde => !right,
-                PreOperator::Negate => -right,
-                PreOperator::Confirm => right,
+            let result = match (self, right) {
+                (PreOperator::Not, right) => Value::from(!right.to_bool()),
+                (PreOperator::Tilde, Value::Int(right)) => Value::Int(!right),
+                (PreOperator::Negate, Value::Int(right)) => Value::Int(right.wrapping_neg()),
+                (PreOperator::Negate, Value::Float(right)) => Value::Float(-right),
+                (PreOperator::Confirm, right) => right,
+                (PreOperator::Tilde, Value::Float(_)) => return Err(self.float_error(range)),
                 _ => unreachable!(),
             };
             stack.push(result);
@@ -178,26 +180,42 @@ impl PreOperator {
         let left = stack.pop().ok_or_else(|| {
             EvaluationError::new("Expected left hand side expression.".to_string(), *range)
         })?;
-        let result: i32 = match self {
+        let result = match (left, right) {
+            (Value::Int(left), Value::Int(right)) => self.process_int_op(left, right, range)?,
+            (left, right) => self.process_float_op(left.to_float(), right.to_float(), range)?,
+        };
+        stack.push(result);
+
+        Ok(())
+    }
+
+    fn process_int_op(
+        &self,
+        left: i32,
+        right: i32,
+        range: &TextRange,
+    ) -> Result<Value, EvaluationError> {
+        let division_by_zero = || EvaluationError::new("Division by zero.".to_string(), *range);
+        let result = match self {
             PreOperator::Equals => (left == right).into(),
             PreOperator::NotEquals => (left != right).into(),
             PreOperator::Lt => (left < right).into(),
             PreOperator::Gt => (left > right).into(),
             PreOperator::Le => (left <= right).into(),
             PreOperator::Ge => (left >= right).into(),
-            PreOperator::Plus => left + right,
-            PreOperator::Minus => left - right,
-            PreOperator::Slash => left / right,
-            PreOperator::Star => left * right,
+            PreOperator::Plus => left.wrapping_add(right).into(),
+            PreOperator::Minus => left.wrapping_sub(right).into(),
+            PreOperator::Slash => left.checked_div(right).ok_or_else(division_by_zero)?.into(),
+            PreOperator::Star => left.wrapping_mul(right).into(),
             PreOperator::And => (to_bool(left) && to_bool(right)).into(),
             PreOperator::Or => (to_bool(left) || to_bool(right)).into(),
-            PreOperator::Bitor => left | right,
-            PreOperator::Bitxor => left ^ right,
-            PreOperator::Ampersand => left & right,
-            PreOperator::Shl => left << right,
-            PreOperator::Shr => left >> right,
-            PreOperator::Ushr => (left as u32 >> right as u32) as i32,
-            PreOperator::Percent => left % right,
+            PreOperator::Bitor => (left | right).into(),
+            PreOperator::Bitxor => (left ^ right).into(),
+            PreOperator::Ampersand => (left & right).into(),
+            PreOperator::Shl => left.wrapping_shl(right as u32).into(),
+            PreOperator::Shr => left.wrapping_shr(right as u32).into(),
+            PreOperator::Ushr => ((left as u32).wrapping_shr(right as u32) as i32).into(),
+            PreOperator::Percent => left.checked_rem(right).ok_or_else(division_by_zero)?.into(),
             PreOperator::Defined
             | PreOperator::Qmark
             | PreOperator::Not
@@ -207,9 +225,54 @@ impl PreOperator {
             | PreOperator::LParen
             | PreOperator::RParen => unreachable!(),
         };
-        stack.push(result);
 
-        Ok(())
+        Ok(result)
+    }
+
+    fn process_float_op(
+        &self,
+        left: f32,
+        right: f32,
+        range: &TextRange,
+    ) -> Result<Value, EvaluationError> {
+        let result = match self {
+            PreOperator::Equals => (left == right).into(),
+            PreOperator::NotEquals => (left != right).into(),
+            PreOperator::Lt => (left < right).into(),
+            PreOperator::Gt => (left > right).into(),
+            PreOperator::Le => (left <= right).into(),
+            PreOperator::Ge => (left >= right).into(),
+            PreOperator::Plus => (left + right).into(),
+            PreOperator::Minus => (left - right).into(),
+            PreOperator::Slash => (left / right).into(),
+            PreOperator::Star => (left * right).into(),
+            PreOperator::And => (left != 0.0 && right != 0.0).into(),
+            PreOperator::Or => (left != 0.0 || right != 0.0).into(),
+            PreOperator::Bitor
+            | PreOperator::Bitxor
+            | PreOperator::Ampersand
+            | PreOperator::Shl
+            | PreOperator::Shr
+            | PreOperator::Ushr
+            | PreOperator::Percent => return Err(self.float_error(range)),
+            PreOperator::Defined
+            | PreOperator::Qmark
+            | PreOperator::Not
+            | PreOperator::Tilde
+            | PreOperator::Negate
+            | PreOperator::Confirm
+            | PreOperator::LParen
+            | PreOperator::RParen => unreachable!(),
+        };
+
+        Ok(result)
+    }
+
+    fn float_error(&self, range: &TextRange) -> EvaluationError {
+        EvaluationError::new(
+            format!("Operator {:?} is not supported on floats.", self),
+            *range,
+        )
     }
 }
 
@@ -1,42 +1,68 @@
 use std::sync::Arc;
 
-use fxhash::FxHashMap;
-use sourcepawn_lexer::TextRange;
+use fxhash::{FxHashMap, FxHashSet};
+use smol_str::SmolStr;
+use sourcepawn_lexer::{TextRange, TextSize};
 
-use crate::{errors::PreprocessorErrors, macros::MacrosMap, offset::SourceMap};
+use crate::{errors::PreprocessorErrors, macros::MacrosMap, offset::SourceMap, StylePragmas};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct PreprocessingResult {
     preprocessed_text: Arc<str>,
     macros: MacrosMap,
+    /// Names of the macros expanded or checked with `defined` in the file.
+    used_macros: FxHashSet<SmolStr>,
     source_map: SourceMap,
     errors: PreprocessorErrors,
     inactive_ranges: Vec<TextRange>,
+    style_pragmas: StylePragmas,
+    /// Offset of the first active `#endinput` directive of the file.
+    endinput: Option<TextSize>,
+
+    /// Whether the preprocessor reached the end of the file without bailing out.
+    complete: bool,
 }
 
 impl PreprocessingResult {
     pub(crate) fn new(
         preprocessed_text: Arc<str>,
         macros: MacrosMap,
+        used_macros: FxHashSet<SmolStr>,
         mut source_map: SourceMap,
         errors: PreprocessorErrors,
         inactive_ranges: Vec<TextRange>,
+        style_pragmas: StylePragmas,
     ) -> Self {
         source_map.sort();
         Self {
             preprocessed_text,
             macros,
+            used_macros,
             source_map,
             errors,
             inactive_ranges,
+            style_pragmas,
+            endinput: None,
+            complete: true,
         }
     }
 
+    /// Mark the result as incomplete, i.e the preprocessor had to stop before the end of the file.
+    pub(crate) fn set_incomplete(&mut self) {
+        self.complete = false;
+    }
+
+    pub(crate) fn set_endinput(&mut self, endinput: Option<TextSize>) {
+        self.endinput = endinput;
+    }
+
     pub fn shrink_to_fit(&mut self) {
         self.macros.shrink_to_fit();
+        self.used_macros.shrink_to_fit();
         self.source_map.shrink_to_fit();
         self.errors.shrink_to_fit();
         self.inactive_ranges.shrink_to_fit();
+        self.style_pragmas.shrink_to_fit();
     }
 
     pub fn default(text: &str) -> Self {
@@ -46,9 +72,13 @@ impl PreprocessingResult {
         Self {
             preprocessed_text: text.to_string().into(),
             macros: FxHashMap::default(),
+            used_macros: FxHashSet::default(),
             source_map,
             errors: Default::default(),
             inactive_ranges: Default::default(),
+            style_pragmas: Default::default(),
+            endinput: None,
+            complete: true,
         }
     }
 
@@ -60,6 +90,10 @@ impl PreprocessingResult {
         &self.macros
     }
 
+    pub fn used_macros(&self) -> &FxHashSet<SmolStr> {
+        &self.used_macros
+    }
+
     pub fn source_map(&self) -> &SourceMap {
         &self.source_map
     }
@@ -71,4 +105,19 @@ impl PreprocessingResult {
     pub fn inactive_ranges(&self) -> &[TextRange] {
         &self.inactive_ranges
     }
+
+    /// `#pragma semicolon` and `#pragma newdecls` directives of the file, and their equivalents.
+    pub fn style_pragmas(&self) -> &StylePragmas {
+        &self.style_pragmas
+    }
+
+    /// Offset of the first `#endinput` directive reached by the preprocessor, after which spcomp
+    /// stops reading the file.
+    pub fn endinput(&self) -> Option<TextSize> {
+        self.endinput
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
 }
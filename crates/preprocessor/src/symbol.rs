@@ -1,49 +1,15 @@
-use std::hash::Hash;
-
-use deepsize::DeepSizeOf;
-use smol_str::SmolStr;
-use sourcepawn_lexer::{Delta, Symbol, TextRange, TokenKind};
-
-/// Wrapper around `Symbol` that does not contain range information.
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct RangeLessSymbol {
-    pub(crate) token_kind: TokenKind,
-    text: SmolStr,
-    pub(crate) delta: Delta,
-}
-
-impl DeepSizeOf for RangeLessSymbol {
-    fn deep_size_of_children(&self, context: &mut deepsize::Context) -> usize {
-        self.text.deep_size_of_children(context)
-    }
-}
-
-impl Hash for RangeLessSymbol {
-    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        self.token_kind.hash(state);
-        self.text.hash(state);
-        self.delta.hash(state);
-    }
-}
-
-impl From<Symbol> for RangeLessSymbol {
-    fn from(symbol: Symbol) -> Self {
-        Self {
-            token_kind: symbol.token_kind,
-            text: symbol.inline_text(), // TODO: Maybe use an option here?
-            delta: symbol.delta,
-        }
-    }
-}
-
-#[allow(clippy::from_over_into)]
-impl Into<Symbol> for &RangeLessSymbol {
-    fn into(self) -> Symbol {
-        Symbol::new(
-            self.token_kind,
-            Some(&self.text),
-            TextRange::default(),
-            self.delta,
-        )
-    }
+use sourcepawn_lexer::{Symbol, TextRange};
+
+/// Copy of a [`Symbol`] for the body of a [macro](crate::Macro), without its range and with the
+/// line continuations removed from its text.
+///
+/// The body is built once when the macro is defined, and its symbols are cloned as they are
+/// when the macro is expanded.
+pub(crate) fn range_less_symbol(symbol: &Symbol) -> Symbol {
+    Symbol::new(
+        symbol.token_kind,
+        Some(&symbol.inline_text()),
+        TextRange::default(),
+        symbol.delta,
+    )
 }
@@ -1,6 +1,16 @@
+use std::sync::Arc;
+
 use sourcepawn_lexer::{SourcepawnLexer, TokenKind};
+use vfs::FileId;
 
-use crate::{evaluator::IfCondition, offset::SourceMap, MacroStore};
+use crate::{
+    db::PreprocessingParams,
+    evaluator::IfCondition,
+    incremental::{ResolvedInclude, ResolvedIncludes},
+    offset::SourceMap,
+    MacroStore, MacrosMap, PreprocessingCache, PreprocessingCacheStats, PreprocessingResult,
+    SourcepawnPreprocessor,
+};
 
 fn evaluate_if_condition(input: &str) -> bool {
     let mut lexer = SourcepawnLexer::new(input);
@@ -154,3 +164,169 @@ fn if_directive_char_2() {
 
     assert!(evaluate_if_condition(input));
 }
+
+#[test]
+fn if_directive_char_3() {
+    let input = r#"#if 'a' > 64 && 'A' == 65"#;
+
+    assert!(evaluate_if_condition(input));
+}
+
+#[test]
+fn if_directive_char_escape() {
+    let input = r#"#if '\n' == 10 && '\x41' == 'A' && '\\' == 92"#;
+
+    assert!(evaluate_if_condition(input));
+}
+
+#[test]
+fn if_directive_string() {
+    let input = r#"#if "a" == "a""#;
+
+    assert!(!evaluate_if_condition(input));
+}
+
+#[test]
+fn if_directive_float_1() {
+    let input = r#"#if 1.8 >= 1.7"#;
+
+    assert!(evaluate_if_condition(input));
+}
+
+#[test]
+fn if_directive_float_2() {
+    let input = r#"#if 2 > 1.5 && 1.0 == 1"#;
+
+    assert!(evaluate_if_condition(input));
+}
+
+#[test]
+fn if_directive_float_3() {
+    let input = r#"#if 0.5 + 0.5 < 1"#;
+
+    assert!(!evaluate_if_condition(input));
+}
+
+#[test]
+fn if_directive_float_bitwise() {
+    let input = r#"#if 1.5 | 1"#;
+
+    assert!(!evaluate_if_condition(input));
+}
+
+#[test]
+fn if_directive_division_by_zero() {
+    let input = r#"#if 1 / 0"#;
+
+    assert!(!evaluate_if_condition(input));
+}
+
+#[test]
+fn if_directive_unresolved_macros() {
+    let input = r#"#if FOO || BAR"#;
+    let mut lexer = SourcepawnLexer::new(input);
+    let mut macro_store = MacroStore::default();
+    let mut source_map = SourceMap::default();
+    let mut if_condition = IfCondition::new(&mut macro_store, &mut source_map);
+    lexer.next();
+    while lexer.in_preprocessor() {
+        if let Some(symbol) = lexer.next() {
+            if_condition.symbols.push(symbol);
+        } else {
+            break;
+        }
+    }
+
+    assert_eq!(if_condition.evaluate(), Ok(false));
+    let names = if_condition
+        .macro_not_found_errors
+        .iter()
+        .map(|err| err.macro_name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["FOO", "BAR"]);
+}
+
+fn preprocess(file_id: FileId, input: &str) -> PreprocessingResult {
+    fn extend_macros(_: &mut MacrosMap, _: String, _: FileId, _: bool) -> anyhow::Result<()> {
+        Ok(())
+    }
+    SourcepawnPreprocessor::new(file_id, input, &mut extend_macros).preprocess_input()
+}
+
+#[test]
+fn preprocessing_cache_hits() {
+    let file_id = FileId::from(0);
+    let text: Arc<str> = "#define FOO 1\nint foo = FOO;\n".into();
+    let params: Arc<PreprocessingParams> = Default::default();
+    let result: Arc<_> = preprocess(file_id, &text).into();
+    let cache = PreprocessingCache::default();
+    assert!(cache.reuse(file_id, &text, &params, |_| true).is_none());
+    cache.insert(
+        file_id,
+        text.clone(),
+        params.clone(),
+        Default::default(),
+        result.clone(),
+    );
+
+    let same_text: Arc<str> = text.to_string().into();
+    let reused = cache.reuse(file_id, &same_text, &params, |_| true).unwrap();
+    assert!(Arc::ptr_eq(&reused, &result));
+
+    let edited: Arc<str> = "#define FOO 1\nint bar = FOO;\n".into();
+    let patched = cache.reuse(file_id, &edited, &params, |_| true).unwrap();
+    assert_eq!(
+        patched.preprocessed_text(),
+        preprocess(file_id, &edited).preprocessed_text()
+    );
+    assert_eq!(
+        cache.stats(),
+        PreprocessingCacheStats {
+            hits: 1,
+            patches: 1,
+            misses: 1,
+        }
+    );
+}
+
+#[test]
+fn preprocessing_cache_resolves_includes_again() {
+    let file_id = FileId::from(0);
+    let text: Arc<str> = "#include \"foo\"\nint foo;\n".into();
+    let params: Arc<PreprocessingParams> = Default::default();
+    let includes = Arc::new(ResolvedIncludes {
+        implicit_includes: Default::default(),
+        includes: vec![ResolvedInclude {
+            file_id,
+            path: "foo.inc".to_string(),
+            quoted: true,
+            inc_file_id: Some(FileId::from(1)),
+        }],
+    });
+    let cache = PreprocessingCache::default();
+    cache.insert(
+        file_id,
+        text.clone(),
+        params.clone(),
+        includes.clone(),
+        preprocess(file_id, &text).into(),
+    );
+
+    let edited: Arc<str> = "#include \"foo\"\nint bar;\n".into();
+    assert!(cache
+        .reuse(file_id, &text, &params, |resolved| {
+            assert_eq!(resolved, includes.as_ref());
+            false
+        })
+        .is_none());
+    assert!(cache.reuse(file_id, &edited, &params, |_| false).is_none());
+    assert!(cache.reuse(file_id, &edited, &params, |_| true).is_some());
+    assert_eq!(
+        cache.stats(),
+        PreprocessingCacheStats {
+            hits: 0,
+            patches: 1,
+            misses: 2,
+        }
+    );
+}
@@ -1,4 +1,9 @@
+use std::{io::Read, path::Path};
+
+use base_db::infer_include_ext;
+use fxhash::{FxHashMap, FxHashSet};
 use serde::Serialize;
+use sourcepawn_lexer::{TextRange, TextSize};
 use vfs::FileId;
 
 use insta::{assert_json_snapshot, assert_snapshot};
@@ -12,13 +17,13 @@ fn extend_macros(
     Ok(())
 }
 
-#[derive(Debug, Default, Serialize)]
+#[derive(Debug, Default, PartialEq, Eq, Serialize)]
 struct Range {
     start: u32,
     end: u32,
 }
 
-#[derive(Debug, Default, Serialize)]
+#[derive(Debug, Default, PartialEq, Eq, Serialize)]
 struct ExpandedSymbol {
     range: Range,
     expanded_range: Range,
@@ -43,7 +48,7 @@ impl From<&ExpandedSymbolOffset> for ExpandedSymbol {
     }
 }
 
-#[derive(Debug, Default, Serialize)]
+#[derive(Debug, Default, PartialEq, Eq, Serialize)]
 struct PreprocessingResult_ {
     vec: Vec<(u32, u32, u32, u32)>,
     expanded_symbols: Vec<ExpandedSymbol>,
@@ -75,7 +80,6 @@ impl From<PreprocessingResult> for PreprocessingResult_ {
     }
 }
 
-#[allow(unused_macros)]
 macro_rules! assert_preproc_eq {
     ($input:expr) => {
         let res = SourcepawnPreprocessor::new(FileId::from(0), $input, &mut extend_macros)
@@ -105,7 +109,36 @@ macro_rules! assert_preproc_eq {
     };
 }
 
-use preprocessor::{ExpandedSymbolOffset, MacrosMap, PreprocessingResult, SourcepawnPreprocessor};
+macro_rules! assert_incremental_eq {
+    ($old_input:expr, $new_input:expr) => {
+        let old_res = SourcepawnPreprocessor::new(FileId::from(0), $old_input, &mut extend_macros)
+            .preprocess_input();
+        let res = reprocess_incrementally($old_input, &old_res, $new_input)
+            .expect("the edit should be reprocessed incrementally");
+        let expected_res =
+            SourcepawnPreprocessor::new(FileId::from(0), $new_input, &mut extend_macros)
+                .preprocess_input();
+        assert_eq!(res.preprocessed_text(), expected_res.preprocessed_text());
+        assert_eq!(res.inactive_ranges(), expected_res.inactive_ranges());
+        assert_eq!(
+            PreprocessingResult_::from(res),
+            PreprocessingResult_::from(expected_res)
+        );
+    };
+}
+
+macro_rules! assert_incremental_none {
+    ($old_input:expr, $new_input:expr) => {
+        let old_res = SourcepawnPreprocessor::new(FileId::from(0), $old_input, &mut extend_macros)
+            .preprocess_input();
+        assert!(reprocess_incrementally($old_input, &old_res, $new_input).is_none());
+    };
+}
+
+use preprocessor::{
+    reprocess_incrementally, ExpandedSymbolOffset, InactiveDirectiveReason, IncludeLimitExceeded,
+    IncludeLimitKind, MacrosMap, PreprocessingResult, PreprocessorError, SourcepawnPreprocessor,
+};
 #[test]
 fn no_preprocessor_directives() {
     let input = r#"
@@ -742,3 +775,768 @@ void foo(){
 
     assert_preproc_eq!(input);
 }
+
+#[test]
+fn incremental_edit_in_function_body() {
+    let old_input = r#"#define FOO 1
+void foo() {
+    int a = FOO;
+    int b;
+}
+#if FOO
+int bar;
+#endif"#;
+    let new_input = r#"#define FOO 1
+void foo() {
+    int a = FOO;
+    int bar_baz	= 2;
+}
+#if FOO
+int bar;
+#endif"#;
+
+    assert_incremental_eq!(old_input, new_input);
+}
+
+#[test]
+fn incremental_insert_lines() {
+    let old_input = r#"void foo() {
+    int a;
+}"#;
+    let new_input = r#"void foo() {
+    int a;
+    int b;
+
+    int c = a + b;
+}"#;
+
+    assert_incremental_eq!(old_input, new_input);
+}
+
+#[test]
+fn incremental_edit_first_line() {
+    let old_input = r#"int foo;
+int bar;"#;
+    let new_input = r#"float foo;
+int bar;"#;
+
+    assert_incremental_eq!(old_input, new_input);
+}
+
+#[test]
+fn incremental_edit_directive() {
+    let old_input = r#"#define FOO 1
+int foo = FOO;"#;
+    let new_input = r#"#define FOO 2
+int foo = FOO;"#;
+
+    assert_incremental_none!(old_input, new_input);
+}
+
+#[test]
+fn incremental_edit_macro_invocation() {
+    let old_input = r#"#define FOO 1
+int foo = FOO;"#;
+    let new_input = r#"#define FOO 1
+int foo = FOO + 1;"#;
+
+    assert_incremental_none!(old_input, new_input);
+}
+
+#[test]
+fn incremental_edit_inactive_region() {
+    let old_input = r#"#if 0
+int foo;
+#endif"#;
+    let new_input = r#"#if 0
+int bar;
+#endif"#;
+
+    assert_incremental_none!(old_input, new_input);
+}
+
+#[test]
+fn incremental_open_block_comment() {
+    let old_input = r#"int foo;
+int bar;"#;
+    let new_input = r#"int foo; /*
+int bar;"#;
+
+    assert_incremental_none!(old_input, new_input);
+}
+
+#[test]
+fn if_directive_condition_symbols() {
+    let input = r#"#define FOO 1 + 2
+#if FOO && defined FOO
+int foo;
+#endif"#;
+    let res =
+        SourcepawnPreprocessor::new(FileId::from(0), input, &mut extend_macros).preprocess_input();
+    let symbols = res.source_map().expanded_symbols();
+
+    assert_eq!(symbols.len(), 2);
+    assert!(symbols.iter().all(|symbol| symbol.in_condition()));
+    assert_eq!(
+        symbols[0].condition_text().map(|text| text.as_str()),
+        Some("1 + 2")
+    );
+    assert_eq!(symbols[1].condition_text(), None);
+}
+
+#[test]
+fn unknown_directive_suggestions() {
+    let input = r#"#inlcude <sourcemod>
+#ifdef FOO
+int foo;
+#endif
+#error "unsupported"
+int bar;"#;
+    let res =
+        SourcepawnPreprocessor::new(FileId::from(0), input, &mut extend_macros).preprocess_input();
+    let errors = &res.errors().unknown_directive_errors;
+
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].text(), "#inlcude");
+    assert_eq!(errors[0].suggestion(), Some("#include"));
+    assert_eq!(errors[1].text(), "#ifdef");
+    assert_eq!(errors[1].suggestion(), Some("#if defined"));
+    assert_eq!(
+        res.preprocessed_text().lines().collect::<Vec<_>>(),
+        ["", "", "int foo;", "", "", "int bar;"]
+    );
+}
+
+#[test]
+fn style_pragmas() {
+    let input = r#"int foo
+#pragma semicolon 1
+#pragma newdecls required
+int bar;
+#pragma newdecls optional
+new baz;"#;
+    let res =
+        SourcepawnPreprocessor::new(FileId::from(0), input, &mut extend_macros).preprocess_input();
+    let pragmas = res.style_pragmas();
+    let offset = |text: &str| TextSize::new(input.find(text).unwrap() as u32);
+
+    assert_eq!(pragmas.iter().count(), 3);
+    assert!(!pragmas.requires_semicolons_at(offset("int foo")));
+    assert!(!pragmas.requires_newdecls_at(offset("int foo")));
+    assert!(pragmas.requires_semicolons_at(offset("int bar")));
+    assert!(pragmas.requires_newdecls_at(offset("int bar")));
+    assert!(pragmas.requires_semicolons_at(offset("new baz")));
+    assert!(!pragmas.requires_newdecls_at(offset("new baz")));
+}
+
+/// Preprocess the file at `path` of `files` the way spcomp does: in a single pass, each file
+/// being included once, with the macros left by an include flowing into the rest of its includer.
+fn spcomp_preprocess(
+    files: &FxHashMap<String, String>,
+    path: &str,
+    macros: MacrosMap,
+    included: &mut FxHashSet<String>,
+) -> PreprocessingResult {
+    let mut include_file =
+        |macros: &mut MacrosMap, mut path: String, _: FileId, _: bool| -> anyhow::Result<()> {
+            infer_include_ext(&mut path);
+            if !files.contains_key(&path) {
+                anyhow::bail!("Include not found");
+            }
+            if included.insert(path.clone()) {
+                *macros = spcomp_preprocess(files, &path, macros.clone(), included)
+                    .macros()
+                    .clone();
+            }
+            Ok(())
+        };
+    let mut preprocessor =
+        SourcepawnPreprocessor::new(FileId::from(0), &files[path], &mut include_file);
+    preprocessor.set_macros(macros);
+    preprocessor.set_implicit_includes(Vec::new());
+    preprocessor.preprocess_input()
+}
+
+fn spcomp_preprocess_files(files: &[(&str, &str)]) -> PreprocessingResult {
+    let files: FxHashMap<String, String> = files
+        .iter()
+        .map(|(path, text)| (path.to_string(), text.to_string()))
+        .collect();
+    spcomp_preprocess(
+        &files,
+        "main.sp",
+        MacrosMap::default(),
+        &mut FxHashSet::default(),
+    )
+}
+
+/// Active lines of the preprocessed text which are not directives.
+fn code_lines(res: &PreprocessingResult) -> Vec<String> {
+    res.preprocessed_text()
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(String::from)
+        .collect()
+}
+
+#[test]
+fn spcomp_sibling_includes_see_previous_macros() {
+    let res = spcomp_preprocess_files(&[
+        (
+            "main.sp",
+            "#include \"a.inc\"\n#include \"b.inc\"\n#if defined B_SAW_A\nint seen;\n#endif\n",
+        ),
+        ("a.inc", "#define A\n"),
+        ("b.inc", "#if defined A\n#define B_SAW_A\n#endif\n"),
+    ]);
+
+    assert_eq!(code_lines(&res), ["int seen;"]);
+}
+
+#[test]
+fn spcomp_undef_in_include() {
+    let res = spcomp_preprocess_files(&[
+        ("main.sp", "#define FOO\n#include \"foo.inc\"\n#if defined FOO\nint defined_foo;\n#else\nint undefined_foo;\n#endif\n"),
+        ("foo.inc", "#undef FOO\n"),
+    ]);
+
+    assert_eq!(code_lines(&res), ["int undefined_foo;"]);
+}
+
+#[test]
+fn spcomp_include_once() {
+    // The second include of `bar.inc` is skipped, the macro undefined after the first one stays
+    // undefined. The code of the includes is not part of the preprocessed text of the includer.
+    let res = spcomp_preprocess_files(&[
+        (
+            "main.sp",
+            "#include \"foo.inc\"\n#include \"bar.inc\"\n#if defined BAR\nint bar;\n#endif\n",
+        ),
+        ("foo.inc", "#include \"bar.inc\"\n#undef BAR\n"),
+        ("bar.inc", "#define BAR\nint bar_count;\n"),
+    ]);
+
+    assert!(code_lines(&res).is_empty());
+}
+
+/// The includes of SourceMod 1.11 shipped with the tests of the server.
+fn stock_includes() -> FxHashMap<String, String> {
+    let path =
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("../sourcepawn-studio/test_data/sourcemod.zip");
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(path).unwrap()).unwrap();
+    let mut res = FxHashMap::default();
+    for idx in 0..archive.len() {
+        let mut file = archive.by_index(idx).unwrap();
+        let Some(name) = file.name().strip_prefix("include/").map(String::from) else {
+            continue;
+        };
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes).unwrap();
+        res.insert(name, String::from_utf8_lossy(&bytes).into_owned());
+    }
+
+    res
+}
+
+#[test]
+fn spcomp_stock_includes() {
+    let mut files = stock_includes();
+    files.insert(
+        "main.sp".to_string(),
+        r#"#include <sourcemod>
+#include <version>
+#include <sourcemod>
+
+#if SOURCEMOD_V_MAJOR == 1 && SOURCEMOD_V_MINOR >= 11
+int modern;
+#endif
+#if defined _clients_included
+int clients;
+#endif
+#if defined _sdktools_included
+int sdktools;
+#endif
+int players[MAXPLAYERS + 1];
+"#
+        .to_string(),
+    );
+    let mut included = FxHashSet::default();
+    let res = spcomp_preprocess(&files, "main.sp", MacrosMap::default(), &mut included);
+
+    assert!(res.errors().unresolved_include_errors.is_empty());
+    assert!(included.contains("version.inc"));
+    assert!(!included.contains("sdktools.inc"));
+    assert!([
+        "_sourcemod_included",
+        "_version_included",
+        "_clients_included",
+        "MAXPLAYERS"
+    ]
+    .iter()
+    .all(|name| res.macros().contains_key(*name)));
+    assert_eq!(
+        code_lines(&res),
+        ["int modern;", "int clients;", "int players[65 + 1];"]
+    );
+}
+
+#[test]
+fn macro_argument_count() {
+    let input = r#"#define FOO(%1,%2) %1 + %2
+#define BAR(%1) %1
+#define BAZ() 1
+int a = FOO(1, 2);
+int b = FOO(1);
+int c = FOO(1, 2, 3);
+int d = BAR();
+int e = BAZ();
+int f = BAZ(1);
+#if FOO(1) == 1
+#endif"#;
+    let res =
+        SourcepawnPreprocessor::new(FileId::from(0), input, &mut extend_macros).preprocess_input();
+    let errors = res
+        .errors()
+        .macro_argument_count_errors
+        .iter()
+        .map(|err| {
+            (
+                &input[usize::from(err.range().start())..usize::from(err.range().end())],
+                err.expected(),
+                err.actual(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        errors,
+        [
+            ("FOO(1)", 2, 1),
+            ("FOO(1, 2, 3)", 2, 3),
+            ("BAZ(1)", 0, 1),
+            ("FOO(1)", 2, 1),
+        ]
+    );
+}
+
+#[test]
+fn use_after_undef() {
+    let input = r#"#define FOO 1
+#define BAR 2
+#undef FOO
+#undef BAZ
+int a = FOO;
+int b = BAR;
+int c = BAZ;
+#define FOO 3
+int d = FOO;
+#undef BAR
+int e = BAR + BAR;"#;
+    let res =
+        SourcepawnPreprocessor::new(FileId::from(0), input, &mut extend_macros).preprocess_input();
+    let errors = res
+        .errors()
+        .use_after_undef_errors
+        .iter()
+        .map(|err| {
+            (
+                err.text(),
+                usize::from(err.range().start()),
+                usize::from(err.undef_range().start()),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let foo_undef = input.find("#undef FOO").unwrap() + "#undef ".len();
+    let bar_undef = input.find("#undef BAR").unwrap() + "#undef ".len();
+    let bar_use = input.find("BAR + BAR").unwrap();
+    assert_eq!(
+        errors,
+        [
+            ("FOO", input.find("FOO;").unwrap(), foo_undef),
+            ("BAR", bar_use, bar_undef),
+            ("BAR", bar_use + "BAR + ".len(), bar_undef),
+        ]
+    );
+}
+
+#[test]
+fn assert_directive() {
+    let input = r#"#define FOO 1
+#assert FOO == 1
+#assert FOO == 2
+  #assert defined BAR \
+    || FOO > 1
+int foo;"#;
+    let res =
+        SourcepawnPreprocessor::new(FileId::from(0), input, &mut extend_macros).preprocess_input();
+    let errors = res
+        .errors()
+        .assertion_failed_errors
+        .iter()
+        .map(|err| {
+            (
+                err.text(),
+                &input[usize::from(err.range().start())..usize::from(err.range().end())],
+            )
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        errors,
+        [
+            ("FOO == 2", "#assert FOO == 2"),
+            (
+                "defined BAR \\\n    || FOO > 1",
+                "#assert defined BAR \\\n    || FOO > 1"
+            ),
+        ]
+    );
+    assert_eq!(
+        res.preprocessed_text().lines().count(),
+        input.lines().count()
+    );
+    assert!(res.preprocessed_text().ends_with("int foo;"));
+}
+
+#[test]
+fn macro_declared_name_range() {
+    let input = r#"#define PLUGIN_NATIVE(%1) public any Native_%1(Handle plugin, int numParams)
+PLUGIN_NATIVE(Foo)
+{
+    return 0;
+}"#;
+    let res =
+        SourcepawnPreprocessor::new(FileId::from(0), input, &mut extend_macros).preprocess_input();
+    let start = res.preprocessed_text().find("Native_Foo").unwrap();
+    let s_range = TextRange::at(TextSize::new(start as u32), TextSize::new(10));
+
+    let u_range = res.source_map().closest_u_name_range(s_range);
+    assert_eq!(&input[u_range], "Foo");
+}
+
+#[test]
+fn unbalanced_condition_directives() {
+    let input = r#"#endif
+#if FOO
+#else
+#else
+#endif
+#if BAR
+int foo;"#;
+    let res =
+        SourcepawnPreprocessor::new(FileId::from(0), input, &mut extend_macros).preprocess_input();
+    let errors = res
+        .errors()
+        .unbalanced_condition_errors
+        .iter()
+        .map(|err| {
+            (
+                err.text(),
+                &input[usize::from(err.range().start())..usize::from(err.range().end())],
+            )
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        errors,
+        [
+            ("#endif without matching #if", "#endif"),
+            ("#else after #else", "#else"),
+            ("unterminated #if at end of file", "#if"),
+        ]
+    );
+}
+
+#[test]
+fn source_map_ranges() {
+    let input = r#"#define FOO 1
+int bar = FOO;
+int baz;"#;
+    let res =
+        SourcepawnPreprocessor::new(FileId::from(0), input, &mut extend_macros).preprocess_input();
+    let text = res.preprocessed_text();
+    let u_range = |name: &str| {
+        let start = input.rfind(name).unwrap();
+        TextRange::at(
+            TextSize::new(start as u32),
+            TextSize::new(name.len() as u32),
+        )
+    };
+    let source_map = res.source_map();
+
+    let s_ranges = source_map
+        .closest_s_ranges([u_range("bar"), u_range("FOO"), u_range("baz")])
+        .map(Option::unwrap)
+        .collect::<Vec<_>>();
+    // The expansion of a macro starts with the whitespace before its call.
+    assert_eq!(
+        s_ranges
+            .iter()
+            .map(|it| text[*it].trim())
+            .collect::<Vec<_>>(),
+        ["bar", "1", "baz"]
+    );
+
+    let u_ranges = source_map.closest_u_ranges(s_ranges).collect::<Vec<_>>();
+    assert_eq!(u_ranges, [u_range("bar"), u_range("FOO"), u_range("baz")]);
+}
+
+#[test]
+fn macro_doc_comments() {
+    let input = r#"/**
+ * Maximum number of players.
+ */
+#define MAXPLAYERS 65
+/** Not attached. */
+
+#define FOO 1
+int foo; /** Not on its own line. */
+#define BAR 2
+/** Version of the plugin. */
+#define VERSION "1.0""#;
+    let res =
+        SourcepawnPreprocessor::new(FileId::from(0), input, &mut extend_macros).preprocess_input();
+    let docs = |name: &str| res.macros().get(name).and_then(|it| it.docs());
+
+    assert_eq!(
+        docs("MAXPLAYERS"),
+        Some("/**\n * Maximum number of players.\n */")
+    );
+    assert_eq!(docs("FOO"), None);
+    assert_eq!(docs("BAR"), None);
+    assert_eq!(docs("VERSION"), Some("/** Version of the plugin. */"));
+}
+
+#[test]
+fn nested_macro_expansions() {
+    let input = r#"#define INNER foo
+#define MIDDLE(%1) INNER + %1
+#define OUTER MIDDLE(1) + INNER
+int x = OUTER;
+int y = INNER;"#;
+    let res =
+        SourcepawnPreprocessor::new(FileId::from(0), input, &mut extend_macros).preprocess_input();
+    let expansions = res
+        .source_map()
+        .expanded_symbols()
+        .iter()
+        .map(|it| (it.idx(), it.nested_macros().to_vec()))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        expansions,
+        [
+            (2, vec![(FileId::from(0), 1), (FileId::from(0), 0)]),
+            (0, vec![])
+        ]
+    );
+}
+
+#[test]
+fn forced_branches() {
+    let input = r#"#define FOO 1
+#if FOO
+int foo;
+#else
+int bar;
+#endif"#;
+    assert_eq!(
+        preprocessor::branch_directive_at(input, TextSize::new(33)),
+        Some(1)
+    );
+    assert_eq!(
+        preprocessor::branch_directive_at(input, TextSize::new(0)),
+        None
+    );
+
+    let mut preprocessor = SourcepawnPreprocessor::new(FileId::from(0), input, &mut extend_macros);
+    preprocessor.set_forced_branches(&[1]);
+    let res = preprocessor.preprocess_input();
+    let text = res.preprocessed_text();
+    assert!(!text.contains("int foo;"));
+    assert!(text.contains("int bar;"));
+}
+
+#[test]
+fn configuration_macros() {
+    let macros = preprocessor::configuration_macros(
+        FileId::from(0),
+        &[
+            "DEBUG".to_string(),
+            "MAX_PLAYERS=64".to_string(),
+            "LEVEL=".to_string(),
+        ],
+    );
+    let mut values = macros
+        .iter()
+        .map(|(name, macro_)| (name.to_string(), macro_.value()))
+        .collect::<Vec<_>>();
+    values.sort();
+    assert_eq!(
+        values,
+        [
+            ("DEBUG".to_string(), "".to_string()),
+            ("LEVEL".to_string(), "0".to_string()),
+            ("MAX_PLAYERS".to_string(), "64".to_string()),
+        ]
+    );
+
+    let input = r#"#if defined DEBUG
+int foo;
+#else
+int bar;
+#endif"#;
+    let mut preprocessor = SourcepawnPreprocessor::new(FileId::from(0), input, &mut extend_macros);
+    preprocessor.set_macros(macros);
+    let res = preprocessor.preprocess_input();
+    assert!(res.preprocessed_text().contains("int foo;"));
+    assert!(!res.preprocessed_text().contains("int bar;"));
+}
+
+#[test]
+fn include_limit_errors() {
+    let input = r#"#include "a.inc"
+#include <b>"#;
+    let mut include_file = |_: &mut MacrosMap, path: String, _: FileId, _: bool| {
+        if path == "b" {
+            return Err(IncludeLimitExceeded {
+                kind: IncludeLimitKind::Depth(1),
+                chain: vec![FileId::from(0), FileId::from(1), FileId::from(2)],
+            }
+            .into());
+        }
+        Ok(())
+    };
+    let res =
+        SourcepawnPreprocessor::new(FileId::from(0), input, &mut include_file).preprocess_input();
+    let errors = &res.errors().include_limit_errors;
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].text(), "b");
+    assert_eq!(errors[0].kind(), IncludeLimitKind::Depth(1));
+    assert_eq!(errors[0].chain().len(), 3);
+    assert!(res.errors().unresolved_include_errors.is_empty());
+}
+
+#[test]
+fn crlf_line_endings() {
+    let input = "#define FOO 1\r\n#if FOO\r\nint foo;\r\n#endif\r\nint bar;\r\n";
+    let res =
+        SourcepawnPreprocessor::new(FileId::from(0), input, &mut extend_macros).preprocess_input();
+    let text = res.preprocessed_text();
+
+    assert_eq!(text.lines().count(), input.lines().count());
+    assert!(!text.replace("\r\n", "").contains('\n'));
+    let offset = input.find("int bar;").unwrap();
+    assert_eq!(text.find("int bar;"), Some(offset));
+}
+
+#[test]
+fn incremental_crlf_line_endings() {
+    let old_input = "int foo;\r\nvoid bar() {\r\n    int a;\r\n}\r\n";
+    let new_input = "int foo;\r\nvoid bar() {\r\n    int ab;\r\n}\r\n";
+    assert_incremental_eq!(old_input, new_input);
+}
+
+#[test]
+fn tab_indentation() {
+    let input = "#define FOO 1\nvoid foo() {\n\tint a\t= FOO;\n\t\treturn;\n}";
+    let res =
+        SourcepawnPreprocessor::new(FileId::from(0), input, &mut extend_macros).preprocess_input();
+
+    assert_eq!(
+        res.preprocessed_text().lines().skip(1).collect::<Vec<_>>(),
+        ["void foo() {", "\tint a\t= 1;", "\t\treturn;", "}"]
+    );
+}
+
+#[test]
+fn cancellation_checks() {
+    let input = "int foo;\n".repeat(30_000);
+    let checks = std::cell::Cell::new(0);
+    let cancellation_check = || checks.set(checks.get() + 1);
+    let mut preprocessor = SourcepawnPreprocessor::new(FileId::from(0), &input, &mut extend_macros);
+    preprocessor.set_cancellation_check(&cancellation_check);
+    let res = preprocessor.preprocess_input();
+
+    assert_eq!(res.preprocessed_text().len(), input.len());
+    assert_eq!(checks.get(), input.len() / (64 * 1024));
+}
+
+#[test]
+fn builtin_macros() {
+    let input = r#"#if defined __DATE__ && defined __BINARY_NAME__
+char name[] = __BINARY_NAME__;
+#endif
+#undef __BINARY_NAME__
+#if !defined __BINARY_NAME__
+int undefined;
+#endif"#;
+    let res =
+        SourcepawnPreprocessor::new(FileId::from(0), input, &mut extend_macros).preprocess_input();
+
+    assert_eq!(
+        res.preprocessed_text().lines().nth(1),
+        Some(r#"char name[] = "plugin.smx";"#)
+    );
+    assert_eq!(
+        res.preprocessed_text().lines().nth(5),
+        Some("int undefined;")
+    );
+    assert!(res.macros().is_empty());
+}
+
+#[test]
+fn inactive_directive_errors() {
+    let input = r#"#if 0
+#define
+#define LOG(%a) a
+#define FOO(%1, %2) %1
+#inlcude <sourcemod>
+#include
+#undef
+#endif
+int foo;"#;
+    let res =
+        SourcepawnPreprocessor::new(FileId::from(0), input, &mut extend_macros).preprocess_input();
+
+    assert_eq!(
+        res.errors()
+            .inactive_directive_errors
+            .iter()
+            .map(|err| err.reason().clone())
+            .collect::<Vec<_>>(),
+        [
+            InactiveDirectiveReason::MissingMacroName,
+            InactiveDirectiveReason::InvalidMacroParameters,
+            InactiveDirectiveReason::UnknownDirective {
+                name: "#inlcude".to_string(),
+                suggestion: Some("#include"),
+            },
+            InactiveDirectiveReason::MissingIncludePath,
+            InactiveDirectiveReason::MissingUndefName,
+        ]
+    );
+    assert!(res.macros().is_empty());
+    assert_eq!(res.preprocessed_text().lines().last(), Some("int foo;"));
+}
+
+#[test]
+fn condition_branch_ranges() {
+    let input = "#if 0\nint a;\n#if 1\nint b;\n#endif\n#else\nint c;\n#endif\n";
+    let ranges = preprocessor::condition_branch_ranges(input);
+
+    assert_eq!(
+        ranges
+            .iter()
+            .map(|range| &input[*range])
+            .collect::<Vec<_>>(),
+        [
+            "#if 0\nint a;\n#if 1\nint b;\n#endif",
+            "#if 1\nint b;",
+            "#else\nint c;",
+        ]
+    );
+}
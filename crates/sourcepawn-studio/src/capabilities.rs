@@ -1,19 +1,28 @@
 use ide::WideEncoding;
 use lsp_types::{
-    CallHierarchyOptions, CallHierarchyServerCapability, ClientCapabilities, CompletionOptions,
-    CompletionOptionsCompletionItem, DocumentSymbolOptions, HoverProviderCapability, MarkupKind,
-    OneOf, PositionEncodingKind, ReferencesOptions, RenameOptions, SemanticTokensFullOptions,
-    SemanticTokensLegend, SemanticTokensOptions, ServerCapabilities, SignatureHelpOptions,
-    TextDocumentSyncCapability, TextDocumentSyncKind, WorkDoneProgressOptions,
+    CallHierarchyOptions, CallHierarchyServerCapability, ClientCapabilities, CodeActionKind,
+    CodeActionOptions, CodeActionProviderCapability, CodeLensOptions, CompletionOptions,
+    CompletionOptionsCompletionItem, DocumentLinkOptions, DocumentOnTypeFormattingOptions,
+    DocumentSymbolOptions, FoldingRangeProviderCapability, HoverProviderCapability,
+    LinkedEditingRangeServerCapabilities, MarkupKind, OneOf, PositionEncodingKind,
+    ReferencesOptions, RenameOptions, SemanticTokensFullOptions, SemanticTokensLegend,
+    SemanticTokensOptions, ServerCapabilities, SignatureHelpOptions, TextDocumentSyncCapability,
+    TextDocumentSyncKind, WorkDoneProgressOptions,
 };
 
 use crate::{
     config::Config,
     line_index::PositionEncoding,
-    lsp::{ext::negotiated_encoding, semantic_tokens},
+    lsp::{
+        ext::{negotiated_encoding, FIX_ALL_CODE_ACTION_KIND},
+        semantic_tokens,
+    },
 };
 
-pub fn server_capabilities(config: &Config) -> ServerCapabilities {
+pub fn server_capabilities(config: &Config, amxxpawn_mode: bool) -> ServerCapabilities {
+    // The AMX Mod X extension formats the documents with clang-format, as the formatter only
+    // knows the syntax of SourcePawn.
+    let formatting = !amxxpawn_mode;
     ServerCapabilities {
         position_encoding: match negotiated_encoding(config.caps()) {
             PositionEncoding::Utf8 => Some(PositionEncodingKind::UTF8),
@@ -28,6 +37,25 @@ pub fn server_capabilities(config: &Config) -> ServerCapabilities {
         )),
         hover_provider: Some(HoverProviderCapability::Simple(true)),
         definition_provider: Some(OneOf::Left(true)),
+        document_highlight_provider: Some(OneOf::Left(true)),
+        linked_editing_range_provider: Some(LinkedEditingRangeServerCapabilities::Simple(true)),
+        document_link_provider: Some(DocumentLinkOptions {
+            resolve_provider: None,
+            work_done_progress_options: WorkDoneProgressOptions {
+                work_done_progress: None,
+            },
+        }),
+        folding_range_provider: Some(FoldingRangeProviderCapability::Simple(true)),
+        document_formatting_provider: formatting.then_some(OneOf::Left(true)),
+        document_range_formatting_provider: formatting.then_some(OneOf::Left(true)),
+        document_on_type_formatting_provider: formatting.then(|| DocumentOnTypeFormattingOptions {
+            first_trigger_character: "}".to_string(),
+            more_trigger_character: Some(vec![":".to_string(), "\n".to_string()]),
+        }),
+        inlay_hint_provider: Some(OneOf::Left(true)),
+        code_lens_provider: Some(CodeLensOptions {
+            resolve_provider: Some(true),
+        }),
         semantic_tokens_provider: Some(
             SemanticTokensOptions {
                 legend: SemanticTokensLegend {
@@ -71,11 +99,26 @@ pub fn server_capabilities(config: &Config) -> ServerCapabilities {
             },
         })),
         rename_provider: Some(OneOf::Right(RenameOptions {
-            prepare_provider: Some(false),
+            prepare_provider: Some(true),
+            work_done_progress_options: WorkDoneProgressOptions {
+                work_done_progress: None,
+            },
+        })),
+        code_action_provider: Some(CodeActionProviderCapability::Options(CodeActionOptions {
+            code_action_kinds: Some(vec![
+                CodeActionKind::QUICKFIX,
+                CodeActionKind::REFACTOR,
+                CodeActionKind::REFACTOR_EXTRACT,
+                CodeActionKind::REFACTOR_REWRITE,
+                CodeActionKind::SOURCE_ORGANIZE_IMPORTS,
+                CodeActionKind::new(FIX_ALL_CODE_ACTION_KIND),
+            ]),
+            resolve_provider: None,
             work_done_progress_options: WorkDoneProgressOptions {
                 work_done_progress: None,
             },
         })),
+        workspace_symbol_provider: Some(OneOf::Left(true)),
         document_symbol_provider: Some(OneOf::Right(DocumentSymbolOptions {
             label: Some("SourcePawn".to_string()),
             work_done_progress_options: WorkDoneProgressOptions {
@@ -0,0 +1,591 @@
+//! Command line interface of the server, to analyze or format a project without an editor, e.g
+//! in CI.
+
+use std::fmt::Write;
+
+use fxhash::FxHashMap;
+use lsp_types::DiagnosticSeverity;
+use paths::AbsPathBuf;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use vfs::FileId;
+use walkdir::WalkDir;
+
+use crate::{
+    config::Config, diagnostics::fetch_native_diagnostics, global_state::GlobalStateSnapshot,
+    lsp::ext::CommandResult, GlobalState,
+};
+
+/// Output format of the diagnostics of the `check` command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckFormat {
+    /// One line per diagnostic, e.g `scripting/plugin.sp:12:5: error[SP0011]: ...`.
+    Human,
+    /// Array of the diagnostics, with their LSP range.
+    Json,
+    /// SARIF log, e.g for the code scanning of GitHub.
+    Sarif,
+}
+
+/// Diagnostic of a file, relative to the root of the project.
+struct FileDiagnostic {
+    path: String,
+    diagnostic: lsp_types::Diagnostic,
+}
+
+/// Analyze the project at `path`, or the file at `path`, and print its diagnostics.
+///
+/// Return whether the check failed, i.e whether there are errors, or warnings if
+/// `deny_warnings` is set.
+pub fn check(
+    path: AbsPathBuf,
+    format: CheckFormat,
+    deny_warnings: bool,
+    amxxpawn_mode: bool,
+) -> anyhow::Result<bool> {
+    let (state, _client) = GlobalState::load_headless(project_root(&path)?, amxxpawn_mode);
+    let res = check_snapshot(state.snapshot(), &path, format, deny_warnings)?;
+    println!("{}", res.output);
+
+    Ok(res.failed)
+}
+
+/// Root of the project to load to analyze `path`, i.e `path` itself if it is a directory, or
+/// its parent.
+pub(crate) fn project_root(path: &AbsPathBuf) -> anyhow::Result<AbsPathBuf> {
+    if path.is_dir() {
+        Ok(path.clone())
+    } else if path.exists() {
+        Ok(path
+            .parent()
+            .ok_or_else(|| anyhow::anyhow!("invalid path: {path}"))?
+            .to_path_buf())
+    } else {
+        anyhow::bail!("no such file or directory: {path}");
+    }
+}
+
+/// Diagnostics of the `.sp` and `.inc` files at `path`, a file or a directory of the project of
+/// the snapshot, with their path relative to the [`project_root`] of `path`.
+pub(crate) fn check_snapshot(
+    snap: GlobalStateSnapshot,
+    path: &AbsPathBuf,
+    format: CheckFormat,
+    deny_warnings: bool,
+) -> anyhow::Result<CommandResult> {
+    let root = project_root(path)?;
+    let mut file_ids = if path.is_dir() {
+        snap.workspace_file_ids()
+            .into_iter()
+            .filter(|file_id| {
+                snap.vfs_read()
+                    .file_path(*file_id)
+                    .as_path()
+                    .is_some_and(|it| it.starts_with(&root))
+            })
+            .collect()
+    } else {
+        vec![snap
+            .vfs_read()
+            .file_id(&path.clone().into())
+            .ok_or_else(|| anyhow::anyhow!("not a SourcePawn file: {path}"))?]
+    };
+    file_ids.sort_by_key(|file_id| relative_path(&snap, &root, *file_id));
+
+    let paths = file_ids
+        .iter()
+        .map(|file_id| (*file_id, relative_path(&snap, &root, *file_id)))
+        .collect::<FxHashMap<_, _>>();
+    let diagnostics = fetch_native_diagnostics(snap, file_ids)
+        .into_iter()
+        .flat_map(|(file_id, diagnostics)| {
+            let path = paths[&file_id].clone();
+            diagnostics
+                .into_iter()
+                .map(move |diagnostic| FileDiagnostic {
+                    path: path.clone(),
+                    diagnostic,
+                })
+        })
+        .collect::<Vec<_>>();
+
+    let output = match format {
+        CheckFormat::Human => to_human(&diagnostics),
+        CheckFormat::Json => {
+            serde_json::to_string_pretty(&to_json(&diagnostics)).expect("invalid JSON")
+        }
+        CheckFormat::Sarif => {
+            serde_json::to_string_pretty(&to_sarif(&diagnostics)).expect("invalid JSON")
+        }
+    };
+    let failed = is_failure(&diagnostics, deny_warnings);
+
+    Ok(CommandResult { output, failed })
+}
+
+/// Whether the check fails, i.e whether there are errors, or warnings if `deny_warnings` is set.
+fn is_failure(diagnostics: &[FileDiagnostic], deny_warnings: bool) -> bool {
+    diagnostics.iter().any(|it| match it.diagnostic.severity {
+        Some(DiagnosticSeverity::ERROR) => true,
+        Some(DiagnosticSeverity::WARNING) => deny_warnings,
+        _ => false,
+    })
+}
+
+/// Format the `.sp` and `.inc` files of the `paths`, which are files or directories, in place,
+/// with the formatting settings of the project file of the `root`, and the `.editorconfig` files
+/// of each file.
+///
+/// With `check`, the files are not written, and the unformatted ones are printed instead. Return
+/// whether the check failed, i.e whether a file is not formatted.
+pub fn format(root: AbsPathBuf, paths: &[AbsPathBuf], check: bool) -> anyhow::Result<bool> {
+    let res = format_files(root, paths, check)?;
+    if !res.output.is_empty() {
+        print!("{}", res.output);
+    }
+
+    Ok(res.failed)
+}
+
+/// Format the files of the `paths` like [`format`], and return the unformatted files, one per
+/// line, with `check`.
+pub(crate) fn format_files(
+    root: AbsPathBuf,
+    paths: &[AbsPathBuf],
+    check: bool,
+) -> anyhow::Result<CommandResult> {
+    let mut project = Config::new(root.clone(), Default::default(), vec![root.into()], false);
+    project.load_project_file()?;
+    let mut res = CommandResult {
+        output: String::new(),
+        failed: false,
+    };
+    for path in paths {
+        if !path.exists() {
+            anyhow::bail!("no such file or directory: {path}");
+        }
+        let walker = WalkDir::new(path)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_entry(|entry| {
+                entry.depth() == 0
+                    || !entry
+                        .file_name()
+                        .to_str()
+                        .is_some_and(|name| name.starts_with('.'))
+            });
+        for entry in walker {
+            let entry = entry?;
+            let is_source = entry
+                .path()
+                .extension()
+                .is_some_and(|ext| ext == "sp" || ext == "inc");
+            if !entry.file_type().is_file() || !is_source {
+                continue;
+            }
+            let Ok(path) = AbsPathBuf::try_from(entry.path().to_path_buf()) else {
+                continue;
+            };
+            let text = std::fs::read_to_string(entry.path())?;
+            let formatted = ide::format(&text, &project.formatting(&path, None));
+            if formatted == text {
+                continue;
+            }
+            if check {
+                let _ = writeln!(res.output, "{}", entry.path().display());
+                res.failed = true;
+            } else {
+                std::fs::write(entry.path(), formatted)?;
+            }
+        }
+    }
+
+    Ok(res)
+}
+
+/// Search the symbols of the project at `path` matching the `query`, with the syntax of the
+/// workspace symbols of the editors, and print them.
+///
+/// Return whether the search failed, i.e whether no symbol matches.
+pub fn symbols(path: AbsPathBuf, query: &str, amxxpawn_mode: bool) -> anyhow::Result<bool> {
+    let (state, _client) = GlobalState::load_headless(project_root(&path)?, amxxpawn_mode);
+    let res = symbols_snapshot(state.snapshot(), &path, query)?;
+    if !res.output.is_empty() {
+        print!("{}", res.output);
+    }
+
+    Ok(res.failed)
+}
+
+/// Symbols of the project of the snapshot matching the `query`, one per line, e.g
+/// `scripting/plugin.sp:12:6: Function OnPluginStart`, with their path relative to `path`.
+pub(crate) fn symbols_snapshot(
+    snap: GlobalStateSnapshot,
+    path: &AbsPathBuf,
+    query: &str,
+) -> anyhow::Result<CommandResult> {
+    let root = project_root(path)?;
+    let limit = snap.config.workspace_symbol_search_limit();
+    let mut output = String::new();
+    for symbol in snap.analysis.workspace_symbols(query, limit)? {
+        let line_index = snap.file_line_index(symbol.range.file_id)?;
+        let Some(range) = line_index.try_range(symbol.range.range) else {
+            continue;
+        };
+        let _ = write!(
+            output,
+            "{}:{}:{}: {:?} ",
+            relative_path(&snap, &root, symbol.range.file_id),
+            range.start.line + 1,
+            range.start.character + 1,
+            symbol.kind
+        );
+        if let Some(container_name) = &symbol.container_name {
+            let _ = write!(output, "{}.", container_name);
+        }
+        let _ = writeln!(output, "{}", symbol.name);
+    }
+    let failed = output.is_empty();
+
+    Ok(CommandResult { output, failed })
+}
+
+/// Path of the file relative to the root, with `/` separators.
+fn relative_path(snap: &GlobalStateSnapshot, root: &AbsPathBuf, file_id: FileId) -> String {
+    let path = snap.vfs_read().file_path(file_id);
+    let Some(path) = path.as_path() else {
+        return path.to_string();
+    };
+    match path.strip_prefix(root) {
+        Some(relative) => relative.as_ref().to_string_lossy().replace('\\', "/"),
+        None => path.to_string(),
+    }
+}
+
+fn severity_name(severity: Option<DiagnosticSeverity>) -> &'static str {
+    match severity {
+        Some(DiagnosticSeverity::ERROR) => "error",
+        Some(DiagnosticSeverity::WARNING) => "warning",
+        Some(DiagnosticSeverity::HINT) => "hint",
+        _ => "information",
+    }
+}
+
+fn code(diagnostic: &lsp_types::Diagnostic) -> Option<String> {
+    match diagnostic.code.as_ref()? {
+        lsp_types::NumberOrString::Number(code) => Some(code.to_string()),
+        lsp_types::NumberOrString::String(code) => Some(code.clone()),
+    }
+}
+
+fn to_human(diagnostics: &[FileDiagnostic]) -> String {
+    let mut res = String::new();
+    for FileDiagnostic { path, diagnostic } in diagnostics {
+        let start = diagnostic.range.start;
+        let _ = write!(
+            res,
+            "{}:{}:{}: {}",
+            path,
+            start.line + 1,
+            start.character + 1,
+            severity_name(diagnostic.severity)
+        );
+        if let Some(code) = code(diagnostic) {
+            let _ = write!(res, "[{}]", code);
+        }
+        let _ = writeln!(res, ": {}", diagnostic.message);
+    }
+    let count = |severity| {
+        diagnostics
+            .iter()
+            .filter(|it| it.diagnostic.severity == Some(severity))
+            .count()
+    };
+    let _ = write!(
+        res,
+        "{} error(s), {} warning(s)",
+        count(DiagnosticSeverity::ERROR),
+        count(DiagnosticSeverity::WARNING)
+    );
+
+    res
+}
+
+fn to_json(diagnostics: &[FileDiagnostic]) -> serde_json::Value {
+    diagnostics
+        .iter()
+        .map(|FileDiagnostic { path, diagnostic }| {
+            json!({
+                "path": path,
+                "range": diagnostic.range,
+                "severity": severity_name(diagnostic.severity),
+                "code": code(diagnostic),
+                "message": diagnostic.message,
+            })
+        })
+        .collect()
+}
+
+fn to_sarif(diagnostics: &[FileDiagnostic]) -> serde_json::Value {
+    let mut rules: Vec<serde_json::Value> = Vec::new();
+    let mut rule_ids: Vec<String> = Vec::new();
+    let results = diagnostics
+        .iter()
+        .map(|FileDiagnostic { path, diagnostic }| {
+            let rule_id = code(diagnostic).unwrap_or_else(|| "sourcepawn-studio".to_string());
+            if !rule_ids.contains(&rule_id) {
+                let mut rule = json!({ "id": rule_id });
+                if let Some(description) = &diagnostic.code_description {
+                    rule["helpUri"] = json!(description.href);
+                }
+                rules.push(rule);
+                rule_ids.push(rule_id.clone());
+            }
+            let range = diagnostic.range;
+            json!({
+                "ruleId": rule_id,
+                "level": match diagnostic.severity {
+                    Some(DiagnosticSeverity::ERROR) => "error",
+                    Some(DiagnosticSeverity::WARNING) => "warning",
+                    _ => "note",
+                },
+                "message": { "text": diagnostic.message },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": path, "uriBaseId": "%SRCROOT%" },
+                        "region": {
+                            "startLine": range.start.line + 1,
+                            "startColumn": range.start.character + 1,
+                            "endLine": range.end.line + 1,
+                            "endColumn": range.end.character + 1,
+                        },
+                    },
+                }],
+            })
+        })
+        .collect::<Vec<_>>();
+
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "sourcepawn-studio",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": "https://github.com/Sarrus1/sourcepawn-studio",
+                    "rules": rules,
+                },
+            },
+            "results": results,
+        }],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    fn diagnostic(
+        path: &str,
+        line: u32,
+        severity: DiagnosticSeverity,
+        code: Option<&str>,
+        message: &str,
+    ) -> FileDiagnostic {
+        let mut diagnostic = lsp_types::Diagnostic::new(
+            lsp_types::Range::new(
+                lsp_types::Position::new(line, 4),
+                lsp_types::Position::new(line, 7),
+            ),
+            Some(severity),
+            code.map(|code| lsp_types::NumberOrString::String(code.to_string())),
+            Some("sourcepawn-studio".to_string()),
+            message.to_string(),
+            None,
+            None,
+        );
+        diagnostic.code_description = code.map(|code| lsp_types::CodeDescription {
+            href: lsp_types::Url::parse(&format!("https://example.com/{code}")).unwrap(),
+        });
+        FileDiagnostic {
+            path: path.to_string(),
+            diagnostic,
+        }
+    }
+
+    fn diagnostics() -> Vec<FileDiagnostic> {
+        vec![
+            diagnostic(
+                "plugin.sp",
+                11,
+                DiagnosticSeverity::ERROR,
+                Some("SP0011"),
+                "undefined symbol `foo`",
+            ),
+            diagnostic(
+                "include/shared.inc",
+                2,
+                DiagnosticSeverity::WARNING,
+                None,
+                "unused variable `bar`",
+            ),
+        ]
+    }
+
+    #[test]
+    fn human_output() {
+        assert_eq!(
+            to_human(&diagnostics()),
+            "plugin.sp:12:5: error[SP0011]: undefined symbol `foo`
+include/shared.inc:3:5: warning: unused variable `bar`
+1 error(s), 1 warning(s)"
+        );
+        assert_eq!(to_human(&[]), "0 error(s), 0 warning(s)");
+    }
+
+    #[test]
+    fn json_output() {
+        assert_eq!(
+            to_json(&diagnostics()),
+            json!([
+                {
+                    "path": "plugin.sp",
+                    "range": {
+                        "start": { "line": 11, "character": 4 },
+                        "end": { "line": 11, "character": 7 },
+                    },
+                    "severity": "error",
+                    "code": "SP0011",
+                    "message": "undefined symbol `foo`",
+                },
+                {
+                    "path": "include/shared.inc",
+                    "range": {
+                        "start": { "line": 2, "character": 4 },
+                        "end": { "line": 2, "character": 7 },
+                    },
+                    "severity": "warning",
+                    "code": null,
+                    "message": "unused variable `bar`",
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn sarif_output() {
+        let mut diagnostics = diagnostics();
+        diagnostics.push(diagnostic(
+            "plugin.sp",
+            20,
+            DiagnosticSeverity::HINT,
+            Some("SP0011"),
+            "undefined symbol `baz`",
+        ));
+        let sarif = to_sarif(&diagnostics);
+
+        assert_eq!(sarif["version"], "2.1.0");
+        let run = &sarif["runs"][0];
+        assert_eq!(run["tool"]["driver"]["name"], "sourcepawn-studio");
+        assert_eq!(
+            run["tool"]["driver"]["rules"],
+            json!([
+                { "id": "SP0011", "helpUri": "https://example.com/SP0011" },
+                { "id": "sourcepawn-studio" },
+            ])
+        );
+        assert_eq!(
+            run["results"][0],
+            json!({
+                "ruleId": "SP0011",
+                "level": "error",
+                "message": { "text": "undefined symbol `foo`" },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": "plugin.sp", "uriBaseId": "%SRCROOT%" },
+                        "region": {
+                            "startLine": 12,
+                            "startColumn": 5,
+                            "endLine": 12,
+                            "endColumn": 8,
+                        },
+                    },
+                }],
+            })
+        );
+        let levels = run["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|result| (result["ruleId"].clone(), result["level"].clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            levels,
+            vec![
+                (json!("SP0011"), json!("error")),
+                (json!("sourcepawn-studio"), json!("warning")),
+                (json!("SP0011"), json!("note")),
+            ]
+        );
+    }
+
+    #[test]
+    fn fail_on_errors_and_denied_warnings() {
+        let diagnostics = diagnostics();
+        assert!(is_failure(&diagnostics, false));
+        assert!(is_failure(&diagnostics, true));
+
+        let warnings = &diagnostics[1..];
+        assert!(!is_failure(warnings, false));
+        assert!(is_failure(warnings, true));
+
+        let hints = [diagnostic(
+            "plugin.sp",
+            0,
+            DiagnosticSeverity::HINT,
+            None,
+            "unused include",
+        )];
+        assert!(!is_failure(&hints, true));
+        assert!(!is_failure(&[], true));
+    }
+
+    #[test]
+    fn format_files_with_the_formatter_of_the_server() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = AbsPathBuf::try_from(dir.path().to_path_buf()).unwrap();
+        fs::write(
+            root.join("sourcepawn.toml"),
+            "[formatting]\nbraceStyle = \"attach\"\nuseTabs = false\nindentWidth = 2\n",
+        )
+        .unwrap();
+        fs::create_dir_all(root.join(".hidden")).unwrap();
+        let unformatted = "void Foo()\n{\nint x=1;\n}\n";
+        fs::write(root.join("plugin.sp"), unformatted).unwrap();
+        fs::write(root.join(".hidden/skipped.sp"), unformatted).unwrap();
+
+        let res = format_files(root.clone(), &[root.clone()], true).unwrap();
+        assert!(res.failed);
+        assert_eq!(res.output, format!("{}\n", root.join("plugin.sp")));
+        assert_eq!(
+            fs::read_to_string(root.join("plugin.sp")).unwrap(),
+            unformatted
+        );
+
+        let res = format_files(root.clone(), &[root.clone()], false).unwrap();
+        assert!(!res.failed);
+        assert_eq!(
+            fs::read_to_string(root.join("plugin.sp")).unwrap(),
+            "void Foo() {\n  int x = 1;\n}\n"
+        );
+        assert_eq!(
+            fs::read_to_string(root.join(".hidden/skipped.sp")).unwrap(),
+            unformatted
+        );
+        assert!(!format_files(root.clone(), &[root], true).unwrap().failed);
+    }
+}
@@ -4,15 +4,25 @@
 //! We currently get this config from `initialize` LSP request, which is not the
 //! best way to do it, but was the simplest thing we could implement.
 
-use ide::{DiagnosticsConfig, HoverConfig, HoverDocFormat};
+use base_db::{IncludeLimits, IncludeSearchLocation, IncludeSearchOrder};
+use flycheck::CompileOptions;
+use fxhash::{FxHashMap, FxHashSet};
+use ide::{
+    AnnotationConfig, BraceStyle, DiagnosticsConfig, ExperimentalFlag, ExperimentalFlags,
+    FormattingConfig, HoverConfig, HoverDocFormat, IndentStyle, InlayHintsConfig,
+    ParameterHintsMode,
+};
 use itertools::Itertools;
 use lsp_types::{ClientCapabilities, MarkupKind};
-use paths::AbsPathBuf;
-use serde::de::DeserializeOwned;
+use paths::{AbsPath, AbsPathBuf};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::iter;
 use std::{collections::HashSet, fmt, path::PathBuf};
 
+use crate::discovery;
+use crate::editorconfig::EditorConfig;
 use crate::lsp;
+use crate::manifest::{Manifest, SOURCEKNIGHT_MANIFEST_NAME};
 use crate::{line_index::PositionEncoding, lsp::ext::negotiated_encoding};
 
 macro_rules! try_ {
@@ -36,7 +46,11 @@ macro_rules! try_or_def {
 
 config_data! {
     struct ConfigData {
-        /// Warm up caches on project load.
+        /// Experimental analyses to enable, e.g `{ "unusedIncludes": true }`. They are still in
+        /// development and may report false positives or slow the server down.
+        analysis_experimental: FxHashMap<String, bool> = "{}",
+
+        /// Warm up caches on project load, and analyze the opened files while the server is idle.
         cachePriming_enable: bool = "true",
         /// How many worker threads to handle priming caches. The default `0` means to pick automatically.
         cachePriming_numThreads: ParallelCachePrimingNumThreads = "0",
@@ -46,17 +60,71 @@ config_data! {
         /// the server's RAM usage.
         /// `0` for unlimited projects.
         cachePriming_projectsThreshold: usize = "5",
+        /// Whether to show the `▶ Compile` lens above `OnPluginStart`, or at the top of the main
+        /// file of a plugin.
+        codeLens_compile_enable: bool = "true",
+        /// Whether to show the number of references above the global functions and methodmaps.
+        codeLens_references_enable: bool = "true",
         /// Linter arguments that will be passed to spcomp.
         /// Note that the compilation target, include directories and output path are already handled by the server.
         compiler_arguments: Vec<String> = "[]",
+        /// Symbols defined when compiling with the `sourcepawn-studio/compile` request, e.g
+        /// `DEBUG=1`.
+        compiler_defines: Vec<String> = "[]",
         /// Compute spcomp diagnostics on save.
+        /// They can also be computed on demand with the `sourcepawn-studio/runFlycheck` notification.
         compiler_onSave: bool = "true",
+        /// Optimization level passed to spcomp with `-O` when compiling with the
+        /// `sourcepawn-studio/compile` request. The default of spcomp is used if `null`.
+        compiler_optimizationLevel: Option<usize> = "null",
+        /// Directory of the `.smx` files compiled with the `sourcepawn-studio/compile` request.
+        /// Relative paths are resolved from the root of the workspace. The `.smx` file is written
+        /// next to the compiled file if `null`.
+        compiler_outputDirectory: Option<String> = "null",
         /// Path to the SourcePawn compiler (spcomp).
         compiler_path: Option<String> = "null",
+        /// Verbosity level passed to spcomp with `-v` when compiling with the
+        /// `sourcepawn-studio/compile` request. The default of spcomp is used if `null`.
+        compiler_verbosity: Option<usize> = "null",
+
+        /// Include the content of the file being analyzed in the reproducer bundles written when
+        /// the server crashes. The bundles are only written to a local temporary directory.
+        crashReports_includeFileContent: bool = "false",
+
+        /// Path of the diagnostics baseline of the project, written with the
+        /// `sourcepawn-studio/writeDiagnosticsBaseline` request. The diagnostics of the baseline
+        /// are not reported, so that only the new ones are. Relative paths are resolved from the
+        /// root of the workspace.
+        diagnostics_baselineFile: Option<String> = "null",
+        /// Additional macro configurations the files are analyzed in, e.g
+        /// `[["DEBUG"], ["MAX_PLAYERS=64"]]`. The syntax errors of the code which is only active in a
+        /// configuration, e.g in an else branch, are reported along with the others.
+        diagnostics_configurations: Vec<Vec<String>> = "[]",
+        /// List of diagnostic codes to disable, e.g `SP0011` or `SPLINT-inactive-code`.
+        diagnostics_disabled: FxHashSet<String> = "[]",
+        /// Whether to check the directives of the inactive regions, and to report the ones which
+        /// would be errors if the region was active.
+        diagnostics_lintInactiveCode: bool = "false",
 
         /// Name of the game we want the events for, as it appears on the Alliedmodders website.
         /// For example, "Counter-Strike: Global Offensive" or "Team Fortress 2".
         eventsGameName: Option<String> = "null",
+        /// Files included at the top of every file, after the implicit includes, so that their
+        /// macros are defined everywhere. Paths are resolved from the root of the workspace, then
+        /// from the include directories.
+        forceIncludes: Vec<String> = "[]",
+        /// Placement of the opening braces of the blocks: on their own line with `allman`, or at
+        /// the end of the line of their statement with `attach`.
+        formatting_braceStyle: BraceStyleDef = "\"allman\"",
+        /// Maximum length of the lines, beyond which the arguments of the calls are wrapped. `0`
+        /// disables the wrapping.
+        formatting_columnLimit: usize = "100",
+        /// Number of spaces of an indentation level, when indenting with spaces. The `indent_size`
+        /// of the `.editorconfig` files, then the tab size of the editor, are used if `null`.
+        formatting_indentWidth: Option<usize> = "null",
+        /// Whether to indent with tabs instead of spaces. The `indent_style` of the `.editorconfig`
+        /// files, then the option of the editor, are used if `null`, and tabs by default.
+        formatting_useTabs: Option<bool> = "null",
 
         /// Whether to show `Debug` action. Only applies when
         /// `#SourcePawnLanguageServer.hover.actions.enable#` is set.
@@ -76,13 +144,45 @@ config_data! {
         /// `#SourcePawnLanguageServer.hover.actions.enable#` is set.
         hover_actions_run_enable: bool             = "true",
 
+        /// Files implicitly included at the top of every file, like spcomp does with `sourcemod`.
+        /// Set it to `[]` for standalone includes or projects which do not use SourceMod.
+        implicitIncludes: Vec<String> = "[\"sourcemod\"]",
         /// Include directories paths for the compiler and the linter.
         includeDirectories: Vec<PathBuf> = "[]",
+        /// Maximum depth of the include chains, the main file being at depth `0`. Deeper includes
+        /// are not expanded and reported. `0` for unlimited depth.
+        includeLimits_depth: usize = "50",
+        /// Maximum number of files included by a project. The includes above the limit are not
+        /// expanded and reported. `0` for unlimited files.
+        includeLimits_files: usize = "2000",
+        /// Locations searched, in order, to resolve the includes with chevrons, e.g
+        /// `#include <foo>`, and the implicit includes.
+        includeSearchOrder_chevrons: Vec<IncludeSearchLocationDef> = "[\"workspace\", \"includeDirectories\"]",
+        /// Locations searched, in order, to resolve the includes with quotes, e.g
+        /// `#include "foo"`. `file` is the directory of the including file and its `include`
+        /// subdirectory, `workspace` is the root of the workspace, and `includeDirectories` are
+        /// the include directories, in the order of the setting.
+        includeSearchOrder_quotes: Vec<IncludeSearchLocationDef> = "[\"file\", \"workspace\", \"includeDirectories\"]",
+
+        /// Arguments of the calls which get the name of their parameter as an inlay hint: `all`
+        /// of them, only the `literals`, or `off`.
+        inlayHints_parameterHints: ParameterHintsDef = "\"literals\"",
 
         /// Disable the language server's syntax linter. This is independant from spcomp.
         linter_disable: bool = "false",
+        /// Main file of the files which are included by several plugins, or whose plugin can't be
+        /// detected, e.g `{ "scripting/modules/shared.sp": "scripting/plugin.sp" }`.
+        /// Relative paths are resolved from the root of the workspace.
+        mainPaths: FxHashMap<String, String> = "{}",
         /// How many worker threads in the main loop. The default `null` means to pick automatically.
         numThreads: Option<usize> = "null",
+        /// Version of SourceMod whose includes are downloaded, cached and searched before the
+        /// include directories, e.g `1.12`, `dev` for the development branch, or a build such as
+        /// `1.12.0-git7110` to pin it. The includes installed locally are used if `null`.
+        sourcemod_version: Option<String> = "null",
+        /// Maximum number of symbols returned by the workspace symbol search. `0` for unlimited
+        /// symbols.
+        workspaceSymbol_searchLimit: usize = "128",
     }
 }
 
@@ -100,9 +200,22 @@ pub struct Config {
     caps: lsp_types::ClientCapabilities,
     root_path: AbsPathBuf,
     data: ConfigData,
+    /// Settings sent by the editor.
+    editor_json: serde_json::Value,
+    /// Settings of the [`PROJECT_FILE_NAME`] file of the root, if any.
+    project_json: Option<serde_json::Value>,
+    /// Package manifest of the root, if any.
+    manifest: Option<Manifest>,
+    /// Directories holding the `.sp` files of the plugins of the workspace.
+    scripting_roots: Vec<AbsPathBuf>,
+    /// Version of SourceMod and directory of its downloaded includes, if any.
+    sourcemod_includes: Option<(String, AbsPathBuf)>,
     is_visual_studio_code: bool,
 }
 
+/// Name of the project file which holds settings shared by the users of a project.
+pub const PROJECT_FILE_NAME: &str = "sourcepawn.toml";
+
 #[derive(Debug)]
 pub struct ConfigError {
     errors: Vec<(String, serde_json::Error)>,
@@ -136,6 +249,11 @@ impl Config {
         Config {
             caps,
             data: ConfigData::default(),
+            editor_json: serde_json::Value::Null,
+            project_json: None,
+            manifest: None,
+            scripting_roots: Vec::new(),
+            sourcemod_includes: None,
             root_path,
             workspace_roots,
             is_visual_studio_code,
@@ -147,6 +265,59 @@ impl Config {
         if json.is_null() || json.as_object().is_some_and(|it| it.is_empty()) {
             return Ok(());
         }
+        self.editor_json = json;
+
+        self.apply()
+    }
+
+    /// Load the [`PROJECT_FILE_NAME`] file of the root, whose settings are merged under the
+    /// settings of the editor, the package manifest of the root, and the scripting roots of the
+    /// workspace.
+    pub fn load_project_file(&mut self) -> Result<(), ConfigError> {
+        let path = self.root_path.join(PROJECT_FILE_NAME);
+        let mut errors = Vec::new();
+        self.scripting_roots = discovery::scripting_roots(&self.workspace_root_paths());
+        self.manifest = match Manifest::load(&self.root_path) {
+            Some(Ok(manifest)) => Some(manifest),
+            Some(Err(err)) => {
+                errors.push((
+                    SOURCEKNIGHT_MANIFEST_NAME.to_string(),
+                    serde::de::Error::custom(err),
+                ));
+                None
+            }
+            None => None,
+        };
+        self.project_json = match std::fs::read_to_string(&path) {
+            Ok(text) => match toml::from_str::<serde_json::Value>(&text) {
+                Ok(json) => Some(json),
+                Err(err) => {
+                    errors.push((PROJECT_FILE_NAME.to_string(), serde::de::Error::custom(err)));
+                    None
+                }
+            },
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+            Err(err) => {
+                errors.push((PROJECT_FILE_NAME.to_string(), serde::de::Error::custom(err)));
+                None
+            }
+        };
+        let res = self.apply();
+        if errors.is_empty() {
+            return res;
+        }
+        if let Err(err) = res {
+            errors.extend(err.errors);
+        }
+
+        Err(ConfigError { errors })
+    }
+
+    fn apply(&mut self) -> Result<(), ConfigError> {
+        let json = match &self.project_json {
+            Some(project_json) => merge_project_json(project_json.clone(), &self.editor_json),
+            None => self.editor_json.clone(),
+        };
         let mut errors = Vec::new();
         self.data = ConfigData::from_json(json, &mut errors);
         tracing::debug!("deserialized config data: {:#?}", self.data);
@@ -175,10 +346,38 @@ impl Config {
         &self.caps
     }
 
+    /// Whether the client only folds whole lines.
+    pub fn line_folding_only(&self) -> bool {
+        self.caps
+            .text_document
+            .as_ref()
+            .and_then(|it| it.folding_range.as_ref())
+            .and_then(|it| it.line_folding_only)
+            .unwrap_or(false)
+    }
+
     pub fn workspace_roots(&self) -> &[PathBuf] {
         &self.workspace_roots
     }
 
+    /// The root followed by the other workspace roots, e.g the folders of a multi-root workspace.
+    pub fn workspace_root_paths(&self) -> Vec<AbsPathBuf> {
+        let mut res = vec![self.root_path.clone()];
+        for path in self.workspace_roots.iter().cloned() {
+            if let Ok(path) = AbsPathBuf::try_from(path) {
+                if !res.contains(&path) {
+                    res.push(path);
+                }
+            }
+        }
+
+        res
+    }
+
+    pub fn scripting_roots(&self) -> &[AbsPathBuf] {
+        &self.scripting_roots
+    }
+
     pub fn is_visual_studio_code(&self) -> bool {
         self.is_visual_studio_code
     }
@@ -195,20 +394,130 @@ impl Config {
     }
 
     pub fn diagnostics(&self) -> DiagnosticsConfig {
+        let mut disabled = self.data.diagnostics_disabled.clone();
+        if self.inactive_regions_notification() {
+            // The inactive regions are already sent to the client.
+            disabled.insert("SPLINT-inactive-code".to_string());
+        }
+        if !self.data.diagnostics_lintInactiveCode {
+            disabled.insert("SPLINT-inactive-directive".to_string());
+        }
         DiagnosticsConfig {
             enabled: true,
-            disable_experimental: false,
-            disabled: HashSet::default(),
+            experimental: self.experimental_flags(),
+            disabled,
         }
     }
 
-    pub fn include_directories(&self) -> Vec<AbsPathBuf> {
-        // FIXME: Instead of dropping invalid paths, we should report them to the user.
+    pub fn diagnostics_baseline_file(&self) -> Option<AbsPathBuf> {
         self.data
+            .diagnostics_baselineFile
+            .as_ref()
+            .map(|path| self.root_path.join(path))
+    }
+
+    /// Experimental analyses enabled by the user.
+    pub fn experimental_flags(&self) -> ExperimentalFlags {
+        self.data
+            .analysis_experimental
+            .iter()
+            .filter(|(_, enabled)| **enabled)
+            .filter_map(|(name, _)| ExperimentalFlag::from_name(name))
+            .collect()
+    }
+
+    pub fn include_directories(&self) -> Vec<AbsPathBuf> {
+        // The downloaded SourceMod includes come first, so that they take precedence over the
+        // SourceMod includes installed locally, e.g in a `scripting/include` directory.
+        let mut res = self.sourcemod_includes().cloned().into_iter().collect_vec();
+        // Relative paths, e.g from the project file, are relative to the root.
+        let configured = self
+            .data
             .includeDirectories
-            .clone()
-            .into_iter()
-            .flat_map(AbsPathBuf::try_from)
+            .iter()
+            .map(|path| self.root_path.join(path));
+        // The directories of the manifest and of the scripting roots come last, so that the
+        // settings take precedence.
+        let inferred = self
+            .manifest
+            .iter()
+            .flat_map(|manifest| manifest.include_directories(&self.root_path))
+            .chain(discovery::include_directories(&self.scripting_roots));
+        for path in configured.chain(inferred) {
+            if !res.contains(&path) {
+                res.push(path);
+            }
+        }
+
+        res
+    }
+
+    pub fn sourcemod_version(&self) -> Option<&str> {
+        self.data.sourcemod_version.as_deref()
+    }
+
+    /// Directory of the downloaded includes of the SourceMod version of the settings, if they
+    /// are downloaded.
+    pub fn sourcemod_includes(&self) -> Option<&AbsPathBuf> {
+        match &self.sourcemod_includes {
+            Some((version, path)) if Some(version.as_str()) == self.sourcemod_version() => {
+                Some(path)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn set_sourcemod_includes(&mut self, version: String, path: AbsPathBuf) {
+        self.sourcemod_includes = Some((version, path));
+    }
+
+    /// Files included at the top of every file, i.e the implicit includes followed by the
+    /// force-included files.
+    pub fn implicit_includes(&self) -> Vec<String> {
+        self.data
+            .implicitIncludes
+            .iter()
+            .chain(self.data.forceIncludes.iter())
+            .cloned()
+            .collect_vec()
+    }
+
+    pub fn include_search_order(&self) -> IncludeSearchOrder {
+        let locations = |locations: &[IncludeSearchLocationDef]| {
+            locations
+                .iter()
+                .map(|location| match location {
+                    IncludeSearchLocationDef::File => IncludeSearchLocation::File,
+                    IncludeSearchLocationDef::Workspace => IncludeSearchLocation::Workspace,
+                    IncludeSearchLocationDef::IncludeDirectories => {
+                        IncludeSearchLocation::IncludeDirectories
+                    }
+                })
+                .collect_vec()
+        };
+        IncludeSearchOrder {
+            quotes: locations(&self.data.includeSearchOrder_quotes),
+            chevrons: locations(&self.data.includeSearchOrder_chevrons),
+        }
+    }
+
+    /// Additional macro configurations, as the definitions of each configuration.
+    pub fn macro_configurations(&self) -> Vec<Vec<String>> {
+        self.data.diagnostics_configurations.clone()
+    }
+
+    pub fn include_limits(&self) -> IncludeLimits {
+        IncludeLimits {
+            depth: self.data.includeLimits_depth,
+            files: self.data.includeLimits_files,
+        }
+    }
+
+    pub fn main_paths(&self) -> Vec<(AbsPathBuf, AbsPathBuf)> {
+        self.data
+            .mainPaths
+            .iter()
+            .map(|(path, main_path)| (self.root_path.join(path), self.root_path.join(main_path)))
             .collect_vec()
     }
 
@@ -248,8 +557,10 @@ impl Config {
         ClientCommandsConfig {
             // run_single: get("sourcepawn-vscode.runSingle"),
             // debug_single: get("sourcepawn-vscode.debugSingle"),
-            // show_reference: get("sourcepawn-vscode.showReferences"),
+            show_reference: get("sourcepawn-vscode.showReferences"),
             goto_location: get("sourcepawn-vscode.gotoLocation"),
+            compile: get("sourcepawn-vscode.compileSM"),
+            trigger_rename: get("sourcepawn-vscode.triggerRename"),
             // trigger_parameter_hints: get("editor.action.triggerParameterHints"),
         }
     }
@@ -268,6 +579,94 @@ impl Config {
             || nb_projects <= self.data.cachePriming_projectsThreshold
     }
 
+    pub fn workspace_symbol_search_limit(&self) -> usize {
+        self.data.workspaceSymbol_searchLimit
+    }
+
+    pub fn annotations(&self) -> AnnotationConfig {
+        let commands = self.client_commands();
+        AnnotationConfig {
+            annotate_references: self.data.codeLens_references_enable && commands.show_reference,
+            annotate_compile: self.data.codeLens_compile_enable && commands.compile,
+        }
+    }
+
+    pub fn code_lens_refresh(&self) -> bool {
+        try_or_def!(
+            self.caps
+                .workspace
+                .as_ref()?
+                .code_lens
+                .as_ref()?
+                .refresh_support?
+        )
+    }
+
+    pub fn inlay_hints(&self) -> InlayHintsConfig {
+        InlayHintsConfig {
+            parameter_hints: match self.data.inlayHints_parameterHints {
+                ParameterHintsDef::All => ParameterHintsMode::All,
+                ParameterHintsDef::Literals => ParameterHintsMode::Literals,
+                ParameterHintsDef::Off => ParameterHintsMode::Off,
+            },
+        }
+    }
+
+    /// Configuration of the formatter for the file at `path`. The indentation which is not set
+    /// is read from the `.editorconfig` files of the file, then from the options of the editor,
+    /// if any.
+    ///
+    /// The line endings of the `.editorconfig` files only apply without an editor, which keeps
+    /// the line endings of its documents.
+    pub fn formatting(
+        &self,
+        path: &AbsPath,
+        options: Option<&lsp_types::FormattingOptions>,
+    ) -> FormattingConfig {
+        let editorconfig = EditorConfig::load(path);
+        let use_tabs = self
+            .data
+            .formatting_useTabs
+            .or(editorconfig.use_tabs)
+            .or(options.map(|options| !options.insert_spaces))
+            .unwrap_or(true);
+        let indent_width = self
+            .data
+            .formatting_indentWidth
+            .map(|width| width as u32)
+            .or(editorconfig.indent_size)
+            .or(options.map(|options| options.tab_size))
+            .unwrap_or(4);
+        FormattingConfig {
+            indent_style: if use_tabs {
+                IndentStyle::Tabs
+            } else {
+                IndentStyle::Spaces(indent_width)
+            },
+            brace_style: match self.data.formatting_braceStyle {
+                BraceStyleDef::Allman => BraceStyle::Allman,
+                BraceStyleDef::Attach => BraceStyle::Attach,
+            },
+            column_limit: self.data.formatting_columnLimit,
+            end_of_line: editorconfig.end_of_line.filter(|_| options.is_none()),
+            insert_final_newline: editorconfig
+                .insert_final_newline
+                .or(options.and_then(|options| options.insert_final_newline))
+                .unwrap_or(true),
+        }
+    }
+
+    pub fn inlay_hints_refresh(&self) -> bool {
+        try_or_def!(
+            self.caps
+                .workspace
+                .as_ref()?
+                .inlay_hint
+                .as_ref()?
+                .refresh_support?
+        )
+    }
+
     pub fn semantic_tokens_refresh(&self) -> bool {
         try_or_def!(
             self.caps
@@ -318,6 +717,14 @@ impl Config {
         self.experimental("serverStatusNotification")
     }
 
+    pub fn inactive_regions_notification(&self) -> bool {
+        self.experimental("inactiveRegionsNotification")
+    }
+
+    pub fn snippet_text_edit(&self) -> bool {
+        self.experimental("snippetTextEdit")
+    }
+
     pub fn compiler_path(&self) -> Option<&str> {
         self.data.compiler_path.as_deref()
     }
@@ -330,6 +737,31 @@ impl Config {
         self.data.compiler_onSave
     }
 
+    /// Options of the compilations of the `sourcepawn-studio/compile` request.
+    pub fn compile_options(&self) -> CompileOptions {
+        CompileOptions {
+            optimization_level: self.data.compiler_optimizationLevel,
+            verbosity: self.data.compiler_verbosity,
+            defines: self.data.compiler_defines.clone(),
+        }
+    }
+
+    pub fn compiler_output_directory(&self) -> Option<AbsPathBuf> {
+        self.data
+            .compiler_outputDirectory
+            .as_ref()
+            .map(|path| self.root_path.join(path))
+    }
+
+    pub fn crash_reports_include_file_content(&self) -> bool {
+        self.data.crashReports_includeFileContent
+    }
+
+    /// Settings in effect, as they are reported in the crash reproducer bundles.
+    pub fn data_json(&self) -> serde_json::Value {
+        serde_json::to_value(&self.data).unwrap_or_default()
+    }
+
     pub fn hover(&self) -> HoverConfig {
         HoverConfig {
             // TODO: Impl these configs
@@ -362,11 +794,36 @@ impl Config {
 
 type ParallelCachePrimingNumThreads = u8;
 
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum IncludeSearchLocationDef {
+    File,
+    Workspace,
+    IncludeDirectories,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ParameterHintsDef {
+    All,
+    Literals,
+    Off,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum BraceStyleDef {
+    Allman,
+    Attach,
+}
+
 pub struct ClientCommandsConfig {
     // pub run_single: bool,
     // pub debug_single: bool,
-    // pub show_reference: bool,
+    pub show_reference: bool,
     pub goto_location: bool,
+    pub compile: bool,
+    pub trigger_rename: bool,
     // pub trigger_parameter_hints: bool,
 }
 
@@ -461,6 +918,44 @@ macro_rules! _config_data {
 }
 use _config_data as config_data;
 
+/// Merge the settings of the project file under the settings of the editor.
+///
+/// Editors usually send the default value of the settings which are not set, e.g VSCode, so a
+/// setting of the editor only overrides the project file if it differs from its default value.
+fn merge_project_json(
+    mut project_json: serde_json::Value,
+    editor_json: &serde_json::Value,
+) -> serde_json::Value {
+    let serde_json::Value::Object(defaults) =
+        serde_json::to_value(ConfigData::default()).expect("config data should serialize")
+    else {
+        return project_json;
+    };
+    for (field, default) in defaults {
+        let pointer = format!("/{}", field.replace('_', "/"));
+        let Some(value) = editor_json.pointer(&pointer) else {
+            continue;
+        };
+        if *value == default && project_json.pointer(&pointer).is_some() {
+            continue;
+        }
+        let mut target = &mut project_json;
+        for key in field.split('_') {
+            if !target.is_object() {
+                *target = serde_json::Value::Object(Default::default());
+            }
+            target = target
+                .as_object_mut()
+                .unwrap()
+                .entry(key)
+                .or_insert(serde_json::Value::Null);
+        }
+        *target = value.clone();
+    }
+
+    project_json
+}
+
 fn get_field<T: DeserializeOwned>(
     json: &mut serde_json::Value,
     error_sink: &mut Vec<(String, serde_json::Error)>,
@@ -534,6 +1029,13 @@ fn field_props(field: &str, ty: &str, doc: &[&str], default: &str) -> serde_json
             "type": "array",
             "items": { "type": "string" },
         },
+        "Vec<Vec<String>>" => set! {
+            "type": "array",
+            "items": {
+                "type": "array",
+                "items": { "type": "string" },
+            },
+        },
         "Vec<PathBuf>" => set! {
             "type": "array",
             "items": { "type": "string" },
@@ -549,6 +1051,10 @@ fn field_props(field: &str, ty: &str, doc: &[&str], default: &str) -> serde_json
         "FxHashMap<String, SnippetDef>" => set! {
             "type": "object",
         },
+        "FxHashMap<String, bool>" => set! {
+            "type": "object",
+            "additionalProperties": { "type": "boolean" },
+        },
         "FxHashMap<String, String>" => set! {
             "type": "object",
         },
@@ -572,6 +1078,35 @@ fn field_props(field: &str, ty: &str, doc: &[&str], default: &str) -> serde_json
             "type": ["null", "array"],
             "items": { "type": "string" },
         },
+        "Vec<IncludeSearchLocationDef>" => set! {
+            "type": "array",
+            "items": {
+                "type": "string",
+                "enum": ["file", "workspace", "includeDirectories"],
+                "enumDescriptions": [
+                    "Directory of the including file, and its `include` subdirectory.",
+                    "Root of the workspace.",
+                    "Include directories, in the order of the setting."
+                ],
+            },
+        },
+        "ParameterHintsDef" => set! {
+            "type": "string",
+            "enum": ["all", "literals", "off"],
+            "enumDescriptions": [
+                "Show the names of the parameters of all the arguments.",
+                "Only show the names of the parameters of the literal arguments, e.g `true` or `\"foo\"`.",
+                "Do not show the names of the parameters."
+            ],
+        },
+        "BraceStyleDef" => set! {
+            "type": "string",
+            "enum": ["allman", "attach"],
+            "enumDescriptions": [
+                "Put the braces on their own line.",
+                "Put the braces at the end of the line of their statement."
+            ],
+        },
         "ParallelCachePrimingNumThreads" => set! {
             "type": "number",
             "minimum": 0,
@@ -767,6 +1302,58 @@ title: VSCode Settings Reference
         ensure_file_contents(&docs_path, &expected);
     }
 
+    #[test]
+    fn project_file_is_merged_under_editor_settings() {
+        let project_json = toml::from_str::<Value>(
+            r#"
+includeDirectories = ["include"]
+
+[compiler]
+path = "spcomp"
+arguments = ["-O2"]
+
+[diagnostics]
+disabled = ["SP0011"]
+"#,
+        )
+        .unwrap();
+        let editor_json = serde_json::json!({
+            "compiler": { "arguments": [], "path": "/usr/bin/spcomp" },
+            "diagnostics": { "disabled": [] },
+            "includeDirectories": [],
+        });
+        let data = ConfigData::from_json(
+            merge_project_json(project_json, &editor_json),
+            &mut Vec::new(),
+        );
+        assert_eq!(data.includeDirectories, vec![PathBuf::from("include")]);
+        assert_eq!(data.compiler_path.as_deref(), Some("/usr/bin/spcomp"));
+        assert_eq!(data.compiler_arguments, vec!["-O2".to_string()]);
+        assert!(data.diagnostics_disabled.contains("SP0011"));
+    }
+
+    #[test]
+    fn downloaded_sourcemod_includes_come_first() {
+        let root = AbsPathBuf::assert(project_root().join("test_data"));
+        let mut config = Config::new(root.clone(), Default::default(), Vec::new(), false);
+        config
+            .update(serde_json::json!({
+                "includeDirectories": ["include"],
+                "sourcemod": { "version": "1.12" },
+            }))
+            .unwrap();
+        assert_eq!(config.include_directories(), vec![root.join("include")]);
+
+        let downloaded = root.join("sourcemod-1.12/include");
+        config.set_sourcemod_includes("1.12".to_string(), downloaded.clone());
+        assert_eq!(
+            config.include_directories(),
+            vec![downloaded.clone(), root.join("include")]
+        );
+        config.set_sourcemod_includes("1.11".to_string(), downloaded);
+        assert_eq!(config.include_directories(), vec![root.join("include")]);
+    }
+
     fn remove_ws(text: &str) -> String {
         text.replace(char::is_whitespace, "")
     }
@@ -0,0 +1,262 @@
+//! Reproducer bundles for the crashes of the server.
+//!
+//! When the analysis panics, the server writes what is needed to investigate the crash into a
+//! temporary directory, and tells the user where to find it so that it can be attached to a bug
+//! report. The paths of the files and of the settings are anonymized, and the content of the files
+//! is only included when the user opted in.
+
+use std::{
+    fmt::Write as _,
+    fs, io,
+    path::{Component, Path, PathBuf},
+    sync::Arc,
+};
+
+use lsp_types::{notification::ShowMessage, MessageType, ShowMessageParams, Url};
+use nohash_hasher::IntMap;
+use parking_lot::RwLock;
+use paths::AbsPath;
+use vfs::{FileId, VfsPath};
+
+use crate::{
+    client::LspClient, config::Config, line_index::LineEndings, lsp::from_proto, version::version,
+    GlobalState,
+};
+
+/// Writes the reproducer bundles of the crashes happening while handling an event.
+pub(crate) struct CrashReporter {
+    client: LspClient,
+    config: Arc<Config>,
+    vfs: Arc<RwLock<(vfs::Vfs, IntMap<FileId, LineEndings>)>>,
+}
+
+impl CrashReporter {
+    pub(crate) fn new(state: &GlobalState) -> Self {
+        Self {
+            client: state.client.clone(),
+            config: Arc::clone(&state.config),
+            vfs: Arc::clone(&state.vfs),
+        }
+    }
+
+    /// Write the reproducer bundle of the last panic of the current thread, which happened while
+    /// handling a message with the given `params`, and tell the user where it is.
+    pub(crate) fn report(&self, method: &str, params: &serde_json::Value) {
+        let Some(panic) = stdx::panic_context::take_last_panic() else {
+            return;
+        };
+        let uri = params
+            .pointer("/textDocument/uri")
+            .and_then(|uri| serde_json::from_value::<Url>(uri.clone()).ok());
+        let path = match self.write_bundle(method, &panic, uri.as_ref()) {
+            Ok(path) => path,
+            Err(err) => {
+                log::error!("Failed to write the crash reproducer bundle: {}", err);
+                return;
+            }
+        };
+        log::error!("Wrote the crash reproducer bundle to {}", path.display());
+        let message = format!(
+            "sourcepawn-studio crashed while handling `{}`. A reproducer bundle was written to {}, \
+             please attach it to your bug report.",
+            method,
+            path.display()
+        );
+        let _ = self
+            .client
+            .send_notification::<ShowMessage>(ShowMessageParams {
+                typ: MessageType::ERROR,
+                message,
+            });
+    }
+
+    fn write_bundle(&self, method: &str, panic: &str, uri: Option<&Url>) -> io::Result<PathBuf> {
+        let dir = tempfile::Builder::new()
+            .prefix("sourcepawn-studio-crash-")
+            .tempdir()?
+            .keep();
+        let root = self.config.root_path();
+        let vfs = &self.vfs.read().0;
+        let path = uri.and_then(|uri| from_proto::vfs_path(uri).ok());
+
+        let mut report = format!("version: {}\nmethod: {}\n", version(), method);
+        if let Some(path) = path.as_ref().and_then(VfsPath::as_path) {
+            let _ = writeln!(report, "file: {}", anonymize(root, path));
+        }
+        report.push('\n');
+        report.push_str(panic);
+        fs::write(dir.join("panic.txt"), report)?;
+
+        let mut config = self.config.data_json();
+        anonymize_config(root, &mut config);
+        let config = serde_json::to_string_pretty(&config)?;
+        fs::write(dir.join("config.json"), config)?;
+
+        let mut files = vfs
+            .iter()
+            .filter_map(|(_, path)| Some(anonymize(root, path.as_path()?)))
+            .collect::<Vec<_>>();
+        files.sort();
+        fs::write(dir.join("files.txt"), files.join("\n"))?;
+
+        if self.config.crash_reports_include_file_content() {
+            if let Some((file_id, path)) = path
+                .as_ref()
+                .and_then(|path| Some((vfs.file_id(path)?, path.as_path()?)))
+            {
+                let name = path.file_name().unwrap_or_default();
+                fs::write(dir.join(name), vfs.file_contents(file_id))?;
+            }
+        }
+
+        Ok(dir)
+    }
+}
+
+/// Settings whose values are paths, relative to the root of the project or absolute.
+const PATH_SETTINGS: &[&str] = &[
+    "compiler_outputDirectory",
+    "compiler_path",
+    "diagnostics_baselineFile",
+    "forceIncludes",
+    "implicitIncludes",
+    "includeDirectories",
+    "mainPaths",
+];
+
+/// Settings whose values are arguments of the compiler, which may contain paths, e.g `-i<path>` or
+/// `NAME=<path>`.
+const ARGUMENT_SETTINGS: &[&str] = &["compiler_arguments", "compiler_defines"];
+
+/// Anonymize the paths of the settings, and the keys of `mainPaths` which are paths too, and the
+/// paths in the arguments of the compiler.
+fn anonymize_config(root: &AbsPath, config: &mut serde_json::Value) {
+    let anonymize_value = |value: &mut serde_json::Value| {
+        if let Some(path) = value.as_str() {
+            *value = anonymize(root, &root.join(path)).into();
+        }
+    };
+    for key in PATH_SETTINGS {
+        match config.get_mut(*key) {
+            Some(serde_json::Value::Array(values)) => values.iter_mut().for_each(anonymize_value),
+            Some(serde_json::Value::Object(values)) => {
+                *values = std::mem::take(values)
+                    .into_iter()
+                    .map(|(path, mut value)| {
+                        anonymize_value(&mut value);
+                        (anonymize(root, &root.join(path)), value)
+                    })
+                    .collect();
+            }
+            Some(value) => anonymize_value(value),
+            None => (),
+        }
+    }
+    for key in ARGUMENT_SETTINGS {
+        let Some(serde_json::Value::Array(values)) = config.get_mut(*key) else {
+            continue;
+        };
+        for value in values {
+            if let Some(argument) = value.as_str() {
+                *value = anonymize_argument(root, argument).into();
+            }
+        }
+    }
+}
+
+/// Anonymize the path in an argument of the compiler, which follows the name of its option or
+/// define, e.g `-i<path>`, `-i=<path>` or `NAME=<path>`, or is the whole argument.
+fn anonymize_argument(root: &AbsPath, argument: &str) -> String {
+    let name_len = match argument.split_once('=') {
+        Some((name, _)) => name.len() + 1,
+        None if argument.starts_with('-') => argument
+            .char_indices()
+            .nth(2)
+            .map_or(argument.len(), |(idx, _)| idx),
+        None => 0,
+    };
+    let (name, value) = argument.split_at(name_len);
+    if !value.contains(['/', '\\']) {
+        return argument.to_owned();
+    }
+
+    format!("{}{}", name, anonymize(root, &root.join(value)))
+}
+
+/// Replace each component of `path` with its hash, keeping the extension, so that the layout of
+/// the project is preserved without leaking the names of its files.
+fn anonymize(root: &AbsPath, path: &AbsPath) -> String {
+    let (prefix, path): (_, &Path) = match path.strip_prefix(root) {
+        Some(path) => ("<root>", path.as_ref()),
+        None => ("<external>", path.as_ref()),
+    };
+    let mut res = prefix.to_owned();
+    for component in path.components() {
+        let Component::Normal(component) = component else {
+            continue;
+        };
+        let component = component.to_string_lossy();
+        let (stem, ext) = match component.rsplit_once('.') {
+            Some((stem, ext)) if !stem.is_empty() => (stem, Some(ext)),
+            _ => (component.as_ref(), None),
+        };
+        let _ = write!(res, "/{:08x}", fxhash::hash32(stem));
+        if let Some(ext) = ext {
+            let _ = write!(res, ".{}", ext);
+        }
+    }
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use paths::AbsPathBuf;
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn anonymize_path_settings() {
+        let root = AbsPathBuf::assert(std::env::temp_dir().join("project"));
+        let mut config = json!({
+            "compiler_path": root.join("tools/spcomp").to_string(),
+            "compiler_outputDirectory": null,
+            "includeDirectories": ["include", "/opt/sourcemod/include"],
+            "implicitIncludes": ["secret/lib"],
+            "mainPaths": { "modules/secret.sp": "secret.sp" },
+            "compiler_arguments": ["-O2", "-i/home/secret/include", "-o=build/secret.smx"],
+            "compiler_defines": ["DEBUG=1", "CONFIG=configs/secret.cfg"],
+            "linter_disable": false,
+        });
+        anonymize_config(&root, &mut config);
+
+        let hash = |name: &str| format!("{:08x}", fxhash::hash32(name));
+        assert_eq!(
+            config,
+            json!({
+                "compiler_path": format!("<root>/{}/{}", hash("tools"), hash("spcomp")),
+                "compiler_outputDirectory": null,
+                "includeDirectories": [
+                    format!("<root>/{}", hash("include")),
+                    format!("<external>/{}/{}/{}", hash("opt"), hash("sourcemod"), hash("include")),
+                ],
+                "implicitIncludes": [format!("<root>/{}/{}", hash("secret"), hash("lib"))],
+                "mainPaths": {
+                    format!("<root>/{}/{}.sp", hash("modules"), hash("secret")):
+                        format!("<root>/{}.sp", hash("secret")),
+                },
+                "compiler_arguments": [
+                    "-O2",
+                    format!("-i<external>/{}/{}/{}", hash("home"), hash("secret"), hash("include")),
+                    format!("-o=<root>/{}/{}.smx", hash("build"), hash("secret")),
+                ],
+                "compiler_defines": [
+                    "DEBUG=1",
+                    format!("CONFIG=<root>/{}/{}.cfg", hash("configs"), hash("secret")),
+                ],
+                "linter_disable": false,
+            })
+        );
+    }
+}
@@ -0,0 +1,263 @@
+//! Projects kept warm by a long-lived daemon.
+//!
+//! The daemon loads and analyzes the projects it is configured with before any client connects,
+//! and keeps them up to date with the disk. The sessions opening one of these projects start from
+//! a copy of its files, and the commands of the CLI sent to the daemon, e.g `check --daemon`, run
+//! on snapshots of its state.
+
+use std::{path::PathBuf, sync::Arc, thread};
+
+use fxhash::FxHashMap;
+use ide::AnalysisHost;
+use lsp_server::{Connection, ErrorCode, Request, Response};
+use lsp_types::request::Request as _;
+use nohash_hasher::IntMap;
+use parking_lot::{Mutex, RwLock};
+use paths::AbsPathBuf;
+use serde::de::DeserializeOwned;
+use vfs::FileId;
+
+use crate::{
+    cli,
+    client::LspClient,
+    config::Config,
+    diagnostics::baseline::DiagnosticsBaseline,
+    global_state::GlobalStateSnapshot,
+    line_index::LineEndings,
+    lsp::ext::{self, CommandResult},
+    mem_docs::MemDocs,
+    GlobalState,
+};
+
+/// State of a project, loaded and analyzed ahead of the sessions.
+///
+/// The state is only read outside of the daemon: the sessions copy its files into their own VFS
+/// and analysis host, so that their settings and unsaved files are not seen by the other
+/// sessions of the project, nor by the commands of the CLI.
+#[derive(Clone)]
+pub(crate) struct WarmProject {
+    vfs: Arc<RwLock<(vfs::Vfs, IntMap<FileId, LineEndings>)>>,
+    analysis_host: Arc<Mutex<AnalysisHost>>,
+    config: Arc<Config>,
+    diagnostics_baseline: Arc<RwLock<DiagnosticsBaseline>>,
+    client: LspClient,
+}
+
+impl WarmProject {
+    /// Snapshot of the project, to run the commands of the CLI on.
+    fn snapshot(&self) -> GlobalStateSnapshot {
+        GlobalStateSnapshot {
+            config: Arc::clone(&self.config),
+            analysis: self.analysis_host.lock().analysis(),
+            mem_docs: MemDocs::default(),
+            semantic_tokens_cache: Default::default(),
+            flycheck: Default::default(),
+            diagnostics_baseline: Arc::clone(&self.diagnostics_baseline),
+            client: self.client.clone(),
+            vfs: Arc::clone(&self.vfs),
+        }
+    }
+
+    /// Copy the files of the project into the VFS of a session.
+    fn copy_files(&self, vfs: &mut vfs::Vfs) {
+        let warm = self.vfs.read();
+        for (file_id, path) in warm.0.iter() {
+            vfs.set_file_contents(path.clone(), Some(warm.0.file_contents(file_id).to_vec()));
+        }
+    }
+}
+
+/// Projects kept warm by the daemon, indexed by their root.
+#[derive(Clone, Default)]
+pub struct WarmProjects {
+    projects: Arc<Mutex<FxHashMap<AbsPathBuf, Option<WarmProject>>>>,
+    amxxpawn_mode: bool,
+}
+
+impl WarmProjects {
+    pub fn new(amxxpawn_mode: bool) -> Self {
+        Self {
+            projects: Default::default(),
+            amxxpawn_mode,
+        }
+    }
+
+    /// Start warming up the project at `root` in the background, and keep it warm.
+    pub fn warm(&self, root: AbsPathBuf) {
+        self.projects.lock().insert(root.clone(), None);
+        let this = self.clone();
+        let res = thread::Builder::new()
+            .name("WarmProject".to_owned())
+            .spawn(move || {
+                log::info!("Warming up {}", root.display());
+                let (state, client) = GlobalState::load_project(root.clone(), this.amxxpawn_mode);
+                log::info!("Warmed up {}", root.display());
+                this.projects
+                    .lock()
+                    .insert(root, Some(state.warm_project()));
+                state.keep_warm(client);
+            });
+        if let Err(err) = res {
+            log::error!("Failed to warm up a project: {}", err);
+        }
+    }
+
+    /// The warm state of the project at `root`, if it is ready.
+    pub(crate) fn get(&self, root: &AbsPathBuf) -> Option<WarmProject> {
+        self.projects.lock().get(root)?.clone()
+    }
+
+    /// The warm state of the innermost project containing `path`, if it is ready.
+    fn find(&self, path: &AbsPathBuf) -> Option<WarmProject> {
+        self.projects
+            .lock()
+            .iter()
+            .filter(|(root, _)| path.starts_with(root))
+            .max_by_key(|(root, _)| root.as_os_str().len())
+            .and_then(|(_, project)| project.clone())
+    }
+
+    /// Run a command of the CLI sent to the daemon, on the warm state of its project if there
+    /// is one.
+    ///
+    /// Return `None` if the request is not a command.
+    pub(crate) fn handle_command(&self, request: Request) -> Option<Response> {
+        let id = request.id.clone();
+        let res = match request.method.as_str() {
+            ext::Check::METHOD => params(request).and_then(|params: ext::CheckParams| {
+                let path = abs_path(params.path)?;
+                self.with_snapshot(&path, |snap| {
+                    cli::check_snapshot(snap, &path, params.format, params.deny_warnings)
+                })
+            }),
+            ext::Format::METHOD => params(request).and_then(|params: ext::FormatParams| {
+                let paths = params
+                    .paths
+                    .into_iter()
+                    .map(abs_path)
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+                cli::format_files(abs_path(params.root)?, &paths, params.check)
+            }),
+            ext::Symbols::METHOD => params(request).and_then(|params: ext::SymbolsParams| {
+                let path = abs_path(params.path)?;
+                self.with_snapshot(&path, |snap| {
+                    cli::symbols_snapshot(snap, &path, &params.query)
+                })
+            }),
+            _ => return None,
+        };
+
+        Some(match res {
+            Ok(res) => Response::new_ok(id, res),
+            Err(err) => Response::new_err(id, ErrorCode::RequestFailed as i32, err.to_string()),
+        })
+    }
+
+    /// Run `f` on a snapshot of the warm project containing `path`, or of the project loaded for
+    /// the command if there is none.
+    fn with_snapshot(
+        &self,
+        path: &AbsPathBuf,
+        f: impl FnOnce(GlobalStateSnapshot) -> anyhow::Result<CommandResult>,
+    ) -> anyhow::Result<CommandResult> {
+        if let Some(project) = self.find(path) {
+            return f(project.snapshot());
+        }
+        let (state, _client) =
+            GlobalState::load_headless(cli::project_root(path)?, self.amxxpawn_mode);
+
+        f(state.snapshot())
+    }
+}
+
+fn params<P: DeserializeOwned>(request: Request) -> anyhow::Result<P> {
+    Ok(serde_json::from_value(request.params)?)
+}
+
+fn abs_path(path: PathBuf) -> anyhow::Result<AbsPathBuf> {
+    AbsPathBuf::try_from(path)
+        .map_err(|path| anyhow::anyhow!("not an absolute path: {}", path.display()))
+}
+
+impl GlobalState {
+    /// Load the project at `root` without a client.
+    ///
+    /// The project is configured with its project file only, as there is no editor to pull
+    /// settings from. The other end of the connection is returned, and must be kept alive so
+    /// that the notifications sent by the state do not fail.
+    pub(crate) fn load_headless(
+        root: AbsPathBuf,
+        amxxpawn_mode: bool,
+    ) -> (GlobalState, Connection) {
+        let (connection, client) = Connection::memory();
+        let mut state = GlobalState::new(connection, amxxpawn_mode);
+        let mut config = Config::new(root.clone(), Default::default(), vec![root.into()], false);
+        if let Err(err) = config.load_project_file() {
+            log::warn!("Invalid project file: {}", err);
+        }
+        state.update_configuration(config, true);
+        // The includes of the `sourcemod.version` setting are downloaded in the background.
+        while state.sourcemod_download.is_some() {
+            let Ok(task) = state.task_pool.receiver.recv() else {
+                break;
+            };
+            state.handle_task(&mut Vec::new(), task);
+        }
+        while state.vfs_progress_config_version < state.vfs_config_version
+            || state.vfs_progress_n_done < state.vfs_progress_n_total
+        {
+            let Ok(message) = state.loader.receiver.recv() else {
+                break;
+            };
+            state.handle_vfs_msg(message);
+        }
+        state.process_changes();
+
+        (state, client)
+    }
+
+    /// Load and analyze the project at `root` without a client.
+    fn load_project(root: AbsPathBuf, amxxpawn_mode: bool) -> (GlobalState, Connection) {
+        let (state, client) = GlobalState::load_headless(root, amxxpawn_mode);
+        let analysis = state.analysis_host.lock().analysis();
+        let num_worker_threads = state.config.prime_caches_num_threads();
+        if analysis
+            .parallel_prime_caches(num_worker_threads, None, Vec::new(), |_| (), |_| None)
+            .is_err()
+        {
+            log::warn!("Warming up was cancelled");
+        }
+
+        (state, client)
+    }
+
+    fn warm_project(&self) -> WarmProject {
+        WarmProject {
+            vfs: Arc::clone(&self.vfs),
+            analysis_host: Arc::clone(&self.analysis_host),
+            config: Arc::clone(&self.config),
+            diagnostics_baseline: Arc::clone(&self.diagnostics_baseline),
+            client: self.client.clone(),
+        }
+    }
+
+    /// Apply the changes of the files of the project on the disk, until the daemon stops.
+    fn keep_warm(mut self, client: Connection) {
+        // Nobody reads the notifications of the state.
+        thread::spawn(move || client.receiver.iter().for_each(drop));
+        while let Ok(message) = self.loader.receiver.recv() {
+            self.handle_vfs_msg(message);
+            self.process_changes();
+        }
+    }
+
+    /// Seed the VFS of the session with the files of the warm project at its root, if there is
+    /// one. They are applied to the analysis host of the session with its other changes.
+    pub(crate) fn adopt_warm_project(&mut self, root: &AbsPathBuf) {
+        let Some(project) = self.warm_projects.get(root) else {
+            return;
+        };
+        log::info!("Copying the files of the warm project {}", root.display());
+        project.copy_files(&mut self.vfs.write().0);
+    }
+}
@@ -7,6 +7,7 @@ use vfs::FileId;
 
 use crate::{global_state::GlobalStateSnapshot, lsp};
 
+pub(crate) mod baseline;
 pub mod to_proto;
 
 // pub(crate) type CheckFixes = Arc<IntMap<usize, IntMap<FileId, Vec<Fix>>>>;
@@ -114,17 +115,28 @@ impl DiagnosticCollection {
         self.changes.insert(file_id);
     }
 
+    /// Diagnostics of the file, without the spcomp diagnostics overlapping a diagnostic of the
+    /// server with the same severity, which report the same error twice.
     pub(crate) fn diagnostics_for(
         &self,
         file_id: FileId,
     ) -> impl Iterator<Item = &lsp_types::Diagnostic> {
-        let native = self.native.get(&file_id).into_iter().flatten();
+        let native = self
+            .native
+            .get(&file_id)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
         let check = self
             .check
             .values()
             .filter_map(move |it| it.get(&file_id))
-            .flatten();
-        native.chain(check)
+            .flatten()
+            .filter(move |check| {
+                !native
+                    .iter()
+                    .any(|it| are_diagnostics_overlapping(it, check))
+            });
+        native.iter().chain(check)
     }
 
     pub(crate) fn take_changes(&mut self) -> Option<IntSet<FileId>> {
@@ -142,43 +154,220 @@ fn are_diagnostics_equal(left: &lsp_types::Diagnostic, right: &lsp_types::Diagno
         && left.message == right.message
 }
 
+fn are_diagnostics_overlapping(
+    left: &lsp_types::Diagnostic,
+    right: &lsp_types::Diagnostic,
+) -> bool {
+    left.severity == right.severity
+        && left.range.start <= right.range.end
+        && right.range.start <= left.range.end
+}
+
 pub(crate) fn fetch_native_diagnostics(
     snapshot: GlobalStateSnapshot,
     subscriptions: Vec<FileId>,
 ) -> Vec<(FileId, Vec<lsp_types::Diagnostic>)> {
+    let baseline = snapshot.diagnostics_baseline.read();
+    subscriptions
+        .into_iter()
+        .filter_map(|file_id| {
+            let diagnostics = native_diagnostics(&snapshot, file_id)?;
+            let diagnostics = match snapshot.baseline_key(file_id) {
+                Some(key) => baseline.filter(&key, diagnostics),
+                None => diagnostics,
+            };
+            Some((file_id, diagnostics))
+        })
+        .collect()
+}
+
+/// Diagnostics of the file computed by the server, including the ones of the baseline.
+pub(crate) fn native_diagnostics(
+    snapshot: &GlobalStateSnapshot,
+    file_id: FileId,
+) -> Option<Vec<lsp_types::Diagnostic>> {
+    let line_index = snapshot.file_line_index(file_id).ok()?;
+    let diagnostics = snapshot
+        .analysis
+        .diagnostics(&snapshot.config.diagnostics(), file_id)
+        .ok()?
+        .into_iter()
+        .filter_map(move |d| {
+            let range = line_index.try_range(d.u_range)?;
+            lsp_types::Diagnostic {
+                range,
+                severity: Some(lsp::to_proto::diagnostic_severity(d.severity)),
+                code: Some(lsp_types::NumberOrString::String(
+                    d.code.as_str().to_string(),
+                )),
+                code_description: lsp_types::Url::parse(&d.code.url())
+                    .ok()
+                    .map(|href| lsp_types::CodeDescription { href }),
+                source: Some("sourcepawn-studio".to_string()),
+                message: d.message,
+                related_information: related_information(snapshot, &d),
+                tags: diagnostic_tags(&d),
+                data: None,
+            }
+            .into()
+        })
+        .collect::<Vec<_>>();
+
+    Some(diagnostics)
+}
+
+fn related_information(
+    snapshot: &GlobalStateSnapshot,
+    d: &ide::Diagnostic,
+) -> Option<Vec<lsp_types::DiagnosticRelatedInformation>> {
+    let res = d
+        .related
+        .iter()
+        .filter_map(|related| {
+            let range = snapshot
+                .file_line_index(related.file_id)
+                .ok()?
+                .try_range(related.u_range)?;
+            Some(lsp_types::DiagnosticRelatedInformation {
+                location: lsp_types::Location::new(snapshot.file_id_to_url(related.file_id), range),
+                message: related.message.clone(),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    (!res.is_empty()).then_some(res)
+}
+
+fn diagnostic_tags(d: &ide::Diagnostic) -> Option<Vec<lsp_types::DiagnosticTag>> {
+    let mut tags = Vec::new();
+    if d.unused {
+        tags.push(lsp_types::DiagnosticTag::UNNECESSARY);
+    }
+    if d.deprecated {
+        tags.push(lsp_types::DiagnosticTag::DEPRECATED);
+    }
+
+    (!tags.is_empty()).then_some(tags)
+}
+
+pub(crate) fn fetch_inactive_regions(
+    snapshot: GlobalStateSnapshot,
+    subscriptions: Vec<FileId>,
+) -> Vec<(FileId, Vec<lsp_types::Range>)> {
     subscriptions
         .into_iter()
         .filter_map(|file_id| {
             let line_index = snapshot.file_line_index(file_id).ok()?;
-            let diagnostics = snapshot
+            let regions = snapshot
                 .analysis
-                .diagnostics(&snapshot.config.diagnostics(), file_id)
+                .inactive_ranges(file_id)
                 .ok()?
                 .into_iter()
-                .filter_map(move |d| {
-                    let range = line_index.try_range(d.u_range)?;
-                    lsp_types::Diagnostic {
-                        range,
-                        severity: Some(lsp::to_proto::diagnostic_severity(d.severity)),
-                        code: Some(lsp_types::NumberOrString::String(
-                            d.code.as_str().to_string(),
-                        )),
-                        // code_description: Some(lsp_types::CodeDescription {
-                        //     href: lsp_types::Url::parse(&d.code.url()).unwrap(),
-                        // }),
-                        code_description: None,
-                        source: Some("sourcepawn-studio".to_string()),
-                        message: d.message,
-                        related_information: None,
-                        tags: d
-                            .unused
-                            .then(|| vec![lsp_types::DiagnosticTag::UNNECESSARY]),
-                        data: None,
-                    }
-                    .into()
-                })
+                .filter_map(|range| line_index.try_range(range))
                 .collect::<Vec<_>>();
-            Some((file_id, diagnostics))
+            Some((file_id, regions))
         })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::{DiagnosticSeverity, Position, Range};
+
+    use super::*;
+
+    fn diagnostic(
+        line: u32,
+        start: u32,
+        end: u32,
+        severity: DiagnosticSeverity,
+        source: &str,
+    ) -> lsp_types::Diagnostic {
+        lsp_types::Diagnostic {
+            range: Range::new(Position::new(line, start), Position::new(line, end)),
+            severity: Some(severity),
+            source: Some(source.to_string()),
+            message: format!("{source} diagnostic"),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn drop_spcomp_diagnostics_duplicating_native_ones() {
+        let file_id = FileId::from(0);
+        let mut diagnostics = DiagnosticCollection::default();
+        diagnostics.set_native_diagnostics(
+            file_id,
+            vec![diagnostic(
+                2,
+                12,
+                15,
+                DiagnosticSeverity::ERROR,
+                "sourcepawn-studio",
+            )],
+        );
+        // The same error, narrowed to the same symbol.
+        diagnostics.add_check_diagnostic(
+            0,
+            file_id,
+            diagnostic(2, 12, 15, DiagnosticSeverity::ERROR, "spcomp"),
+        );
+        // The same error, on the whole line.
+        diagnostics.add_check_diagnostic(
+            0,
+            file_id,
+            diagnostic(2, 0, 1000, DiagnosticSeverity::ERROR, "spcomp"),
+        );
+        // A warning on the same symbol.
+        diagnostics.add_check_diagnostic(
+            0,
+            file_id,
+            diagnostic(2, 12, 15, DiagnosticSeverity::WARNING, "spcomp"),
+        );
+        // An error of another symbol of the line.
+        diagnostics.add_check_diagnostic(
+            0,
+            file_id,
+            diagnostic(2, 4, 7, DiagnosticSeverity::ERROR, "spcomp"),
+        );
+        // An error on another line.
+        diagnostics.add_check_diagnostic(
+            0,
+            file_id,
+            diagnostic(5, 0, 4, DiagnosticSeverity::ERROR, "spcomp"),
+        );
+
+        assert_eq!(
+            diagnostics
+                .diagnostics_for(file_id)
+                .cloned()
+                .collect::<Vec<_>>(),
+            vec![
+                diagnostic(2, 12, 15, DiagnosticSeverity::ERROR, "sourcepawn-studio"),
+                diagnostic(2, 12, 15, DiagnosticSeverity::WARNING, "spcomp"),
+                diagnostic(2, 4, 7, DiagnosticSeverity::ERROR, "spcomp"),
+                diagnostic(5, 0, 4, DiagnosticSeverity::ERROR, "spcomp"),
+            ]
+        );
+    }
+
+    #[test]
+    fn keep_spcomp_diagnostics_without_native_ones() {
+        let file_id = FileId::from(0);
+        let mut diagnostics = DiagnosticCollection::default();
+        diagnostics.add_check_diagnostic(
+            0,
+            file_id,
+            diagnostic(2, 0, 1000, DiagnosticSeverity::ERROR, "spcomp"),
+        );
+
+        assert_eq!(
+            diagnostics
+                .diagnostics_for(file_id)
+                .cloned()
+                .collect::<Vec<_>>(),
+            vec![diagnostic(2, 0, 1000, DiagnosticSeverity::ERROR, "spcomp")]
+        );
+        assert_eq!(diagnostics.diagnostics_for(FileId::from(1)).count(), 0);
+    }
+}
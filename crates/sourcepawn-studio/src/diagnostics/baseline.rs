@@ -0,0 +1,163 @@
+//! Baseline of the diagnostics of a project.
+//!
+//! The baseline records the diagnostics of a legacy project at one point in time, so that only
+//! the diagnostics introduced afterwards are reported. Diagnostics are matched by file, code and
+//! message, and not by range, so that they stay suppressed when the lines around them are edited.
+use std::{collections::BTreeMap, fs};
+
+use anyhow::Context;
+use itertools::Itertools;
+use paths::AbsPath;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct DiagnosticsBaseline {
+    /// Diagnostics of each file, by path relative to the root of the project.
+    files: BTreeMap<String, Vec<BaselineEntry>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct BaselineEntry {
+    code: String,
+    message: String,
+    /// Number of diagnostics of the file with this code and message.
+    count: usize,
+}
+
+impl DiagnosticsBaseline {
+    /// Read the baseline from `path`, or return an empty baseline if it does not exist yet.
+    pub(crate) fn load(path: &AbsPath) -> anyhow::Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("failed to read the baseline {}", path.display()))?;
+        serde_json::from_str(&text)
+            .with_context(|| format!("failed to parse the baseline {}", path.display()))
+    }
+
+    pub(crate) fn write(&self, path: &AbsPath) -> anyhow::Result<()> {
+        let text = serde_json::to_string_pretty(self)?;
+        fs::write(path, text + "\n")
+            .with_context(|| format!("failed to write the baseline {}", path.display()))
+    }
+
+    /// Add the diagnostics of `file` to the baseline.
+    pub(crate) fn add(&mut self, file: String, diagnostics: &[lsp_types::Diagnostic]) {
+        if diagnostics.is_empty() {
+            return;
+        }
+        let entries = diagnostics
+            .iter()
+            .counts_by(|d| (diagnostic_code(d), d.message.clone()))
+            .into_iter()
+            .map(|((code, message), count)| BaselineEntry {
+                code,
+                message,
+                count,
+            })
+            .sorted_by(|a, b| (&a.code, &a.message).cmp(&(&b.code, &b.message)))
+            .collect_vec();
+        self.files.insert(file, entries);
+    }
+
+    /// Number of diagnostics in the baseline.
+    pub(crate) fn diagnostics_count(&self) -> usize {
+        self.files.values().flatten().map(|it| it.count).sum()
+    }
+
+    /// Remove the diagnostics of `file` which are in the baseline.
+    ///
+    /// When a file has more diagnostics with the same code and message than the baseline, the
+    /// extra ones are kept, as they can't be told apart from the baselined ones.
+    pub(crate) fn filter(
+        &self,
+        file: &str,
+        mut diagnostics: Vec<lsp_types::Diagnostic>,
+    ) -> Vec<lsp_types::Diagnostic> {
+        let Some(entries) = self.files.get(file) else {
+            return diagnostics;
+        };
+        let mut remaining = entries
+            .iter()
+            .map(|it| ((it.code.as_str(), it.message.as_str()), it.count))
+            .collect::<BTreeMap<_, _>>();
+        diagnostics.retain(|d| {
+            let code = diagnostic_code(d);
+            match remaining.get_mut(&(code.as_str(), d.message.as_str())) {
+                Some(count) if *count > 0 => {
+                    *count -= 1;
+                    false
+                }
+                _ => true,
+            }
+        });
+
+        diagnostics
+    }
+}
+
+/// Key of a file in the baseline, i.e its path relative to `root` with `/` separators.
+pub(crate) fn baseline_key(root: &AbsPath, path: &AbsPath) -> Option<String> {
+    let path = path.strip_prefix(root)?;
+    Some(
+        path.components()
+            .map(|it| it.as_os_str().to_string_lossy())
+            .join("/"),
+    )
+}
+
+fn diagnostic_code(d: &lsp_types::Diagnostic) -> String {
+    match &d.code {
+        Some(lsp_types::NumberOrString::String(code)) => code.clone(),
+        Some(lsp_types::NumberOrString::Number(code)) => code.to_string(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diagnostic(code: &str, message: &str, line: u32) -> lsp_types::Diagnostic {
+        lsp_types::Diagnostic {
+            range: lsp_types::Range::new(
+                lsp_types::Position::new(line, 0),
+                lsp_types::Position::new(line, 1),
+            ),
+            code: Some(lsp_types::NumberOrString::String(code.to_string())),
+            message: message.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn filter_baselined_diagnostics() {
+        let mut baseline = DiagnosticsBaseline::default();
+        baseline.add(
+            "scripting/plugin.sp".to_string(),
+            &[
+                diagnostic("SP0001", "unresolved reference `foo`", 1),
+                diagnostic("SP0001", "unresolved reference `foo`", 4),
+                diagnostic("SP0002", "unresolved field `bar`", 2),
+            ],
+        );
+        assert_eq!(baseline.diagnostics_count(), 3);
+
+        // The lines moved, and a new occurrence of `foo` was introduced.
+        let diagnostics = vec![
+            diagnostic("SP0001", "unresolved reference `foo`", 10),
+            diagnostic("SP0001", "unresolved reference `foo`", 11),
+            diagnostic("SP0001", "unresolved reference `foo`", 12),
+            diagnostic("SP0002", "unresolved field `bar`", 13),
+            diagnostic("SP0002", "unresolved field `baz`", 14),
+        ];
+        let filtered = baseline.filter("scripting/plugin.sp", diagnostics.clone());
+        assert_eq!(filtered, [diagnostics[2].clone(), diagnostics[4].clone()]);
+
+        assert_eq!(
+            baseline.filter("scripting/other.sp", diagnostics.clone()),
+            diagnostics
+        );
+    }
+}
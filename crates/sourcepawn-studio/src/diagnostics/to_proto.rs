@@ -1,25 +1,153 @@
 use flycheck::SpCompDiagnostic;
 use lsp_types::{Position, Range};
+use rowan::{TextRange, TextSize};
 
-pub fn map_spcomp_diagnostic_to_lsp(diagnostic: &SpCompDiagnostic) -> lsp_types::Diagnostic {
-    let range = Range {
-        start: Position {
-            line: diagnostic.line_index(),
-            character: 0,
-        },
-        end: Position {
-            line: diagnostic.line_index(),
-            character: 1000,
-        },
-    };
+use crate::line_index::LineIndex;
+
+/// Convert an spcomp diagnostic to an LSP diagnostic.
+///
+/// spcomp only reports the line of a diagnostic, so the range is narrowed to the symbol quoted in
+/// its message when it can be found on the line, and to the content of the line otherwise.
+pub(crate) fn map_spcomp_diagnostic_to_lsp(
+    diagnostic: &SpCompDiagnostic,
+    text: Option<&str>,
+    line_index: Option<&LineIndex>,
+) -> lsp_types::Diagnostic {
+    let range = text
+        .zip(line_index)
+        .and_then(|(text, line_index)| line_index.try_range(spcomp_range(diagnostic, text)?))
+        .unwrap_or(Range {
+            start: Position {
+                line: diagnostic.line_index(),
+                character: 0,
+            },
+            end: Position {
+                line: diagnostic.line_index(),
+                character: 1000,
+            },
+        });
     lsp_types::Diagnostic {
         range,
         severity: diagnostic.severity().to_lsp_severity().into(),
         code: Some(lsp_types::NumberOrString::String(
             diagnostic.code().to_string(),
         )),
+        code_description: lsp_types::Url::parse(&diagnostic.url())
+            .ok()
+            .map(|href| lsp_types::CodeDescription { href }),
         source: Some("spcomp".to_string()),
         message: diagnostic.message().to_string(),
         ..Default::default()
     }
 }
+
+fn spcomp_range(diagnostic: &SpCompDiagnostic, text: &str) -> Option<TextRange> {
+    let line_start = text
+        .split_inclusive('\n')
+        .take(diagnostic.line_index() as usize)
+        .map(str::len)
+        .sum::<usize>();
+    let line = text.get(line_start..)?.split('\n').next()?;
+    let line = line.trim_end_matches('\r');
+    let (offset, len) = match quoted_symbol(diagnostic.message())
+        .and_then(|symbol| Some((line.find(symbol)?, symbol.len())))
+    {
+        Some(it) => it,
+        None => {
+            let content = line.trim();
+            if content.is_empty() {
+                return None;
+            }
+            (line.len() - line.trim_start().len(), content.len())
+        }
+    };
+
+    Some(TextRange::at(
+        TextSize::try_from(line_start + offset).ok()?,
+        TextSize::try_from(len).ok()?,
+    ))
+}
+
+/// Symbol quoted in the message of an spcomp diagnostic, e.g `foo` for `undefined symbol "foo"`.
+fn quoted_symbol(message: &str) -> Option<&str> {
+    let (_, rest) = message.split_once('"')?;
+    let (symbol, _) = rest.split_once('"')?;
+
+    (!symbol.is_empty()).then_some(symbol)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::line_index::{LineEndings, PositionEncoding};
+
+    use super::*;
+
+    const TEXT: &str = "void Foo()\n{\n    int x = foo;\n\n}\n";
+
+    fn range(text: &str, line: u32, message: &str) -> Range {
+        let diagnostic = SpCompDiagnostic::try_from_line(&format!(
+            "/scripting/plugin.sp({}) : error 017: {}",
+            line + 1,
+            message
+        ))
+        .unwrap();
+        let line_index = LineIndex {
+            index: Arc::new(ide::LineIndex::new(text)),
+            endings: LineEndings::Unix,
+            encoding: PositionEncoding::Utf8,
+        };
+
+        map_spcomp_diagnostic_to_lsp(&diagnostic, Some(text), Some(&line_index)).range
+    }
+
+    fn lsp_range(line: u32, start: u32, end: u32) -> Range {
+        Range::new(Position::new(line, start), Position::new(line, end))
+    }
+
+    #[test]
+    fn narrow_to_the_quoted_symbol() {
+        assert_eq!(
+            range(TEXT, 2, "undefined symbol \"foo\""),
+            lsp_range(2, 12, 15)
+        );
+    }
+
+    #[test]
+    fn narrow_to_the_content_of_the_line() {
+        assert_eq!(
+            range(TEXT, 2, "function should return a value"),
+            lsp_range(2, 4, 16)
+        );
+        assert_eq!(
+            range(TEXT, 2, "undefined symbol \"bar\""),
+            lsp_range(2, 4, 16)
+        );
+    }
+
+    #[test]
+    fn whole_line_without_content() {
+        assert_eq!(range(TEXT, 3, "invalid expression"), lsp_range(3, 0, 1000));
+        assert_eq!(
+            range(TEXT, 10, "invalid expression"),
+            lsp_range(10, 0, 1000)
+        );
+    }
+
+    #[test]
+    fn whole_line_without_text() {
+        let diagnostic = SpCompDiagnostic::try_from_line(
+            "/scripting/plugin.sp(3) : warning 204: symbol is assigned a value that is never used: \"x\"",
+        )
+        .unwrap();
+
+        let diagnostic = map_spcomp_diagnostic_to_lsp(&diagnostic, None, None);
+        assert_eq!(diagnostic.range, lsp_range(2, 0, 1000));
+        assert_eq!(
+            diagnostic.severity,
+            Some(lsp_types::DiagnosticSeverity::WARNING)
+        );
+        assert_eq!(diagnostic.source.as_deref(), Some("spcomp"));
+    }
+}
@@ -0,0 +1,110 @@
+//! Discovery of the plugins of the workspace.
+//!
+//! A workspace can hold many plugins, e.g a SourceMod installation whose
+//! `addons/sourcemod/scripting` directory has dozens of `.sp` entry points sharing the `.inc`
+//! files of its `include` subdirectory. spcomp searches the `include` subdirectory of the compiled
+//! file for its includes, so the `include` subdirectory of each scripting root is an include
+//! directory of the plugins of the root.
+//!
+//! Which files are main files is inferred from the include graph: a `.sp` file which is not
+//! included by another file is the entry point of a plugin, and is preprocessed with its own
+//! macros, while the files it includes are analyzed with the macros defined by the entry point.
+
+use paths::AbsPathBuf;
+use walkdir::WalkDir;
+
+/// Maximum depth of the scripting roots, relative to the workspace roots. Deep enough for
+/// `addons/sourcemod/scripting` in a subdirectory of the workspace.
+const MAX_DEPTH: usize = 5;
+
+/// Directories which never hold the sources of a plugin.
+const IGNORED_DIRS: &[&str] = &["include", "node_modules", "target"];
+
+/// Find the scripting roots of the workspace, i.e the `scripting` directories which hold `.sp`
+/// files, and the workspace roots themselves when they hold `.sp` files.
+pub(crate) fn scripting_roots(workspace_roots: &[AbsPathBuf]) -> Vec<AbsPathBuf> {
+    let mut res = Vec::new();
+    for workspace_root in workspace_roots {
+        // The root can be the FS' root. Do not scrape the whole FS in that case.
+        if workspace_root.parent().is_none() {
+            continue;
+        }
+        let walker = WalkDir::new(workspace_root)
+            .max_depth(MAX_DEPTH)
+            .into_iter()
+            .filter_entry(|entry| {
+                entry.depth() == 0
+                    || (entry.file_type().is_dir()
+                        && entry.file_name().to_str().is_some_and(|name| {
+                            !name.starts_with('.') && !IGNORED_DIRS.contains(&name)
+                        }))
+            });
+        for entry in walker.filter_map(Result::ok) {
+            let is_candidate =
+                entry.depth() == 0 || entry.file_name().to_str() == Some("scripting");
+            if !is_candidate || !has_plugin_files(entry.path()) {
+                continue;
+            }
+            let Ok(path) = AbsPathBuf::try_from(entry.into_path()) else {
+                continue;
+            };
+            if !res.contains(&path) {
+                res.push(path);
+            }
+        }
+    }
+
+    res
+}
+
+/// Include directories of the scripting roots, i.e their existing `include` subdirectories.
+pub(crate) fn include_directories(scripting_roots: &[AbsPathBuf]) -> Vec<AbsPathBuf> {
+    scripting_roots
+        .iter()
+        .map(|root| root.join("include"))
+        .filter(|path| path.is_dir())
+        .collect()
+}
+
+fn has_plugin_files(dir: &std::path::Path) -> bool {
+    std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .any(|entry| entry.path().extension().is_some_and(|ext| ext == "sp"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn discover_scripting_roots() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = AbsPathBuf::try_from(dir.path().to_path_buf()).unwrap();
+        for path in [
+            "addons/sourcemod/scripting/foo.sp",
+            "addons/sourcemod/scripting/bar.sp",
+            "addons/sourcemod/scripting/include/shared.inc",
+            "addons/sourcemod/scripting/bar/module.sp",
+            "other/scripting/include/only_includes.inc",
+            ".sourceknight/package/addons/sourcemod/scripting/baz.sp",
+        ] {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, "").unwrap();
+        }
+
+        let scripting_roots = scripting_roots(&[root.clone()]);
+        assert_eq!(
+            scripting_roots,
+            vec![root.join("addons/sourcemod/scripting")]
+        );
+        assert_eq!(
+            include_directories(&scripting_roots),
+            vec![root.join("addons/sourcemod/scripting/include")]
+        );
+    }
+}
@@ -6,8 +6,8 @@ use std::{fmt, panic, thread};
 use stdx::thread::ThreadIntent;
 
 use crate::{
-    global_state::GlobalStateSnapshot, lsp::LspError, main_loop::Task, version::version,
-    GlobalState,
+    crash_report::CrashReporter, global_state::GlobalStateSnapshot, lsp::LspError, main_loop::Task,
+    version::version, GlobalState,
 };
 
 /// A visitor for routing a raw JSON request to an appropriate handler function.
@@ -96,11 +96,15 @@ impl RequestDispatcher<'_> {
         };
 
         let world = self.global_state.snapshot();
+        let crash_reporter = CrashReporter::new(self.global_state);
         self.global_state.task_pool.handle.spawn(intent, move || {
             let result = panic::catch_unwind(move || {
                 let _pctx = stdx::panic_context::enter(panic_context);
                 f(world, params)
             });
+            if result.is_err() {
+                crash_reporter.report(&req.method, &req.params);
+            }
             match thread_result_to_response::<R>(req.id.clone(), result) {
                 Ok(response) => Task::Response(response),
                 Err(_) => Task::Retry(req),
@@ -0,0 +1,279 @@
+//! Properties of the [EditorConfig](https://editorconfig.org) files of the files to format.
+//!
+//! The `.editorconfig` files are searched in the directory of the file and its ancestors, up to
+//! the one which sets `root = true`. The sections whose glob matches the file apply, the sections
+//! of the files closer to it, and the later sections of a file, taking precedence.
+
+use ide::EndOfLine;
+use paths::AbsPath;
+use regex::Regex;
+
+const EDITORCONFIG_FILE_NAME: &str = ".editorconfig";
+
+/// Properties of a file which are used by the formatter.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct EditorConfig {
+    pub(crate) use_tabs: Option<bool>,
+    pub(crate) indent_size: Option<u32>,
+    pub(crate) end_of_line: Option<EndOfLine>,
+    pub(crate) insert_final_newline: Option<bool>,
+    /// Value of `indent_size`, kept to resolve `indent_size = tab` with `tab_width`.
+    raw_indent_size: Option<String>,
+    tab_width: Option<u32>,
+}
+
+impl EditorConfig {
+    /// Load the properties of a file from the `.editorconfig` files of its ancestors.
+    pub(crate) fn load(path: &AbsPath) -> Self {
+        let mut files = Vec::new();
+        let mut next = path.parent();
+        while let Some(dir) = next {
+            next = dir.parent();
+            let Ok(text) = std::fs::read_to_string(dir.join(EDITORCONFIG_FILE_NAME)) else {
+                continue;
+            };
+            let file = EditorConfigFile::parse(&text);
+            let is_root = file.root;
+            files.push((dir, file));
+            if is_root {
+                break;
+            }
+        }
+
+        let mut res = Self::default();
+        for (dir, file) in files.iter().rev() {
+            let Some(relative) = path.strip_prefix(dir) else {
+                continue;
+            };
+            let relative = relative.as_ref().to_string_lossy().replace('\\', "/");
+            for (glob, properties) in &file.sections {
+                if glob.is_match(&relative) {
+                    for (key, value) in properties {
+                        res.set(key, value);
+                    }
+                }
+            }
+        }
+
+        res
+    }
+
+    fn set(&mut self, key: &str, value: &str) {
+        if value == "unset" {
+            match key {
+                "indent_style" => self.use_tabs = None,
+                "indent_size" => self.raw_indent_size = None,
+                "tab_width" => self.tab_width = None,
+                "end_of_line" => self.end_of_line = None,
+                "insert_final_newline" => self.insert_final_newline = None,
+                _ => (),
+            }
+        } else {
+            match key {
+                "indent_style" => match value {
+                    "tab" => self.use_tabs = Some(true),
+                    "space" => self.use_tabs = Some(false),
+                    _ => (),
+                },
+                "indent_size" => self.raw_indent_size = Some(value.to_string()),
+                "tab_width" => self.tab_width = value.parse().ok().or(self.tab_width),
+                "end_of_line" => match value {
+                    "lf" => self.end_of_line = Some(EndOfLine::Lf),
+                    "crlf" => self.end_of_line = Some(EndOfLine::Crlf),
+                    _ => (),
+                },
+                "insert_final_newline" => match value {
+                    "true" => self.insert_final_newline = Some(true),
+                    "false" => self.insert_final_newline = Some(false),
+                    _ => (),
+                },
+                _ => (),
+            }
+        }
+        self.indent_size = match self.raw_indent_size.as_deref() {
+            Some("tab") => self.tab_width,
+            Some(size) => size.parse().ok(),
+            None => None,
+        };
+    }
+}
+
+/// Parsed `.editorconfig` file.
+#[derive(Debug, Default)]
+struct EditorConfigFile {
+    root: bool,
+    /// Globs of the sections, and their properties with lowercase keys and values.
+    sections: Vec<(Regex, Vec<(String, String)>)>,
+}
+
+impl EditorConfigFile {
+    fn parse(text: &str) -> Self {
+        let mut res = Self::default();
+        let mut in_preamble = true;
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(['#', ';']) {
+                continue;
+            }
+            if let Some(glob) = line
+                .strip_prefix('[')
+                .and_then(|line| line.strip_suffix(']'))
+            {
+                in_preamble = false;
+                match glob_to_regex(glob) {
+                    Some(glob) => res.sections.push((glob, Vec::new())),
+                    // The properties of an invalid section are ignored.
+                    None => res.sections.push((Regex::new("$^").unwrap(), Vec::new())),
+                }
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let key = key.trim().to_lowercase();
+            let value = value.trim().to_lowercase();
+            if in_preamble {
+                res.root |= key == "root" && value == "true";
+            } else if let Some((_, properties)) = res.sections.last_mut() {
+                properties.push((key, value));
+            }
+        }
+
+        res
+    }
+}
+
+/// Regex matching the paths relative to the directory of the `.editorconfig` file which match a
+/// glob. The globs without a `/` match the files at any depth.
+fn glob_to_regex(glob: &str) -> Option<Regex> {
+    let (glob, prefix) = match glob.strip_prefix('/') {
+        Some(glob) => (glob, ""),
+        None if glob.contains('/') => (glob, ""),
+        None => (glob, "(?:.*/)?"),
+    };
+    let mut res = format!("^{prefix}");
+    let mut chars = glob.chars().peekable();
+    let mut braces = 0;
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                res.push_str(".*");
+            }
+            '*' => res.push_str("[^/]*"),
+            '?' => res.push_str("[^/]"),
+            '[' => {
+                let mut class = String::new();
+                for c in chars.by_ref() {
+                    if c == ']' {
+                        break;
+                    }
+                    class.push(c);
+                }
+                // The ranges of the classes are kept, e.g `[a-z]`.
+                let (negation, class) = match class.strip_prefix('!') {
+                    Some(class) => ("^", class),
+                    None => ("", class.as_str()),
+                };
+                res.push('[');
+                res.push_str(negation);
+                for c in class.chars() {
+                    if matches!(c, '\\' | '[' | '^' | '&' | '~') {
+                        res.push('\\');
+                    }
+                    res.push(c);
+                }
+                res.push(']');
+            }
+            '{' => {
+                braces += 1;
+                res.push_str("(?:");
+            }
+            ',' if braces > 0 => res.push('|'),
+            '}' if braces > 0 => {
+                braces -= 1;
+                res.push(')');
+            }
+            '\\' => {
+                if let Some(c) = chars.next() {
+                    res.push_str(&regex::escape(&c.to_string()));
+                }
+            }
+            _ => res.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    res.push('$');
+
+    Regex::new(&res).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use paths::AbsPathBuf;
+
+    use super::*;
+
+    #[test]
+    fn match_globs() {
+        let glob = glob_to_regex("*.{sp,inc}").unwrap();
+        assert!(glob.is_match("plugin.sp"));
+        assert!(glob.is_match("scripting/include/shared.inc"));
+        assert!(!glob.is_match("plugin.smx"));
+
+        let glob = glob_to_regex("/scripting/**.sp").unwrap();
+        assert!(glob.is_match("scripting/modules/foo.sp"));
+        assert!(!glob.is_match("other/scripting/foo.sp"));
+    }
+
+    #[test]
+    fn load_editorconfig_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = AbsPathBuf::try_from(dir.path().to_path_buf()).unwrap();
+        fs::create_dir_all(root.join("project/scripting")).unwrap();
+        fs::write(
+            root.join(".editorconfig"),
+            "[*]\nindent_style = space\nindent_size = 8\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("project/.editorconfig"),
+            r#"root = true
+
+[*]
+indent_style = space
+indent_size = 2
+end_of_line = crlf
+
+; The includes keep the indentation of SourceMod.
+[*.inc]
+indent_style = tab
+indent_size = tab
+tab_width = 4
+insert_final_newline = false
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            EditorConfig::load(&root.join("project/scripting/plugin.sp")),
+            EditorConfig {
+                use_tabs: Some(false),
+                indent_size: Some(2),
+                end_of_line: Some(EndOfLine::Crlf),
+                insert_final_newline: None,
+                raw_indent_size: Some("2".to_string()),
+                tab_width: None,
+            }
+        );
+        let config = EditorConfig::load(&root.join("project/scripting/plugin.inc"));
+        assert_eq!(config.use_tabs, Some(true));
+        assert_eq!(config.indent_size, Some(4));
+        assert_eq!(config.insert_final_newline, Some(false));
+        assert_eq!(
+            EditorConfig::load(&root.join("other.sp")).indent_size,
+            Some(8)
+        );
+    }
+}
@@ -1,24 +1,31 @@
 use anyhow::Result;
 use crossbeam::channel::Receiver;
+use fxhash::FxHashMap;
 use itertools::Itertools;
 use lsp_server::{Connection, Response};
 use lsp_types::{
-    notification::{DidOpenTextDocument, Exit, Initialized},
+    notification::{
+        DidChangeTextDocument, DidOpenTextDocument, Exit, Initialized, Notification,
+        PublishDiagnostics,
+    },
     request::{Completion, Initialize, ResolveCompletionItem, Shutdown},
     ClientCapabilities, CompletionContext, CompletionItem, CompletionItemKind, CompletionParams,
-    CompletionResponse, CompletionTriggerKind, DidOpenTextDocumentParams, InitializeParams,
-    InitializedParams, Location, LocationLink, Position, Range, SignatureHelp,
-    TextDocumentIdentifier, TextDocumentItem, TextDocumentPositionParams, Url, WorkspaceFolder,
+    CompletionResponse, CompletionTriggerKind, Diagnostic, DidChangeTextDocumentParams,
+    DidOpenTextDocumentParams, InitializeParams, InitializedParams, Location, LocationLink,
+    Position, PublishDiagnosticsParams, Range, SignatureHelp, TextDocumentContentChangeEvent,
+    TextDocumentIdentifier, TextDocumentItem, TextDocumentPositionParams, Url,
+    VersionedTextDocumentIdentifier, WorkspaceFolder,
 };
+use parking_lot::Mutex;
 use std::{
     env,
     fs::File,
     io,
     path::{Path, PathBuf},
     str::FromStr,
-    sync::Once,
+    sync::{Arc, Once},
     thread::JoinHandle,
-    time::Duration,
+    time::{Duration, Instant},
 };
 use tempfile::{tempdir, TempDir};
 use zip::ZipArchive;
@@ -66,8 +73,17 @@ impl Fixture {
         }
     }
 
+    /// Open the `.sp` and `.inc` documents of the fixture. The other documents, e.g the project
+    /// file, are only written.
     pub fn setup(&self, client: &LspClient, dir: &Path) {
         for document in &self.documents {
+            if !document
+                .path
+                .extension()
+                .is_some_and(|ext| ext == "sp" || ext == "inc")
+            {
+                continue;
+            }
             let text = String::from(&document.text);
             let path = dir.join(&document.path);
 
@@ -150,6 +166,10 @@ pub struct TestBed {
     #[allow(unused)]
     temp_sm_dir_path: PathBuf,
     pub internal_rx: Receiver<InternalMessage>,
+    /// Diagnostics published by the server, and when they were last published.
+    published_diagnostics: Arc<Mutex<(FxHashMap<Url, Vec<Diagnostic>>, Instant)>>,
+    /// Inactive regions last pushed by the server for each document.
+    inactive_regions: Arc<Mutex<FxHashMap<Url, Vec<Range>>>>,
     client: LspClient,
     client_thread: Option<JoinHandle<()>>,
     server_thread: Option<JoinHandle<()>>,
@@ -202,11 +222,15 @@ impl TestBed {
         let (internal_tx, internal_rx) = crossbeam::channel::unbounded();
 
         let client = LspClient::new(client_conn.sender);
+        let published_diagnostics = Arc::new(Mutex::new((FxHashMap::default(), Instant::now())));
+        let inactive_regions = Arc::new(Mutex::new(FxHashMap::default()));
 
         let server_thread =
             std::thread::spawn(move || GlobalState::new(server_conn, false).run().unwrap());
         let client_thread = {
             let client = client.clone();
+            let published_diagnostics = Arc::clone(&published_diagnostics);
+            let inactive_regions = Arc::clone(&inactive_regions);
             std::thread::spawn(move || {
                 let destination = temp_sm_dir_path_;
                 for message in &client_conn.receiver {
@@ -247,7 +271,23 @@ impl TestBed {
                         lsp_server::Message::Response(response) => {
                             client.recv_response(response).unwrap();
                         }
-                        lsp_server::Message::Notification(_) => {}
+                        lsp_server::Message::Notification(notification) => {
+                            if notification.method == PublishDiagnostics::METHOD {
+                                let params: PublishDiagnosticsParams =
+                                    serde_json::from_value(notification.params).unwrap();
+                                let mut published = published_diagnostics.lock();
+                                published.0.insert(params.uri, params.diagnostics);
+                                published.1 = Instant::now();
+                            } else if notification.method
+                                == lsp::ext::InactiveRegionsNotification::METHOD
+                            {
+                                let params: lsp::ext::InactiveRegionsParams =
+                                    serde_json::from_value(notification.params).unwrap();
+                                inactive_regions
+                                    .lock()
+                                    .insert(params.text_document.uri, params.regions);
+                            }
+                        }
                     }
                 }
             })
@@ -262,6 +302,8 @@ impl TestBed {
             temp_sm_dir_path,
             client,
             internal_rx,
+            published_diagnostics,
+            inactive_regions,
             client_thread: Some(client_thread),
             server_thread: Some(server_thread),
         })
@@ -299,7 +341,7 @@ impl TestBed {
             .iter()
             .find_map(|document| document.cursor.map(|cursor| (document, cursor)))?;
 
-        let uri = Url::from_file_path(self.temp_dir_path.join(&document.path)).unwrap();
+        let uri = self.uri(&document.path);
         let id = TextDocumentIdentifier::new(uri);
         Some(TextDocumentPositionParams::new(id, cursor))
     }
@@ -313,11 +355,84 @@ impl TestBed {
         &self.temp_dir_path
     }
 
+    /// Replace the text of the opened document at `path`, as the client does when it is edited.
+    /// The diagnostics published before are then waited for again.
+    pub fn change_document(&self, path: impl AsRef<Path>, text: &str) {
+        self.client
+            .send_notification::<DidChangeTextDocument>(DidChangeTextDocumentParams {
+                text_document: VersionedTextDocumentIdentifier::new(self.uri(path), 1),
+                content_changes: vec![TextDocumentContentChangeEvent {
+                    range: None,
+                    range_length: None,
+                    text: text.to_string(),
+                }],
+            })
+            .unwrap();
+        self.published_diagnostics.lock().1 = Instant::now();
+    }
+
+    /// URI of the file of the fixture at `path`.
+    pub fn uri(&self, path: impl AsRef<Path>) -> Url {
+        Url::from_file_path(self.temp_dir_path.join(path)).unwrap()
+    }
+
     #[allow(unused)]
     pub fn documents(&self) -> &[Document] {
         &self.fixture.documents
     }
 
+    /// Wait for the server to publish the diagnostics of the opened documents, and return them
+    /// with the path of their document, sorted.
+    pub fn diagnostics(&self) -> Vec<(String, Diagnostic)> {
+        let uris = self
+            .fixture
+            .documents
+            .iter()
+            .filter(|document| {
+                document
+                    .path
+                    .extension()
+                    .is_some_and(|ext| ext == "sp" || ext == "inc")
+            })
+            .map(|document| {
+                let uri = self.uri(&document.path);
+                (document.path.to_string_lossy().replace('\\', "/"), uri)
+            })
+            .collect_vec();
+        // The diagnostics are published again as the analysis progresses, so wait for them to
+        // settle.
+        let start = Instant::now();
+        while start.elapsed() < Duration::from_secs(30) {
+            std::thread::sleep(Duration::from_millis(100));
+            let published = self.published_diagnostics.lock();
+            if uris.iter().all(|(_, uri)| published.0.contains_key(uri))
+                && published.1.elapsed() > Duration::from_millis(500)
+            {
+                break;
+            }
+        }
+
+        let published = self.published_diagnostics.lock();
+        uris.into_iter()
+            .flat_map(|(path, uri)| {
+                published
+                    .0
+                    .get(&uri)
+                    .cloned()
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(move |diagnostic| (path.clone(), diagnostic))
+            })
+            .sorted_by_key(|(path, diagnostic)| {
+                (
+                    path.clone(),
+                    diagnostic.range.start.line,
+                    diagnostic.range.start.character,
+                )
+            })
+            .collect()
+    }
+
     /// Remove the tempdir path from the uri, so that the tests are not dependent
     /// on the tempdir.
     pub fn anonymize_uri(&self, uri: &mut Url) {
@@ -330,24 +445,43 @@ impl TestBed {
     }
 }
 
-pub fn goto_definition(fixture: &str) -> Vec<LocationLink> {
-    let test_bed = TestBed::new(fixture, true).unwrap();
+/// Start a server on the fixture, initialized with the client `capabilities` on top of the
+/// workspace ones every test needs.
+fn start(fixture: &str, add_sourcemod: bool, capabilities: serde_json::Value) -> TestBed {
+    let test_bed = TestBed::new(fixture, add_sourcemod).unwrap();
     test_bed
-        .initialize(
-            serde_json::from_value(serde_json::json!({
-                "textDocument": {
-                    "definition": {
-                        "linkSupport": true
-                    }
-                },
-                "workspace": {
-                    "configuration": true,
-                    "workspace_folders": true
-                }
-            }))
-            .unwrap(),
-        )
+        .initialize(client_capabilities(capabilities))
         .unwrap();
+
+    test_bed
+}
+
+/// Client capabilities made of the `capabilities`, e.g `{ "textDocument": { ... } }`, and of the
+/// workspace ones every test needs.
+fn client_capabilities(mut capabilities: serde_json::Value) -> ClientCapabilities {
+    capabilities.as_object_mut().unwrap().insert(
+        "workspace".to_string(),
+        serde_json::json!({
+            "configuration": true,
+            "workspace_folders": true
+        }),
+    );
+
+    serde_json::from_value(capabilities).unwrap()
+}
+
+pub fn goto_definition(fixture: &str) -> Vec<LocationLink> {
+    let test_bed = start(
+        fixture,
+        true,
+        serde_json::json!({
+            "textDocument": {
+                "definition": {
+                    "linkSupport": true
+                }
+            }
+        }),
+    );
     let text_document_position = test_bed.cursor().unwrap();
     let params = lsp_types::request::GotoTypeDefinitionParams {
         text_document_position_params: text_document_position,
@@ -371,25 +505,19 @@ pub fn goto_definition(fixture: &str) -> Vec<LocationLink> {
 }
 
 pub fn complete(fixture: &str, trigger_character: Option<String>) -> Vec<CompletionItem> {
-    let test_bed = TestBed::new(fixture, true).unwrap();
-    test_bed
-        .initialize(
-            serde_json::from_value(serde_json::json!({
-                "textDocument": {
-                    "completion": {
-                        "completionItem": {
-                            "documentationFormat": ["plaintext", "markdown"]
-                        }
+    let test_bed = start(
+        fixture,
+        true,
+        serde_json::json!({
+            "textDocument": {
+                "completion": {
+                    "completionItem": {
+                        "documentationFormat": ["plaintext", "markdown"]
                     }
-                },
-                "workspace": {
-                    "configuration": true,
-                    "workspace_folders": true
                 }
-            }))
-            .unwrap(),
-        )
-        .unwrap();
+            }
+        }),
+    );
     let text_document_position = test_bed.cursor().unwrap();
     let mut items = match test_bed
         .client()
@@ -443,21 +571,15 @@ pub fn complete(fixture: &str, trigger_character: Option<String>) -> Vec<Complet
 }
 
 pub fn signature_help(fixture: &str) -> SignatureHelp {
-    let test_bed = TestBed::new(fixture, true).unwrap();
-    test_bed
-        .initialize(
-            serde_json::from_value(serde_json::json!({
-                "textDocument": {
-                    "signatureHelpProvider": {}
-                },
-                "workspace": {
-                    "configuration": true,
-                    "workspace_folders": true
-                }
-            }))
-            .unwrap(),
-        )
-        .unwrap();
+    let test_bed = start(
+        fixture,
+        true,
+        serde_json::json!({
+            "textDocument": {
+                "signatureHelpProvider": {}
+            }
+        }),
+    );
     let text_document_position = test_bed.cursor().unwrap();
     let params = lsp_types::SignatureHelpParams {
         text_document_position_params: text_document_position,
@@ -495,32 +617,26 @@ pub fn unzip_file(zip_file_path: &Path, destination: &Path) -> Result<(), io::Er
 }
 
 pub fn hover(fixture: &str) -> lsp::ext::Hover {
-    let test_bed = TestBed::new(fixture, true).unwrap();
-    test_bed
-        .initialize(
-            serde_json::from_value(serde_json::json!({
-                "textDocument": {
-                    "hover": {
-                        "contentFormat": [
-                            "plaintext",
-                            "markdown"
-                        ]
-                    }
-                },
-                "workspace": {
-                    "configuration": true,
-                    "workspace_folders": true
-                },
-                "experimental": {
-                    "hoverActions": true,
-                    "commands": {
-                        "commands": ["sourcepawn-vscode.gotoLocation"],
-                    },
+    let test_bed = start(
+        fixture,
+        true,
+        serde_json::json!({
+            "textDocument": {
+                "hover": {
+                    "contentFormat": [
+                        "plaintext",
+                        "markdown"
+                    ]
                 }
-            }))
-            .unwrap(),
-        )
-        .unwrap();
+            },
+            "experimental": {
+                "hoverActions": true,
+                "commands": {
+                    "commands": ["sourcepawn-vscode.gotoLocation"],
+                },
+            }
+        }),
+    );
     let text_document_position = test_bed.cursor().unwrap();
     let params = lsp_types::HoverParams {
         text_document_position_params: text_document_position,
@@ -551,3 +667,791 @@ pub fn hover(fixture: &str) -> lsp::ext::Hover {
 
     res
 }
+
+/// Syntax tree of the first document, or of the smallest node containing its first range.
+pub fn syntax_tree(fixture: &str) -> String {
+    let test_bed = start(fixture, false, serde_json::json!({}));
+    let document = test_bed.documents().first().unwrap();
+    let uri = test_bed.uri(&document.path);
+    let params = lsp::ext::SyntaxTreeParams {
+        text_document: Some(TextDocumentIdentifier::new(uri)),
+        range: document.ranges.first().copied(),
+    };
+
+    test_bed
+        .client()
+        .send_request::<lsp::ext::SyntaxTree>(params)
+        .unwrap()
+}
+
+/// Semantic tokens of the first document of the fixture, as their line, start character,
+/// length, type and modifiers: the tokens of the whole document, and the tokens of the range from
+/// the start of its first range to the end of its last range.
+pub fn semantic_tokens(fixture: &str) -> (Vec<[u32; 5]>, Vec<[u32; 5]>) {
+    let test_bed = start(fixture, false, serde_json::json!({}));
+    let document = test_bed.documents().first().unwrap();
+    let text_document = TextDocumentIdentifier::new(test_bed.uri(&document.path));
+    let range = Range::new(
+        document.ranges.first().unwrap().start,
+        document.ranges.last().unwrap().end,
+    );
+
+    let full = match test_bed
+        .client()
+        .send_request::<lsp_types::request::SemanticTokensFullRequest>(
+            lsp_types::SemanticTokensParams {
+                text_document: text_document.clone(),
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            },
+        )
+        .unwrap()
+    {
+        Some(lsp_types::SemanticTokensResult::Tokens(tokens)) => tokens.data,
+        Some(lsp_types::SemanticTokensResult::Partial(tokens)) => tokens.data,
+        None => Vec::new(),
+    };
+    let range = match test_bed
+        .client()
+        .send_request::<lsp_types::request::SemanticTokensRangeRequest>(
+            lsp_types::SemanticTokensRangeParams {
+                text_document,
+                range,
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            },
+        )
+        .unwrap()
+    {
+        Some(lsp_types::SemanticTokensRangeResult::Tokens(tokens)) => tokens.data,
+        Some(lsp_types::SemanticTokensRangeResult::Partial(tokens)) => tokens.data,
+        None => Vec::new(),
+    };
+
+    (
+        decode_semantic_tokens(&full),
+        decode_semantic_tokens(&range),
+    )
+}
+
+/// Resolve the positions of semantic tokens, which are relative to the previous token.
+fn decode_semantic_tokens(tokens: &[lsp_types::SemanticToken]) -> Vec<[u32; 5]> {
+    let mut line = 0;
+    let mut start = 0;
+    tokens
+        .iter()
+        .map(|token| {
+            if token.delta_line > 0 {
+                start = 0;
+            }
+            line += token.delta_line;
+            start += token.delta_start;
+            [
+                line,
+                start,
+                token.length,
+                token.token_type,
+                token.token_modifiers_bitset,
+            ]
+        })
+        .collect()
+}
+
+/// Outline of the first document, as the depth, the name and the kind of each symbol in
+/// pre-order.
+pub fn document_symbols(fixture: &str) -> Vec<(usize, String, lsp_types::SymbolKind)> {
+    let test_bed = start(
+        fixture,
+        false,
+        serde_json::json!({
+            "textDocument": {
+                "documentSymbol": {
+                    "hierarchicalDocumentSymbolSupport": true
+                }
+            }
+        }),
+    );
+    let document = test_bed.documents().first().unwrap();
+    let uri = test_bed.uri(&document.path);
+    let params = lsp_types::DocumentSymbolParams {
+        text_document: TextDocumentIdentifier::new(uri),
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+    };
+
+    fn flatten(
+        symbols: Vec<lsp_types::DocumentSymbol>,
+        depth: usize,
+        res: &mut Vec<(usize, String, lsp_types::SymbolKind)>,
+    ) {
+        for symbol in symbols {
+            res.push((depth, symbol.name, symbol.kind));
+            flatten(symbol.children.unwrap_or_default(), depth + 1, res);
+        }
+    }
+
+    let mut res = Vec::new();
+    match test_bed
+        .client()
+        .send_request::<lsp_types::request::DocumentSymbolRequest>(params)
+        .unwrap()
+    {
+        Some(lsp_types::DocumentSymbolResponse::Nested(symbols)) => flatten(symbols, 0, &mut res),
+        Some(lsp_types::DocumentSymbolResponse::Flat(_)) => panic!("expected nested symbols"),
+        None => (),
+    }
+
+    res
+}
+
+/// Names and container names of the symbols of the workspace matching the query, in the order of
+/// the response.
+pub fn workspace_symbols(fixture: &str, query: &str) -> Vec<(String, Option<String>)> {
+    let test_bed = start(fixture, false, serde_json::json!({}));
+    let params = lsp_types::WorkspaceSymbolParams {
+        query: query.to_string(),
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+    };
+
+    match test_bed
+        .client()
+        .send_request::<lsp_types::request::WorkspaceSymbolRequest>(params)
+        .unwrap()
+    {
+        Some(lsp_types::WorkspaceSymbolResponse::Flat(symbols)) => symbols
+            .into_iter()
+            .map(|symbol| (symbol.name, symbol.container_name))
+            .collect(),
+        Some(lsp_types::WorkspaceSymbolResponse::Nested(symbols)) => symbols
+            .into_iter()
+            .map(|symbol| (symbol.name, symbol.container_name))
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+/// Diagnostics of the documents of the fixture, with the path of their document, as their code
+/// and message.
+pub fn diagnostics(fixture: &str, add_sourcemod: bool) -> Vec<(String, String, String)> {
+    let test_bed = start(fixture, add_sourcemod, serde_json::json!({}));
+
+    coded_diagnostics(&test_bed)
+}
+
+/// Diagnostics of the fixture, like [`diagnostics`], after the text of the document at `path` is
+/// replaced by `text`.
+pub fn diagnostics_after_change(
+    fixture: &str,
+    path: &str,
+    text: &str,
+) -> Vec<(String, String, String)> {
+    let test_bed = start(fixture, false, serde_json::json!({}));
+    test_bed.diagnostics();
+    test_bed.change_document(path, text);
+
+    coded_diagnostics(&test_bed)
+}
+
+/// Diagnostics published for the documents of a test bed, as their path, code and message.
+fn coded_diagnostics(test_bed: &TestBed) -> Vec<(String, String, String)> {
+    test_bed
+        .diagnostics()
+        .into_iter()
+        .map(|(path, diagnostic)| {
+            let code = match diagnostic.code {
+                Some(lsp_types::NumberOrString::String(code)) => code,
+                Some(lsp_types::NumberOrString::Number(code)) => code.to_string(),
+                None => String::new(),
+            };
+            (path, code, diagnostic.message)
+        })
+        .collect()
+}
+
+/// Inactive regions pushed for the first document of the fixture, before and after its text is
+/// replaced by `text`, if any were pushed.
+pub fn inactive_regions(fixture: &str, text: &str) -> (Option<Vec<Range>>, Option<Vec<Range>>) {
+    let test_bed = start(
+        fixture,
+        false,
+        serde_json::json!({
+            "experimental": {
+                "inactiveRegionsNotification": true
+            }
+        }),
+    );
+    let path = test_bed.documents().first().unwrap().path.clone();
+    let uri = test_bed.uri(&path);
+    // The regions are pushed along with the diagnostics.
+    test_bed.diagnostics();
+    let before = test_bed.inactive_regions.lock().remove(&uri);
+    test_bed.change_document(&path, text);
+    test_bed.diagnostics();
+    let after = test_bed.inactive_regions.lock().remove(&uri);
+
+    (before, after)
+}
+
+/// Inlay hints of the first document of the fixture, as their position and label.
+pub fn inlay_hints(fixture: &str) -> Vec<(Position, String)> {
+    let test_bed = start(fixture, false, serde_json::json!({}));
+    let document = test_bed.documents().first().unwrap();
+    let uri = test_bed.uri(&document.path);
+    let params = lsp_types::InlayHintParams {
+        text_document: TextDocumentIdentifier::new(uri),
+        range: Range::new(
+            Position::new(0, 0),
+            Position::new(document.text.lines().count() as u32, 0),
+        ),
+        work_done_progress_params: Default::default(),
+    };
+
+    test_bed
+        .client()
+        .send_request::<lsp_types::request::InlayHintRequest>(params)
+        .unwrap()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|hint| {
+            let label = match hint.label {
+                lsp_types::InlayHintLabel::String(label) => label,
+                lsp_types::InlayHintLabel::LabelParts(parts) => {
+                    parts.into_iter().map(|part| part.value).collect()
+                }
+            };
+            (hint.position, label)
+        })
+        .collect()
+}
+
+/// Code lenses of the first document of the fixture, resolved, as their line and title.
+pub fn code_lenses(fixture: &str) -> Vec<(u32, String)> {
+    let test_bed = start(
+        fixture,
+        false,
+        serde_json::json!({
+            "experimental": {
+                "commands": {
+                    "commands": [
+                        "sourcepawn-vscode.showReferences",
+                        "sourcepawn-vscode.compileSM"
+                    ]
+                }
+            }
+        }),
+    );
+    let document = test_bed.documents().first().unwrap();
+    let uri = test_bed.uri(&document.path);
+    let params = lsp_types::CodeLensParams {
+        text_document: TextDocumentIdentifier::new(uri),
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+    };
+
+    test_bed
+        .client()
+        .send_request::<lsp_types::request::CodeLensRequest>(params)
+        .unwrap()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|code_lens| match code_lens.command {
+            Some(_) => code_lens,
+            None => test_bed
+                .client()
+                .send_request::<lsp_types::request::CodeLensResolve>(code_lens)
+                .unwrap(),
+        })
+        .map(|code_lens| {
+            (
+                code_lens.range.start.line,
+                code_lens
+                    .command
+                    .map(|command| command.title)
+                    .unwrap_or_default(),
+            )
+        })
+        .collect()
+}
+
+/// Compile the first document of the fixture, with `spcomp` as the shell script run as the
+/// compiler if it is set.
+///
+/// The `compiler.path` setting is prepended to the project file of the fixture, whose settings
+/// must therefore be written with dotted keys, e.g `compiler.verbosity = 0`.
+pub fn compile(fixture: &str, spcomp: Option<&str>) -> Result<lsp::ext::CompileResult, String> {
+    let test_bed = TestBed::new(fixture, false).unwrap();
+    if let Some(spcomp) = spcomp {
+        let spcomp_path = test_bed.directory().join("spcomp");
+        std::fs::write(&spcomp_path, spcomp).unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&spcomp_path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        }
+        let project_file_path = test_bed.directory().join("sourcepawn.toml");
+        let project_file = std::fs::read_to_string(&project_file_path).unwrap_or_default();
+        std::fs::write(
+            &project_file_path,
+            format!(
+                "compiler.path = '{}'\n{}",
+                spcomp_path.display(),
+                project_file
+            ),
+        )
+        .unwrap();
+    }
+    test_bed
+        .initialize(client_capabilities(serde_json::json!({})))
+        .unwrap();
+    let document = test_bed.documents().first().unwrap();
+    let uri = test_bed.uri(&document.path);
+    let params = lsp::ext::CompileParams {
+        text_document: TextDocumentIdentifier::new(uri),
+        work_done_progress_params: Default::default(),
+    };
+
+    test_bed
+        .client()
+        .send_request::<lsp::ext::Compile>(params)
+        .map_err(|err| err.to_string())
+}
+
+/// Project model of the document of the fixture at `path`, or of the whole workspace, as JSON.
+///
+/// The URIs of the fixture are replaced by their path relative to the fixture, e.g
+/// `scripting/main.sp`, or `.` for the fixture itself, and the lists of main files are sorted.
+pub fn project_model(fixture: &str, path: Option<&str>) -> serde_json::Value {
+    let test_bed = start(fixture, false, serde_json::json!({}));
+    let params = lsp::ext::ProjectModelParams {
+        text_document: path.map(|path| TextDocumentIdentifier::new(test_bed.uri(path))),
+    };
+    let mut res = test_bed
+        .client()
+        .send_request::<lsp::ext::ProjectModel>(params)
+        .unwrap();
+    res.main_files.sort();
+    for file in &mut res.files {
+        file.compiled_as_part_of.sort();
+    }
+
+    let root = Url::from_file_path(test_bed.directory()).unwrap();
+    let json = serde_json::to_string(&res)
+        .unwrap()
+        .replace(&format!("\"{root}/"), "\"")
+        .replace(&format!("\"{root}\""), "\".\"");
+
+    serde_json::from_str(&json).unwrap()
+}
+
+/// Include graph of the document of the fixture at `path`, or of the whole workspace, as its
+/// sorted nodes and edges, with the paths relative to the fixture. The edges are made of their
+/// source, target, kind and the line of their directive.
+#[allow(clippy::type_complexity)]
+pub fn include_graph(
+    fixture: &str,
+    path: Option<&str>,
+) -> (Vec<String>, Vec<(String, String, String, Option<u32>)>) {
+    let test_bed = start(fixture, false, serde_json::json!({}));
+    let params = lsp::ext::IncludeGraphParams {
+        text_document: path.map(|path| TextDocumentIdentifier::new(test_bed.uri(path))),
+    };
+    let res = test_bed
+        .client()
+        .send_request::<lsp::ext::IncludeGraph>(params)
+        .unwrap();
+    let relative_path = |uri: Url| {
+        let path = uri.to_file_path().unwrap();
+        let path = path.strip_prefix(test_bed.directory()).unwrap();
+        path.to_string_lossy().replace('\\', "/")
+    };
+    let nodes = res.nodes.into_iter().map(relative_path).sorted().collect();
+    let edges = res
+        .edges
+        .into_iter()
+        .map(|edge| {
+            (
+                relative_path(edge.source),
+                relative_path(edge.target),
+                format!("{:?}", edge.kind),
+                edge.range.map(|range| range.start.line),
+            )
+        })
+        .sorted()
+        .collect();
+
+    (nodes, edges)
+}
+
+/// Folding ranges of the first document of the fixture, as their start line, end line and kind,
+/// for a client which folds whole lines if `line_folding_only` is set.
+pub fn folding_ranges(
+    fixture: &str,
+    line_folding_only: bool,
+) -> Vec<(u32, u32, Option<lsp_types::FoldingRangeKind>)> {
+    let test_bed = start(
+        fixture,
+        false,
+        serde_json::json!({
+            "textDocument": {
+                "foldingRange": {
+                    "lineFoldingOnly": line_folding_only
+                }
+            }
+        }),
+    );
+    let document = test_bed.documents().first().unwrap();
+    let params = lsp_types::FoldingRangeParams {
+        text_document: TextDocumentIdentifier::new(test_bed.uri(&document.path)),
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+    };
+
+    test_bed
+        .client()
+        .send_request::<lsp_types::request::FoldingRangeRequest>(params)
+        .unwrap()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|fold| (fold.start_line, fold.end_line, fold.kind))
+        .sorted_by_key(|(start_line, end_line, _)| (*start_line, *end_line))
+        .collect()
+}
+
+/// Links of the first document of the fixture, as their range and the path of their target
+/// relative to the fixture, sorted by position.
+pub fn document_links(fixture: &str) -> Vec<(Range, String)> {
+    let test_bed = start(fixture, false, serde_json::json!({}));
+    let document = test_bed.documents().first().unwrap();
+    let params = lsp_types::DocumentLinkParams {
+        text_document: TextDocumentIdentifier::new(test_bed.uri(&document.path)),
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+    };
+
+    test_bed
+        .client()
+        .send_request::<lsp_types::request::DocumentLinkRequest>(params)
+        .unwrap()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|link| {
+            let path = link.target.unwrap().to_file_path().unwrap();
+            let path = path.strip_prefix(test_bed.directory()).unwrap();
+            (link.range, path.to_string_lossy().replace('\\', "/"))
+        })
+        .sorted_by_key(|(range, _)| (range.start.line, range.start.character))
+        .collect()
+}
+
+/// Start and kind of the occurrences highlighted for the symbol under the cursor, sorted by
+/// position.
+pub fn document_highlight(fixture: &str) -> Vec<(Position, lsp_types::DocumentHighlightKind)> {
+    let test_bed = start(fixture, false, serde_json::json!({}));
+    let params = lsp_types::DocumentHighlightParams {
+        text_document_position_params: test_bed.cursor().unwrap(),
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+    };
+
+    test_bed
+        .client()
+        .send_request::<lsp_types::request::DocumentHighlightRequest>(params)
+        .unwrap()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|highlight| (highlight.range.start, highlight.kind.unwrap()))
+        .sorted_by_key(|(position, _)| *position)
+        .collect()
+}
+
+/// Ranges linked to the symbol under the cursor, sorted by position.
+pub fn linked_editing_ranges(fixture: &str) -> Option<Vec<Range>> {
+    let test_bed = start(fixture, false, serde_json::json!({}));
+    let params = lsp_types::LinkedEditingRangeParams {
+        text_document_position_params: test_bed.cursor().unwrap(),
+        work_done_progress_params: Default::default(),
+    };
+
+    test_bed
+        .client()
+        .send_request::<lsp_types::request::LinkedEditingRange>(params)
+        .unwrap()
+        .map(|linked| {
+            linked
+                .ranges
+                .into_iter()
+                .sorted_by_key(|range| range.start)
+                .collect()
+        })
+}
+
+/// Range of the name to rename under the cursor, or the reason why it cannot be renamed.
+pub fn prepare_rename(fixture: &str) -> Result<Option<Range>, String> {
+    let test_bed = start(
+        fixture,
+        true,
+        serde_json::json!({
+            "textDocument": {
+                "rename": {
+                    "prepareSupport": true
+                }
+            }
+        }),
+    );
+    let response = test_bed
+        .client()
+        .send_request::<lsp_types::request::PrepareRenameRequest>(test_bed.cursor().unwrap())
+        .map_err(|err| err.to_string())?;
+
+    Ok(response.map(|response| match response {
+        lsp_types::PrepareRenameResponse::Range(range) => range,
+        lsp_types::PrepareRenameResponse::RangeWithPlaceholder { range, .. } => range,
+        lsp_types::PrepareRenameResponse::DefaultBehavior { .. } => {
+            panic!("expected a range")
+        }
+    }))
+}
+
+/// Titles of the code actions at the range of the fixture, or at its cursor, with the text of
+/// the document of the cursor after their edits.
+pub fn code_actions(fixture: &str) -> Vec<(String, String)> {
+    let test_bed = start(
+        fixture,
+        true,
+        serde_json::json!({
+            "textDocument": {
+                "codeAction": {
+                    "codeActionLiteralSupport": {
+                        "codeActionKind": {
+                            "valueSet": ["quickfix", "refactor", "source"]
+                        }
+                    }
+                }
+            }
+        }),
+    );
+    let text_document_position = test_bed.cursor().unwrap();
+    let document = test_bed
+        .documents()
+        .iter()
+        .find(|document| document.cursor.is_some())
+        .unwrap();
+    let range = document.ranges.first().copied().unwrap_or(Range::new(
+        text_document_position.position,
+        text_document_position.position,
+    ));
+    let params = lsp_types::CodeActionParams {
+        text_document: text_document_position.text_document.clone(),
+        range,
+        context: Default::default(),
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+    };
+    let actions = test_bed
+        .client()
+        .send_request::<lsp::ext::CodeActionRequest>(params)
+        .unwrap()
+        .unwrap_or_default();
+
+    actions
+        .into_iter()
+        .map(|action| {
+            let edits = action
+                .edit
+                .and_then(|edit| {
+                    edit.changes
+                        .get(&text_document_position.text_document.uri)
+                        .cloned()
+                })
+                .unwrap_or_default()
+                .into_iter()
+                .map(|edit| (edit.range, edit.new_text))
+                .collect::<Vec<_>>();
+            (action.title, apply_edits(&document.text, &edits))
+        })
+        .collect()
+}
+
+/// Text of the document of the cursor of the fixture after the code action with a title, if it
+/// is offered.
+pub fn apply_code_action(fixture: &str, title: &str) -> Option<String> {
+    code_actions(fixture)
+        .into_iter()
+        .find_map(|(it, text)| (it == title).then_some(text))
+}
+
+/// Text of the document of the cursor of the fixture after renaming the symbol under the cursor,
+/// or the message of the error of the rename.
+pub fn rename(fixture: &str, new_name: &str) -> Result<String, String> {
+    let test_bed = start(
+        fixture,
+        true,
+        serde_json::json!({
+            "textDocument": {
+                "rename": {
+                    "prepareSupport": true
+                }
+            }
+        }),
+    );
+    let text_document_position = test_bed.cursor().unwrap();
+    let document = test_bed
+        .documents()
+        .iter()
+        .find(|document| document.cursor.is_some())
+        .unwrap();
+    let params = lsp_types::RenameParams {
+        text_document_position: text_document_position.clone(),
+        new_name: new_name.to_string(),
+        work_done_progress_params: Default::default(),
+    };
+    let edit = test_bed
+        .client()
+        .send_request::<lsp_types::request::Rename>(params)
+        .map_err(|err| err.to_string())?;
+    let edits = edit
+        .and_then(|edit| edit.changes)
+        .and_then(|mut changes| changes.remove(&text_document_position.text_document.uri))
+        .unwrap_or_default()
+        .into_iter()
+        .map(|edit| (edit.range, edit.new_text))
+        .collect::<Vec<_>>();
+
+    Ok(apply_edits(&document.text, &edits))
+}
+
+/// Code of the first document of the fixture matching a structural search query, and its text
+/// after the replacements, or the message of the error of the query.
+pub fn ssr(fixture: &str, query: &str) -> Result<(Vec<String>, String), String> {
+    let test_bed = start(fixture, false, serde_json::json!({}));
+    let document = test_bed.documents().first().unwrap();
+    let uri = test_bed.uri(&document.path);
+    let params = lsp::ext::SsrParams {
+        query: query.to_string(),
+        parse_only: false,
+    };
+    let res = test_bed
+        .client()
+        .send_request::<lsp::ext::Ssr>(params)
+        .map_err(|err| err.to_string())?;
+    let mut matches = res
+        .matches
+        .into_iter()
+        .filter(|location| location.uri == uri)
+        .map(|location| location.range)
+        .collect::<Vec<_>>();
+    matches.sort_by_key(|range| (range.start.line, range.start.character));
+    let edits = res
+        .edit
+        .changes
+        .and_then(|mut changes| changes.remove(&uri))
+        .unwrap_or_default()
+        .into_iter()
+        .map(|edit| (edit.range, edit.new_text))
+        .collect::<Vec<_>>();
+
+    Ok((
+        matches
+            .into_iter()
+            .map(|range| {
+                document.text
+                    [offset(&document.text, range.start)..offset(&document.text, range.end)]
+                    .to_string()
+            })
+            .collect(),
+        apply_edits(&document.text, &edits),
+    ))
+}
+
+/// Text of the first document of the fixture after formatting it with tabs, or [`None`] if the
+/// server does not format it.
+pub fn format(fixture: &str) -> Option<String> {
+    let test_bed = start(fixture, false, serde_json::json!({}));
+    let document = test_bed.documents().first().unwrap();
+    let params = lsp_types::DocumentFormattingParams {
+        text_document: lsp_types::TextDocumentIdentifier::new(test_bed.uri(&document.path)),
+        options: lsp_types::FormattingOptions {
+            tab_size: 4,
+            insert_spaces: false,
+            ..Default::default()
+        },
+        work_done_progress_params: Default::default(),
+    };
+    let edits = test_bed
+        .client()
+        .send_request::<lsp_types::request::Formatting>(params)
+        .unwrap()?
+        .into_iter()
+        .map(|edit| (edit.range, edit.new_text))
+        .collect::<Vec<_>>();
+
+    Some(apply_edits(&document.text, &edits))
+}
+
+/// Summary of the fixes of the diagnostics of the workspace, only of the diagnostics with the
+/// `code` if there is one, and the texts of the documents they edit, with their path.
+pub fn fix_all(fixture: &str, code: Option<&str>) -> (String, Vec<(String, String)>) {
+    let test_bed = start(fixture, false, serde_json::json!({}));
+    // Wait for the project to be analyzed.
+    test_bed.diagnostics();
+    let res = test_bed
+        .client()
+        .send_request::<lsp::ext::FixAll>(lsp::ext::FixAllParams {
+            code: code.map(str::to_string),
+            text_document: None,
+        })
+        .unwrap();
+    let mut changes = res.edit.changes.unwrap_or_default();
+    let texts = test_bed
+        .documents()
+        .iter()
+        .filter_map(|document| {
+            let edits = changes
+                .remove(&test_bed.uri(&document.path))?
+                .into_iter()
+                .map(|edit| (edit.range, edit.new_text))
+                .collect::<Vec<_>>();
+            Some((
+                document.path.to_string_lossy().replace('\\', "/"),
+                apply_edits(&document.text, &edits),
+            ))
+        })
+        .collect();
+
+    (res.summary, texts)
+}
+
+fn apply_edits(text: &str, edits: &[(Range, String)]) -> String {
+    let offset = |position| offset(text, position);
+    let mut edits = edits
+        .iter()
+        .map(|(range, new_text)| (offset(range.start), offset(range.end), new_text))
+        .collect::<Vec<_>>();
+    // The edits are applied from the end, to keep the offsets of the previous ones.
+    edits.sort_by_key(|(start, end, _)| std::cmp::Reverse((*start, *end)));
+    let mut res = text.to_string();
+    for (start, end, new_text) in edits {
+        res.replace_range(start..end, new_text);
+    }
+
+    res
+}
+
+/// Byte offset of an LSP position in a text.
+fn offset(text: &str, position: Position) -> usize {
+    let line_start = text
+        .split_inclusive('\n')
+        .take(position.line as usize)
+        .map(str::len)
+        .sum::<usize>();
+    let mut units = 0;
+    text[line_start..]
+        .char_indices()
+        .find(|(_, c)| {
+            let found = units >= position.character || *c == '\n';
+            units += c.len_utf16() as u32;
+            found
+        })
+        .map_or(text.len(), |(idx, _)| line_start + idx)
+}
@@ -12,16 +12,21 @@ use parking_lot::{
     MappedRwLockReadGuard, Mutex, RwLock, RwLockReadGuard, RwLockUpgradableReadGuard,
     RwLockWriteGuard,
 };
+use paths::AbsPathBuf;
 use serde::Serialize;
 use std::{sync::Arc, time::Instant};
 use tempfile::TempDir;
 use threadpool::ThreadPool;
-use vfs::{FileId, Vfs};
+use vfs::{FileId, Vfs, VfsPath};
 
 use crate::{
     client::LspClient,
     config::{Config, ConfigError},
-    diagnostics::DiagnosticCollection,
+    daemon::WarmProjects,
+    diagnostics::{
+        baseline::{baseline_key, DiagnosticsBaseline},
+        DiagnosticCollection,
+    },
     line_index::{LineEndings, LineIndex},
     lsp::{self, from_proto, to_proto::url_from_abs_path},
     main_loop::Task,
@@ -52,6 +57,8 @@ pub struct GlobalState {
 
     pub(crate) task_pool: Handle<TaskPool<Task>, Receiver<Task>>,
     pub(crate) diagnostics: DiagnosticCollection,
+    /// Diagnostics of the `diagnostics.baselineFile` setting, which are not reported.
+    pub(crate) diagnostics_baseline: Arc<RwLock<DiagnosticsBaseline>>,
     pub(crate) mem_docs: MemDocs,
     pub(crate) source_root_config: SourceRootConfig,
     pub(crate) semantic_tokens_cache: Arc<Mutex<FxHashMap<Url, SemanticTokens>>>,
@@ -68,7 +75,14 @@ pub struct GlobalState {
     pub config: Arc<Config>,
     pub(crate) config_errors: Option<ConfigError>,
 
-    pub(crate) analysis_host: AnalysisHost,
+    /// Shared with the other sessions and the daemon when the project is warm.
+    pub(crate) analysis_host: Arc<Mutex<AnalysisHost>>,
+    /// Main files pinned with the `sourcepawn-studio/setProjectMainPath` request, which take
+    /// precedence over the `mainPaths` setting.
+    pub(crate) main_paths: FxHashMap<AbsPathBuf, AbsPathBuf>,
+    /// Branches of the conditions forced active with the
+    /// `sourcepawn-studio/toggleForcedBranch` request, by file.
+    pub(crate) forced_branches: FxHashMap<FileId, Vec<u32>>,
 
     // Flycheck
     pub(crate) flycheck: Arc<FxHashMap<FileId, FlycheckHandle>>,
@@ -77,6 +91,9 @@ pub struct GlobalState {
     pub(crate) flycheck_receiver: Receiver<flycheck::Message>,
     pub(crate) last_flycheck_error: Option<String>,
 
+    /// Version of SourceMod whose includes are being downloaded, if any.
+    pub(crate) sourcemod_download: Option<String>,
+
     // VFS
     pub(crate) loader: Handle<Box<dyn vfs::loader::Handle>, Receiver<vfs::loader::Message>>,
     pub(crate) vfs: Arc<RwLock<(vfs::Vfs, IntMap<FileId, LineEndings>)>>,
@@ -87,6 +104,9 @@ pub struct GlobalState {
 
     // op queues
     pub(crate) prime_caches_queue: OpQueue,
+
+    /// Projects warmed up by the daemon, which the session can adopt.
+    pub(crate) warm_projects: WarmProjects,
 }
 
 impl GlobalState {
@@ -121,13 +141,16 @@ impl GlobalState {
             source_root_config: SourceRootConfig::default(),
             semantic_tokens_cache: Arc::new(Mutex::new(FxHashMap::default())),
             diagnostics: DiagnosticCollection::default(),
+            diagnostics_baseline: Arc::default(),
 
             shutdown_requested: false,
             last_reported_status: None,
 
             config: Arc::default(),
             config_errors: Default::default(),
-            analysis_host: AnalysisHost::default(),
+            analysis_host: Arc::default(),
+            main_paths: FxHashMap::default(),
+            forced_branches: FxHashMap::default(),
 
             flycheck: Arc::new(FxHashMap::default()),
             flycheck_tempdir: TempDir::new().expect("failed to create temp dir"),
@@ -135,6 +158,8 @@ impl GlobalState {
             flycheck_receiver,
             last_flycheck_error: None,
 
+            sourcemod_download: None,
+
             loader,
             vfs: Arc::new(RwLock::new((vfs::Vfs::default(), IntMap::default()))),
             vfs_config_version: 0,
@@ -143,16 +168,26 @@ impl GlobalState {
             vfs_progress_n_done: 0,
 
             prime_caches_queue: Default::default(),
+
+            warm_projects: WarmProjects::default(),
         }
     }
 
+    /// Let the session adopt the projects warmed up by the daemon.
+    pub fn with_warm_projects(mut self, warm_projects: WarmProjects) -> Self {
+        self.warm_projects = warm_projects;
+        self
+    }
+
     pub(crate) fn snapshot(&self) -> GlobalStateSnapshot {
         GlobalStateSnapshot {
             config: Arc::clone(&self.config),
-            analysis: self.analysis_host.analysis(),
+            analysis: self.analysis_host.lock().analysis(),
             mem_docs: self.mem_docs.clone(),
             semantic_tokens_cache: Arc::clone(&self.semantic_tokens_cache),
             flycheck: self.flycheck.clone(),
+            diagnostics_baseline: Arc::clone(&self.diagnostics_baseline),
+            client: self.client.clone(),
             vfs: Arc::clone(&self.vfs),
         }
     }
@@ -241,7 +276,9 @@ impl GlobalState {
             let mut guard = self.vfs.write();
             let changed_files = guard.0.take_changes();
             if changed_files.is_empty() {
-                return false;
+                drop(guard);
+                let main_files_changed = self.update_main_files();
+                return self.update_forced_branches() || main_files_changed;
             }
 
             // downgrade to read lock to allow more readers while we are normalizing text
@@ -348,7 +385,7 @@ impl GlobalState {
             (change, changed_files)
         };
 
-        self.analysis_host.apply_change(change);
+        self.analysis_host.lock().apply_change(change);
 
         let mut files = self
             .vfs
@@ -361,10 +398,41 @@ impl GlobalState {
             })
             .collect_vec();
         files.sort(); // FIXME: Maybe we can avoid sorting here? This was done to make the query deterministic.
-        self.analysis_host.set_known_files(files);
+        self.analysis_host.lock().set_known_files(files);
+        self.update_main_files();
+        self.update_forced_branches();
 
         true
     }
+
+    /// Resolve the main files pinned in the settings or by the client, and pass them to the
+    /// analysis.
+    ///
+    /// Returns `true` if the main files changed.
+    pub(crate) fn update_main_files(&mut self) -> bool {
+        let main_files = {
+            let guard = self.vfs.read();
+            let file_id = |path: &AbsPathBuf| guard.0.file_id(&VfsPath::from(path.clone()));
+            self.config
+                .main_paths()
+                .into_iter()
+                .filter(|(path, _)| !self.main_paths.contains_key(path))
+                .chain(self.main_paths.clone())
+                .filter_map(|(path, main_path)| Some((file_id(&path)?, file_id(&main_path)?)))
+                .collect()
+        };
+
+        self.analysis_host.lock().set_main_files(main_files)
+    }
+
+    /// Pass the branches forced active by the client to the analysis.
+    ///
+    /// Returns `true` if the forced branches changed.
+    pub(crate) fn update_forced_branches(&mut self) -> bool {
+        self.analysis_host
+            .lock()
+            .set_forced_branches(self.forced_branches.clone())
+    }
 }
 
 /// An immutable snapshot of the world's state at a point in time.
@@ -375,7 +443,10 @@ pub(crate) struct GlobalStateSnapshot {
     pub(crate) mem_docs: MemDocs,
     pub(crate) semantic_tokens_cache: Arc<Mutex<FxHashMap<Url, SemanticTokens>>>,
     pub(crate) flycheck: Arc<FxHashMap<FileId, FlycheckHandle>>,
-    vfs: Arc<RwLock<(vfs::Vfs, IntMap<FileId, LineEndings>)>>,
+    pub(crate) diagnostics_baseline: Arc<RwLock<DiagnosticsBaseline>>,
+    /// Client to report the progress of the long-running requests with.
+    pub(crate) client: LspClient,
+    pub(crate) vfs: Arc<RwLock<(vfs::Vfs, IntMap<FileId, LineEndings>)>>,
 }
 
 impl std::panic::UnwindSafe for GlobalStateSnapshot {}
@@ -393,6 +464,31 @@ impl GlobalStateSnapshot {
         file_id_to_url(&self.vfs_read(), id)
     }
 
+    /// The `.sp` and `.inc` files of the workspace, excluding the include directories.
+    pub(crate) fn workspace_file_ids(&self) -> Vec<FileId> {
+        let vfs = self.vfs_read();
+        vfs.iter()
+            .filter(|(_, path)| {
+                let Some(path) = path.as_path() else {
+                    return false;
+                };
+                matches!(path.name_and_extension(), Some((_, Some("sp" | "inc"))))
+                    && self
+                        .config
+                        .workspace_roots()
+                        .iter()
+                        .any(|root| path.as_ref().starts_with(root))
+            })
+            .map(|(file_id, _)| file_id)
+            .collect()
+    }
+
+    /// Key of the file in the diagnostics baseline.
+    pub(crate) fn baseline_key(&self, file_id: FileId) -> Option<String> {
+        let vfs = self.vfs_read();
+        baseline_key(self.config.root_path(), vfs.file_path(file_id).as_path()?)
+    }
+
     pub(crate) fn file_line_index(&self, file_id: FileId) -> Cancellable<LineIndex> {
         let endings = self.vfs.read().1[&file_id];
         let index = self.analysis.file_line_index(file_id)?;
@@ -11,8 +11,9 @@ use vfs::{FileId, VfsPath};
 
 use crate::{
     capabilities::ClientCapabilitiesExt,
-    config::Config,
-    lsp::{from_proto, utils::apply_document_changes},
+    config::{Config, PROJECT_FILE_NAME},
+    lsp::{ext::RunFlycheckParams, from_proto, utils::apply_document_changes},
+    manifest::SOURCEKNIGHT_MANIFEST_NAME,
     mem_docs::DocumentData,
     GlobalState,
 };
@@ -135,15 +136,46 @@ pub(crate) fn handle_did_save_text_document(
     Ok(())
 }
 
+pub(crate) fn handle_run_flycheck(
+    state: &mut GlobalState,
+    params: RunFlycheckParams,
+) -> anyhow::Result<()> {
+    if let Some(vfs_path) = params
+        .text_document
+        .and_then(|text_document| from_proto::vfs_path(&text_document.uri).ok())
+    {
+        if run_flycheck(state, vfs_path) {
+            return Ok(());
+        }
+    }
+    // No specific flycheck was triggered, so let's trigger all of them.
+    for flycheck in state.flycheck.values() {
+        flycheck.restart();
+    }
+    Ok(())
+}
+
 pub(crate) fn handle_did_change_watched_files(
     state: &mut GlobalState,
     params: DidChangeWatchedFilesParams,
 ) -> anyhow::Result<()> {
+    let project_files = [PROJECT_FILE_NAME, SOURCEKNIGHT_MANIFEST_NAME]
+        .map(|name| state.config.root_path().join(name));
+    let mut project_file_changed = false;
     for change in params.changes {
         if let Ok(path) = from_proto::abs_path(&change.uri) {
+            if project_files.contains(&path) {
+                project_file_changed = true;
+                continue;
+            }
             state.loader.handle.invalidate(path);
         }
     }
+    if project_file_changed {
+        let mut config = Config::clone(&*state.config);
+        state.config_errors = config.load_project_file().err();
+        state.update_configuration(config, false);
+    }
     Ok(())
 }
 
@@ -1,9 +1,10 @@
-use std::panic::AssertUnwindSafe;
+use std::{panic::AssertUnwindSafe, sync::Arc};
 
 use anyhow::{bail, Context};
-use base_db::FileRange;
-use ide::{CompletionKind, HoverAction, HoverGotoTypeData};
+use base_db::{FileExtension, FileRange, IncludeType};
+use ide::{Annotation, AnnotationKind, CompletionKind, HoverAction, HoverGotoTypeData};
 use ide_db::SymbolKind;
+use lsp_server::ErrorCode;
 use lsp_types::{
     CallHierarchyIncomingCall, CallHierarchyIncomingCallsParams, CallHierarchyItem,
     CallHierarchyOutgoingCall, CallHierarchyOutgoingCallsParams, CallHierarchyPrepareParams,
@@ -11,18 +12,27 @@ use lsp_types::{
     SemanticTokensParams, SemanticTokensRangeParams, SemanticTokensRangeResult,
     SemanticTokensResult, SignatureHelp, SignatureHelpParams, Url,
 };
+use paths::AbsPathBuf;
 use stdx::format_to;
 use vfs::FileId;
 
 use crate::{
-    global_state::GlobalStateSnapshot,
+    diagnostics::{baseline::DiagnosticsBaseline, native_diagnostics},
+    global_state::{GlobalState, GlobalStateSnapshot},
+    line_index::{LineEndings, LineIndex},
     lsp::{
         self,
         ext::{
-            AnalyzerStatusParams, ItemTreeParams, PreprocessedDocumentParams,
-            ProjectMainPathParams, ProjectsGraphvizParams, SyntaxTreeParams,
+            AnalyzerStatusParams, CodeLensResolveData, CompileParams, CompileResult,
+            ConvertToNewSyntaxParams, ConvertToNewSyntaxResult, ExplainDiagnosticParams,
+            FixAllParams, FixAllResult, IncludeGraphEdge, IncludeGraphEdgeKind, IncludeGraphParams,
+            IncludeGraphResult, ItemTreeParams, MacroDefinition, MacrosParams,
+            PreprocessedDocumentParams, ProjectMainPathParams, ProjectModelFile,
+            ProjectModelParams, ProjectModelResult, ProjectsGraphvizParams,
+            SetProjectMainPathParams, SourceMapping, SsrParams, SsrResult, SyntaxTreeParams,
+            WriteDiagnosticsBaselineResult,
         },
-        from_proto, to_proto,
+        from_proto, to_proto, LspError,
     },
 };
 
@@ -128,20 +138,331 @@ pub(crate) fn handle_references(
     Ok(Some(to_proto::references_response(&snap, franges)?))
 }
 
+pub(crate) fn handle_document_highlight(
+    snap: GlobalStateSnapshot,
+    params: lsp_types::DocumentHighlightParams,
+) -> anyhow::Result<Option<Vec<lsp_types::DocumentHighlight>>> {
+    let pos = from_proto::file_position(&snap, params.text_document_position_params)?;
+    let line_index = snap.file_line_index(pos.file_id)?;
+
+    let Some(highlights) = snap.analysis.highlight_related(pos)? else {
+        return Ok(None);
+    };
+
+    Ok(Some(
+        highlights
+            .into_iter()
+            .filter_map(|highlight| to_proto::document_highlight(&line_index, highlight))
+            .collect(),
+    ))
+}
+
+pub(crate) fn handle_linked_editing_range(
+    snap: GlobalStateSnapshot,
+    params: lsp_types::LinkedEditingRangeParams,
+) -> anyhow::Result<Option<lsp_types::LinkedEditingRanges>> {
+    let pos = from_proto::file_position(&snap, params.text_document_position_params)?;
+    let line_index = snap.file_line_index(pos.file_id)?;
+
+    let Some(ranges) = snap.analysis.linked_editing_ranges(pos)? else {
+        return Ok(None);
+    };
+
+    Ok(Some(lsp_types::LinkedEditingRanges {
+        ranges: ranges
+            .into_iter()
+            .filter_map(|range| line_index.try_range(range))
+            .collect(),
+        word_pattern: None,
+    }))
+}
+
+pub(crate) fn handle_prepare_rename(
+    snap: GlobalStateSnapshot,
+    params: lsp_types::TextDocumentPositionParams,
+) -> anyhow::Result<Option<lsp_types::PrepareRenameResponse>> {
+    let pos = from_proto::file_position(&snap, params)?;
+    let line_index = snap.file_line_index(pos.file_id)?;
+
+    let range = snap
+        .analysis
+        .prepare_rename(pos)?
+        .map_err(|err| LspError::new(ErrorCode::InvalidParams as i32, err.to_string()))?;
+
+    Ok(line_index
+        .try_range(range)
+        .map(lsp_types::PrepareRenameResponse::Range))
+}
+
 pub(crate) fn handle_rename(
     snap: GlobalStateSnapshot,
     params: lsp_types::RenameParams,
 ) -> anyhow::Result<Option<lsp_types::WorkspaceEdit>> {
     let pos = from_proto::file_position(&snap, params.text_document_position.clone())?;
 
-    let source_change = match snap.analysis.rename(pos, &params.new_name)? {
-        None => return Ok(None),
-        Some(it) => it,
-    };
+    let source_change = snap
+        .analysis
+        .rename(pos, &params.new_name)?
+        .map_err(|err| LspError::new(ErrorCode::InvalidParams as i32, err.to_string()))?;
 
     Ok(Some(to_proto::workspace_edit(&snap, source_change)))
 }
 
+pub(crate) fn handle_code_action(
+    snap: GlobalStateSnapshot,
+    params: lsp_types::CodeActionParams,
+) -> anyhow::Result<Option<Vec<lsp::ext::CodeAction>>> {
+    let frange = from_proto::file_range(&snap, &params.text_document, params.range)?;
+    let line_index = snap.file_line_index(frange.file_id)?;
+    let only = params.context.only.as_deref();
+    let requested = |kind: &lsp_types::CodeActionKind| {
+        only.map_or(true, |only| {
+            only.iter().any(|it| kind.as_str().starts_with(it.as_str()))
+        })
+    };
+
+    let mut res = Vec::new();
+    let fix_all_kind = lsp_types::CodeActionKind::new(lsp::ext::FIX_ALL_CODE_ACTION_KIND);
+    // The fix all action is only offered when explicitly requested, e.g on save.
+    if only.is_some() && requested(&fix_all_kind) {
+        let fix_all = snap
+            .analysis
+            .fix_all(&snap.config.diagnostics(), None, &[frange.file_id])?;
+        if !fix_all.fixes.is_empty() {
+            res.push(lsp::ext::CodeAction {
+                title: fix_all.summary(),
+                kind: Some(fix_all_kind),
+                edit: Some(to_proto::snippet_workspace_edit(
+                    &snap,
+                    fix_all.source_change,
+                )),
+                ..Default::default()
+            });
+        }
+    }
+    if requested(&lsp_types::CodeActionKind::SOURCE_ORGANIZE_IMPORTS) {
+        if let Some(assist) = snap.analysis.organize_includes(frange.file_id)? {
+            res.push(to_proto::code_action(&snap, assist, Vec::new()));
+        }
+    }
+    if requested(&lsp_types::CodeActionKind::REFACTOR) {
+        let assists = snap.analysis.generate_property(frange)?.into_iter();
+        for assist in assists.chain(snap.analysis.implement_forwards(frange)?) {
+            res.push(to_proto::code_action(&snap, assist, Vec::new()));
+        }
+    }
+    if requested(&lsp_types::CodeActionKind::REFACTOR_EXTRACT) {
+        let assists = snap.analysis.extract_variable(frange)?.into_iter();
+        for assist in assists.chain(snap.analysis.introduce_constant(frange)?) {
+            res.push(to_proto::code_action(&snap, assist, Vec::new()));
+        }
+    }
+    if requested(&lsp_types::CodeActionKind::REFACTOR_REWRITE) {
+        let assists = snap.analysis.convert_syntax_assists(frange)?.into_iter();
+        let surround_with = snap
+            .analysis
+            .surround_with(frange, snap.config.snippet_text_edit())?;
+        for assist in assists.chain(surround_with) {
+            res.push(to_proto::code_action(&snap, assist, Vec::new()));
+        }
+    }
+    if !requested(&lsp_types::CodeActionKind::QUICKFIX) {
+        return Ok(Some(res));
+    }
+
+    for diagnostic in snap
+        .analysis
+        .diagnostics(&snap.config.diagnostics(), frange.file_id)?
+    {
+        if diagnostic.u_range.intersect(frange.range).is_none() {
+            continue;
+        }
+        let range = line_index.range(diagnostic.u_range);
+        let code = lsp_types::NumberOrString::String(diagnostic.code.as_str().to_string());
+        let diagnostics = params
+            .context
+            .diagnostics
+            .iter()
+            .filter(|it| it.range == range && it.code.as_ref() == Some(&code))
+            .cloned()
+            .collect::<Vec<_>>();
+        for fix in diagnostic.fixes.into_iter().flatten() {
+            res.push(to_proto::code_action(&snap, fix, diagnostics.clone()));
+        }
+    }
+    let assists = snap.analysis.auto_import(frange)?.into_iter();
+    for assist in assists.chain(snap.analysis.create_function(frange)?) {
+        res.push(to_proto::code_action(&snap, assist, Vec::new()));
+    }
+
+    Ok(Some(res))
+}
+
+pub(crate) fn handle_fix_all(
+    snap: GlobalStateSnapshot,
+    params: FixAllParams,
+) -> anyhow::Result<FixAllResult> {
+    let file_ids = match &params.text_document {
+        Some(text_document) => vec![from_proto::file_id(&snap, &text_document.uri)?],
+        None => snap.workspace_file_ids(),
+    };
+    let fix_all = snap.analysis.fix_all(
+        &snap.config.diagnostics(),
+        params.code.as_deref(),
+        &file_ids,
+    )?;
+
+    Ok(FixAllResult {
+        fixes: fix_all.fixes.values().sum(),
+        summary: fix_all.summary(),
+        edit: to_proto::workspace_edit(&snap, fix_all.source_change),
+    })
+}
+
+pub(crate) fn handle_convert_to_new_syntax(
+    snap: GlobalStateSnapshot,
+    params: ConvertToNewSyntaxParams,
+) -> anyhow::Result<ConvertToNewSyntaxResult> {
+    let file_ids = match &params.text_document {
+        Some(text_document) => vec![from_proto::file_id(&snap, &text_document.uri)?],
+        None => snap.workspace_file_ids(),
+    };
+    let conversion = snap.analysis.convert_to_new_syntax(&file_ids)?;
+
+    Ok(ConvertToNewSyntaxResult {
+        conversions: conversion.conversions,
+        summary: conversion.summary(),
+        edit: to_proto::workspace_edit(&snap, conversion.source_change),
+    })
+}
+
+pub(crate) fn handle_ssr(
+    snap: GlobalStateSnapshot,
+    params: SsrParams,
+) -> anyhow::Result<SsrResult> {
+    let file_ids = if params.parse_only {
+        Vec::new()
+    } else {
+        snap.workspace_file_ids()
+    };
+    let ssr = snap
+        .analysis
+        .structural_search_replace(&params.query, &file_ids)??;
+    let mut matches = Vec::new();
+    for frange in ssr.matches {
+        if let Some(location) = to_proto::location(&snap, frange)? {
+            matches.push(location);
+        }
+    }
+
+    Ok(SsrResult {
+        matches,
+        edit: to_proto::workspace_edit(&snap, ssr.source_change),
+    })
+}
+
+pub(crate) fn handle_write_diagnostics_baseline(
+    snap: GlobalStateSnapshot,
+    _params: (),
+) -> anyhow::Result<WriteDiagnosticsBaselineResult> {
+    let path = snap
+        .config
+        .diagnostics_baseline_file()
+        .context("the `diagnostics.baselineFile` setting is not set")?;
+    let mut baseline = DiagnosticsBaseline::default();
+    for file_id in snap.workspace_file_ids() {
+        let Some(key) = snap.baseline_key(file_id) else {
+            continue;
+        };
+        let diagnostics = native_diagnostics(&snap, file_id).unwrap_or_default();
+        baseline.add(key, &diagnostics);
+    }
+    baseline.write(&path)?;
+    let diagnostics = baseline.diagnostics_count();
+    *snap.diagnostics_baseline.write() = baseline;
+
+    Ok(WriteDiagnosticsBaselineResult {
+        uri: to_proto::url_from_abs_path(&path),
+        diagnostics,
+    })
+}
+
+pub(crate) fn handle_compile(
+    snap: GlobalStateSnapshot,
+    params: CompileParams,
+) -> anyhow::Result<CompileResult> {
+    let path = from_proto::abs_path(&params.text_document.uri)?;
+    let compiler_path = snap
+        .config
+        .compiler_path()
+        .context("the `compiler.path` setting is not set")?;
+    let (Some(parent), Some((name, _))) = (path.parent(), path.name_and_extension()) else {
+        bail!("{} is not a file", path);
+    };
+    let out_dir = snap
+        .config
+        .compiler_output_directory()
+        .unwrap_or_else(|| parent.to_path_buf());
+    std::fs::create_dir_all(&out_dir)
+        .with_context(|| format!("failed to create the output directory {}", out_dir))?;
+    let out_path = out_dir.join(format!("{name}.smx"));
+
+    let token = params.work_done_progress_params.work_done_token;
+    let report = |progress: lsp_types::WorkDoneProgress| {
+        if let Some(token) = token.clone() {
+            let _ = snap
+                .client
+                .send_notification::<lsp_types::notification::Progress>(
+                    lsp_types::ProgressParams {
+                        token,
+                        value: lsp_types::ProgressParamsValue::WorkDone(progress),
+                    },
+                );
+        }
+    };
+    report(lsp_types::WorkDoneProgress::Begin(
+        lsp_types::WorkDoneProgressBegin {
+            title: format!("Compiling {name}"),
+            ..Default::default()
+        },
+    ));
+    let args = flycheck::build_compile_args(
+        &path,
+        &out_path,
+        &snap.config.include_directories(),
+        &snap.config.compile_options(),
+        &snap.config.compiler_arguments(),
+    );
+    let output = flycheck::compile(compiler_path, args);
+    let message = match &output {
+        Ok(output) if output.success => format!("Compiled to {}", out_path),
+        Ok(_) => "Compilation failed".to_string(),
+        Err(err) => format!("Failed to run spcomp: {err}"),
+    };
+    report(lsp_types::WorkDoneProgress::End(
+        lsp_types::WorkDoneProgressEnd {
+            message: Some(message),
+        },
+    ));
+    let output = output.with_context(|| format!("failed to run {compiler_path}"))?;
+    let count = |severity: flycheck::SpCompSeverity| {
+        output
+            .diagnostics
+            .iter()
+            .filter(|diagnostic| *diagnostic.severity() == severity)
+            .count()
+    };
+
+    Ok(CompileResult {
+        success: output.success,
+        uri: to_proto::url_from_abs_path(&out_path),
+        errors: count(flycheck::SpCompSeverity::Error)
+            + count(flycheck::SpCompSeverity::FatalError),
+        warnings: count(flycheck::SpCompSeverity::Warning),
+        output: output.output,
+    })
+}
+
 pub(crate) fn handle_symbol(
     snap: GlobalStateSnapshot,
     params: lsp_types::DocumentSymbolParams,
@@ -159,6 +480,168 @@ pub(crate) fn handle_symbol(
     )))
 }
 
+pub(crate) fn handle_workspace_symbol(
+    snap: GlobalStateSnapshot,
+    params: lsp_types::WorkspaceSymbolParams,
+) -> anyhow::Result<Option<lsp_types::WorkspaceSymbolResponse>> {
+    let limit = snap.config.workspace_symbol_search_limit();
+    let mut res = Vec::new();
+    for symbol in snap.analysis.workspace_symbols(&params.query, limit)? {
+        if let Some(symbol) = to_proto::workspace_symbol(&snap, symbol)? {
+            res.push(symbol);
+        }
+    }
+
+    Ok(Some(lsp_types::WorkspaceSymbolResponse::Flat(res)))
+}
+
+pub(crate) fn handle_document_link(
+    snap: GlobalStateSnapshot,
+    params: lsp_types::DocumentLinkParams,
+) -> anyhow::Result<Option<Vec<lsp_types::DocumentLink>>> {
+    let file_id = from_proto::file_id(&snap, &params.text_document.uri)?;
+    let line_index = snap.file_line_index(file_id)?;
+
+    let links = snap
+        .analysis
+        .document_links(file_id)?
+        .into_iter()
+        .map(|link| lsp_types::DocumentLink {
+            range: line_index.range(link.range),
+            target: Some(snap.file_id_to_url(link.target)),
+            tooltip: None,
+            data: None,
+        })
+        .collect();
+
+    Ok(Some(links))
+}
+
+pub(crate) fn handle_folding_range(
+    snap: GlobalStateSnapshot,
+    params: lsp_types::FoldingRangeParams,
+) -> anyhow::Result<Option<Vec<lsp_types::FoldingRange>>> {
+    let file_id = from_proto::file_id(&snap, &params.text_document.uri)?;
+    let line_index = snap.file_line_index(file_id)?;
+    let line_folding_only = snap.config.line_folding_only();
+
+    let folds = snap
+        .analysis
+        .folding_ranges(file_id)?
+        .into_iter()
+        .filter_map(|fold| to_proto::folding_range(&line_index, line_folding_only, fold))
+        .collect();
+
+    Ok(Some(folds))
+}
+
+pub(crate) fn handle_formatting(
+    snap: GlobalStateSnapshot,
+    params: lsp_types::DocumentFormattingParams,
+) -> anyhow::Result<Option<Vec<lsp_types::TextEdit>>> {
+    let file_id = from_proto::file_id(&snap, &params.text_document.uri)?;
+    let line_index = snap.file_line_index(file_id)?;
+    let path = from_proto::abs_path(&params.text_document.uri)?;
+    let config = snap.config.formatting(&path, Some(&params.options));
+
+    Ok(snap
+        .analysis
+        .format(file_id, &config)?
+        .map(|edit| vec![to_proto::text_edit(&line_index, edit)]))
+}
+
+pub(crate) fn handle_range_formatting(
+    snap: GlobalStateSnapshot,
+    params: lsp_types::DocumentRangeFormattingParams,
+) -> anyhow::Result<Option<Vec<lsp_types::TextEdit>>> {
+    let frange = from_proto::file_range(&snap, &params.text_document, params.range)?;
+    let line_index = snap.file_line_index(frange.file_id)?;
+    let path = from_proto::abs_path(&params.text_document.uri)?;
+    let config = snap.config.formatting(&path, Some(&params.options));
+
+    Ok(snap
+        .analysis
+        .format_range(frange, &config)?
+        .map(|edit| vec![to_proto::text_edit(&line_index, edit)]))
+}
+
+pub(crate) fn handle_on_type_formatting(
+    snap: GlobalStateSnapshot,
+    params: lsp_types::DocumentOnTypeFormattingParams,
+) -> anyhow::Result<Option<Vec<lsp_types::TextEdit>>> {
+    let Some(char_typed) = params.ch.chars().next() else {
+        return Ok(None);
+    };
+    let path = from_proto::abs_path(&params.text_document_position.text_document.uri)?;
+    let position = from_proto::file_position(&snap, params.text_document_position)?;
+    let line_index = snap.file_line_index(position.file_id)?;
+    let config = snap.config.formatting(&path, Some(&params.options));
+
+    Ok(snap
+        .analysis
+        .on_char_typed(position, char_typed, &config)?
+        .map(|edit| vec![to_proto::text_edit(&line_index, edit)]))
+}
+
+pub(crate) fn handle_inlay_hints(
+    snap: GlobalStateSnapshot,
+    params: lsp_types::InlayHintParams,
+) -> anyhow::Result<Option<Vec<lsp_types::InlayHint>>> {
+    let frange = from_proto::file_range(&snap, &params.text_document, params.range)?;
+    let line_index = snap.file_line_index(frange.file_id)?;
+
+    let hints = snap
+        .analysis
+        .inlay_hints(
+            &snap.config.inlay_hints(),
+            frange.file_id,
+            Some(frange.range),
+        )?
+        .into_iter()
+        .filter_map(|hint| to_proto::inlay_hint(&line_index, hint))
+        .collect();
+
+    Ok(Some(hints))
+}
+
+pub(crate) fn handle_code_lens(
+    snap: GlobalStateSnapshot,
+    params: lsp_types::CodeLensParams,
+) -> anyhow::Result<Option<Vec<lsp_types::CodeLens>>> {
+    let file_id = from_proto::file_id(&snap, &params.text_document.uri)?;
+    let annotations = snap
+        .analysis
+        .annotations(&snap.config.annotations(), file_id)?;
+
+    let mut res = Vec::new();
+    for annotation in annotations {
+        to_proto::code_lens(&mut res, &snap, annotation)?;
+    }
+
+    Ok(Some(res))
+}
+
+pub(crate) fn handle_code_lens_resolve(
+    snap: GlobalStateSnapshot,
+    code_lens: lsp_types::CodeLens,
+) -> anyhow::Result<lsp_types::CodeLens> {
+    let Some(data) = code_lens.data.clone() else {
+        return Ok(code_lens);
+    };
+    let data: CodeLensResolveData = serde_json::from_value(data)?;
+    let pos = from_proto::file_position(&snap, data.position)?;
+    let range = from_proto::text_range(&snap.file_line_index(pos.file_id)?, code_lens.range)?;
+    let annotation = snap.analysis.resolve_annotation(Annotation {
+        range,
+        kind: AnnotationKind::HasReferences { pos, data: None },
+    })?;
+
+    let mut acc = Vec::new();
+    to_proto::code_lens(&mut acc, &snap, annotation)?;
+
+    Ok(acc.pop().unwrap_or(code_lens))
+}
+
 pub(crate) fn handle_hover(
     snap: GlobalStateSnapshot,
     params: lsp_types::HoverParams,
@@ -392,16 +875,20 @@ pub(crate) fn handle_syntax_tree(
     snap: GlobalStateSnapshot,
     params: SyntaxTreeParams,
 ) -> anyhow::Result<String> {
-    let _tree = snap.analysis.parse(from_proto::file_id(
+    let file_id = from_proto::file_id(
         &snap,
         &params
             .text_document
             .context("No text_document parameter passed.")?
             .uri,
-    )?)?;
+    )?;
+    let line_index = snap.file_line_index(file_id)?;
+    let range = params
+        .range
+        .map(|range| from_proto::text_range(&line_index, range))
+        .transpose()?;
 
-    // Ok(prettify_s_expression(&tree.root_node().to_sexp()))
-    Ok("".to_string())
+    Ok(snap.analysis.syntax_tree(file_id, range)?)
 }
 
 pub(crate) fn handle_projects_graphviz(
@@ -419,6 +906,123 @@ pub(crate) fn handle_projects_graphviz(
         .ok_or_else(|| anyhow::anyhow!("Failed to generate graphviz"))
 }
 
+pub(crate) fn handle_project_model(
+    snap: GlobalStateSnapshot,
+    params: ProjectModelParams,
+) -> anyhow::Result<ProjectModelResult> {
+    let mut file_ids = match params.text_document {
+        Some(text_document) => vec![from_proto::file_id(&snap, &text_document.uri)?],
+        None => snap.workspace_file_ids(),
+    };
+    file_ids.sort();
+    let subgraphs = snap.analysis.graph()?.find_subgraphs();
+    let plugins = subgraphs
+        .iter()
+        .filter(|subgraph| subgraph.root.extension == FileExtension::Sp)
+        .collect::<Vec<_>>();
+
+    let mut files = Vec::new();
+    for file_id in file_ids {
+        files.push(ProjectModelFile {
+            uri: to_proto::url(&snap, file_id),
+            compiled_as_part_of: plugins
+                .iter()
+                .filter(|subgraph| subgraph.contains_file(file_id))
+                .map(|subgraph| to_proto::url(&snap, subgraph.root.file_id))
+                .collect(),
+            main_file: snap
+                .analysis
+                .project_main_file(file_id)?
+                .map(|it| to_proto::url(&snap, it)),
+            pinned: snap.analysis.pinned_main_file(file_id)?.is_some(),
+        });
+    }
+    let dir_urls = |paths: &[AbsPathBuf]| -> Vec<Url> {
+        paths
+            .iter()
+            .map(|path| to_proto::url_from_abs_path(path))
+            .collect()
+    };
+
+    Ok(ProjectModelResult {
+        workspace_roots: dir_urls(&snap.config.workspace_root_paths()),
+        scripting_roots: dir_urls(snap.config.scripting_roots()),
+        include_directories: dir_urls(&snap.config.include_directories()),
+        main_files: plugins
+            .iter()
+            .map(|subgraph| to_proto::url(&snap, subgraph.root.file_id))
+            .collect(),
+        files,
+    })
+}
+
+pub(crate) fn handle_include_graph(
+    snap: GlobalStateSnapshot,
+    params: IncludeGraphParams,
+) -> anyhow::Result<IncludeGraphResult> {
+    let file_id = params
+        .text_document
+        .map(|text_document| from_proto::file_id(&snap, &text_document.uri))
+        .transpose()?;
+    let graph = snap.analysis.include_graph(file_id)?;
+
+    let edges = graph
+        .edges
+        .into_iter()
+        .map(|edge| {
+            let range = match edge.range {
+                Some(range) => Some(snap.file_line_index(edge.source)?.range(range)),
+                None => None,
+            };
+            Ok(IncludeGraphEdge {
+                source: to_proto::url(&snap, edge.source),
+                target: to_proto::url(&snap, edge.target),
+                kind: match edge.type_ {
+                    IncludeType::Include => IncludeGraphEdgeKind::Include,
+                    IncludeType::TryInclude => IncludeGraphEdgeKind::TryInclude,
+                },
+                range,
+            })
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    Ok(IncludeGraphResult {
+        nodes: graph
+            .nodes
+            .into_iter()
+            .map(|file_id| to_proto::url(&snap, file_id))
+            .collect(),
+        edges,
+    })
+}
+
+pub(crate) fn handle_macros(
+    snap: GlobalStateSnapshot,
+    params: MacrosParams,
+) -> anyhow::Result<Vec<MacroDefinition>> {
+    let file_id = from_proto::file_id(&snap, &params.text_document.uri)?;
+
+    snap.analysis
+        .file_macros(file_id)?
+        .into_iter()
+        .map(|macro_| {
+            let range = match macro_.range {
+                Some(range) => Some(snap.file_line_index(macro_.file_id)?.range(range)),
+                None => None,
+            };
+            Ok(MacroDefinition {
+                name: macro_.name.to_string(),
+                params: macro_
+                    .params
+                    .map(|params| params.iter().map(|it| it.to_string()).collect()),
+                body: macro_.body,
+                uri: to_proto::url(&snap, macro_.file_id),
+                range,
+            })
+        })
+        .collect()
+}
+
 pub(crate) fn handle_preprocessed_document(
     snap: GlobalStateSnapshot,
     params: PreprocessedDocumentParams,
@@ -435,6 +1039,38 @@ pub(crate) fn handle_preprocessed_document(
         .map(|it| it.to_string())
 }
 
+pub(crate) fn handle_preprocessed_source_map(
+    snap: GlobalStateSnapshot,
+    params: PreprocessedDocumentParams,
+) -> anyhow::Result<Vec<SourceMapping>> {
+    let uri = params
+        .text_document
+        .ok_or_else(|| anyhow::anyhow!("No uri received in request"))?
+        .uri;
+    let file_id = from_proto::file_id(&snap, &uri)?;
+    let line_index = snap.file_line_index(file_id)?;
+    // The preprocessed text only uses `\n` as line separator.
+    let preprocessed_line_index = LineIndex {
+        index: Arc::new(ide::LineIndex::new(
+            &snap.analysis.preprocessed_text(file_id)?,
+        )),
+        endings: LineEndings::Unix,
+        encoding: snap.config.position_encoding(),
+    };
+
+    Ok(snap
+        .analysis
+        .preprocessed_source_map(file_id)?
+        .into_iter()
+        .filter_map(|(u_range, s_range)| {
+            Some(SourceMapping {
+                source_range: line_index.try_range(u_range)?,
+                preprocessed_range: preprocessed_line_index.try_range(s_range)?,
+            })
+        })
+        .collect())
+}
+
 pub(crate) fn handle_item_tree(
     snap: GlobalStateSnapshot,
     params: ItemTreeParams,
@@ -513,9 +1149,69 @@ pub(crate) fn handle_project_main_path(
     let file_id = from_proto::file_id(&snap, &uri)?;
 
     snap.analysis
-        .projects_for_file(file_id)
+        .project_main_file(file_id)
         .context("Failed to get project for file")?
-        .first()
-        .map(|it| to_proto::url(&snap, *it))
+        .map(|it| to_proto::url(&snap, it))
         .ok_or_else(|| anyhow::anyhow!("No project found for file"))
 }
+
+pub(crate) fn handle_set_project_main_path(
+    state: &mut GlobalState,
+    params: SetProjectMainPathParams,
+) -> anyhow::Result<()> {
+    let path = from_proto::abs_path(&params.uri)?;
+    match params.main_uri {
+        Some(main_uri) => {
+            let main_path = from_proto::abs_path(&main_uri)?;
+            if state
+                .vfs
+                .read()
+                .0
+                .file_id(&main_path.clone().into())
+                .is_none()
+            {
+                bail!("file not found: {main_path}");
+            }
+            state.main_paths.insert(path, main_path);
+        }
+        None => {
+            state.main_paths.remove(&path);
+        }
+    }
+
+    Ok(())
+}
+
+pub(crate) fn handle_toggle_forced_branch(
+    state: &mut GlobalState,
+    params: lsp_types::TextDocumentPositionParams,
+) -> anyhow::Result<bool> {
+    let snap = state.snapshot();
+    let position = from_proto::file_position(&snap, params)?;
+    let Some(idx) = snap.analysis.branch_directive_at(position)? else {
+        bail!("no #if, #elseif or #else directive on the line");
+    };
+    let branches = state.forced_branches.entry(position.file_id).or_default();
+    let forced = match branches.iter().position(|it| *it == idx) {
+        Some(i) => {
+            branches.remove(i);
+            false
+        }
+        None => {
+            branches.push(idx);
+            true
+        }
+    };
+    if branches.is_empty() {
+        state.forced_branches.remove(&position.file_id);
+    }
+
+    Ok(forced)
+}
+
+pub(crate) fn handle_explain_diagnostic(
+    _snap: GlobalStateSnapshot,
+    params: ExplainDiagnosticParams,
+) -> anyhow::Result<Option<String>> {
+    Ok(ide::explain_diagnostic(&params.code).map(String::from))
+}
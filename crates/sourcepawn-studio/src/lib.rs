@@ -1,7 +1,12 @@
 mod capabilities;
+mod cli;
 mod client;
+mod crash_report;
+mod daemon;
 mod diagnostics;
+mod discovery;
 mod dispatch;
+mod editorconfig;
 pub mod fixture;
 mod global_state;
 mod handlers {
@@ -10,11 +15,14 @@ mod handlers {
 }
 mod line_index;
 mod main_loop;
+mod manifest;
 mod mem_docs;
 mod op_queue;
 mod progress;
 mod reload;
+mod sourcemod;
 mod task_pool;
+mod transport;
 mod version;
 
 mod config;
@@ -22,7 +30,13 @@ pub mod lsp;
 
 use serde::de::DeserializeOwned;
 
-pub use self::{client::LspClient, global_state::GlobalState};
+pub use self::{
+    cli::{check, format, symbols, CheckFormat},
+    client::LspClient,
+    daemon::WarmProjects,
+    global_state::GlobalState,
+    transport::{send_command, Transport},
+};
 
 pub fn from_json<T: DeserializeOwned>(
     what: &'static str,
@@ -1,10 +1,12 @@
+use std::{collections::HashMap, path::PathBuf};
+
 use ide::WideEncoding;
 use lsp_types::{
     notification::Notification, request::Request, PositionEncodingKind, TextDocumentIdentifier, Url,
 };
 use serde::{Deserialize, Serialize};
 
-use crate::line_index::PositionEncoding;
+use crate::{cli::CheckFormat, line_index::PositionEncoding};
 
 pub fn negotiated_encoding(caps: &lsp_types::ClientCapabilities) -> PositionEncoding {
     let client_encodings = match &caps.general {
@@ -38,6 +40,22 @@ pub struct PreprocessedDocumentParams {
     pub text_document: Option<TextDocumentIdentifier>,
 }
 
+pub enum PreprocessedSourceMap {}
+
+impl Request for PreprocessedSourceMap {
+    type Params = PreprocessedDocumentParams;
+    type Result = Vec<SourceMapping>;
+    const METHOD: &'static str = "sourcepawn-studio/preprocessedSourceMap";
+}
+
+/// A range of a document and the range of its preprocessed text it corresponds to.
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceMapping {
+    pub source_range: lsp_types::Range,
+    pub preprocessed_range: lsp_types::Range,
+}
+
 pub enum SyntaxTree {}
 
 impl Request for SyntaxTree {
@@ -50,6 +68,8 @@ impl Request for SyntaxTree {
 #[serde(rename_all = "camelCase")]
 pub struct SyntaxTreeParams {
     pub text_document: Option<TextDocumentIdentifier>,
+    /// Selection to show the smallest enclosing node of, the whole tree is shown if omitted.
+    pub range: Option<lsp_types::Range>,
 }
 
 pub enum ItemTree {}
@@ -94,6 +114,31 @@ pub struct ProjectMainPathParams {
     pub uri: Option<Url>,
 }
 
+pub enum SetProjectMainPath {}
+
+impl Request for SetProjectMainPath {
+    type Params = SetProjectMainPathParams;
+    type Result = ();
+    const METHOD: &'static str = "sourcepawn-studio/setProjectMainPath";
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SetProjectMainPathParams {
+    pub uri: Url,
+    /// Main file to pin for the file, or [`None`] to clear the pinned main file.
+    pub main_uri: Option<Url>,
+}
+
+pub enum ToggleForcedBranch {}
+
+impl Request for ToggleForcedBranch {
+    type Params = lsp_types::TextDocumentPositionParams;
+    /// Whether the branch is forced active after the request.
+    type Result = bool;
+    const METHOD: &'static str = "sourcepawn-studio/toggleForcedBranch";
+}
+
 pub enum ProjectsGraphviz {}
 
 impl Request for ProjectsGraphviz {
@@ -108,6 +153,332 @@ pub struct ProjectsGraphvizParams {
     pub text_document: Option<TextDocumentIdentifier>,
 }
 
+pub enum ProjectModel {}
+
+impl Request for ProjectModel {
+    type Params = ProjectModelParams;
+    type Result = ProjectModelResult;
+    const METHOD: &'static str = "sourcepawn-studio/projectModel";
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectModelParams {
+    /// Document to describe, all the files of the workspace are described if omitted.
+    pub text_document: Option<TextDocumentIdentifier>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectModelResult {
+    pub workspace_roots: Vec<Url>,
+    /// Directories holding the `.sp` files of the plugins of the workspace.
+    pub scripting_roots: Vec<Url>,
+    /// Include directories, in the order they are searched.
+    pub include_directories: Vec<Url>,
+    /// Files which are not included by another file, i.e the entry points of the plugins.
+    pub main_files: Vec<Url>,
+    pub files: Vec<ProjectModelFile>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectModelFile {
+    pub uri: Url,
+    /// Main files of the plugins the file is compiled as part of.
+    pub compiled_as_part_of: Vec<Url>,
+    /// Main file the file is analyzed as part of, omitted if its project can't be detected.
+    pub main_file: Option<Url>,
+    /// Whether the main file is pinned by the user.
+    pub pinned: bool,
+}
+
+pub enum IncludeGraph {}
+
+impl Request for IncludeGraph {
+    type Params = IncludeGraphParams;
+    type Result = IncludeGraphResult;
+    const METHOD: &'static str = "sourcepawn-studio/includeGraph";
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct IncludeGraphParams {
+    /// Document to compute the graph of, the graph of the whole workspace is computed if omitted.
+    pub text_document: Option<TextDocumentIdentifier>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct IncludeGraphResult {
+    pub nodes: Vec<Url>,
+    pub edges: Vec<IncludeGraphEdge>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct IncludeGraphEdge {
+    pub source: Url,
+    pub target: Url,
+    pub kind: IncludeGraphEdgeKind,
+    /// Range of the include directive in the source document, omitted for the implicit
+    /// includes, e.g sourcemod.
+    pub range: Option<lsp_types::Range>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum IncludeGraphEdgeKind {
+    Include,
+    TryInclude,
+}
+
+pub enum Macros {}
+
+impl Request for Macros {
+    type Params = MacrosParams;
+    type Result = Vec<MacroDefinition>;
+    const METHOD: &'static str = "sourcepawn-studio/macros";
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MacrosParams {
+    pub text_document: TextDocumentIdentifier,
+}
+
+/// Macro visible at the end of a document.
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MacroDefinition {
+    pub name: String,
+    /// Parameters of the macro, omitted if it does not take arguments.
+    pub params: Option<Vec<String>>,
+    pub body: String,
+    /// Document which defines the macro.
+    pub uri: Url,
+    /// Range of the `#define` directive, omitted for the macros which are not defined by a
+    /// directive.
+    pub range: Option<lsp_types::Range>,
+}
+
+pub enum ExplainDiagnostic {}
+
+impl Request for ExplainDiagnostic {
+    type Params = ExplainDiagnosticParams;
+    type Result = Option<String>;
+    const METHOD: &'static str = "sourcepawn-studio/explain";
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ExplainDiagnosticParams {
+    pub code: String,
+}
+
+/// Kind of the code action applying all the fixes of a document.
+pub const FIX_ALL_CODE_ACTION_KIND: &str = "source.fixAll.sourcepawn";
+
+pub enum FixAll {}
+
+impl Request for FixAll {
+    type Params = FixAllParams;
+    type Result = FixAllResult;
+    const METHOD: &'static str = "sourcepawn-studio/fixAll";
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FixAllParams {
+    /// Only apply the fixes of the diagnostics with this code.
+    pub code: Option<String>,
+    /// Document to fix, the whole workspace is fixed if omitted.
+    pub text_document: Option<TextDocumentIdentifier>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FixAllResult {
+    pub edit: lsp_types::WorkspaceEdit,
+    /// Number of fixes applied.
+    pub fixes: usize,
+    pub summary: String,
+}
+
+pub enum ConvertToNewSyntax {}
+
+impl Request for ConvertToNewSyntax {
+    type Params = ConvertToNewSyntaxParams;
+    type Result = ConvertToNewSyntaxResult;
+    const METHOD: &'static str = "sourcepawn-studio/convertToNewSyntax";
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ConvertToNewSyntaxParams {
+    /// Document to convert, the whole workspace is converted if omitted.
+    pub text_document: Option<TextDocumentIdentifier>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ConvertToNewSyntaxResult {
+    pub edit: lsp_types::WorkspaceEdit,
+    /// Number of declarations converted.
+    pub conversions: usize,
+    pub summary: String,
+}
+
+pub enum Ssr {}
+
+impl Request for Ssr {
+    type Params = SsrParams;
+    type Result = SsrResult;
+    const METHOD: &'static str = "sourcepawn-studio/ssr";
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SsrParams {
+    /// Pattern to search, optionally followed by `==>>` and its replacement, e.g
+    /// `GetClientOfUserId(GetClientUserId($c)) ==>> $c`.
+    pub query: String,
+    /// Only check that the query is valid, without searching the workspace.
+    pub parse_only: bool,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SsrResult {
+    /// Locations of the code matching the pattern.
+    pub matches: Vec<lsp_types::Location>,
+    /// Edit replacing the matches, empty if the query has no replacement.
+    pub edit: lsp_types::WorkspaceEdit,
+}
+
+pub enum WriteDiagnosticsBaseline {}
+
+impl Request for WriteDiagnosticsBaseline {
+    type Params = ();
+    type Result = WriteDiagnosticsBaselineResult;
+    const METHOD: &'static str = "sourcepawn-studio/writeDiagnosticsBaseline";
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WriteDiagnosticsBaselineResult {
+    /// The `diagnostics.baselineFile` the baseline was written to.
+    pub uri: Url,
+    /// Number of diagnostics in the baseline.
+    pub diagnostics: usize,
+}
+
+pub enum Compile {}
+
+impl Request for Compile {
+    type Params = CompileParams;
+    type Result = CompileResult;
+    const METHOD: &'static str = "sourcepawn-studio/compile";
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CompileParams {
+    pub text_document: TextDocumentIdentifier,
+    #[serde(flatten)]
+    pub work_done_progress_params: lsp_types::WorkDoneProgressParams,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CompileResult {
+    /// Whether spcomp exited successfully.
+    pub success: bool,
+    /// The `.smx` file the plugin was compiled to.
+    pub uri: Url,
+    /// Output of spcomp.
+    pub output: String,
+    pub errors: usize,
+    pub warnings: usize,
+}
+
+/// `check` command of the CLI, sent to the daemon.
+pub enum Check {}
+
+impl Request for Check {
+    type Params = CheckParams;
+    type Result = CommandResult;
+    const METHOD: &'static str = "sourcepawn-studio/check";
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckParams {
+    /// Absolute path of the project, or of a file, to check.
+    pub path: PathBuf,
+    pub format: CheckFormat,
+    pub deny_warnings: bool,
+}
+
+/// `format` command of the CLI, sent to the daemon.
+pub enum Format {}
+
+impl Request for Format {
+    type Params = FormatParams;
+    type Result = CommandResult;
+    const METHOD: &'static str = "sourcepawn-studio/format";
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FormatParams {
+    /// Absolute path of the project whose project file configures the formatting.
+    pub root: PathBuf,
+    /// Absolute paths of the files and directories to format.
+    pub paths: Vec<PathBuf>,
+    pub check: bool,
+}
+
+/// `symbols` command of the CLI, sent to the daemon.
+pub enum Symbols {}
+
+impl Request for Symbols {
+    type Params = SymbolsParams;
+    type Result = CommandResult;
+    const METHOD: &'static str = "sourcepawn-studio/symbols";
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SymbolsParams {
+    /// Absolute path of the project to search.
+    pub path: PathBuf,
+    pub query: String,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandResult {
+    /// What the command prints.
+    pub output: String,
+    /// Whether the command failed, i.e whether the CLI exits with 1.
+    pub failed: bool,
+}
+
+pub enum InactiveRegionsNotification {}
+
+impl Notification for InactiveRegionsNotification {
+    type Params = InactiveRegionsParams;
+    const METHOD: &'static str = "sourcepawn-studio/inactiveRegions";
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct InactiveRegionsParams {
+    pub text_document: TextDocumentIdentifier,
+    pub regions: Vec<lsp_types::Range>,
+}
+
 pub enum ServerStatusNotification {}
 
 impl Notification for ServerStatusNotification {
@@ -116,10 +487,14 @@ impl Notification for ServerStatusNotification {
 }
 
 #[derive(Deserialize, Serialize, PartialEq, Eq, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
 pub struct ServerStatusParams {
     pub health: Health,
     pub quiescent: bool,
     pub message: Option<String>,
+    /// Names of the experimental analyses enabled by the user.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub experimental_flags: Vec<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
@@ -142,6 +517,20 @@ pub struct SpcompStatusParams {
     pub quiescent: bool,
 }
 
+pub enum RunFlycheck {}
+
+impl Notification for RunFlycheck {
+    type Params = RunFlycheckParams;
+    const METHOD: &'static str = "sourcepawn-studio/runFlycheck";
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RunFlycheckParams {
+    /// Document to check the projects of, all the projects are checked if omitted.
+    pub text_document: Option<TextDocumentIdentifier>,
+}
+
 pub enum HoverRequest {}
 
 impl Request for HoverRequest {
@@ -173,7 +562,55 @@ pub struct CommandLink {
     pub tooltip: Option<String>,
 }
 
+pub enum CodeActionRequest {}
+
+impl Request for CodeActionRequest {
+    type Params = lsp_types::CodeActionParams;
+    type Result = Option<Vec<CodeAction>>;
+    const METHOD: &'static str = lsp_types::request::CodeActionRequest::METHOD;
+}
+
+// LSP v3.17 code actions cannot insert snippets, vscode supports them.
+#[derive(Debug, PartialEq, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CodeAction {
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<lsp_types::CodeActionKind>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diagnostics: Option<Vec<lsp_types::Diagnostic>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub edit: Option<SnippetWorkspaceEdit>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<lsp_types::Command>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_preferred: Option<bool>,
+}
+
+#[derive(Debug, PartialEq, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnippetWorkspaceEdit {
+    pub changes: HashMap<Url, Vec<SnippetTextEdit>>,
+}
+
+/// A text edit whose new text is a snippet when its format is `InsertTextFormat::SNIPPET`.
+#[derive(Debug, PartialEq, Eq, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnippetTextEdit {
+    pub range: lsp_types::Range,
+    pub new_text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub insert_text_format: Option<lsp_types::InsertTextFormat>,
+}
+
 #[derive(Debug, Deserialize, Default)]
 pub struct ClientCommandOptions {
     pub commands: Vec<String>,
 }
+
+/// Data of the unresolved reference code lenses, sent back by the client in `codeLens/resolve`.
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CodeLensResolveData {
+    pub position: lsp_types::TextDocumentPositionParams,
+}
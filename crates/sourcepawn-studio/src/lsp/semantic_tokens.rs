@@ -66,7 +66,11 @@ define_semantic_token_types![
     custom {
         (BOOLEAN, "boolean"),
         (CHAR, "char"),
+        (ENUM_STRUCT, "enumStruct") => STRUCT,
+        (FORWARD, "forward") => FUNCTION,
         (GENERIC, "generic") => TYPE_PARAMETER,
+        (METHODMAP, "methodmap") => CLASS,
+        (NATIVE, "native") => FUNCTION,
     }
 ];
 
@@ -103,6 +107,8 @@ define_semantic_token_modifiers![
         DECLARATION,
         STATIC,
         DEFAULT_LIBRARY,
+        DEPRECATED,
+        READONLY,
     }
     custom {
         (ASYNC, "async"),
@@ -1,15 +1,21 @@
 use std::{
+    ops::Range,
     path,
-    sync::atomic::{AtomicU32, Ordering},
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
 };
 
-use base_db::FileRange;
+use base_db::{FilePosition, FileRange};
 use ide::{
-    Cancellable, CompletionKind, Highlight, HlMod, HlRange, HlTag, Markup, NavigationTarget,
-    Severity, SignatureHelp,
+    Annotation, AnnotationKind, Cancellable, CompletionKind, Fold, FoldKind, Highlight,
+    HighlightedRange, HlMod, HlRange, HlTag, InlayHint, InlayKind, Markup, NavigationTarget,
+    Severity, SignatureHelp, WorkspaceSymbol,
 };
 use ide_db::{
-    CallItem, IncomingCallItem, OutgoingCallItem, SourceChange, SymbolId, SymbolKind, Symbols,
+    Assist, AssistKind, CallItem, Documentation, IncomingCallItem, OutgoingCallItem, SourceChange,
+    SymbolId, SymbolKind, Symbols,
 };
 use itertools::Itertools;
 use lsp_types::TextEdit;
@@ -17,7 +23,11 @@ use paths::AbsPath;
 use rowan::{TextRange, TextSize};
 use vfs::FileId;
 
-use crate::{global_state::GlobalStateSnapshot, line_index::LineIndex};
+use crate::{
+    global_state::GlobalStateSnapshot,
+    line_index::{LineEndings, LineIndex},
+    lsp::ext::{self, CodeLensResolveData},
+};
 
 use super::semantic_tokens;
 
@@ -157,8 +167,8 @@ fn semantic_token_type_and_modifiers(
         HlTag::Symbol(symbol) => match symbol {
             SymbolKind::Macro => semantic_tokens::MACRO,
             SymbolKind::Function => semantic_tokens::FUNCTION,
-            SymbolKind::Native => semantic_tokens::FUNCTION,
-            SymbolKind::Forward => semantic_tokens::INTERFACE,
+            SymbolKind::Native => semantic_tokens::NATIVE,
+            SymbolKind::Forward => semantic_tokens::FORWARD,
             SymbolKind::Constructor => semantic_tokens::METHOD,
             SymbolKind::Destructor => semantic_tokens::METHOD,
             SymbolKind::Typedef => semantic_tokens::INTERFACE,
@@ -166,9 +176,9 @@ fn semantic_token_type_and_modifiers(
             SymbolKind::Functag => semantic_tokens::INTERFACE,
             SymbolKind::Funcenum => semantic_tokens::INTERFACE,
             SymbolKind::Method => semantic_tokens::METHOD,
-            SymbolKind::EnumStruct => semantic_tokens::STRUCT,
-            SymbolKind::Field => semantic_tokens::VARIABLE,
-            SymbolKind::Methodmap => semantic_tokens::CLASS,
+            SymbolKind::EnumStruct => semantic_tokens::ENUM_STRUCT,
+            SymbolKind::Field => semantic_tokens::PROPERTY,
+            SymbolKind::Methodmap => semantic_tokens::METHODMAP,
             SymbolKind::Property => semantic_tokens::PROPERTY,
             SymbolKind::Struct => semantic_tokens::STRUCT,
             SymbolKind::Enum => semantic_tokens::ENUM,
@@ -187,6 +197,8 @@ fn semantic_token_type_and_modifiers(
     for modifier in highlight.mods.iter() {
         let modifier = match modifier {
             HlMod::Macro => semantic_tokens::MACRO_MODIFIER,
+            HlMod::Deprecated => semantic_tokens::DEPRECATED,
+            HlMod::Readonly => semantic_tokens::READONLY,
         };
         mods |= modifier;
     }
@@ -278,6 +290,11 @@ pub(crate) fn completion_item(
         detail: item.detail.map(|it| it.to_string()),
         documentation: item.documentation.map(Into::into),
         data: item.data.and_then(|it| serde_json::to_value(it).ok()),
+        command: item.retrigger_completion.then(|| lsp_types::Command {
+            title: "Trigger suggest".to_string(),
+            command: "editor.action.triggerSuggest".to_string(),
+            arguments: None,
+        }),
         ..Default::default()
     }
 }
@@ -315,23 +332,29 @@ pub(crate) fn completion_item_kind(kind: CompletionKind) -> lsp_types::Completio
 }
 
 pub(crate) fn signature_help(sig: SignatureHelp) -> lsp_types::SignatureHelp {
+    let doc = sig.doc.map(|doc| doc.parse());
+    let labels = parameter_labels(&sig.signature, &sig.parameters);
+    let parameters = sig
+        .parameters
+        .iter()
+        .zip(labels)
+        .map(|(name, label)| lsp_types::ParameterInformation {
+            label,
+            documentation: doc
+                .as_ref()
+                .and_then(|doc| doc.param(name))
+                .map(|description| Documentation::from(description).into()),
+        })
+        .collect_vec();
     lsp_types::SignatureHelp {
         signatures: vec![lsp_types::SignatureInformation {
             label: sig.signature,
-            documentation: sig.doc.clone().map(|doc| doc.into()),
-            parameters: sig
-                .parameters
-                .into_iter()
-                .map(|it| lsp_types::ParameterInformation {
-                    label: lsp_types::ParameterLabel::Simple(it.clone()),
-                    documentation: sig
-                        .doc
-                        .clone()
-                        // This is not efficient, but it's not a hot path.
-                        .and_then(|doc| doc.param_description(&it).map(|it| it.into())),
-                })
-                .collect_vec()
-                .into(),
+            // The parameters are documented separately, next to the active one.
+            documentation: doc
+                .map(|doc| doc.without_params().to_markdown())
+                .filter(|markdown| !markdown.is_empty())
+                .map(markdown_documentation),
+            parameters: parameters.into(),
             active_parameter: sig.active_parameter,
         }],
         active_signature: Default::default(),
@@ -339,6 +362,140 @@ pub(crate) fn signature_help(sig: SignatureHelp) -> lsp_types::SignatureHelp {
     }
 }
 
+/// Labels of the parameters of a signature, as the offsets of their names in the signature, so
+/// that a parameter whose name is also part of the name of the function is highlighted correctly.
+fn parameter_labels(signature: &str, parameters: &[String]) -> Vec<lsp_types::ParameterLabel> {
+    let mut pos = signature.find('(').unwrap_or_default();
+    parameters
+        .iter()
+        .map(|name| {
+            let Some(idx) = find_word(&signature[pos..], name) else {
+                return lsp_types::ParameterLabel::Simple(name.clone());
+            };
+            let start = pos + idx;
+            pos = start + name.len();
+            let utf16_len = |text: &str| text.encode_utf16().count() as u32;
+            lsp_types::ParameterLabel::LabelOffsets([
+                utf16_len(&signature[..start]),
+                utf16_len(&signature[..pos]),
+            ])
+        })
+        .collect()
+}
+
+/// Byte offset of the first occurrence of a word in a text, which is not part of another word.
+fn find_word(text: &str, word: &str) -> Option<usize> {
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+    text.match_indices(word)
+        .find(|(idx, _)| {
+            !text[..*idx].ends_with(is_ident) && !text[idx + word.len()..].starts_with(is_ident)
+        })
+        .map(|(idx, _)| idx)
+}
+
+fn markdown_documentation(value: String) -> lsp_types::Documentation {
+    lsp_types::Documentation::MarkupContent(lsp_types::MarkupContent {
+        kind: lsp_types::MarkupKind::Markdown,
+        value,
+    })
+}
+
+pub(crate) fn document_highlight(
+    line_index: &LineIndex,
+    highlight: HighlightedRange,
+) -> Option<lsp_types::DocumentHighlight> {
+    Some(lsp_types::DocumentHighlight {
+        range: line_index.try_range(highlight.range)?,
+        kind: Some(match highlight.category {
+            ide::ReferenceCategory::Read => lsp_types::DocumentHighlightKind::READ,
+            ide::ReferenceCategory::Write => lsp_types::DocumentHighlightKind::WRITE,
+        }),
+    })
+}
+
+pub(crate) fn inlay_hint(line_index: &LineIndex, hint: InlayHint) -> Option<lsp_types::InlayHint> {
+    Some(lsp_types::InlayHint {
+        position: line_index.try_position(hint.position)?,
+        label: lsp_types::InlayHintLabel::String(format!("{}:", hint.label)),
+        kind: Some(match hint.kind {
+            InlayKind::Parameter => lsp_types::InlayHintKind::PARAMETER,
+        }),
+        text_edits: None,
+        tooltip: None,
+        padding_left: None,
+        padding_right: Some(true),
+        data: None,
+    })
+}
+
+pub(crate) fn code_lens(
+    acc: &mut Vec<lsp_types::CodeLens>,
+    snap: &GlobalStateSnapshot,
+    annotation: Annotation,
+) -> Cancellable<()> {
+    match annotation.kind {
+        AnnotationKind::HasReferences { pos, data } => {
+            let line_index = snap.file_line_index(pos.file_id)?;
+            let (Some(range), Some(position)) = (
+                line_index.try_range(annotation.range),
+                line_index.try_position(pos.offset),
+            ) else {
+                return Ok(());
+            };
+            let doc_pos = lsp_types::TextDocumentPositionParams::new(
+                lsp_types::TextDocumentIdentifier::new(url(snap, pos.file_id)),
+                position,
+            );
+            let Some(ranges) = data else {
+                // The references are computed when the lens is resolved.
+                acc.push(lsp_types::CodeLens {
+                    range,
+                    command: None,
+                    data: serde_json::to_value(CodeLensResolveData { position: doc_pos }).ok(),
+                });
+                return Ok(());
+            };
+            let locations = references_response(snap, ranges)?;
+            let title = match locations.len() {
+                1 => "1 reference".to_owned(),
+                n => format!("{n} references"),
+            };
+            acc.push(lsp_types::CodeLens {
+                range,
+                command: Some(command::show_references(
+                    title,
+                    &doc_pos.text_document.uri,
+                    position,
+                    locations,
+                )),
+                data: None,
+            });
+        }
+        AnnotationKind::Compile { file_id } => {
+            let line_index = snap.file_line_index(file_id)?;
+            let Some(range) = line_index.try_range(annotation.range) else {
+                return Ok(());
+            };
+            acc.push(lsp_types::CodeLens {
+                range,
+                command: Some(command::compile(&url(snap, file_id))),
+                data: None,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+pub(crate) fn text_edit(line_index: &LineIndex, edit: ide_db::TextEdit) -> TextEdit {
+    let new_text = match line_index.endings {
+        LineEndings::Unix => edit.replacement_text().to_string(),
+        LineEndings::Dos => edit.replacement_text().replace('\n', "\r\n"),
+    };
+
+    TextEdit::new(line_index.range(*edit.range()), new_text)
+}
+
 pub(crate) fn workspace_edit(
     snap: &GlobalStateSnapshot,
     source_change: SourceChange,
@@ -369,6 +526,91 @@ pub(crate) fn workspace_edit(
     }
 }
 
+/// Workspace edit of a code action, whose edits are snippets if the source change is one.
+pub(crate) fn snippet_workspace_edit(
+    snap: &GlobalStateSnapshot,
+    source_change: SourceChange,
+) -> ext::SnippetWorkspaceEdit {
+    let insert_text_format = source_change
+        .is_snippet
+        .then_some(lsp_types::InsertTextFormat::SNIPPET);
+    let changes = source_change
+        .source_file_edits
+        .into_iter()
+        .flat_map(|(file_id, edits)| {
+            let line_index = snap.file_line_index(file_id).ok()?;
+            let uri = url(snap, file_id);
+            let text_edits = edits
+                .into_iter()
+                .map(|edit| ext::SnippetTextEdit {
+                    range: line_index.range(*edit.range()),
+                    new_text: edit.replacement_text().to_string(),
+                    insert_text_format,
+                })
+                .collect();
+            Some((uri, text_edits))
+        })
+        .collect();
+
+    ext::SnippetWorkspaceEdit { changes }
+}
+
+pub(crate) fn code_action_kind(kind: AssistKind) -> lsp_types::CodeActionKind {
+    match kind {
+        AssistKind::QuickFix => lsp_types::CodeActionKind::QUICKFIX,
+        AssistKind::Generate => lsp_types::CodeActionKind::REFACTOR,
+        AssistKind::OrganizeIncludes => lsp_types::CodeActionKind::SOURCE_ORGANIZE_IMPORTS,
+        AssistKind::RefactorExtract => lsp_types::CodeActionKind::REFACTOR_EXTRACT,
+        AssistKind::RefactorRewrite => lsp_types::CodeActionKind::REFACTOR_REWRITE,
+    }
+}
+
+pub(crate) fn code_action(
+    snap: &GlobalStateSnapshot,
+    assist: Assist,
+    diagnostics: Vec<lsp_types::Diagnostic>,
+) -> ext::CodeAction {
+    let command = assist
+        .trigger_rename
+        .filter(|_| snap.config.client_commands().trigger_rename)
+        .and_then(|pos| {
+            let position = edited_position(snap, &assist.source_change, pos)?;
+            Some(command::trigger_rename(&url(snap, pos.file_id), position))
+        });
+    ext::CodeAction {
+        title: assist.label,
+        kind: Some(code_action_kind(assist.id.1)),
+        diagnostics: (!diagnostics.is_empty()).then_some(diagnostics),
+        edit: Some(snippet_workspace_edit(snap, assist.source_change)),
+        command,
+        is_preferred: None,
+    }
+}
+
+/// Position of `pos`, an offset in the text of its file once `source_change` is applied.
+fn edited_position(
+    snap: &GlobalStateSnapshot,
+    source_change: &SourceChange,
+    pos: FilePosition,
+) -> Option<lsp_types::Position> {
+    let mut text = snap.analysis.file_text(pos.file_id).ok()?.to_string();
+    let edits = source_change
+        .source_file_edits
+        .get(&pos.file_id)?
+        .iter()
+        .sorted_by_key(|edit| edit.range().start());
+    for edit in edits.rev() {
+        text.replace_range(Range::<usize>::from(*edit.range()), edit.replacement_text());
+    }
+    let line_index = LineIndex {
+        index: Arc::new(ide::LineIndex::new(&text)),
+        endings: snap.file_line_index(pos.file_id).ok()?.endings,
+        encoding: snap.config.position_encoding(),
+    };
+
+    line_index.try_position(pos.offset)
+}
+
 pub(crate) fn document_symbols(
     _snap: &GlobalStateSnapshot,
     line_index: &LineIndex,
@@ -380,15 +622,10 @@ pub(crate) fn document_symbols(
         .collect_vec()
 }
 
-fn document_symbol(
-    idx: &SymbolId,
-    symbols: &Symbols,
-    line_index: &LineIndex,
-) -> Option<lsp_types::DocumentSymbol> {
+pub(crate) fn symbol_kind(kind: SymbolKind) -> lsp_types::SymbolKind {
     use lsp_types::SymbolKind as SK;
 
-    let symbol = &symbols[idx];
-    let kind = match symbol.kind() {
+    match kind {
         SymbolKind::Macro => SK::CONSTANT,
         SymbolKind::Function => SK::FUNCTION,
         SymbolKind::Native => SK::FUNCTION,
@@ -407,7 +644,40 @@ fn document_symbol(
         SymbolKind::Enum => SK::ENUM,
         SymbolKind::Variant => SK::ENUM_MEMBER,
         SymbolKind::Global | SymbolKind::Local => SK::VARIABLE,
+    }
+}
+
+pub(crate) fn workspace_symbol(
+    snap: &GlobalStateSnapshot,
+    symbol: WorkspaceSymbol,
+) -> Cancellable<Option<lsp_types::SymbolInformation>> {
+    let Some(location) = location(snap, symbol.range)? else {
+        return Ok(None);
+    };
+    #[allow(deprecated)]
+    let symbol = lsp_types::SymbolInformation {
+        name: symbol.name.to_string(),
+        kind: symbol_kind(symbol.kind),
+        tags: if symbol.deprecated {
+            Some(vec![lsp_types::SymbolTag::DEPRECATED])
+        } else {
+            None
+        },
+        deprecated: None,
+        location,
+        container_name: symbol.container_name.map(|it| it.to_string()),
     };
+
+    Ok(Some(symbol))
+}
+
+fn document_symbol(
+    idx: &SymbolId,
+    symbols: &Symbols,
+    line_index: &LineIndex,
+) -> Option<lsp_types::DocumentSymbol> {
+    let symbol = &symbols[idx];
+    let kind = symbol_kind(symbol.kind());
     let full_range = line_index.try_range(symbol.full_range())?;
     #[allow(deprecated)]
     let symbol = lsp_types::DocumentSymbol {
@@ -445,6 +715,52 @@ fn document_symbol(
     symbol.into()
 }
 
+/// Convert a [`Fold`] to an LSP folding range.
+///
+/// Clients which only fold whole lines keep the closing brace of a block visible.
+pub(crate) fn folding_range(
+    line_index: &LineIndex,
+    line_folding_only: bool,
+    fold: Fold,
+) -> Option<lsp_types::FoldingRange> {
+    let range = line_index.try_range(fold.range)?;
+    let kind = match fold.kind {
+        FoldKind::Comment => Some(lsp_types::FoldingRangeKind::Comment),
+        FoldKind::Includes => Some(lsp_types::FoldingRangeKind::Imports),
+        FoldKind::Condition => Some(lsp_types::FoldingRangeKind::Region),
+        FoldKind::Block => None,
+    };
+    if line_folding_only {
+        let end_line = match fold.kind {
+            FoldKind::Block => range.end.line.checked_sub(1)?,
+            FoldKind::Comment | FoldKind::Includes | FoldKind::Condition => range.end.line,
+        };
+        if end_line <= range.start.line {
+            return None;
+        }
+        Some(lsp_types::FoldingRange {
+            start_line: range.start.line,
+            start_character: None,
+            end_line,
+            end_character: None,
+            kind,
+            collapsed_text: None,
+        })
+    } else {
+        if range.end.line <= range.start.line {
+            return None;
+        }
+        Some(lsp_types::FoldingRange {
+            start_line: range.start.line,
+            start_character: Some(range.start.character),
+            end_line: range.end.line,
+            end_character: Some(range.end.character),
+            kind,
+            collapsed_text: None,
+        })
+    }
+}
+
 pub(crate) fn call_hierarchy_outgoing(
     snap: &GlobalStateSnapshot,
     outgoing_items: Vec<OutgoingCallItem>,
@@ -557,4 +873,77 @@ pub(crate) mod command {
             arguments: Some(vec![value]),
         })
     }
+
+    pub(crate) fn show_references(
+        title: String,
+        uri: &lsp_types::Url,
+        position: lsp_types::Position,
+        locations: Vec<lsp_types::Location>,
+    ) -> lsp_types::Command {
+        // We cannot use the 'editor.action.showReferences' command directly
+        // because that command requires vscode types which we convert in the handler
+        // on the client side.
+        lsp_types::Command {
+            title,
+            command: "sourcepawn-vscode.showReferences".into(),
+            arguments: Some(vec![
+                to_value(uri).unwrap(),
+                to_value(position).unwrap(),
+                to_value(locations).unwrap(),
+            ]),
+        }
+    }
+
+    pub(crate) fn compile(uri: &lsp_types::Url) -> lsp_types::Command {
+        lsp_types::Command {
+            title: "▶\u{fe0e} Compile".into(),
+            command: "sourcepawn-vscode.compileSM".into(),
+            arguments: Some(vec![to_value(uri).unwrap()]),
+        }
+    }
+
+    pub(crate) fn trigger_rename(
+        uri: &lsp_types::Url,
+        position: lsp_types::Position,
+    ) -> lsp_types::Command {
+        lsp_types::Command {
+            title: "Rename".into(),
+            command: "sourcepawn-vscode.triggerRename".into(),
+            arguments: Some(vec![to_value(uri).unwrap(), to_value(position).unwrap()]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parameter_labels_skip_the_function_name() {
+        assert_eq!(
+            parameter_labels(
+                "void set_name(int name_len, const char[] name)",
+                &["name_len".to_string(), "name".to_string()]
+            ),
+            vec![
+                lsp_types::ParameterLabel::LabelOffsets([18, 26]),
+                lsp_types::ParameterLabel::LabelOffsets([41, 45]),
+            ]
+        );
+    }
+
+    #[test]
+    fn parameter_labels_missing_parameter() {
+        assert_eq!(
+            parameter_labels("void Foo(int client)", &["target".to_string()]),
+            vec![lsp_types::ParameterLabel::Simple("target".to_string())]
+        );
+    }
+
+    #[test]
+    fn find_whole_words() {
+        assert_eq!(find_word("int maxlen, int len", "len"), Some(16));
+        assert_eq!(find_word("int maxlen", "len"), None);
+        assert_eq!(find_word("int len_", "len"), None);
+    }
 }
@@ -1,7 +1,7 @@
 use clap::ArgAction;
 use clap::Parser;
+use clap::Subcommand;
 use log::LevelFilter;
-use lsp_server::Connection;
 use std::env;
 use std::error::Error;
 use std::fs::OpenOptions;
@@ -9,10 +9,14 @@ use std::io;
 use std::path::PathBuf;
 use std::time::SystemTime;
 
-use sourcepawn_studio::GlobalState;
+use paths::AbsPathBuf;
+use sourcepawn_studio::{lsp::ext, send_command, CheckFormat, Transport, WarmProjects};
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
+/// Environment variable holding the token which the clients of `--port` must pass.
+const TOKEN_VAR: &str = "SOURCEPAWN_STUDIO_TOKEN";
+
 /// An implementation of the Language Server Protocol for SourcePawn
 #[derive(Debug, Parser, Clone)]
 #[clap(version)]
@@ -36,6 +40,121 @@ pub struct Opts {
     /// Write the logging output to FILE
     #[clap(long, name = "FILE", value_parser)]
     log_file: Option<PathBuf>,
+
+    /// Listen for clients on the TCP PORT of the loopback interface instead of using stdio. The
+    /// clients must pass the token of the SOURCEPAWN_STUDIO_TOKEN environment variable in the
+    /// `token` field of their initialization options
+    #[clap(long, name = "PORT", global = true)]
+    port: Option<u16>,
+
+    /// Listen for clients on the unix domain SOCKET instead of using stdio. Only the user can
+    /// connect to it
+    #[cfg(unix)]
+    #[clap(
+        long,
+        name = "SOCKET",
+        value_parser,
+        conflicts_with = "PORT",
+        global = true
+    )]
+    socket: Option<PathBuf>,
+
+    /// Run the check, format or symbols command on the daemon listening on ADDRESS, a TCP port or
+    /// the path of a socket, instead of loading the project. Over TCP, the token of the
+    /// SOURCEPAWN_STUDIO_TOKEN environment variable is passed to the daemon
+    #[clap(long, name = "ADDRESS", global = true)]
+    daemon: Option<String>,
+
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand, Clone)]
+enum Command {
+    /// Run as a daemon serving the clients of --port or --socket, and keep the given projects
+    /// analyzed ahead of them. Without --port or --socket, the daemon listens on the
+    /// sourcepawn-studio.sock socket of the runtime directory of the user
+    Daemon {
+        /// Root directory of a project to keep warm
+        #[clap(long = "project", name = "DIR", value_parser)]
+        projects: Vec<PathBuf>,
+    },
+    /// Analyze a project, or a file, without an editor and print its diagnostics. Exit with a
+    /// non-zero code if there are errors, e.g to fail a CI pipeline
+    Check {
+        /// Root directory of the project, or file to check
+        #[clap(name = "PATH", value_parser, default_value = ".")]
+        path: PathBuf,
+
+        /// Output format of the diagnostics
+        #[clap(long, value_enum, default_value = "human")]
+        format: CheckFormat,
+
+        /// Also exit with a non-zero code if there are warnings
+        #[clap(long)]
+        deny_warnings: bool,
+    },
+    /// Format the .sp and .inc files in place, with the formatter of the server
+    Format {
+        /// Files, or directories whose files to format
+        #[clap(name = "PATHS", value_parser, default_value = ".")]
+        paths: Vec<PathBuf>,
+
+        /// Do not write the files, print the unformatted ones and exit with a non-zero code if
+        /// there are any
+        #[clap(long)]
+        check: bool,
+    },
+    /// Search the symbols of a project, with the syntax of the workspace symbols of the editors,
+    /// e.g `#Player` for the types only, and print them. Exit with a non-zero code if none
+    /// matches
+    Symbols {
+        /// Symbols to search
+        #[clap(name = "QUERY")]
+        query: String,
+
+        /// Root directory of the project
+        #[clap(long, value_parser, default_value = ".")]
+        path: PathBuf,
+    },
+}
+
+impl Opts {
+    fn transport(&self) -> Result<Transport, Box<dyn Error + Sync + Send>> {
+        #[cfg(unix)]
+        if let Some(socket) = &self.socket {
+            return Ok(Transport::Socket(socket.clone()));
+        }
+        match self.port {
+            Some(port) => {
+                let token = env::var(TOKEN_VAR).unwrap_or_default();
+                if token.is_empty() {
+                    return Err(
+                        format!("--port requires a token in the {TOKEN_VAR} variable").into(),
+                    );
+                }
+                Ok(Transport::Tcp { port, token })
+            }
+            #[cfg(unix)]
+            None if matches!(self.command, Some(Command::Daemon { .. })) => {
+                Ok(Transport::Socket(default_socket()))
+            }
+            None => Ok(Transport::Stdio),
+        }
+    }
+}
+
+/// Socket of the daemon when none is given, in the runtime directory of the user, or in the
+/// temporary directory.
+#[cfg(unix)]
+fn default_socket() -> PathBuf {
+    match env::var_os("XDG_RUNTIME_DIR") {
+        Some(dir) => PathBuf::from(dir).join("sourcepawn-studio.sock"),
+        None => {
+            let user = env::var("USER").unwrap_or_default();
+            env::temp_dir().join(format!("sourcepawn-studio-{user}.sock"))
+        }
+    }
 }
 
 fn main() -> Result<(), Box<dyn Error + Sync + Send>> {
@@ -58,13 +177,102 @@ fn main() -> Result<(), Box<dyn Error + Sync + Send>> {
     log::info!("Starting sourcepawn-studio version {}", VERSION);
     env::set_var("RUST_BACKTRACE", "full");
     env::set_var("RUST_LIB_BACKTRACE", "full");
-    let (connection, threads) = Connection::stdio();
-    GlobalState::new(connection, opts.amxxpawn_mode).run()?;
-    threads.join()?;
+    if let Some(failed) = run_command(&opts)? {
+        std::process::exit(if failed { 1 } else { 0 });
+    }
+    let transport = opts.transport()?;
+    let warm_projects = WarmProjects::new(opts.amxxpawn_mode);
+    if let Some(Command::Daemon { projects }) = &opts.command {
+        if matches!(transport, Transport::Stdio) {
+            return Err("the daemon requires --port".into());
+        }
+        let cwd = AbsPathBuf::assert(env::current_dir()?);
+        for project in projects {
+            warm_projects.warm(cwd.absolutize(project).normalize());
+        }
+    }
+    transport.serve(opts.amxxpawn_mode, warm_projects)?;
 
     Ok(())
 }
 
+/// Run the `check`, `format` or `symbols` command, on the daemon of `--daemon` if it is set.
+///
+/// Return whether the command failed, or `None` if the command is not one of them.
+fn run_command(opts: &Opts) -> Result<Option<bool>, Box<dyn Error + Sync + Send>> {
+    let cwd = AbsPathBuf::assert(env::current_dir()?);
+    let daemon = opts.daemon.as_deref();
+    let token = env::var(TOKEN_VAR).ok();
+    let token = token.as_deref();
+    let failed = match &opts.command {
+        Some(Command::Check {
+            path,
+            format,
+            deny_warnings,
+        }) => {
+            let path = cwd.absolutize(path).normalize();
+            match daemon {
+                Some(address) => print_output(send_command::<ext::Check>(
+                    address,
+                    token,
+                    ext::CheckParams {
+                        path: path.into(),
+                        format: *format,
+                        deny_warnings: *deny_warnings,
+                    },
+                )?),
+                None => {
+                    sourcepawn_studio::check(path, *format, *deny_warnings, opts.amxxpawn_mode)?
+                }
+            }
+        }
+        Some(Command::Format { paths, check }) => {
+            let paths = paths
+                .iter()
+                .map(|path| cwd.absolutize(path).normalize())
+                .collect::<Vec<_>>();
+            match daemon {
+                Some(address) => print_output(send_command::<ext::Format>(
+                    address,
+                    token,
+                    ext::FormatParams {
+                        root: cwd.into(),
+                        paths: paths.into_iter().map(Into::into).collect(),
+                        check: *check,
+                    },
+                )?),
+                None => sourcepawn_studio::format(cwd, &paths, *check)?,
+            }
+        }
+        Some(Command::Symbols { query, path }) => {
+            let path = cwd.absolutize(path).normalize();
+            match daemon {
+                Some(address) => print_output(send_command::<ext::Symbols>(
+                    address,
+                    token,
+                    ext::SymbolsParams {
+                        path: path.into(),
+                        query: query.clone(),
+                    },
+                )?),
+                None => sourcepawn_studio::symbols(path, query, opts.amxxpawn_mode)?,
+            }
+        }
+        _ => return Ok(None),
+    };
+
+    Ok(Some(failed))
+}
+
+/// Print the output of a command run by the daemon, and return whether it failed.
+fn print_output(res: ext::CommandResult) -> bool {
+    if !res.output.is_empty() {
+        println!("{}", res.output.trim_end_matches('\n'));
+    }
+
+    res.failed
+}
+
 fn setup_logger(opts: Opts) {
     let verbosity_level = if !opts.quiet {
         match opts.verbosity {
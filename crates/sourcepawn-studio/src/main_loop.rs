@@ -1,4 +1,9 @@
-use std::{env, path::PathBuf, time::Instant};
+use std::{
+    env,
+    panic::{self, AssertUnwindSafe},
+    path::PathBuf,
+    time::Instant,
+};
 
 use always_assert::always;
 use base_db::SourceDatabase;
@@ -18,7 +23,8 @@ use vfs::{FileId, VfsPath};
 use crate::{
     capabilities::{server_capabilities, ClientCapabilitiesExt},
     config::Config,
-    diagnostics::fetch_native_diagnostics,
+    crash_report::CrashReporter,
+    diagnostics::{fetch_inactive_regions, fetch_native_diagnostics},
     dispatch::{NotificationDispatcher, RequestDispatcher},
     from_json,
     global_state::file_id_to_url,
@@ -41,7 +47,12 @@ pub(crate) enum Task {
     Response(lsp_server::Response),
     Retry(lsp_server::Request),
     Diagnostics(Vec<(FileId, Vec<lsp_types::Diagnostic>)>),
+    InactiveRegions(Vec<(FileId, Vec<lsp_types::Range>)>),
     PrimeCaches(PrimeCachesProgress),
+    SourcemodIncludes {
+        version: String,
+        result: anyhow::Result<AbsPathBuf>,
+    },
 }
 
 #[derive(Debug)]
@@ -58,6 +69,7 @@ impl GlobalState {
             self.pool.max_count()
         );
 
+        stdx::panic_context::install();
         let mut ignored = self.initialize()?;
 
         while let Some(event) = ignored.pop() {
@@ -69,7 +81,7 @@ impl GlobalState {
             ) {
                 return Ok(());
             }
-            self.handle_event(event)?;
+            self.handle_event_or_report_crash(event)?;
         }
 
         self.reload_flycheck();
@@ -84,7 +96,7 @@ impl GlobalState {
             ) {
                 return Ok(());
             }
-            self.handle_event(event)?;
+            self.handle_event_or_report_crash(event)?;
         }
         Ok(())
     }
@@ -149,7 +161,7 @@ impl GlobalState {
             }
         }
 
-        let server_capabilities = server_capabilities(&config);
+        let server_capabilities = server_capabilities(&config, self.amxxpawn_mode);
 
         let result = InitializeResult {
             capabilities: server_capabilities,
@@ -183,6 +195,8 @@ impl GlobalState {
             Vec::new()
         };
 
+        self.config_errors = config.load_project_file().err();
+        self.adopt_warm_project(&root_path);
         self.update_configuration(config, true);
 
         log::debug!("Server is initialized.");
@@ -321,8 +335,14 @@ impl GlobalState {
             .iter()
             .map(|path| self.vfs.read().0.file_id(path).unwrap())
             .collect_vec();
+        let opened_files = files_to_prime.clone();
         let files_to_prime = if self.config.files_to_prime_below_threshold(
-            self.analysis_host.raw_database().graph().find_roots().len(),
+            self.analysis_host
+                .lock()
+                .raw_database()
+                .graph()
+                .find_roots()
+                .len(),
         ) {
             None
         } else {
@@ -339,6 +359,7 @@ impl GlobalState {
                     let res = analysis.parallel_prime_caches(
                         num_worker_threads,
                         files_to_prime,
+                        opened_files,
                         |progress| {
                             let report = PrimeCachesProgress::Report(progress);
                             sender.send(Task::PrimeCaches(report)).unwrap();
@@ -397,6 +418,13 @@ impl GlobalState {
         use super::handlers::request as handlers;
         use lsp_types::request as lsp_request;
 
+        // The formatting capabilities are not advertised in AMX Mod X mode.
+        if !dispatcher.global_state.amxxpawn_mode {
+            dispatcher
+                .on::<lsp_request::Formatting>(handlers::handle_formatting)
+                .on::<lsp_request::RangeFormatting>(handlers::handle_range_formatting)
+                .on::<lsp_request::OnTypeFormatting>(handlers::handle_on_type_formatting);
+        }
         dispatcher
             .on_latency_sensitive::<lsp_request::SemanticTokensFullRequest>(
                 handlers::handle_semantic_tokens_full,
@@ -412,18 +440,40 @@ impl GlobalState {
             .on::<lsp_request::GotoDefinition>(handlers::handle_goto_definition)
             .on::<lsp_request::SignatureHelpRequest>(handlers::handle_signature_help)
             .on::<lsp_request::References>(handlers::handle_references)
+            .on::<lsp_request::DocumentHighlightRequest>(handlers::handle_document_highlight)
+            .on::<lsp_request::LinkedEditingRange>(handlers::handle_linked_editing_range)
+            .on::<lsp_request::PrepareRenameRequest>(handlers::handle_prepare_rename)
             .on::<lsp_request::Rename>(handlers::handle_rename)
+            .on::<lsp::ext::CodeActionRequest>(handlers::handle_code_action)
             .on::<lsp_request::DocumentSymbolRequest>(handlers::handle_symbol)
+            .on::<lsp_request::WorkspaceSymbolRequest>(handlers::handle_workspace_symbol)
+            .on::<lsp_request::DocumentLinkRequest>(handlers::handle_document_link)
+            .on::<lsp_request::FoldingRangeRequest>(handlers::handle_folding_range)
+            .on::<lsp_request::InlayHintRequest>(handlers::handle_inlay_hints)
+            .on::<lsp_request::CodeLensRequest>(handlers::handle_code_lens)
+            .on::<lsp_request::CodeLensResolve>(handlers::handle_code_lens_resolve)
             .on::<lsp_request::CallHierarchyPrepare>(handlers::handle_call_hierarchy_prepare)
             .on::<lsp_request::CallHierarchyIncomingCalls>(handlers::handle_call_hierarchy_incoming)
             .on::<lsp_request::CallHierarchyOutgoingCalls>(handlers::handle_call_hierarchy_outgoing)
             .on::<lsp::ext::HoverRequest>(handlers::handle_hover)
             .on::<lsp::ext::SyntaxTree>(handlers::handle_syntax_tree)
             .on::<lsp::ext::ProjectsGraphviz>(handlers::handle_projects_graphviz)
+            .on::<lsp::ext::ProjectModel>(handlers::handle_project_model)
+            .on::<lsp::ext::IncludeGraph>(handlers::handle_include_graph)
+            .on::<lsp::ext::Macros>(handlers::handle_macros)
             .on::<lsp::ext::PreprocessedDocument>(handlers::handle_preprocessed_document)
+            .on::<lsp::ext::PreprocessedSourceMap>(handlers::handle_preprocessed_source_map)
             .on::<lsp::ext::ItemTree>(handlers::handle_item_tree)
             .on::<lsp::ext::AnalyzerStatus>(handlers::handle_analyzer_status)
             .on::<lsp::ext::ProjectMainPath>(handlers::handle_project_main_path)
+            .on_sync_mut::<lsp::ext::SetProjectMainPath>(handlers::handle_set_project_main_path)
+            .on_sync_mut::<lsp::ext::ToggleForcedBranch>(handlers::handle_toggle_forced_branch)
+            .on::<lsp::ext::ExplainDiagnostic>(handlers::handle_explain_diagnostic)
+            .on::<lsp::ext::FixAll>(handlers::handle_fix_all)
+            .on::<lsp::ext::ConvertToNewSyntax>(handlers::handle_convert_to_new_syntax)
+            .on::<lsp::ext::Ssr>(handlers::handle_ssr)
+            .on::<lsp::ext::WriteDiagnosticsBaseline>(handlers::handle_write_diagnostics_baseline)
+            .on::<lsp::ext::Compile>(handlers::handle_compile)
             .finish();
         log::debug!("Handled request id: {:?}", req_id);
     }
@@ -443,6 +493,7 @@ impl GlobalState {
         .on_sync_mut::<notifs::DidChangeConfiguration>(handlers::handle_did_change_configuration)?
         .on_sync_mut::<notifs::DidChangeWatchedFiles>(handlers::handle_did_change_watched_files)? // TODO: Implement this.
         .on_sync_mut::<notifs::WorkDoneProgressCancel>(handlers::handle_work_done_progress_cancel)?
+        .on_sync_mut::<lsp::ext::RunFlycheck>(handlers::handle_run_flycheck)?
         .finish();
 
         Ok(())
@@ -476,11 +527,21 @@ impl GlobalState {
             .handle
             .spawn(ThreadIntent::LatencySensitive, {
                 let snapshot = self.snapshot();
+                let subscriptions = subscriptions.clone();
                 move || Task::Diagnostics(fetch_native_diagnostics(snapshot, subscriptions))
             });
+
+        if self.config.inactive_regions_notification() {
+            self.task_pool
+                .handle
+                .spawn(ThreadIntent::LatencySensitive, {
+                    let snapshot = self.snapshot();
+                    move || Task::InactiveRegions(fetch_inactive_regions(snapshot, subscriptions))
+                });
+        }
     }
 
-    fn handle_vfs_msg(&mut self, message: vfs::loader::Message) {
+    pub(crate) fn handle_vfs_msg(&mut self, message: vfs::loader::Message) {
         match message {
             vfs::loader::Message::Loaded { files } => {
                 let vfs = &mut self.vfs.write();
@@ -524,13 +585,20 @@ impl GlobalState {
     fn handle_flycheck_msg(&mut self, message: flycheck::Message) {
         match message {
             flycheck::Message::AddDiagnostic { id, diagnostic, .. } => {
-                let diag = crate::diagnostics::to_proto::map_spcomp_diagnostic_to_lsp(&diagnostic);
-                if let Some(file_id) = self
+                let file_id = self
                     .vfs
                     .read()
                     .0
-                    .file_id(&VfsPath::from(diagnostic.path().to_owned()))
-                {
+                    .file_id(&VfsPath::from(diagnostic.path().to_owned()));
+                if let Some(file_id) = file_id {
+                    let snapshot = self.snapshot();
+                    let text = snapshot.analysis.file_text(file_id).ok();
+                    let line_index = snapshot.file_line_index(file_id).ok();
+                    let diag = crate::diagnostics::to_proto::map_spcomp_diagnostic_to_lsp(
+                        &diagnostic,
+                        text.as_deref(),
+                        line_index.as_ref(),
+                    );
                     self.diagnostics.add_check_diagnostic(id, file_id, diag)
                 }
             }
@@ -586,7 +654,11 @@ impl GlobalState {
         }
     }
 
-    fn handle_task(&mut self, prime_caches_progress: &mut Vec<PrimeCachesProgress>, task: Task) {
+    pub(crate) fn handle_task(
+        &mut self,
+        prime_caches_progress: &mut Vec<PrimeCachesProgress>,
+        task: Task,
+    ) {
         match task {
             Task::Response(response) => self.respond(response),
             Task::Retry(req) if !self.is_completed(&req) => self.on_request(req),
@@ -597,6 +669,17 @@ impl GlobalState {
                         .set_native_diagnostics(file_id, diagnostics)
                 }
             }
+            Task::InactiveRegions(regions_per_file) => {
+                for (file_id, regions) in regions_per_file {
+                    let uri = file_id_to_url(&self.vfs.read().0, file_id);
+                    self.send_notification::<lsp::ext::InactiveRegionsNotification>(
+                        lsp::ext::InactiveRegionsParams {
+                            text_document: lsp_types::TextDocumentIdentifier { uri },
+                            regions,
+                        },
+                    );
+                }
+            }
             Task::PrimeCaches(progress) => match progress {
                 PrimeCachesProgress::Begin => prime_caches_progress.push(progress),
                 PrimeCachesProgress::Report(_) => {
@@ -610,6 +693,55 @@ impl GlobalState {
                 }
                 PrimeCachesProgress::End { .. } => prime_caches_progress.push(progress),
             },
+            Task::SourcemodIncludes { version, result } => {
+                self.sourcemod_download = None;
+                self.report_progress(
+                    "Downloading SourceMod includes",
+                    Progress::End,
+                    None,
+                    None,
+                    None,
+                );
+                match result {
+                    Ok(path) => {
+                        let mut config = Config::clone(&*self.config);
+                        config.set_sourcemod_includes(version, path);
+                        self.update_configuration(config, false);
+                    }
+                    Err(err) => {
+                        let message = format!(
+                            "Failed to download the includes of SourceMod {version}: {err:#}"
+                        );
+                        log::error!("{}", message);
+                        self.send_notification::<ShowMessage>(ShowMessageParams {
+                            typ: MessageType::ERROR,
+                            message,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    /// Handle an event, and write a reproducer bundle if handling it panics.
+    ///
+    /// The panic is resumed afterwards, as the state of the server can't be trusted anymore.
+    fn handle_event_or_report_crash(&mut self, event: Event) -> anyhow::Result<()> {
+        let message = match &event {
+            Event::Lsp(Message::Request(req)) => Some((req.method.clone(), req.params.clone())),
+            Event::Lsp(Message::Notification(not)) => {
+                Some((not.method.clone(), not.params.clone()))
+            }
+            _ => None,
+        };
+        match panic::catch_unwind(AssertUnwindSafe(|| self.handle_event(event))) {
+            Ok(res) => res,
+            Err(err) => {
+                let (method, params) =
+                    message.unwrap_or_else(|| ("main loop".to_owned(), serde_json::Value::Null));
+                CrashReporter::new(self).report(&method, &params);
+                panic::resume_unwind(err)
+            }
         }
     }
 
@@ -698,9 +830,15 @@ impl GlobalState {
         if self.is_quiescent() {
             let became_quiescent = !(was_quiescent);
 
-            if became_quiescent && self.config.prefill_caches() {
-                self.prime_caches_queue
-                    .request_op("became quiescent".to_string(), ());
+            if self.config.prefill_caches() {
+                if became_quiescent {
+                    self.prime_caches_queue
+                        .request_op("became quiescent".to_string(), ());
+                } else if memdocs_added_or_removed {
+                    // Analyze the newly opened files while the server is idle.
+                    self.prime_caches_queue
+                        .request_op("opened files changed".to_string(), ());
+                }
             }
 
             let client_refresh = !was_quiescent || state_changed;
@@ -712,17 +850,14 @@ impl GlobalState {
                 }
 
                 // Refresh code lens if the client supports it.
-                // if self.config.code_lens_refresh() {
-                //     self.send_request::<lsp_types::request::CodeLensRefresh>((), |_, _| ());
-                // }
+                if self.config.code_lens_refresh() {
+                    self.send_request::<lsp_types::request::CodeLensRefresh>((), |_, _| ());
+                }
 
                 // Refresh inlay hints if the client supports it.
-                // if (self.send_hint_refresh_query || self.proc_macro_changed)
-                //     && self.config.inlay_hints_refresh()
-                // {
-                //     self.send_request::<lsp_types::request::InlayHintRefreshRequest>((), |_, _| ());
-                //     self.send_hint_refresh_query = false;
-                // }
+                if self.config.inlay_hints_refresh() {
+                    self.send_request::<lsp_types::request::InlayHintRefreshRequest>((), |_, _| ());
+                }
             }
 
             let update_diagnostics = (!was_quiescent || state_changed || memdocs_added_or_removed)
@@ -0,0 +1,381 @@
+//! Package manifests of the workspace.
+//!
+//! [sourceknight](https://github.com/tmick0/sourceknight) projects declare their dependencies in a
+//! `sourceknight.yaml` file at the root of the workspace:
+//!
+//! ```yaml
+//! project:
+//!   sourceknight: 0.2
+//!   name: myplugin
+//!   dependencies:
+//!     - name: sourcemod
+//!       type: tar
+//!       version: 1.11.0-git6934
+//!       location: https://sm.alliedmods.net/smdrop/1.11/sourcemod-1.11.0-git6934-linux.tar.gz
+//!       unpack:
+//!       - source: /addons
+//!         dest: /addons
+//!   root: /
+//!   output: /addons/sourcemod/plugins
+//!   targets:
+//!     - myplugin
+//! ```
+//!
+//! The dependencies are unpacked in `.sourceknight/package`, and the plugins are compiled from the
+//! `addons/sourcemod/scripting` directory of the `root`, so that the include directories of a
+//! project can be inferred from its manifest.
+
+use paths::{AbsPath, AbsPathBuf};
+
+/// Name of the manifest of the sourceknight projects.
+pub const SOURCEKNIGHT_MANIFEST_NAME: &str = "sourceknight.yaml";
+
+/// Directory the dependencies of a sourceknight project are unpacked to.
+const SOURCEKNIGHT_PACKAGE_DIR: &str = ".sourceknight/package";
+
+/// Directory of the sources of the plugins, relative to the root of a SourceMod installation.
+const SCRIPTING_DIR: &str = "addons/sourcemod/scripting";
+
+/// Package manifest of a workspace.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Manifest {
+    pub name: Option<String>,
+    /// Names of the dependencies, e.g `sourcemod`.
+    pub dependencies: Vec<String>,
+    /// Directories the dependencies are unpacked to, relative to the package directory.
+    pub unpack_destinations: Vec<String>,
+    /// Root of the sources of the plugins, relative to the workspace.
+    pub root: String,
+    /// Names of the plugins, without their `.sp` extension.
+    pub targets: Vec<String>,
+}
+
+impl Manifest {
+    /// Load the manifest of a workspace, if it has one.
+    pub fn load(workspace: &AbsPath) -> Option<Result<Self, String>> {
+        let path = workspace.join(SOURCEKNIGHT_MANIFEST_NAME);
+        match std::fs::read_to_string(&path) {
+            Ok(text) => Some(Self::parse(&text)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+            Err(err) => Some(Err(err.to_string())),
+        }
+    }
+
+    /// Parse a `sourceknight.yaml` manifest.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let document = Yaml::parse(text);
+        let project = document
+            .get("project")
+            .ok_or_else(|| "missing `project` section".to_string())?;
+
+        let mut res = Manifest {
+            name: project.get("name").and_then(Yaml::as_str).map(String::from),
+            root: project
+                .get("root")
+                .and_then(Yaml::as_str)
+                .unwrap_or("/")
+                .to_string(),
+            targets: project
+                .get("targets")
+                .map(|targets| targets.items().filter_map(Yaml::as_str).map(String::from))
+                .into_iter()
+                .flatten()
+                .collect(),
+            ..Default::default()
+        };
+        for dependency in project
+            .get("dependencies")
+            .map(Yaml::items)
+            .into_iter()
+            .flatten()
+        {
+            res.dependencies.extend(
+                dependency
+                    .get("name")
+                    .and_then(Yaml::as_str)
+                    .map(String::from),
+            );
+            res.unpack_destinations.extend(
+                dependency
+                    .get("unpack")
+                    .map(Yaml::items)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|unpack| unpack.get("dest")?.as_str())
+                    .map(String::from),
+            );
+        }
+
+        Ok(res)
+    }
+
+    /// Include directories of the project: the include directories unpacked from the
+    /// dependencies, and the one of the sources of the project.
+    pub fn include_directories(&self, workspace: &AbsPath) -> Vec<AbsPathBuf> {
+        let package = workspace.join(SOURCEKNIGHT_PACKAGE_DIR);
+        let mut res = Vec::new();
+        if !self.dependencies.is_empty() {
+            res.push(package.join(SCRIPTING_DIR).join("include"));
+        }
+        res.extend(
+            self.unpack_destinations
+                .iter()
+                .map(|dest| dest.trim_matches('/'))
+                .filter(|dest| dest.ends_with("include"))
+                .map(|dest| package.join(dest)),
+        );
+        res.push(self.scripting_directory(workspace).join("include"));
+        res.dedup();
+
+        res
+    }
+
+    /// Directory of the sources of the plugins of the project.
+    pub fn scripting_directory(&self, workspace: &AbsPath) -> AbsPathBuf {
+        match self.root.trim_matches('/') {
+            "" => workspace.join(SCRIPTING_DIR),
+            root => workspace.join(root).join(SCRIPTING_DIR),
+        }
+    }
+}
+
+/// Node of the subset of YAML used by the manifests: block mappings and sequences of plain or
+/// quoted scalars, and flow sequences of scalars.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Yaml {
+    Scalar(String),
+    Sequence(Vec<Yaml>),
+    Mapping(Vec<(String, Yaml)>),
+}
+
+impl Yaml {
+    fn parse(text: &str) -> Self {
+        let mut lines = text
+            .lines()
+            .filter_map(|line| {
+                let line = strip_comment(line).trim_end();
+                let content = line.trim_start();
+                (!content.is_empty() && content != "---")
+                    .then(|| (line.len() - content.len(), content.to_string()))
+            })
+            .collect::<Vec<_>>();
+        let indent = lines.first().map(|(indent, _)| *indent).unwrap_or_default();
+
+        parse_block(&mut lines, &mut 0, indent)
+    }
+
+    fn get(&self, key: &str) -> Option<&Yaml> {
+        match self {
+            Yaml::Mapping(entries) => entries.iter().find(|(it, _)| it == key).map(|(_, it)| it),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            Yaml::Scalar(it) => Some(it),
+            _ => None,
+        }
+    }
+
+    fn items(&self) -> impl Iterator<Item = &Yaml> {
+        match self {
+            Yaml::Sequence(items) => items.as_slice(),
+            _ => &[],
+        }
+        .iter()
+    }
+}
+
+/// Parse the block starting at the line `i`, whose lines are indented by `indent`.
+fn parse_block(lines: &mut [(usize, String)], i: &mut usize, indent: usize) -> Yaml {
+    match lines.get(*i) {
+        Some((_, content)) if is_sequence_item(content) => parse_sequence(lines, i, indent),
+        Some(_) => parse_mapping(lines, i, indent),
+        None => Yaml::Scalar(String::new()),
+    }
+}
+
+fn parse_mapping(lines: &mut [(usize, String)], i: &mut usize, indent: usize) -> Yaml {
+    let mut entries = Vec::new();
+    while let Some((line_indent, content)) = lines.get(*i) {
+        if *line_indent != indent || is_sequence_item(content) {
+            break;
+        }
+        let (key, value) = match content.split_once(": ") {
+            Some((key, value)) => (key, value.trim()),
+            None => (content.trim_end_matches(':'), ""),
+        };
+        let key = unquote(key.trim()).to_string();
+        *i += 1;
+        let value = if !value.is_empty() {
+            parse_scalar(value)
+        } else {
+            match lines.get(*i) {
+                // Sequences are allowed at the indentation of their key.
+                Some((next_indent, next))
+                    if *next_indent > indent
+                        || (*next_indent == indent && is_sequence_item(next)) =>
+                {
+                    let next_indent = *next_indent;
+                    parse_block(lines, i, next_indent)
+                }
+                _ => Yaml::Scalar(String::new()),
+            }
+        };
+        entries.push((key, value));
+    }
+
+    Yaml::Mapping(entries)
+}
+
+fn parse_sequence(lines: &mut [(usize, String)], i: &mut usize, indent: usize) -> Yaml {
+    let mut items = Vec::new();
+    while let Some((line_indent, content)) = lines.get(*i) {
+        if *line_indent != indent || !is_sequence_item(content) {
+            break;
+        }
+        let item = content[1..].trim_start();
+        if item.is_empty() {
+            *i += 1;
+            match lines.get(*i) {
+                Some((next_indent, _)) if *next_indent > indent => {
+                    let next_indent = *next_indent;
+                    items.push(parse_block(lines, i, next_indent));
+                }
+                _ => items.push(Yaml::Scalar(String::new())),
+            }
+        } else if is_mapping_entry(item) {
+            // The first entry of a mapping item is on the line of its dash, so the line is
+            // rewritten as if the entry was on its own line.
+            let item_indent = indent + content.len() - item.len();
+            lines[*i] = (item_indent, item.to_string());
+            items.push(parse_mapping(lines, i, item_indent));
+        } else {
+            items.push(parse_scalar(item));
+            *i += 1;
+        }
+    }
+
+    Yaml::Sequence(items)
+}
+
+fn parse_scalar(value: &str) -> Yaml {
+    match value
+        .strip_prefix('[')
+        .and_then(|value| value.strip_suffix(']'))
+    {
+        Some(items) => Yaml::Sequence(
+            items
+                .split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(|item| Yaml::Scalar(unquote(item).to_string()))
+                .collect(),
+        ),
+        None => Yaml::Scalar(unquote(value).to_string()),
+    }
+}
+
+fn is_sequence_item(content: &str) -> bool {
+    content == "-" || content.starts_with("- ")
+}
+
+fn is_mapping_entry(content: &str) -> bool {
+    !content.starts_with(['"', '\''])
+        && (content.contains(": ") || (content.ends_with(':') && !content.contains(' ')))
+}
+
+fn unquote(text: &str) -> &str {
+    for quote in ['"', '\''] {
+        if let Some(text) = text
+            .strip_prefix(quote)
+            .and_then(|text| text.strip_suffix(quote))
+        {
+            return text;
+        }
+    }
+
+    text
+}
+
+/// Strip the comment of a line, i.e from a `#` at the start of the line or after a whitespace,
+/// outside of quotes.
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut previous = ' ';
+    for (offset, c) in line.char_indices() {
+        match (c, quote) {
+            ('"' | '\'', None) => quote = Some(c),
+            (_, Some(it)) if it == c => quote = None,
+            ('#', None) if previous.is_whitespace() => return &line[..offset],
+            _ => (),
+        }
+        previous = c;
+    }
+
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_sourceknight_manifest() {
+        let text = r#"# Manifest of the plugin.
+project:
+  sourceknight: 0.2
+  name: myplugin
+  dependencies:
+    - name: sourcemod
+      type: tar
+      version: 1.11.0-git6934
+      location: https://sm.alliedmods.net/smdrop/1.11/sourcemod-1.11.0-git6934-linux.tar.gz
+      unpack:
+      - source: /addons
+        dest: /addons
+    - name: "multicolors"
+      type: git
+      repo: https://github.com/Bara/Multi-Colors
+      unpack:
+      - source: /addons/sourcemod/scripting/include
+        dest: /addons/sourcemod/scripting/include/multicolors # Nested on purpose.
+  root: /src
+  output: /addons/sourcemod/plugins
+  targets: [myplugin, 'other']
+"#;
+        let manifest = Manifest::parse(text).unwrap();
+        assert_eq!(
+            manifest,
+            Manifest {
+                name: Some("myplugin".to_string()),
+                dependencies: vec!["sourcemod".to_string(), "multicolors".to_string()],
+                unpack_destinations: vec![
+                    "/addons".to_string(),
+                    "/addons/sourcemod/scripting/include/multicolors".to_string()
+                ],
+                root: "/src".to_string(),
+                targets: vec!["myplugin".to_string(), "other".to_string()],
+            }
+        );
+
+        let workspace = AbsPathBuf::assert("/workspace".into());
+        assert_eq!(
+            manifest
+                .include_directories(&workspace)
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            vec![
+                "/workspace/.sourceknight/package/addons/sourcemod/scripting/include",
+                "/workspace/src/addons/sourcemod/scripting/include",
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_manifest_without_project() {
+        assert!(Manifest::parse("name: foo\n").is_err());
+    }
+}
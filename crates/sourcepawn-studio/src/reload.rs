@@ -1,5 +1,7 @@
 use std::sync::Arc;
-use std::{mem, vec};
+use std::{fs, iter, mem, path::PathBuf, vec};
+
+use base_db::{Gamedata, Phrases, GAMEDATA_FILE_EXTENSION, PHRASES_FILE_EXTENSION};
 
 use flycheck::{FlycheckConfig, FlycheckHandle};
 use fxhash::FxHashMap;
@@ -8,9 +10,12 @@ use paths::AbsPathBuf;
 use vfs::VfsPath;
 
 use crate::lsp;
-use crate::{config::Config, GlobalState};
+use crate::{
+    config::Config, diagnostics::baseline::DiagnosticsBaseline, main_loop::Task,
+    progress::Progress, sourcemod, GlobalState,
+};
 
-use stdx::format_to;
+use stdx::{format_to, thread::ThreadIntent};
 
 impl GlobalState {
     pub(crate) fn is_quiescent(&self) -> bool {
@@ -24,6 +29,13 @@ impl GlobalState {
             health: lsp::ext::Health::Ok,
             quiescent: self.is_quiescent(),
             message: None,
+            experimental_flags: self
+                .config
+                .experimental_flags()
+                .names()
+                .into_iter()
+                .map(ToOwned::to_owned)
+                .collect(),
         };
         let mut message = String::new();
 
@@ -35,12 +47,19 @@ impl GlobalState {
         status
     }
 
-    pub(crate) fn update_configuration(&mut self, config: Config, initialization: bool) {
+    pub(crate) fn update_configuration(&mut self, mut config: Config, initialization: bool) {
+        self.fetch_sourcemod_includes(&mut config);
         let old_config = mem::replace(&mut self.config, Arc::new(config));
         if self.config.include_directories() != old_config.include_directories()
             || self.config.root_path() != old_config.root_path()
         {
-            let mut roots = vec![VfsPath::from(self.config.root_path().clone())];
+            let mut roots = self
+                .config
+                .workspace_root_paths()
+                .into_iter()
+                .map(VfsPath::from)
+                .collect_vec();
+            self.source_root_config.local_roots = roots.len();
             roots.extend(
                 self.config
                     .include_directories()
@@ -55,18 +74,40 @@ impl GlobalState {
                 .map(vfs::loader::Entry::sp_files_recursively)
                 .collect_vec();
             let watch = (0..load.len()).collect_vec();
-            // The root_path can be the FS' root. Do not scrape the whole FS in that case.
-            if self.config.root_path().parent().is_some() {
-                load.push(vfs::loader::Entry::sp_files_recursively(
-                    self.config.root_path().clone(),
-                ));
-            }
+            // A workspace root can be the FS' root. Do not scrape the whole FS in that case.
+            load.extend(
+                self.config
+                    .workspace_root_paths()
+                    .into_iter()
+                    .filter(|root| root.parent().is_some())
+                    .map(vfs::loader::Entry::sp_files_recursively),
+            );
             self.vfs_config_version += 1;
             self.loader.handle.set_config(vfs::loader::Config {
                 load,
                 watch,
                 version: self.vfs_config_version,
             });
+            self.load_phrases();
+            self.load_gamedata();
+        }
+        self.analysis_host
+            .lock()
+            .set_implicit_includes(self.config.implicit_includes());
+        self.analysis_host
+            .lock()
+            .set_include_search_order(self.config.include_search_order());
+        self.analysis_host
+            .lock()
+            .set_include_limits(self.config.include_limits());
+        self.analysis_host
+            .lock()
+            .set_macro_configurations(self.config.macro_configurations());
+        if initialization
+            || self.config.diagnostics_baseline_file() != old_config.diagnostics_baseline_file()
+            || self.config.root_path() != old_config.root_path()
+        {
+            self.load_diagnostics_baseline();
         }
         if !initialization
             && (self.config.compiler_path() != old_config.compiler_path()
@@ -77,8 +118,141 @@ impl GlobalState {
         }
     }
 
+    /// Use the cached includes of the `sourcemod.version` setting, or download them in the
+    /// background. The configuration is updated again once they are downloaded.
+    fn fetch_sourcemod_includes(&mut self, config: &mut Config) {
+        let Some(version) = config.sourcemod_version().map(ToOwned::to_owned) else {
+            return;
+        };
+        if config.sourcemod_includes().is_some() {
+            return;
+        }
+        if let Some(path) = sourcemod::cached_includes(&version) {
+            config.set_sourcemod_includes(version, path);
+            return;
+        }
+        if self.sourcemod_download.as_ref() == Some(&version) {
+            return;
+        }
+        self.sourcemod_download = Some(version.clone());
+        self.report_progress(
+            "Downloading SourceMod includes",
+            Progress::Begin,
+            Some(version.clone()),
+            None,
+            None,
+        );
+        self.task_pool.handle.spawn(ThreadIntent::Worker, move || {
+            let result = sourcemod::download_includes(&version);
+            Task::SourcemodIncludes { version, result }
+        });
+    }
+
+    /// Load the diagnostics baseline of the `diagnostics.baselineFile` setting.
+    pub(crate) fn load_diagnostics_baseline(&mut self) {
+        let baseline = match self.config.diagnostics_baseline_file() {
+            Some(path) => DiagnosticsBaseline::load(&path).unwrap_or_else(|err| {
+                log::warn!("{:#}", err);
+                DiagnosticsBaseline::default()
+            }),
+            None => DiagnosticsBaseline::default(),
+        };
+        *self.diagnostics_baseline.write() = baseline;
+    }
+
+    /// Directories of a kind of SourceMod data next to the root and to the include directories,
+    /// e.g `addons/sourcemod/translations` for `addons/sourcemod/scripting/include`.
+    fn sourcemod_data_dirs(&self, name: &str) -> Vec<PathBuf> {
+        let mut dirs: Vec<PathBuf> = Vec::new();
+        for dir in
+            iter::once(self.config.root_path().clone()).chain(self.config.include_directories())
+        {
+            let dir = PathBuf::from(dir);
+            for ancestor in dir.ancestors().take(3) {
+                let data_dir = ancestor.join(name);
+                if data_dir.is_dir() && !dirs.contains(&data_dir) {
+                    dirs.push(data_dir);
+                }
+            }
+        }
+
+        dirs
+    }
+
+    /// Load the phrases of the translation files of the project, from the `translations`
+    /// directories.
+    pub(crate) fn load_phrases(&mut self) {
+        let dirs = self.sourcemod_data_dirs("translations");
+        let mut phrases = Phrases::default();
+        for entry in dirs
+            .iter()
+            .filter_map(|dir| fs::read_dir(dir).ok())
+            .flatten()
+        {
+            let Ok(entry) = entry else {
+                continue;
+            };
+            let path = entry.path();
+            let Some(file) = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .filter(|name| name.ends_with(PHRASES_FILE_EXTENSION))
+                .and_then(|name| name.strip_suffix(".txt"))
+            else {
+                continue;
+            };
+            match fs::read_to_string(&path) {
+                Ok(text) => phrases.add_file(file, text.trim_start_matches('\u{feff}')),
+                Err(err) => log::warn!("Failed to read {}: {}", path.display(), err),
+            }
+        }
+        self.analysis_host.lock().set_phrases(phrases);
+    }
+
+    /// Load the entries of the gamedata files of the project, from the `gamedata` directories.
+    ///
+    /// A gamedata file is either a `foo.games.txt` file, or a `foo.games` directory whose files
+    /// are merged.
+    pub(crate) fn load_gamedata(&mut self) {
+        let mut gamedata = Gamedata::default();
+        for entry in self
+            .sourcemod_data_dirs("gamedata")
+            .iter()
+            .filter_map(|dir| fs::read_dir(dir).ok())
+            .flatten()
+        {
+            let Ok(entry) = entry else {
+                continue;
+            };
+            let path = entry.path();
+            let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            let (file, paths) = if path.is_dir() {
+                let paths = fs::read_dir(&path)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|entry| Some(entry.ok()?.path()))
+                    .filter(|path| path.to_string_lossy().ends_with(GAMEDATA_FILE_EXTENSION))
+                    .collect_vec();
+                (name, paths)
+            } else if let Some(file) = name.strip_suffix(GAMEDATA_FILE_EXTENSION) {
+                (file, vec![path.clone()])
+            } else {
+                continue;
+            };
+            for path in paths {
+                match fs::read_to_string(&path) {
+                    Ok(text) => gamedata.add_file(file, text.trim_start_matches('\u{feff}')),
+                    Err(err) => log::warn!("Failed to read {}: {}", path.display(), err),
+                }
+            }
+        }
+        self.analysis_host.lock().set_gamedata(gamedata);
+    }
+
     pub fn reload_flycheck(&mut self) {
-        let analysis = self.analysis_host.analysis();
+        let analysis = self.analysis_host.lock().analysis();
         let Some(compiler_path) = self.config.compiler_path() else {
             return;
         };
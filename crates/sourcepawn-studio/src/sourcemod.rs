@@ -0,0 +1,166 @@
+//! Download of the includes of a SourceMod version.
+//!
+//! The includes are extracted from the SourceMod builds of the
+//! [AlliedModders drop site](https://sm.alliedmods.net/smdrop) and cached in the cache directory
+//! of the user, by version. A version is either a branch, e.g `1.12`, `dev` for the development
+//! branch, or a build, e.g `1.12.0-git7110`. The build of a branch is pinned when it is
+//! downloaded, and is only updated when its cache is deleted.
+
+use std::{
+    env, fs,
+    io::{self, Cursor},
+    path::PathBuf,
+};
+
+use anyhow::{bail, Context};
+use lazy_static::lazy_static;
+use paths::AbsPathBuf;
+use regex::Regex;
+use zip::ZipArchive;
+
+const SMDROP_URL: &str = "https://sm.alliedmods.net/smdrop";
+
+/// Directory of the includes in a SourceMod build.
+const INCLUDE_DIR: &str = "addons/sourcemod/scripting/include/";
+
+/// File of the cache of a version, holding the name of the archive of its build.
+const BUILD_FILE: &str = "build";
+
+/// Directory of the cached includes of the version, if they are downloaded.
+pub(crate) fn cached_includes(version: &str) -> Option<AbsPathBuf> {
+    let dir = version_dir(version)?.join("include");
+
+    dir.is_dir().then_some(dir)
+}
+
+/// Download the includes of the version, and return their directory.
+pub(crate) fn download_includes(version: &str) -> anyhow::Result<AbsPathBuf> {
+    let dir = version_dir(version)
+        .with_context(|| format!("invalid SourceMod version or no cache directory: {version}"))?;
+    let (branch, build) = match parse_build(version) {
+        Some(branch) => (branch, format!("sourcemod-{version}-windows.zip")),
+        None => {
+            let branch = match version {
+                "dev" => latest_branch()?,
+                _ => version.to_string(),
+            };
+            // The Windows builds are zip archives, and hold the same includes as the other ones.
+            let build = get(&format!("{SMDROP_URL}/{branch}/sourcemod-latest-windows"))?;
+            (branch, String::from_utf8_lossy(&build).trim().to_string())
+        }
+    };
+    log::info!("Downloading the includes of SourceMod {version} from {build}");
+    let archive = get(&format!("{SMDROP_URL}/{branch}/{build}"))?;
+
+    // Extract in a temporary directory first, so that an interrupted download is not cached.
+    let tmp_dir = dir.join("include.tmp");
+    let _ = fs::remove_dir_all(&tmp_dir);
+    extract_includes(&archive, &tmp_dir)?;
+    let include_dir = dir.join("include");
+    let _ = fs::remove_dir_all(&include_dir);
+    fs::rename(&tmp_dir, &include_dir)?;
+    fs::write(dir.join(BUILD_FILE), &build)?;
+
+    Ok(include_dir)
+}
+
+/// Branch of a build, e.g `1.12` for `1.12.0-git7110`, or [`None`] if the version is not a build.
+fn parse_build(version: &str) -> Option<String> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(r"^(\d+\.\d+)\.\d+-git\d+$").unwrap();
+    }
+
+    Some(RE.captures(version)?.get(1)?.as_str().to_string())
+}
+
+/// Latest branch of the drop site, i.e the development branch.
+fn latest_branch() -> anyhow::Result<String> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(r#"href="(\d+)\.(\d+)/""#).unwrap();
+    }
+    let listing = get(&format!("{SMDROP_URL}/"))?;
+    let listing = String::from_utf8_lossy(&listing);
+
+    RE.captures_iter(&listing)
+        .filter_map(|capture| {
+            Some((
+                capture.get(1)?.as_str().parse::<u32>().ok()?,
+                capture.get(2)?.as_str().parse::<u32>().ok()?,
+            ))
+        })
+        .max()
+        .map(|(major, minor)| format!("{major}.{minor}"))
+        .context("no branch found on the drop site")
+}
+
+fn get(url: &str) -> anyhow::Result<Vec<u8>> {
+    let response = minreq::get(url)
+        .send()
+        .with_context(|| format!("failed to fetch {url}"))?;
+    if response.status_code != 200 {
+        bail!("failed to fetch {url}: {}", response.status_code);
+    }
+
+    Ok(response.into_bytes())
+}
+
+fn extract_includes(archive: &[u8], dest: &AbsPathBuf) -> anyhow::Result<()> {
+    let mut archive = ZipArchive::new(Cursor::new(archive))?;
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i)?;
+        let Some(path) = file.enclosed_name().map(|path| path.to_path_buf()) else {
+            continue;
+        };
+        let Ok(path) = path.strip_prefix(INCLUDE_DIR) else {
+            continue;
+        };
+        let dest_path = dest.join(path);
+        if file.is_dir() {
+            fs::create_dir_all(&dest_path)?;
+            continue;
+        }
+        if let Some(parent) = dest_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        io::copy(&mut file, &mut fs::File::create(&dest_path)?)?;
+    }
+    if !dest.is_dir() {
+        bail!("no includes found in the archive");
+    }
+
+    Ok(())
+}
+
+/// Cache directory of the version, e.g `~/.cache/sourcepawn-studio/sourcemod/1.12`.
+fn version_dir(version: &str) -> Option<AbsPathBuf> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(r"^(dev|\d+\.\d+|\d+\.\d+\.\d+-git\d+)$").unwrap();
+    }
+    if !RE.is_match(version) {
+        return None;
+    }
+    let cache_dir = env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("LOCALAPPDATA").map(PathBuf::from))
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))?;
+    let cache_dir = AbsPathBuf::try_from(cache_dir).ok()?;
+
+    Some(
+        cache_dir
+            .join("sourcepawn-studio")
+            .join("sourcemod")
+            .join(version),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_sourcemod_build() {
+        assert_eq!(parse_build("1.12.0-git7110"), Some("1.12".to_string()));
+        assert_eq!(parse_build("1.12"), None);
+        assert_eq!(parse_build("dev"), None);
+    }
+}
@@ -0,0 +1,351 @@
+//! Transports the language server can be served over.
+
+use std::{
+    io::{BufReader, Read, Write},
+    net::{Ipv4Addr, TcpListener, TcpStream},
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
+
+use crossbeam::channel::bounded;
+use lsp_server::{Connection, ErrorCode, Message, Response};
+use lsp_types::notification::Notification;
+
+use crate::{GlobalState, WarmProjects};
+
+/// Transport used to communicate with the clients.
+#[derive(Debug, Clone)]
+pub enum Transport {
+    /// A single client, over stdin and stdout.
+    Stdio,
+    /// Any number of clients, over TCP on the loopback interface. The other users of the machine
+    /// can connect to it, so the clients must pass the token in the `initializationOptions` of
+    /// their `initialize` request.
+    Tcp { port: u16, token: String },
+    /// Any number of clients, over a unix domain socket which only the user can connect to.
+    #[cfg(unix)]
+    Socket(std::path::PathBuf),
+}
+
+impl Transport {
+    /// Serve the language server over the transport.
+    ///
+    /// Sockets keep listening after a client disconnects, which lets the server run as a
+    /// long-lived daemon. Each client is served in its own session, with its own analysis, which
+    /// starts from a copy of the files of `warm_projects` when the client opens one of them.
+    pub fn serve(self, amxxpawn_mode: bool, warm_projects: WarmProjects) -> anyhow::Result<()> {
+        match self {
+            Transport::Stdio => {
+                let (connection, threads) = Connection::stdio();
+                GlobalState::new(connection, amxxpawn_mode)
+                    .with_warm_projects(warm_projects)
+                    .run()?;
+                threads.join()?;
+            }
+            Transport::Tcp { port, token } => {
+                let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+                log::info!("Listening on {}", listener.local_addr()?);
+                for stream in listener.incoming() {
+                    let stream = stream?;
+                    spawn_session(
+                        stream.try_clone()?,
+                        stream,
+                        Some(token.clone()),
+                        amxxpawn_mode,
+                        warm_projects.clone(),
+                    );
+                }
+            }
+            #[cfg(unix)]
+            Transport::Socket(path) => {
+                use std::os::unix::fs::FileTypeExt;
+
+                // The socket of a previous daemon is not removed when it stops.
+                if std::fs::metadata(&path).is_ok_and(|metadata| metadata.file_type().is_socket()) {
+                    std::fs::remove_file(&path)?;
+                }
+                let listener = bind_socket(&path)?;
+                log::info!("Listening on {}", path.display());
+                for stream in listener.incoming() {
+                    let stream = stream?;
+                    spawn_session(
+                        stream.try_clone()?,
+                        stream,
+                        None,
+                        amxxpawn_mode,
+                        warm_projects.clone(),
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Bind a unix domain socket at `path`, which only the user can connect to.
+///
+/// The socket is bound inside a directory only the user can enter, and moved to `path` once its
+/// permissions are restricted, so that no other user can connect to it in the meantime.
+#[cfg(unix)]
+fn bind_socket(path: &std::path::Path) -> std::io::Result<std::os::unix::net::UnixListener> {
+    use std::os::unix::{fs::PermissionsExt, net::UnixListener};
+
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => std::path::Path::new("."),
+    };
+    // Temporary directories are created with the 0700 mode.
+    let dir = tempfile::Builder::new()
+        .prefix(".sourcepawn-studio")
+        .tempdir_in(parent)?;
+    let bound = dir.path().join("socket");
+    let listener = UnixListener::bind(&bound)?;
+    std::fs::set_permissions(&bound, std::fs::Permissions::from_mode(0o600))?;
+    std::fs::rename(&bound, path)?;
+
+    Ok(listener)
+}
+
+/// Serve a client in its own thread, until it exits or disconnects. When there is a `token`, the
+/// client is disconnected if its first request does not pass it.
+///
+/// A command of the CLI, e.g `check --daemon`, is answered without starting a session.
+fn spawn_session<R, W>(
+    reader: R,
+    mut writer: W,
+    token: Option<String>,
+    amxxpawn_mode: bool,
+    warm_projects: WarmProjects,
+) where
+    R: Read + Send + 'static,
+    W: Write + Send + 'static,
+{
+    static SESSIONS: AtomicUsize = AtomicUsize::new(0);
+    let id = SESSIONS.fetch_add(1, Ordering::Relaxed);
+    log::info!("Starting session {}", id);
+    let res = thread::Builder::new()
+        .name(format!("Session{}", id))
+        .spawn(move || {
+            let mut reader = BufReader::new(reader);
+            let Ok(Some(first)) = Message::read(&mut reader) else {
+                return;
+            };
+            if token.is_some_and(|token| !is_authenticated(&first, &token)) {
+                log::warn!("Session {} was not authenticated", id);
+                if let Message::Request(request) = first {
+                    let response = Response::new_err(
+                        request.id,
+                        ErrorCode::InvalidRequest as i32,
+                        "invalid token".to_string(),
+                    );
+                    let _ = Message::Response(response).write(&mut writer);
+                }
+                return;
+            }
+            if let Message::Request(request) = &first {
+                if let Some(response) = warm_projects.handle_command(request.clone()) {
+                    log::info!("Session {} ran the command {}", id, request.method);
+                    let _ = Message::Response(response).write(&mut writer);
+                    return;
+                }
+            }
+            let connection = stream_connection(reader, writer, first);
+            let state =
+                GlobalState::new(connection, amxxpawn_mode).with_warm_projects(warm_projects);
+            match state.run() {
+                Ok(()) => log::info!("Session {} ended", id),
+                Err(err) => log::error!("Session {} failed: {}", id, err),
+            }
+        });
+    if let Err(err) = res {
+        log::error!("Failed to spawn session {}: {}", id, err);
+    }
+}
+
+/// Create a connection reading and writing LSP messages from a stream, whose `first` message
+/// was already read.
+///
+/// Unlike [`Connection::stdio`], the IO threads stop silently when the client goes away, so that
+/// a disconnecting client does not bring the other sessions down.
+fn stream_connection<R, W>(mut reader: BufReader<R>, mut writer: W, first: Message) -> Connection
+where
+    R: Read + Send + 'static,
+    W: Write + Send + 'static,
+{
+    let (reader_sender, receiver) = bounded::<Message>(0);
+    thread::spawn(move || {
+        let mut next = Some(first);
+        while let Some(msg) = next.take().or_else(|| Message::read(&mut reader).ok()?) {
+            let is_exit = matches!(
+                &msg,
+                Message::Notification(notification)
+                if notification.method == lsp_types::notification::Exit::METHOD
+            );
+            if reader_sender.send(msg).is_err() || is_exit {
+                break;
+            }
+        }
+    });
+    let (sender, writer_receiver) = bounded::<Message>(0);
+    thread::spawn(move || {
+        // Keep draining the messages once the client is gone, as the session would panic when
+        // failing to send them.
+        let mut connected = true;
+        for msg in writer_receiver {
+            if !connected {
+                continue;
+            }
+            if let Err(err) = msg.write(&mut writer) {
+                log::warn!("Failed to write to the client: {}", err);
+                connected = false;
+            }
+        }
+    });
+
+    Connection { sender, receiver }
+}
+
+/// Whether the first message of a client passes the token in the `initializationOptions` of its
+/// `initialize` request, or in the parameters of a command of the CLI.
+fn is_authenticated(message: &Message, token: &str) -> bool {
+    let Message::Request(request) = message else {
+        return false;
+    };
+    let Some(given) = ["/initializationOptions/token", "/token"]
+        .iter()
+        .find_map(|pointer| request.params.pointer(pointer)?.as_str())
+    else {
+        return false;
+    };
+
+    // Compare all the bytes, not to reveal the length of the matching prefix by the time taken.
+    given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+/// Send a command of the CLI to the daemon listening on `address`, a TCP port or the path of a
+/// unix domain socket, and return its result. The `token` is required over TCP.
+pub fn send_command<R>(
+    address: &str,
+    token: Option<&str>,
+    params: R::Params,
+) -> anyhow::Result<R::Result>
+where
+    R: lsp_types::request::Request,
+{
+    let mut params = serde_json::to_value(params)?;
+    if let Some(token) = token {
+        params["token"] = token.into();
+    }
+    let request = lsp_server::Request::new(0.into(), R::METHOD.to_string(), params);
+    let response = match address.parse::<u16>() {
+        Ok(port) => exchange(TcpStream::connect((Ipv4Addr::LOCALHOST, port))?, request)?,
+        #[cfg(unix)]
+        Err(_) => exchange(std::os::unix::net::UnixStream::connect(address)?, request)?,
+        #[cfg(not(unix))]
+        Err(_) => anyhow::bail!("invalid port: {address}"),
+    };
+    if let Some(err) = response.error {
+        anyhow::bail!(err.message);
+    }
+
+    Ok(serde_json::from_value(response.result.unwrap_or_default())?)
+}
+
+fn exchange<S: Read + Write>(
+    mut stream: S,
+    request: lsp_server::Request,
+) -> anyhow::Result<Response> {
+    Message::Request(request).write(&mut stream)?;
+    match Message::read(&mut BufReader::new(stream))? {
+        Some(Message::Response(response)) => Ok(response),
+        _ => anyhow::bail!("the daemon closed the connection"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_server::Request;
+    use paths::AbsPathBuf;
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn authenticate_clients() {
+        let request =
+            |params| Message::Request(Request::new(0.into(), "initialize".into(), params));
+
+        assert!(is_authenticated(
+            &request(json!({ "initializationOptions": { "token": "secret" } })),
+            "secret"
+        ));
+        assert!(is_authenticated(
+            &request(json!({ "token": "secret" })),
+            "secret"
+        ));
+        assert!(!is_authenticated(
+            &request(json!({ "initializationOptions": { "token": "secreT" } })),
+            "secret"
+        ));
+        assert!(!is_authenticated(
+            &request(json!({ "token": "secre" })),
+            "secret"
+        ));
+        assert!(!is_authenticated(&request(json!({})), "secret"));
+        assert!(!is_authenticated(
+            &Message::Notification(lsp_server::Notification::new(
+                "initialized".into(),
+                json!({ "token": "secret" })
+            )),
+            "secret"
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn bind_private_sockets() {
+        use std::os::unix::{fs::PermissionsExt, net::UnixStream};
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("daemon.sock");
+        let _listener = bind_socket(&path).unwrap();
+
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert!(UnixStream::connect(&path).is_ok());
+        // The directory the socket was bound in is removed.
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn handle_commands() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = AbsPathBuf::try_from(dir.path().to_path_buf()).unwrap();
+        std::fs::write(root.join("plugin.sp"), "public void OnPluginStart(){}\n").unwrap();
+        let warm_projects = WarmProjects::default();
+
+        let response = warm_projects
+            .handle_command(Request::new(
+                0.into(),
+                "sourcepawn-studio/format".into(),
+                json!({ "root": root.to_string(), "paths": [root.to_string()], "check": true }),
+            ))
+            .unwrap();
+        assert_eq!(
+            response.result,
+            Some(json!({
+                "output": format!("{}\n", root.join("plugin.sp").display()),
+                "failed": true,
+            }))
+        );
+        assert!(warm_projects
+            .handle_command(Request::new(0.into(), "initialize".into(), json!({})))
+            .is_none());
+    }
+}
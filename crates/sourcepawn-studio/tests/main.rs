@@ -1 +1,2 @@
 mod text_document;
+mod workspace;
@@ -0,0 +1,91 @@
+use sourcepawn_studio::fixture::{apply_code_action, code_actions};
+
+#[test]
+fn add_include_after_includes() {
+    assert_eq!(
+        apply_code_action(
+            r#"
+%! main.sp
+#include <sourcemod>
+
+public void OnPluginStart()
+{
+    CS_RespawnPlayer(1);
+    ^^^^^^^^^^^^^^^^
+    |
+}
+"#,
+            "Add `#include <cstrike>`",
+        )
+        .unwrap(),
+        r#"#include <sourcemod>
+#include <cstrike>
+
+public void OnPluginStart()
+{
+    CS_RespawnPlayer(1);
+}"#
+    );
+}
+
+#[test]
+fn add_include_at_top() {
+    assert_eq!(
+        apply_code_action(
+            r#"
+%! main.sp
+public void OnPluginStart()
+{
+    TF2_RespawnPlayer(1);
+    ^^^^^^^^^^^^^^^^^
+    |
+}
+"#,
+            "Add `#include <tf2>`",
+        )
+        .unwrap(),
+        r#"#include <tf2>
+public void OnPluginStart()
+{
+    TF2_RespawnPlayer(1);
+}"#
+    );
+}
+
+#[test]
+fn add_include_of_enum_variant() {
+    assert!(code_actions(
+        r#"
+%! main.sp
+#include <sourcemod>
+
+public void OnPluginStart()
+{
+    int class = TFClass_Scout;
+                ^^^^^^^^^^^^^
+                |
+}
+"#,
+    )
+    .iter()
+    .any(|(title, _)| title == "Add `#include <tf2>`"));
+}
+
+#[test]
+fn no_include_for_defined_symbol() {
+    assert!(!code_actions(
+        r#"
+%! main.sp
+#include <cstrike>
+
+public void OnPluginStart()
+{
+    CS_RespawnPlayer(1);
+    ^^^^^^^^^^^^^^^^
+    |
+}
+"#,
+    )
+    .iter()
+    .any(|(title, _)| title.starts_with("Add `#include")));
+}
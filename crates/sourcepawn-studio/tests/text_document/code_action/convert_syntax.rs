@@ -0,0 +1,296 @@
+use sourcepawn_studio::fixture::{apply_code_action, code_actions};
+
+const CONVERT: &str = "Convert to the new syntax";
+const CONVERT_FILE: &str = "Convert the file to the new syntax";
+
+#[test]
+fn convert_declaration() {
+    assert_eq!(
+        apply_code_action(
+            r#"
+%! main.sp
+void Foo()
+{
+    decl String:name[64], String:other[64];
+       |
+    new Float:x;
+}
+"#,
+            CONVERT,
+        )
+        .unwrap(),
+        r#"void Foo()
+{
+    char name[64], other[64];
+    new Float:x;
+}"#
+    );
+}
+
+#[test]
+fn convert_variables_of_different_types() {
+    assert_eq!(
+        apply_code_action(
+            r#"
+%! main.sp
+void Foo()
+{
+    new Handle:h, Float:x = 1.0, y;
+          |
+}
+"#,
+            CONVERT,
+        )
+        .unwrap(),
+        r#"void Foo()
+{
+    Handle h;
+    float x = 1.0;
+    int y;
+}"#
+    );
+}
+
+#[test]
+fn convert_function() {
+    assert_eq!(
+        apply_code_action(
+            r#"
+%! main.sp
+stock Float:Foo(&Float:x, const String:name[], bool:enabled, any:...)
+       |
+{
+    return x;
+}
+"#,
+            CONVERT,
+        )
+        .unwrap(),
+        r#"stock float Foo(float &x, const char name[], bool enabled, any...)
+{
+    return x;
+}"#
+    );
+}
+
+#[test]
+fn convert_function_without_return_type() {
+    assert_eq!(
+        apply_code_action(
+            r#"
+%! main.sp
+stock Foo(x)
+       |
+{
+    return x;
+}
+
+stock Bar()
+{
+}
+"#,
+            CONVERT_FILE,
+        )
+        .unwrap(),
+        r#"#pragma newdecls required
+
+stock int Foo(int x)
+{
+    return x;
+}
+
+stock void Bar()
+{
+}"#
+    );
+}
+
+#[test]
+fn convert_for_loop() {
+    assert_eq!(
+        apply_code_action(
+            r#"
+%! main.sp
+void Foo()
+{
+    for (new i = 0, j = 1; i < j; i++) {}
+          |
+}
+"#,
+            CONVERT,
+        )
+        .unwrap(),
+        r#"void Foo()
+{
+    for (int i = 0, j = 1; i < j; i++) {}
+}"#
+    );
+}
+
+#[test]
+fn convert_for_loop_of_different_types() {
+    let actions = code_actions(
+        r#"
+%! main.sp
+void Foo()
+{
+    for (new i = 0, Float:x = 0.0; i < 3; i++) {}
+          |
+}
+"#,
+    );
+    assert!(actions.iter().all(|(title, _)| title != CONVERT));
+}
+
+#[test]
+fn convert_file_and_require_new_declarations() {
+    assert_eq!(
+        apply_code_action(
+            r#"
+%! main.sp
+#include "foo.inc"
+
+new g_count, Float:g_ratio;
+
+public OnClientPutInServer(client)
+          |
+{
+    decl String:name[64];
+    for (new i = 0; i < 3; i++) {}
+    CreateTimer2(1.0, Timer_Callback);
+}
+
+public Timer_Callback(Handle:timer)
+{
+    return 0;
+}
+
+%! foo.inc
+typedef TimerCallback = function Action (Handle timer);
+native void CreateTimer2(float interval, TimerCallback callback);
+forward void OnClientPutInServer(int client);
+"#,
+            CONVERT_FILE,
+        )
+        .unwrap(),
+        r#"#include "foo.inc"
+
+#pragma newdecls required
+
+int g_count;
+float g_ratio;
+
+public void OnClientPutInServer(int client)
+{
+    char name[64];
+    for (int i = 0; i < 3; i++) {}
+    CreateTimer2(1.0, Timer_Callback);
+}
+
+public Action Timer_Callback(Handle timer)
+{
+    return 0;
+}"#
+    );
+}
+
+#[test]
+fn convert_file_with_leftovers() {
+    // The declarations of a loop with different types are left, the pragma would reject them.
+    assert_eq!(
+        apply_code_action(
+            r#"
+%! main.sp
+new g_count;
+
+public OnPluginStart()
+          |
+{
+    for (new i = 0, Float:x = 0.0; i < 3; i++) {}
+}
+"#,
+            CONVERT_FILE,
+        )
+        .unwrap(),
+        r#"int g_count;
+
+public void OnPluginStart()
+{
+    for (new i = 0, Float:x = 0.0; i < 3; i++) {}
+}"#
+    );
+}
+
+#[test]
+fn keep_newdecls_pragma() {
+    assert_eq!(
+        apply_code_action(
+            r#"
+%! main.sp
+#pragma newdecls optional
+
+new g_count;
+
+stock Foo()
+       |
+{
+}
+"#,
+            CONVERT_FILE,
+        )
+        .unwrap(),
+        r#"#pragma newdecls optional
+
+int g_count;
+
+stock void Foo()
+{
+}"#
+    );
+}
+
+#[test]
+fn keep_require_newdecls_directive() {
+    assert_eq!(
+        apply_code_action(
+            r#"
+%! main.sp
+#require_newdecls
+
+new g_count;
+     |
+"#,
+            CONVERT_FILE,
+        )
+        .unwrap(),
+        r#"#require_newdecls
+
+int g_count;"#
+    );
+}
+
+#[test]
+fn require_newdecls_despite_inactive_pragma() {
+    assert_eq!(
+        apply_code_action(
+            r#"
+%! main.sp
+#if 0
+#pragma newdecls optional
+#endif
+
+new g_count;
+     |
+"#,
+            CONVERT_FILE,
+        )
+        .unwrap(),
+        r#"#pragma newdecls required
+
+#if 0
+#pragma newdecls optional
+#endif
+
+int g_count;"#
+    );
+}
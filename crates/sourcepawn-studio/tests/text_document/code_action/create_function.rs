@@ -0,0 +1,135 @@
+use sourcepawn_studio::fixture::{apply_code_action, code_actions};
+
+fn is_offered(fixture: &str, title: &str) -> bool {
+    code_actions(fixture)
+        .iter()
+        .any(|(action, _)| action == title)
+}
+
+#[test]
+fn create_function_below_caller() {
+    assert_eq!(
+        apply_code_action(
+            r#"
+%! main.sp
+void Foo(int client)
+{
+    GiveCredits(client, 10);
+    ^^^^^^^^^^^
+    |
+}
+"#,
+            "Create function `GiveCredits`",
+        )
+        .unwrap(),
+        r#"void Foo(int client)
+{
+    GiveCredits(client, 10);
+}
+
+void GiveCredits(int client, int value)
+{
+}"#
+    );
+}
+
+#[test]
+fn return_type_of_declaration() {
+    assert_eq!(
+        apply_code_action(
+            r#"
+%! main.sp
+void Foo(int client)
+{
+    int credits = GetCredits(client);
+                  ^^^^^^^^^^
+                  |
+}
+"#,
+            "Create function `GetCredits`",
+        )
+        .unwrap(),
+        r#"void Foo(int client)
+{
+    int credits = GetCredits(client);
+}
+
+int GetCredits(int client)
+{
+    return 0;
+}"#
+    );
+}
+
+#[test]
+fn return_type_of_condition() {
+    assert_eq!(
+        apply_code_action(
+            r#"
+%! main.sp
+void Foo(int client)
+{
+    if (IsVip(client))
+        ^^^^^
+        |
+    {
+    }
+}
+"#,
+            "Create function `IsVip`",
+        )
+        .unwrap(),
+        r#"void Foo(int client)
+{
+    if (IsVip(client))
+    {
+    }
+}
+
+bool IsVip(int client)
+{
+    return false;
+}"#
+    );
+}
+
+#[test]
+fn create_function_in_include() {
+    assert!(is_offered(
+        r#"
+%! main.sp
+#include "shop.sp"
+
+void Foo(int client)
+{
+    GiveCredits(client, 10);
+    ^^^^^^^^^^^
+    |
+}
+
+%! shop.sp
+int g_Credits[65];
+"#,
+        "Create function `GiveCredits` in `shop.sp`",
+    ));
+}
+
+#[test]
+fn no_create_defined_function() {
+    assert!(!is_offered(
+        r#"
+%! main.sp
+void GiveCredits(int client, int value)
+{
+}
+
+void Foo(int client)
+{
+    GiveCredits(client, 10);
+    ^^^^^^^^^^^
+    |
+}
+"#,
+        "Create function `GiveCredits`",
+    ));
+}
@@ -0,0 +1,149 @@
+use sourcepawn_studio::fixture::{apply_code_action, code_actions};
+
+const EXTRACT: &str = "Extract into variable";
+
+fn is_offered(fixture: &str) -> bool {
+    code_actions(fixture)
+        .iter()
+        .any(|(title, _)| title == EXTRACT)
+}
+
+#[test]
+fn extract_call() {
+    assert_eq!(
+        apply_code_action(
+            r#"
+%! main.sp
+int GetHealth(int client)
+{
+    return client;
+}
+
+void Foo(int client)
+{
+    PrintToServer("%d", GetHealth(client) * 2);
+                        ^^^^^^^^^^^^^^^^^
+                        |
+}
+"#,
+            EXTRACT,
+        )
+        .unwrap(),
+        r#"int GetHealth(int client)
+{
+    return client;
+}
+
+void Foo(int client)
+{
+    int health = GetHealth(client);
+    PrintToServer("%d", health * 2);
+}"#
+    );
+}
+
+#[test]
+fn extract_left_operand_of_logical_operator() {
+    assert_eq!(
+        apply_code_action(
+            r#"
+%! main.sp
+bool IsValid(int client)
+{
+    return client > 0;
+}
+
+void Foo(int client)
+{
+    if (IsValid(client) && client < 64)
+        ^^^^^^^^^^^^^^^
+        |
+    {
+    }
+}
+"#,
+            EXTRACT,
+        )
+        .unwrap(),
+        r#"bool IsValid(int client)
+{
+    return client > 0;
+}
+
+void Foo(int client)
+{
+    bool isValid = IsValid(client);
+    if (isValid && client < 64)
+    {
+    }
+}"#
+    );
+}
+
+#[test]
+fn no_extract_right_operand_of_logical_operator() {
+    assert!(!is_offered(
+        r#"
+%! main.sp
+bool IsValid(int client)
+{
+    return client > 0;
+}
+
+void Foo(int client)
+{
+    if (client > 0 && IsValid(client))
+                      ^^^^^^^^^^^^^^^
+                      |
+    {
+    }
+    if (client == 0 || IsValid(client))
+                       ^^^^^^^^^^^^^^^
+    {
+    }
+}
+"#,
+    ));
+}
+
+#[test]
+fn no_extract_branch_of_ternary() {
+    assert!(!is_offered(
+        r#"
+%! main.sp
+int GetHealth(int client)
+{
+    return client;
+}
+
+void Foo(int client)
+{
+    int health = client > 0 ? GetHealth(client) : 0;
+                              ^^^^^^^^^^^^^^^^^
+                              |
+}
+"#,
+    ));
+}
+
+#[test]
+fn no_extract_condition_of_loop() {
+    assert!(!is_offered(
+        r#"
+%! main.sp
+int GetHealth(int client)
+{
+    return client;
+}
+
+void Foo(int client)
+{
+    while (GetHealth(client) > 0)
+           ^^^^^^^^^^^^^^^^^
+           |
+    {
+    }
+}
+"#,
+    ));
+}
@@ -0,0 +1,176 @@
+use sourcepawn_studio::fixture::{apply_code_action, code_actions};
+
+fn is_offered(fixture: &str, title: &str) -> bool {
+    code_actions(fixture)
+        .iter()
+        .any(|(action, _)| action == title)
+}
+
+#[test]
+fn property_from_methods() {
+    assert_eq!(
+        apply_code_action(
+            r#"
+%! main.sp
+methodmap Player
+{
+    public int GetHealth()
+               ^
+               |
+    {
+        return 0;
+    }
+    public void SetHealth(int value)
+    {
+    }
+}
+"#,
+            "Generate property `Health`",
+        )
+        .unwrap(),
+        r#"methodmap Player
+{
+    public int GetHealth()
+    {
+        return 0;
+    }
+    public void SetHealth(int value)
+    {
+    }
+    property int Health
+    {
+        public get()
+        {
+            return this.GetHealth();
+        }
+        public set(int value)
+        {
+            this.SetHealth(value);
+        }
+    }
+}"#
+    );
+}
+
+#[test]
+fn read_only_property_from_getter() {
+    assert_eq!(
+        apply_code_action(
+            r#"
+%! main.sp
+methodmap Player
+{
+    public int GetHealth()
+               ^
+               |
+    {
+        return 0;
+    }
+}
+"#,
+            "Generate property `Health`",
+        )
+        .unwrap(),
+        r#"methodmap Player
+{
+    public int GetHealth()
+    {
+        return 0;
+    }
+    property int Health
+    {
+        public get()
+        {
+            return this.GetHealth();
+        }
+    }
+}"#
+    );
+}
+
+#[test]
+fn native_property() {
+    assert_eq!(
+        apply_code_action(
+            r#"
+%! main.sp
+methodmap Player
+          ^
+          |
+{
+    property int Property
+    {
+        public native get();
+    }
+}
+"#,
+            "Generate native property",
+        )
+        .unwrap(),
+        r#"methodmap Player
+{
+    property int Property
+    {
+        public native get();
+    }
+    property int Property2
+    {
+        public native get();
+        public native set(int value);
+    }
+}"#
+    );
+}
+
+#[test]
+fn string_map_property() {
+    assert_eq!(
+        apply_code_action(
+            r#"
+%! main.sp
+methodmap Config < StringMap
+          ^
+          |
+{
+}
+"#,
+            "Generate property backed by a value of the map",
+        )
+        .unwrap(),
+        r#"methodmap Config < StringMap
+{
+    property int Property
+    {
+        public get()
+        {
+            int value;
+            this.GetValue("Property", value);
+            return value;
+        }
+        public set(int value)
+        {
+            this.SetValue("Property", value);
+        }
+    }
+}"#
+    );
+}
+
+#[test]
+fn no_property_in_method_body() {
+    assert!(!is_offered(
+        r#"
+%! main.sp
+methodmap Player
+{
+    public int GetHealth()
+    {
+        return 0;
+        ^^^^^^
+        |
+    }
+}
+"#,
+        "Generate native property",
+    ));
+}
@@ -0,0 +1,109 @@
+use sourcepawn_studio::fixture::{apply_code_action, code_actions};
+
+const FORWARDS: &str = r#"
+%! events.inc
+forward void OnEventA();
+forward Action OnEventB(int client, int &buttons);
+forward bool OnEventC(int client);
+"#;
+
+#[test]
+fn implement_missing_forwards() {
+    assert_eq!(
+        apply_code_action(
+            &format!(
+                r#"
+%! main.sp
+#include "events.inc"
+          ^
+          |
+
+public void OnEventA()
+{{
+}}
+{FORWARDS}"#
+            ),
+            "Implement the 2 missing forwards of `events`",
+        )
+        .unwrap(),
+        r#"#include "events.inc"
+
+public void OnEventA()
+{
+}
+
+public Action OnEventB(int client, int &buttons)
+{
+    return Plugin_Continue;
+}
+
+public bool OnEventC(int client)
+{
+    return true;
+}
+"#
+    );
+}
+
+#[test]
+fn implement_one_forward() {
+    assert_eq!(
+        apply_code_action(
+            &format!(
+                r#"
+%! main.sp
+#include "events.inc"
+          ^
+          |
+
+public void OnEventA()
+{{
+}}
+{FORWARDS}"#
+            ),
+            "Implement forward `OnEventC`",
+        )
+        .unwrap(),
+        r#"#include "events.inc"
+
+public void OnEventA()
+{
+}
+
+public bool OnEventC(int client)
+{
+    return true;
+}
+"#
+    );
+}
+
+#[test]
+fn no_implemented_forwards() {
+    let titles = code_actions(&format!(
+        r#"
+%! main.sp
+#include "events.inc"
+          ^
+          |
+
+public void OnEventA()
+{{
+}}
+
+public Action OnEventB(int client, int &buttons)
+{{
+    return Plugin_Continue;
+}}
+
+public bool OnEventC(int client)
+{{
+    return true;
+}}
+{FORWARDS}"#
+    ))
+    .into_iter()
+    .map(|(title, _)| title)
+    .collect::<Vec<_>>();
+    assert!(!titles.iter().any(|title| title.starts_with("Implement")));
+}
@@ -0,0 +1,87 @@
+use sourcepawn_studio::fixture::{apply_code_action, code_actions};
+
+#[test]
+fn introduce_macro() {
+    assert_eq!(
+        apply_code_action(
+            r#"
+%! main.sp
+int Foo()
+{
+    return 100;
+           |
+}
+
+int Bar()
+{
+    return 100;
+}
+"#,
+            "Introduce macro for `100`",
+        )
+        .unwrap(),
+        r#"#define CONSTANT 100
+
+int Foo()
+{
+    return CONSTANT;
+}
+
+int Bar()
+{
+    return CONSTANT;
+}"#
+    );
+}
+
+#[test]
+fn introduce_static_const_string() {
+    assert_eq!(
+        apply_code_action(
+            r#"
+%! main.sp
+// Prints the prefix.
+void Foo()
+{
+    PrintToServer("max health");
+                   |
+}
+
+void Bar()
+{
+    PrintToServer("max health");
+}
+"#,
+            "Introduce static constant for `\"max health\"`",
+        )
+        .unwrap(),
+        r#"static const char MAX_HEALTH[] = "max health";
+
+// Prints the prefix.
+void Foo()
+{
+    PrintToServer(MAX_HEALTH);
+}
+
+void Bar()
+{
+    PrintToServer(MAX_HEALTH);
+}"#
+    );
+}
+
+#[test]
+fn no_introduce_constant_for_single_literal() {
+    assert!(!code_actions(
+        r#"
+%! main.sp
+int Foo()
+{
+    return 100;
+           |
+}
+"#,
+    )
+    .iter()
+    .any(|(title, _)| title.starts_with("Introduce")));
+}
@@ -0,0 +1,9 @@
+mod auto_import;
+mod convert_syntax;
+mod create_function;
+mod extract_variable;
+mod generate_property;
+mod implement_forwards;
+mod introduce_constant;
+mod organize_includes;
+mod surround_with;
@@ -0,0 +1,84 @@
+use sourcepawn_studio::fixture::{apply_code_action, code_actions};
+
+const ORGANIZE: &str = "Organize includes";
+
+#[test]
+fn sort_group_and_dedup() {
+    assert_eq!(
+        apply_code_action(
+            r#"
+%! main.sp
+#include "plugin/utils.sp"
+#include <multicolors>
+#include <sdktools>
+#include <sourcemod>
+|
+#include <sdktools>
+
+public void OnPluginStart()
+{
+}
+"#,
+            ORGANIZE,
+        )
+        .unwrap(),
+        r#"#include <sourcemod>
+#include <sdktools>
+
+#include <multicolors>
+
+#include "plugin/utils.sp"
+
+public void OnPluginStart()
+{
+}"#
+    );
+}
+
+#[test]
+fn keep_directives_between_includes() {
+    assert_eq!(
+        apply_code_action(
+            r#"
+%! main.sp
+#include <sdktools>
+#include <sourcemod>
+|
+#pragma newdecls required
+#include <cstrike>
+#include <clientprefs>
+#if defined FOO
+#include <sdktools_sound>
+#include <adt>
+#endif
+"#,
+            ORGANIZE,
+        )
+        .unwrap(),
+        r#"#include <sourcemod>
+#include <sdktools>
+#pragma newdecls required
+#include <clientprefs>
+#include <cstrike>
+#if defined FOO
+#include <sdktools_sound>
+#include <adt>
+#endif"#
+    );
+}
+
+#[test]
+fn no_organize_organized_includes() {
+    assert!(!code_actions(
+        r#"
+%! main.sp
+#include <sourcemod>
+|
+#include <sdktools>
+
+#include "plugin/utils.sp"
+"#,
+    )
+    .iter()
+    .any(|(title, _)| title == ORGANIZE));
+}
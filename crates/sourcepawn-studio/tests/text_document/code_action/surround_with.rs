@@ -0,0 +1,119 @@
+use sourcepawn_studio::fixture::{apply_code_action, code_actions};
+
+fn surround_titles(fixture: &str) -> Vec<String> {
+    code_actions(fixture)
+        .into_iter()
+        .map(|(title, _)| title)
+        .filter(|title| title.starts_with("Surround with"))
+        .collect()
+}
+
+const STATEMENT: &str = r#"
+%! main.sp
+void Foo()
+{
+    PrintToServer("foo");
+    ^^^^^^^^^^^^^^^^^^^^^
+    |
+}
+"#;
+
+#[test]
+fn surround_with_if() {
+    assert_eq!(
+        apply_code_action(STATEMENT, "Surround with `if`").unwrap(),
+        r#"void Foo()
+{
+    if (condition)
+    {
+        PrintToServer("foo");
+    }
+}"#
+    );
+}
+
+#[test]
+fn surround_with_for() {
+    assert_eq!(
+        apply_code_action(STATEMENT, "Surround with `for`").unwrap(),
+        r#"void Foo()
+{
+    for (int i = 0; i < count; i++)
+    {
+        PrintToServer("foo");
+    }
+}"#
+    );
+}
+
+#[test]
+fn surround_with_do_while() {
+    assert_eq!(
+        apply_code_action(STATEMENT, "Surround with `do { } while (false);`").unwrap(),
+        r#"void Foo()
+{
+    do
+    {
+        PrintToServer("foo");
+    } while (false);
+}"#
+    );
+}
+
+#[test]
+fn surround_statements_with_if_defined() {
+    assert_eq!(
+        apply_code_action(
+            r#"
+%! main.sp
+void Foo()
+{
+    PrintToServer("foo"); PrintToServer("bar");
+    ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^
+    |
+}
+"#,
+            "Surround with `#if defined DEBUG`"
+        )
+        .unwrap(),
+        r#"void Foo()
+{
+    #if defined DEBUG
+    PrintToServer("foo"); PrintToServer("bar");
+    #endif
+}"#
+    );
+}
+
+#[test]
+fn only_if_defined_outside_of_functions() {
+    assert_eq!(
+        surround_titles(
+            r#"
+%! main.sp
+int g_Debug;
+^^^^^^^^^^^^
+|
+"#
+        ),
+        vec!["Surround with `#if defined DEBUG`".to_string()]
+    );
+}
+
+#[test]
+fn no_surround_part_of_statement() {
+    assert_eq!(
+        surround_titles(
+            r#"
+%! main.sp
+void Foo()
+{
+    PrintToServer("foo");
+    ^^^^^^^^^^^^^
+    |
+}
+"#
+        ),
+        Vec::<String>::new()
+    );
+}
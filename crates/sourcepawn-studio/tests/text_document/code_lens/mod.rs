@@ -0,0 +1,74 @@
+use sourcepawn_studio::fixture::code_lenses;
+
+const COMPILE: &str = "▶\u{fe0e} Compile";
+
+#[test]
+fn references_and_compile() {
+    assert_eq!(
+        code_lenses(
+            r#"
+%! main.sp
+#include "foo.sp"
+
+methodmap Foo {}
+
+public void OnPluginStart()
+{
+    Bar();
+    Bar();
+    Baz();
+}
+
+void Bar() {}
+
+%! foo.sp
+void Baz() {}
+"#,
+        ),
+        vec![
+            (2, "0 references".to_string()),
+            (4, "0 references".to_string()),
+            (4, COMPILE.to_string()),
+            (11, "2 references".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn references_from_another_file() {
+    assert_eq!(
+        code_lenses(
+            r#"
+%! foo.sp
+void Baz() {}
+
+%! main.sp
+#include "foo.sp"
+
+public void OnPluginStart()
+{
+    Baz();
+}
+"#,
+        ),
+        vec![(0, "1 reference".to_string())]
+    );
+}
+
+#[test]
+fn compile_at_top_without_plugin_start() {
+    assert_eq!(
+        code_lenses(
+            r#"
+%! main.sp
+#include "foo.sp"
+
+void Bar() {}
+
+%! foo.sp
+void Baz() {}
+"#,
+        ),
+        vec![(0, COMPILE.to_string()), (2, "0 references".to_string())]
+    );
+}
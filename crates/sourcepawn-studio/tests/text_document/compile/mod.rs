@@ -0,0 +1,118 @@
+use sourcepawn_studio::fixture::compile;
+
+#[cfg(unix)]
+#[test]
+fn compile_with_the_configured_options() {
+    let res = compile(
+        r#"
+%! scripting/main.sp
+public void OnPluginStart()
+{
+    int x = 1;
+}
+
+%! sourcepawn.toml
+compiler.optimizationLevel = 2
+compiler.verbosity = 0
+compiler.defines = ["DEBUG=1"]
+compiler.outputDirectory = "plugins"
+"#,
+        Some(
+            r#"#!/bin/sh
+echo "$@"
+echo "$1(3) : warning 204: symbol is assigned a value that is never used: \"x\""
+"#,
+        ),
+    )
+    .unwrap();
+
+    assert!(res.success);
+    assert_eq!(res.errors, 0);
+    assert_eq!(res.warnings, 1);
+    assert!(res.uri.path().ends_with("/plugins/main.smx"));
+    let args = res
+        .output
+        .lines()
+        .next()
+        .unwrap()
+        .split(' ')
+        .collect::<Vec<_>>();
+    assert!(args[0].ends_with("/scripting/main.sp"));
+    assert!(args.contains(&format!("-o{}", res.uri.path()).as_str()));
+    assert!(args.contains(&"-O2"));
+    assert!(args.contains(&"-v0"));
+    assert!(args.contains(&"DEBUG=1"));
+    assert!(!args.contains(&"--syntax-only"));
+}
+
+#[cfg(unix)]
+#[test]
+fn compile_next_to_the_file_by_default() {
+    let res = compile(
+        r#"
+%! scripting/main.sp
+public void OnPluginStart() {}
+"#,
+        Some(
+            r#"#!/bin/sh
+echo "$@"
+"#,
+        ),
+    )
+    .unwrap();
+
+    assert!(res.success);
+    assert!(res.uri.path().ends_with("/scripting/main.smx"));
+    let args = res
+        .output
+        .lines()
+        .next()
+        .unwrap()
+        .split(' ')
+        .collect::<Vec<_>>();
+    assert!(!args
+        .iter()
+        .any(|arg| arg.starts_with("-O") || arg.starts_with("-v")));
+}
+
+#[cfg(unix)]
+#[test]
+fn failed_compilation() {
+    let res = compile(
+        r#"
+%! main.sp
+public void OnPluginStart()
+{
+    foo();
+}
+"#,
+        Some(
+            r#"#!/bin/sh
+echo "$1(3) : error 017: undefined symbol \"foo\""
+echo "$1(3) : fatal error 417: cannot read from file: \"bar\"" >&2
+exit 1
+"#,
+        ),
+    )
+    .unwrap();
+
+    assert!(!res.success);
+    assert_eq!(res.errors, 2);
+    assert_eq!(res.warnings, 0);
+    assert_eq!(res.output.lines().count(), 2);
+}
+
+#[test]
+fn compiler_path_is_required() {
+    assert_eq!(
+        compile(
+            r#"
+%! main.sp
+public void OnPluginStart() {}
+"#,
+            None,
+        )
+        .map(|res| res.success),
+        Err("the `compiler.path` setting is not set".to_string())
+    );
+}
@@ -0,0 +1,30 @@
+use sourcepawn_studio::fixture::complete;
+
+#[test]
+fn convar_names() {
+    let items = complete(
+        r#"
+%! main.sp
+public void OnPluginStart()
+{
+    CreateConVar("sm_foo_enabled", "1", "Whether foo is enabled");
+    ConVar foo = FindConVar("");
+                             |
+}
+
+%! other.sp
+public void OnPluginStart()
+{
+    CreateConVar("sm_bar", "0");
+}
+"#,
+        None,
+    );
+    assert_eq!(
+        items
+            .iter()
+            .map(|item| (item.label.as_str(), item.detail.as_deref()))
+            .collect::<Vec<_>>(),
+        vec![("sm_bar", Some("\"0\"")), ("sm_foo_enabled", Some("\"1\""))]
+    );
+}
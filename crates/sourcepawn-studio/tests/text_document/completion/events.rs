@@ -0,0 +1,109 @@
+use lsp_types::{CompletionItem, Documentation, MarkupContent};
+use sourcepawn_studio::fixture::complete;
+
+const TF2: &str = r#"
+%! sourcepawn.toml
+eventsGameName = "Team Fortress 2"
+"#;
+
+fn games(items: &[CompletionItem]) -> Vec<&str> {
+    let mut games = items
+        .iter()
+        .filter_map(|item| item.detail.as_deref())
+        .collect::<Vec<_>>();
+    games.sort();
+    games.dedup();
+    games
+}
+
+#[test]
+fn events_of_game() {
+    let items = complete(
+        &format!(
+            r#"
+%! main.sp
+public void OnPluginStart()
+{{
+    HookEvent("");
+               |
+}}
+{TF2}"#
+        ),
+        None,
+    );
+    assert_eq!(
+        games(&items),
+        vec!["Generic Source", "Generic Source Server", "Team Fortress 2"]
+    );
+    assert!(items.iter().any(|item| item.label == "player_changeclass"
+        && item.detail.as_deref() == Some("Team Fortress 2")));
+    assert!(items.iter().any(|item| item.label == "server_spawn"
+        && item.detail.as_deref() == Some("Generic Source Server")));
+}
+
+#[test]
+fn events_of_all_games() {
+    let items = complete(
+        r#"
+%! main.sp
+public void OnPluginStart()
+{
+    HookEventEx("");
+                 |
+}
+"#,
+        None,
+    );
+    let games = games(&items);
+    assert!(games.contains(&"Counter-Strike: Source"));
+    assert!(games.contains(&"Team Fortress 2"));
+    assert!(items.iter().any(|item| item.label == "bomb_beginplant"
+        && item.detail.as_deref() == Some("Counter-Strike: Source")));
+}
+
+#[test]
+fn event_fields_link() {
+    let items = complete(
+        &format!(
+            r#"
+%! main.sp
+public void OnPluginStart()
+{{
+    UnhookEvent("");
+                 |
+}}
+{TF2}"#
+        ),
+        None,
+    );
+    let item = items
+        .iter()
+        .find(|item| item.label == "player_changeclass")
+        .unwrap();
+    let Some(Documentation::MarkupContent(MarkupContent { value, .. })) = &item.documentation
+    else {
+        panic!("Expected a markdown documentation.");
+    };
+    assert!(value.ends_with(
+        "[Fields of `player_changeclass` on the Alliedmodders wiki]\
+        (https://wiki.alliedmods.net/Team_Fortress_2_Events#player_changeclass)"
+    ));
+}
+
+#[test]
+fn event_callback_argument() {
+    let items = complete(
+        &format!(
+            r#"
+%! main.sp
+public void OnPluginStart()
+{{
+    HookEvent("player_death", "");
+                               |
+}}
+{TF2}"#
+        ),
+        None,
+    );
+    assert!(!items.iter().any(|item| item.label == "player_changeclass"));
+}
@@ -1,6 +1,25 @@
 use insta::assert_json_snapshot;
 use sourcepawn_studio::fixture::complete;
 
+/// Label and insert text of the include completions, and whether they complete again once
+/// inserted.
+fn include_completions(fixture: &str, trigger_character: char) -> Vec<(String, String, bool)> {
+    complete(fixture, Some(trigger_character.to_string()))
+        .into_iter()
+        .map(|item| {
+            (
+                item.label,
+                item.insert_text.unwrap_or_default(),
+                item.command.is_some(),
+            )
+        })
+        .collect()
+}
+
+fn item(label: &str, insert_text: &str, retrigger: bool) -> (String, String, bool) {
+    (label.to_string(), insert_text.to_string(), retrigger)
+}
+
 #[test]
 fn include_1() {
     assert_json_snapshot!(complete(
@@ -95,3 +114,108 @@ int foo;"#,
         Some("/".to_string())
     ));
 }
+
+const INCLUDE_DIRECTORY: &str = r#"
+%! main.sp
+int main;
+%! other.inc
+int other;
+%! include/foo.inc
+int foo;
+%! include/sub/bar.inc
+int bar;
+%! include/sub/baz.inc
+int baz;
+"#;
+
+#[test]
+fn include_quotes_prefer_include_directory() {
+    assert_eq!(
+        include_completions(
+            &format!(
+                r#"{INCLUDE_DIRECTORY}
+%! plugin.sp
+#include ""
+          |
+"#
+            ),
+            '"'
+        ),
+        vec![
+            item("foo.inc", "foo", false),
+            item("main.sp", "main.sp", false),
+            item("other.inc", "other", false),
+            item("sub", "sub/", true),
+        ]
+    );
+}
+
+#[test]
+fn include_chevrons_search_workspace() {
+    assert_eq!(
+        include_completions(
+            &format!(
+                r#"{INCLUDE_DIRECTORY}
+%! plugin.sp
+#include <>
+          |
+"#
+            ),
+            '<'
+        ),
+        vec![
+            item("include", "include/", true),
+            item("main.sp", "main.sp", false),
+            item("other.inc", "other", false),
+        ]
+    );
+}
+
+#[test]
+fn include_closing_element() {
+    assert_eq!(
+        include_completions(
+            r#"
+%! main.sp
+#include <
+          |
+%! foo.inc
+int foo;
+%! sub/bar.inc
+int bar;
+"#,
+            '<'
+        ),
+        vec![item("foo.inc", "foo>", false), item("sub", "sub/", true)]
+    );
+}
+
+#[test]
+fn include_directory_navigation() {
+    assert_eq!(
+        include_completions(
+            &format!(
+                r#"{INCLUDE_DIRECTORY}
+%! plugin.sp
+#include <include/sub/>
+                      |
+"#
+            ),
+            '/'
+        ),
+        vec![item("bar.inc", "bar", false), item("baz.inc", "baz", false)]
+    );
+    assert_eq!(
+        include_completions(
+            &format!(
+                r#"{INCLUDE_DIRECTORY}
+%! plugin.sp
+#include "sub/"
+              |
+"#
+            ),
+            '/'
+        ),
+        vec![item("bar.inc", "bar", false), item("baz.inc", "baz", false)]
+    );
+}
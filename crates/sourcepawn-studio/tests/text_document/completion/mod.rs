@@ -1,3 +1,7 @@
+mod convars;
+mod events;
 mod include;
 mod macros;
+mod phrases;
+mod recovery;
 mod variables;
@@ -0,0 +1,103 @@
+use sourcepawn_studio::fixture::complete;
+
+const TRANSLATIONS: &str = r##"
+%! translations/test.phrases.txt
+"Phrases"
+{
+    "Welcome"
+    {
+        "en"        "Welcome!"
+    }
+    "Kicked player"
+    {
+        "#format"   "{1:s},{2:d}"
+        "en"        "Kicked {1} after {2} warnings"
+    }
+}
+"##;
+
+fn labels(fixture: &str) -> Vec<String> {
+    complete(&format!("{fixture}{TRANSLATIONS}"), None)
+        .into_iter()
+        .map(|item| item.label)
+        .collect()
+}
+
+#[test]
+fn phrase_of_format_string() {
+    assert_eq!(
+        labels(
+            r#"
+%! main.sp
+native int Format(char[] buffer, int maxlength, const char[] format, any ...);
+
+void Foo()
+{
+    char buffer[64];
+    Format(buffer, sizeof(buffer), "%t", "");
+                                          |
+}
+"#,
+        ),
+        vec!["Kicked player".to_string(), "Welcome".to_string()]
+    );
+}
+
+#[test]
+fn phrase_after_phrase_arguments() {
+    assert_eq!(
+        labels(
+            r#"
+%! main.sp
+native int Format(char[] buffer, int maxlength, const char[] format, any ...);
+
+void Foo(int client)
+{
+    char buffer[64];
+    Format(buffer, sizeof(buffer), "%T %t", "Kicked player", client, "name", 2, "");
+                                                                                 |
+}
+"#,
+        ),
+        vec!["Kicked player".to_string(), "Welcome".to_string()]
+    );
+}
+
+#[test]
+fn no_phrase_for_other_specifiers() {
+    assert_eq!(
+        labels(
+            r#"
+%! main.sp
+native int Format(char[] buffer, int maxlength, const char[] format, any ...);
+
+void Foo()
+{
+    char buffer[64];
+    Format(buffer, sizeof(buffer), "%s", "");
+                                          |
+}
+"#,
+        ),
+        Vec::<String>::new()
+    );
+}
+
+#[test]
+fn translation_file() {
+    assert_eq!(
+        labels(
+            r#"
+%! main.sp
+native void LoadTranslations(const char[] file);
+
+public void OnPluginStart()
+{
+    LoadTranslations("");
+                      |
+}
+"#,
+        ),
+        vec!["test.phrases".to_string()]
+    );
+}
@@ -0,0 +1,51 @@
+use sourcepawn_studio::fixture::complete;
+
+fn labels(fixture: &str) -> Vec<String> {
+    complete(fixture, None)
+        .into_iter()
+        .map(|item| item.label)
+        .collect()
+}
+
+#[test]
+fn below_half_typed_function() {
+    let labels = labels(
+        r#"
+%! main.sp
+int foo;
+
+public void OnPlu
+
+void bar()
+{
+    int baz;
+
+    |
+    ^
+}
+"#,
+    );
+    assert!(labels.contains(&"foo".to_string()));
+    assert!(labels.contains(&"bar".to_string()));
+    assert!(labels.contains(&"baz".to_string()));
+}
+
+#[test]
+fn below_half_typed_statement() {
+    let labels = labels(
+        r#"
+%! main.sp
+void bar()
+{
+    int foo;
+    foo = Get
+    int baz;
+
+    |
+    ^
+}
+"#,
+    );
+    assert!(labels.contains(&"foo".to_string()));
+    assert!(labels.contains(&"baz".to_string()));
+}
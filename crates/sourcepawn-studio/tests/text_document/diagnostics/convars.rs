@@ -0,0 +1,62 @@
+use sourcepawn_studio::fixture::diagnostics;
+
+fn duplicate_convars(fixture: &str) -> Vec<(String, String)> {
+    diagnostics(fixture, false)
+        .into_iter()
+        .filter(|(_, code, _)| code == "SPLINT-duplicate-convar")
+        .map(|(path, _, message)| (path, message))
+        .collect()
+}
+
+#[test]
+fn duplicate_convar() {
+    assert_eq!(
+        duplicate_convars(
+            r#"
+%! main.sp
+public void OnPluginStart()
+{
+    CreateConVar("sm_foo_enabled", "1");
+    CreateConVar("sm_bar", "1");
+}
+
+%! other.sp
+public void OnPluginStart()
+{
+    CreateConVar("sm_foo_enabled", "0");
+}
+"#,
+        ),
+        vec![
+            (
+                "main.sp".to_string(),
+                "ConVar `sm_foo_enabled` is also created in `other.sp`".to_string()
+            ),
+            (
+                "other.sp".to_string(),
+                "ConVar `sm_foo_enabled` is also created in `main.sp`".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn convar_created_twice_by_a_file() {
+    assert_eq!(
+        duplicate_convars(
+            r#"
+%! main.sp
+public void OnPluginStart()
+{
+    CreateConVar("sm_foo_enabled", "1");
+}
+
+public void OnMapStart()
+{
+    CreateConVar("sm_foo_enabled", "1");
+}
+"#,
+        ),
+        Vec::<(String, String)>::new()
+    );
+}
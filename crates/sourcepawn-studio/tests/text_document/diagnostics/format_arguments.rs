@@ -0,0 +1,233 @@
+use sourcepawn_studio::fixture::diagnostics;
+
+const FORMAT: &str = r#"
+%! format.inc
+native int Format(char[] buffer, int maxlength, const char[] format, any ...);
+"#;
+
+/// Messages of the diagnostics of the format strings of the fixture, which may use `Format`.
+fn format_diagnostics(fixture: &str) -> Vec<String> {
+    diagnostics(&format!("{fixture}{FORMAT}"), false)
+        .into_iter()
+        .filter(|(_, code, _)| {
+            code == "SPLINT-incorrect-format-arguments"
+                || code == "SPLINT-mismatched-format-argument"
+                || code == "SPLINT-incorrect-phrase-arguments"
+        })
+        .map(|(_, _, message)| message)
+        .collect()
+}
+
+#[test]
+fn matching_arguments() {
+    assert_eq!(
+        format_diagnostics(
+            r#"
+%! main.sp
+#include "format.inc"
+
+void Foo(int client)
+{
+    char buffer[64];
+    Format(buffer, sizeof(buffer), "%N has %d points, %.2f%% of %s", client, 1, 2.0, "total");
+}
+"#,
+        ),
+        Vec::<String>::new()
+    );
+}
+
+#[test]
+fn escaped_percent_takes_no_argument() {
+    assert_eq!(
+        format_diagnostics(
+            r#"
+%! main.sp
+#include "format.inc"
+
+void Foo()
+{
+    char buffer[64];
+    Format(buffer, sizeof(buffer), "100%%", 1);
+}
+"#,
+        ),
+        vec!["format string expects 0 arguments, found 1".to_string()]
+    );
+}
+
+#[test]
+fn extra_arguments() {
+    assert_eq!(
+        format_diagnostics(
+            r#"
+%! main.sp
+#include "format.inc"
+
+void Foo()
+{
+    char buffer[64];
+    Format(buffer, sizeof(buffer), "%d", 1, 2);
+}
+"#,
+        ),
+        vec!["format string expects 1 arguments, found 2".to_string()]
+    );
+}
+
+#[test]
+fn missing_arguments() {
+    assert_eq!(
+        format_diagnostics(
+            r#"
+%! main.sp
+#include "format.inc"
+
+void Foo()
+{
+    char buffer[64];
+    Format(buffer, sizeof(buffer), "%d %s", 1);
+}
+"#,
+        ),
+        vec!["format string expects 2 arguments, found 1".to_string()]
+    );
+}
+
+#[test]
+fn mismatched_argument() {
+    assert_eq!(
+        format_diagnostics(
+            r#"
+%! main.sp
+#include "format.inc"
+
+void Foo()
+{
+    char buffer[64];
+    Format(buffer, sizeof(buffer), "%d %f", 1.0, 1);
+}
+"#,
+        ),
+        vec![
+            "`%d` expects an integer, found `float`".to_string(),
+            "`%f` expects a float, found `int`".to_string(),
+        ]
+    );
+}
+
+#[test]
+fn char_arrays_and_chars_are_strings() {
+    assert_eq!(
+        format_diagnostics(
+            r#"
+%! main.sp
+#include "format.inc"
+
+void Foo()
+{
+    char buffer[64];
+    char name[32];
+    char c = 'a';
+    Format(buffer, sizeof(buffer), "%s %s %c", name, c, c);
+}
+"#,
+        ),
+        Vec::<String>::new()
+    );
+}
+
+#[test]
+fn string_literal_is_not_an_integer() {
+    assert_eq!(
+        format_diagnostics(
+            r#"
+%! main.sp
+#include "format.inc"
+
+void Foo()
+{
+    char buffer[64];
+    Format(buffer, sizeof(buffer), "%d", "text");
+}
+"#,
+        ),
+        vec!["`%d` expects an integer, found `String`".to_string()]
+    );
+}
+
+#[test]
+fn translated_phrase_takes_client() {
+    assert_eq!(
+        format_diagnostics(
+            r#"
+%! main.sp
+#include "format.inc"
+
+void Foo(int client)
+{
+    char buffer[64];
+    Format(buffer, sizeof(buffer), "%T", "Welcome", client);
+    Format(buffer, sizeof(buffer), "%T", "Welcome", 1.0);
+}
+"#,
+        ),
+        vec!["`%T` expects an integer, found `float`".to_string()]
+    );
+}
+
+#[test]
+fn translated_phrase_arguments() {
+    assert_eq!(
+        format_diagnostics(
+            r##"
+%! main.sp
+#include "format.inc"
+
+public void OnPluginStart()
+{
+    LoadTranslations("test.phrases");
+}
+
+void Foo(int client)
+{
+    char buffer[64];
+    Format(buffer, sizeof(buffer), "%T", "Kicked player", client, "name", 2);
+    Format(buffer, sizeof(buffer), "%T", "Kicked player", client, "name");
+}
+
+native void LoadTranslations(const char[] file);
+
+%! translations/test.phrases.txt
+"Phrases"
+{
+    "Kicked player"
+    {
+        "#format"   "{1:s},{2:d}"
+        "en"        "Kicked {1} after {2} warnings"
+    }
+}
+"##,
+        ),
+        vec!["phrase `Kicked player` expects 2 arguments, found 1".to_string()]
+    );
+}
+
+#[test]
+fn named_arguments_are_not_checked() {
+    assert_eq!(
+        format_diagnostics(
+            r#"
+%! main.sp
+#include "format.inc"
+
+void Foo()
+{
+    char buffer[64];
+    Format(buffer, .maxlength = sizeof(buffer), .format = "%d %d", 1);
+}
+"#,
+        ),
+        Vec::<String>::new()
+    );
+}
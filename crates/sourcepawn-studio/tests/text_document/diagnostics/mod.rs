@@ -0,0 +1,227 @@
+mod convars;
+mod format_arguments;
+mod phrases;
+mod plugin_info;
+mod sql_queries;
+mod unused_includes;
+
+use sourcepawn_studio::fixture::{diagnostics, diagnostics_after_change};
+
+fn unused_macros(fixture: &str) -> Vec<(String, String)> {
+    diagnostics(fixture, false)
+        .into_iter()
+        .filter(|(_, code, _)| code == "SPLINT-unused-macro")
+        .map(|(path, _, message)| (path, message))
+        .collect()
+}
+
+#[test]
+fn unused_macro() {
+    assert_eq!(
+        unused_macros(
+            r#"
+%! main.sp
+#define UNUSED 1
+#define USED 2
+
+int Foo()
+{
+    return USED;
+}
+"#,
+        ),
+        vec![(
+            "main.sp".to_string(),
+            "macro `UNUSED` is never used".to_string()
+        )]
+    );
+}
+
+#[test]
+fn unused_macro_include_dir() {
+    assert_eq!(
+        unused_macros(
+            r#"
+%! main.sp
+#include "lib"
+
+void OnPluginStart()
+{
+}
+
+%! include/lib.inc
+#define LIB_UNUSED 1
+"#,
+        ),
+        vec![]
+    );
+}
+
+#[test]
+fn unused_macro_shared_module() {
+    assert_eq!(
+        unused_macros(
+            r#"
+%! a.sp
+#include "shared.sp"
+
+void A()
+{
+}
+
+%! b.sp
+#include "shared.sp"
+
+int B()
+{
+    return SHARED_B;
+}
+
+%! shared.sp
+#define SHARED_B 1
+"#,
+        ),
+        vec![]
+    );
+}
+
+/// Files with an include closing a cycle of includes.
+fn circular_includes(fixture: &str) -> Vec<String> {
+    diagnostics(fixture, false)
+        .into_iter()
+        .filter(|(_, code, _)| code == "SP0014")
+        .map(|(path, _, _)| path)
+        .collect()
+}
+
+#[test]
+fn circular_include() {
+    assert_eq!(
+        circular_includes(
+            r#"
+%! main.sp
+#include "a.inc"
+
+%! a.inc
+#include "b.inc"
+
+%! b.inc
+#include "a.inc"
+"#,
+        ),
+        vec!["b.inc".to_string()]
+    );
+}
+
+#[test]
+fn circular_include_after_change() {
+    // The cycle is closed by the include of `a.inc`, whose text does not change, so its previous
+    // result must not be reused.
+    let circular_includes = diagnostics_after_change(
+        r#"
+%! main.sp
+#include "b.inc"
+#include "a.inc"
+
+%! a.inc
+#include "b.inc"
+
+%! b.inc
+"#,
+        "b.inc",
+        "#include \"a.inc\"\n",
+    )
+    .into_iter()
+    .filter(|(_, code, _)| code == "SP0014")
+    .map(|(path, _, _)| path)
+    .collect::<Vec<_>>();
+    assert_eq!(circular_includes, vec!["a.inc".to_string()]);
+}
+
+#[test]
+fn circular_include_with_guard() {
+    assert_eq!(
+        circular_includes(
+            r#"
+%! main.sp
+#include "a.inc"
+
+%! a.inc
+#if defined _a_included
+ #endinput
+#endif
+#define _a_included
+
+#include "b.inc"
+
+%! b.inc
+#include "a.inc"
+"#,
+        ),
+        Vec::<String>::new()
+    );
+}
+
+#[test]
+fn circular_include_with_inactive_endinput() {
+    assert_eq!(
+        circular_includes(
+            r#"
+%! main.sp
+#include "a.inc"
+
+%! a.inc
+// The guard is missing its #endinput.
+#if defined _a_included
+#endif
+#define _a_included
+
+#include "b.inc"
+
+%! b.inc
+#include "a.inc"
+"#,
+        ),
+        vec!["b.inc".to_string()]
+    );
+    assert_eq!(
+        circular_includes(
+            r#"
+%! main.sp
+#include "a.inc"
+
+%! a.inc
+#if 0
+ #endinput
+#endif
+
+#include "b.inc"
+
+%! b.inc
+#include "a.inc"
+"#,
+        ),
+        vec!["b.inc".to_string()]
+    );
+}
+
+#[test]
+fn circular_include_with_endinput_after_include() {
+    assert_eq!(
+        circular_includes(
+            r#"
+%! main.sp
+#include "a.inc"
+
+%! a.inc
+#include "b.inc"
+
+#endinput
+
+%! b.inc
+#include "a.inc"
+"#,
+        ),
+        vec!["b.inc".to_string()]
+    );
+}
@@ -0,0 +1,115 @@
+use sourcepawn_studio::fixture::diagnostics;
+
+const TRANSLATIONS: &str = r##"
+%! translations/test.phrases.txt
+"Phrases"
+{
+    "Welcome"
+    {
+        "en"        "Welcome!"
+    }
+}
+"##;
+
+/// Messages of the diagnostics of the phrases of the fixture.
+fn phrase_diagnostics(fixture: &str) -> Vec<String> {
+    diagnostics(&format!("{fixture}{TRANSLATIONS}"), false)
+        .into_iter()
+        .filter(|(_, code, _)| {
+            code == "SPLINT-unknown-phrase" || code == "SPLINT-unloaded-translation"
+        })
+        .map(|(_, _, message)| message)
+        .collect()
+}
+
+#[test]
+fn loaded_phrase() {
+    assert_eq!(
+        phrase_diagnostics(
+            r#"
+%! main.sp
+native int Format(char[] buffer, int maxlength, const char[] format, any ...);
+native void LoadTranslations(const char[] file);
+
+public void OnPluginStart()
+{
+    LoadTranslations("test.phrases");
+    char buffer[64];
+    Format(buffer, sizeof(buffer), "%t", "Welcome");
+}
+"#,
+        ),
+        Vec::<String>::new()
+    );
+}
+
+#[test]
+fn unknown_phrase() {
+    assert_eq!(
+        phrase_diagnostics(
+            r#"
+%! main.sp
+native int Format(char[] buffer, int maxlength, const char[] format, any ...);
+native void LoadTranslations(const char[] file);
+
+public void OnPluginStart()
+{
+    LoadTranslations("test.phrases");
+    char buffer[64];
+    Format(buffer, sizeof(buffer), "%t", "Goodbye");
+}
+"#,
+        ),
+        vec!["phrase `Goodbye` is not defined by any translation file".to_string()]
+    );
+}
+
+#[test]
+fn unloaded_translation() {
+    assert_eq!(
+        phrase_diagnostics(
+            r#"
+%! main.sp
+native int Format(char[] buffer, int maxlength, const char[] format, any ...);
+
+public void OnPluginStart()
+{
+    char buffer[64];
+    Format(buffer, sizeof(buffer), "%t", "Welcome");
+}
+"#,
+        ),
+        vec![
+            "phrase `Welcome` is defined by `test.phrases`, which is never loaded with `LoadTranslations`"
+                .to_string()
+        ]
+    );
+}
+
+#[test]
+fn translation_loaded_by_another_file() {
+    assert_eq!(
+        phrase_diagnostics(
+            r#"
+%! main.sp
+#include "translations.sp"
+native int Format(char[] buffer, int maxlength, const char[] format, any ...);
+
+void Foo()
+{
+    char buffer[64];
+    Format(buffer, sizeof(buffer), "%t", "Welcome");
+}
+
+%! translations.sp
+native void LoadTranslations(const char[] file);
+
+public void OnPluginStart()
+{
+    LoadTranslations("test.phrases");
+}
+"#,
+        ),
+        Vec::<String>::new()
+    );
+}
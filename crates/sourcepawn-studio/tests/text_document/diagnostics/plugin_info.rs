@@ -0,0 +1,86 @@
+use sourcepawn_studio::fixture::diagnostics;
+
+fn plugin_info_diagnostics(fixture: &str) -> Vec<String> {
+    diagnostics(fixture, false)
+        .into_iter()
+        .filter(|(_, code, _)| {
+            code == "SPLINT-incomplete-plugin-info" || code == "SPLINT-non-literal-plugin-version"
+        })
+        .map(|(_, _, message)| message)
+        .collect()
+}
+
+#[test]
+fn complete_plugin_info() {
+    assert_eq!(
+        plugin_info_diagnostics(
+            r#"
+%! main.sp
+#define PLUGIN_VERSION "1.0.0"
+
+public Plugin myinfo =
+{
+    name = "Foo",
+    author = "Bar",
+    version = PLUGIN_VERSION,
+};
+"#,
+        ),
+        Vec::<String>::new()
+    );
+}
+
+#[test]
+fn missing_fields() {
+    assert_eq!(
+        plugin_info_diagnostics(
+            r#"
+%! main.sp
+public Plugin myinfo =
+{
+    name = "Foo",
+    description = "Does foo",
+};
+"#,
+        ),
+        vec!["`myinfo` does not set the `author`, `version` of the plugin".to_string()]
+    );
+}
+
+#[test]
+fn empty_field() {
+    assert_eq!(
+        plugin_info_diagnostics(
+            r#"
+%! main.sp
+public Plugin myinfo =
+{
+    name = "",
+    author = "Bar",
+    version = "1.0.0",
+};
+"#,
+        ),
+        vec!["`myinfo` does not set the `name` of the plugin".to_string()]
+    );
+}
+
+#[test]
+fn non_literal_version() {
+    assert_eq!(
+        plugin_info_diagnostics(
+            r#"
+%! main.sp
+#define PLUGIN_VERSION 1
+
+public Plugin myinfo =
+{
+    name = "Foo",
+    author = "Bar",
+    version = PLUGIN_VERSION,
+};
+"#,
+        ),
+        vec!["the version of the plugin is not a string literal".to_string()]
+    );
+}
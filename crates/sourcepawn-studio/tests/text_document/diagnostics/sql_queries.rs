@@ -0,0 +1,143 @@
+use sourcepawn_studio::fixture::diagnostics;
+
+fn sql_diagnostics(fixture: &str) -> Vec<String> {
+    diagnostics(fixture, false)
+        .into_iter()
+        .filter(|(_, code, _)| {
+            code == "SPLINT-unescaped-sql-argument" || code == "SPLINT-mismatched-sql-parameters"
+        })
+        .map(|(_, _, message)| message)
+        .collect()
+}
+
+#[test]
+fn unescaped_argument() {
+    assert_eq!(
+        sql_diagnostics(
+            r#"
+%! main.sp
+void Foo(const char[] name, int score)
+{
+    char query[256];
+    Format(query, sizeof(query), "SELECT * FROM users WHERE name = '%s' AND score > %d", name, score);
+}
+"#,
+        ),
+        vec!["`name` is formatted in an SQL query without being escaped".to_string()]
+    );
+}
+
+#[test]
+fn escaped_arguments() {
+    assert_eq!(
+        sql_diagnostics(
+            r#"
+%! main.sp
+void Foo(Database db, const char[] name, const char[] tag)
+{
+    char escapedName[64], escapedTag[64], query[256];
+    SQL_EscapeString(db, name, escapedName, sizeof(escapedName));
+    db.Escape(tag, escapedTag, sizeof(escapedTag));
+    FormatEx(query, sizeof(query), "UPDATE users SET tag = '%s' WHERE name = '%s'", escapedTag, escapedName);
+}
+"#,
+        ),
+        Vec::<String>::new()
+    );
+}
+
+#[test]
+fn not_a_query() {
+    assert_eq!(
+        sql_diagnostics(
+            r#"
+%! main.sp
+void Foo(const char[] name)
+{
+    char message[256], query[256];
+    Format(message, sizeof(message), "Hello %s", name);
+    Format(query, sizeof(query), "SELECT * FROM users WHERE name = '%s'", "admin");
+}
+"#,
+        ),
+        Vec::<String>::new()
+    );
+}
+
+#[test]
+fn unbound_placeholders() {
+    assert_eq!(
+        sql_diagnostics(
+            r#"
+%! main.sp
+void Foo(Database db, const char[] name)
+{
+    char error[256];
+    DBStatement stmt = SQL_PrepareQuery(db, "INSERT INTO users VALUES (?, ?)", error, sizeof(error));
+    SQL_BindParamString(stmt, 0, name, false);
+}
+"#,
+        ),
+        vec!["1 of the 2 `?` placeholders of the query are never bound".to_string()]
+    );
+}
+
+#[test]
+fn out_of_range_parameter() {
+    assert_eq!(
+        sql_diagnostics(
+            r#"
+%! main.sp
+void Foo(Database db, const char[] name, int score)
+{
+    char error[256];
+    DBStatement stmt = SQL_PrepareQuery(db, "INSERT INTO users VALUES (?, ?)", error, sizeof(error));
+    stmt.BindString(0, name, false);
+    stmt.BindInt(1, score);
+    stmt.BindInt(2, score);
+}
+"#,
+        ),
+        vec!["parameter 2 is out of range, the query has 2 `?` placeholders".to_string()]
+    );
+}
+
+#[test]
+fn bound_parameters() {
+    assert_eq!(
+        sql_diagnostics(
+            r#"
+%! main.sp
+void Foo(Database db, const char[] name, int score)
+{
+    char error[256];
+    DBStatement stmt = SQL_PrepareQuery(db, "SELECT * FROM users WHERE name = ? AND tag != '?' AND score > ?", error, sizeof(error));
+    stmt.BindString(0, name, false);
+    SQL_BindParamInt(stmt, 1, score);
+}
+"#,
+        ),
+        Vec::<String>::new()
+    );
+}
+
+#[test]
+fn parameters_bound_with_unknown_index() {
+    assert_eq!(
+        sql_diagnostics(
+            r#"
+%! main.sp
+void Foo(Database db, int[] scores)
+{
+    char error[256];
+    DBStatement stmt = SQL_PrepareQuery(db, "INSERT INTO scores VALUES (?, ?, ?)", error, sizeof(error));
+    for (int i = 0; i < 3; i++)
+    {
+        stmt.BindInt(i, scores[i]);
+    }
+}
+"#,
+        ),
+        Vec::<String>::new()
+    );
+}
@@ -0,0 +1,126 @@
+use sourcepawn_studio::fixture::{apply_code_action, diagnostics};
+
+const EXPERIMENTAL: &str = r#"
+%! sourcepawn.toml
+[analysis]
+experimental = { unusedIncludes = true }
+"#;
+
+fn unused_includes(fixture: &str) -> Vec<(String, String)> {
+    diagnostics(fixture, false)
+        .into_iter()
+        .filter(|(_, code, _)| code == "SPLINT-unused-include")
+        .map(|(path, _, message)| (path, message))
+        .collect()
+}
+
+#[test]
+fn unused_include() {
+    assert_eq!(
+        unused_includes(&format!(
+            r#"
+%! main.sp
+#include "unused"
+#include "helpers"
+
+public void OnPluginStart()
+{{
+    Helper();
+}}
+
+%! unused.inc
+void Unused()
+{{
+}}
+
+%! helpers.inc
+void Helper()
+{{
+}}
+{EXPERIMENTAL}"#
+        )),
+        vec![("main.sp".to_string(), "`unused` is never used".to_string())]
+    );
+}
+
+#[test]
+fn include_used_through_a_macro() {
+    assert_eq!(
+        unused_includes(&format!(
+            r#"
+%! main.sp
+#include "constants"
+
+public void OnPluginStart()
+{{
+    int x = MAX_PLAYERS;
+}}
+
+%! constants.inc
+#define MAX_PLAYERS 64
+{EXPERIMENTAL}"#
+        )),
+        Vec::<(String, String)>::new()
+    );
+}
+
+#[test]
+fn unused_include_disabled() {
+    assert_eq!(
+        unused_includes(
+            r#"
+%! main.sp
+#include "unused"
+
+public void OnPluginStart()
+{
+}
+
+%! unused.inc
+void Unused()
+{
+}
+"#
+        ),
+        Vec::<(String, String)>::new()
+    );
+}
+
+#[test]
+fn remove_unused_include() {
+    assert_eq!(
+        apply_code_action(
+            &format!(
+                r#"
+%! main.sp
+#include "helpers"
+#include "unused"
+          |
+
+public void OnPluginStart()
+{{
+    Helper();
+}}
+
+%! unused.inc
+void Unused()
+{{
+}}
+
+%! helpers.inc
+void Helper()
+{{
+}}
+{EXPERIMENTAL}"#
+            ),
+            "Remove the unused include",
+        )
+        .unwrap(),
+        r#"#include "helpers"
+
+public void OnPluginStart()
+{
+    Helper();
+}"#
+    );
+}
@@ -0,0 +1,106 @@
+use lsp_types::{DocumentHighlightKind, Position};
+use sourcepawn_studio::fixture::document_highlight;
+
+const READ: DocumentHighlightKind = DocumentHighlightKind::READ;
+const WRITE: DocumentHighlightKind = DocumentHighlightKind::WRITE;
+
+#[test]
+fn local_reads_and_writes() {
+    assert_eq!(
+        document_highlight(
+            r#"
+%! main.sp
+void Foo()
+{
+    int x = 1;
+        |
+    int y;
+    x++;
+    y = x + 1;
+    x = y;
+    Bar(x);
+}
+
+void Bar(int x)
+{
+    x = 2;
+}
+"#,
+        ),
+        vec![
+            (Position::new(2, 8), WRITE),
+            (Position::new(4, 4), WRITE),
+            (Position::new(5, 8), READ),
+            (Position::new(6, 4), WRITE),
+            (Position::new(7, 8), READ),
+        ]
+    );
+}
+
+#[test]
+fn global_in_the_whole_file() {
+    assert_eq!(
+        document_highlight(
+            r#"
+%! main.sp
+int g_Count;
+    |
+
+void Foo()
+{
+    g_Count = 1;
+}
+
+int Bar()
+{
+    return g_Count;
+}
+"#,
+        ),
+        vec![
+            (Position::new(0, 4), READ),
+            (Position::new(4, 4), WRITE),
+            (Position::new(9, 11), READ),
+        ]
+    );
+}
+
+#[test]
+fn indexed_assignment() {
+    assert_eq!(
+        document_highlight(
+            r#"
+%! main.sp
+void Foo()
+{
+    int arr[3];
+    arr[0] = 1;
+    |
+    int y = arr[1];
+}
+"#,
+        ),
+        vec![
+            (Position::new(2, 8), READ),
+            (Position::new(3, 4), WRITE),
+            (Position::new(4, 12), READ),
+        ]
+    );
+}
+
+#[test]
+fn keyword() {
+    assert_eq!(
+        document_highlight(
+            r#"
+%! main.sp
+void Foo()
+{
+    int x = 1;
+    |
+}
+"#,
+        ),
+        Vec::<(Position, DocumentHighlightKind)>::new()
+    );
+}
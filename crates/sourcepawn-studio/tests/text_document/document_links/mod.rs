@@ -0,0 +1,43 @@
+use lsp_types::{Position, Range};
+use sourcepawn_studio::fixture::document_links;
+
+#[test]
+fn includes() {
+    assert_eq!(
+        document_links(
+            r#"
+%! main.sp
+#include <foo>
+#include "bar.inc"
+#tryinclude "missing.inc"
+%! foo.inc
+int foo;
+%! include/bar.inc
+int bar;
+"#
+        ),
+        vec![
+            (
+                Range::new(Position::new(0, 10), Position::new(0, 13)),
+                "foo.inc".to_string()
+            ),
+            (
+                Range::new(Position::new(1, 10), Position::new(1, 17)),
+                "include/bar.inc".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn unresolved_include() {
+    assert_eq!(
+        document_links(
+            r#"
+%! main.sp
+#include "missing.inc"
+"#
+        ),
+        vec![]
+    );
+}
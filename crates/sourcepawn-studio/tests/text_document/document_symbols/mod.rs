@@ -0,0 +1,104 @@
+use lsp_types::SymbolKind;
+use sourcepawn_studio::fixture::document_symbols;
+
+fn outline(symbols: &[(usize, &str, SymbolKind)]) -> Vec<(usize, String, SymbolKind)> {
+    symbols
+        .iter()
+        .map(|(depth, name, kind)| (*depth, name.to_string(), *kind))
+        .collect()
+}
+
+#[test]
+fn enum_values_are_nested() {
+    assert_eq!(
+        document_symbols(
+            r#"
+%! main.sp
+enum Color
+{
+    Color_Red,
+    Color_Green = 2,
+}
+
+enum
+{
+    Unnamed_Value,
+}
+"#,
+        ),
+        outline(&[
+            (0, "Color", SymbolKind::ENUM),
+            (1, "Color_Red", SymbolKind::ENUM_MEMBER),
+            (1, "Color_Green", SymbolKind::ENUM_MEMBER),
+            (0, "enum", SymbolKind::ENUM),
+            (1, "Unnamed_Value", SymbolKind::ENUM_MEMBER),
+        ])
+    );
+}
+
+#[test]
+fn enum_struct_fields_and_methods() {
+    assert_eq!(
+        document_symbols(
+            r#"
+%! main.sp
+enum struct Player
+{
+    int health;
+    float speed;
+
+    void Heal(int amount)
+    {
+        int max = 100;
+        this.health += amount;
+    }
+}
+"#,
+        ),
+        outline(&[
+            (0, "Player", SymbolKind::STRUCT),
+            (1, "health", SymbolKind::FIELD),
+            (1, "speed", SymbolKind::FIELD),
+            (1, "Heal", SymbolKind::METHOD),
+            (2, "max", SymbolKind::VARIABLE),
+        ])
+    );
+}
+
+#[test]
+fn methodmap_members() {
+    assert_eq!(
+        document_symbols(
+            r#"
+%! main.sp
+methodmap Foo
+{
+    public Foo()
+    {
+        return view_as<Foo>(1);
+    }
+
+    property int Bar
+    {
+        public get()
+        {
+            return 1;
+        }
+    }
+
+    public void Baz() {}
+}
+
+void Qux() {}
+"#,
+        ),
+        outline(&[
+            (0, "Foo", SymbolKind::CLASS),
+            (1, "Foo", SymbolKind::CONSTRUCTOR),
+            (1, "Bar", SymbolKind::PROPERTY),
+            (2, "get", SymbolKind::METHOD),
+            (1, "Baz", SymbolKind::METHOD),
+            (0, "Qux", SymbolKind::FUNCTION),
+        ])
+    );
+}
@@ -0,0 +1,176 @@
+use lsp_types::FoldingRangeKind;
+use sourcepawn_studio::fixture::folding_ranges;
+
+#[test]
+fn block() {
+    let fixture = r#"
+%! main.sp
+void Foo()
+{
+    int x;
+}
+"#;
+    assert_eq!(folding_ranges(fixture, false), vec![(1, 3, None)]);
+    // The closing brace stays visible.
+    assert_eq!(folding_ranges(fixture, true), vec![(1, 2, None)]);
+}
+
+#[test]
+fn empty_block() {
+    let fixture = r#"
+%! main.sp
+void Foo()
+{
+}
+"#;
+    assert_eq!(folding_ranges(fixture, false), vec![(1, 2, None)]);
+    assert_eq!(folding_ranges(fixture, true), vec![]);
+}
+
+#[test]
+fn nested_blocks() {
+    assert_eq!(
+        folding_ranges(
+            r#"
+%! main.sp
+void Foo(int x)
+{
+    if (x)
+    {
+        x = 0;
+    }
+}
+"#,
+            true,
+        ),
+        vec![(1, 5, None), (3, 4, None)]
+    );
+}
+
+#[test]
+fn block_comment() {
+    let fixture = r#"
+%! main.sp
+/**
+ * Foo.
+ */
+int foo;
+"#;
+    let folds = vec![(0, 2, Some(FoldingRangeKind::Comment))];
+    assert_eq!(folding_ranges(fixture, false), folds);
+    assert_eq!(folding_ranges(fixture, true), folds);
+}
+
+#[test]
+fn condition() {
+    let fixture = r#"
+%! main.sp
+#if defined FOO
+int foo;
+#else
+int bar;
+#endif
+"#;
+    let folds = vec![
+        (0, 1, Some(FoldingRangeKind::Region)),
+        (2, 3, Some(FoldingRangeKind::Region)),
+    ];
+    assert_eq!(folding_ranges(fixture, false), folds);
+    assert_eq!(folding_ranges(fixture, true), folds);
+}
+
+#[test]
+fn inactive_condition() {
+    assert_eq!(
+        folding_ranges(
+            r#"
+%! main.sp
+#if 0
+void Foo()
+{
+}
+#endif
+"#,
+            false,
+        ),
+        vec![(0, 3, Some(FoldingRangeKind::Region))]
+    );
+}
+
+#[test]
+fn consecutive_includes() {
+    assert_eq!(
+        folding_ranges(
+            r#"
+%! main.sp
+#include <sourcemod>
+#tryinclude "foo.inc"
+#include "bar.inc"
+"#,
+            false,
+        ),
+        vec![(0, 2, Some(FoldingRangeKind::Imports))]
+    );
+}
+
+#[test]
+fn single_include() {
+    assert_eq!(
+        folding_ranges(
+            r#"
+%! main.sp
+#include <sourcemod>
+int foo;
+"#,
+            false,
+        ),
+        vec![]
+    );
+}
+
+#[test]
+fn includes_split_by_blank_line() {
+    assert_eq!(
+        folding_ranges(
+            r#"
+%! main.sp
+#include <sourcemod>
+#include <sdktools>
+
+#include "foo.inc"
+#include "bar.inc"
+"#,
+            false,
+        ),
+        vec![
+            (0, 1, Some(FoldingRangeKind::Imports)),
+            (3, 4, Some(FoldingRangeKind::Imports)),
+        ]
+    );
+}
+
+#[test]
+fn line_comments() {
+    assert_eq!(
+        folding_ranges(
+            r#"
+%! main.sp
+// Foo
+// Bar
+/* Baz
+ */
+// Qux
+int foo;
+
+// Quux
+// Corge
+"#,
+            false,
+        ),
+        vec![
+            (0, 1, Some(FoldingRangeKind::Comment)),
+            (2, 3, Some(FoldingRangeKind::Comment)),
+            (6, 7, Some(FoldingRangeKind::Comment)),
+        ]
+    );
+}
@@ -0,0 +1,35 @@
+use sourcepawn_studio::fixture::format;
+
+#[test]
+fn document() {
+    assert_eq!(
+        format(
+            r#"
+%! main.sp
+void Foo()
+{
+int x=1;
+if(x)
+Bar( x );
+}
+"#,
+        ),
+        Some("void Foo()\n{\n\tint x = 1;\n\tif (x)\n\t\tBar(x);\n}\n".to_string())
+    );
+}
+
+#[test]
+fn syntax_errors() {
+    assert_eq!(
+        format(
+            r#"
+%! main.sp
+void Foo()
+{
+int x=1
+Bar( x );
+"#,
+        ),
+        None
+    );
+}
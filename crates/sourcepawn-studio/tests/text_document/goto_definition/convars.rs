@@ -0,0 +1,66 @@
+use itertools::Itertools;
+use sourcepawn_studio::fixture::goto_definition;
+
+/// Paths and lines of the targets of the definitions of the fixture.
+fn targets(fixture: &str) -> Vec<(String, u32)> {
+    goto_definition(fixture)
+        .into_iter()
+        .map(|location| {
+            (
+                location.target_uri.path().to_string(),
+                location.target_selection_range.start.line,
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn convar_of_another_file() {
+    assert_eq!(
+        targets(
+            r#"
+%! main.sp
+public void OnPluginStart()
+{
+    ConVar foo = FindConVar("sm_foo_enabled");
+                               |
+}
+
+%! other.sp
+public void OnPluginStart()
+{
+    CreateConVar("sm_bar", "0");
+    CreateConVar("sm_foo_enabled", "1", "Whether foo is enabled");
+}
+"#,
+        ),
+        vec![("/other.sp".to_string(), 3)]
+    );
+}
+
+#[test]
+fn duplicate_convars() {
+    assert_eq!(
+        targets(
+            r#"
+%! main.sp
+public void OnPluginStart()
+{
+    CreateConVar("sm_foo_enabled", "1");
+    ConVar foo = FindConVar("sm_foo_enabled");
+                               |
+}
+
+%! other.sp
+public void OnPluginStart()
+{
+    CreateConVar("sm_foo_enabled", "1");
+}
+"#,
+        )
+        .into_iter()
+        .sorted()
+        .collect::<Vec<_>>(),
+        vec![("/main.sp".to_string(), 2), ("/other.sp".to_string(), 2)]
+    );
+}
@@ -401,3 +401,55 @@ void bar() {
 "#,
     ));
 }
+
+#[test]
+fn preprocessor_isolation_1() {
+    assert_json_snapshot!(goto_definition(
+        r#"
+%! main.sp
+#define FOO
+#include "foo.sp"
+#if defined FOO
+int foo;
+#else
+int foo;
+#endif
+void bar() {
+    foo = 1;
+     |
+     ^
+}
+
+%! foo.sp
+#undef FOO
+"#,
+    ));
+}
+
+#[test]
+fn preprocessor_isolation_2() {
+    assert_json_snapshot!(goto_definition(
+        r#"
+%! main.sp
+#include "foo.sp"
+#include "bar.sp"
+#if defined BAR
+int foo;
+#else
+int foo;
+#endif
+void bar() {
+    foo = 1;
+     |
+     ^
+}
+
+%! foo.sp
+#include "bar.sp"
+#undef BAR
+
+%! bar.sp
+#define BAR
+"#,
+    ));
+}
@@ -2,12 +2,14 @@ use insta::assert_json_snapshot;
 use sourcepawn_studio::fixture::goto_definition;
 
 mod arrays;
+mod convars;
 mod enum_structs;
 mod enums;
 mod function_declarations;
 mod functions;
 mod macros;
 mod methodmaps;
+mod recovery;
 mod statements;
 mod structs;
 mod typedefs;
@@ -0,0 +1,51 @@
+use sourcepawn_studio::fixture::goto_definition;
+
+/// Lines of the names of the definitions of the symbol at the cursor of the fixture.
+fn definition_lines(fixture: &str) -> Vec<u32> {
+    goto_definition(fixture)
+        .into_iter()
+        .map(|location| location.target_selection_range.start.line)
+        .collect()
+}
+
+#[test]
+fn below_half_typed_function() {
+    assert_eq!(
+        definition_lines(
+            r#"
+%! main.sp
+int foo;
+
+public void OnPlu
+
+void bar()
+{
+    foo = 1;
+     |
+     ^
+}
+"#,
+        ),
+        vec![0]
+    );
+}
+
+#[test]
+fn below_half_typed_statement() {
+    assert_eq!(
+        definition_lines(
+            r#"
+%! main.sp
+void bar()
+{
+    foo = Get
+    int baz;
+    baz = 1;
+     |
+     ^
+}
+"#,
+        ),
+        vec![3]
+    );
+}
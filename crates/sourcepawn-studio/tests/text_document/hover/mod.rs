@@ -3,4 +3,5 @@ mod enums;
 mod functions;
 mod macros;
 mod methodmaps;
+mod plugin_info;
 mod variables;
@@ -0,0 +1,30 @@
+use lsp_types::HoverContents;
+use sourcepawn_studio::fixture::hover;
+
+#[test]
+fn plugin_info_metadata() {
+    let res = hover(
+        r#"
+%! main.sp
+#define PLUGIN_VERSION "1.0.0"
+
+public Plugin myinfo =
+               |
+{
+    name = "Foo",
+    author = "Bar | Baz",
+    version = PLUGIN_VERSION,
+};
+"#,
+    );
+    let HoverContents::Markup(contents) = res.hover.contents else {
+        panic!("expected a markup hover");
+    };
+    assert!(contents.value.ends_with(
+        "| Field | Value |
+| --- | --- |
+| name | Foo |
+| author | Bar \\| Baz |
+| version | 1.0.0 |"
+    ));
+}
@@ -0,0 +1,48 @@
+use lsp_types::{Position, Range};
+use sourcepawn_studio::fixture::inactive_regions;
+
+#[test]
+fn published_and_cleared() {
+    assert_eq!(
+        inactive_regions(
+            r#"
+%! main.sp
+void Foo()
+{
+}
+
+#if 0
+void Bar()
+{
+}
+#endif
+"#,
+            "void Foo()\n{\n}\n",
+        ),
+        (
+            Some(vec![Range::new(Position::new(4, 0), Position::new(8, 6))]),
+            Some(vec![])
+        )
+    );
+}
+
+#[test]
+fn active_condition() {
+    assert_eq!(
+        inactive_regions(
+            r#"
+%! main.sp
+#if 1
+void Foo()
+{
+}
+#endif
+"#,
+            "#if 0\nvoid Foo()\n{\n}\n#endif",
+        ),
+        (
+            Some(vec![]),
+            Some(vec![Range::new(Position::new(0, 0), Position::new(4, 6))])
+        )
+    );
+}
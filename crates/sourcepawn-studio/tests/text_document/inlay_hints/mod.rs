@@ -0,0 +1,101 @@
+use sourcepawn_studio::fixture::inlay_hints;
+
+/// Line, character and label of the inlay hints of the fixture.
+fn hints(fixture: &str) -> Vec<(u32, u32, String)> {
+    inlay_hints(fixture)
+        .into_iter()
+        .map(|(position, label)| (position.line, position.character, label))
+        .collect()
+}
+
+const CALLS: &str = r#"
+%! main.sp
+void SetHealth(int client, int amount, bool notify) {}
+void Foo(int client, int value)
+{
+    SetHealth(client, 100, true);
+    SetHealth(client, value, false);
+}
+"#;
+
+#[test]
+fn literals_only() {
+    assert_eq!(
+        hints(CALLS),
+        vec![
+            (3, 22, "amount:".to_string()),
+            (3, 27, "notify:".to_string()),
+            (4, 29, "notify:".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn all_arguments() {
+    assert_eq!(
+        hints(&format!(
+            "{CALLS}
+%! sourcepawn.toml
+[inlayHints]
+parameterHints = \"all\"
+"
+        )),
+        vec![
+            (3, 22, "amount:".to_string()),
+            (3, 27, "notify:".to_string()),
+            (4, 22, "amount:".to_string()),
+            (4, 29, "notify:".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn off() {
+    assert_eq!(
+        hints(&format!(
+            "{CALLS}
+%! sourcepawn.toml
+[inlayHints]
+parameterHints = \"off\"
+"
+        )),
+        Vec::<(u32, u32, String)>::new()
+    );
+}
+
+#[test]
+fn constructor() {
+    assert_eq!(
+        hints(
+            r#"
+%! main.sp
+methodmap Foo {
+    public Foo(int size) {}
+}
+void Bar()
+{
+    Foo foo = new Foo(10);
+}
+"#,
+        ),
+        vec![(5, 22, "size:".to_string())]
+    );
+}
+
+#[test]
+fn macro_expanded_call() {
+    assert_eq!(
+        hints(
+            r#"
+%! main.sp
+#define HEAL(%1) SetHealth(%1, 100, true)
+void SetHealth(int client, int amount, bool notify) {}
+void Foo(int client)
+{
+    HEAL(client);
+}
+"#,
+        ),
+        Vec::<(u32, u32, String)>::new()
+    );
+}
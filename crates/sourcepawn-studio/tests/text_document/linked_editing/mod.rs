@@ -0,0 +1,86 @@
+use lsp_types::{Position, Range};
+use sourcepawn_studio::fixture::linked_editing_ranges;
+
+fn range(line: u32, start: u32, end: u32) -> Range {
+    Range::new(Position::new(line, start), Position::new(line, end))
+}
+
+#[test]
+fn loop_variable() {
+    assert_eq!(
+        linked_editing_ranges(
+            r#"
+%! main.sp
+void Foo()
+{
+    for (int i = 0; i < 10; i++)
+             |
+    {
+        Bar(i);
+    }
+}
+
+void Bar(int i) {}
+"#,
+        ),
+        Some(vec![
+            range(2, 13, 14),
+            range(2, 20, 21),
+            range(2, 28, 29),
+            range(4, 12, 13),
+        ])
+    );
+}
+
+#[test]
+fn parameter() {
+    assert_eq!(
+        linked_editing_ranges(
+            r#"
+%! main.sp
+void Foo(int x)
+{
+    x = 1;
+    |
+}
+"#,
+        ),
+        Some(vec![range(0, 13, 14), range(2, 4, 5)])
+    );
+}
+
+#[test]
+fn global_is_not_linked() {
+    assert_eq!(
+        linked_editing_ranges(
+            r#"
+%! main.sp
+int g_Count;
+
+void Foo()
+{
+    g_Count = 1;
+    |
+}
+"#,
+        ),
+        None
+    );
+}
+
+#[test]
+fn single_occurrence_is_not_linked() {
+    assert_eq!(
+        linked_editing_ranges(
+            r#"
+%! main.sp
+void Foo()
+{
+    int x;
+        |
+}
+"#,
+        ),
+        None
+    );
+}
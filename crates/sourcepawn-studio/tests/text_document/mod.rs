@@ -1,4 +1,19 @@
+mod code_action;
+mod code_lens;
+mod compile;
 mod completion;
+mod diagnostics;
+mod document_highlight;
+mod document_links;
+mod document_symbols;
+mod folding_range;
+mod formatting;
 mod goto_definition;
 mod hover;
+mod inactive_regions;
+mod inlay_hints;
+mod linked_editing;
+mod rename;
+mod semantic_tokens;
 mod signature_help;
+mod syntax_tree;
@@ -0,0 +1,364 @@
+use lsp_types::{Position, Range};
+use sourcepawn_studio::fixture::{prepare_rename, rename};
+
+#[test]
+fn rename_local() {
+    assert_eq!(
+        rename(
+            r#"
+%! main.sp
+void Foo()
+{
+    int x = 1;
+        |
+    x++;
+}
+"#,
+            "y",
+        ),
+        Ok(r#"void Foo()
+{
+    int y = 1;
+    y++;
+}"#
+        .to_string())
+    );
+}
+
+#[test]
+fn rename_local_to_used_global() {
+    assert_eq!(
+        rename(
+            r#"
+%! main.sp
+int g_count;
+
+void Foo()
+{
+    int x = 1;
+        |
+    g_count += x;
+}
+"#,
+            "g_count",
+        ),
+        Err(
+            "cannot rename to `g_count`, it conflicts with the global variable `g_count`"
+                .to_string()
+        )
+    );
+}
+
+#[test]
+fn rename_local_to_unused_global() {
+    assert_eq!(
+        rename(
+            r#"
+%! main.sp
+int g_count;
+
+void Foo()
+{
+    int x = 1;
+        |
+    x++;
+}
+"#,
+            "g_count",
+        ),
+        Ok(r#"int g_count;
+
+void Foo()
+{
+    int g_count = 1;
+    g_count++;
+}"#
+        .to_string())
+    );
+}
+
+#[test]
+fn rename_global_to_local() {
+    assert_eq!(
+        rename(
+            r#"
+%! main.sp
+int g_count;
+    |
+
+void Foo()
+{
+    int x = 1;
+    g_count += x;
+}
+"#,
+            "x",
+        ),
+        Err("cannot rename to `x`, it conflicts with the local `x`".to_string())
+    );
+}
+
+#[test]
+fn rename_global_to_function() {
+    assert_eq!(
+        rename(
+            r#"
+%! main.sp
+int g_count;
+    |
+
+void Foo()
+{
+}
+"#,
+            "Foo",
+        ),
+        Err("cannot rename to `Foo`, it conflicts with the function `Foo`".to_string())
+    );
+}
+
+#[test]
+fn rename_macro_to_macro() {
+    assert_eq!(
+        rename(
+            r#"
+%! main.sp
+#define FOO 1
+         |
+#define BAR 2
+
+int x = FOO + BAR;
+"#,
+            "BAR",
+        ),
+        Err("cannot rename to `BAR`, it conflicts with the macro `BAR`".to_string())
+    );
+}
+
+#[test]
+fn rename_macro() {
+    assert_eq!(
+        rename(
+            r#"
+%! main.sp
+#define FOO 1
+         |
+
+#if defined FOO
+int x = FOO;
+#endif
+
+#undef FOO
+"#,
+            "BAR",
+        ),
+        Ok(r#"#define BAR 1
+
+#if defined BAR
+int x = BAR;
+#endif
+
+#undef BAR"#
+            .to_string())
+    );
+}
+
+#[test]
+fn rename_macro_from_expansion() {
+    assert_eq!(
+        rename(
+            r#"
+%! main.sp
+#define FOO(%1) %1 + 1
+
+#if FOO(1) == 2
+int x = FOO(2);
+        |
+#endif
+"#,
+            "BAR",
+        ),
+        Ok(r#"#define BAR(%1) %1 + 1
+
+#if BAR(1) == 2
+int x = BAR(2);
+#endif"#
+            .to_string())
+    );
+}
+
+#[test]
+fn rename_function_to_macro() {
+    assert_eq!(
+        rename(
+            r#"
+%! main.sp
+#define BAR 2
+
+void Foo()
+      |
+{
+}
+"#,
+            "BAR",
+        ),
+        Err("cannot rename to `BAR`, it conflicts with the macro `BAR`".to_string())
+    );
+}
+
+#[test]
+fn rename_field_to_global() {
+    assert_eq!(
+        rename(
+            r#"
+%! main.sp
+int count;
+
+enum struct Foo {
+    int x;
+        |
+}
+
+void Bar()
+{
+    Foo foo;
+    foo.x = count;
+}
+"#,
+            "count",
+        ),
+        Ok(r#"int count;
+
+enum struct Foo {
+    int count;
+}
+
+void Bar()
+{
+    Foo foo;
+    foo.count = count;
+}"#
+        .to_string())
+    );
+}
+
+#[test]
+fn rename_method_to_function() {
+    assert_eq!(
+        rename(
+            r#"
+%! main.sp
+methodmap Foo {
+    public void Run() {}
+                 |
+}
+
+void Start()
+{
+}
+
+void Bar(Foo foo)
+{
+    foo.Run();
+}
+"#,
+            "Start",
+        ),
+        Ok(r#"methodmap Foo {
+    public void Start() {}
+}
+
+void Start()
+{
+}
+
+void Bar(Foo foo)
+{
+    foo.Start();
+}"#
+        .to_string())
+    );
+}
+
+#[test]
+fn rename_to_keyword() {
+    assert_eq!(
+        rename(
+            r#"
+%! main.sp
+int x;
+    |
+"#,
+            "float",
+        ),
+        Err("`float` is not a valid identifier".to_string())
+    );
+}
+
+#[test]
+fn prepare_rename_local() {
+    assert_eq!(
+        prepare_rename(
+            r#"
+%! main.sp
+void Foo()
+{
+    int x = 1;
+    x++;
+    |
+}
+"#,
+        ),
+        Ok(Some(Range::new(Position::new(3, 4), Position::new(3, 5))))
+    );
+}
+
+#[test]
+fn prepare_rename_literal() {
+    assert_eq!(
+        prepare_rename(
+            r#"
+%! main.sp
+int x = 12;
+        |
+"#,
+        ),
+        Err("cannot rename a literal".to_string())
+    );
+}
+
+#[test]
+fn prepare_rename_keyword() {
+    assert_eq!(
+        prepare_rename(
+            r#"
+%! main.sp
+int Foo()
+{
+    return 1;
+    |
+}
+"#,
+        ),
+        Err("cannot rename a keyword".to_string())
+    );
+}
+
+#[test]
+fn prepare_rename_sourcemod_native() {
+    assert_eq!(
+        prepare_rename(
+            r#"
+%! main.sp
+#include <sourcemod>
+
+public void OnPluginStart()
+{
+    PrintToServer("");
+    |
+}
+"#,
+        ),
+        Err("cannot rename `PrintToServer`, it is defined in an include directory".to_string())
+    );
+}
@@ -0,0 +1,53 @@
+use sourcepawn_studio::fixture::semantic_tokens;
+
+#[test]
+fn range_is_slice_of_full() {
+    let (full, range) = semantic_tokens(
+        r#"
+%! main.sp
+#define MAX 10
+
+enum struct Foo {
+    int bar;
+}
+
+int Baz(Foo foo)
+{
+    int count = MAX;
+^^^^^^^^^^^^^^^^^^
+    for (int i = 0; i < count; i++)
+    {
+        foo.bar += i;
+^^^^^^^^^^^^^^^^^^^^^
+    }
+    return foo.bar;
+}
+"#,
+    );
+    assert!(!range.is_empty());
+    assert_eq!(
+        range,
+        full.into_iter()
+            .filter(|[line, ..]| (8..=11).contains(line))
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn range_of_whole_document_is_full() {
+    let (full, range) = semantic_tokens(
+        r#"
+%! main.sp
+int foo;
+^^^^^^^^
+
+public void OnPluginStart()
+{
+    foo = 1;
+}
+^
+"#,
+    );
+    assert!(!full.is_empty());
+    assert_eq!(range, full);
+}
@@ -0,0 +1,76 @@
+use sourcepawn_studio::fixture::syntax_tree;
+
+/// Whether a node of the tree is printed with its range and text, whatever its field and depth.
+fn has_node(tree: &str, node: &str) -> bool {
+    tree.lines().any(|line| line.ends_with(node))
+}
+
+#[test]
+fn whole_file() {
+    let tree = syntax_tree(
+        r#"
+%! main.sp
+int foo = 1;
+"#,
+    );
+
+    let lines = tree.lines().collect::<Vec<_>>();
+    assert_eq!(lines[0], "source_file@0..12");
+    assert!(lines[1..].iter().all(|line| line.starts_with("  ")));
+    assert!(has_node(&tree, "identifier@4..7 \"foo\""));
+    assert!(has_node(&tree, "int_literal@10..11 \"1\""));
+}
+
+#[test]
+fn smallest_node_of_the_selection() {
+    assert_eq!(
+        syntax_tree(
+            r#"
+%! main.sp
+void Foo()
+{
+    int foo = 1;
+        ^^^
+}
+"#,
+        ),
+        "identifier@21..24 \"foo\"\n"
+    );
+}
+
+#[test]
+fn selection_spanning_several_nodes() {
+    let tree = syntax_tree(
+        r#"
+%! main.sp
+void Foo()
+{
+    int foo = 1;
+        ^^^^^^^
+}
+"#,
+    );
+
+    let lines = tree.lines().collect::<Vec<_>>();
+    assert!(!lines[0].starts_with(' '));
+    assert!(!lines[0].starts_with("function_definition"));
+    assert!(has_node(&tree, "identifier@21..24 \"foo\""));
+    assert!(has_node(&tree, "int_literal@27..28 \"1\""));
+    assert!(!has_node(&tree, "identifier@5..8 \"Foo\""));
+}
+
+#[test]
+fn long_leaves_are_truncated() {
+    let tree = syntax_tree(&format!(
+        r#"
+%! main.sp
+char foo[] = "{}";
+"#,
+        "a".repeat(100)
+    ));
+
+    assert!(has_node(
+        &tree,
+        &format!("string_literal@13..115 \"\\\"{}...\"", "a".repeat(63))
+    ));
+}
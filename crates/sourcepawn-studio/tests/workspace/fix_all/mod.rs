@@ -0,0 +1,110 @@
+use sourcepawn_studio::fixture::fix_all;
+
+const FIXABLE: &str = r#"
+%! main.sp
+#define UNUSED 1
+
+#pragam semicolon 1
+
+public void OnPluginStart()
+{
+}
+"#;
+
+#[test]
+fn all_codes() {
+    assert_eq!(
+        fix_all(FIXABLE, None),
+        (
+            "Applied 2 fixes in 1 file (SP0013: 1, SPLINT-unused-macro: 1)".to_string(),
+            vec![(
+                "main.sp".to_string(),
+                "\n#pragma semicolon 1\n\npublic void OnPluginStart()\n{\n}".to_string()
+            )]
+        )
+    );
+}
+
+#[test]
+fn code_filter() {
+    assert_eq!(
+        fix_all(FIXABLE, Some("SPLINT-unused-macro")),
+        (
+            "Applied 1 fix in 1 file (SPLINT-unused-macro: 1)".to_string(),
+            vec![(
+                "main.sp".to_string(),
+                "\n#pragam semicolon 1\n\npublic void OnPluginStart()\n{\n}".to_string()
+            )]
+        )
+    );
+}
+
+#[test]
+fn overlapping_fixes_of_other_files() {
+    // Both diagnostics are fixed by loading the translation file in `main.sp`, the fix of
+    // `other.inc` is skipped.
+    assert_eq!(
+        fix_all(
+            r#"
+%! main.sp
+#include "other.inc"
+
+public void OnPluginStart()
+{
+    char buffer[64];
+    Format(buffer, sizeof(buffer), "%t", "Welcome");
+}
+
+%! other.inc
+native int Format(char[] buffer, int maxlength, const char[] format, any ...);
+
+void Foo()
+{
+    char buffer[64];
+    Format(buffer, sizeof(buffer), "%t", "Welcome");
+}
+
+%! translations/test.phrases.txt
+"Phrases"
+{
+    "Welcome"
+    {
+        "en"        "Welcome!"
+    }
+}
+"#,
+            Some("SPLINT-unloaded-translation"),
+        ),
+        (
+            "Applied 1 fix in 1 file (SPLINT-unloaded-translation: 1)".to_string(),
+            vec![(
+                "main.sp".to_string(),
+                r#"#include "other.inc"
+
+public void OnPluginStart()
+{
+    LoadTranslations("test.phrases");
+    char buffer[64];
+    Format(buffer, sizeof(buffer), "%t", "Welcome");
+}"#
+                .to_string()
+            )]
+        )
+    );
+}
+
+#[test]
+fn nothing_to_fix() {
+    assert_eq!(
+        fix_all(
+            r#"
+%! main.sp
+public void OnPluginStart()
+{
+}
+"#,
+            None,
+        ),
+        ("No fixes to apply".to_string(), vec![])
+    );
+}
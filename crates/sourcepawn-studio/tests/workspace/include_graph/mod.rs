@@ -0,0 +1,66 @@
+use sourcepawn_studio::fixture::include_graph;
+
+const FIXTURE: &str = r#"
+%! main.sp
+#include "a.inc"
+#tryinclude "b.inc"
+%! a.inc
+#include "c.inc"
+%! b.inc
+
+#include "c.inc"
+%! c.inc
+#include "d.inc"
+%! d.inc
+int d;
+%! other.sp
+int other;
+"#;
+
+fn edge(
+    source: &str,
+    target: &str,
+    kind: &str,
+    line: u32,
+) -> (String, String, String, Option<u32>) {
+    (
+        source.to_string(),
+        target.to_string(),
+        kind.to_string(),
+        Some(line),
+    )
+}
+
+#[test]
+fn document() {
+    assert_eq!(
+        include_graph(FIXTURE, Some("main.sp")),
+        (
+            vec![
+                "a.inc".to_string(),
+                "b.inc".to_string(),
+                "c.inc".to_string(),
+                "d.inc".to_string(),
+                "main.sp".to_string(),
+            ],
+            vec![
+                edge("a.inc", "c.inc", "Include", 0),
+                edge("b.inc", "c.inc", "Include", 1),
+                edge("c.inc", "d.inc", "Include", 0),
+                edge("main.sp", "a.inc", "Include", 0),
+                edge("main.sp", "b.inc", "TryInclude", 1),
+            ]
+        )
+    );
+}
+
+#[test]
+fn workspace() {
+    let (nodes, edges) = include_graph(FIXTURE, None);
+    assert_eq!(
+        nodes,
+        vec!["a.inc", "b.inc", "c.inc", "d.inc", "main.sp", "other.sp"]
+    );
+    // The includes of `c.inc` are listed once, although it is included by two files.
+    assert_eq!(edges.len(), 5);
+}
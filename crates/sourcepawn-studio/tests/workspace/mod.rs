@@ -0,0 +1,5 @@
+mod fix_all;
+mod include_graph;
+mod project_model;
+mod ssr;
+mod symbols;
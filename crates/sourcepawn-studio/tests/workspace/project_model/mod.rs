@@ -0,0 +1,85 @@
+use serde_json::json;
+use sourcepawn_studio::fixture::project_model;
+
+#[test]
+fn main_files_of_the_workspace() {
+    let res = project_model(
+        r#"
+%! scripting/a.sp
+#include "shared.sp"
+
+%! scripting/b.sp
+#include "shared.sp"
+
+%! scripting/shared.sp
+int g_Shared;
+
+%! scripting/include/orphan.inc
+int g_Orphan;
+"#,
+        None,
+    );
+
+    assert_eq!(res["workspaceRoots"], json!(["."]));
+    assert_eq!(
+        res["mainFiles"],
+        json!(["scripting/a.sp", "scripting/b.sp"])
+    );
+    let file = |uri: &str| {
+        res["files"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|file| file["uri"] == uri)
+            .cloned()
+            .unwrap()
+    };
+    assert_eq!(
+        file("scripting/shared.sp")["compiledAsPartOf"],
+        json!(["scripting/a.sp", "scripting/b.sp"])
+    );
+    assert_eq!(
+        file("scripting/a.sp"),
+        json!({
+            "uri": "scripting/a.sp",
+            "compiledAsPartOf": ["scripting/a.sp"],
+            "mainFile": "scripting/a.sp",
+            "pinned": false,
+        })
+    );
+    assert_eq!(
+        file("scripting/include/orphan.inc")["compiledAsPartOf"],
+        json!([])
+    );
+}
+
+#[test]
+fn pinned_main_file() {
+    let res = project_model(
+        r#"
+%! scripting/a.sp
+#include "shared.sp"
+
+%! scripting/b.sp
+#include "shared.sp"
+
+%! scripting/shared.sp
+int g_Shared;
+
+%! sourcepawn.toml
+[mainPaths]
+"scripting/shared.sp" = "scripting/b.sp"
+"#,
+        Some("scripting/shared.sp"),
+    );
+
+    assert_eq!(
+        res["files"],
+        json!([{
+            "uri": "scripting/shared.sp",
+            "compiledAsPartOf": ["scripting/a.sp", "scripting/b.sp"],
+            "mainFile": "scripting/b.sp",
+            "pinned": true,
+        }])
+    );
+}
@@ -0,0 +1,211 @@
+use sourcepawn_studio::fixture::ssr;
+
+#[test]
+fn ssr_match() {
+    assert_eq!(
+        ssr(
+            r#"
+%! main.sp
+int Foo(int x)
+{
+    return x;
+}
+
+void Bar()
+{
+    Foo(1);
+    Foo(2 + 3);
+    Foo(Foo(4));
+}
+"#,
+            "Foo($a)",
+        ),
+        Ok((
+            vec![
+                "Foo(1)".to_string(),
+                "Foo(2 + 3)".to_string(),
+                "Foo(Foo(4))".to_string()
+            ],
+            r#"int Foo(int x)
+{
+    return x;
+}
+
+void Bar()
+{
+    Foo(1);
+    Foo(2 + 3);
+    Foo(Foo(4));
+}"#
+            .to_string()
+        ))
+    );
+}
+
+#[test]
+fn ssr_replace() {
+    assert_eq!(
+        ssr(
+            r#"
+%! main.sp
+int Foo(int x, int y)
+{
+    return x - y;
+}
+
+void Bar(int a, int b)
+{
+    Foo(a, b + 1);
+}
+"#,
+            "Foo($x, $y) ==>> Foo($y, $x)",
+        )
+        .map(|(_, text)| text),
+        Ok(r#"int Foo(int x, int y)
+{
+    return x - y;
+}
+
+void Bar(int a, int b)
+{
+    Foo(b + 1, a);
+}"#
+        .to_string())
+    );
+}
+
+#[test]
+fn ssr_repeated_placeholder() {
+    assert_eq!(
+        ssr(
+            r#"
+%! main.sp
+int Max(int x, int y)
+{
+    return x > y ? x : y;
+}
+
+void Bar(int a, int b)
+{
+    Max(a, a);
+    Max(a, b);
+}
+"#,
+            "Max($x, $x) ==>> $x",
+        ),
+        Ok((
+            vec!["Max(a, a)".to_string()],
+            r#"int Max(int x, int y)
+{
+    return x > y ? x : y;
+}
+
+void Bar(int a, int b)
+{
+    a;
+    Max(a, b);
+}"#
+            .to_string()
+        ))
+    );
+}
+
+#[test]
+fn ssr_dollar_in_literals() {
+    assert_eq!(
+        ssr(
+            r#"
+%! main.sp
+void Bar(int client)
+{
+    PrintToServer("$%d", client);
+    PrintToServer("%d", client);
+}
+"#,
+            r#"PrintToServer("$%d", $c) ==>> PrintToServer("$ %d", $c)"#,
+        ),
+        Ok((
+            vec![r#"PrintToServer("$%d", client)"#.to_string()],
+            r#"void Bar(int client)
+{
+    PrintToServer("$ %d", client);
+    PrintToServer("%d", client);
+}"#
+            .to_string()
+        ))
+    );
+    assert_eq!(
+        ssr(
+            r#"
+%! main.sp
+void Bar(int c)
+{
+    Foo('$');
+}
+"#,
+            "Foo('$') ==>> Foo('$', '\\'')",
+        )
+        .map(|(_, text)| text),
+        Ok(r#"void Bar(int c)
+{
+    Foo('$', '\'');
+}"#
+        .to_string())
+    );
+}
+
+#[test]
+fn ssr_skip_macro_expansions() {
+    assert_eq!(
+        ssr(
+            r#"
+%! main.sp
+#define CALL_FOO Foo(1)
+
+int Foo(int x)
+{
+    return x;
+}
+
+void Bar()
+{
+    CALL_FOO;
+    Foo(2);
+}
+"#,
+            "Foo($a) ==>> Foo($a + 1)",
+        ),
+        Ok((
+            vec!["Foo(2)".to_string()],
+            r#"#define CALL_FOO Foo(1)
+
+int Foo(int x)
+{
+    return x;
+}
+
+void Bar()
+{
+    CALL_FOO;
+    Foo(2 + 1);
+}"#
+            .to_string()
+        ))
+    );
+}
+
+#[test]
+fn ssr_unknown_placeholder() {
+    assert_eq!(
+        ssr(
+            r#"
+%! main.sp
+void Bar()
+{
+}
+"#,
+            "Foo($a) ==>> Foo($b)",
+        ),
+        Err("the placeholder `$b` of the replacement is not in the search pattern".to_string())
+    );
+}
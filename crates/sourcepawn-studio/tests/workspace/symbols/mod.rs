@@ -0,0 +1,133 @@
+use sourcepawn_studio::fixture::workspace_symbols;
+
+fn symbols(symbols: &[(&str, Option<&str>)]) -> Vec<(String, Option<String>)> {
+    symbols
+        .iter()
+        .map(|(name, container_name)| (name.to_string(), container_name.map(ToString::to_string)))
+        .collect()
+}
+
+#[test]
+fn fuzzy_ranking() {
+    assert_eq!(
+        workspace_symbols(
+            r#"
+%! main.sp
+#include "foo.sp"
+
+void CxlientName() {}
+void ClientNameLength() {}
+void ClientName() {}
+void Foo() {}
+
+%! foo.sp
+void GetClientName() {}
+"#,
+            "clientname",
+        ),
+        symbols(&[
+            ("ClientName", None),
+            ("ClientNameLength", None),
+            ("GetClientName", None),
+            ("CxlientName", None),
+        ])
+    );
+}
+
+#[test]
+fn word_starts_rank_above_subsequences() {
+    assert_eq!(
+        workspace_symbols(
+            r#"
+%! main.sp
+void Recycle() {}
+void RegConsoleCmd() {}
+void Foo() {}
+"#,
+            "rcc",
+        ),
+        symbols(&[("RegConsoleCmd", None), ("Recycle", None)])
+    );
+}
+
+#[test]
+fn types_only() {
+    assert_eq!(
+        workspace_symbols(
+            r#"
+%! main.sp
+enum Foo
+{
+    Foo_Value,
+}
+
+methodmap FooMap
+{
+    public FooMap()
+    {
+        return view_as<FooMap>(1);
+    }
+}
+
+void Foo_Func() {}
+"#,
+            "#foo",
+        ),
+        symbols(&[("Foo", None), ("FooMap", None)])
+    );
+}
+
+#[test]
+fn functions_and_methods_only() {
+    assert_eq!(
+        workspace_symbols(
+            r#"
+%! main.sp
+enum Foo
+{
+    Foo_Value,
+}
+
+methodmap FooMap
+{
+    public FooMap()
+    {
+        return view_as<FooMap>(1);
+    }
+
+    public void FooMethod() {}
+}
+
+void Foo_Func()
+{
+    int foo_local;
+}
+"#,
+            "f foo",
+        ),
+        symbols(&[
+            ("FooMap", Some("FooMap")),
+            ("Foo_Func", None),
+            ("FooMethod", Some("FooMap")),
+        ])
+    );
+}
+
+#[test]
+fn locals_are_skipped() {
+    assert_eq!(
+        workspace_symbols(
+            r#"
+%! main.sp
+int foo_global;
+
+void Bar()
+{
+    int foo_local;
+}
+"#,
+            "foo_",
+        ),
+        symbols(&[("foo_global", None)])
+    );
+}
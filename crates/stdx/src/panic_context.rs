@@ -2,16 +2,29 @@
 //!
 //! FIXME: upstream to <https://github.com/kriomant/panic-context> ?
 
-use std::{cell::RefCell, panic, sync::Once};
+use std::{backtrace::Backtrace, cell::RefCell, panic, sync::Once};
 
 pub fn enter(context: String) -> PanicContext {
-    static ONCE: Once = Once::new();
-    ONCE.call_once(PanicContext::init);
+    install();
+    // Forget the panics which were already handled.
+    LAST_PANIC.with(|last| last.borrow_mut().take());
 
     with_ctx(|ctx| ctx.push(context));
     PanicContext { _priv: () }
 }
 
+/// Install the panic hook, which is otherwise installed when a context is first entered.
+pub fn install() {
+    static ONCE: Once = Once::new();
+    ONCE.call_once(PanicContext::init);
+}
+
+/// Take the report of the last panic of the current thread, with its message, its context and
+/// its backtrace.
+pub fn take_last_panic() -> Option<String> {
+    LAST_PANIC.with(|last| last.borrow_mut().take())
+}
+
 #[must_use]
 pub struct PanicContext {
     _priv: (),
@@ -30,6 +43,14 @@ impl PanicContext {
                         eprintln!("> {frame}\n");
                     }
                 }
+                let report = format!(
+                    "{panic_info}\n\nPanic context:\n{}\nBacktrace:\n{}",
+                    ctx.iter()
+                        .map(|frame| format!("> {frame}\n"))
+                        .collect::<String>(),
+                    Backtrace::force_capture()
+                );
+                LAST_PANIC.with(|last| *last.borrow_mut() = Some(report));
                 default_hook(panic_info);
             });
         };
@@ -43,6 +64,10 @@ impl Drop for PanicContext {
     }
 }
 
+thread_local! {
+    static LAST_PANIC: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
 fn with_ctx(f: impl FnOnce(&mut Vec<String>)) {
     thread_local! {
         static CTX: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
@@ -275,6 +275,7 @@ pub enum TSKind {
     anon_call_arguments_repeat1_ = 270,
     anon_array_literal_repeat1_ = 271,
     anon_string_literal_repeat1_ = 272,
+    ERROR = 65535,
 }
 impl From<tree_sitter::Node<'_>> for TSKind {
     fn from(v: tree_sitter::Node<'_>) -> Self {
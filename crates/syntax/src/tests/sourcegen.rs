@@ -39,12 +39,16 @@ fn generate_node_kinds() {
         let kind_id: TokenStream = format!("{}", kind_id).parse().unwrap();
         quote! { #name = #kind_id }
     });
+    // The error nodes are not part of the kinds of the grammar, they use the reserved
+    // `ts_builtin_sym_error` id.
+    let error_kind_id: TokenStream = format!("{}", u16::MAX).parse().unwrap();
     let stream = quote! {
         #![allow(bad_style, missing_docs, unreachable_pub, unused)]
         #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
         #[repr(u16)]
         pub enum TSKind {
-            #(#entries),*
+            #(#entries),*,
+            ERROR = #error_kind_id
         }
 
         impl From<tree_sitter::Node<'_>> for TSKind {
@@ -19,10 +19,14 @@ let output: OutputChannel;
 
 /**
  * Callback for the Compile file command.
- * @param  {URI} args URI of the document to be compiled. This will be overrided if MainPathCompilation is set to true.
+ * @param  {URI | string} args URI of the document to be compiled, as a string when the command comes from
+ * a code lens. This will be overrided if MainPathCompilation is set to true.
  * @returns Promise
  */
-export async function run(args: URI): Promise<number> {
+export async function run(args: URI | string): Promise<number> {
+  if (typeof args === "string") {
+    args = URI.parse(args);
+  }
   let fileToCompilePath: string;
 
   // If we always compile the main path, we always ignore the path of the current editor
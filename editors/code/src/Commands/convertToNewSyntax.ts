@@ -0,0 +1,28 @@
+import * as vscode from "vscode";
+import { convertToNewSyntax, ConvertToNewSyntaxParams } from "../lsp_ext";
+import { Cmd, CtxInit } from "../ctx";
+
+export function convertToNewSyntaxCommand(ctx: CtxInit): Cmd {
+  return async () => {
+    const params: ConvertToNewSyntaxParams = {};
+    const doc = vscode.window.activeTextEditor?.document;
+    if (doc !== undefined && doc.languageId === "sourcepawn") {
+      const scope = await vscode.window.showQuickPick(["Current file", "Workspace"], {
+        placeHolder: "Files to convert",
+      });
+      if (scope === undefined) {
+        return;
+      }
+      if (scope === "Current file") {
+        params.textDocument = ctx.client.code2ProtocolConverter.asTextDocumentIdentifier(doc);
+      }
+    }
+
+    const result = await ctx.client.sendRequest(convertToNewSyntax, params);
+    if (result.conversions > 0) {
+      const edit = await ctx.client.protocol2CodeConverter.asWorkspaceEdit(result.edit);
+      await vscode.workspace.applyEdit(edit);
+    }
+    void vscode.window.showInformationMessage(result.summary);
+  };
+}
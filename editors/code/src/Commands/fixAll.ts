@@ -0,0 +1,50 @@
+import * as vscode from "vscode";
+import { fixAll, FixAllParams } from "../lsp_ext";
+import { Cmd, CtxInit } from "../ctx";
+
+const ALL_CODES = "All diagnostics";
+
+export function fixAllCommand(ctx: CtxInit): Cmd {
+  return async () => {
+    const codes = new Set<string>();
+    for (const [_, diagnostics] of vscode.languages.getDiagnostics()) {
+      for (const diagnostic of diagnostics) {
+        if (diagnostic.source !== "sourcepawn-studio" || diagnostic.code === undefined) {
+          continue;
+        }
+        const code = typeof diagnostic.code === "object" ? diagnostic.code.value : diagnostic.code;
+        codes.add(code.toString());
+      }
+    }
+    const code = await vscode.window.showQuickPick([ALL_CODES, ...Array.from(codes).sort()], {
+      placeHolder: "Diagnostic code to fix",
+    });
+    if (code === undefined) {
+      return;
+    }
+
+    const params: FixAllParams = {};
+    if (code !== ALL_CODES) {
+      params.code = code;
+    }
+    const doc = vscode.window.activeTextEditor?.document;
+    if (doc !== undefined && doc.languageId === "sourcepawn") {
+      const scope = await vscode.window.showQuickPick(["Current file", "Workspace"], {
+        placeHolder: "Files to fix",
+      });
+      if (scope === undefined) {
+        return;
+      }
+      if (scope === "Current file") {
+        params.textDocument = ctx.client.code2ProtocolConverter.asTextDocumentIdentifier(doc);
+      }
+    }
+
+    const result = await ctx.client.sendRequest(fixAll, params);
+    if (result.fixes > 0) {
+      const edit = await ctx.client.protocol2CodeConverter.asWorkspaceEdit(result.edit);
+      await vscode.workspace.applyEdit(edit);
+    }
+    void vscode.window.showInformationMessage(result.summary);
+  };
+}
@@ -1,5 +1,10 @@
 import * as vscode from "vscode";
-import { preprocessedDocument, PreprocessedDocumentParams } from "../lsp_ext";
+import {
+  preprocessedDocument,
+  PreprocessedDocumentParams,
+  preprocessedSourceMap,
+  SourceMapping,
+} from "../lsp_ext";
 import { isSPFile, sleep } from "../spUtils";
 import { Cmd, CtxInit } from "../ctx";
 
@@ -9,6 +14,9 @@ export function preprocessedDocumentCommand(ctx: CtxInit): Cmd {
       "sourcepawn-studio-preprocessed-file://preprocessedFile/file.sp"
     );
     readonly eventEmitter = new vscode.EventEmitter<vscode.Uri>();
+    // Source map of the last preprocessed document, used to sync the cursors.
+    private sourceUri: vscode.Uri | undefined;
+    private sourceMap: SourceMapping[] = [];
     constructor() {
       vscode.workspace.onDidChangeTextDocument(
         this.onDidChangeTextDocument,
@@ -20,6 +28,60 @@ export function preprocessedDocumentCommand(ctx: CtxInit): Cmd {
         this,
         ctx.subscriptions
       );
+      vscode.window.onDidChangeTextEditorSelection(
+        this.onDidChangeTextEditorSelection,
+        this,
+        ctx.subscriptions
+      );
+    }
+
+    private onDidChangeTextEditorSelection(
+      event: vscode.TextEditorSelectionChangeEvent
+    ) {
+      if (
+        event.textEditor.document.uri.toString() !== this.sourceUri?.toString()
+      ) {
+        return;
+      }
+      const preprocessedEditor = vscode.window.visibleTextEditors.find(
+        (editor) => editor.document.uri.toString() === this.uri.toString()
+      );
+      if (preprocessedEditor === undefined) {
+        return;
+      }
+      const position = event.selections[0].active;
+      // Pick the smallest mapped range containing the cursor.
+      let best: SourceMapping | undefined;
+      for (const mapping of this.sourceMap) {
+        const range = ctx.client.protocol2CodeConverter.asRange(
+          mapping.sourceRange
+        );
+        if (!range.contains(position)) {
+          continue;
+        }
+        if (
+          best === undefined ||
+          ctx.client.protocol2CodeConverter
+            .asRange(best.sourceRange)
+            .contains(range)
+        ) {
+          best = mapping;
+        }
+      }
+      if (best === undefined) {
+        return;
+      }
+      const range = ctx.client.protocol2CodeConverter.asRange(
+        best.preprocessedRange
+      );
+      preprocessedEditor.selection = new vscode.Selection(
+        range.start,
+        range.end
+      );
+      preprocessedEditor.revealRange(
+        range,
+        vscode.TextEditorRevealType.InCenterIfOutsideViewport
+      );
     }
 
     private onDidChangeTextDocument(event: vscode.TextDocumentChangeEvent) {
@@ -53,6 +115,10 @@ export function preprocessedDocumentCommand(ctx: CtxInit): Cmd {
       if (text === undefined) {
         return "";
       }
+      this.sourceUri = doc.uri;
+      this.sourceMap =
+        (await ctx?.client.sendRequest(preprocessedSourceMap, params, ct)) ??
+        [];
       return text;
     }
 
@@ -0,0 +1,43 @@
+import * as vscode from "vscode";
+import { projectModel } from "../lsp_ext";
+import { Cmd, CtxInit } from "../ctx";
+
+export function projectModelCommand(ctx: CtxInit): Cmd {
+  const tdcp = new (class implements vscode.TextDocumentContentProvider {
+    readonly uri = vscode.Uri.parse(
+      "sourcepawn-studio-project-model://projectModel.json"
+    );
+    readonly eventEmitter = new vscode.EventEmitter<vscode.Uri>();
+
+    async provideTextDocumentContent(
+      _uri: vscode.Uri,
+      ct: vscode.CancellationToken
+    ): Promise<string> {
+      const model = await ctx?.client.sendRequest(projectModel, {}, ct);
+      if (model === undefined) {
+        return "";
+      }
+      return JSON.stringify(model, null, 2);
+    }
+
+    get onDidChange(): vscode.Event<vscode.Uri> {
+      return this.eventEmitter.event;
+    }
+  })();
+
+  ctx.pushExtCleanup(
+    vscode.workspace.registerTextDocumentContentProvider(
+      "sourcepawn-studio-project-model",
+      tdcp
+    )
+  );
+
+  return async () => {
+    const document = await vscode.workspace.openTextDocument(tdcp.uri);
+    tdcp.eventEmitter.fire(tdcp.uri);
+    void (await vscode.window.showTextDocument(document, {
+      viewColumn: vscode.ViewColumn.Two,
+      preserveFocus: true,
+    }));
+  };
+}
@@ -19,9 +19,18 @@ import { preprocessedDocumentCommand } from "./preprocessedDocument";
 import { syntaxTreeCommand } from "./syntaxTree";
 import { itemTreeCommand } from "./itemTree";
 import { analyzerStatusCommand } from "./analyzerStatus";
+import { projectModelCommand } from "./projectModel";
+import { fixAllCommand } from "./fixAll";
+import { convertToNewSyntaxCommand } from "./convertToNewSyntax";
+import { ssrCommand } from "./ssr";
+import { writeDiagnosticsBaselineCommand } from "./writeDiagnosticsBaseline";
+import { runFlycheckCommand } from "./runFlycheck";
+import { clearMainFileCommand, setMainFileCommand } from "./setMainFile";
+import { toggleForcedBranchCommand } from "./toggleForcedBranch";
 import { Cmd, CommandFactory, Ctx, CtxInit } from "../ctx";
 import * as lc from "vscode-languageclient";
 import { LINKED_COMMANDS } from "../client";
+import * as sp from "../lsp_ext";
 
 /**
  * Register all the vscode.commands of the extension.
@@ -150,6 +159,51 @@ function gotoLocation(ctx: CtxInit): Cmd {
   };
 }
 
+function showReferences(ctx: CtxInit): Cmd {
+  return async (uri: string, position: lc.Position, locations: lc.Location[]) => {
+    const client = ctx.client;
+    await vscode.commands.executeCommand(
+      "editor.action.showReferences",
+      vscode.Uri.parse(uri),
+      client.protocol2CodeConverter.asPosition(position),
+      locations.map(client.protocol2CodeConverter.asLocation)
+    );
+  };
+}
+
+function triggerRename(ctx: CtxInit): Cmd {
+  return async (uri: string, position: lc.Position) => {
+    const client = ctx.client;
+    const editor = await vscode.window.showTextDocument(vscode.Uri.parse(uri));
+    const pos = client.protocol2CodeConverter.asPosition(position);
+    editor.selection = new vscode.Selection(pos, pos);
+    await vscode.commands.executeCommand("editor.action.rename");
+  };
+}
+
+function applySnippetWorkspaceEdit(ctx: CtxInit): Cmd {
+  return async (edit: sp.SnippetWorkspaceEdit) => {
+    const client = ctx.client;
+    for (const [uri, edits] of Object.entries(edit.changes)) {
+      const editor = await vscode.window.showTextDocument(vscode.Uri.parse(uri));
+      // Apply the edits from the end of the document, so the ranges of the others stay valid.
+      const sorted = [...edits].sort(
+        (a, b) =>
+          b.range.start.line - a.range.start.line ||
+          b.range.start.character - a.range.start.character
+      );
+      for (const it of sorted) {
+        const range = client.protocol2CodeConverter.asRange(it.range);
+        if (it.insertTextFormat === lc.InsertTextFormat.Snippet) {
+          await editor.insertSnippet(new vscode.SnippetString(it.newText), range);
+        } else {
+          await editor.edit((builder) => builder.replace(range, it.newText));
+        }
+      }
+    }
+  };
+}
+
 /**
  * Prepare a record of server specific commands.
  * @returns Record
@@ -193,9 +247,45 @@ export function createServerCommands(): Record<string, CommandFactory> {
     analyzerStatus: {
       enabled: analyzerStatusCommand,
     },
+    projectModel: {
+      enabled: projectModelCommand,
+    },
+    fixAll: {
+      enabled: fixAllCommand,
+    },
+    convertToNewSyntax: {
+      enabled: convertToNewSyntaxCommand,
+    },
+    ssr: {
+      enabled: ssrCommand,
+    },
+    writeDiagnosticsBaseline: {
+      enabled: writeDiagnosticsBaselineCommand,
+    },
+    runFlycheck: {
+      enabled: runFlycheckCommand,
+    },
+    setMainFile: {
+      enabled: setMainFileCommand,
+    },
+    clearMainFile: {
+      enabled: clearMainFileCommand,
+    },
+    toggleForcedBranch: {
+      enabled: toggleForcedBranchCommand,
+    },
     gotoLocation: {
       enabled: gotoLocation,
     },
+    showReferences: {
+      enabled: showReferences,
+    },
+    triggerRename: {
+      enabled: triggerRename,
+    },
+    applySnippetWorkspaceEdit: {
+      enabled: applySnippetWorkspaceEdit,
+    },
     linkToCommand: { enabled: linkToCommand },
   };
 }
@@ -0,0 +1,16 @@
+import * as vscode from "vscode";
+import { runFlycheck } from "../lsp_ext";
+import { Cmd, CtxInit } from "../ctx";
+
+export function runFlycheckCommand(ctx: CtxInit): Cmd {
+  return async () => {
+    const editor = vscode.window.activeTextEditor;
+    const textDocument =
+      editor !== undefined && editor.document.languageId === "sourcepawn"
+        ? ctx.client.code2ProtocolConverter.asTextDocumentIdentifier(
+            editor.document
+          )
+        : null;
+    await ctx.client.sendNotification(runFlycheck, { textDocument });
+  };
+}
@@ -0,0 +1,49 @@
+import * as vscode from "vscode";
+import { setProjectMainPath } from "../lsp_ext";
+import { Cmd, CtxInit } from "../ctx";
+
+function targetUri(uri?: vscode.Uri): vscode.Uri | undefined {
+  if (uri !== undefined) {
+    return uri;
+  }
+  const doc = vscode.window.activeTextEditor?.document;
+  if (doc === undefined || doc.languageId !== "sourcepawn") {
+    return undefined;
+  }
+  return doc.uri;
+}
+
+export function setMainFileCommand(ctx: CtxInit): Cmd {
+  return async (uri?: vscode.Uri) => {
+    const target = targetUri(uri);
+    if (target === undefined) {
+      return;
+    }
+    const mainUris = await vscode.window.showOpenDialog({
+      canSelectMany: false,
+      filters: { SourcePawn: ["sp"] },
+      openLabel: "Set as main file",
+      title: `Main file of ${vscode.workspace.asRelativePath(target)}`,
+    });
+    if (mainUris === undefined || mainUris.length === 0) {
+      return;
+    }
+    await ctx.client.sendRequest(setProjectMainPath, {
+      uri: target.toString(),
+      mainUri: mainUris[0].toString(),
+    });
+  };
+}
+
+export function clearMainFileCommand(ctx: CtxInit): Cmd {
+  return async (uri?: vscode.Uri) => {
+    const target = targetUri(uri);
+    if (target === undefined) {
+      return;
+    }
+    await ctx.client.sendRequest(setProjectMainPath, {
+      uri: target.toString(),
+      mainUri: null,
+    });
+  };
+}
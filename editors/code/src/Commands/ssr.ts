@@ -0,0 +1,51 @@
+import * as vscode from "vscode";
+import { ssr } from "../lsp_ext";
+import { Cmd, CtxInit } from "../ctx";
+
+export function ssrCommand(ctx: CtxInit): Cmd {
+  return async () => {
+    const query = await vscode.window.showInputBox({
+      title: "Structural Search Replace",
+      prompt: "Pattern to search, and its optional replacement, e.g `Foo($a) ==>> Bar($a)`",
+      placeHolder: "GetClientOfUserId(GetClientUserId($c)) ==>> $c",
+      validateInput: async (query: string) => {
+        try {
+          await ctx.client.sendRequest(ssr, { query, parseOnly: true });
+          return null;
+        } catch (e) {
+          return String(e);
+        }
+      },
+    });
+    if (query === undefined) {
+      return;
+    }
+
+    const result = await ctx.client.sendRequest(ssr, { query, parseOnly: false });
+    if (result.matches.length === 0) {
+      void vscode.window.showInformationMessage("No code matches the pattern.");
+      return;
+    }
+    if (query.includes("==>>")) {
+      const edit = await ctx.client.protocol2CodeConverter.asWorkspaceEdit(result.edit);
+      await vscode.workspace.applyEdit(edit);
+      void vscode.window.showInformationMessage(`Replaced ${result.matches.length} matches.`);
+      return;
+    }
+
+    const items = result.matches.map((location) => {
+      const uri = vscode.Uri.parse(location.uri);
+      return {
+        label: `${vscode.workspace.asRelativePath(uri)}:${location.range.start.line + 1}`,
+        uri,
+        range: ctx.client.protocol2CodeConverter.asRange(location.range),
+      };
+    });
+    const item = await vscode.window.showQuickPick(items, {
+      placeHolder: `${items.length} matches`,
+    });
+    if (item !== undefined) {
+      await vscode.window.showTextDocument(item.uri, { selection: item.range });
+    }
+  };
+}
@@ -40,16 +40,22 @@ export function syntaxTreeCommand(ctx: CtxInit): Cmd {
       ct: vscode.CancellationToken
     ): Promise<string> {
       const params: SyntaxTreeParams = {};
-      const doc = vscode.window.activeTextEditor?.document;
-      if (doc === undefined) {
+      const editor = vscode.window.activeTextEditor;
+      const doc = editor?.document;
+      if (editor === undefined || doc === undefined) {
         return "";
       }
+      if (!editor.selection.isEmpty) {
+        params.range = ctx?.client.code2ProtocolConverter.asRange(
+          editor.selection
+        );
+      }
       params.textDocument =
         ctx?.client.code2ProtocolConverter.asTextDocumentIdentifier(doc);
       if (params.textDocument === undefined) {
         return "";
       }
-      const text = await ctx?.client.sendRequest(syntaxTree, params);
+      const text = await ctx?.client.sendRequest(syntaxTree, params, ct);
       if (text === undefined) {
         return "";
       }
@@ -0,0 +1,27 @@
+import * as vscode from "vscode";
+import { toggleForcedBranch } from "../lsp_ext";
+import { Cmd, CtxInit } from "../ctx";
+
+export function toggleForcedBranchCommand(ctx: CtxInit): Cmd {
+  return async () => {
+    const editor = vscode.window.activeTextEditor;
+    if (editor === undefined || editor.document.languageId !== "sourcepawn") {
+      return;
+    }
+    const forced = await ctx.client.sendRequest(toggleForcedBranch, {
+      textDocument:
+        ctx.client.code2ProtocolConverter.asTextDocumentIdentifier(
+          editor.document
+        ),
+      position: ctx.client.code2ProtocolConverter.asPosition(
+        editor.selection.active
+      ),
+    });
+    void vscode.window.setStatusBarMessage(
+      forced
+        ? "This branch is now forced active for the analysis."
+        : "This branch is no longer forced active.",
+      3000
+    );
+  };
+}
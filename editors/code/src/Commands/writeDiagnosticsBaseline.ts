@@ -0,0 +1,13 @@
+import * as vscode from "vscode";
+import { writeDiagnosticsBaseline } from "../lsp_ext";
+import { Cmd, CtxInit } from "../ctx";
+
+export function writeDiagnosticsBaselineCommand(ctx: CtxInit): Cmd {
+  return async () => {
+    const result = await ctx.client.sendRequest(writeDiagnosticsBaseline);
+    const path = vscode.Uri.parse(result.uri).fsPath;
+    void vscode.window.showInformationMessage(
+      `Wrote ${result.diagnostics} diagnostics to the baseline ${path}.`
+    );
+  };
+}
@@ -62,6 +62,40 @@ function renderHoverActions(
   return result;
 }
 
+function isSnippetEdit(edit: sp.SnippetWorkspaceEdit): boolean {
+  return Object.values(edit.changes).some((edits) =>
+    edits.some((it) => it.insertTextFormat === lc.InsertTextFormat.Snippet)
+  );
+}
+
+// VSCode does not support snippets in the edits of the code actions, their edits are applied
+// by a command instead.
+async function asCodeAction(
+  client: lc.LanguageClient,
+  action: sp.CodeAction,
+  token: vscode.CancellationToken
+): Promise<vscode.CodeAction> {
+  if (action.edit === undefined || !isSnippetEdit(action.edit)) {
+    return client.protocol2CodeConverter.asCodeAction(
+      action as lc.CodeAction,
+      token
+    );
+  }
+  const res = new vscode.CodeAction(
+    action.title,
+    action.kind === undefined
+      ? undefined
+      : client.protocol2CodeConverter.asCodeActionKind(action.kind)
+  );
+  res.isPreferred = action.isPreferred;
+  res.command = {
+    title: action.title,
+    command: "sourcepawn-vscode.applySnippetWorkspaceEdit",
+    arguments: [action.edit],
+  };
+  return res;
+}
+
 export async function createClient(
   traceOutputChannel: vscode.OutputChannel,
   outputChannel: vscode.OutputChannel,
@@ -120,6 +154,40 @@ export async function createClient(
           }
         );
     },
+    async provideCodeActions(
+      document: vscode.TextDocument,
+      range: vscode.Range,
+      context: vscode.CodeActionContext,
+      token: vscode.CancellationToken,
+      _next: lc.ProvideCodeActionsSignature
+    ) {
+      const params: lc.CodeActionParams = {
+        textDocument:
+          client.code2ProtocolConverter.asTextDocumentIdentifier(document),
+        range: client.code2ProtocolConverter.asRange(range),
+        context: await client.code2ProtocolConverter.asCodeActionContext(
+          context,
+          token
+        ),
+      };
+      return client.sendRequest(sp.codeAction, params, token).then(
+        async (actions) => {
+          if (!actions) return null;
+          return Promise.all(
+            actions.map((action) => asCodeAction(client, action, token))
+          );
+        },
+        (error) => {
+          client.handleFailedRequest(
+            lc.CodeActionRequest.type,
+            token,
+            error,
+            null
+          );
+          return Promise.resolve(null);
+        }
+      );
+    },
   };
   clientOptions.markdown = {
     supportHtml: true,
@@ -152,8 +220,14 @@ class ExperimentalFeatures implements lc.StaticFeature {
       colorDiagnosticOutput: true,
       openServerLogs: true,
       localDocs: true,
+      inactiveRegionsNotification: true,
       commands: {
-        commands: ["sourcepawn-vscode.gotoLocation"],
+        commands: [
+          "sourcepawn-vscode.gotoLocation",
+          "sourcepawn-vscode.showReferences",
+          "sourcepawn-vscode.compileSM",
+          "sourcepawn-vscode.triggerRename",
+        ],
       },
       ...capabilities.experimental,
     };
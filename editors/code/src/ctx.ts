@@ -8,6 +8,7 @@ import { LazyOutputChannel } from "./spUtils";
 import { createClient } from "./client";
 import { execFile } from "child_process";
 import { Section, getConfig } from "./configUtils";
+import { InactiveRegions } from "./inactiveRegions";
 
 export type CommandFactory = {
   enabled: (ctx: CtxInit) => Cmd;
@@ -48,6 +49,7 @@ class SpCompStatus {
 export class Ctx {
   readonly serverStatusBar: vscode.StatusBarItem;
   readonly spcompStatus: SpCompStatus;
+  readonly inactiveRegions: InactiveRegions;
 
   private _client: lc.LanguageClient | undefined;
   private _serverPath: string | undefined;
@@ -76,6 +78,7 @@ export class Ctx {
     this.serverStatusBar.show();
 
     this.spcompStatus = new SpCompStatus();
+    this.inactiveRegions = new InactiveRegions();
 
     this.clientSubscriptions = [];
     this.commandDisposables = [];
@@ -108,6 +111,7 @@ export class Ctx {
 
   dispose() {
     this.serverStatusBar.dispose();
+    this.inactiveRegions.dispose();
     void this.disposeClient();
     this.commandDisposables.forEach((disposable) => disposable.dispose());
   }
@@ -173,6 +177,9 @@ export class Ctx {
       this.pushClientCleanup(
         this._client.onNotification(lsp_ext.spcompStatus, (params) => this.setSpcompStatus(params))
       );
+      this.pushClientCleanup(
+        this._client.onNotification(lsp_ext.inactiveRegions, (params) => this.inactiveRegions.update(params))
+      );
       // this.pushClientCleanup(
       //   this._client.onNotification(lsp_ext.openServerLogs, () => {
       //     this.outputChannel!.show();
@@ -313,6 +320,9 @@ export class Ctx {
         });
         return;
     }
+    if (status.experimentalFlags?.length) {
+      statusBar.tooltip.appendText(`\n\nExperimental analyses: ${status.experimentalFlags.join(", ")}`);
+    }
     if (statusBar.tooltip.value) {
       statusBar.tooltip.appendText("\n\n");
     }
@@ -0,0 +1,45 @@
+import * as vscode from "vscode";
+
+import * as lsp_ext from "./lsp_ext";
+
+/**
+ * Dims the regions of the documents which are excluded by the preprocessor.
+ */
+export class InactiveRegions implements vscode.Disposable {
+  private readonly decorationType = vscode.window.createTextEditorDecorationType({
+    opacity: "0.5",
+    rangeBehavior: vscode.DecorationRangeBehavior.ClosedClosed,
+  });
+  private readonly regions = new Map<string, vscode.Range[]>();
+  private readonly disposables: vscode.Disposable[] = [];
+
+  constructor() {
+    this.disposables.push(
+      vscode.window.onDidChangeVisibleTextEditors((editors) => editors.forEach((editor) => this.apply(editor))),
+      vscode.workspace.onDidCloseTextDocument((document) => this.regions.delete(document.uri.toString()))
+    );
+  }
+
+  update(params: lsp_ext.InactiveRegionsParams) {
+    const uri = vscode.Uri.parse(params.textDocument.uri).toString();
+    this.regions.set(
+      uri,
+      params.regions.map(
+        (range) => new vscode.Range(range.start.line, range.start.character, range.end.line, range.end.character)
+      )
+    );
+    vscode.window.visibleTextEditors
+      .filter((editor) => editor.document.uri.toString() === uri)
+      .forEach((editor) => this.apply(editor));
+  }
+
+  private apply(editor: vscode.TextEditor) {
+    const regions = this.regions.get(editor.document.uri.toString());
+    editor.setDecorations(this.decorationType, regions ?? []);
+  }
+
+  dispose() {
+    this.decorationType.dispose();
+    this.disposables.forEach((disposable) => disposable.dispose());
+  }
+}
@@ -14,12 +14,24 @@ export type PreprocessedDocumentParams = {
   textDocument?: lc.TextDocumentIdentifier;
 };
 
+export const preprocessedSourceMap = new lc.RequestType<
+  PreprocessedDocumentParams,
+  SourceMapping[],
+  void
+>("sourcepawn-studio/preprocessedSourceMap");
+
+export type SourceMapping = {
+  sourceRange: lc.Range;
+  preprocessedRange: lc.Range;
+};
+
 export const syntaxTree = new lc.RequestType<SyntaxTreeParams, string, void>(
   "sourcepawn-studio/syntaxTree"
 );
 
 export type SyntaxTreeParams = {
   textDocument?: lc.TextDocumentIdentifier;
+  range?: lc.Range;
 };
 
 export const analyzerStatus = new lc.RequestType<
@@ -50,6 +62,23 @@ export type ProjectMainPathParams = {
   uri?: lc.URI;
 };
 
+export const setProjectMainPath = new lc.RequestType<
+  SetProjectMainPathParams,
+  void,
+  void
+>("sourcepawn-studio/setProjectMainPath");
+
+export type SetProjectMainPathParams = {
+  uri: lc.URI;
+  mainUri: lc.URI | null;
+};
+
+export const toggleForcedBranch = new lc.RequestType<
+  lc.TextDocumentPositionParams,
+  boolean,
+  void
+>("sourcepawn-studio/toggleForcedBranch");
+
 export const projectsGraphviz = new lc.RequestType<
   ProjectsGraphvizParams,
   string,
@@ -60,6 +89,133 @@ export type ProjectsGraphvizParams = {
   textDocument?: lc.TextDocumentIdentifier;
 };
 
+export const projectModel = new lc.RequestType<
+  ProjectModelParams,
+  ProjectModelResult,
+  void
+>("sourcepawn-studio/projectModel");
+
+export type ProjectModelParams = {
+  textDocument?: lc.TextDocumentIdentifier;
+};
+
+export type ProjectModelResult = {
+  workspaceRoots: string[];
+  scriptingRoots: string[];
+  includeDirectories: string[];
+  mainFiles: string[];
+  files: ProjectModelFile[];
+};
+
+export type ProjectModelFile = {
+  uri: string;
+  compiledAsPartOf: string[];
+  mainFile?: string;
+  pinned: boolean;
+};
+
+export const includeGraph = new lc.RequestType<
+  IncludeGraphParams,
+  IncludeGraphResult,
+  void
+>("sourcepawn-studio/includeGraph");
+
+export type IncludeGraphParams = {
+  textDocument?: lc.TextDocumentIdentifier;
+};
+
+export type IncludeGraphResult = {
+  nodes: string[];
+  edges: IncludeGraphEdge[];
+};
+
+export type IncludeGraphEdge = {
+  source: string;
+  target: string;
+  kind: "include" | "tryInclude";
+  range?: lc.Range;
+};
+
+export const macros = new lc.RequestType<
+  MacrosParams,
+  MacroDefinition[],
+  void
+>("sourcepawn-studio/macros");
+
+export type MacrosParams = {
+  textDocument: lc.TextDocumentIdentifier;
+};
+
+export type MacroDefinition = {
+  name: string;
+  params?: string[];
+  body: string;
+  uri: string;
+  range?: lc.Range;
+};
+
+export const explainDiagnostic = new lc.RequestType<
+  ExplainDiagnosticParams,
+  string | null,
+  void
+>("sourcepawn-studio/explain");
+
+export type ExplainDiagnosticParams = {
+  code: string;
+};
+
+export const fixAll = new lc.RequestType<FixAllParams, FixAllResult, void>(
+  "sourcepawn-studio/fixAll"
+);
+
+export type FixAllParams = {
+  code?: string;
+  textDocument?: lc.TextDocumentIdentifier;
+};
+
+export type FixAllResult = {
+  edit: lc.WorkspaceEdit;
+  fixes: number;
+  summary: string;
+};
+
+export const convertToNewSyntax = new lc.RequestType<
+  ConvertToNewSyntaxParams,
+  ConvertToNewSyntaxResult,
+  void
+>("sourcepawn-studio/convertToNewSyntax");
+
+export type ConvertToNewSyntaxParams = {
+  textDocument?: lc.TextDocumentIdentifier;
+};
+
+export type ConvertToNewSyntaxResult = {
+  edit: lc.WorkspaceEdit;
+  conversions: number;
+  summary: string;
+};
+
+export const ssr = new lc.RequestType<SsrParams, SsrResult, void>("sourcepawn-studio/ssr");
+
+export type SsrParams = {
+  query: string;
+  parseOnly: boolean;
+};
+
+export type SsrResult = {
+  matches: lc.Location[];
+  edit: lc.WorkspaceEdit;
+};
+
+export const writeDiagnosticsBaseline = new lc.RequestType0<WriteDiagnosticsBaselineResult, void>(
+  "sourcepawn-studio/writeDiagnosticsBaseline"
+);
+
+export type WriteDiagnosticsBaselineResult = {
+  uri: string;
+  diagnostics: number;
+};
+
 export const serverStatus = new lc.NotificationType<ServerStatusParams>(
   "sourcepawn-studio/serverStatus"
 );
@@ -67,6 +223,7 @@ export type ServerStatusParams = {
   health: "ok" | "warning" | "error";
   quiescent: boolean;
   message?: string;
+  experimentalFlags?: string[];
 };
 
 export const spcompStatus = new lc.NotificationType<ServerStatusParams>(
@@ -76,6 +233,38 @@ export type SpcompStatusParams = {
   quiescent: boolean;
 };
 
+export const compile = new lc.RequestType<CompileParams, CompileResult, void>(
+  "sourcepawn-studio/compile"
+);
+
+export type CompileParams = {
+  textDocument: lc.TextDocumentIdentifier;
+  workDoneToken?: lc.ProgressToken;
+};
+
+export type CompileResult = {
+  success: boolean;
+  uri: string;
+  output: string;
+  errors: number;
+  warnings: number;
+};
+
+export const runFlycheck = new lc.NotificationType<RunFlycheckParams>(
+  "sourcepawn-studio/runFlycheck"
+);
+export type RunFlycheckParams = {
+  textDocument: lc.TextDocumentIdentifier | null;
+};
+
+export const inactiveRegions = new lc.NotificationType<InactiveRegionsParams>(
+  "sourcepawn-studio/inactiveRegions"
+);
+export type InactiveRegionsParams = {
+  textDocument: lc.TextDocumentIdentifier;
+  regions: lc.Range[];
+};
+
 export const hover = new lc.RequestType<
   lc.HoverParams,
   (lc.Hover & { actions: CommandLinkGroup[] }) | null,
@@ -92,3 +281,24 @@ export type CommandLinkGroup = {
   title?: string;
   commands: CommandLink[];
 };
+
+export const codeAction = new lc.RequestType<
+  lc.CodeActionParams,
+  CodeAction[] | null,
+  void
+>(lc.CodeActionRequest.method);
+
+export type CodeAction = {
+  title: string;
+  kind?: string;
+  diagnostics?: lc.Diagnostic[];
+  edit?: SnippetWorkspaceEdit;
+  command?: lc.Command;
+  isPreferred?: boolean;
+};
+export type SnippetWorkspaceEdit = {
+  changes: { [uri: string]: SnippetTextEdit[] };
+};
+export type SnippetTextEdit = lc.TextEdit & {
+  insertTextFormat?: lc.InsertTextFormat;
+};
@@ -3,7 +3,6 @@ import { URI } from "vscode-uri";
 import * as lc from "vscode-languageclient/node";
 
 import { createServerCommands, registerSMCommands } from "./Commands/registerCommands";
-import { SMDocumentFormattingEditProvider } from "./Formatters/spFormat";
 import { KVDocumentFormattingEditProvider } from "./Formatters/kvFormat";
 
 import { Ctx } from "./ctx";
@@ -69,7 +68,7 @@ export async function activate(context: vscode.ExtensionContext) {
       documentSelector,
       workspaceFolder: folder,
       synchronize: {
-        fileEvents: vscode.workspace.createFileSystemWatcher(`${parentDirectory}/**/*.{inc,sp}`),
+        fileEvents: vscode.workspace.createFileSystemWatcher(`${parentDirectory}/**/{*.inc,*.sp,sourcepawn.toml,sourceknight.yaml}`),
       },
     };
     let ctx = new Ctx(parentDirectoryUri.toString(), context, createServerCommands(), clientOptions);
@@ -94,16 +93,6 @@ export async function activate(context: vscode.ExtensionContext) {
   registerSMCommands(context);
   buildDoctorStatusBar();
 
-  context.subscriptions.push(
-    vscode.languages.registerDocumentFormattingEditProvider(
-      {
-        language: "sourcepawn",
-        scheme: "file",
-      },
-      new SMDocumentFormattingEditProvider()
-    )
-  );
-
   context.subscriptions.push(
     vscode.languages.registerDocumentFormattingEditProvider(
       {